@@ -0,0 +1,544 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*Benchmarks for readers and writers in both endiannesses.
+
+  Run with `cargo bench --features bench`.
+
+  Alongside synthetic workloads, the corpus benchmarks
+  decode streams laid out like real formats:
+  a FLAC STREAMINFO header, a fixed-Huffman DEFLATE block
+  and a run of H.264 sequence parameter sets.*/
+
+#[macro_use]
+extern crate criterion;
+extern crate bitstream_io;
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::hint::black_box;
+use std::io;
+
+use criterion::{BenchmarkId, Criterion, Throughput};
+use bitstream_io::{BigEndian, LittleEndian, BitRead, BitReader, BitWriter,
+                   Endianness};
+use bitstream_io::huffman::{compile_read_tree, compile_write_tree,
+                            compile_dense_write_tree,
+                            ReadHuffmanTree, WriteHuffmanTree};
+use bitstream_io::word::WordReader;
+use bitstream_io::unpack::unpack_u16;
+use bitstream_io::pack::pack_u32;
+use common::noise;
+
+const DATA_LEN: usize = 64 * 1024;
+
+fn bench_reads<E: Endianness>(c: &mut Criterion, name: &str) {
+    let data = noise(DATA_LEN, 1);
+    let mut group = c.benchmark_group("read");
+    group.throughput(Throughput::Bytes(DATA_LEN as u64));
+
+    group.bench_with_input(BenchmarkId::new("bit", name), &data, |b, data| {
+        b.iter(|| {
+            let mut r = BitReader::<E, _>::from_read(&data[..]);
+            for _ in 0..(data.len() * 8) {
+                black_box(r.read_bit().unwrap());
+            }
+        })
+    });
+
+    for &bits in [5u32, 13, 32].iter() {
+        let id = BenchmarkId::new(format!("u32x{}", bits), name);
+        group.bench_with_input(id, &data, |b, data| {
+            b.iter(|| {
+                let mut r = BitReader::<E, _>::from_read(&data[..]);
+                for _ in 0..(data.len() * 8 / bits as usize) {
+                    black_box(r.read::<u32>(bits).unwrap());
+                }
+            })
+        });
+
+        let id = BenchmarkId::new(format!("word_u32x{}", bits), name);
+        group.bench_with_input(id, &data, |b, data| {
+            b.iter(|| {
+                let mut r = WordReader::<E, _>::new(&data[..]);
+                for _ in 0..(data.len() * 8 / bits as usize) {
+                    black_box(r.read::<u32>(bits).unwrap());
+                }
+            })
+        });
+    }
+
+    group.bench_with_input(BenchmarkId::new("i16x7", name), &data,
+                           |b, data| {
+        b.iter(|| {
+            let mut r = BitReader::<E, _>::from_read(&data[..]);
+            for _ in 0..(data.len() * 8 / 7) {
+                black_box(r.read_signed::<i16>(7).unwrap());
+            }
+        })
+    });
+
+    group.bench_with_input(BenchmarkId::new("u64x64", name), &data,
+                           |b, data| {
+        b.iter(|| {
+            let mut r = BitReader::<E, _>::from_read(&data[..]);
+            for _ in 0..(data.len() / 8) {
+                black_box(r.read::<u64>(64).unwrap());
+            }
+        })
+    });
+
+    group.bench_with_input(BenchmarkId::new("bytes", name), &data,
+                           |b, data| {
+        let mut buf = vec![0; data.len() - 1];
+        b.iter(|| {
+            let mut r = BitReader::<E, _>::from_read(&data[..]);
+            r.read::<u8>(3).unwrap();
+            r.read_bytes(&mut buf).unwrap();
+            black_box(&buf);
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_unary<E: Endianness>(c: &mut Criterion, name: &str) {
+    let values: Vec<u32> = noise(DATA_LEN / 4, 1).iter()
+        .map(|&b| b.trailing_zeros().min(7) * 3)
+        .collect();
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<E>::new(&mut data);
+        for &value in values.iter() {
+            w.write_unary0(value).unwrap();
+        }
+        w.byte_align().unwrap();
+    }
+
+    let mut group = c.benchmark_group("unary");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_with_input(BenchmarkId::new("read", name), &data, |b, data| {
+        b.iter(|| {
+            let mut r = BitReader::<E, _>::from_read(&data[..]);
+            for _ in 0..values.len() {
+                black_box(r.read_unary0().unwrap());
+            }
+        })
+    });
+    group.bench_with_input(BenchmarkId::new("word_read", name), &data,
+                           |b, data| {
+        b.iter(|| {
+            let mut r = WordReader::<E, _>::new(&data[..]);
+            for _ in 0..values.len() {
+                black_box(r.read_unary0().unwrap());
+            }
+        })
+    });
+    group.bench_with_input(BenchmarkId::new("write", name), &values,
+                           |b, values| {
+        let mut out = Vec::with_capacity(data.len());
+        b.iter(|| {
+            out.clear();
+            let mut w = BitWriter::<E>::new(&mut out);
+            for &value in values.iter() {
+                w.write_unary0(value).unwrap();
+            }
+            w.byte_align().unwrap();
+        })
+    });
+    group.finish();
+
+    // long runs, as in silent stretches of audio
+    let long_values: Vec<u32> = noise(256, 1).iter()
+        .map(|&b| u32::from(b) * 64)
+        .collect();
+    let mut long_data = Vec::new();
+    {
+        let mut w = BitWriter::<E>::new(&mut long_data);
+        for &value in long_values.iter() {
+            w.write_unary1(value).unwrap();
+        }
+        w.byte_align().unwrap();
+    }
+
+    let mut group = c.benchmark_group("long_unary");
+    group.throughput(Throughput::Bytes(long_data.len() as u64));
+    group.bench_with_input(BenchmarkId::new("read", name), &long_data,
+                           |b, data| {
+        b.iter(|| {
+            let mut r = BitReader::<E, _>::from_read(&data[..]);
+            for _ in 0..long_values.len() {
+                black_box(r.read_unary1().unwrap());
+            }
+        })
+    });
+    group.bench_with_input(BenchmarkId::new("buffered_read", name),
+                           &long_data, |b, data| {
+        b.iter(|| {
+            let mut r = BitReader::<E, _>::from_read(&data[..]);
+            for _ in 0..long_values.len() {
+                black_box(r.read_unary1_buffered().unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+fn bench_rice<E: Endianness>(c: &mut Criterion, name: &str) {
+    // residuals with a k of 4, as from a FLAC subframe
+    let values: Vec<u32> = noise(DATA_LEN / 2, 1).iter()
+        .map(|&b| u32::from(b) / 4)
+        .collect();
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<E>::new(&mut data);
+        for &value in values.iter() {
+            w.write_unary1(value >> 4).unwrap();
+            w.write(4, value & 0b1111).unwrap();
+        }
+        w.byte_align().unwrap();
+    }
+
+    let mut group = c.benchmark_group("rice");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    let mut residuals = vec![0i32; values.len()];
+    group.bench_with_input(BenchmarkId::new("word_read", name), &data,
+                           |b, data| {
+        b.iter(|| {
+            let mut r = WordReader::<E, _>::new(&data[..]);
+            for residual in residuals.iter_mut() {
+                let folded = (r.read_unary1().unwrap() << 4) |
+                    r.read::<u32>(4).unwrap();
+                *residual = ((folded >> 1) as i32) ^ -((folded & 1) as i32);
+            }
+            black_box(&residuals);
+        })
+    });
+    group.bench_with_input(BenchmarkId::new("word_block", name), &data,
+                           |b, data| {
+        b.iter(|| {
+            let mut r = WordReader::<E, _>::new(&data[..]);
+            r.read_rice_block(4, &mut residuals).unwrap();
+            black_box(&residuals);
+        })
+    });
+    group.finish();
+}
+
+fn bench_unpack<E: Endianness>(c: &mut Criterion, name: &str) {
+    let data = noise(DATA_LEN, 1);
+    let mut group = c.benchmark_group("unpack");
+    group.throughput(Throughput::Bytes(DATA_LEN as u64));
+
+    for &bits in [10u32, 12].iter() {
+        let mut samples = vec![0u16; DATA_LEN * 8 / bits as usize];
+        let id = BenchmarkId::new(format!("read_u16x{}", bits), name);
+        group.bench_with_input(id, &data, |b, data| {
+            b.iter(|| {
+                let mut r = BitReader::<E, _>::from_read(&data[..]);
+                for sample in samples.iter_mut() {
+                    *sample = r.read(bits).unwrap();
+                }
+                black_box(&samples);
+            })
+        });
+
+        let id = BenchmarkId::new(format!("u16x{}", bits), name);
+        group.bench_with_input(id, &data, |b, data| {
+            b.iter(|| {
+                unpack_u16::<E>(data, bits, &mut samples).unwrap();
+                black_box(&samples);
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_pack<E: Endianness>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group("pack");
+    group.throughput(Throughput::Bytes(DATA_LEN as u64));
+
+    for &bits in [20u32, 24].iter() {
+        let samples = noise(DATA_LEN * 8 / bits as usize, 1).iter()
+            .map(|&b| u32::from(b) << (bits - 8))
+            .collect::<Vec<u32>>();
+        let id = BenchmarkId::new(format!("write_u32x{}", bits), name);
+        group.bench_with_input(id, &samples, |b, samples| {
+            let mut out = Vec::with_capacity(DATA_LEN);
+            b.iter(|| {
+                out.clear();
+                let mut w = BitWriter::<E>::new(&mut out);
+                for &sample in samples.iter() {
+                    w.write(bits, sample).unwrap();
+                }
+            })
+        });
+
+        let id = BenchmarkId::new(format!("u32x{}", bits), name);
+        group.bench_with_input(id, &samples, |b, samples| {
+            let mut out = vec![0; DATA_LEN];
+            b.iter(|| {
+                black_box(pack_u32::<E>(samples, bits, &mut out).unwrap());
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_writes<E: Endianness>(c: &mut Criterion, name: &str) {
+    let data = noise(DATA_LEN, 1);
+    let mut group = c.benchmark_group("write");
+    group.throughput(Throughput::Bytes(DATA_LEN as u64));
+
+    group.bench_with_input(BenchmarkId::new("bit", name), &data, |b, data| {
+        let mut out = Vec::with_capacity(data.len());
+        b.iter(|| {
+            out.clear();
+            let mut w = BitWriter::<E>::new(&mut out);
+            for &byte in data.iter() {
+                for i in 0..8 {
+                    w.write_bit(byte & (1 << i) != 0).unwrap();
+                }
+            }
+        })
+    });
+
+    for &bits in [5u32, 13].iter() {
+        let id = BenchmarkId::new(format!("u32x{}", bits), name);
+        group.bench_with_input(id, &data, |b, data| {
+            let mut out = Vec::with_capacity(data.len());
+            b.iter(|| {
+                out.clear();
+                let mut w = BitWriter::<E>::new(&mut out);
+                for chunk in data.chunks(4) {
+                    let value = chunk.iter()
+                        .fold(0u32, |acc, &b| (acc << 8) | u32::from(b));
+                    w.write(bits, value & ((1 << bits) - 1)).unwrap();
+                }
+                w.byte_align().unwrap();
+            })
+        });
+    }
+
+    group.bench_with_input(BenchmarkId::new("bytes", name), &data,
+                           |b, data| {
+        let mut out = Vec::with_capacity(data.len() + 1);
+        b.iter(|| {
+            out.clear();
+            let mut w = BitWriter::<E>::new(&mut out);
+            w.write(3, 0b101u8).unwrap();
+            w.write_bytes(data).unwrap();
+            w.byte_align().unwrap();
+        })
+    });
+
+    group.finish();
+}
+
+/*FLAC's fLaC marker, followed by a final STREAMINFO metadata block*/
+fn flac_header() -> Vec<u8> {
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<BigEndian>::new(&mut data);
+        w.write_bytes(b"fLaC").unwrap();
+        w.write_bit(true).unwrap();
+        w.write(7, 0u8).unwrap();
+        w.write(24, 34u32).unwrap();
+        w.write(16, 4096u16).unwrap();
+        w.write(16, 4096u16).unwrap();
+        w.write(24, 1542u32).unwrap();
+        w.write(24, 8546u32).unwrap();
+        w.write(20, 44100u32).unwrap();
+        w.write(3, 1u8).unwrap();
+        w.write(5, 15u8).unwrap();
+        w.write(36, 304844u64).unwrap();
+        w.write_bytes(&noise(16, 1)).unwrap();
+    }
+    data
+}
+
+// fields are parsed but never examined
+#[allow(dead_code)]
+struct StreamInfo {
+    block_sizes: (u16, u16),
+    frame_sizes: (u32, u32),
+    sample_rate: u32,
+    channels: u8,
+    bits_per_sample: u8,
+    total_samples: u64,
+    md5: [u8; 16],
+}
+
+fn parse_flac_header(data: &[u8]) -> io::Result<StreamInfo> {
+    let mut r = BitReader::<BigEndian, _>::from_read(data);
+    let mut marker = [0; 4];
+    r.read_bytes(&mut marker)?;
+    r.skip(32)?;
+    let mut info = StreamInfo{block_sizes: (r.read(16)?, r.read(16)?),
+                              frame_sizes: (r.read(24)?, r.read(24)?),
+                              sample_rate: r.read(20)?,
+                              channels: r.read::<u8>(3)? + 1,
+                              bits_per_sample: r.read::<u8>(5)? + 1,
+                              total_samples: r.read(36)?,
+                              md5: [0; 16]};
+    r.read_bytes(&mut info.md5)?;
+    Ok(info)
+}
+
+/*DEFLATE's fixed literal/length codes, which are stored
+  most significant bit first within a little-endian stream*/
+fn deflate_codes() -> Vec<(u16, Vec<u8>)> {
+    fn code(value: u32, bits: u32) -> Vec<u8> {
+        (0..bits).rev().map(|i| ((value >> i) & 1) as u8).collect()
+    }
+    (0..288u32).map(|symbol| {
+        let bits = match symbol {
+            0..=143 => code(0b0011_0000 + symbol, 8),
+            144..=255 => code(0b1_1001_0000 + symbol - 144, 9),
+            256..=279 => code(symbol - 256, 7),
+            _ => code(0b1100_0000 + symbol - 280, 8),
+        };
+        (symbol as u16, bits)
+    }).collect()
+}
+
+/*a final fixed-Huffman block of literals, mostly text*/
+fn deflate_block(tree: &WriteHuffmanTree<LittleEndian, u16>) -> Vec<u8> {
+    let text = b"It was the best of times, it was the worst of times, \
+                 it was the age of wisdom, it was the age of foolishness";
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<LittleEndian>::new(&mut data);
+        w.write_bit(true).unwrap();
+        w.write(2, 1u8).unwrap();
+        for (i, &byte) in noise(DATA_LEN / 8, 1).iter().enumerate() {
+            let literal = if byte < 0xE0 {text[i % text.len()]} else {byte};
+            w.write_huffman(tree, u16::from(literal)).unwrap();
+        }
+        w.write_huffman(tree, 256).unwrap();
+        w.byte_align().unwrap();
+    }
+    data
+}
+
+fn inflate_block(data: &[u8], tree: &[ReadHuffmanTree<LittleEndian, u16>])
+    -> io::Result<usize> {
+    let mut r = BitReader::<LittleEndian, _>::from_read(data);
+    r.skip(3)?;
+    let mut literals = 0;
+    while r.read_huffman(tree)? != 256 {
+        literals += 1;
+    }
+    Ok(literals)
+}
+
+/*a run of byte-aligned H.264 sequence parameter set payloads,
+  whose fields are mostly Exp-Golomb codes*/
+fn h264_sps() -> Vec<u8> {
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<BigEndian>::new(&mut data);
+        for i in 0..1024u64 {
+            w.write(8, 66u8).unwrap();
+            w.write(8, 0xC0u8).unwrap();
+            w.write(8, 30u8).unwrap();
+            w.write_exp_golomb(0, i % 32).unwrap();
+            w.write_exp_golomb(0, 4).unwrap();
+            w.write_exp_golomb(0, 0).unwrap();
+            w.write_exp_golomb(0, 4).unwrap();
+            w.write_exp_golomb(0, 1 + i % 4).unwrap();
+            w.write_bit(false).unwrap();
+            w.write_exp_golomb(0, 119).unwrap();
+            w.write_exp_golomb(0, 67).unwrap();
+            w.write_bit(true).unwrap();
+            w.write_bit(true).unwrap();
+            w.write_bit(false).unwrap();
+            w.write_bit(false).unwrap();
+            w.write_bit(true).unwrap();
+            w.byte_align().unwrap();
+        }
+    }
+    data
+}
+
+fn parse_h264_sps(data: &[u8]) -> io::Result<u64> {
+    let mut r = BitReader::<BigEndian, _>::from_read(data);
+    let mut checksum = 0;
+    for _ in 0..1024 {
+        let profile: u64 = r.read(8)?;
+        r.skip(16)?;
+        checksum += profile;
+        for _ in 0..5 {
+            checksum += r.read_exp_golomb(0)?;
+        }
+        r.skip(1)?;
+        checksum += r.read_exp_golomb(0)? * r.read_exp_golomb(0)?;
+        r.skip(5)?;
+        r.byte_align();
+    }
+    Ok(checksum)
+}
+
+fn bench_corpora(c: &mut Criterion) {
+    let mut group = c.benchmark_group("corpus");
+
+    let flac = flac_header();
+    group.throughput(Throughput::Bytes(flac.len() as u64));
+    group.bench_function("flac_streaminfo", |b| {
+        b.iter(|| black_box(parse_flac_header(&flac).unwrap()))
+    });
+
+    let codes = deflate_codes();
+    let read_tree = compile_read_tree(codes.clone()).unwrap();
+    let write_tree = compile_write_tree(codes).unwrap();
+    let deflate = deflate_block(&write_tree);
+    group.throughput(Throughput::Bytes(deflate.len() as u64));
+    group.bench_function("deflate_fixed_inflate", |b| {
+        b.iter(|| black_box(inflate_block(&deflate, &read_tree).unwrap()))
+    });
+    group.bench_function("deflate_fixed_deflate", |b| {
+        b.iter(|| black_box(deflate_block(&write_tree)))
+    });
+    let dense_tree = compile_dense_write_tree(deflate_codes()).unwrap();
+    group.bench_function("deflate_fixed_deflate_dense", |b| {
+        b.iter(|| black_box(deflate_block(&dense_tree)))
+    });
+
+    let sps = h264_sps();
+    group.throughput(Throughput::Bytes(sps.len() as u64));
+    group.bench_function("h264_sps", |b| {
+        b.iter(|| black_box(parse_h264_sps(&sps).unwrap()))
+    });
+
+    group.finish();
+}
+
+fn bench_big_endian(c: &mut Criterion) {
+    bench_reads::<BigEndian>(c, "big");
+    bench_unary::<BigEndian>(c, "big");
+    bench_rice::<BigEndian>(c, "big");
+    bench_unpack::<BigEndian>(c, "big");
+    bench_pack::<BigEndian>(c, "big");
+    bench_writes::<BigEndian>(c, "big");
+}
+
+fn bench_little_endian(c: &mut Criterion) {
+    bench_reads::<LittleEndian>(c, "little");
+    bench_unary::<LittleEndian>(c, "little");
+    bench_rice::<LittleEndian>(c, "little");
+    bench_unpack::<LittleEndian>(c, "little");
+    bench_pack::<LittleEndian>(c, "little");
+    bench_writes::<LittleEndian>(c, "little");
+}
+
+criterion_group!(benches, bench_big_endian, bench_little_endian,
+                 bench_corpora);
+criterion_main!(benches);
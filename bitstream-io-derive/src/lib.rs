@@ -0,0 +1,372 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Derive macros for bitstream-io's `FromBitStream` and `ToBitStream` traits.
+//!
+//! These are re-exported by bitstream-io when its `derive`
+//! feature is enabled and should be used from there.
+//!
+//! Each field of a struct is read or written in order,
+//! according to its attributes:
+//!
+//! | Attribute | Read | Write |
+//! |---|---|---|
+//! | `#[bits(N)]` | `read::<T>(N)` | `write::<T>(N, value)` |
+//! | `#[bits(N, signed)]` | `read_signed::<T>(N)` | `write_signed::<T>(N, value)` |
+//! | `#[bytes]` | `read_bytes` | `write_bytes` |
+//! | none, for `bool` fields | `read_bit` | `write_bit` |
+//! | none, for other fields | `parse::<T>()` | `build(&value)` |
+//!
+//! A `#[bits]` attribute may also include `expect = VALUE`,
+//! in which case reading any other value is an error.
+//!
+//! The generated implementations use `std::io::Error`
+//! as their error type, so any nested types parsed or built
+//! must have errors which convert from it.
+//!
+//! `ConstBits` uses the same attributes to total a struct's
+//! size in bits at compile time,
+//! and `Describe` uses them to list a struct's fields.
+//! `#[bytes]` fields must then be arrays,
+//! and any nested fields must also implement the same trait.
+
+#![warn(missing_docs)]
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::parse::{Parse, ParseStream};
+use syn::{Data, DeriveInput, Expr, Fields, Ident, Index, Member, Type};
+
+/// Derives `FromBitStream` for a struct.
+#[proc_macro_derive(FromBitStream, attributes(bits, bytes))]
+pub fn derive_from_bitstream(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    expand_from(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Derives `ToBitStream` for a struct.
+#[proc_macro_derive(ToBitStream, attributes(bits, bytes))]
+pub fn derive_to_bitstream(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    expand_to(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Derives `ConstBits` for a struct.
+#[proc_macro_derive(ConstBits, attributes(bits, bytes))]
+pub fn derive_const_bits(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    expand_const_bits(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Derives `Describe` for a struct.
+#[proc_macro_derive(Describe, attributes(bits, bytes))]
+pub fn derive_describe(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    expand_describe(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+// How a single field is read or written
+enum Layout {
+    Bits(Box<BitsAttr>),
+    Bytes,
+    Bit,
+    Nested,
+}
+
+// The contents of a #[bits(...)] attribute
+struct BitsAttr {
+    bits: Expr,
+    signed: bool,
+    expect: Option<Expr>,
+}
+
+impl Parse for BitsAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let bits = input.parse()?;
+        let mut signed = false;
+        let mut expect = None;
+        while !input.is_empty() {
+            input.parse::<syn::Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let name: Ident = input.parse()?;
+            if name == "signed" {
+                signed = true;
+            } else if name == "expect" {
+                input.parse::<syn::Token![=]>()?;
+                expect = Some(input.parse()?);
+            } else {
+                return Err(syn::Error::new(name.span(),
+                                           "expected `signed` or `expect`"));
+            }
+        }
+        Ok(BitsAttr{bits, signed, expect})
+    }
+}
+
+struct Field<'a> {
+    member: Member,
+    binding: Ident,
+    ty: &'a Type,
+    layout: Layout,
+}
+
+fn fields(input: &DeriveInput) -> syn::Result<(Vec<Field<'_>>, bool)> {
+    let data = match input.data {
+        Data::Struct(ref data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident, "bit stream derives only support structs"));
+        }
+    };
+    let named = matches!(data.fields, Fields::Named(_));
+    let mut result = Vec::new();
+    for (i, field) in data.fields.iter().enumerate() {
+        let (member, binding) = match field.ident {
+            Some(ref ident) => (Member::Named(ident.clone()), ident.clone()),
+            None => (Member::Unnamed(Index::from(i)),
+                     format_ident!("field_{}", i)),
+        };
+        let mut layout = None;
+        for attr in field.attrs.iter() {
+            let parsed = if attr.path().is_ident("bits") {
+                Layout::Bits(Box::new(attr.parse_args()?))
+            } else if attr.path().is_ident("bytes") {
+                attr.meta.require_path_only()?;
+                Layout::Bytes
+            } else {
+                continue;
+            };
+            if layout.is_some() {
+                return Err(syn::Error::new_spanned(
+                    attr, "only one layout attribute is allowed per field"));
+            }
+            layout = Some(parsed);
+        }
+        let layout = layout.unwrap_or_else(|| if is_bool(&field.ty) {
+            Layout::Bit
+        } else {
+            Layout::Nested
+        });
+        result.push(Field{member, binding, ty: &field.ty, layout});
+    }
+    Ok((result, named))
+}
+
+fn is_bool(ty: &Type) -> bool {
+    match *ty {
+        Type::Path(ref path) => path.qself.is_none() &&
+            path.path.is_ident("bool"),
+        _ => false,
+    }
+}
+
+fn expand_from(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let (fields, named) = fields(input)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let reads = fields.iter().map(|field| {
+        let Field{ref binding, ty, ref layout, ..} = *field;
+        match *layout {
+            Layout::Bits(ref attr) => {
+                let BitsAttr{ref bits, signed, ref expect} = **attr;
+                let method = if signed {
+                    quote!(read_signed)
+                } else {
+                    quote!(read)
+                };
+                let check = expect.as_ref().map(|expect| {
+                    let message = format!("unexpected value for {}", binding);
+                    quote! {
+                        if #binding != #expect {
+                            return Err(::std::io::Error::new(
+                                ::std::io::ErrorKind::InvalidData,
+                                #message));
+                        }
+                    }
+                });
+                quote! {
+                    let #binding = <R as ::bitstream_io::BitRead>::#method::<#ty>(
+                        r, #bits)?;
+                    #check
+                }
+            }
+            Layout::Bytes => quote! {
+                let mut #binding: #ty = ::std::default::Default::default();
+                ::bitstream_io::BitRead::read_bytes(r, &mut #binding)?;
+            },
+            Layout::Bit => quote! {
+                let #binding = ::bitstream_io::BitRead::read_bit(r)?;
+            },
+            Layout::Nested => quote! {
+                let #binding = ::bitstream_io::BitRead::parse::<#ty>(r)?;
+            },
+        }
+    });
+    let bindings = fields.iter().map(|f| &f.binding);
+    let construct = if named {
+        quote!(#name{#(#bindings),*})
+    } else {
+        quote!(#name(#(#bindings),*))
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::bitstream_io::FromBitStream
+            for #name #ty_generics #where_clause {
+            type Error = ::std::io::Error;
+
+            fn from_reader<R: ::bitstream_io::BitRead + ?Sized>(r: &mut R) ->
+                ::std::result::Result<Self, ::std::io::Error> {
+                #(#reads)*
+                Ok(#construct)
+            }
+        }
+    })
+}
+
+fn expand_to(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let (fields, _) = fields(input)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let writes = fields.iter().map(|field| {
+        let Field{ref member, ty, ref layout, ..} = *field;
+        match *layout {
+            Layout::Bits(ref attr) => {
+                let BitsAttr{ref bits, signed, ..} = **attr;
+                let method = if signed {
+                    quote!(write_signed)
+                } else {
+                    quote!(write)
+                };
+                quote! {
+                    <W as ::bitstream_io::BitWrite>::#method::<#ty>(
+                        w, #bits, self.#member)?;
+                }
+            }
+            Layout::Bytes => quote! {
+                ::bitstream_io::BitWrite::write_bytes(w, &self.#member)?;
+            },
+            Layout::Bit => quote! {
+                ::bitstream_io::BitWrite::write_bit(w, self.#member)?;
+            },
+            Layout::Nested => quote! {
+                ::bitstream_io::BitWrite::build::<#ty>(w, &self.#member)?;
+            },
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::bitstream_io::ToBitStream
+            for #name #ty_generics #where_clause {
+            type Error = ::std::io::Error;
+
+            fn to_writer<W: ::bitstream_io::BitWrite + ?Sized>(&self,
+                                                              w: &mut W) ->
+                ::std::result::Result<(), ::std::io::Error> {
+                #(#writes)*
+                Ok(())
+            }
+        }
+    })
+}
+
+fn expand_const_bits(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let (fields, _) = fields(input)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let sizes = fields.iter().map(|field| {
+        let Field{ty, ref layout, ..} = *field;
+        match *layout {
+            Layout::Bits(ref attr) => {
+                let bits = &attr.bits;
+                quote!((#bits))
+            }
+            Layout::Bytes => quote! {
+                (::std::mem::size_of::<#ty>() as u32 * 8)
+            },
+            Layout::Bit => quote!(1),
+            Layout::Nested => quote! {
+                <#ty as ::bitstream_io::ConstBits>::BITS
+            },
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::bitstream_io::ConstBits
+            for #name #ty_generics #where_clause {
+            const BITS: u32 = 0 #(+ #sizes)*;
+        }
+    })
+}
+
+fn expand_describe(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let (fields, _) = fields(input)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let descriptors = fields.iter().map(|field| {
+        let Field{ref member, ty, ref layout, ..} = *field;
+        let field_name = match *member {
+            Member::Named(ref ident) => ident.to_string(),
+            Member::Unnamed(ref index) => index.index.to_string(),
+        };
+        let kind = match *layout {
+            Layout::Bits(ref attr) => {
+                let bits = &attr.bits;
+                if attr.signed {
+                    quote!(::bitstream_io::inspect::FieldKind::Signed(#bits))
+                } else {
+                    quote!(::bitstream_io::inspect::FieldKind::Unsigned(#bits))
+                }
+            }
+            Layout::Bytes => quote! {
+                ::bitstream_io::inspect::FieldKind::Bytes(
+                    ::std::mem::size_of::<#ty>() as u32)
+            },
+            Layout::Bit => quote!(::bitstream_io::inspect::FieldKind::Flag),
+            Layout::Nested => quote! {
+                ::bitstream_io::inspect::FieldKind::Nested(
+                    <#ty as ::bitstream_io::inspect::Describe>::FIELDS)
+            },
+        };
+        quote! {
+            ::bitstream_io::inspect::FieldDescriptor{name: #field_name,
+                                                     kind: #kind}
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::bitstream_io::inspect::Describe
+            for #name #ty_generics #where_clause {
+            const FIELDS: &'static [::bitstream_io::inspect::FieldDescriptor] =
+                &[#(#descriptors),*];
+        }
+    })
+}
@@ -0,0 +1,69 @@
+/* Copyright 2017 Brian Langenberger
+ *
+ * Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+ * http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+ * <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+ * option. This file may not be copied, modified, or distributed
+ * except according to those terms.
+ *
+ * C interface to bitstream-io readers, built with the "ffi" feature.
+ */
+
+#ifndef BITSTREAM_IO_H
+#define BITSTREAM_IO_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* status codes returned by reading functions */
+#define BITSTREAM_OK 0
+#define BITSTREAM_EOF -1
+#define BITSTREAM_INVALID_INPUT -2
+#define BITSTREAM_INVALID_DATA -3
+#define BITSTREAM_NULL_POINTER -4
+#define BITSTREAM_ERROR -5
+
+typedef struct BitstreamReader BitstreamReader;
+
+/* creates a reader over a copy of the buffer,
+   little-endian if little_endian is nonzero, big-endian otherwise */
+BitstreamReader *bitstream_reader_new(const uint8_t *data,
+                                      size_t len,
+                                      int little_endian);
+
+/* reads an unsigned value of up to 64 bits */
+int bitstream_reader_read(BitstreamReader *reader,
+                          uint32_t bits,
+                          uint64_t *value);
+
+/* reads a twos-complement signed value of up to 64 bits */
+int bitstream_reader_read_signed(BitstreamReader *reader,
+                                 uint32_t bits,
+                                 int64_t *value);
+
+/* reads a unary value, counting bits until stop_bit (0 or 1) is read */
+int bitstream_reader_read_unary(BitstreamReader *reader,
+                                int stop_bit,
+                                uint32_t *value);
+
+/* skips the given number of bits */
+int bitstream_reader_skip(BitstreamReader *reader, uint32_t bits);
+
+/* discards any bits up to the next whole byte */
+void bitstream_reader_byte_align(BitstreamReader *reader);
+
+/* returns the number of bits read so far */
+uint64_t bitstream_reader_position(const BitstreamReader *reader);
+
+/* destroys the reader */
+void bitstream_reader_free(BitstreamReader *reader);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BITSTREAM_IO_H */
@@ -0,0 +1,242 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Reading and writing arbitrary-precision integers
+// via the num-bigint crate.
+//
+// Values are transferred as 32-bit digits, so that the stream
+// order of their bits matches that of the fixed-width methods:
+// most-significant digit first for big-endian streams
+// and least-significant digit first for little-endian streams.
+
+use io;
+
+use num_bigint::{BigInt, BigUint, Sign};
+
+use super::{BitReader, BitWriter, BigEndian, LittleEndian};
+
+// Returns the number of 32-bit digits needed for the given bits
+// and the number of bits in the most-significant digit.
+#[inline]
+fn digits(bits: u32) -> (usize, u32) {
+    let count = bits.div_ceil(32);
+    (count as usize, bits - 32 * count.saturating_sub(1))
+}
+
+fn check_unsigned(bits: u32, value: &BigUint) -> Result<(), io::Error> {
+    if value.bits() > u64::from(bits) {
+        Err(io::Error::new(io::ErrorKind::InvalidInput,
+                           "excessive value for bits written"))
+    } else {
+        Ok(())
+    }
+}
+
+// Converts a signed value to its twos-complement form in the given bits.
+fn to_twos_complement(bits: u32, value: &BigInt) ->
+    Result<BigUint, io::Error> {
+
+    let magnitude = value.magnitude();
+    let fits = match value.sign() {
+        Sign::NoSign => true,
+        Sign::Plus => magnitude.bits() < u64::from(bits),
+        Sign::Minus => {
+            (bits > 0) && (*magnitude <= BigUint::from(1u32) << (bits - 1))
+        }
+    };
+    if !fits {
+        Err(io::Error::new(io::ErrorKind::InvalidInput,
+                           "excessive value for bits written"))
+    } else if value.sign() == Sign::Minus {
+        Ok((BigUint::from(1u32) << bits) - magnitude)
+    } else {
+        Ok(magnitude.clone())
+    }
+}
+
+// Converts a twos-complement value in the given bits to a signed value.
+fn from_twos_complement(bits: u32, value: BigUint) -> BigInt {
+    if (bits > 0) && value.bit(u64::from(bits - 1)) {
+        BigInt::from(value) - BigInt::from(BigUint::from(1u32) << bits)
+    } else {
+        BigInt::from(value)
+    }
+}
+
+impl<R: io::Read> BitReader<BigEndian, R> {
+    /// Reads an unsigned value of any size from the stream.
+    ///
+    /// Requires the `num-bigint` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// extern crate num_bigint;
+    /// # extern crate bitstream_io;
+    /// # fn main() {
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// use num_bigint::BigUint;
+    /// let data = [0xFF; 17];
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// let value = reader.read_biguint(130).unwrap();
+    /// assert_eq!(value, (BigUint::from(1u32) << 130) - 1u32);
+    /// # }
+    /// ```
+    pub fn read_biguint(&mut self, bits: u32) -> Result<BigUint, io::Error> {
+        let (count, top) = digits(bits);
+        let mut value = vec![0; count];
+        for (i, digit) in value.iter_mut().rev().enumerate() {
+            *digit = self.read::<u32>(if i == 0 {top} else {32})?;
+        }
+        Ok(BigUint::new(value))
+    }
+
+    /// Reads a twos-complement signed value of any size from the stream.
+    ///
+    /// Requires the `num-bigint` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn read_bigint(&mut self, bits: u32) -> Result<BigInt, io::Error> {
+        self.read_biguint(bits).map(|u| from_twos_complement(bits, u))
+    }
+}
+
+impl<R: io::Read> BitReader<LittleEndian, R> {
+    /// Reads an unsigned value of any size from the stream.
+    ///
+    /// Requires the `num-bigint` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn read_biguint(&mut self, bits: u32) -> Result<BigUint, io::Error> {
+        let (count, top) = digits(bits);
+        let mut value = vec![0; count];
+        for (i, digit) in value.iter_mut().enumerate() {
+            *digit = self.read::<u32>(if i + 1 == count {top} else {32})?;
+        }
+        Ok(BigUint::new(value))
+    }
+
+    /// Reads a twos-complement signed value of any size from the stream.
+    ///
+    /// Requires the `num-bigint` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn read_bigint(&mut self, bits: u32) -> Result<BigInt, io::Error> {
+        self.read_biguint(bits).map(|u| from_twos_complement(bits, u))
+    }
+}
+
+impl<'a, W: io::Write + ?Sized> BitWriter<'a, BigEndian, W> {
+    /// Writes an unsigned value of any size to the stream.
+    ///
+    /// Requires the `num-bigint` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the value is too large
+    /// to fit the given number of bits.
+    ///
+    /// # Example
+    /// ```
+    /// extern crate num_bigint;
+    /// # extern crate bitstream_io;
+    /// # fn main() {
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// use num_bigint::{BigInt, BigUint};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     writer.write_biguint(68, &(BigUint::from(1u32) << 67)).unwrap();
+    ///     writer.write_bigint(68, &BigInt::from(-1)).unwrap();
+    ///     assert!(writer.write_biguint(4, &BigUint::from(16u32)).is_err());
+    /// }
+    /// assert_eq!(data, [0x80, 0, 0, 0, 0, 0, 0, 0,
+    ///                   0x0F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+    /// # }
+    /// ```
+    pub fn write_biguint(&mut self, bits: u32, value: &BigUint) ->
+        Result<(), io::Error> {
+
+        check_unsigned(bits, value)?;
+        let (count, top) = digits(bits);
+        let mut value = value.to_u32_digits();
+        value.resize(count, 0);
+        for (i, digit) in value.iter().rev().enumerate() {
+            self.write(if i == 0 {top} else {32}, *digit)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a twos-complement signed value of any size to the stream.
+    ///
+    /// Requires the `num-bigint` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the value is too large
+    /// to fit the given number of bits.
+    pub fn write_bigint(&mut self, bits: u32, value: &BigInt) ->
+        Result<(), io::Error> {
+
+        to_twos_complement(bits, value)
+            .and_then(|u| self.write_biguint(bits, &u))
+    }
+}
+
+impl<'a, W: io::Write + ?Sized> BitWriter<'a, LittleEndian, W> {
+    /// Writes an unsigned value of any size to the stream.
+    ///
+    /// Requires the `num-bigint` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the value is too large
+    /// to fit the given number of bits.
+    pub fn write_biguint(&mut self, bits: u32, value: &BigUint) ->
+        Result<(), io::Error> {
+
+        check_unsigned(bits, value)?;
+        let (count, top) = digits(bits);
+        let mut value = value.to_u32_digits();
+        value.resize(count, 0);
+        for (i, digit) in value.iter().enumerate() {
+            self.write(if i + 1 == count {top} else {32}, *digit)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a twos-complement signed value of any size to the stream.
+    ///
+    /// Requires the `num-bigint` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the value is too large
+    /// to fit the given number of bits.
+    pub fn write_bigint(&mut self, bits: u32, value: &BigInt) ->
+        Result<(), io::Error> {
+
+        to_twos_complement(bits, value)
+            .and_then(|u| self.write_biguint(bits, &u))
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// Defines a struct over a fixed-width, register-style bit layout.
+///
+/// The struct wraps a single unsigned integer holding the whole register,
+/// and each named field covers a range of its bits,
+/// numbered from 0 at the least-significant bit.
+/// Each field is given as a getter name, a setter name
+/// and its half-open bit range.
+/// Ranges are checked at compile time.
+///
+/// The struct implements `FromBitStream` and `ToBitStream`,
+/// which read and write the whole register as a single value
+/// in the stream's endianness,
+/// and `ConstBits` with the register's full width.
+/// Setters discard any bits of their value
+/// which do not fit the field.
+///
+/// # Example
+/// ```
+/// #[macro_use]
+/// extern crate bitstream_io;
+/// use std::io::{Read, Cursor};
+/// use bitstream_io::{BigEndian, BitRead, BitReader};
+///
+/// define_bitfields! {
+///     /// A CAN identifier field
+///     #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+///     pub struct CanId(u16) {
+///         /// The message priority
+///         priority, set_priority: 8..11;
+///         /// The message type
+///         message, set_message: 0..8;
+///     }
+/// }
+///
+/// # fn main() {
+/// let data = [0b0000_0101, 0b0010_0001];
+/// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+/// let mut id: CanId = reader.parse().unwrap();
+/// assert_eq!(id.priority(), 0b101);
+/// assert_eq!(id.message(), 0b0010_0001);
+/// id.set_priority(0b010);
+/// assert_eq!(id, CanId(0b0000_0010_0010_0001));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! define_bitfields {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident($t:ty) {
+            $(
+                $(#[$field_attr:meta])*
+                $get:ident, $set:ident : $lo:tt .. $hi:tt
+            );* $(;)*
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name(pub $t);
+
+        $(
+            const _: () = assert!(($lo < $hi) && ($hi <= <$t>::BITS),
+                                  "invalid bit range for field");
+        )*
+
+        impl $name {
+            $(
+                $(#[$field_attr])*
+                #[inline]
+                pub fn $get(&self) -> $t {
+                    (self.0 >> $lo) & (!(0 as $t) >> (<$t>::BITS - ($hi - $lo)))
+                }
+
+                #[doc = concat!("Sets the value of the `", stringify!($get),
+                                "` field.")]
+                #[inline]
+                pub fn $set(&mut self, value: $t) {
+                    let mask = !(0 as $t) >> (<$t>::BITS - ($hi - $lo));
+                    self.0 = (self.0 & !(mask << $lo)) | ((value & mask) << $lo);
+                }
+            )*
+        }
+
+        impl $crate::ConstBits for $name {
+            const BITS: u32 = <$t>::BITS;
+        }
+
+        impl $crate::FromBitStream for $name {
+            type Error = $crate::io::Error;
+
+            fn from_reader<R: $crate::BitRead + ?Sized>(r: &mut R) ->
+                $crate::io::Result<Self> {
+                <R as $crate::BitRead>::read::<$t>(r, <$t>::BITS).map($name)
+            }
+        }
+
+        impl $crate::ToBitStream for $name {
+            type Error = $crate::io::Error;
+
+            fn to_writer<W: $crate::BitWrite + ?Sized>(&self, w: &mut W) ->
+                $crate::io::Result<()> {
+                <W as $crate::BitWrite>::write::<$t>(w, <$t>::BITS, self.0)
+            }
+        }
+    };
+}
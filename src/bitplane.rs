@@ -0,0 +1,176 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Functions for reading or writing samples as bit-planes.
+//!
+//! Rather than storing each sample's bits together,
+//! bit-plane coding stores the most-significant bit of every sample,
+//! followed by the next most-significant bit of every sample,
+//! and so on down to the least-significant bits.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Write, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::bitplane::{read_planes, write_planes};
+//!
+//! let samples: [u8; 4] = [0b101, 0b011, 0b110, 0b001];
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     write_planes(&mut writer, 3, &samples).unwrap();
+//!     writer.byte_align().unwrap();
+//! }
+//! assert_eq!(data, [0b1010_0110, 0b1101_0000]);
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! let mut read_samples = [0u8; 4];
+//! read_planes(&mut reader, 3, &mut read_samples).unwrap();
+//! assert_eq!(read_samples, samples);
+//! ```
+
+#![warn(missing_docs)]
+
+use io;
+
+use super::{BitReader, BitWriter, Endianness, Numeric};
+
+/// Writes all the samples as `bits` bit-planes,
+/// from the most-significant plane to the least-significant.
+///
+/// # Errors
+///
+/// Passes along any I/O error from the underlying stream.
+/// Returns an error if the sample type is too small
+/// to hold the given number of bits,
+/// or if any sample is too large to fit the given number of bits.
+pub fn write_planes<E, N>(writer: &mut BitWriter<E>,
+                          bits: u32,
+                          samples: &[N]) -> Result<(), io::Error>
+    where E: Endianness, N: Numeric {
+
+    if bits > N::bits_size() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "excessive bits for type written"));
+    } else if (bits < N::bits_size()) &&
+        samples.iter().any(|s| *s >= (N::one() << bits)) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "excessive value for bits written"));
+    }
+    for plane in (0..bits).rev() {
+        write_plane(writer, plane, samples)?;
+    }
+    Ok(())
+}
+
+/// Reads `bits` bit-planes into the samples,
+/// from the most-significant plane to the least-significant.
+/// All samples are cleared before reading.
+/// Signed samples are not sign-extended.
+///
+/// # Errors
+///
+/// Passes along any I/O error from the underlying stream.
+/// Returns an error if the sample type is too small
+/// to hold the given number of bits.
+pub fn read_planes<E, N>(reader: &mut BitReader<E>,
+                         bits: u32,
+                         samples: &mut [N]) -> Result<(), io::Error>
+    where E: Endianness, N: Numeric {
+
+    if bits > N::bits_size() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "excessive bits for type read"));
+    }
+    for sample in samples.iter_mut() {
+        *sample = N::default();
+    }
+    for plane in (0..bits).rev() {
+        read_plane(reader, plane, samples)?;
+    }
+    Ok(())
+}
+
+/// Writes a single bit-plane, the bit at position `plane`
+/// of every sample, where 0 is the least-significant bit.
+///
+/// # Errors
+///
+/// Passes along any I/O error from the underlying stream.
+/// Returns an error if the sample type is too small
+/// to hold the given plane.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{BigEndian, BitWriter};
+/// use bitstream_io::bitplane::write_plane;
+/// let samples: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+/// let mut data = Vec::new();
+/// {
+///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+///     write_plane(&mut writer, 0, &samples).unwrap();
+/// }
+/// assert_eq!(data, [0b10101010]);
+/// ```
+pub fn write_plane<E, N>(writer: &mut BitWriter<E>,
+                         plane: u32,
+                         samples: &[N]) -> Result<(), io::Error>
+    where E: Endianness, N: Numeric {
+
+    if plane >= N::bits_size() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "excessive bits for type written"));
+    }
+    let two = N::one() << 1;
+    for sample in samples {
+        writer.write_bit(!((*sample >> plane) % two).is_zero())?;
+    }
+    Ok(())
+}
+
+/// Reads a single bit-plane, setting the bit at position `plane`
+/// of every sample for each 1 bit read,
+/// where 0 is the least-significant bit.
+/// Other bits of each sample are left as-is.
+///
+/// # Errors
+///
+/// Passes along any I/O error from the underlying stream.
+/// Returns an error if the sample type is too small
+/// to hold the given plane.
+///
+/// # Example
+/// ```
+/// use std::io::{Read, Cursor};
+/// use bitstream_io::{BigEndian, BitReader};
+/// use bitstream_io::bitplane::read_plane;
+/// let data = [0b10101010, 0b01100110];
+/// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+/// let mut samples = [0u8; 8];
+/// read_plane(&mut reader, 0, &mut samples).unwrap();
+/// read_plane(&mut reader, 1, &mut samples).unwrap();
+/// assert_eq!(samples, [1, 2, 3, 0, 1, 2, 3, 0]);
+/// ```
+pub fn read_plane<E, N>(reader: &mut BitReader<E>,
+                        plane: u32,
+                        samples: &mut [N]) -> Result<(), io::Error>
+    where E: Endianness, N: Numeric {
+
+    if plane >= N::bits_size() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "excessive bits for type read"));
+    }
+    for sample in samples.iter_mut() {
+        if reader.read_bit()? {
+            *sample |= N::one() << plane;
+        }
+    }
+    Ok(())
+}
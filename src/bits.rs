@@ -0,0 +1,173 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Conversions between bit streams and the bitvec crate's types.
+//!
+//! This module requires the `bitvec` feature.
+//!
+//! Bits are always transferred in stream order: the first bit
+//! read from or written to the stream is index 0 of the
+//! `BitVec` or `BitSlice`, whatever its `BitOrder`.
+//! A `BitSlice<u8, Msb0>` therefore has the same memory layout
+//! as a big-endian stream, and a `BitSlice<u8, Lsb0>`
+//! the same layout as a little-endian one.
+//!
+//! ## Example
+//!
+//! ```
+//! extern crate bitvec;
+//! extern crate bitstream_io;
+//! use std::io::{Read, Cursor};
+//! use bitvec::prelude::*;
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//!
+//! # fn main() {
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     writer.write_bitslice(bits![u8, Msb0; 1, 0, 1]).unwrap();
+//!     writer.write(5, 0b1_0001u8).unwrap();
+//! }
+//! assert_eq!(data, [0b1011_0001]);
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! let bits: BitVec<u8, Msb0> = reader.read_bitvec(3).unwrap();
+//! assert_eq!(bits, bits![1, 0, 1]);
+//!
+//! let flags = bits![u8, Lsb0; 0, 1, 1, 0, 1];
+//! let mut reader = BitReader::<BigEndian, _>::from_bitslice(flags);
+//! assert_eq!(reader.read::<u8>(2).unwrap(), 0b01);
+//! assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+use core::marker::PhantomData;
+
+use bitvec::order::BitOrder;
+use bitvec::slice::BitSlice;
+use bitvec::store::BitStore;
+use bitvec::vec::BitVec;
+
+use io;
+use super::{BitQueue, BitReader, BitWriter, Endianness};
+
+impl<E: Endianness, R: io::Read> BitReader<E, R> {
+    /// Reads the given number of bits into a new `BitVec`,
+    /// in stream order.
+    ///
+    /// Requires the `bitvec` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn read_bitvec<O: BitOrder>(&mut self, bits: u32) ->
+        Result<BitVec<u8, O>, io::Error> {
+        let mut vec = BitVec::with_capacity(bits as usize);
+        for _ in 0..bits {
+            vec.push(self.read_bit()?);
+        }
+        Ok(vec)
+    }
+
+    /// Reads every remaining bit of the stream into a new `BitVec`,
+    /// in stream order, including those left in a partial byte.
+    ///
+    /// Requires the `bitvec` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn read_to_end_bitvec<O: BitOrder>(&mut self) ->
+        Result<BitVec<u8, O>, io::Error> {
+        let (bytes, trailing) = self.read_to_end_bits()?;
+        let unused = if trailing > 0 {8 - trailing as usize} else {0};
+        let mut packed = BitReader::<E, _>::from_read(&bytes[..]);
+        let mut vec = BitVec::with_capacity(bytes.len() * 8 - unused);
+        for _ in 0..bytes.len() * 8 - unused {
+            vec.push(packed.read_bit()?);
+        }
+        Ok(vec)
+    }
+}
+
+impl<'b, E, T, O> BitReader<E, BitSliceSource<'b, E, T, O>>
+    where E: Endianness, T: BitStore, O: BitOrder {
+    /// Builds a reader over the bits of a `BitSlice`, in slice order.
+    ///
+    /// If the slice's length isn't a multiple of 8,
+    /// its final byte is padded with 0 bits.
+    ///
+    /// Requires the `bitvec` feature.
+    pub fn from_bitslice(bits: &'b BitSlice<T, O>) ->
+        BitReader<E, BitSliceSource<'b, E, T, O>> {
+        BitReader::from_read(BitSliceSource::new(bits))
+    }
+}
+
+impl<'a, E: Endianness, W: io::Write + ?Sized> BitWriter<'a, E, W> {
+    /// Writes all the bits of a `BitSlice`, in slice order.
+    ///
+    /// Requires the `bitvec` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn write_bitslice<T, O>(&mut self, bits: &BitSlice<T, O>) ->
+        Result<(), io::Error> where T: BitStore, O: BitOrder {
+        for bit in bits.iter().by_vals() {
+            self.write_bit(bit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads bytes packed from the bits of a `BitSlice`
+/// in the stream's endianness.
+pub struct BitSliceSource<'b, E, T, O>
+    where E: Endianness, T: BitStore, O: BitOrder {
+    bits: &'b BitSlice<T, O>,
+    phantom: PhantomData<E>,
+}
+
+impl<'b, E, T, O> BitSliceSource<'b, E, T, O>
+    where E: Endianness, T: BitStore, O: BitOrder {
+    /// Wraps the slice.
+    #[inline]
+    pub fn new(bits: &'b BitSlice<T, O>) -> BitSliceSource<'b, E, T, O> {
+        BitSliceSource{bits, phantom: PhantomData}
+    }
+
+    /// Returns the bits not yet read.
+    #[inline]
+    pub fn remaining(&self) -> &'b BitSlice<T, O> {self.bits}
+}
+
+impl<'b, E, T, O> io::Read for BitSliceSource<'b, E, T, O>
+    where E: Endianness, T: BitStore, O: BitOrder {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        for byte in buf.iter_mut() {
+            if self.bits.is_empty() {
+                break;
+            }
+            let (head, tail) = self.bits.split_at(self.bits.len().min(8));
+            let mut queue: BitQueue<E, u8> = BitQueue::new();
+            for bit in head.iter().by_vals() {
+                queue.push(1, u8::from(bit));
+            }
+            let padding = queue.remaining_len();
+            queue.push(padding, 0);
+            *byte = queue.value();
+            self.bits = tail;
+            read += 1;
+        }
+        Ok(read)
+    }
+}
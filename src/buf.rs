@@ -0,0 +1,122 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Adaptors between the `bytes` crate's buffers and bit streams.
+//!
+//! `BufSource` reads from any `Buf`, including non-contiguous
+//! chains of `Bytes`, and `BufSink` writes to any `BufMut`,
+//! so that network buffers can be read and written
+//! without first copying them to or from a `Vec<u8>`.
+//!
+//! ## Example
+//!
+//! ```
+//! extern crate bytes;
+//! extern crate bitstream_io;
+//! use bytes::{Buf, Bytes, BytesMut};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::buf::BufSink;
+//!
+//! # fn main() {
+//! let mut frame = BytesMut::new();
+//! {
+//!     let mut sink = BufSink::new(&mut frame);
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut sink);
+//!     writer.write(4, 0xAu8).unwrap();
+//!     writer.write(12, 0x123u16).unwrap();
+//! }
+//! assert_eq!(&frame[..], &[0xA1, 0x23]);
+//!
+//! // a header and payload arriving in separate buffers
+//! let chain = Bytes::from_static(&[0xA1]).chain(Bytes::from_static(&[0x23]));
+//! let mut reader = BitReader::<BigEndian, _>::from_buf(chain);
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0xA);
+//! assert_eq!(reader.read::<u16>(12).unwrap(), 0x123);
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+use core::cmp::min;
+
+use bytes::{Buf, BufMut};
+
+use io;
+use super::{BitReader, Endianness};
+
+/// Reads bytes from a `Buf`, advancing past them.
+pub struct BufSource<B: Buf> {
+    buf: B,
+}
+
+impl<B: Buf> BufSource<B> {
+    /// Wraps the buffer.
+    #[inline]
+    pub fn new(buf: B) -> BufSource<B> {
+        BufSource{buf}
+    }
+
+    /// Returns a reference to the buffer.
+    #[inline]
+    pub fn get_ref(&self) -> &B {&self.buf}
+
+    /// Returns the buffer, positioned after any bytes read.
+    #[inline]
+    pub fn into_inner(self) -> B {self.buf}
+}
+
+impl<B: Buf> io::Read for BufSource<B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let amount = min(buf.len(), self.buf.remaining());
+        self.buf.copy_to_slice(&mut buf[..amount]);
+        Ok(amount)
+    }
+}
+
+/// Writes bytes to a `BufMut`.
+///
+/// Writing to a buffer without enough room left
+/// fills it and then returns a `WriteZero` error.
+pub struct BufSink<B: BufMut> {
+    buf: B,
+}
+
+impl<B: BufMut> BufSink<B> {
+    /// Wraps the buffer.
+    #[inline]
+    pub fn new(buf: B) -> BufSink<B> {
+        BufSink{buf}
+    }
+
+    /// Returns a reference to the buffer.
+    #[inline]
+    pub fn get_ref(&self) -> &B {&self.buf}
+
+    /// Returns the buffer.
+    #[inline]
+    pub fn into_inner(self) -> B {self.buf}
+}
+
+impl<B: BufMut> io::Write for BufSink<B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let amount = min(buf.len(), self.buf.remaining_mut());
+        self.buf.put_slice(&buf[..amount]);
+        Ok(amount)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {Ok(())}
+}
+
+impl<E: Endianness, B: Buf> BitReader<E, BufSource<B>> {
+    /// Wraps a BitReader around a `Buf`, such as `Bytes`
+    /// or a chain of several buffers, without copying it.
+    pub fn from_buf(buf: B) -> BitReader<E, BufSource<B>> {
+        BitReader::from_read(BufSource::new(buf))
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Building small bit streams in a single expression.
+//!
+//! A `BitStreamBuilder` writes fields with chained calls
+//! and returns the finished bytes, which makes test vectors
+//! and small packets much shorter to spell out than
+//! a `BitWriter` over a `Vec` would.
+//! Since it's meant for fixtures, a field which doesn't fit
+//! its width is a panic rather than an error.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::BigEndian;
+//! use bitstream_io::builder::BitStreamBuilder;
+//!
+//! let (bytes, trailing) = BitStreamBuilder::<BigEndian>::new()
+//!     .bits(3, 0b101)
+//!     .bytes(b"ab")
+//!     .unary0(4)
+//!     .build();
+//! assert_eq!(bytes, [0b101_01100, 0b001_01100, 0b010_11110]);
+//! assert_eq!(trailing, 0);
+//! ```
+
+#![warn(missing_docs)]
+
+use alloc::vec::Vec;
+use core::mem;
+use io;
+
+use super::{BitQueue, BitWriter, Endianness};
+
+/// Builds a stream by writing fields with chained calls.
+///
+/// # Panics
+///
+/// Each method panics if its field can't be written,
+/// such as a value too large for its number of bits.
+#[derive(Clone)]
+pub struct BitStreamBuilder<E: Endianness> {
+    data: Vec<u8>,
+    bitqueue: BitQueue<E, u8>,
+}
+
+impl<E: Endianness> BitStreamBuilder<E> {
+    /// Starts an empty stream.
+    #[inline]
+    pub fn new() -> BitStreamBuilder<E> {
+        BitStreamBuilder{data: Vec::new(), bitqueue: BitQueue::new()}
+    }
+
+    /// Writes a single bit.
+    #[inline]
+    pub fn bit(self, bit: bool) -> Self {
+        self.with_writer(|w| w.write_bit(bit))
+    }
+
+    /// Writes an unsigned value of up to 64 bits.
+    #[inline]
+    pub fn bits(self, bits: u32, value: u64) -> Self {
+        self.with_writer(|w| w.write(bits, value))
+    }
+
+    /// Writes a twos-complement signed value of up to 64 bits.
+    #[inline]
+    pub fn signed(self, bits: u32, value: i64) -> Self {
+        self.with_writer(|w| w.write_signed(bits, value))
+    }
+
+    /// Writes the whole bytes of a buffer,
+    /// which needn't be byte-aligned.
+    #[inline]
+    pub fn bytes(self, buf: &[u8]) -> Self {
+        self.with_writer(|w| w.write_bytes(buf))
+    }
+
+    /// Writes a value in unary, with 1 bits ending in a 0 bit.
+    #[inline]
+    pub fn unary0(self, value: u32) -> Self {
+        self.with_writer(|w| w.write_unary0(value))
+    }
+
+    /// Writes a value in unary, with 0 bits ending in a 1 bit.
+    #[inline]
+    pub fn unary1(self, value: u32) -> Self {
+        self.with_writer(|w| w.write_unary1(value))
+    }
+
+    /// Pads the stream with 0 bits to the next whole byte.
+    #[inline]
+    pub fn align(self) -> Self {
+        self.with_writer(|w| w.byte_align())
+    }
+
+    /// Returns the number of bits written so far.
+    #[inline]
+    pub fn len_bits(&self) -> u64 {
+        self.data.len() as u64 * 8 + u64::from(self.bitqueue.len())
+    }
+
+    /// Finishes the stream, returning its bytes
+    /// along with the number of bits used in the final byte
+    /// if it's a partial one, or 0 if every byte is whole.
+    /// A partial final byte is padded with 0 bits.
+    pub fn build(self) -> (Vec<u8>, u32) {
+        let trailing = self.bitqueue.len();
+        (self.align().data, trailing)
+    }
+
+    // writes to the stream so far, panicking on any error
+    fn with_writer<F>(mut self, f: F) -> Self
+        where F: FnOnce(&mut BitWriter<E>) -> Result<(), io::Error> {
+        let bitqueue = mem::take(&mut self.bitqueue);
+        let mut writer = BitWriter::from_parts(&mut self.data, bitqueue);
+        let result = f(&mut writer);
+        self.bitqueue = writer.into_bitqueue();
+        if let Err(err) = result {
+            panic!("unable to build stream: {}", err);
+        }
+        self
+    }
+}
+
+impl<E: Endianness> Default for BitStreamBuilder<E> {
+    #[inline]
+    fn default() -> Self {BitStreamBuilder::new()}
+}
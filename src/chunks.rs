@@ -0,0 +1,169 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reading from a sequence of separate byte buffers.
+//!
+//! Reassembling a frame from the network often leaves it
+//! spread over several buffers.
+//! A `ChunkSource` reads from such a sequence of chunks in order,
+//! as though they were one contiguous stream,
+//! so that a frame can be read without copying it into one `Vec` first.
+//! Fields may span the boundaries between chunks,
+//! and empty chunks are passed over.
+//!
+//! Slices of chunks need neither the standard library
+//! nor an allocator, while `Vec`s and `VecDeque`s of chunks
+//! need the `alloc` feature.
+//! Chains of the `bytes` crate's buffers are read with `buf::BufSource`.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitReader};
+//!
+//! let header = [0b1010_0001];
+//! let payload = [0x23, 0x45];
+//! let chunks: [&[u8]; 3] = [&header, &[], &payload];
+//! let mut reader = BitReader::<BigEndian, _>::from_chunks(&chunks[..]);
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0b1010);
+//! assert_eq!(reader.read::<u32>(20).unwrap(), 0x12345);
+//! assert!(reader.read_bit().is_err());
+//! ```
+
+#![warn(missing_docs)]
+
+#[cfg(feature = "alloc")]
+use alloc::collections::VecDeque;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cmp::min;
+
+use io;
+use super::{BitReader, Endianness};
+
+/// A sequence of byte buffers, read one after another.
+pub trait Chunks {
+    /// Returns the chunk at the given index,
+    /// or `None` if it's past the last chunk.
+    fn chunk(&self, index: usize) -> Option<&[u8]>;
+}
+
+impl<T: AsRef<[u8]>> Chunks for [T] {
+    #[inline]
+    fn chunk(&self, index: usize) -> Option<&[u8]> {
+        self.get(index).map(|chunk| chunk.as_ref())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: AsRef<[u8]>> Chunks for Vec<T> {
+    #[inline]
+    fn chunk(&self, index: usize) -> Option<&[u8]> {
+        self.get(index).map(|chunk| chunk.as_ref())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: AsRef<[u8]>> Chunks for VecDeque<T> {
+    #[inline]
+    fn chunk(&self, index: usize) -> Option<&[u8]> {
+        self.get(index).map(|chunk| chunk.as_ref())
+    }
+}
+
+impl<C: Chunks + ?Sized> Chunks for &C {
+    #[inline]
+    fn chunk(&self, index: usize) -> Option<&[u8]> {
+        (**self).chunk(index)
+    }
+}
+
+/// Reads bytes from a sequence of chunks in order.
+#[derive(Clone, Debug)]
+pub struct ChunkSource<C> {
+    chunks: C,
+    index: usize,
+    offset: usize,
+}
+
+impl<C: Chunks> ChunkSource<C> {
+    /// Wraps the chunks, starting from the first byte of the first.
+    #[inline]
+    pub fn new(chunks: C) -> ChunkSource<C> {
+        ChunkSource{chunks, index: 0, offset: 0}
+    }
+
+    /// Returns a reference to the chunks.
+    #[inline]
+    pub fn get_ref(&self) -> &C {&self.chunks}
+
+    /// Returns the index of the chunk holding the next byte,
+    /// and that byte's offset within it.
+    #[inline]
+    pub fn position(&self) -> (usize, usize) {(self.index, self.offset)}
+
+    /// Returns the chunks.
+    #[inline]
+    pub fn into_inner(self) -> C {self.chunks}
+
+    // the rest of the current chunk, moving past any chunks
+    // which have been read entirely
+    fn remaining(&mut self) -> &[u8] {
+        while self.chunks.chunk(self.index)
+            .is_some_and(|chunk| chunk.len() <= self.offset) {
+            self.index += 1;
+            self.offset = 0;
+        }
+        match self.chunks.chunk(self.index) {
+            Some(chunk) => &chunk[self.offset..],
+            None => &[],
+        }
+    }
+}
+
+impl<C: Chunks> io::Read for ChunkSource<C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        while read < buf.len() {
+            let amount = {
+                let remaining = self.remaining();
+                let amount = min(buf.len() - read, remaining.len());
+                buf[read..read + amount].copy_from_slice(&remaining[..amount]);
+                amount
+            };
+            if amount == 0 {
+                break;
+            }
+            self.offset += amount;
+            read += amount;
+        }
+        Ok(read)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<C: Chunks> io::BufRead for ChunkSource<C> {
+    #[inline]
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.remaining())
+    }
+
+    #[inline]
+    fn consume(&mut self, amount: usize) {
+        self.offset += amount;
+    }
+}
+
+impl<E: Endianness, C: Chunks> BitReader<E, ChunkSource<C>> {
+    /// Wraps a BitReader around a sequence of chunks,
+    /// such as a slice of byte slices or a `VecDeque<Vec<u8>>`,
+    /// reading them in order without copying them together.
+    pub fn from_chunks(chunks: C) -> BitReader<E, ChunkSource<C>> {
+        BitReader::from_read(ChunkSource::new(chunks))
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A bridge between bit-packed frames and `tokio_util`'s codecs.
+//!
+//! `BitCodec` implements `Decoder` and `Encoder` for any type
+//! implementing `FromBitStream` and `ToBitStream`, so that it can
+//! be used with `FramedRead`, `FramedWrite` or `Framed`.
+//! Each frame starts on a byte boundary and is padded
+//! to a whole number of bytes.
+//!
+//! When decoding, the frame is parsed directly from the bytes
+//! accumulated so far.  If the parser runs out of data,
+//! the decoder asks the framer for more and leaves the
+//! buffer untouched; otherwise it advances the buffer
+//! past the bytes the frame used.
+//!
+//! ## Example
+//!
+//! ```
+//! extern crate bytes;
+//! extern crate tokio_util;
+//! extern crate bitstream_io;
+//! use std::io;
+//! use bytes::BytesMut;
+//! use tokio_util::codec::{Decoder, Encoder};
+//! use bitstream_io::{BigEndian, BitRead, BitWrite, FromBitStream, ToBitStream};
+//! use bitstream_io::codec::BitCodec;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Packet {
+//!     kind: u8,
+//!     length: u16,
+//! }
+//!
+//! impl FromBitStream for Packet {
+//!     type Error = io::Error;
+//!     fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+//!         Ok(Packet{kind: r.read(3)?, length: r.read(10)?})
+//!     }
+//! }
+//!
+//! impl ToBitStream for Packet {
+//!     type Error = io::Error;
+//!     fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+//!         w.write(3, self.kind)?;
+//!         w.write(10, self.length)
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let mut codec = BitCodec::<BigEndian, Packet>::new();
+//! let mut buf = BytesMut::new();
+//! codec.encode(Packet{kind: 5, length: 1000}, &mut buf).unwrap();
+//! assert_eq!(&buf[..], &[0b1011_1111, 0b0100_0000]);
+//!
+//! // only part of the frame has arrived
+//! let mut partial = BytesMut::from(&buf[..1]);
+//! assert!(codec.decode(&mut partial).unwrap().is_none());
+//! assert_eq!(partial.len(), 1);
+//!
+//! assert_eq!(codec.decode(&mut buf).unwrap(),
+//!            Some(Packet{kind: 5, length: 1000}));
+//! assert_eq!(codec.frame_bits(), 13);
+//! assert!(buf.is_empty());
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+
+use bytes::{Buf, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use buf::BufSink;
+use read;
+use super::{BitWriter, Endianness, FromBitStream, ToBitStream};
+
+/// Decodes and encodes byte-aligned, bit-packed frames of type `T`.
+pub struct BitCodec<E: Endianness, T> {
+    frame_bits: u64,
+    phantom: PhantomData<fn() -> (E, T)>,
+}
+
+impl<E: Endianness, T> BitCodec<E, T> {
+    /// Builds a new codec.
+    #[inline]
+    pub fn new() -> BitCodec<E, T> {
+        BitCodec{frame_bits: 0, phantom: PhantomData}
+    }
+
+    /// Returns the exact number of bits used by the most
+    /// recently decoded frame, not including any padding.
+    #[inline]
+    pub fn frame_bits(&self) -> u64 {self.frame_bits}
+}
+
+impl<E: Endianness, T> Default for BitCodec<E, T> {
+    #[inline]
+    fn default() -> Self {BitCodec::new()}
+}
+
+impl<E: Endianness, T> Clone for BitCodec<E, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        BitCodec{frame_bits: self.frame_bits, phantom: PhantomData}
+    }
+}
+
+impl<E: Endianness, T> fmt::Debug for BitCodec<E, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BitCodec")
+            .field("frame_bits", &self.frame_bits)
+            .finish()
+    }
+}
+
+impl<E, T> Decoder for BitCodec<E, T>
+    where E: Endianness,
+          T: FromBitStream,
+          T::Error: From<io::Error> {
+    type Item = T;
+    type Error = T::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) ->
+        Result<Option<T>, T::Error> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+        match read::parse_accumulated::<E, T>(&src[..], 0)? {
+            Some((frame, bits)) => {
+                self.frame_bits = bits;
+                src.advance(bits.div_ceil(8) as usize);
+                Ok(Some(frame))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<E, T> Encoder<T> for BitCodec<E, T>
+    where E: Endianness,
+          T: ToBitStream,
+          T::Error: From<io::Error> {
+    type Error = T::Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) ->
+        Result<(), T::Error> {
+        let mut sink = BufSink::new(dst);
+        let mut writer = BitWriter::<E>::new(&mut sink);
+        item.to_writer(&mut writer)?;
+        writer.byte_align()?;
+        Ok(())
+    }
+}
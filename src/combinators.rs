@@ -0,0 +1,161 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Parser combinators over `BitRead` streams.
+//!
+//! A parser is any function or closure which takes a `BitRead` stream
+//! and returns a `Result`.
+//! The functions in this module build parsers for single values
+//! and combine them into parsers for larger structures,
+//! in the style of parser combinator libraries such as nom
+//! but reading directly from a bit stream.
+//!
+//! Because a `BitRead` stream can't be rewound,
+//! parsers never backtrack; any bits a failed parser has read
+//! remain consumed.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Read, Cursor};
+//! use bitstream_io::{BigEndian, BitReader};
+//! use bitstream_io::combinators::{tag, unsigned, signed, length_prefixed,
+//!                                 many_till, flag};
+//!
+//! let data = [0b1010_0010, 0b1111_0001, 0b0010_0110, 0b1000_0000];
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//!
+//! let mut magic = tag(4, 0b1010u8);
+//! let mut samples = length_prefixed(4, signed::<_, i8>(4));
+//! let mut values = many_till(unsigned::<_, u8>(3), flag());
+//!
+//! magic(&mut reader).unwrap();
+//! assert_eq!(samples(&mut reader).unwrap(), vec![-1, 1]);
+//! assert_eq!(values(&mut reader).unwrap(), vec![0b010, 0b110]);
+//! ```
+
+#![warn(missing_docs)]
+
+use alloc::vec::Vec;
+use io;
+
+use super::{BitRead, Numeric, SignedNumeric};
+
+/// Returns a parser for an unsigned value of the given number of bits.
+#[inline]
+pub fn unsigned<R, U>(bits: u32) -> impl FnMut(&mut R) -> io::Result<U>
+    where R: BitRead + ?Sized, U: Numeric {
+    move |r: &mut R| r.read(bits)
+}
+
+/// Returns a parser for a twos-complement signed value
+/// of the given number of bits.
+#[inline]
+pub fn signed<R, S>(bits: u32) -> impl FnMut(&mut R) -> io::Result<S>
+    where R: BitRead + ?Sized, S: SignedNumeric {
+    move |r: &mut R| r.read_signed(bits)
+}
+
+/// Returns a parser for a single bit as a `bool`.
+#[inline]
+pub fn flag<R>() -> impl FnMut(&mut R) -> io::Result<bool>
+    where R: BitRead + ?Sized {
+    |r: &mut R| r.read_bit()
+}
+
+/// Returns a parser which reads a value of the given number of bits
+/// and fails with `InvalidData` unless it matches the expected value.
+pub fn tag<R, U>(bits: u32, expected: U) -> impl FnMut(&mut R) -> io::Result<U>
+    where R: BitRead + ?Sized, U: Numeric {
+    move |r: &mut R| {
+        let value = r.read(bits)?;
+        if value == expected {
+            Ok(value)
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidData,
+                               "unexpected tag value"))
+        }
+    }
+}
+
+/// Returns a parser which applies a function to a parser's result.
+pub fn map<R, P, F, T, U, E>(mut parser: P, mut f: F) ->
+    impl FnMut(&mut R) -> Result<U, E>
+    where R: BitRead + ?Sized,
+          P: FnMut(&mut R) -> Result<T, E>,
+          F: FnMut(T) -> U {
+    move |r: &mut R| parser(r).map(&mut f)
+}
+
+/// Returns a parser which runs two parsers in order
+/// and returns both of their results.
+pub fn pair<R, P, Q, T, U, E>(mut first: P, mut second: Q) ->
+    impl FnMut(&mut R) -> Result<(T, U), E>
+    where R: BitRead + ?Sized,
+          P: FnMut(&mut R) -> Result<T, E>,
+          Q: FnMut(&mut R) -> Result<U, E> {
+    move |r: &mut R| {
+        let t = first(r)?;
+        let u = second(r)?;
+        Ok((t, u))
+    }
+}
+
+/// Returns a parser which runs a parser the given number of times
+/// and collects its results.
+pub fn count<R, P, T, E>(mut parser: P, times: usize) ->
+    impl FnMut(&mut R) -> Result<Vec<T>, E>
+    where R: BitRead + ?Sized, P: FnMut(&mut R) -> Result<T, E> {
+    move |r: &mut R| {
+        let mut items = Vec::with_capacity(times);
+        for _ in 0..times {
+            items.push(parser(r)?);
+        }
+        Ok(items)
+    }
+}
+
+/// Returns a parser which reads an unsigned count
+/// of the given number of bits, then runs a parser
+/// that many times and collects its results.
+pub fn length_prefixed<R, P, T, E>(length_bits: u32, mut parser: P) ->
+    impl FnMut(&mut R) -> Result<Vec<T>, E>
+    where R: BitRead + ?Sized,
+          P: FnMut(&mut R) -> Result<T, E>,
+          E: From<io::Error> {
+    move |r: &mut R| {
+        let len: u64 = r.read(length_bits)?;
+        let mut items = Vec::new();
+        for _ in 0..len {
+            items.push(parser(r)?);
+        }
+        Ok(items)
+    }
+}
+
+/// Returns a parser which runs a parser repeatedly
+/// until the terminator parser returns `true`,
+/// and collects the results.
+///
+/// The terminator is run before each item, so that
+/// a stream of flag bits, each indicating whether
+/// an item follows, is parsed with `many_till(item, flag())`
+/// where a 1 bit ends the list.
+pub fn many_till<R, P, Q, T, E>(mut parser: P, mut terminator: Q) ->
+    impl FnMut(&mut R) -> Result<Vec<T>, E>
+    where R: BitRead + ?Sized,
+          P: FnMut(&mut R) -> Result<T, E>,
+          Q: FnMut(&mut R) -> Result<bool, E> {
+    move |r: &mut R| {
+        let mut items = Vec::new();
+        while !terminator(r)? {
+            items.push(parser(r)?);
+        }
+        Ok(items)
+    }
+}
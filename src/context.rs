@@ -0,0 +1,114 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Locating errors within a stream.
+//!
+//! This module requires the `std` feature.
+//!
+//! A bare `UnexpectedEof` error says nothing about where
+//! a parser gave up.
+//! A `ContextReader` wraps a `BitRead` stream and attaches
+//! the bit offset of the field being read, along with the
+//! most recent label given to it, to any error from that stream.
+//! The error keeps its original kind, and its message and source
+//! carry the added context as a `ContextError`.
+//! Errors raised by parsers themselves, such as invalid values,
+//! are passed along unchanged.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitRead, BitReader};
+//! use bitstream_io::context::{ContextError, ContextReader};
+//!
+//! let data = [0xFF, 0x01];
+//! let mut reader = ContextReader::new(
+//!     BitReader::<BigEndian, _>::from_read(&data[..]));
+//! reader.set_label("sync");
+//! assert_eq!(reader.read::<u16>(12).unwrap(), 0xFF0);
+//! reader.set_label("sample rate");
+//! let err = reader.read::<u8>(8).unwrap_err();
+//! assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+//!
+//! let context = ContextError::find(&err).unwrap();
+//! assert_eq!(context.position(), 12);
+//! assert_eq!(context.label(), Some("sample rate"));
+//! assert_eq!(err.to_string(),
+//!            "failed to fill whole buffer at bit 12 (sample rate)");
+//! ```
+
+#![warn(missing_docs)]
+
+use std::fmt;
+use std::io;
+
+use super::BitRead;
+use tap::{Observer, Tap, TapError};
+
+/// The label given to the field being read when an error occurred,
+/// if any.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FieldLabel(Option<&'static str>);
+
+impl fmt::Display for FieldLabel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(label) => write!(f, " ({})", label),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An error along with where in the stream it occurred.
+pub type ContextError = TapError<FieldLabel>;
+
+impl TapError<FieldLabel> {
+    /// Returns the label most recently given to the reader, if any.
+    #[inline]
+    pub fn label(&self) -> Option<&'static str> {self.context().0}
+}
+
+/// Attaches the offset and label of the field being read to errors.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Context {
+    label: Option<&'static str>,
+}
+
+impl Observer for Context {
+    fn error(&self, offset: u64, error: io::Error) -> io::Error {
+        ContextError::new(error, offset, FieldLabel(self.label)).into()
+    }
+}
+
+/// Wraps a bit stream, attaching the current bit offset
+/// and field label to any errors it returns.
+///
+/// Offsets count from where the stream was when it was wrapped.
+pub type ContextReader<R> = Tap<R, Context>;
+
+impl<R: BitRead> Tap<R, Context> {
+    /// Wraps the stream, starting at bit offset 0 with no label.
+    pub fn new(inner: R) -> ContextReader<R> {
+        Tap::with_observer(inner, Context::default())
+    }
+
+    /// Labels the fields which follow,
+    /// until the label is next changed or cleared.
+    #[inline]
+    pub fn set_label(&mut self, label: &'static str) {
+        self.observer_mut().label = Some(label);
+    }
+
+    /// Removes the current label.
+    #[inline]
+    pub fn clear_label(&mut self) {self.observer_mut().label = None;}
+
+    /// Returns the current label, if any.
+    #[inline]
+    pub fn label(&self) -> Option<&'static str> {self.observer().label}
+}
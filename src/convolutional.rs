@@ -0,0 +1,332 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Adaptors for rate 1/2 convolutional coding.
+//!
+//! Each data bit is shifted into a register of `constraint` bits
+//! and the parity of that register masked by each of the
+//! code's two generator polynomials is written to the stream.
+//! Blocks are terminated by flushing the register with 0 bits,
+//! so a block of `n` data bits is stored as `2 * (n + constraint - 1)`
+//! bits.
+//!
+//! Blocks are decoded with a hard-decision Viterbi decoder,
+//! which finds the most likely data given the received bits.
+//!
+//! This module requires the `convolutional` feature,
+//! which the `fec` feature also enables.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Write, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::convolutional::{Convolutional,
+//!                                   ConvolutionalWriter, ViterbiReader};
+//!
+//! let code = Convolutional::nasa();
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     {
+//!         let mut encoder = ConvolutionalWriter::new(&mut writer, &code)
+//!             .unwrap();
+//!         encoder.write(16, 0xBEEF).unwrap();
+//!         encoder.finish().unwrap();
+//!     }
+//!     writer.byte_align().unwrap();
+//! }
+//! assert_eq!(data.len(), 6);
+//!
+//! // flip a couple of bits in transit
+//! data[0] ^= 0b0100_0000;
+//! data[3] ^= 0b0000_0010;
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! let mut decoder = ViterbiReader::new(&mut reader, &code, 16).unwrap();
+//! assert_eq!(decoder.read(16).unwrap(), 0xBEEF);
+//! ```
+
+#![warn(missing_docs)]
+
+use alloc::vec::Vec;
+use io;
+use alloc::collections::VecDeque;
+
+use super::{BitReader, BitWriter, BitQueue, Endianness};
+
+/// The parameters of a rate 1/2 convolutional code.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Convolutional {
+    /// The number of bits in the encoder's shift register, from 2 to 16
+    pub constraint: u32,
+    /// The two generator polynomials, whose outputs are written
+    /// in order, where the least-significant bit of each
+    /// corresponds to the most recent data bit
+    pub polys: [u32; 2],
+}
+
+impl Convolutional {
+    /// The constraint length 7 code with polynomials 171 and 133 (octal),
+    /// as used by NASA/CCSDS, DVB and 802.11
+    pub const fn nasa() -> Convolutional {
+        Convolutional{constraint: 7, polys: [0o171, 0o133]}
+    }
+
+    fn check(&self) -> Result<(), io::Error> {
+        if (2..=16).contains(&self.constraint) &&
+            self.polys.iter().all(|p| (*p != 0) &&
+                                  (*p < (1 << self.constraint))) {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidInput,
+                               "invalid convolutional code"))
+        }
+    }
+
+    // returns the two output bits for the given register contents
+    #[inline]
+    fn outputs(&self, register: u32) -> (bool, bool) {
+        ((register & self.polys[0]).count_ones() & 1 == 1,
+         (register & self.polys[1]).count_ones() & 1 == 1)
+    }
+}
+
+/// Writes data bits to a `BitWriter` as a convolutionally coded block.
+pub struct ConvolutionalWriter<'w, 'a: 'w, E: 'w + Endianness> {
+    writer: &'w mut BitWriter<'a, E>,
+    code: Convolutional,
+    register: u32,
+}
+
+impl<'w, 'a: 'w, E: Endianness> ConvolutionalWriter<'w, 'a, E> {
+    /// Wraps a ConvolutionalWriter around a `BitWriter`
+    /// using the given code.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the code's parameters are invalid.
+    pub fn new(writer: &'w mut BitWriter<'a, E>,
+               code: &Convolutional) ->
+        Result<ConvolutionalWriter<'w, 'a, E>, io::Error> {
+
+        code.check()?;
+        Ok(ConvolutionalWriter{writer, code: *code, register: 0})
+    }
+
+    /// Encodes a single data bit.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn write_bit(&mut self, bit: bool) -> Result<(), io::Error> {
+        self.register = ((self.register << 1) | (bit as u32)) &
+            ((1 << self.code.constraint) - 1);
+        let (first, second) = self.code.outputs(self.register);
+        self.writer.write_bit(first)?;
+        self.writer.write_bit(second)
+    }
+
+    /// Encodes an unsigned value of up to 64 bits,
+    /// taking its bits in the stream's order.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the number of bits is larger than 64
+    /// or if the value is too large to fit the given number of bits.
+    pub fn write(&mut self, bits: u32, value: u64) -> Result<(), io::Error> {
+        if bits > 64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive bits for type written"));
+        } else if (bits < 64) && (value >= (1 << bits)) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive value for bits written"));
+        } else if bits == 0 {
+            return Ok(());
+        }
+        let mut queue: BitQueue<E, u64> = BitQueue::from_value(value, bits);
+        while !queue.is_empty() {
+            self.write_bit(queue.pop(1) == 1)?;
+        }
+        Ok(())
+    }
+
+    /// Terminates the current block by flushing the encoder's
+    /// register with 0 bits, after which a new block may be written.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn finish(&mut self) -> Result<(), io::Error> {
+        for _ in 1..self.code.constraint {
+            self.write_bit(false)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a convolutionally coded block of data bits from a `BitReader`.
+///
+/// Data bits are decoded once enough of the following coded bits
+/// have been received, so the decoder may read ahead of the bits
+/// returned, but never beyond the end of the block.
+pub struct ViterbiReader<'r, E: 'r + Endianness> {
+    reader: &'r mut BitReader<E>,
+    code: Convolutional,
+    // data bits in the block whose coded bits have not been read
+    unread: u64,
+    // data bits in the block which have not been returned
+    remaining: u64,
+    metrics: Vec<u32>,
+    // one bit per state per received step, set if the state's
+    // surviving predecessor had its oldest register bit set
+    decisions: VecDeque<Vec<u64>>,
+    decoded: VecDeque<bool>,
+}
+
+impl<'r, E: Endianness> ViterbiReader<'r, E> {
+    /// Wraps a ViterbiReader around a `BitReader`
+    /// for a terminated block of the given number of data bits
+    /// using the given code, which must match the one used
+    /// to write the block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the code's parameters are invalid.
+    pub fn new(reader: &'r mut BitReader<E>,
+               code: &Convolutional,
+               bits: u64) -> Result<ViterbiReader<'r, E>, io::Error> {
+        code.check()?;
+        let states = 1 << (code.constraint - 1);
+        let mut metrics = vec![u32::MAX / 2; states];
+        metrics[0] = 0;
+        Ok(ViterbiReader{reader,
+                         code: *code,
+                         unread: bits,
+                         remaining: bits,
+                         metrics,
+                         decisions: VecDeque::new(),
+                         decoded: VecDeque::new()})
+    }
+
+    /// Reads a single data bit.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if every data bit in the block has been read.
+    pub fn read_bit(&mut self) -> Result<bool, io::Error> {
+        if let Some(bit) = self.decoded.pop_front() {
+            return Ok(bit);
+        } else if self.remaining == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                      "end of convolutional block"));
+        }
+
+        let depth = 5 * self.code.constraint as usize;
+        while (self.decisions.len() < depth) && (self.unread > 0) {
+            self.receive()?;
+            self.unread -= 1;
+        }
+
+        if self.unread > 0 {
+            // decode the oldest bit from the most likely state
+            let best = (0..self.metrics.len())
+                .min_by_key(|s| self.metrics[*s])
+                .unwrap();
+            let bits = self.traceback(best);
+            self.decisions.pop_front();
+            self.remaining -= 1;
+            Ok(bits[0])
+        } else {
+            // decode the remainder of the block from its terminated state
+            for _ in 1..self.code.constraint {
+                self.receive()?;
+            }
+            let bits = self.traceback(0);
+            self.decoded.extend(bits.iter().take(self.remaining as usize));
+            self.decisions.clear();
+            self.remaining = 0;
+            Ok(self.decoded.pop_front().unwrap())
+        }
+    }
+
+    /// Reads an unsigned value of up to 64 bits,
+    /// assembled from data bits in the stream's order.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the number of bits is larger than 64
+    /// or if the block does not have enough data bits remaining.
+    pub fn read(&mut self, bits: u32) -> Result<u64, io::Error> {
+        if bits > 64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive bits for type read"));
+        }
+        let mut queue: BitQueue<E, u64> = BitQueue::new();
+        for _ in 0..bits {
+            queue.push(1, self.read_bit()? as u64);
+        }
+        Ok(queue.value())
+    }
+
+    /// Returns the number of data bits in the block
+    /// which have not yet been read.
+    #[inline]
+    pub fn remaining(&self) -> u64 {self.remaining + self.decoded.len() as u64}
+
+    // reads a pair of coded bits and updates the path metrics
+    fn receive(&mut self) -> Result<(), io::Error> {
+        let first = self.reader.read_bit()?;
+        let second = self.reader.read_bit()?;
+        let states = self.metrics.len();
+        let high = self.code.constraint - 2;
+        let mut metrics = vec![0; states];
+        let mut decisions = vec![0u64; states.div_ceil(64)];
+        for (next, metric) in metrics.iter_mut().enumerate() {
+            let mut candidates = [0; 2];
+            for (oldest, candidate) in candidates.iter_mut().enumerate() {
+                let register = ((oldest as u32) << (high + 1)) | next as u32;
+                let (a, b) = self.code.outputs(register);
+                let previous = (register >> 1) as usize;
+                *candidate = self.metrics[previous] +
+                    ((a != first) as u32) + ((b != second) as u32);
+            }
+            if candidates[1] < candidates[0] {
+                *metric = candidates[1];
+                decisions[next / 64] |= 1 << (next % 64);
+            } else {
+                *metric = candidates[0];
+            }
+        }
+        // keep metrics from growing without bound
+        let least = *metrics.iter().min().unwrap();
+        for metric in metrics.iter_mut() {
+            *metric -= least;
+        }
+        self.metrics = metrics;
+        self.decisions.push_back(decisions);
+        Ok(())
+    }
+
+    // returns the data bits of the surviving path
+    // ending at the given state, oldest first
+    fn traceback(&self, mut state: usize) -> Vec<bool> {
+        let high = self.code.constraint - 2;
+        let mut bits = Vec::with_capacity(self.decisions.len());
+        for decisions in self.decisions.iter().rev() {
+            bits.push(state & 1 == 1);
+            let oldest = (decisions[state / 64] >> (state % 64)) & 1;
+            state = (state >> 1) | ((oldest as usize) << high);
+        }
+        bits.reverse();
+        bits
+    }
+}
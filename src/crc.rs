@@ -0,0 +1,330 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Cyclic redundancy checks calculated bit-by-bit.
+//!
+//! Because CRCs are calculated a single bit at a time,
+//! in the order those bits appear in the stream,
+//! the checked region need not start or end on a byte boundary.
+//!
+//! Bits are shifted into the CRC register in stream order,
+//! so a CRC whose specification processes the most-significant bit
+//! of each byte first matches a big-endian stream,
+//! while a CRC specified with reflected input
+//! (such as the common CRC-32) matches a little-endian stream
+//! when its result is also reflected.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Write, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::crc::{Crc, CrcReader, CrcWriter};
+//!
+//! let crc = Crc::crc8();
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     writer.write(3, 0b101).unwrap();   // not covered by the CRC
+//!     let checksum = {
+//!         let mut crc_writer = CrcWriter::new(&mut writer, &crc);
+//!         crc_writer.write(5, 0b11001).unwrap();
+//!         crc_writer.write(7, 0b1010101).unwrap();
+//!         crc_writer.checksum()
+//!     };
+//!     writer.write(8, checksum).unwrap();
+//!     writer.byte_align().unwrap();
+//! }
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+//! let checksum = {
+//!     let mut crc_reader = CrcReader::new(&mut reader, &crc);
+//!     assert_eq!(crc_reader.read(5).unwrap(), 0b11001);
+//!     assert_eq!(crc_reader.read(7).unwrap(), 0b1010101);
+//!     crc_reader.checksum()
+//! };
+//! assert_eq!(reader.read::<u64>(8).unwrap(), checksum);
+//! ```
+
+#![warn(missing_docs)]
+
+use io;
+
+use super::{BitReader, BitWriter, BitQueue, Endianness};
+
+/// The parameters of a cyclic redundancy check.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Crc {
+    /// The width of the CRC register, in bits, from 1 to 64
+    pub width: u32,
+    /// The generator polynomial, without its leading 1 bit
+    pub poly: u64,
+    /// The register's initial value
+    pub init: u64,
+    /// Whether the register's bits are reversed
+    /// before being combined with `xor_out`
+    pub reflect_out: bool,
+    /// A value exclusive-ORed with the register to form the result
+    pub xor_out: u64,
+}
+
+impl Crc {
+    /// CRC-8 with polynomial 0x07, as used by ATM HEC and SMBus
+    pub const fn crc8() -> Crc {
+        Crc{width: 8, poly: 0x07, init: 0,
+            reflect_out: false, xor_out: 0}
+    }
+
+    /// CRC-16/CCITT-FALSE, with polynomial 0x1021 and initial value 0xFFFF
+    pub const fn crc16_ccitt() -> Crc {
+        Crc{width: 16, poly: 0x1021, init: 0xFFFF,
+            reflect_out: false, xor_out: 0}
+    }
+
+    /// CRC-32/MPEG-2, as used by MPEG transport streams
+    pub const fn crc32_mpeg2() -> Crc {
+        Crc{width: 32, poly: 0x04C1_1DB7, init: 0xFFFF_FFFF,
+            reflect_out: false, xor_out: 0}
+    }
+
+    /// CRC-32, as used by Ethernet, zlib and PNG.
+    /// Its input is reflected, so it should be calculated
+    /// over little-endian streams.
+    pub const fn crc32() -> Crc {
+        Crc{width: 32, poly: 0x04C1_1DB7, init: 0xFFFF_FFFF,
+            reflect_out: true, xor_out: 0xFFFF_FFFF}
+    }
+
+    /// Returns a new digest with this CRC's initial value
+    pub fn digest(&self) -> Digest {
+        debug_assert!((1..=64).contains(&self.width));
+        Digest{crc: *self, register: self.init & self.mask()}
+    }
+
+    /// Calculates a CRC over `bits` bits of the given bytes,
+    /// starting `skip` bits from the start of the buffer.
+    /// Bits within each byte are taken in the given endianness's order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the range extends beyond the end of the buffer.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::BigEndian;
+    /// use bitstream_io::crc::Crc;
+    /// let data = b"123456789";
+    /// let crc = Crc::crc16_ccitt();
+    /// assert_eq!(crc.checksum::<BigEndian>(data, 0, 72).unwrap(), 0x29B1);
+    /// // the same nine bytes, but shifted 3 bits into the buffer
+    /// let shifted = [0x06, 0x26, 0x46, 0x66, 0x86, 0xA6, 0xC6, 0xE7, 0x07, 0x20];
+    /// assert_eq!(crc.checksum::<BigEndian>(&shifted, 3, 72).unwrap(), 0x29B1);
+    /// ```
+    pub fn checksum<E: Endianness>(&self,
+                                   data: &[u8],
+                                   skip: u64,
+                                   bits: u64) -> Result<u64, io::Error> {
+        match skip.checked_add(bits) {
+            Some(end) if end <= (data.len() as u64) * 8 => {}
+            _ => {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                          "CRC range beyond end of data"));
+            }
+        }
+        let mut digest = self.digest();
+        let mut position = skip;
+        let end = skip + bits;
+        while position < end {
+            let offset = (position % 8) as u32;
+            let available = (8 - offset).min((end - position) as u32);
+            let mut queue: BitQueue<E, u8> =
+                BitQueue::from_value(data[(position / 8) as usize], 8);
+            if offset > 0 {
+                queue.drop(offset);
+            }
+            for _ in 0..available {
+                digest.update_bit(queue.pop(1) == 1);
+            }
+            position += u64::from(available);
+        }
+        Ok(digest.value())
+    }
+
+    #[inline]
+    fn mask(&self) -> u64 {
+        if self.width == 64 {u64::MAX} else {(1 << self.width) - 1}
+    }
+}
+
+/// A CRC calculation in progress.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Digest {
+    crc: Crc,
+    register: u64,
+}
+
+impl Digest {
+    /// Shifts a single bit into the CRC register.
+    #[inline]
+    pub fn update_bit(&mut self, bit: bool) {
+        let top = (self.register >> (self.crc.width - 1)) & 1 == 1;
+        self.register = (self.register << 1) & self.crc.mask();
+        if top != bit {
+            self.register ^= self.crc.poly;
+        }
+    }
+
+    /// Shifts the lowest `bits` bits of `value` into the CRC register
+    /// in the order they would appear in a stream of the given endianness.
+    pub fn update<E: Endianness>(&mut self, bits: u32, value: u64) {
+        debug_assert!(bits <= 64);
+        if bits == 0 {
+            return;
+        }
+        let mut queue: BitQueue<E, u64> = BitQueue::from_value(value, bits);
+        while !queue.is_empty() {
+            self.update_bit(queue.pop(1) == 1);
+        }
+    }
+
+    /// Returns the CRC of all bits processed so far.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::LittleEndian;
+    /// use bitstream_io::crc::Crc;
+    /// let mut digest = Crc::crc32().digest();
+    /// for byte in b"123456789".iter() {
+    ///     digest.update::<LittleEndian>(8, u64::from(*byte));
+    /// }
+    /// assert_eq!(digest.value(), 0xCBF43926);
+    /// ```
+    pub fn value(&self) -> u64 {
+        let register = if self.crc.reflect_out {
+            self.register.reverse_bits() >> (64 - self.crc.width)
+        } else {
+            self.register
+        };
+        (register ^ self.crc.xor_out) & self.crc.mask()
+    }
+}
+
+/// Reads values from a `BitReader` while calculating a CRC
+/// over every bit read.
+pub struct CrcReader<'r, E: 'r + Endianness> {
+    reader: &'r mut BitReader<E>,
+    digest: Digest,
+}
+
+impl<'r, E: Endianness> CrcReader<'r, E> {
+    /// Wraps a CrcReader around a `BitReader`
+    /// which calculates the given CRC.
+    pub fn new(reader: &'r mut BitReader<E>, crc: &Crc) -> CrcReader<'r, E> {
+        CrcReader{reader, digest: crc.digest()}
+    }
+
+    /// Reads a single bit.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn read_bit(&mut self) -> Result<bool, io::Error> {
+        let bit = self.reader.read_bit()?;
+        self.digest.update_bit(bit);
+        Ok(bit)
+    }
+
+    /// Reads an unsigned value of up to 64 bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Also returns an error if the number of bits is larger than 64.
+    pub fn read(&mut self, bits: u32) -> Result<u64, io::Error> {
+        let value = self.reader.read::<u64>(bits)?;
+        self.digest.update::<E>(bits, value);
+        Ok(value)
+    }
+
+    /// Fills the buffer with bytes read from the stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        self.reader.read_bytes(buf)?;
+        for byte in buf.iter() {
+            self.digest.update::<E>(8, u64::from(*byte));
+        }
+        Ok(())
+    }
+
+    /// Returns the CRC of all bits read so far.
+    #[inline]
+    pub fn checksum(&self) -> u64 {self.digest.value()}
+}
+
+/// Writes values to a `BitWriter` while calculating a CRC
+/// over every bit written.
+pub struct CrcWriter<'w, 'a: 'w, E: 'w + Endianness> {
+    writer: &'w mut BitWriter<'a, E>,
+    digest: Digest,
+}
+
+impl<'w, 'a: 'w, E: Endianness> CrcWriter<'w, 'a, E> {
+    /// Wraps a CrcWriter around a `BitWriter`
+    /// which calculates the given CRC.
+    pub fn new(writer: &'w mut BitWriter<'a, E>,
+               crc: &Crc) -> CrcWriter<'w, 'a, E> {
+        CrcWriter{writer, digest: crc.digest()}
+    }
+
+    /// Writes a single bit.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn write_bit(&mut self, bit: bool) -> Result<(), io::Error> {
+        self.writer.write_bit(bit)?;
+        self.digest.update_bit(bit);
+        Ok(())
+    }
+
+    /// Writes an unsigned value of up to 64 bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the input type is too small
+    /// to hold the given number of bits.
+    /// Returns an error if the value is too large
+    /// to fit the given number of bits.
+    pub fn write(&mut self, bits: u32, value: u64) -> Result<(), io::Error> {
+        self.writer.write(bits, value)?;
+        self.digest.update::<E>(bits, value);
+        Ok(())
+    }
+
+    /// Writes the entirety of a byte buffer to the stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn write_bytes(&mut self, buf: &[u8]) -> Result<(), io::Error> {
+        self.writer.write_bytes(buf)?;
+        for byte in buf {
+            self.digest.update::<E>(8, u64::from(*byte));
+        }
+        Ok(())
+    }
+
+    /// Returns the CRC of all bits written so far.
+    #[inline]
+    pub fn checksum(&self) -> u64 {self.digest.value()}
+}
@@ -0,0 +1,248 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Adaptors for reading or writing predictively-coded integer sequences.
+//!
+//! Rather than storing each value of a sequence directly,
+//! these adaptors store the difference between each value and a
+//! prediction made from the values preceding it.
+//! That residual is then stored as either a fixed-width
+//! twos-complement value or as a Rice code.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Write, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::delta::{DeltaReader, DeltaWriter, Order, Residual};
+//!
+//! let samples = [10, 12, 14, 17, 20, 22, 23, 23];
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     {
+//!         let mut delta = DeltaWriter::new(&mut writer,
+//!                                          Order::First,
+//!                                          Residual::Rice(2));
+//!         for sample in samples.iter() {
+//!             delta.write(*sample).unwrap();
+//!         }
+//!     }
+//!     writer.byte_align().unwrap();
+//! }
+//! assert_eq!(data.len(), 5);
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! let mut delta = DeltaReader::new(&mut reader,
+//!                                  Order::First,
+//!                                  Residual::Rice(2));
+//! for sample in samples.iter() {
+//!     assert_eq!(delta.read().unwrap(), *sample);
+//! }
+//! ```
+
+#![warn(missing_docs)]
+
+use io;
+
+use super::{BitReader, BitWriter, Endianness};
+use zigzag;
+
+/// The order of prediction applied to a sequence.
+///
+/// Until enough values have been processed for the given order,
+/// lower orders are used instead,
+/// starting from a prediction of 0 for the very first value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Order {
+    /// Each value is predicted to be the same as the previous value
+    First,
+    /// Each value is predicted to continue the slope
+    /// of the two previous values
+    Second,
+}
+
+/// How residuals between values and their predictions are stored.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Residual {
+    /// A twos-complement signed value of the given number of bits
+    Fixed(u32),
+    /// A Rice code with the given parameter, whose folded quotient
+    /// is stored as 0 bits terminated by a 1 bit
+    Rice(u32),
+}
+
+// The history of a predictively-coded sequence
+struct Predictor {
+    order: Order,
+    history: [i64; 2],
+    count: usize,
+}
+
+impl Predictor {
+    fn new(order: Order) -> Predictor {
+        Predictor{order, history: [0, 0], count: 0}
+    }
+
+    fn predict(&self) -> i64 {
+        match (self.order, self.count) {
+            (_, 0) => 0,
+            (Order::First, _) | (Order::Second, 1) => self.history[0],
+            (Order::Second, _) => {
+                self.history[0].wrapping_mul(2).wrapping_sub(self.history[1])
+            }
+        }
+    }
+
+    fn update(&mut self, value: i64) {
+        self.history[1] = self.history[0];
+        self.history[0] = value;
+        self.count = self.count.saturating_add(1);
+    }
+}
+
+/// Writes integers to a `BitWriter` as residuals from their predictions.
+pub struct DeltaWriter<'w, 'a: 'w, E: 'w + Endianness> {
+    writer: &'w mut BitWriter<'a, E>,
+    residual: Residual,
+    predictor: Predictor,
+}
+
+impl<'w, 'a: 'w, E: Endianness> DeltaWriter<'w, 'a, E> {
+    /// Wraps a DeltaWriter around a `BitWriter`
+    /// using the given prediction order and residual format.
+    pub fn new(writer: &'w mut BitWriter<'a, E>,
+               order: Order,
+               residual: Residual) -> DeltaWriter<'w, 'a, E> {
+        DeltaWriter{writer, residual, predictor: Predictor::new(order)}
+    }
+
+    /// Writes the next value of the sequence.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if a fixed-width residual is too large
+    /// to fit its number of bits,
+    /// or if a Rice code's quotient is too large.
+    pub fn write(&mut self, value: i64) -> Result<(), io::Error> {
+        let residual = value.wrapping_sub(self.predictor.predict());
+        match self.residual {
+            Residual::Fixed(bits) => {
+                write_fixed(self.writer, bits, residual)?;
+            }
+            Residual::Rice(k) => {
+                write_rice(self.writer, k, residual)?;
+            }
+        }
+        self.predictor.update(value);
+        Ok(())
+    }
+}
+
+/// Reads integers from a `BitReader` stored as residuals
+/// from their predictions.
+pub struct DeltaReader<'r, E: 'r + Endianness> {
+    reader: &'r mut BitReader<E>,
+    residual: Residual,
+    predictor: Predictor,
+}
+
+impl<'r, E: Endianness> DeltaReader<'r, E> {
+    /// Wraps a DeltaReader around a `BitReader`
+    /// using the given prediction order and residual format,
+    /// which must match those used to write the sequence.
+    pub fn new(reader: &'r mut BitReader<E>,
+               order: Order,
+               residual: Residual) -> DeltaReader<'r, E> {
+        DeltaReader{reader, residual, predictor: Predictor::new(order)}
+    }
+
+    /// Reads the next value of the sequence.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if a fixed-width residual's number of bits
+    /// is 0 or larger than 64,
+    /// or if a Rice code's value is too large.
+    pub fn read(&mut self) -> Result<i64, io::Error> {
+        let residual = match self.residual {
+            Residual::Fixed(bits) => read_fixed(self.reader, bits)?,
+            Residual::Rice(k) => read_rice(self.reader, k)?,
+        };
+        let value = self.predictor.predict().wrapping_add(residual);
+        self.predictor.update(value);
+        Ok(value)
+    }
+}
+
+fn write_fixed<E: Endianness>(writer: &mut BitWriter<E>,
+                              bits: u32,
+                              residual: i64) -> Result<(), io::Error> {
+    if (bits == 0) || (bits > 64) {
+        Err(io::Error::new(io::ErrorKind::InvalidInput,
+                           "excessive bits for type written"))
+    } else if (bits < 64) &&
+        ((residual < (-1 << (bits - 1))) || (residual >= (1 << (bits - 1)))) {
+        Err(io::Error::new(io::ErrorKind::InvalidInput,
+                           "excessive value for bits written"))
+    } else if bits < 64 {
+        writer.write(bits, (residual as u64) & ((1 << bits) - 1))
+    } else {
+        writer.write(bits, residual as u64)
+    }
+}
+
+fn read_fixed<E: Endianness>(reader: &mut BitReader<E>,
+                             bits: u32) -> Result<i64, io::Error> {
+    if (bits == 0) || (bits > 64) {
+        Err(io::Error::new(io::ErrorKind::InvalidInput,
+                           "excessive bits for type read"))
+    } else {
+        let unsigned = reader.read::<u64>(bits)?;
+        Ok(((unsigned << (64 - bits)) as i64) >> (64 - bits))
+    }
+}
+
+fn write_rice<E: Endianness>(writer: &mut BitWriter<E>,
+                             k: u32,
+                             residual: i64) -> Result<(), io::Error> {
+    if k > 63 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "excessive Rice parameter"));
+    }
+    let folded = zigzag::encode(64, residual);
+    let quotient = folded >> k;
+    if quotient > u64::from(u32::MAX) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "excessive Rice quotient"));
+    }
+    writer.write_unary1(quotient as u32)?;
+    if k > 0 {
+        writer.write(k, folded & ((1 << k) - 1))
+    } else {
+        Ok(())
+    }
+}
+
+fn read_rice<E: Endianness>(reader: &mut BitReader<E>,
+                            k: u32) -> Result<i64, io::Error> {
+    if k > 63 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "excessive Rice parameter"));
+    }
+    let quotient = u64::from(reader.read_unary1()?);
+    if (k > 0) && (quotient >> (64 - k) != 0) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                  "excessive Rice value"));
+    }
+    let remainder = if k > 0 {reader.read::<u64>(k)?} else {0};
+    let folded = (quotient << k) | remainder;
+    Ok(zigzag::decode(64, folded))
+}
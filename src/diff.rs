@@ -0,0 +1,168 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Bit-level differences between two streams.
+//!
+//! `diff` compares an old stream against a new one
+//! and returns a `Patch` listing every run of differing bits,
+//! numbered in the order a `BitReader` of the same endianness
+//! would read them.
+//! Whereas `test_util::bit_diff` only describes
+//! the first difference, a patch holds all of them,
+//! which makes it useful for checking exactly which fields
+//! of a codec's output changed between versions.
+//!
+//! Since each run lists bits which differ, applying a patch
+//! flips those bits, turning a copy of the old stream
+//! into the new one.
+//! This makes patches a compact way to update
+//! packed configuration blobs in place.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::BigEndian;
+//! use bitstream_io::diff::diff;
+//!
+//! let old = [0b1011_0000, 0x12, 0x34];
+//! let new = [0b1000_0000, 0x12, 0x35];
+//! let patch = diff::<BigEndian>(&old, &new);
+//! assert_eq!(patch.ranges(), &[2..4, 23..24]);
+//!
+//! let mut data = old;
+//! patch.apply(&mut data).unwrap();
+//! assert_eq!(data, new);
+//! ```
+
+#![warn(missing_docs)]
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ops::Range;
+use io;
+
+use super::{is_little_endian, Endianness};
+
+/// The runs of bits which differ between two streams
+/// of the given endianness.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Patch<E: Endianness> {
+    phantom: PhantomData<E>,
+    len: usize,
+    ranges: Vec<Range<u64>>,
+}
+
+/// Compares an old stream to a new one,
+/// returning a patch of the bits which differ.
+///
+/// The patch is for streams as long as the new one.
+/// Any bytes past the end of the old stream are taken to be 0,
+/// so a shorter old stream should be padded with 0s,
+/// and a longer one truncated, before the patch is applied.
+///
+/// # Example
+/// ```
+/// use bitstream_io::LittleEndian;
+/// use bitstream_io::diff::diff;
+///
+/// // little-endian streams number bits from each byte's lowest
+/// let patch = diff::<LittleEndian>(&[0b1011_0000], &[0b1000_0001, 0xFF]);
+/// assert_eq!(patch.ranges(), &[0..1, 4..6, 8..16]);
+/// assert_eq!(patch.differing_bits(), 11);
+/// assert_eq!(patch.target_len(), 2);
+/// ```
+pub fn diff<E: Endianness>(old: &[u8], new: &[u8]) -> Patch<E> {
+    let mut ranges: Vec<Range<u64>> = Vec::new();
+    for (i, &byte) in new.iter().enumerate() {
+        let changed = old.get(i).map_or(0, |&old| old) ^ byte;
+        if changed == 0 {
+            continue;
+        }
+        // the changed bits, in the order they're read
+        let changed = if is_little_endian::<E>() {
+            changed.reverse_bits()
+        } else {
+            changed
+        };
+        for bit in 0..8 {
+            if changed & (0x80 >> bit) != 0 {
+                let position = i as u64 * 8 + bit;
+                match ranges.last_mut() {
+                    Some(range) if range.end == position => {
+                        range.end += 1;
+                    }
+                    _ => ranges.push(position..position + 1),
+                }
+            }
+        }
+    }
+    Patch{phantom: PhantomData, len: new.len(), ranges}
+}
+
+impl<E: Endianness> Patch<E> {
+    /// Returns the runs of differing bits, in stream order,
+    /// each starting and ending at a bit position
+    /// counted from the start of the stream.
+    #[inline]
+    pub fn ranges(&self) -> &[Range<u64>] {&self.ranges}
+
+    /// Returns the length in bytes of the stream
+    /// the patch is applied to.
+    #[inline]
+    pub fn target_len(&self) -> usize {self.len}
+
+    /// Returns true if the streams compared were identical.
+    #[inline]
+    pub fn is_empty(&self) -> bool {self.ranges.is_empty()}
+
+    /// Returns the total number of bits which differ.
+    pub fn differing_bits(&self) -> u64 {
+        self.ranges.iter().map(|range| range.end - range.start).sum()
+    }
+
+    /// Flips every differing bit of the given buffer,
+    /// turning the old stream into the new one or,
+    /// since patches work either way, the new stream into the old one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidInput` error if the buffer's length
+    /// doesn't match the patch's target length,
+    /// in which case the buffer is left unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::BigEndian;
+    /// use bitstream_io::diff::diff;
+    ///
+    /// let patch = diff::<BigEndian>(&[0x00, 0x00], &[0x0F, 0xF0]);
+    /// assert_eq!(patch.ranges(), &[4..12]);
+    ///
+    /// let mut data = [0x0F, 0xF0];
+    /// patch.apply(&mut data).unwrap();
+    /// assert_eq!(data, [0x00, 0x00]);
+    /// assert!(patch.apply(&mut [0x00]).is_err());
+    /// ```
+    pub fn apply(&self, buf: &mut [u8]) -> Result<(), io::Error> {
+        if buf.len() != self.len {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "buffer length doesn't match patch"));
+        }
+        for range in self.ranges.iter() {
+            for position in range.clone() {
+                let bit = (position % 8) as u32;
+                buf[(position / 8) as usize] ^= if is_little_endian::<E>() {
+                    1 << bit
+                } else {
+                    0x80 >> bit
+                };
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,405 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Elias-Fano coding of monotone integer sequences.
+//!
+//! An Elias-Fano coded sequence of `n` non-decreasing values,
+//! all less than some universe `u`, splits each value into
+//! `l` = floor(log2(`u` / `n`)) low bits and the remaining high bits.
+//! All the low bits are stored first, `l` bits per value.
+//! The high bits follow, each stored as the difference from the
+//! previous value's high bits in unary as 0 bits terminated by a 1 bit.
+//! This takes at most 2 + log2(`u` / `n`) bits per value.
+//!
+//! Neither the number of values nor the universe are stored
+//! in the stream, so formats must store them elsewhere as needed.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Write, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::elias_fano::EliasFano;
+//!
+//! let postings = EliasFano::from_values(
+//!     100, &[3, 4, 7, 13, 14, 15, 21, 43]).unwrap();
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     postings.write(&mut writer).unwrap();
+//!     writer.byte_align().unwrap();
+//! }
+//! assert_eq!(data.len(), 5);
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! let postings = EliasFano::read(&mut reader, 100, 8).unwrap();
+//! assert_eq!(postings.get(3), Some(13));
+//! assert_eq!(postings.rank(14), 4);
+//! assert_eq!(postings.iter().collect::<Vec<u64>>(),
+//!            vec![3, 4, 7, 13, 14, 15, 21, 43]);
+//! ```
+
+#![warn(missing_docs)]
+
+use alloc::vec::Vec;
+use io;
+
+use super::{BitReader, BitWriter, Endianness};
+
+/// An Elias-Fano coded sequence of non-decreasing values,
+/// supporting random access by index and by rank.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EliasFano {
+    universe: u64,
+    len: usize,
+    low_bits: u32,
+    low: Vec<u64>,
+    high: Vec<u64>,
+    // the number of 1 bits in all high words before each word
+    ones_before: Vec<usize>,
+}
+
+impl EliasFano {
+    /// Builds a sequence from non-decreasing values
+    /// which must all be less than `universe`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the values are not in non-decreasing order
+    /// or if any value is not less than `universe`.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::elias_fano::EliasFano;
+    /// assert!(EliasFano::from_values(10, &[1, 2, 3]).is_ok());
+    /// assert!(EliasFano::from_values(10, &[3, 2, 1]).is_err());
+    /// assert!(EliasFano::from_values(10, &[1, 2, 10]).is_err());
+    /// ```
+    pub fn from_values(universe: u64, values: &[u64]) ->
+        Result<EliasFano, io::Error> {
+
+        let mut builder = Builder::new(universe, values.len());
+        let mut previous = 0;
+        for value in values {
+            if (*value < previous) || (*value >= universe) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          "invalid Elias-Fano sequence"));
+            }
+            builder.push_low(*value & builder.low_mask());
+            builder.push_high(*value >> builder.low_bits);
+            previous = *value;
+        }
+        Ok(builder.build())
+    }
+
+    /// Reads a sequence of `len` values, all less than `universe`,
+    /// from the given reader.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if a value read is not less than `universe`
+    /// or if the values read are not in non-decreasing order.
+    pub fn read<E: Endianness>(reader: &mut BitReader<E>,
+                               universe: u64,
+                               len: usize) -> Result<EliasFano, io::Error> {
+        let mut builder = Builder::new(universe, len);
+        for _ in 0..len {
+            let low = if builder.low_bits > 0 {
+                reader.read::<u64>(builder.low_bits)?
+            } else {
+                0
+            };
+            builder.push_low(low);
+        }
+        let max_high = if universe > 0 {(universe - 1) >> builder.low_bits}
+                       else {0};
+        let mut high = 0u64;
+        for _ in 0..len {
+            high = high.saturating_add(u64::from(reader.read_unary1()?));
+            if high > max_high {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          "invalid Elias-Fano sequence"));
+            }
+            builder.push_high(high);
+        }
+        let sequence = builder.build();
+        // values sharing their high bits may still have low bits
+        // which go down, so the whole sequence is checked
+        let mut previous = 0;
+        for value in sequence.iter() {
+            if (value < previous) || (value >= universe) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          "invalid Elias-Fano sequence"));
+            }
+            previous = value;
+        }
+        Ok(sequence)
+    }
+
+    /// Writes the sequence to the given writer.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn write<E: Endianness>(&self, writer: &mut BitWriter<E>) ->
+        Result<(), io::Error> {
+
+        if self.low_bits > 0 {
+            for index in 0..self.len {
+                writer.write(self.low_bits, self.low(index))?;
+            }
+        }
+        let mut previous = 0;
+        for value in self.iter() {
+            let high = value >> self.low_bits;
+            let difference = high - previous;
+            if difference > u64::from(u32::MAX) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          "excessive Elias-Fano difference"));
+            }
+            writer.write_unary1(difference as u32)?;
+            previous = high;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of values in the sequence
+    #[inline]
+    pub fn len(&self) -> usize {self.len}
+
+    /// Returns true if the sequence has no values
+    #[inline]
+    pub fn is_empty(&self) -> bool {self.len == 0}
+
+    /// Returns the universe all values are less than
+    #[inline]
+    pub fn universe(&self) -> u64 {self.universe}
+
+    /// Returns the value at the given index, if any
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::elias_fano::EliasFano;
+    /// let sequence = EliasFano::from_values(1000, &[5, 50, 500]).unwrap();
+    /// assert_eq!(sequence.get(0), Some(5));
+    /// assert_eq!(sequence.get(2), Some(500));
+    /// assert_eq!(sequence.get(3), None);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<u64> {
+        if index < self.len {
+            let position = self.select1(index);
+            let high = (position - index) as u64;
+            Some((high << self.low_bits) | self.low(index))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of values in the sequence
+    /// which are less than the given value.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::elias_fano::EliasFano;
+    /// let sequence = EliasFano::from_values(1000, &[5, 50, 50, 500]).unwrap();
+    /// assert_eq!(sequence.rank(0), 0);
+    /// assert_eq!(sequence.rank(6), 1);
+    /// assert_eq!(sequence.rank(50), 1);
+    /// assert_eq!(sequence.rank(51), 3);
+    /// assert_eq!(sequence.rank(1000), 4);
+    /// ```
+    pub fn rank(&self, value: u64) -> usize {
+        if value >= self.universe {
+            return self.len;
+        }
+        let high = value >> self.low_bits;
+        let low = value & low_mask(self.low_bits);
+
+        // skip past all values whose high bits are smaller
+        let mut position = if high == 0 {
+            0
+        } else {
+            match self.select0((high - 1) as usize) {
+                Some(position) => position + 1,
+                None => return self.len,
+            }
+        };
+        let mut index = position - high as usize;
+
+        // then past values with the same high bits but smaller low bits
+        while (index < self.len) && self.high_bit(position) &&
+            (self.low(index) < low) {
+            index += 1;
+            position += 1;
+        }
+        index
+    }
+
+    /// Returns an iterator over all the values in the sequence
+    pub fn iter(&self) -> Iter<'_> {
+        Iter{sequence: self, index: 0, position: 0}
+    }
+
+    fn low(&self, index: usize) -> u64 {
+        if self.low_bits == 0 {
+            return 0;
+        }
+        let bit = index * self.low_bits as usize;
+        let (word, offset) = (bit / 64, (bit % 64) as u32);
+        let mut value = self.low[word] >> offset;
+        if offset + self.low_bits > 64 {
+            value |= self.low[word + 1] << (64 - offset);
+        }
+        value & low_mask(self.low_bits)
+    }
+
+    #[inline]
+    fn high_bit(&self, position: usize) -> bool {
+        (self.high[position / 64] >> (position % 64)) & 1 == 1
+    }
+
+    // returns the position of the given 1 bit, which must exist
+    fn select1(&self, index: usize) -> usize {
+        let word = self.ones_before.partition_point(|o| *o <= index) - 1;
+        word * 64 + select_in_word(self.high[word],
+                                   (index - self.ones_before[word]) as u32)
+    }
+
+    // returns the position of the given 0 bit, if any
+    fn select0(&self, index: usize) -> Option<usize> {
+        if index >= self.high.len() * 64 - self.len {
+            return None;
+        }
+        let zeros_before = |w: usize| w * 64 - self.ones_before[w];
+        let (mut word, mut end) = (0, self.high.len());
+        while end - word > 1 {
+            let middle = (word + end) / 2;
+            if zeros_before(middle) <= index {word = middle} else {end = middle}
+        }
+        Some(word * 64 + select_in_word(!self.high[word],
+                                        (index - zeros_before(word)) as u32))
+    }
+}
+
+/// An iterator over the values of an Elias-Fano coded sequence
+pub struct Iter<'a> {
+    sequence: &'a EliasFano,
+    index: usize,
+    position: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.index < self.sequence.len {
+            while !self.sequence.high_bit(self.position) {
+                self.position += 1;
+            }
+            let high = (self.position - self.index) as u64;
+            let value = (high << self.sequence.low_bits) |
+                self.sequence.low(self.index);
+            self.index += 1;
+            self.position += 1;
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.sequence.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+// Accumulates low and high bits for a sequence
+struct Builder {
+    universe: u64,
+    len: usize,
+    low_bits: u32,
+    low: Vec<u64>,
+    low_len: usize,
+    high: Vec<u64>,
+    high_count: usize,
+}
+
+impl Builder {
+    fn new(universe: u64, len: usize) -> Builder {
+        let low_bits = if (len > 0) && (universe / len as u64 > 1) {
+            63 - (universe / len as u64).leading_zeros()
+        } else {
+            0
+        };
+        Builder{universe,
+                len,
+                low_bits,
+                low: Vec::with_capacity((len * low_bits as usize).div_ceil(64)),
+                low_len: 0,
+                high: Vec::new(),
+                high_count: 0}
+    }
+
+    #[inline]
+    fn low_mask(&self) -> u64 {low_mask(self.low_bits)}
+
+    fn push_low(&mut self, value: u64) {
+        if self.low_bits == 0 {
+            return;
+        }
+        let offset = (self.low_len % 64) as u32;
+        if offset == 0 {
+            self.low.push(value);
+        } else {
+            *self.low.last_mut().unwrap() |= value << offset;
+            if offset + self.low_bits > 64 {
+                self.low.push(value >> (64 - offset));
+            }
+        }
+        self.low_len += self.low_bits as usize;
+    }
+
+    fn push_high(&mut self, high: u64) {
+        let position = high as usize + self.high_count;
+        while self.high.len() <= position / 64 {
+            self.high.push(0);
+        }
+        self.high[position / 64] |= 1 << (position % 64);
+        self.high_count += 1;
+    }
+
+    fn build(self) -> EliasFano {
+        let mut ones_before = Vec::with_capacity(self.high.len());
+        let mut ones = 0;
+        for word in self.high.iter() {
+            ones_before.push(ones);
+            ones += word.count_ones() as usize;
+        }
+        EliasFano{universe: self.universe,
+                  len: self.len,
+                  low_bits: self.low_bits,
+                  low: self.low,
+                  high: self.high,
+                  ones_before}
+    }
+}
+
+#[inline]
+fn low_mask(bits: u32) -> u64 {
+    if bits < 64 {(1 << bits) - 1} else {!0}
+}
+
+// returns the position of the given 1 bit within a word
+#[inline]
+fn select_in_word(mut word: u64, index: u32) -> usize {
+    for _ in 0..index {
+        word &= word - 1;
+    }
+    word.trailing_zeros() as usize
+}
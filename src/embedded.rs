@@ -0,0 +1,130 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Adaptors between the `embedded-io` crate's traits and bit streams.
+//!
+//! This module requires the `embedded-io` feature,
+//! which doesn't need the standard library or an allocator.
+//!
+//! `EmbeddedSource` reads from any `embedded_io::Read`
+//! and `EmbeddedSink` writes to any `embedded_io::Write`,
+//! so that bits may be streamed directly to and from drivers
+//! such as UARTs and SPI buses rather than through buffers in RAM.
+//! Errors from the driver become I/O errors of the nearest kind.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::embedded::EmbeddedSink;
+//!
+//! let mut frame = [0; 2];
+//! {
+//!     let mut sink = EmbeddedSink::new(&mut frame[..]);
+//!     let mut writer = BitWriter::<BigEndian, _>::from_write(&mut sink);
+//!     writer.write(4, 0xAu8).unwrap();
+//!     writer.write(12, 0x123u16).unwrap();
+//! }
+//! assert_eq!(frame, [0xA1, 0x23]);
+//!
+//! let mut reader = BitReader::<BigEndian, _>::from_embedded(&frame[..]);
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0xA);
+//! assert_eq!(reader.read::<u16>(12).unwrap(), 0x123);
+//! ```
+
+#![warn(missing_docs)]
+
+use embedded_io::{self, Error as EmbeddedError};
+
+use io;
+use super::{BitReader, Endianness};
+
+/// Reads bytes from an `embedded_io::Read` source.
+pub struct EmbeddedSource<T: embedded_io::Read> {
+    inner: T,
+}
+
+impl<T: embedded_io::Read> EmbeddedSource<T> {
+    /// Wraps the source.
+    #[inline]
+    pub fn new(inner: T) -> EmbeddedSource<T> {
+        EmbeddedSource{inner}
+    }
+
+    /// Returns a reference to the source.
+    #[inline]
+    pub fn get_ref(&self) -> &T {&self.inner}
+
+    /// Returns a mutable reference to the source.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {&mut self.inner}
+
+    /// Returns the source.
+    #[inline]
+    pub fn into_inner(self) -> T {self.inner}
+}
+
+impl<T: embedded_io::Read> io::Read for EmbeddedSource<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf).map_err(convert_error)
+    }
+}
+
+/// Writes bytes to an `embedded_io::Write` sink.
+pub struct EmbeddedSink<T: embedded_io::Write> {
+    inner: T,
+}
+
+impl<T: embedded_io::Write> EmbeddedSink<T> {
+    /// Wraps the sink.
+    #[inline]
+    pub fn new(inner: T) -> EmbeddedSink<T> {
+        EmbeddedSink{inner}
+    }
+
+    /// Returns a reference to the sink.
+    #[inline]
+    pub fn get_ref(&self) -> &T {&self.inner}
+
+    /// Returns a mutable reference to the sink.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {&mut self.inner}
+
+    /// Returns the sink.
+    #[inline]
+    pub fn into_inner(self) -> T {self.inner}
+}
+
+impl<T: embedded_io::Write> io::Write for EmbeddedSink<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf).map_err(convert_error)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush().map_err(convert_error)
+    }
+}
+
+impl<E: Endianness, T: embedded_io::Read> BitReader<E, EmbeddedSource<T>> {
+    /// Wraps a BitReader around an `embedded_io::Read` source,
+    /// such as a serial port driver.
+    pub fn from_embedded(inner: T) -> BitReader<E, EmbeddedSource<T>> {
+        BitReader::from_read(EmbeddedSource::new(inner))
+    }
+}
+
+// converts a driver's error to an I/O error of the nearest kind
+fn convert_error<X: EmbeddedError>(err: X) -> io::Error {
+    io::Error::from(match err.kind() {
+        embedded_io::ErrorKind::InvalidInput => io::ErrorKind::InvalidInput,
+        embedded_io::ErrorKind::InvalidData => io::ErrorKind::InvalidData,
+        embedded_io::ErrorKind::Interrupted => io::ErrorKind::Interrupted,
+        embedded_io::ErrorKind::WriteZero => io::ErrorKind::WriteZero,
+        _ => io::ErrorKind::Other,
+    })
+}
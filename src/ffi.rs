@@ -0,0 +1,246 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A C interface to bitstream readers.
+//!
+//! This module requires the `ffi` feature.
+//! The crate may then be built as a C library with
+//! `cargo rustc --release --features ffi --crate-type cdylib`
+//! (or `staticlib`), and used through the declarations
+//! in `include/bitstream_io.h`.
+//!
+//! Readers are created over a copy of a caller's buffer,
+//! so the buffer may be freed as soon as the reader is created.
+//! Every reading function returns one of the `BITSTREAM_` status
+//! codes and stores any value read through its output pointer,
+//! which is left untouched on error.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::ffi::*;
+//!
+//! let data = [0b1011_0111, 0b1000_0001];
+//! unsafe {
+//!     let reader = bitstream_reader_new(data.as_ptr(), data.len(), 0);
+//!     let mut value = 0;
+//!     assert_eq!(bitstream_reader_read(reader, 4, &mut value), BITSTREAM_OK);
+//!     assert_eq!(value, 0b1011);
+//!     let mut signed = 0;
+//!     assert_eq!(bitstream_reader_read_signed(reader, 4, &mut signed),
+//!                BITSTREAM_OK);
+//!     assert_eq!(signed, 7);
+//!     let mut unary = 0;
+//!     assert_eq!(bitstream_reader_read_unary(reader, 1, &mut unary),
+//!                BITSTREAM_OK);
+//!     assert_eq!(unary, 0);
+//!     assert_eq!(bitstream_reader_read(reader, 8, &mut value), BITSTREAM_EOF);
+//!     bitstream_reader_free(reader);
+//! }
+//! ```
+
+#![warn(missing_docs)]
+
+use std::io::{self, Cursor};
+use std::os::raw::c_int;
+use std::ptr;
+use std::slice;
+
+use super::{BigEndian, LittleEndian, BitReader};
+
+/// The operation succeeded
+pub const BITSTREAM_OK: c_int = 0;
+/// The stream ran out of data
+pub const BITSTREAM_EOF: c_int = -1;
+/// An argument, such as a bit count, was out of range
+pub const BITSTREAM_INVALID_INPUT: c_int = -2;
+/// The stream's data was invalid
+pub const BITSTREAM_INVALID_DATA: c_int = -3;
+/// A required pointer was null
+pub const BITSTREAM_NULL_POINTER: c_int = -4;
+/// Any other error
+pub const BITSTREAM_ERROR: c_int = -5;
+
+enum Inner {
+    Big(BitReader<BigEndian, Cursor<Vec<u8>>>),
+    Little(BitReader<LittleEndian, Cursor<Vec<u8>>>),
+}
+
+/// An opaque bitstream reader handle.
+pub struct BitstreamReader {
+    inner: Inner,
+}
+
+macro_rules! with_reader {
+    ($reader:expr, $r:ident => $body:expr) => {
+        match $reader.inner {
+            Inner::Big(ref mut $r) => $body,
+            Inner::Little(ref mut $r) => $body,
+        }
+    }
+}
+
+fn status(err: io::Error) -> c_int {
+    match err.kind() {
+        io::ErrorKind::UnexpectedEof => BITSTREAM_EOF,
+        io::ErrorKind::InvalidInput => BITSTREAM_INVALID_INPUT,
+        io::ErrorKind::InvalidData => BITSTREAM_INVALID_DATA,
+        _ => BITSTREAM_ERROR,
+    }
+}
+
+unsafe fn store<T>(result: Result<T, io::Error>, out: *mut T) -> c_int {
+    match result {
+        Ok(value) => {
+            *out = value;
+            BITSTREAM_OK
+        }
+        Err(err) => status(err),
+    }
+}
+
+/// Creates a reader over a copy of the given buffer.
+/// The stream is little-endian if `little_endian` is nonzero,
+/// and big-endian otherwise.
+///
+/// Returns null if `data` is null and `len` is nonzero.
+///
+/// # Safety
+///
+/// `data` must point to at least `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_new(data: *const u8,
+                                              len: usize,
+                                              little_endian: c_int) ->
+    *mut BitstreamReader {
+    let bytes = if len == 0 {
+        Vec::new()
+    } else if data.is_null() {
+        return ptr::null_mut();
+    } else {
+        slice::from_raw_parts(data, len).to_vec()
+    };
+    let inner = if little_endian != 0 {
+        Inner::Little(BitReader::from_read(Cursor::new(bytes)))
+    } else {
+        Inner::Big(BitReader::from_read(Cursor::new(bytes)))
+    };
+    Box::into_raw(Box::new(BitstreamReader{inner}))
+}
+
+/// Reads an unsigned value of up to 64 bits.
+///
+/// # Safety
+///
+/// `reader` must be null or a live reader from `bitstream_reader_new`,
+/// and `value` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_read(reader: *mut BitstreamReader,
+                                               bits: u32,
+                                               value: *mut u64) -> c_int {
+    if reader.is_null() || value.is_null() {
+        return BITSTREAM_NULL_POINTER;
+    }
+    store(with_reader!(*reader, r => r.read(bits)), value)
+}
+
+/// Reads a twos-complement signed value of up to 64 bits.
+///
+/// # Safety
+///
+/// `reader` must be null or a live reader from `bitstream_reader_new`,
+/// and `value` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_read_signed(
+    reader: *mut BitstreamReader, bits: u32, value: *mut i64) -> c_int {
+    if reader.is_null() || value.is_null() {
+        return BITSTREAM_NULL_POINTER;
+    }
+    store(with_reader!(*reader, r => r.read_signed(bits)), value)
+}
+
+/// Reads a unary value, counting bits until `stop_bit` is read.
+/// `stop_bit` is 0 or 1.
+///
+/// # Safety
+///
+/// `reader` must be null or a live reader from `bitstream_reader_new`,
+/// and `value` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_read_unary(
+    reader: *mut BitstreamReader, stop_bit: c_int, value: *mut u32) -> c_int {
+    if reader.is_null() || value.is_null() {
+        return BITSTREAM_NULL_POINTER;
+    }
+    let result = match stop_bit {
+        0 => with_reader!(*reader, r => r.read_unary0()),
+        1 => with_reader!(*reader, r => r.read_unary1()),
+        _ => return BITSTREAM_INVALID_INPUT,
+    };
+    store(result, value)
+}
+
+/// Skips the given number of bits.
+///
+/// # Safety
+///
+/// `reader` must be null or a live reader from `bitstream_reader_new`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_skip(reader: *mut BitstreamReader,
+                                               bits: u32) -> c_int {
+    if reader.is_null() {
+        return BITSTREAM_NULL_POINTER;
+    }
+    match with_reader!(*reader, r => r.skip(bits)) {
+        Ok(()) => BITSTREAM_OK,
+        Err(err) => status(err),
+    }
+}
+
+/// Discards any bits up to the next whole byte.
+///
+/// # Safety
+///
+/// `reader` must be null or a live reader from `bitstream_reader_new`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_byte_align(
+    reader: *mut BitstreamReader) {
+    if !reader.is_null() {
+        with_reader!(*reader, r => r.byte_align())
+    }
+}
+
+/// Returns the number of bits read so far, or 0 if `reader` is null.
+///
+/// # Safety
+///
+/// `reader` must be null or a live reader from `bitstream_reader_new`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_position(
+    reader: *const BitstreamReader) -> u64 {
+    if reader.is_null() {
+        return 0;
+    }
+    match (*reader).inner {
+        Inner::Big(ref r) => r.position_in_bits(),
+        Inner::Little(ref r) => r.position_in_bits(),
+    }
+}
+
+/// Destroys a reader.  Does nothing if `reader` is null.
+///
+/// # Safety
+///
+/// `reader` must be null or a live reader from `bitstream_reader_new`,
+/// and must not be used again afterward.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_free(reader: *mut BitstreamReader) {
+    if !reader.is_null() {
+        drop(Box::from_raw(reader));
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reading and writing flag fields defined with the bitflags crate.
+//!
+//! This module requires the `bitflags` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! #[macro_use]
+//! extern crate bitflags;
+//! # extern crate bitstream_io;
+//! use std::io::{Read, Write, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::flags::UnknownBits;
+//!
+//! bitflags! {
+//!     #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+//!     struct Access: u8 {
+//!         const READ = 0b100;
+//!         const WRITE = 0b010;
+//!         const EXECUTE = 0b001;
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     writer.write_flags(3, Access::READ | Access::WRITE,
+//!                        UnknownBits::Error).unwrap();
+//!     writer.write(5, 0b1_1111u8).unwrap();
+//! }
+//! assert_eq!(data, [0b1101_1111]);
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! assert_eq!(reader.read_flags::<Access>(3, UnknownBits::Error).unwrap(),
+//!            Access::READ | Access::WRITE);
+//! assert!(reader.read_flags::<Access>(5, UnknownBits::Error).is_err());
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+use io;
+
+use bitflags::Flags;
+
+use super::{BitReader, BitWriter, Endianness, Numeric};
+
+/// How to handle bits which don't correspond to any defined flag.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UnknownBits {
+    /// Unknown bits are an error
+    Error,
+    /// Unknown bits are discarded
+    Truncate,
+    /// Unknown bits are kept as-is
+    Preserve,
+}
+
+impl<E: Endianness, R: io::Read> BitReader<E, R> {
+    /// Reads a flag field of the given number of bits,
+    /// handling any unknown bits according to the given policy.
+    ///
+    /// Requires the `bitflags` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the number of bits is larger than the
+    /// flags' underlying type, or an `InvalidData` error
+    /// if the policy is `UnknownBits::Error` and unknown bits are set.
+    pub fn read_flags<F>(&mut self, bits: u32, unknown: UnknownBits) ->
+        Result<F, io::Error> where F: Flags, F::Bits: Numeric {
+
+        let value = self.read::<F::Bits>(bits)?;
+        match unknown {
+            UnknownBits::Error => F::from_bits(value).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData,
+                               "unknown flag bits read")
+            }),
+            UnknownBits::Truncate => Ok(F::from_bits_truncate(value)),
+            UnknownBits::Preserve => Ok(F::from_bits_retain(value)),
+        }
+    }
+}
+
+impl<'a, E: Endianness, W: io::Write + ?Sized> BitWriter<'a, E, W> {
+    /// Writes a flag field of the given number of bits,
+    /// handling any unknown bits according to the given policy.
+    ///
+    /// Requires the `bitflags` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the number of bits is larger than the
+    /// flags' underlying type, if the flags are too large to fit
+    /// the given number of bits, or if the policy is `UnknownBits::Error`
+    /// and unknown bits are set.
+    pub fn write_flags<F>(&mut self, bits: u32, flags: F,
+                          unknown: UnknownBits) -> Result<(), io::Error>
+        where F: Flags, F::Bits: Numeric {
+
+        let value = match unknown {
+            UnknownBits::Error => match F::from_bits(flags.bits()) {
+                Some(_) => flags.bits(),
+                None => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                              "unknown flag bits written"));
+                }
+            },
+            UnknownBits::Truncate => F::from_bits_truncate(flags.bits()).bits(),
+            UnknownBits::Preserve => flags.bits(),
+        };
+        self.write(bits, value)
+    }
+}
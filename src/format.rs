@@ -0,0 +1,94 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Format strings for reading several values at once
+//! with the `parse_fmt` method.
+//!
+//! A format string is a whitespace-separated list of fields,
+//! each a letter followed by a number of bits:
+//!
+//! | Field | Meaning |
+//! |---|---|
+//! | `uN` | an unsigned value of N bits, up to 64 |
+//! | `sN` or `iN` | a signed value of N bits, from 1 to 64 |
+//! | `xN` | N bits which are skipped and not returned |
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Read, Cursor};
+//! use bitstream_io::{BigEndian, BitReader};
+//! use bitstream_io::format::Value;
+//!
+//! let data = [0b1011_0111, 0xFF];
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! assert_eq!(reader.parse_fmt("u1 x3 s4 u8").unwrap(),
+//!            vec![Value::Unsigned(1), Value::Signed(7), Value::Unsigned(255)]);
+//! ```
+
+#![warn(missing_docs)]
+
+use alloc::vec::Vec;
+use io;
+
+/// A value read by a format string field.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    /// A value read by a `uN` field
+    Unsigned(u64),
+    /// A value read by an `sN` or `iN` field
+    Signed(i64),
+}
+
+impl Value {
+    /// Returns the value as a `u64`
+    /// if it is unsigned or a non-negative signed value.
+    pub fn to_u64(&self) -> Option<u64> {
+        match *self {
+            Value::Unsigned(u) => Some(u),
+            Value::Signed(s) if s >= 0 => Some(s as u64),
+            Value::Signed(_) => None,
+        }
+    }
+
+    /// Returns the value as an `i64`
+    /// if it is signed or an unsigned value small enough to fit.
+    pub fn to_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Unsigned(u) if u <= i64::MAX as u64 => Some(u as i64),
+            Value::Unsigned(_) => None,
+            Value::Signed(s) => Some(s),
+        }
+    }
+}
+
+// A single field of a format string
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Field {
+    Unsigned(u32),
+    Signed(u32),
+    Skip(u32),
+}
+
+// Parses the whole of a format string,
+// so that invalid formats are rejected before anything is read.
+pub(crate) fn parse(format: &str) -> Result<Vec<Field>, io::Error> {
+    format.split_whitespace().map(|field| {
+        let mut chars = field.chars();
+        let kind = chars.next();
+        let bits = chars.as_str().parse::<u32>().ok();
+        match (kind, bits) {
+            (Some('u'), Some(bits)) if bits <= 64 => Ok(Field::Unsigned(bits)),
+            (Some('s'), Some(bits)) | (Some('i'), Some(bits))
+                if (1..=64).contains(&bits) => Ok(Field::Signed(bits)),
+            (Some('x'), Some(bits)) => Ok(Field::Skip(bits)),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                    "invalid format string field")),
+        }
+    }).collect()
+}
@@ -0,0 +1,322 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Frames of a payload followed by its CRC.
+//!
+//! This module requires the `alloc` feature.
+//!
+//! Writing a checksummed frame by hand means wrapping the payload
+//! in a `CrcWriter`, padding it to a byte boundary if the format
+//! calls for it without letting the padding into the CRC,
+//! then writing the CRC at its own width.
+//! Reading one back means doing the same in reverse,
+//! and not trusting any of the payload until the CRC has matched.
+//! `BitWriter::write_checksummed` and `BitReader::read_checksummed`
+//! do each of these in one call, given the frame's `Framing`.
+//!
+//! The CRC covers exactly the payload's bits, which needn't
+//! start or end on a byte boundary.
+//! A payload is buffered whole before its CRC is checked,
+//! then read through a `FrameReader` which can't read past it.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitRead, BitReader, BitWriter};
+//! use bitstream_io::crc::Crc;
+//! use bitstream_io::frame::{FrameError, Framing};
+//!
+//! let framing = Framing{crc: Crc::crc8(), align: true};
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian, _>::from_write(&mut data);
+//!     writer.write(4, 0b1001u8).unwrap();   // not part of the frame
+//!     writer.write_checksummed(&framing, |w| {
+//!         w.write(3, 0b101u8)?;
+//!         w.write(8, 0x5Au8)
+//!     }).unwrap();
+//! }
+//! // 4 + 11 bits, 1 bit of padding and an 8-bit CRC
+//! assert_eq!(data.len(), 3);
+//!
+//! let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0b1001);
+//! let mut payload = reader.read_checksummed(&framing, 11).unwrap();
+//! assert_eq!(payload.read::<u8>(3).unwrap(), 0b101);
+//! assert_eq!(payload.read::<u8>(8).unwrap(), 0x5A);
+//! assert!(payload.read_bit().is_err());
+//!
+//! // a single flipped bit fails the check
+//! data[1] ^= 0b0100_0000;
+//! let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+//! reader.skip(4).unwrap();
+//! match reader.read_checksummed(&framing, 11) {
+//!     Err(FrameError::Checksum{..}) => {}
+//!     _ => panic!("corrupt frame accepted"),
+//! }
+//! ```
+
+#![warn(missing_docs)]
+
+#[cfg(feature = "std")]
+use std::error;
+use alloc::vec::Vec;
+use core::fmt;
+use io;
+
+use super::{BitQueue, BitRead, BitReader, BitWriter, Endianness, Numeric,
+            SignedNumeric};
+use crc::Crc;
+use read::read_within;
+
+/// How a checksummed frame is laid out.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Framing {
+    /// The CRC calculated over each frame's payload,
+    /// which follows the payload at the CRC's width
+    pub crc: Crc,
+    /// Whether the stream is padded with 0 bits to a whole byte
+    /// between the payload and its CRC.
+    /// The padding isn't covered by the CRC.
+    pub align: bool,
+}
+
+/// An error from `BitReader::read_checksummed`.
+#[derive(Debug)]
+pub enum FrameError {
+    /// An error from the underlying stream
+    Io(io::Error),
+    /// A CRC which doesn't match the frame's payload
+    Checksum {
+        /// The CRC read from the stream
+        expected: u64,
+        /// The CRC calculated over the payload read
+        calculated: u64,
+    },
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FrameError::Io(ref err) => err.fmt(f),
+            FrameError::Checksum{expected, calculated} => {
+                write!(f, "frame checksum {:#X} doesn't match payload's {:#X}",
+                       expected, calculated)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for FrameError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            FrameError::Io(ref err) => Some(err),
+            FrameError::Checksum{..} => None,
+        }
+    }
+}
+
+impl From<io::Error> for FrameError {
+    fn from(err: io::Error) -> Self {FrameError::Io(err)}
+}
+
+impl From<FrameError> for io::Error {
+    /// Checksum mismatches become `InvalidData` errors
+    fn from(err: FrameError) -> Self {
+        match err {
+            FrameError::Io(err) => err,
+            #[cfg(feature = "std")]
+            mismatch => io::Error::new(io::ErrorKind::InvalidData, mismatch),
+            #[cfg(not(feature = "std"))]
+            FrameError::Checksum{..} => {
+                io::Error::new(io::ErrorKind::InvalidData,
+                               "frame checksum mismatch")
+            }
+        }
+    }
+}
+
+impl<'a, E: Endianness, W: io::Write + ?Sized> BitWriter<'a, E, W> {
+    /// Writes a frame whose payload is written by the given function,
+    /// followed by any padding the framing calls for and the CRC
+    /// of exactly the bits the function wrote.
+    ///
+    /// The payload is buffered until the function returns,
+    /// so nothing is written to the stream if it fails.
+    /// Returns the CRC written.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the function
+    /// or any I/O error from the underlying stream.
+    pub fn write_checksummed<F>(&mut self, framing: &Framing, f: F) ->
+        Result<u64, io::Error>
+        where F: FnOnce(&mut BitWriter<E>) -> Result<(), io::Error> {
+        let mut payload = Vec::new();
+        let partial = {
+            let mut writer = BitWriter::from_parts(&mut payload,
+                                                   BitQueue::new());
+            f(&mut writer)?;
+            writer.into_bitqueue()
+        };
+
+        let mut digest = framing.crc.digest();
+        for byte in payload.iter() {
+            digest.update::<E>(8, u64::from(*byte));
+        }
+        let partial_bits = partial.len();
+        let partial = partial.value();
+        digest.update::<E>(partial_bits, u64::from(partial));
+        let checksum = digest.value();
+
+        self.write_bytes(&payload)?;
+        self.write(partial_bits, partial)?;
+        if framing.align {
+            self.byte_align()?;
+        }
+        self.write(framing.crc.width, checksum)?;
+        Ok(checksum)
+    }
+}
+
+impl<E: Endianness, R: io::Read> BitReader<E, R> {
+    /// Reads a frame of the given number of payload bits,
+    /// followed by any padding the framing calls for and its CRC,
+    /// returning a reader over the payload if the CRC matches it.
+    ///
+    /// The whole frame is consumed whether or not the CRC matches,
+    /// so the stream is left at whatever follows it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Checksum` error if the CRC read doesn't match
+    /// the payload, or passes along any I/O error
+    /// from the underlying stream.
+    pub fn read_checksummed(&mut self,
+                            framing: &Framing,
+                            payload_bits: u64) ->
+        Result<FrameReader<E>, FrameError> {
+        let mut payload = Vec::new();
+        let mut remaining = payload_bits / 8;
+        while remaining > 0 {
+            let start = payload.len();
+            let chunk = remaining.min(4096);
+            payload.resize(start + chunk as usize, 0);
+            self.read_bytes(&mut payload[start..])?;
+            remaining -= chunk;
+        }
+        let partial_bits = (payload_bits % 8) as u32;
+        if partial_bits > 0 {
+            let partial: u8 = self.read(partial_bits)?;
+            let mut writer = BitWriter::<E, _>::from_write(&mut payload);
+            writer.write(partial_bits, partial)?;
+            writer.byte_align()?;
+        }
+        if framing.align {
+            self.byte_align();
+        }
+        let expected = self.read::<u64>(framing.crc.width)?;
+
+        let calculated = framing.crc.checksum::<E>(&payload, 0, payload_bits)?;
+        if expected == calculated {
+            Ok(FrameReader{reader: BitReader::from_vec(payload),
+                           bits: payload_bits,
+                           remaining: payload_bits})
+        } else {
+            Err(FrameError::Checksum{expected, calculated})
+        }
+    }
+}
+
+/// A `BitRead` stream over a checksummed frame's payload,
+/// as returned by `BitReader::read_checksummed`.
+///
+/// Any read which would go beyond the payload returns an
+/// `UnexpectedEof` error without consuming anything,
+/// as does one which the payload's reader rejects.
+pub struct FrameReader<E: Endianness> {
+    reader: BitReader<E, io::Cursor<Vec<u8>>>,
+    bits: u64,
+    remaining: u64,
+}
+
+impl<E: Endianness> FrameReader<E> {
+    /// Returns the number of bits in the payload.
+    #[inline]
+    pub fn payload_bits(&self) -> u64 {self.bits}
+
+    /// Returns the number of payload bits not yet read.
+    #[inline]
+    pub fn remaining(&self) -> u64 {self.remaining}
+
+    fn within<T, F>(&mut self, bits: u64, read: F) -> Result<T, io::Error>
+        where F: FnOnce(&mut BitReader<E, io::Cursor<Vec<u8>>>) ->
+              Result<T, io::Error> {
+        read_within(&mut self.reader, &mut self.remaining, bits,
+                    "read beyond end of frame payload", read)
+    }
+}
+
+impl<E: Endianness> BitRead for FrameReader<E> {
+    #[inline]
+    fn read_bit(&mut self) -> Result<bool, io::Error> {
+        self.within(1, |r| r.read_bit())
+    }
+
+    #[inline]
+    fn read<U>(&mut self, bits: u32) -> Result<U, io::Error>
+        where U: Numeric {
+        self.within(u64::from(bits), |r| r.read(bits))
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> Result<S, io::Error>
+        where S: SignedNumeric {
+        self.within(u64::from(bits), |r| r.read_signed(bits))
+    }
+
+    #[inline]
+    fn skip(&mut self, bits: u32) -> Result<(), io::Error> {
+        self.within(u64::from(bits), |r| r.skip(bits))
+    }
+
+    #[inline]
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        self.within(buf.len() as u64 * 8, |r| r.read_bytes(buf))
+    }
+
+    fn read_unary0(&mut self) -> Result<u32, io::Error> {
+        let mut count = 0;
+        while self.read_bit()? {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn read_unary1(&mut self) -> Result<u32, io::Error> {
+        let mut count = 0;
+        while !self.read_bit()? {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Alignment is relative to the start of the payload.
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.reader.byte_aligned()
+    }
+
+    fn byte_align(&mut self) {
+        let read = self.bits - self.remaining;
+        let padding = (8 - read % 8) % 8;
+        self.remaining = self.remaining.saturating_sub(padding);
+        self.reader.byte_align()
+    }
+}
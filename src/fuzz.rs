@@ -0,0 +1,283 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Helpers for fuzzing decoders through the `arbitrary` crate.
+//!
+//! This module requires the `arbitrary` feature.
+//!
+//! Purely random bytes rarely get past a decoder's first few checks,
+//! so this module generates streams with some structure instead.
+//! A `Schema` lists a stream's fields, each with a width
+//! and optionally a range of values or a fixed value
+//! such as a sync word, and a `Stream` is an arbitrary stream
+//! following that schema.
+//! An `Encoded` value is an arbitrary value of a type
+//! implementing `ToBitStream`, which may derive `Arbitrary`,
+//! alongside its encoding.
+//!
+//! `check_parse` and `check_decoder` then run a
+//! `FromBitStream` implementation over a fuzzer's input,
+//! panicking if any value it parses doesn't round-trip.
+//!
+//! ## Example
+//!
+//! ```
+//! extern crate arbitrary;
+//! extern crate bitstream_io;
+//! use arbitrary::{Arbitrary, Unstructured};
+//! use bitstream_io::{BigEndian, BitRead, BitReader};
+//! use bitstream_io::fuzz::{Field, Schema, Stream};
+//!
+//! struct Header;
+//!
+//! impl Schema for Header {
+//!     const FIELDS: &'static [Field] = &[
+//!         Field::Constant{bits: 12, value: 0xFFF},
+//!         Field::Range{bits: 4, min: 1, max: 3},
+//!         Field::Bits(10),
+//!     ];
+//! }
+//!
+//! # fn main() {
+//! let noise = [0x5A; 16];
+//! let mut u = Unstructured::new(&noise);
+//! let stream = Stream::<BigEndian, Header>::arbitrary(&mut u).unwrap();
+//! assert_eq!(stream.data.len(), 4);
+//!
+//! let mut reader = BitReader::<BigEndian, _>::from_read(&stream.data[..]);
+//! assert_eq!(reader.read::<u16>(12).unwrap(), 0xFFF);
+//! assert!((1..=3).contains(&reader.read::<u8>(4).unwrap()));
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use arbitrary::{Arbitrary, Unstructured};
+
+use io::Cursor;
+use test_util::{encode, roundtrip};
+use super::{BigEndian, LittleEndian, Endianness, BitReader, BitWriter,
+            BitRead, FromBitStream, ToBitStream};
+
+/// A field of a generated stream.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Field {
+    /// Any value of the given number of bits,
+    /// which may be wider than 64
+    Bits(u32),
+    /// A value from `min` to `max` inclusive,
+    /// written in the given number of bits (up to 64)
+    Range {
+        /// The field's width in bits
+        bits: u32,
+        /// The smallest value generated
+        min: u64,
+        /// The largest value generated
+        max: u64,
+    },
+    /// A fixed value, written in the given number of bits (up to 64)
+    Constant {
+        /// The field's width in bits
+        bits: u32,
+        /// The field's value
+        value: u64,
+    },
+}
+
+/// A list of fields describing the layout of a stream.
+pub trait Schema {
+    /// The stream's fields, in order
+    const FIELDS: &'static [Field];
+}
+
+/// Writes an arbitrary stream with the given fields,
+/// padded with 0 bits to a whole byte.
+///
+/// # Errors
+///
+/// Passes along any error from the data source,
+/// such as running out of data while choosing a ranged value.
+///
+/// # Panics
+///
+/// Panics if a `Range` or `Constant` field's value
+/// doesn't fit in its width.
+///
+/// # Example
+/// ```
+/// extern crate arbitrary;
+/// extern crate bitstream_io;
+/// use arbitrary::Unstructured;
+/// use bitstream_io::LittleEndian;
+/// use bitstream_io::fuzz::{arbitrary_stream, Field};
+///
+/// # fn main() {
+/// let mut u = Unstructured::new(&[0xFF, 0xFF]);
+/// let fields = [Field::Bits(3), Field::Constant{bits: 2, value: 0}];
+/// let data = arbitrary_stream::<LittleEndian>(&mut u, &fields).unwrap();
+/// assert_eq!(data, [0b111]);
+/// # }
+/// ```
+pub fn arbitrary_stream<E: Endianness>(u: &mut Unstructured,
+                                       fields: &[Field]) ->
+    arbitrary::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    {
+        let mut writer = BitWriter::<E>::new(&mut data);
+        for field in fields {
+            match *field {
+                Field::Bits(mut bits) => {
+                    while bits > 0 {
+                        let chunk = bits.min(64);
+                        let max = u64::MAX >> (64 - chunk);
+                        let value = u.int_in_range(0..=max)?;
+                        writer.write(chunk, value).expect("in-memory write");
+                        bits -= chunk;
+                    }
+                }
+                Field::Range{bits, min, max} => {
+                    let value = u.int_in_range(min..=max)?;
+                    writer.write(bits, value)
+                        .expect("ranged value doesn't fit in field");
+                }
+                Field::Constant{bits, value} => {
+                    writer.write(bits, value)
+                        .expect("constant value doesn't fit in field");
+                }
+            }
+        }
+        writer.byte_align().expect("in-memory write");
+    }
+    Ok(data)
+}
+
+/// An arbitrary byte-aligned stream following a `Schema`.
+pub struct Stream<E: Endianness, S: Schema> {
+    /// The stream's bytes
+    pub data: Vec<u8>,
+    phantom: PhantomData<fn() -> (E, S)>,
+}
+
+impl<'a, E: Endianness, S: Schema> Arbitrary<'a> for Stream<E, S> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Stream{data: arbitrary_stream::<E>(u, S::FIELDS)?,
+                  phantom: PhantomData})
+    }
+}
+
+impl<E: Endianness, S: Schema> fmt::Debug for Stream<E, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Stream").field("data", &self.data).finish()
+    }
+}
+
+/// An arbitrary value along with its byte-aligned encoding.
+pub struct Encoded<E: Endianness, T> {
+    /// The value generated
+    pub value: T,
+    /// The value's encoding, padded with 0 bits to a whole byte
+    pub data: Vec<u8>,
+    phantom: PhantomData<fn() -> E>,
+}
+
+impl<'a, E, T> Arbitrary<'a> for Encoded<E, T>
+    where E: Endianness, T: Arbitrary<'a> + ToBitStream,
+          T::Error: fmt::Debug {
+    /// Generates a value and encodes it.
+    ///
+    /// Values which fail to encode,
+    /// such as those with fields too large for their widths,
+    /// are rejected as being in the wrong format.
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let value = T::arbitrary(u)?;
+        let data = encode::<E, T>(&value)
+            .map_err(|_| arbitrary::Error::IncorrectFormat)?;
+        Ok(Encoded{value, data, phantom: PhantomData})
+    }
+}
+
+impl<E: Endianness, T: fmt::Debug> fmt::Debug for Encoded<E, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Encoded")
+            .field("value", &self.value)
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
+/// Parses a value from the start of the data in the given endianness,
+/// and checks that any value parsed round-trips.
+///
+/// Data which fails to parse is expected from a fuzzer
+/// and returns `None`.
+///
+/// # Panics
+///
+/// Panics with a description of the failure if a parsed value
+/// doesn't write and read back as itself.
+///
+/// # Example
+/// ```
+/// use std::io;
+/// use bitstream_io::{BigEndian, BitRead, BitWrite,
+///                    FromBitStream, ToBitStream};
+/// use bitstream_io::fuzz::check_parse;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Pair(u8, u8);
+///
+/// impl FromBitStream for Pair {
+///     type Error = io::Error;
+///
+///     fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+///         Ok(Pair(r.read(4)?, r.read(4)?))
+///     }
+/// }
+///
+/// impl ToBitStream for Pair {
+///     type Error = io::Error;
+///
+///     fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+///         w.write(4, self.0)?;
+///         w.write(4, self.1)
+///     }
+/// }
+///
+/// assert_eq!(check_parse::<BigEndian, Pair>(&[0x12, 0x34]), Some(Pair(1, 2)));
+/// assert_eq!(check_parse::<BigEndian, Pair>(&[]), None);
+/// ```
+pub fn check_parse<E, T>(data: &[u8]) -> Option<T>
+    where E: Endianness,
+          T: ToBitStream + FromBitStream + PartialEq + fmt::Debug,
+          <T as ToBitStream>::Error: fmt::Debug,
+          <T as FromBitStream>::Error: fmt::Debug {
+    let mut reader = BitReader::<E, _>::from_read(Cursor::new(data));
+    let value = reader.parse::<T>().ok()?;
+    if let Err(message) = roundtrip::<E, T>(&value) {
+        panic!("parsed value failed to round trip: {}", message);
+    }
+    Some(value)
+}
+
+/// Runs `check_parse` over the data in both big-endian
+/// and little-endian streams, as the body of a fuzz target.
+///
+/// # Panics
+///
+/// Panics with a description of the failure if a parsed value
+/// doesn't write and read back as itself.
+pub fn check_decoder<T>(data: &[u8])
+    where T: ToBitStream + FromBitStream + PartialEq + fmt::Debug,
+          <T as ToBitStream>::Error: fmt::Debug,
+          <T as FromBitStream>::Error: fmt::Debug {
+    check_parse::<BigEndian, T>(data);
+    check_parse::<LittleEndian, T>(data);
+}
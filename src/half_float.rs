@@ -0,0 +1,127 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reading and writing 16-bit floats defined by the half crate.
+//!
+//! This module requires the `half` feature.
+//!
+//! Both `f16` and `bf16` values may be read and written
+//! at any bit position, and also implement `Primitive`
+//! for use with `read_aligned` and `write_aligned`.
+//!
+//! ## Example
+//!
+//! ```
+//! extern crate half;
+//! extern crate bitstream_io;
+//! use std::io::{Read, Cursor};
+//! use half::{bf16, f16};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//!
+//! # fn main() {
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     writer.write(4, 0b1010u8).unwrap();
+//!     writer.write_f16(f16::from_f32(1.5)).unwrap();
+//!     writer.write_bf16(bf16::from_f32(-2.0)).unwrap();
+//!     writer.write(4, 0b0101u8).unwrap();
+//! }
+//! assert_eq!(data, [0xA3, 0xE0, 0x0C, 0x00, 0x05]);
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0b1010);
+//! assert_eq!(reader.read_f16().unwrap().to_f32(), 1.5);
+//! assert_eq!(reader.read_bf16().unwrap().to_f32(), -2.0);
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+use io;
+
+use half::{bf16, f16};
+
+use super::{BitReader, BitWriter, Endianness, Primitive};
+
+impl<E: Endianness, R: io::Read> BitReader<E, R> {
+    /// Reads an IEEE 754 half-precision value from the stream.
+    ///
+    /// Requires the `half` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    #[inline]
+    pub fn read_f16(&mut self) -> Result<f16, io::Error> {
+        self.read::<u16>(16).map(f16::from_bits)
+    }
+
+    /// Reads a bfloat16 value from the stream.
+    ///
+    /// Requires the `half` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    #[inline]
+    pub fn read_bf16(&mut self) -> Result<bf16, io::Error> {
+        self.read::<u16>(16).map(bf16::from_bits)
+    }
+}
+
+impl<'a, E: Endianness, W: io::Write + ?Sized> BitWriter<'a, E, W> {
+    /// Writes an IEEE 754 half-precision value to the stream.
+    ///
+    /// Requires the `half` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    #[inline]
+    pub fn write_f16(&mut self, value: f16) -> Result<(), io::Error> {
+        self.write(16, value.to_bits())
+    }
+
+    /// Writes a bfloat16 value to the stream.
+    ///
+    /// Requires the `half` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    #[inline]
+    pub fn write_bf16(&mut self, value: bf16) -> Result<(), io::Error> {
+        self.write(16, value.to_bits())
+    }
+}
+
+macro_rules! define_half_primitive {
+    ($t:ty) => {
+        impl Primitive for $t {
+            type Bytes = [u8; 2];
+            #[inline(always)]
+            fn buffer() -> Self::Bytes {[0; 2]}
+            #[inline(always)]
+            fn to_be_bytes(self) -> Self::Bytes {self.to_be_bytes()}
+            #[inline(always)]
+            fn to_le_bytes(self) -> Self::Bytes {self.to_le_bytes()}
+            #[inline(always)]
+            fn from_be_bytes(bytes: Self::Bytes) -> Self {
+                <$t>::from_be_bytes(bytes)
+            }
+            #[inline(always)]
+            fn from_le_bytes(bytes: Self::Bytes) -> Self {
+                <$t>::from_le_bytes(bytes)
+            }
+        }
+    }
+}
+
+define_half_primitive!(f16);
+define_half_primitive!(bf16);
@@ -0,0 +1,257 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Adaptors for reading or writing Hamming-protected data.
+//!
+//! Each 4-bit data word is stored as either a 7-bit Hamming(7,4)
+//! codeword, which allows any single-bit error to be corrected,
+//! or as an 8-bit extended Hamming codeword (SECDED)
+//! which also allows any double-bit error to be detected.
+//!
+//! Codewords are laid out with Hamming position 1 as the
+//! most-significant bit, followed by SECDED's overall parity bit
+//! as the least-significant bit, and are read or written as a single
+//! value with the stream's endianness.
+//!
+//! This module requires the `fec` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Write, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::hamming::{Code, HammingReader, HammingWriter};
+//!
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     let mut hamming = HammingWriter::new(&mut writer, Code::Secded);
+//!     hamming.write_bytes(b"Hi").unwrap();
+//! }
+//! assert_eq!(data.len(), 4);
+//!
+//! // flip a single bit in transit
+//! data[1] ^= 0b0010_0000;
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! let mut hamming = HammingReader::new(&mut reader, Code::Secded);
+//! let mut bytes = [0; 2];
+//! hamming.read_bytes(&mut bytes).unwrap();
+//! assert_eq!(&bytes, b"Hi");
+//! assert_eq!(hamming.corrected(), 1);
+//! ```
+
+#![warn(missing_docs)]
+
+use io;
+
+use super::{BitReader, BitWriter, Endianness};
+use tables::ONES;
+
+/// The kind of Hamming code used to protect data words.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Code {
+    /// Hamming(7,4), which corrects any single-bit error
+    Hamming74,
+    /// Extended Hamming(8,4), which corrects any single-bit error
+    /// and detects any double-bit error
+    Secded,
+}
+
+impl Code {
+    /// The size of each codeword, in bits
+    #[inline]
+    pub fn codeword_bits(&self) -> u32 {
+        match *self {
+            Code::Hamming74 => 7,
+            Code::Secded => 8,
+        }
+    }
+}
+
+/// The result of decoding a single codeword.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Decoded {
+    /// The codeword was received intact
+    Valid(u8),
+    /// A single-bit error in the codeword was corrected
+    Corrected(u8),
+    /// A double-bit error was detected, which cannot be corrected
+    Uncorrectable,
+}
+
+/// Encodes a 4-bit data word to a codeword.
+///
+/// # Example
+/// ```
+/// use bitstream_io::hamming::{Code, encode};
+/// assert_eq!(encode(Code::Hamming74, 0b1011), 0b0110011);
+/// assert_eq!(encode(Code::Secded, 0b1011), 0b01100110);
+/// ```
+pub const fn encode(code: Code, nibble: u8) -> u8 {
+    debug_assert!(nibble < 16);
+    let d1 = (nibble >> 3) & 1;
+    let d2 = (nibble >> 2) & 1;
+    let d3 = (nibble >> 1) & 1;
+    let d4 = nibble & 1;
+    let p1 = d1 ^ d2 ^ d4;
+    let p2 = d1 ^ d3 ^ d4;
+    let p3 = d2 ^ d3 ^ d4;
+    let codeword = (p1 << 6) | (p2 << 5) | (d1 << 4) | (p3 << 3) |
+                   (d2 << 2) | (d3 << 1) | d4;
+    match code {
+        Code::Hamming74 => codeword,
+        Code::Secded => (codeword << 1) | (ONES[codeword as usize] & 1),
+    }
+}
+
+/// Decodes a codeword to a 4-bit data word,
+/// correcting a single-bit error if necessary.
+///
+/// Hamming(7,4) codewords with two or more errors
+/// are miscorrected rather than detected.
+///
+/// # Example
+/// ```
+/// use bitstream_io::hamming::{Code, Decoded, decode};
+/// assert_eq!(decode(Code::Secded, 0b01100110), Decoded::Valid(0b1011));
+/// assert_eq!(decode(Code::Secded, 0b01000110), Decoded::Corrected(0b1011));
+/// assert_eq!(decode(Code::Secded, 0b01000100), Decoded::Uncorrectable);
+/// ```
+pub fn decode(code: Code, codeword: u8) -> Decoded {
+    let hamming = match code {
+        Code::Hamming74 => codeword & 0x7F,
+        Code::Secded => codeword >> 1,
+    };
+    let syndrome = (1..8)
+        .filter(|position| (hamming >> (7 - position)) & 1 == 1)
+        .fold(0, |syndrome, position| syndrome ^ position);
+    let corrected = if syndrome == 0 {
+        hamming
+    } else {
+        hamming ^ (1 << (7 - syndrome))
+    };
+    let nibble = (((corrected >> 4) & 1) << 3) | (corrected & 0b111);
+
+    match code {
+        Code::Hamming74 if syndrome == 0 => Decoded::Valid(nibble),
+        Code::Hamming74 => Decoded::Corrected(nibble),
+        Code::Secded => {
+            match (syndrome, ONES[codeword as usize] & 1) {
+                (0, 0) => Decoded::Valid(nibble),
+                (_, 1) => Decoded::Corrected(nibble),
+                (_, _) => Decoded::Uncorrectable,
+            }
+        }
+    }
+}
+
+/// Writes Hamming-protected data words to a `BitWriter`.
+pub struct HammingWriter<'w, 'a: 'w, E: 'w + Endianness> {
+    writer: &'w mut BitWriter<'a, E>,
+    code: Code,
+}
+
+impl<'w, 'a: 'w, E: Endianness> HammingWriter<'w, 'a, E> {
+    /// Wraps a HammingWriter around a `BitWriter`
+    /// using the given code.
+    pub fn new(writer: &'w mut BitWriter<'a, E>,
+               code: Code) -> HammingWriter<'w, 'a, E> {
+        HammingWriter{writer, code}
+    }
+
+    /// Writes a 4-bit data word as a single codeword.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the value is larger than 4 bits.
+    pub fn write_nibble(&mut self, nibble: u8) -> Result<(), io::Error> {
+        if nibble >= 16 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive value for bits written"));
+        }
+        self.writer.write(self.code.codeword_bits(),
+                          encode(self.code, nibble))
+    }
+
+    /// Writes each byte as two codewords,
+    /// its most-significant nibble first.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn write_bytes(&mut self, buf: &[u8]) -> Result<(), io::Error> {
+        for byte in buf {
+            self.write_nibble(byte >> 4)?;
+            self.write_nibble(byte & 0xF)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads Hamming-protected data words from a `BitReader`.
+pub struct HammingReader<'r, E: 'r + Endianness> {
+    reader: &'r mut BitReader<E>,
+    code: Code,
+    corrected: u64,
+}
+
+impl<'r, E: Endianness> HammingReader<'r, E> {
+    /// Wraps a HammingReader around a `BitReader`
+    /// using the given code, which must match the one used
+    /// to write the data.
+    pub fn new(reader: &'r mut BitReader<E>,
+               code: Code) -> HammingReader<'r, E> {
+        HammingReader{reader, code, corrected: 0}
+    }
+
+    /// Reads and decodes a single codeword.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn read_nibble(&mut self) -> Result<Decoded, io::Error> {
+        let codeword = self.reader.read::<u8>(self.code.codeword_bits())?;
+        let decoded = decode(self.code, codeword);
+        if let Decoded::Corrected(_) = decoded {
+            self.corrected += 1;
+        }
+        Ok(decoded)
+    }
+
+    /// Fills the buffer with bytes read as two codewords each,
+    /// its most-significant nibble first.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if any codeword is uncorrectable.
+    pub fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        for byte in buf.iter_mut() {
+            let high = self.read_valid_nibble()?;
+            let low = self.read_valid_nibble()?;
+            *byte = (high << 4) | low;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of codewords corrected so far.
+    #[inline]
+    pub fn corrected(&self) -> u64 {self.corrected}
+
+    fn read_valid_nibble(&mut self) -> Result<u8, io::Error> {
+        match self.read_nibble()? {
+            Decoded::Valid(nibble) | Decoded::Corrected(nibble) => Ok(nibble),
+            Decoded::Uncorrectable => {
+                Err(io::Error::new(io::ErrorKind::InvalidData,
+                                   "uncorrectable Hamming codeword"))
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Showing the bits read just before an error.
+//!
+//! This module requires the `std` feature.
+//!
+//! Knowing that a stream is corrupt at some offset
+//! rarely says why.
+//! A `HistoryReader` wraps a `BitRead` stream and keeps
+//! the last bits read through it in a ring buffer of a given size.
+//! Any error from the stream carries those bits as a `HistoryError`,
+//! and `history` shows them at any time, such as after
+//! a parser rejects an invalid value.
+//! Either way, the bits are shown in stream order,
+//! grouped into the bytes they were read from,
+//! with a `^` marking where the next read starts.
+//!
+//! Since a `BitRead` stream doesn't say what order its bits are in,
+//! the reader's endianness must be given to recover them
+//! from each value read.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitRead, BitReader};
+//! use bitstream_io::history::{HistoryError, HistoryReader};
+//!
+//! let data = [0xFF, 0x81];
+//! let mut reader = HistoryReader::<BigEndian, _>::new(
+//!     BitReader::<BigEndian, _>::from_read(&data[..]), 12);
+//! assert_eq!(reader.read::<u8>(3).unwrap(), 0b111);
+//! assert_eq!(reader.read::<u16>(10).unwrap(), 0b11111_10000);
+//! let err = reader.read::<u8>(4).unwrap_err();
+//!
+//! let history = HistoryError::find(&err).unwrap();
+//! assert_eq!(history.position(), 13);
+//! assert_eq!(history.history(),
+//!            "last 12 bits before bit 13:\n\
+//!             .1111111 10000\n              ^");
+//! ```
+
+#![warn(missing_docs)]
+
+use std::cmp::min;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+
+use super::{is_little_endian, BitRead, Endianness, Numeric, SignedNumeric};
+use tap::{Observer, Tap, TapError};
+
+/// The bits read before an error, as shown by `HistoryReader::history`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryText(String);
+
+impl fmt::Display for HistoryText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\n{}", self.0)
+    }
+}
+
+/// An error along with the bits read just before it.
+pub type HistoryError = TapError<HistoryText>;
+
+impl TapError<HistoryText> {
+    /// Returns the bits read before the error,
+    /// as shown by `HistoryReader::history`.
+    #[inline]
+    pub fn history(&self) -> &str {&self.context().0}
+}
+
+/// Keeps the bits most recently read from a stream
+/// of the given endianness.
+pub struct History<E: Endianness> {
+    phantom: PhantomData<E>,
+    capacity: usize,
+    bits: VecDeque<bool>,
+}
+
+impl<E: Endianness> History<E> {
+    /// Keeps up to the given number of bits.
+    pub fn new(capacity: usize) -> History<E> {
+        History{phantom: PhantomData,
+                capacity,
+                bits: VecDeque::with_capacity(capacity)}
+    }
+
+    /// Returns the bits kept, oldest first.
+    #[inline]
+    pub fn bits(&self) -> &VecDeque<bool> {&self.bits}
+
+    /// Shows the bits kept, as `HistoryReader::history` does,
+    /// given the offset of the bit following them.
+    pub fn show(&self, position: u64) -> String {
+        let start = position - self.bits.len() as u64;
+        let lead = (start % 8) as usize;
+        let mut line = String::with_capacity(lead + self.bits.len() * 9 / 8);
+        for (i, bit) in (0..lead).map(|_| None)
+            .chain(self.bits.iter().map(Some)).enumerate() {
+            if i > 0 && i % 8 == 0 {
+                line.push(' ');
+            }
+            line.push(match bit {
+                Some(true) => '1',
+                Some(false) => '0',
+                None => '.',
+            });
+        }
+        let read = lead + self.bits.len();
+        format!("last {} bits before bit {}:\n{}\n{:>width$}",
+                self.bits.len(), position, line, "^",
+                width = read + read / 8 + 1)
+    }
+
+    // keeps a run of bits of the same value
+    fn push_run(&mut self, bit: bool, count: u64) {
+        for _ in 0..min(count, self.capacity as u64) {
+            if self.bits.len() == self.capacity {
+                self.bits.pop_front();
+            }
+            self.bits.push_back(bit);
+        }
+    }
+
+    // keeps the lowest bits of a value in stream order
+    fn push_value<N: Numeric>(&mut self, bits: u32, value: N) {
+        let little_endian = is_little_endian::<E>();
+        for i in 0..bits {
+            let shift = if little_endian {i} else {bits - 1 - i};
+            self.push_run((value >> shift).to_u8() & 1 == 1, 1);
+        }
+    }
+}
+
+impl<E: Endianness> Observer for History<E> {
+    fn bit(&mut self, _offset: u64, bit: bool) {self.push_run(bit, 1)}
+
+    fn unsigned<U>(&mut self, _offset: u64, bits: u32, value: U)
+        where U: Numeric {
+        self.push_value(bits, value)
+    }
+
+    fn signed<S>(&mut self, _offset: u64, bits: u32, value: S)
+        where S: SignedNumeric {
+        self.push_value(bits, value)
+    }
+
+    fn bytes(&mut self, _offset: u64, buf: &[u8]) {
+        for byte in buf.iter() {
+            self.push_value(8, *byte);
+        }
+    }
+
+    fn unary0(&mut self, _offset: u64, value: u32) {
+        self.push_run(true, u64::from(value));
+        self.push_run(false, 1);
+    }
+
+    fn unary1(&mut self, _offset: u64, value: u32) {
+        self.push_run(false, u64::from(value));
+        self.push_run(true, 1);
+    }
+
+    fn skipped(&mut self, _offset: u64, bits: u64) {
+        if bits > 0 {
+            self.bits.clear();
+        }
+    }
+
+    fn padding(&mut self, _offset: u64, bits: &[bool]) {
+        for bit in bits.iter() {
+            self.push_run(*bit, 1);
+        }
+    }
+
+    // only the skipped bits which would still be kept are read
+    #[inline]
+    fn watched(&self, skip: u32) -> u32 {
+        min(self.capacity, skip as usize) as u32
+    }
+
+    fn error(&self, offset: u64, error: io::Error) -> io::Error {
+        HistoryError::new(error, offset, HistoryText(self.show(offset)))
+            .into()
+    }
+}
+
+/// Wraps a bit stream of the given endianness,
+/// keeping the bits most recently read through it.
+///
+/// Offsets count from where the stream was when it was wrapped.
+pub type HistoryReader<E, R> = Tap<R, History<E>>;
+
+impl<E: Endianness, R: BitRead> Tap<R, History<E>> {
+    /// Wraps the stream, keeping up to the given number of bits.
+    pub fn new(inner: R, capacity: usize) -> HistoryReader<E, R> {
+        Tap::with_observer(inner, History::new(capacity))
+    }
+
+    /// Returns the bits kept, oldest first.
+    #[inline]
+    pub fn bits(&self) -> &VecDeque<bool> {self.observer().bits()}
+
+    /// Shows the bits kept, in stream order,
+    /// with a `^` on the following line marking the current position.
+    ///
+    /// Bits are grouped by the bytes of the stream
+    /// they were read from, counting from where it was wrapped,
+    /// and any bits of the first byte before those kept
+    /// are shown as `.`.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{LittleEndian, BitRead, BitReader};
+    /// use bitstream_io::history::HistoryReader;
+    ///
+    /// let data = [0b0000_0101, 0xFF];
+    /// let mut reader = HistoryReader::<LittleEndian, _>::new(
+    ///     BitReader::<LittleEndian, _>::from_read(&data[..]), 64);
+    /// assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+    /// reader.byte_align();
+    /// assert_eq!(reader.history(),
+    ///            "last 8 bits before bit 8:\n10100000\n         ^");
+    /// ```
+    pub fn history(&self) -> String {
+        self.observer().show(self.position())
+    }
+}
@@ -11,12 +11,23 @@
 
 #![warn(missing_docs)]
 
-use std::fmt;
-use std::marker::PhantomData;
-use std::collections::BTreeMap;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::convert::TryInto;
+#[cfg(feature = "alloc")]
+use core::marker::PhantomData;
+#[cfg(feature = "alloc")]
 use super::Endianness;
+#[cfg(feature = "alloc")]
 use super::BitQueue;
 
+#[cfg(feature = "alloc")]
 /// A compiled Huffman tree element for use with the `read_huffman` method.
 /// Returned by `compile_read_tree`.
 ///
@@ -36,6 +47,7 @@ pub enum ReadHuffmanTree<E: Endianness, T: Clone> {
     InvalidState
 }
 
+#[cfg(feature = "alloc")]
 /// Given a vector of symbol/code pairs, compiles a Huffman tree
 /// for reading.
 ///
@@ -94,20 +106,21 @@ pub fn compile_read_tree<E,T>(values: Vec<(T,Vec<u8>)>) ->
     Ok(result.into_boxed_slice())
 }
 
+#[cfg(feature = "alloc")]
 fn compile_queue<E,T>(mut queue: BitQueue<E,u8>, tree: &FinalHuffmanTree<T>) ->
     ReadHuffmanTree<E,T> where E: Endianness, T: Clone {
-    match tree {
-        &FinalHuffmanTree::Leaf(ref value) => {
+    match *tree {
+        FinalHuffmanTree::Leaf(ref value) => {
             let len = queue.len();
             ReadHuffmanTree::Done(
                 value.clone(), queue.value(), len, PhantomData)
         }
-        &FinalHuffmanTree::Tree(ref bit0, ref bit1) => {
+        FinalHuffmanTree::Tree(ref bit0, ref bit1) => {
             if queue.is_empty() {
                 ReadHuffmanTree::Continue(
                     (0..256).map(
                     |byte| compile_queue(
-                        BitQueue::from_value(byte as u8, 8), &tree))
+                        BitQueue::from_value(byte as u8, 8), tree))
                     .collect::<Vec<ReadHuffmanTree<E,T>>>()
                     .into_boxed_slice())
             } else {
@@ -121,12 +134,14 @@ fn compile_queue<E,T>(mut queue: BitQueue<E,u8>, tree: &FinalHuffmanTree<T>) ->
     }
 }
 
+#[cfg(feature = "alloc")]
 // A complete Huffman tree with no empty nodes
 enum FinalHuffmanTree<T: Clone> {
     Leaf(T),
     Tree(Box<FinalHuffmanTree<T>>, Box<FinalHuffmanTree<T>>)
 }
 
+#[cfg(feature = "alloc")]
 impl<T: Clone> FinalHuffmanTree<T> {
     fn new(values: Vec<(T, Vec<u8>)>) ->
         Result<FinalHuffmanTree<T>,HuffmanTreeError> {
@@ -140,6 +155,7 @@ impl<T: Clone> FinalHuffmanTree<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 // Work-in-progress trees may have empty nodes during construction
 // but those are not allowed in a finalized tree.
 // If the user wants some codes to be None or an error symbol of some sort,
@@ -150,6 +166,7 @@ enum WipHuffmanTree<T: Clone> {
     Tree(Box<WipHuffmanTree<T>>, Box<WipHuffmanTree<T>>)
 }
 
+#[cfg(feature = "alloc")]
 impl<T: Clone> WipHuffmanTree<T> {
     fn new_empty() -> WipHuffmanTree<T> {
         WipHuffmanTree::Empty
@@ -181,9 +198,9 @@ impl<T: Clone> WipHuffmanTree<T> {
     }
 
     fn add(&mut self, code: &[u8], symbol: T) -> Result<(),HuffmanTreeError> {
-        match self {
-            &mut WipHuffmanTree::Empty => {
-                if code.len() == 0 {
+        match *self {
+            WipHuffmanTree::Empty => {
+                if code.is_empty() {
                     *self = WipHuffmanTree::new_leaf(symbol);
                     Ok(())
                 } else {
@@ -191,15 +208,15 @@ impl<T: Clone> WipHuffmanTree<T> {
                     self.add(code, symbol)
                 }
             }
-            &mut WipHuffmanTree::Leaf(_) => {
-                Err(if code.len() == 0 {
+            WipHuffmanTree::Leaf(_) => {
+                Err(if code.is_empty() {
                     HuffmanTreeError::DuplicateLeaf
                 } else {
                     HuffmanTreeError::OrphanedLeaf
                 })
             }
-            &mut WipHuffmanTree::Tree(ref mut zero, ref mut one) => {
-                if code.len() == 0 {
+            WipHuffmanTree::Tree(ref mut zero, ref mut one) => {
+                if code.is_empty() {
                     Err(HuffmanTreeError::DuplicateLeaf)
                 } else {
                     match code[0] {
@@ -245,6 +262,7 @@ impl fmt::Display for HuffmanTreeError {
     }
 }
 
+#[cfg(feature = "alloc")]
 /// Given a vector of symbol/code pairs, compiles a Huffman tree
 /// for writing.
 ///
@@ -309,16 +327,84 @@ pub fn compile_write_tree<E,T>(values: Vec<(T,Vec<u8>)>) ->
         map.entry(symbol).or_insert(encoded.into_boxed_slice());
     }
 
-    Ok(WriteHuffmanTree{map: map, phantom: PhantomData})
+    Ok(WriteHuffmanTree{map,
+                        dense: Box::new([]),
+                        index: None,
+                        phantom: PhantomData})
+}
+
+// the largest symbol given a slot in a dense write table
+#[cfg(feature = "alloc")]
+const MAX_DENSE_SYMBOL: usize = 0xFFFF;
+
+#[cfg(feature = "alloc")]
+/// Given a vector of symbol/code pairs whose symbols are small
+/// non-negative integers, compiles a Huffman tree for writing
+/// which also holds a table of codes indexed by symbol.
+///
+/// This accepts the same input as `compile_write_tree`,
+/// but `write_huffman` then finds each symbol's code with
+/// a single array lookup and writes it with a single call,
+/// rather than searching the tree for it.
+/// Symbols larger than 65535 or with codes longer than 32 bits
+/// are left out of the table and found in the tree as usual.
+///
+/// ## Example
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{LittleEndian, BitWriter};
+/// use bitstream_io::huffman::compile_dense_write_tree;
+/// let tree = compile_dense_write_tree(
+///     vec![(0u16, vec![0]),
+///          (1, vec![1, 0]),
+///          (2, vec![1, 1, 0]),
+///          (256, vec![1, 1, 1])]).unwrap();
+/// let mut data = Vec::new();
+/// {
+///     let mut writer = BitWriter::<LittleEndian>::new(&mut data);
+///     writer.write_huffman(&tree, 1).unwrap();
+///     writer.write_huffman(&tree, 2).unwrap();
+///     writer.write_huffman(&tree, 256).unwrap();
+/// }
+/// assert_eq!(data, [0b11101101]);
+/// ```
+pub fn compile_dense_write_tree<E,T>(values: Vec<(T,Vec<u8>)>) ->
+    Result<WriteHuffmanTree<E,T>,HuffmanTreeError>
+    where E: Endianness, T: Ord + Clone + TryInto<usize> {
+
+    let mut tree = compile_write_tree(values)?;
+
+    let mut dense = Vec::new();
+    for (symbol, code) in tree.map.iter() {
+        if let (Ok(index), [(bits, value)]) =
+            (symbol.clone().try_into(), code.as_ref()) {
+            if index <= MAX_DENSE_SYMBOL {
+                if dense.len() <= index {
+                    dense.resize(index + 1, None);
+                }
+                dense[index] = Some((*bits, *value));
+            }
+        }
+    }
+    tree.dense = dense.into_boxed_slice();
+    tree.index = Some(|symbol: &T| symbol.clone().try_into().ok());
+    Ok(tree)
 }
 
+#[cfg(feature = "alloc")]
 /// A compiled Huffman tree for use with the `write_huffman` method.
-/// Returned by `compiled_write_tree`.
+/// Returned by `compiled_write_tree`
+/// or `compile_dense_write_tree`.
 pub struct WriteHuffmanTree<E: Endianness, T: Ord> {
     map: BTreeMap<T,Box<[(u32, u32)]>>,
+    // codes of up to 32 bits, indexed by symbol
+    dense: Box<[Option<(u32, u32)>]>,
+    // turns a symbol into an index into the dense table, if it has one
+    index: Option<fn(&T) -> Option<usize>>,
     phantom: PhantomData<E>
 }
 
+#[cfg(feature = "alloc")]
 impl<E: Endianness, T: Ord + Clone> WriteHuffmanTree<E,T> {
     /// Returns true if symbol is in tree.
     pub fn has_symbol(&self, symbol: T) -> bool {
@@ -330,4 +416,103 @@ impl<E: Endianness, T: Ord + Clone> WriteHuffmanTree<E,T> {
     pub fn get(&self, symbol: T) -> &[(u32, u32)] {
         self.map[&symbol].as_ref()
     }
+
+    /// Given symbol, returns its code as a single (bits, value) pair
+    /// if it is in the tree's dense table.
+    #[inline]
+    pub fn get_dense(&self, symbol: &T) -> Option<(u32, u32)> {
+        self.index
+            .and_then(|index| index(symbol))
+            .and_then(|i| self.dense.get(i).cloned())
+            .and_then(|code| code)
+    }
+}
+
+/// A symbol and its code in a fixed Huffman table,
+/// for use with the `read_huffman_code` and `write_huffman_code` methods.
+///
+/// Unlike compiled trees, fixed tables need no allocation
+/// and may be built as constants or in caller-provided storage,
+/// at the cost of a linear search for each symbol.
+///
+/// A code's bits are read from or written to the stream
+/// from most-significant to least, so that the code
+/// `vec![1, 1, 0]` given to `compile_read_tree` has a `len` of 3
+/// and a `code` of `0b110`.
+///
+/// ## Example
+/// ```
+/// use bitstream_io::{BigEndian, BitRead, BitReader};
+/// use bitstream_io::huffman::HuffmanCode;
+///
+/// const TABLE: [HuffmanCode<char>; 4] = [
+///     HuffmanCode{symbol: 'a', len: 1, code: 0b0},
+///     HuffmanCode{symbol: 'b', len: 2, code: 0b10},
+///     HuffmanCode{symbol: 'c', len: 3, code: 0b110},
+///     HuffmanCode{symbol: 'd', len: 3, code: 0b111},
+/// ];
+///
+/// let data = [0b1011_0111];
+/// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+/// assert_eq!(reader.read_huffman_code(&TABLE).unwrap(), 'b');
+/// assert_eq!(reader.read_huffman_code(&TABLE).unwrap(), 'c');
+/// assert_eq!(reader.read_huffman_code(&TABLE).unwrap(), 'd');
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HuffmanCode<T> {
+    /// The symbol the code stands for
+    pub symbol: T,
+    /// The number of bits in the code, up to 32
+    pub len: u32,
+    /// The code's bits
+    pub code: u32,
+}
+
+/// Checks that a fixed Huffman table is complete and unambiguous,
+/// as `compile_read_tree` does for the trees it compiles.
+///
+/// # Errors
+///
+/// Returns `InvalidBit` if any code is longer than 32 bits
+/// or has bits set beyond its length,
+/// `DuplicateLeaf` or `OrphanedLeaf` if any code is the same as,
+/// or a prefix of, another code,
+/// and `MissingLeaf` if some sequence of bits matches no code.
+///
+/// ## Example
+/// ```
+/// use bitstream_io::huffman::{HuffmanCode, HuffmanTreeError, validate_table};
+///
+/// assert_eq!(validate_table(&[HuffmanCode{symbol: 1, len: 1, code: 0},
+///                             HuffmanCode{symbol: 2, len: 1, code: 1}]),
+///            Ok(()));
+/// assert_eq!(validate_table(&[HuffmanCode{symbol: 1, len: 1, code: 0},
+///                             HuffmanCode{symbol: 2, len: 2, code: 0b10}]),
+///            Err(HuffmanTreeError::MissingLeaf));
+/// ```
+pub fn validate_table<T>(table: &[HuffmanCode<T>]) ->
+    Result<(), HuffmanTreeError> {
+    let mut coverage = 0u64;
+    for (i, a) in table.iter().enumerate() {
+        if (a.len > 32) || (u64::from(a.code) >> a.len != 0) {
+            return Err(HuffmanTreeError::InvalidBit);
+        }
+        for b in table[i + 1..].iter() {
+            let (short, long) = if a.len <= b.len {(a, b)} else {(b, a)};
+            if u64::from(long.code) >> (long.len - short.len) ==
+                u64::from(short.code) {
+                return Err(if short.len == long.len {
+                    HuffmanTreeError::DuplicateLeaf
+                } else {
+                    HuffmanTreeError::OrphanedLeaf
+                });
+            }
+        }
+        coverage += 1 << (32 - a.len);
+    }
+    if coverage == 1 << 32 {
+        Ok(())
+    } else {
+        Err(HuffmanTreeError::MissingLeaf)
+    }
 }
@@ -0,0 +1,208 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Field descriptions of bitstream structures, for generic tooling.
+//!
+//! A type implementing `Describe` lists the fields it reads and writes
+//! in stream order, along with their widths and signedness.
+//! `inspect` uses such a list to read any described structure
+//! field by field, returning each field's offset and value
+//! so that it can be printed without knowing the type itself.
+//!
+//! `Describe` can be derived with the `derive` feature,
+//! using the same field attributes as `FromBitStream`.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Read, Cursor};
+//! use bitstream_io::{BigEndian, BitReader};
+//! use bitstream_io::inspect::{Describe, FieldDescriptor, FieldKind, inspect};
+//!
+//! struct BlockHeader;
+//!
+//! impl Describe for BlockHeader {
+//!     const FIELDS: &'static [FieldDescriptor] = &[
+//!         FieldDescriptor{name: "last_block", kind: FieldKind::Flag},
+//!         FieldDescriptor{name: "block_type", kind: FieldKind::Unsigned(7)},
+//!         FieldDescriptor{name: "block_size", kind: FieldKind::Unsigned(24)},
+//!     ];
+//! }
+//!
+//! let data = [0x84, 0x00, 0x00, 0x22];
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! let entries = inspect(&mut reader, BlockHeader::FIELDS).unwrap();
+//! let lines: Vec<String> = entries.iter().map(|e| e.to_string()).collect();
+//! assert_eq!(lines, vec!["0: last_block (1 bit) = true",
+//!                        "1: block_type (7 bits) = 4",
+//!                        "8: block_size (24 bits) = 34"]);
+//! ```
+
+#![warn(missing_docs)]
+
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::fmt;
+#[cfg(feature = "alloc")]
+use io;
+
+#[cfg(feature = "alloc")]
+use super::BitRead;
+
+/// A type whose stream layout is a fixed list of fields.
+pub trait Describe {
+    /// The type's fields, in the order they occur in the stream
+    const FIELDS: &'static [FieldDescriptor];
+}
+
+/// A single field of a described structure.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FieldDescriptor {
+    /// The field's name
+    pub name: &'static str,
+    /// How the field is stored
+    pub kind: FieldKind,
+}
+
+/// How a field is stored in the stream.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FieldKind {
+    /// An unsigned value of the given number of bits
+    Unsigned(u32),
+    /// A twos-complement signed value of the given number of bits
+    Signed(u32),
+    /// A single bit
+    Flag,
+    /// The given number of whole bytes
+    Bytes(u32),
+    /// A nested structure with the given fields
+    Nested(&'static [FieldDescriptor]),
+}
+
+impl FieldDescriptor {
+    /// Returns the field's total size in bits.
+    pub fn bits(&self) -> u64 {
+        match self.kind {
+            FieldKind::Unsigned(bits) | FieldKind::Signed(bits) => u64::from(bits),
+            FieldKind::Flag => 1,
+            FieldKind::Bytes(bytes) => u64::from(bytes) * 8,
+            FieldKind::Nested(fields) => fields.iter().map(|f| f.bits()).sum(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// A field's value as read by `inspect`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldValue {
+    /// The value of an unsigned field
+    Unsigned(u64),
+    /// The value of a signed field
+    Signed(i64),
+    /// The value of a single-bit field
+    Flag(bool),
+    /// The value of a byte field
+    Bytes(Vec<u8>),
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FieldValue::Unsigned(u) => write!(f, "{}", u),
+            FieldValue::Signed(s) => write!(f, "{}", s),
+            FieldValue::Flag(b) => write!(f, "{}", b),
+            FieldValue::Bytes(ref bytes) => {
+                for byte in bytes.iter() {
+                    write!(f, "{:02X}", byte)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// A single field read by `inspect`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// The field's name, prefixed by the names of any
+    /// structures it's nested in, separated by dots
+    pub path: String,
+    /// The field's offset in bits from the start of the structure
+    pub offset: u64,
+    /// The field's size in bits
+    pub bits: u64,
+    /// The field's value
+    pub value: FieldValue,
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} ({} bit{}) = {}",
+               self.offset, self.path, self.bits,
+               if self.bits == 1 {""} else {"s"}, self.value)
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Reads a structure with the given fields from the stream
+/// and returns its fields in order, with nested structures flattened.
+///
+/// # Errors
+///
+/// Passes along any I/O error from the underlying stream.
+/// Returns an error if any unsigned or signed field
+/// is wider than 64 bits.
+pub fn inspect<R>(reader: &mut R, fields: &[FieldDescriptor]) ->
+    Result<Vec<Entry>, io::Error> where R: BitRead + ?Sized {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    inspect_fields(reader, fields, "", &mut offset, &mut entries)?;
+    Ok(entries)
+}
+
+#[cfg(feature = "alloc")]
+fn inspect_fields<R>(reader: &mut R,
+                     fields: &[FieldDescriptor],
+                     prefix: &str,
+                     offset: &mut u64,
+                     entries: &mut Vec<Entry>) -> Result<(), io::Error>
+    where R: BitRead + ?Sized {
+    for field in fields.iter() {
+        let path = if prefix.is_empty() {
+            field.name.to_string()
+        } else {
+            format!("{}.{}", prefix, field.name)
+        };
+        let value = match field.kind {
+            FieldKind::Unsigned(bits) => FieldValue::Unsigned(reader.read(bits)?),
+            FieldKind::Signed(bits) => {
+                FieldValue::Signed(reader.read_signed(bits)?)
+            }
+            FieldKind::Flag => FieldValue::Flag(reader.read_bit()?),
+            FieldKind::Bytes(bytes) => {
+                let mut buf = vec![0; bytes as usize];
+                reader.read_bytes(&mut buf)?;
+                FieldValue::Bytes(buf)
+            }
+            FieldKind::Nested(nested) => {
+                inspect_fields(reader, nested, &path, offset, entries)?;
+                continue;
+            }
+        };
+        let bits = field.bits();
+        entries.push(Entry{path, offset: *offset, bits, value});
+        *offset += bits;
+    }
+    Ok(())
+}
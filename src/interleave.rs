@@ -0,0 +1,248 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Adaptors for block interleaving bits.
+//!
+//! A block interleaver fills a matrix of bits one row at a time
+//! and sends that matrix one column at a time,
+//! so that a burst of errors in transit is spread
+//! across many rows once deinterleaved.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Write, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::interleave::{Interleaver, InterleaveWriter,
+//!                                DeinterleaveReader};
+//!
+//! let block = Interleaver{rows: 2, columns: 4};
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     let mut interleaver = InterleaveWriter::new(&mut writer, block).unwrap();
+//!     interleaver.write(4, 0b1111).unwrap();
+//!     interleaver.write(4, 0b0000).unwrap();
+//! }
+//! assert_eq!(data, [0b1010_1010]);
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! let mut deinterleaver = DeinterleaveReader::new(&mut reader, block).unwrap();
+//! assert_eq!(deinterleaver.read(8).unwrap(), 0b1111_0000);
+//! ```
+
+#![warn(missing_docs)]
+
+use alloc::vec::Vec;
+use io;
+
+use super::{BitReader, BitWriter, BitQueue, Endianness};
+
+/// The dimensions of an interleaving block.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Interleaver {
+    /// The number of rows in the block
+    pub rows: usize,
+    /// The number of columns in the block, which is
+    /// the number of bits written to each row
+    pub columns: usize,
+}
+
+impl Interleaver {
+    /// The number of bits in the block
+    #[inline]
+    pub fn block_bits(&self) -> usize {self.rows * self.columns}
+
+    fn check(&self) -> Result<(), io::Error> {
+        match self.rows.checked_mul(self.columns) {
+            Some(bits) if bits > 0 => Ok(()),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                    "invalid interleaver dimensions")),
+        }
+    }
+}
+
+/// Writes bits to a `BitWriter` in interleaved blocks.
+pub struct InterleaveWriter<'w, 'a: 'w, E: 'w + Endianness> {
+    writer: &'w mut BitWriter<'a, E>,
+    block: Interleaver,
+    buffer: Vec<bool>,
+}
+
+impl<'w, 'a: 'w, E: Endianness> InterleaveWriter<'w, 'a, E> {
+    /// Wraps an InterleaveWriter around a `BitWriter`
+    /// using the given block dimensions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the block has no bits.
+    pub fn new(writer: &'w mut BitWriter<'a, E>,
+               block: Interleaver) ->
+        Result<InterleaveWriter<'w, 'a, E>, io::Error> {
+
+        block.check()?;
+        Ok(InterleaveWriter{writer,
+                            block,
+                            buffer: Vec::with_capacity(block.block_bits())})
+    }
+
+    /// Writes a single bit, which is sent
+    /// once its block has been filled.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn write_bit(&mut self, bit: bool) -> Result<(), io::Error> {
+        self.buffer.push(bit);
+        if self.buffer.len() == self.block.block_bits() {
+            self.send()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Writes an unsigned value of up to 64 bits,
+    /// taking its bits in the stream's order.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the number of bits is larger than 64
+    /// or if the value is too large to fit the given number of bits.
+    pub fn write(&mut self, bits: u32, value: u64) -> Result<(), io::Error> {
+        if bits > 64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive bits for type written"));
+        } else if (bits < 64) && (value >= (1 << bits)) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive value for bits written"));
+        } else if bits == 0 {
+            return Ok(());
+        }
+        let mut queue: BitQueue<E, u64> = BitQueue::from_value(value, bits);
+        while !queue.is_empty() {
+            self.write_bit(queue.pop(1) == 1)?;
+        }
+        Ok(())
+    }
+
+    /// Pads a partially filled block with 0 bits and sends it.
+    /// Does nothing if no bits are waiting to be sent.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn finish(&mut self) -> Result<(), io::Error> {
+        if self.buffer.is_empty() {
+            Ok(())
+        } else {
+            self.buffer.resize(self.block.block_bits(), false);
+            self.send()
+        }
+    }
+
+    // sends a full block one column at a time
+    fn send(&mut self) -> Result<(), io::Error> {
+        for column in 0..self.block.columns {
+            for row in 0..self.block.rows {
+                self.writer.write_bit(
+                    self.buffer[row * self.block.columns + column])?;
+            }
+        }
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+/// Reads bits from a `BitReader` of interleaved blocks.
+pub struct DeinterleaveReader<'r, E: 'r + Endianness> {
+    reader: &'r mut BitReader<E>,
+    block: Interleaver,
+    buffer: Vec<bool>,
+    position: usize,
+}
+
+impl<'r, E: Endianness> DeinterleaveReader<'r, E> {
+    /// Wraps a DeinterleaveReader around a `BitReader`
+    /// using the given block dimensions,
+    /// which must match those used to write the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the block has no bits.
+    pub fn new(reader: &'r mut BitReader<E>,
+               block: Interleaver) ->
+        Result<DeinterleaveReader<'r, E>, io::Error> {
+
+        block.check()?;
+        Ok(DeinterleaveReader{reader,
+                              block,
+                              buffer: Vec::with_capacity(block.block_bits()),
+                              position: 0})
+    }
+
+    /// Reads a single bit, receiving a whole block as needed.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn read_bit(&mut self) -> Result<bool, io::Error> {
+        if self.position == self.buffer.len() {
+            self.receive()?;
+        }
+        let bit = self.buffer[self.position];
+        self.position += 1;
+        Ok(bit)
+    }
+
+    /// Reads an unsigned value of up to 64 bits,
+    /// assembled from bits in the stream's order.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the number of bits is larger than 64.
+    pub fn read(&mut self, bits: u32) -> Result<u64, io::Error> {
+        if bits > 64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive bits for type read"));
+        }
+        let mut queue: BitQueue<E, u64> = BitQueue::new();
+        for _ in 0..bits {
+            queue.push(1, self.read_bit()? as u64);
+        }
+        Ok(queue.value())
+    }
+
+    /// Discards any bits remaining in the current block,
+    /// such as the padding written by `InterleaveWriter::finish`.
+    pub fn discard_block(&mut self) {
+        self.buffer.clear();
+        self.position = 0;
+    }
+
+    // receives a full block one column at a time
+    fn receive(&mut self) -> Result<(), io::Error> {
+        let (rows, columns) = (self.block.rows, self.block.columns);
+        self.buffer.clear();
+        self.buffer.resize(self.block.block_bits(), false);
+        self.position = 0;
+        for column in 0..columns {
+            for row in 0..rows {
+                match self.reader.read_bit() {
+                    Ok(bit) => {self.buffer[row * columns + column] = bit;}
+                    Err(err) => {
+                        self.buffer.clear();
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
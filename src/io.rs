@@ -0,0 +1,290 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A minimal subset of `std::io` for use without the standard library.
+//!
+//! This provides the `Read` and `Write` traits, `Error` type
+//! and `Cursor` used by the rest of the crate when the `std`
+//! feature is disabled, with implementations over in-memory buffers:
+//! byte slices and `Cursor`s for reading,
+//! and mutable byte slices and, with the `alloc` feature,
+//! `Vec<u8>` for writing.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//!
+//! let mut data = [0; 2];
+//! {
+//!     let mut buf = &mut data[..];
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut buf);
+//!     writer.write(12, 0xABCu16).unwrap();
+//!     writer.write(4, 0x5u8).unwrap();
+//! }
+//! let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+//! assert_eq!(reader.read::<u16>(12).unwrap(), 0xABC);
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0x5);
+//! ```
+
+#![warn(missing_docs)]
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cmp::min;
+use core::fmt;
+use core::result;
+
+/// The general categories of I/O error.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// A parameter was incorrect
+    InvalidInput,
+    /// Data read from a stream was not valid
+    InvalidData,
+    /// A stream ended before all the requested data was read
+    UnexpectedEof,
+    /// A stream could not accept all the data written to it
+    WriteZero,
+    /// An operation was interrupted and may be retried
+    Interrupted,
+    /// Any other error
+    Other,
+}
+
+impl ErrorKind {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            ErrorKind::InvalidInput => "invalid input parameter",
+            ErrorKind::InvalidData => "invalid data",
+            ErrorKind::UnexpectedEof => "unexpected end of file",
+            ErrorKind::WriteZero => "write zero",
+            ErrorKind::Interrupted => "operation interrupted",
+            ErrorKind::Other => "other error",
+        }
+    }
+}
+
+/// An I/O error, consisting of a kind and a static description.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    kind: ErrorKind,
+    message: &'static str,
+}
+
+impl Error {
+    /// Creates a new error of the given kind with a description.
+    pub const fn new(kind: ErrorKind, message: &'static str) -> Error {
+        Error{kind, message}
+    }
+
+    /// Returns the error's kind.
+    pub const fn kind(&self) -> ErrorKind {self.kind}
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Error {
+        Error{kind, message: kind.as_str()}
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+/// A specialized `Result` type for I/O operations.
+pub type Result<T> = result::Result<T, Error>;
+
+/// A source of bytes.
+pub trait Read {
+    /// Reads some bytes into the buffer, returning how many were read.
+    /// Returns 0 at the end of the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from the underlying source.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+    /// Reads exactly enough bytes to fill the buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `UnexpectedEof` error if the stream ends first,
+    /// or any other error from the underlying source.
+    fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+        while !buf.is_empty() {
+            match self.read(buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    let tmp = buf;
+                    buf = &mut tmp[n..];
+                }
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if buf.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::new(ErrorKind::UnexpectedEof,
+                           "failed to fill whole buffer"))
+        }
+    }
+}
+
+/// A sink for bytes.
+pub trait Write {
+    /// Writes some bytes from the buffer, returning how many were written.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from the underlying sink.
+    fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+    /// Flushes any buffered bytes to the underlying sink.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from the underlying sink.
+    fn flush(&mut self) -> Result<()>;
+
+    /// Writes all bytes from the buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `WriteZero` error if the sink stops accepting bytes,
+    /// or any other error from the underlying sink.
+    fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+        while !buf.is_empty() {
+            match self.write(buf) {
+                Ok(0) => {
+                    return Err(Error::new(ErrorKind::WriteZero,
+                                          "failed to write whole buffer"));
+                }
+                Ok(n) => buf = &buf[n..],
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Read for &[u8] {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let amount = min(buf.len(), self.len());
+        let (a, b) = self.split_at(amount);
+        buf[..amount].copy_from_slice(a);
+        *self = b;
+        Ok(amount)
+    }
+}
+
+impl<R: Read + ?Sized> Read for &mut R {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        (**self).read(buf)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R: Read + ?Sized> Read for Box<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        (**self).read(buf)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Write for Vec<u8> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<()> {Ok(())}
+}
+
+impl Write for &mut [u8] {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let amount = min(buf.len(), self.len());
+        let (a, b) = ::core::mem::take(self).split_at_mut(amount);
+        a.copy_from_slice(&buf[..amount]);
+        *self = b;
+        Ok(amount)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<()> {Ok(())}
+}
+
+impl<W: Write + ?Sized> Write for &mut W {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        (**self).write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<()> {
+        (**self).flush()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: Write + ?Sized> Write for Box<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        (**self).write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<()> {
+        (**self).flush()
+    }
+}
+
+/// Wraps an in-memory buffer to give it a read position.
+#[derive(Clone, Debug, Default)]
+pub struct Cursor<T> {
+    inner: T,
+    position: u64,
+}
+
+impl<T> Cursor<T> {
+    /// Wraps the buffer with a position of 0.
+    pub fn new(inner: T) -> Cursor<T> {
+        Cursor{inner, position: 0}
+    }
+
+    /// Returns the wrapped buffer.
+    pub fn into_inner(self) -> T {self.inner}
+
+    /// Returns a reference to the wrapped buffer.
+    pub fn get_ref(&self) -> &T {&self.inner}
+
+    /// Returns the current position.
+    pub fn position(&self) -> u64 {self.position}
+
+    /// Sets the current position.
+    pub fn set_position(&mut self, position: u64) {self.position = position}
+}
+
+impl<T: AsRef<[u8]>> Read for Cursor<T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let data = self.inner.as_ref();
+        let start = min(self.position, data.len() as u64) as usize;
+        let amount = (&data[start..]).read(buf)?;
+        self.position += amount as u64;
+        Ok(amount)
+    }
+}
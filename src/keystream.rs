@@ -0,0 +1,177 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Encrypting and decrypting streams with a stream cipher.
+//!
+//! This module requires the `keystream` feature,
+//! which doesn't need the standard library or an allocator.
+//!
+//! `CipherReader` and `CipherWriter` XOR the keystream
+//! of a stream cipher, such as AES-CTR or ChaCha, with the bytes
+//! passing through them, beneath a `BitReader` or `BitWriter`.
+//! Fields may then be read from or written to an encrypted payload
+//! directly, without decrypting it to a buffer first.
+//!
+//! Any cipher implementing `cipher::StreamCipher` may be used,
+//! such as `ctr::Ctr128BE<aes::Aes128>` or `chacha20::ChaCha20`,
+//! initialized with the payload's key and nonce.
+//!
+//! Because a keystream is consumed one byte per byte of the stream,
+//! a few things are needed to keep it in step:
+//!
+//! * a `CipherWriter` only sees whole bytes,
+//!   so its `BitWriter` must be byte-aligned
+//!   before the last of the payload is considered written
+//! * a `CipherWriter` hands every byte to its sink with `write_all`,
+//!   and a sink which fails part way through leaves the keystream
+//!   ahead of the bytes written, after which the writer
+//!   shouldn't be used
+//! * neither wrapper can seek, since that would skip
+//!   or repeat part of the keystream
+//!
+//! ## Example
+//!
+//! ```
+//! extern crate chacha20;
+//! # extern crate bitstream_io;
+//! use chacha20::ChaCha20;
+//! use chacha20::cipher::KeyIvInit;
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::keystream::{CipherReader, CipherWriter};
+//!
+//! # fn main() {
+//! let key = [0x42; 32];
+//! let nonce = [0x24; 12];
+//!
+//! let mut payload = [0; 2];
+//! {
+//!     let cipher = ChaCha20::new(&key.into(), &nonce.into());
+//!     let mut sink = CipherWriter::new(&mut payload[..], cipher);
+//!     let mut writer = BitWriter::<BigEndian, _>::from_write(&mut sink);
+//!     writer.write(4, 0xAu8).unwrap();
+//!     writer.write(12, 0x123u16).unwrap();
+//! }
+//! assert_ne!(payload, [0xA1, 0x23]);
+//!
+//! let cipher = ChaCha20::new(&key.into(), &nonce.into());
+//! let source = CipherReader::new(&payload[..], cipher);
+//! let mut reader = BitReader::<BigEndian, _>::from_read(source);
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0xA);
+//! assert_eq!(reader.read::<u16>(12).unwrap(), 0x123);
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+use cipher::StreamCipher;
+use io;
+
+/// Decrypts the bytes read from a stream with a stream cipher.
+#[derive(Clone, Debug)]
+pub struct CipherReader<R, C> {
+    inner: R,
+    cipher: C,
+}
+
+impl<R: io::Read, C: StreamCipher> CipherReader<R, C> {
+    /// Wraps the stream, with the cipher's keystream positioned
+    /// at the stream's next byte.
+    #[inline]
+    pub fn new(inner: R, cipher: C) -> CipherReader<R, C> {
+        CipherReader{inner, cipher}
+    }
+
+    /// Returns a reference to the stream.
+    #[inline]
+    pub fn get_ref(&self) -> &R {&self.inner}
+
+    /// Returns a mutable reference to the stream.
+    /// Bytes read from it directly aren't decrypted
+    /// and put the keystream out of step.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {&mut self.inner}
+
+    /// Returns the stream and its cipher.
+    #[inline]
+    pub fn into_inner(self) -> (R, C) {(self.inner, self.cipher)}
+}
+
+impl<R: io::Read, C: StreamCipher> io::Read for CipherReader<R, C> {
+    /// Returns an error if the cipher's keystream runs out,
+    /// in which case the bytes read are lost.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.cipher.try_apply_keystream(&mut buf[..read])
+            .map_err(|_| keystream_exhausted())?;
+        Ok(read)
+    }
+}
+
+/// Encrypts the bytes written to a stream with a stream cipher.
+#[derive(Clone, Debug)]
+pub struct CipherWriter<W, C> {
+    inner: W,
+    cipher: C,
+}
+
+impl<W: io::Write, C: StreamCipher> CipherWriter<W, C> {
+    /// Wraps the stream, with the cipher's keystream positioned
+    /// at the stream's next byte.
+    #[inline]
+    pub fn new(inner: W, cipher: C) -> CipherWriter<W, C> {
+        CipherWriter{inner, cipher}
+    }
+
+    /// Returns a reference to the stream.
+    #[inline]
+    pub fn get_ref(&self) -> &W {&self.inner}
+
+    /// Returns a mutable reference to the stream.
+    /// Bytes written to it directly aren't encrypted
+    /// and don't advance the keystream.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {&mut self.inner}
+
+    /// Returns the stream and its cipher.
+    #[inline]
+    pub fn into_inner(self) -> (W, C) {(self.inner, self.cipher)}
+}
+
+impl<W: io::Write, C: StreamCipher> io::Write for CipherWriter<W, C> {
+    /// Returns an error if the cipher's keystream runs out,
+    /// before writing any of the bytes it couldn't encrypt.
+    /// If an error occurs after some bytes have been written,
+    /// their count is returned instead, as with any short write.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // the keystream can't be rewound, so every byte it's applied to
+        // must reach the stream before it's counted as written
+        let mut chunk = [0; 256];
+        let mut written = 0;
+        for bytes in buf.chunks(chunk.len()) {
+            let chunk = &mut chunk[..bytes.len()];
+            chunk.copy_from_slice(bytes);
+            let result = self.cipher.try_apply_keystream(chunk)
+                .map_err(|_| keystream_exhausted())
+                .and_then(|()| self.inner.write_all(chunk));
+            match result {
+                Ok(()) => written += chunk.len(),
+                Err(_) if written > 0 => return Ok(written),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(written)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {self.inner.flush()}
+}
+
+fn keystream_exhausted() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput,
+                   "stream too long for cipher's keystream")
+}
@@ -22,24 +22,212 @@
 //! In addition, reader streams do not consume any more bytes
 //! from the underlying reader than necessary, buffering only a
 //! single partial byte as needed.
+//! Buffered streams may also be read through a `word::WordReader`,
+//! which caches 64 bits at a time from the stream's buffer
+//! with the same guarantee, and is much faster for many small fields.
 //! Writer streams also write out all whole bytes as they are accumulated.
 //!
 //! Readers and writers are also designed to work with integer
 //! types of any possible size.
 //! Many of Rust's built-in integer types are supported by default.
+//!
+//! The default `std` feature may be disabled in favor of the `alloc`
+//! feature for use without the standard library.
+//! Streams then use the crate's own minimal `io` module,
+//! which reads from byte slices and `Cursor`s and writes to `Vec<u8>`,
+//! and the `derive`, `serde`, `num-bigint`, `test-util`, `tokio-util`,
+//! `futures`, `mmap`, `wasm`, `ffi`, `python`, `arbitrary`, `rayon`,
+//! `prefetch` and `simd` features are unavailable.
+//!
+//! With neither feature, nothing is allocated at all.
+//! Readers are then built with `BitReader::from_read` over byte slices,
+//! writers write to mutable byte slices,
+//! and Huffman codes are read and written with fixed tables
+//! of `huffman::HuffmanCode` values rather than compiled trees.
+//! Modules which need to allocate are unavailable.
+//! The `embedded-io` feature lets such streams read from
+//! and write to drivers implementing that crate's traits.
+//!
+//! Nothing outside of the `mmap`, `rayon`, `prefetch` and `simd` features
+//! depends on the platform, so the crate builds for
+//! `wasm32-unknown-unknown` as-is.
+//! The `wasm` feature also exports slice-based readers and writers
+//! to JavaScript through `wasm-bindgen`.
+//!
+//! The opt-in `unchecked-bitqueue` feature drops the checks
+//! on `BitQueue` lengths from release builds,
+//! which speeds up decoding in tight loops.
+//! Debug builds keep them as assertions.
+//! It applies to `BitQueue` alone and involves no `unsafe` code,
+//! so a queue misused without those checks gives wrong values
+//! but never reads out of bounds.
+//! Huffman tree lookups, and every other index into a slice,
+//! remain bounds-checked.
+//!
+//! The opt-in `perf-counters` feature has readers and writers
+//! count their calls to the underlying stream, the bytes transferred
+//! and the operations they had to shift for lack of alignment,
+//! as described in the `perf` module.
+//!
+//! The opt-in `keystream` feature adds the `keystream` module,
+//! whose adaptors decrypt and encrypt the bytes beneath a stream
+//! with any `cipher::StreamCipher`, such as AES-CTR or ChaCha.
 
 #![warn(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(feature = "alloc")]
+#[cfg_attr(not(feature = "std"), macro_use)]
+extern crate alloc;
+
+use core::ops::{Shl, ShlAssign, Shr, ShrAssign, Rem, RemAssign, BitOrAssign,
+                BitXor, Not, Sub};
+use core::marker::PhantomData;
+use core::fmt::Debug;
+
+/// The I/O traits and error type used throughout this crate,
+/// which are this crate's own `io` module without the `std` feature.
+#[cfg(feature = "std")]
+pub use std::io;
+#[cfg(not(feature = "std"))]
+pub mod io;
 
-use std::ops::{Shl, ShlAssign, Shr, ShrAssign, Rem, RemAssign, BitOrAssign,
-               BitXor, Not, Sub};
-use std::marker::PhantomData;
-use std::fmt::Debug;
+#[cfg(all(feature = "num-bigint", feature = "std"))]
+extern crate num_bigint;
+#[cfg(feature = "derive")]
+extern crate bitstream_io_derive;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "bitflags")]
+extern crate bitflags;
+#[cfg(feature = "bytes")]
+extern crate bytes;
+#[cfg(feature = "embedded-io")]
+extern crate embedded_io;
+#[cfg(feature = "tokio-util")]
+extern crate tokio_util;
+#[cfg(feature = "futures")]
+extern crate futures_core;
+#[cfg(feature = "futures")]
+extern crate futures_io;
+#[cfg(feature = "bitvec")]
+extern crate bitvec;
+#[cfg(feature = "half")]
+extern crate half;
+#[cfg(feature = "mmap")]
+extern crate memmap2;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
+#[cfg(feature = "python")]
+extern crate pyo3;
+#[cfg(feature = "arbitrary")]
+extern crate arbitrary;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "test-util")]
+extern crate proptest;
+#[cfg(feature = "keystream")]
+extern crate cipher;
 
 pub mod read;
 pub mod write;
 pub mod huffman;
-pub use read::BitReader;
-pub use write::BitWriter;
+pub mod delta;
+pub mod bitplane;
+pub mod zigzag;
+#[cfg(feature = "alloc")]
+pub mod elias_fano;
+pub mod varint;
+pub mod crc;
+#[cfg(feature = "alloc")]
+pub mod frame;
+pub mod linecode;
+pub mod random;
+pub mod transcode;
+#[cfg(feature = "alloc")]
+pub mod interleave;
+#[cfg(feature = "alloc")]
+pub mod mux;
+#[cfg(feature = "alloc")]
+pub mod diff;
+#[cfg(feature = "alloc")]
+pub mod builder;
+pub mod mixed;
+#[cfg(feature = "alloc")]
+pub mod format;
+#[cfg(feature = "alloc")]
+pub mod combinators;
+pub mod inspect;
+#[cfg(feature = "fec")]
+pub mod hamming;
+#[cfg(all(feature = "convolutional", feature = "alloc"))]
+pub mod convolutional;
+#[cfg(feature = "serde")]
+pub mod serde_bits;
+#[cfg(feature = "bitflags")]
+pub mod flags;
+#[cfg(feature = "bytes")]
+pub mod buf;
+#[cfg(feature = "embedded-io")]
+pub mod embedded;
+#[cfg(feature = "tokio-util")]
+pub mod codec;
+#[cfg(feature = "futures")]
+pub mod stream;
+#[cfg(feature = "bitvec")]
+pub mod bits;
+#[cfg(feature = "half")]
+pub mod half_float;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+#[cfg(feature = "prefetch")]
+pub mod prefetch;
+#[cfg(feature = "std")]
+pub mod tap;
+#[cfg(feature = "std")]
+pub mod meter;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod context;
+#[cfg(feature = "std")]
+pub mod history;
+#[cfg(feature = "std")]
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod word;
+pub mod unpack;
+pub mod pack;
+pub mod chunks;
+#[cfg(feature = "perf-counters")]
+pub mod perf;
+#[cfg(feature = "keystream")]
+pub mod keystream;
+pub mod tables;
+mod minifloat;
+mod bitfields;
+#[cfg(all(feature = "num-bigint", feature = "std"))]
+mod bigint;
+pub use read::{AlignmentGuard, BitRead, BitReader, FromBitStream,
+               FromBitStreamWith, LimitedReader};
+pub use write::{BitWrite, BitWriter, ToBitStream, ToBitStreamWith};
+pub use inspect::Describe;
+#[cfg(feature = "derive")]
+pub use bitstream_io_derive::{ConstBits, Describe, FromBitStream, ToBitStream};
 
 
 /// This trait extends many common integer types (both unsigned and signed)
@@ -64,6 +252,15 @@ pub trait Numeric: Sized + Copy + Default + Debug + PartialOrd +
     /// Assuming 0 <= value < 256, returns this value as a `u8` type
     fn to_u8(self) -> u8;
 
+    /// Returns the lowest bits of a `u64` value in this type
+    fn from_u64(u: u64) -> Self {
+        let mut value = Self::default();
+        for shift in (0..Self::bits_size().min(64)).step_by(8) {
+            value |= Self::from_u8((u >> shift) as u8) << shift;
+        }
+        value
+    }
+
     /// Counts the number of 1 bits
     fn count_ones(self) -> u32;
 
@@ -75,6 +272,40 @@ pub trait Numeric: Sized + Copy + Default + Debug + PartialOrd +
 
     /// Size of type in bits
     fn bits_size() -> u32;
+
+    /// Returns only the lowest `bits` bits of this value,
+    /// with all higher bits cleared.
+    /// Values are returned as-is if `bits` is at least
+    /// the size of the type.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::Numeric;
+    /// assert_eq!(0xABCDu16.truncate(8), 0xCD);
+    /// assert_eq!(0xABCDu16.truncate(0), 0);
+    /// assert_eq!(0xABCDu16.truncate(16), 0xABCD);
+    /// assert_eq!((-1i8).truncate(4), 0b1111);
+    /// ```
+    fn truncate(self, bits: u32) -> Self {
+        if bits >= Self::bits_size() {
+            self
+        } else {
+            // the value's higher bits, shifted out and back,
+            // are the bits to clear
+            self ^ ((self >> bits) << bits)
+        }
+    }
+}
+
+/// This trait extends the built-in integer types with
+/// their zero value and size as constants,
+/// so that a `BitQueue` of them may be built in constant expressions.
+pub trait ConstNumeric: Numeric {
+    /// The value of 0 in this type
+    const ZERO: Self;
+
+    /// The size of this type in bits
+    const BITS_SIZE: u32;
 }
 
 macro_rules! define_numeric {
@@ -89,6 +320,8 @@ macro_rules! define_numeric {
             #[inline(always)]
             fn to_u8(self) -> u8 {self as u8}
             #[inline(always)]
+            fn from_u64(u: u64) -> Self {u as $t}
+            #[inline(always)]
             fn count_ones(self) -> u32 {self.count_ones()}
             #[inline(always)]
             fn leading_zeros(self) -> u32 {self.leading_zeros()}
@@ -96,7 +329,65 @@ macro_rules! define_numeric {
             fn trailing_zeros(self) -> u32 {self.trailing_zeros()}
             #[inline(always)]
             fn bits_size() -> u32 {$bits}
+            #[inline]
+            fn truncate(self, bits: u32) -> Self {
+                if bits >= $bits {self} else {self & !(!0 << bits)}
+            }
         }
+
+        impl ConstNumeric for $t {
+            const ZERO: Self = 0;
+            const BITS_SIZE: u32 = $bits;
+        }
+    }
+}
+
+// Returns true if the endianness reads bits starting from
+// the least significant bit of each byte.
+#[inline(always)]
+pub(crate) fn is_little_endian<E: Endianness + ?Sized>() -> bool {
+    let mut bits = 0;
+    let mut value = 0u8;
+    E::push(&mut bits, &mut value, 1, 1);
+    E::push(&mut bits, &mut value, 7, 0);
+    value == 1
+}
+
+// Returns true if the value is 2^bits or more, and so too large
+// to write in the given number of bits.
+// For signed types, 2^bits may be out of the type's range,
+// in which case no value is too large.
+#[inline]
+pub(crate) fn exceeds_bits<N: Numeric>(value: N, bits: u32) -> bool {
+    if bits < N::bits_size() {
+        let limit = N::one() << bits;
+        limit > N::default() && value >= limit
+    } else {
+        false
+    }
+}
+
+/// Returns the number of bits `write_ranged` uses for values
+/// from `min` to `max` inclusive, which is 0 if the range
+/// holds only a single value.
+///
+/// Since this is a `const fn`, the width of a constant range
+/// may itself be a constant, as when sizing a packet's fields.
+///
+/// # Example
+/// ```
+/// use bitstream_io::ranged_bits;
+/// const HEALTH_BITS: u32 = ranged_bits(0, 100);
+/// assert_eq!(HEALTH_BITS, 7);
+/// assert_eq!(ranged_bits(-512, 511), 10);
+/// assert_eq!(ranged_bits(i64::MIN, i64::MAX), 64);
+/// assert_eq!(ranged_bits(5, 5), 0);
+/// ```
+pub const fn ranged_bits(min: i64, max: i64) -> u32 {
+    if max > min {
+        64 - (max.wrapping_sub(min) as u64).leading_zeros()
+    } else {
+        0
     }
 }
 
@@ -113,6 +404,32 @@ pub trait SignedNumeric: Numeric {
     /// Given a negative value and a certain number of bits,
     /// returns this value as a twos-complement positive number.
     fn as_unsigned(self, bits: u32) -> Self;
+
+    /// Given a twos-complement value in the lowest `bits` bits,
+    /// returns it sign-extended to the full size of the type.
+    /// Any higher bits are ignored.
+    /// Values are returned as-is if `bits` is at least
+    /// the size of the type, and 0 is returned if `bits` is 0.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::SignedNumeric;
+    /// assert_eq!(0b0111i8.sign_extend(4), 7);
+    /// assert_eq!(0b1011i8.sign_extend(4), -5);
+    /// assert_eq!(0xFF_FFFFi32.sign_extend(24), -1);
+    /// assert_eq!(0x1_7F_FFFFi32.sign_extend(24), 0x7F_FFFF);
+    /// assert_eq!((-1i32).sign_extend(0), 0);
+    /// ```
+    fn sign_extend(self, bits: u32) -> Self {
+        if bits == 0 {
+            Self::default()
+        } else if bits >= Self::bits_size() {
+            self
+        } else {
+            let shift = Self::bits_size() - bits;
+            (self << shift) >> shift
+        }
+    }
 }
 
 macro_rules! define_signed_numeric {
@@ -136,11 +453,99 @@ define_numeric!(u32, 32);
 define_numeric!(i32, 32);
 define_numeric!(u64, 64);
 define_numeric!(i64, 64);
+define_numeric!(u128, 128);
+define_numeric!(i128, 128);
 
 define_signed_numeric!(i8);
 define_signed_numeric!(i16);
 define_signed_numeric!(i32);
 define_signed_numeric!(i64);
+define_signed_numeric!(i128);
+
+/// A fixed-size primitive type which converts to and from
+/// big-endian and little-endian bytes.
+///
+/// This allows byte-aligned values to be read and written
+/// whole, without going through a bit accumulator.
+pub trait Primitive: Sized {
+    /// The type's representation as bytes, such as `[u8; 4]`
+    type Bytes: AsRef<[u8]> + AsMut<[u8]>;
+
+    /// Returns a byte representation filled with 0s
+    fn buffer() -> Self::Bytes;
+
+    /// Returns this value as big-endian bytes
+    fn to_be_bytes(self) -> Self::Bytes;
+
+    /// Returns this value as little-endian bytes
+    fn to_le_bytes(self) -> Self::Bytes;
+
+    /// Builds a value from big-endian bytes
+    fn from_be_bytes(bytes: Self::Bytes) -> Self;
+
+    /// Builds a value from little-endian bytes
+    fn from_le_bytes(bytes: Self::Bytes) -> Self;
+}
+
+macro_rules! define_primitive {
+    ($t:ty, $bytes:expr) => {
+        impl Primitive for $t {
+            type Bytes = [u8; $bytes];
+            #[inline(always)]
+            fn buffer() -> Self::Bytes {[0; $bytes]}
+            #[inline(always)]
+            fn to_be_bytes(self) -> Self::Bytes {self.to_be_bytes()}
+            #[inline(always)]
+            fn to_le_bytes(self) -> Self::Bytes {self.to_le_bytes()}
+            #[inline(always)]
+            fn from_be_bytes(bytes: Self::Bytes) -> Self {
+                <$t>::from_be_bytes(bytes)
+            }
+            #[inline(always)]
+            fn from_le_bytes(bytes: Self::Bytes) -> Self {
+                <$t>::from_le_bytes(bytes)
+            }
+        }
+    }
+}
+
+define_primitive!(u8, 1);
+define_primitive!(i8, 1);
+define_primitive!(u16, 2);
+define_primitive!(i16, 2);
+define_primitive!(u32, 4);
+define_primitive!(i32, 4);
+define_primitive!(u64, 8);
+define_primitive!(i64, 8);
+define_primitive!(u128, 16);
+define_primitive!(i128, 16);
+define_primitive!(f32, 4);
+define_primitive!(f64, 8);
+
+/// A type whose size in bits, when read from or written to
+/// a stream, is known at compile time.
+///
+/// This allows containers to compute field offsets,
+/// validate frame sizes and reserve space for values
+/// to be written later.
+///
+/// # Example
+/// ```
+/// use bitstream_io::ConstBits;
+///
+/// struct Header;
+///
+/// impl ConstBits for Header {
+///     const BITS: u32 = 4 + 12;
+/// }
+///
+/// const FRAME_BITS: u32 = Header::BITS + 8 * 128;
+/// assert_eq!(FRAME_BITS, 1040);
+/// ```
+pub trait ConstBits {
+    /// The number of bits the type occupies in a stream
+    const BITS: u32;
+}
 
 /// A stream's endianness, or byte order, for determining
 /// how bits should be read.
@@ -176,6 +581,53 @@ pub trait Endianness {
     /// Returns the next number of 1 bits from an accumulator
     /// with the given bits and value.
     fn next_ones<N>(bits: u32, value: N) -> u32 where N: Numeric;
+
+    /// Reads a twos-complement signed value from the given reader
+    /// with the given number of bits,
+    /// which must be from 1 to the size of the output type.
+    fn read_signed<R, S>(r: &mut R, bits: u32) -> Result<S, io::Error>
+        where R: BitRead + ?Sized, S: SignedNumeric {
+
+        // the sign bit is read along with the rest of the value,
+        // as the value's highest bit
+        r.read::<S>(bits).map(|value| value.sign_extend(bits))
+    }
+
+    /// Writes a twos-complement signed value to the given writer
+    /// with the given number of bits,
+    /// which must be from 1 to the size of the input type.
+    fn write_signed<W, S>(w: &mut W, bits: u32, value: S) ->
+        Result<(), io::Error> where W: BitWrite + ?Sized, S: SignedNumeric {
+
+        // the sign bit is written along with the rest of the value,
+        // as the value's highest bit
+        let unsigned = value.truncate(bits);
+        if unsigned.sign_extend(bits) == value {
+            w.write(bits, unsigned)
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidInput,
+                               "excessive value for bits written"))
+        }
+    }
+
+    /// Converts a primitive value from bytes in this byte order.
+    fn from_bytes<V>(bytes: V::Bytes) -> V where V: Primitive {
+        // bytes are in the same order as the bits pushed
+        if is_little_endian::<Self>() {
+            V::from_le_bytes(bytes)
+        } else {
+            V::from_be_bytes(bytes)
+        }
+    }
+
+    /// Converts a primitive value to bytes in this byte order.
+    fn to_bytes<V>(value: V) -> V::Bytes where V: Primitive {
+        if is_little_endian::<Self>() {
+            value.to_le_bytes()
+        } else {
+            value.to_be_bytes()
+        }
+    }
 }
 
 /// Big-endian, or most significant bits first
@@ -201,7 +653,10 @@ impl Endianness for BigEndian {
     fn pop<N>(bits_acc: &mut u32,
               value_acc: &mut N,
               bits: u32) -> N where N: Numeric {
-        if bits < *bits_acc {
+        if bits == 0 {
+            // shifting a full accumulator by its own size would overflow
+            N::default()
+        } else if bits < *bits_acc {
             let offset = *bits_acc - bits;
             let to_return = *value_acc >> offset;
             *value_acc %= N::one() << offset;
@@ -219,7 +674,9 @@ impl Endianness for BigEndian {
     fn drop<N>(bits_acc: &mut u32,
                value_acc: &mut N,
                bits: u32) where N: Numeric {
-        if bits < *bits_acc {
+        if bits == 0 {
+            // nothing to drop
+        } else if bits < *bits_acc {
             *value_acc %= N::one() << (*bits_acc - bits);
             *bits_acc -= bits;
         } else {
@@ -242,6 +699,28 @@ impl Endianness for BigEndian {
             (!value).leading_zeros()
         }
     }
+
+    fn write_signed<W, S>(w: &mut W, bits: u32, value: S) ->
+        Result<(), io::Error> where W: BitWrite + ?Sized, S: SignedNumeric {
+
+        if value.is_negative() {
+            w.write_bit(true)
+            .and_then(|()| w.write(bits - 1, value.as_unsigned(bits)))
+        } else {
+            w.write_bit(false)
+            .and_then(|()| w.write(bits - 1, value))
+        }
+    }
+
+    #[inline]
+    fn from_bytes<V>(bytes: V::Bytes) -> V where V: Primitive {
+        V::from_be_bytes(bytes)
+    }
+
+    #[inline]
+    fn to_bytes<V>(value: V) -> V::Bytes where V: Primitive {
+        value.to_be_bytes()
+    }
 }
 
 /// Little-endian, or least significant bits first
@@ -302,10 +781,74 @@ impl Endianness for LittleEndian {
     fn next_ones<N>(_: u32, value: N) -> u32 where N: Numeric {
         (value ^ !N::default()).trailing_zeros()
     }
+
+    fn write_signed<W, S>(w: &mut W, bits: u32, value: S) ->
+        Result<(), io::Error> where W: BitWrite + ?Sized, S: SignedNumeric {
+
+        if value.is_negative() {
+            w.write(bits - 1, value.as_unsigned(bits))
+            .and_then(|()| w.write_bit(true))
+        } else {
+            w.write(bits - 1, value)
+            .and_then(|()| w.write_bit(false))
+        }
+    }
+
+    #[inline]
+    fn from_bytes<V>(bytes: V::Bytes) -> V where V: Primitive {
+        V::from_le_bytes(bytes)
+    }
+
+    #[inline]
+    fn to_bytes<V>(value: V) -> V::Bytes where V: Primitive {
+        value.to_le_bytes()
+    }
+}
+
+// asserts the given condition, except in release builds
+// with the `unchecked-bitqueue` feature, where it's assumed to hold
+macro_rules! check {
+    ($($arg:tt)*) => {
+        if cfg!(feature = "unchecked-bitqueue") {
+            debug_assert!($($arg)*)
+        } else {
+            assert!($($arg)*)
+        }
+    }
 }
 
 /// A queue for efficiently pushing bits onto a value
 /// and popping them off a value.
+///
+/// This is the accumulator at the heart of the readers and writers,
+/// and is also useful on its own for building custom coders
+/// which need to gather or split up bits in a given endianness.
+/// Any `Numeric` type may back the queue, from `u8` up to `u128`,
+/// and its size determines how many bits the queue may hold.
+///
+/// Bits are pushed onto the tail of the queue and popped off its head,
+/// so a big-endian queue pops its most significant bits first
+/// while a little-endian queue pops its least significant bits first.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, LittleEndian, BitQueue};
+///
+/// let mut queue: BitQueue<BigEndian, u128> = BitQueue::new();
+/// queue.push(4, 0b1011);
+/// queue.push(100, 1);
+/// assert_eq!(queue.len(), 104);
+/// assert_eq!(queue.peek(4), 0b1011);
+/// assert_eq!(queue.pop(4), 0b1011);
+/// assert_eq!(queue.pop(100), 1);
+/// assert!(queue.is_empty());
+///
+/// let mut queue: BitQueue<LittleEndian, u64> = BitQueue::new();
+/// queue.push(4, 0b1011);
+/// queue.push(4, 0b0001);
+/// assert_eq!(queue.pop(2), 0b11);
+/// assert_eq!(queue.value(), 0b0001_10);
+/// ```
 pub struct BitQueue<E: Endianness, N: Numeric> {
     phantom: PhantomData<E>,
     value: N,
@@ -323,23 +866,17 @@ impl<E: Endianness, N: Numeric> BitQueue<E, N> {
     /// Panics if the value is larger than the given number of bits.
     #[inline]
     pub fn from_value(value: N, bits: u32) -> BitQueue<E,N> {
-        if bits < N::bits_size() {
-            assert!(value < (N::one() << bits));
-        } else {
-            assert!(bits <= N::bits_size());
-        }
-        BitQueue{phantom: PhantomData, value: value, bits: bits}
+        assert!(bits <= N::bits_size());
+        assert!(!exceeds_bits(value, bits));
+        BitQueue{phantom: PhantomData, value, bits}
     }
 
     /// Sets the queue to a given value with the given number of bits
     /// Panics if the value is larger than the given number of bits
     #[inline]
     pub fn set(&mut self, value: N, bits: u32) {
-        if bits < N::bits_size() {
-            assert!(value < (N::one() << bits));
-        } else {
-            assert!(bits <= N::bits_size());
-        }
+        assert!(bits <= N::bits_size());
+        assert!(!exceeds_bits(value, bits));
         self.value = value;
         self.bits = bits;
     }
@@ -350,7 +887,7 @@ impl<E: Endianness, N: Numeric> BitQueue<E, N> {
 
     /// Returns the total bits in the queue
     #[inline(always)]
-    pub fn len(&self) -> u32 {self.bits}
+    pub const fn len(&self) -> u32 {self.bits}
 
     /// Returns the maximum bits the queue can hold
     #[inline(always)]
@@ -362,7 +899,7 @@ impl<E: Endianness, N: Numeric> BitQueue<E, N> {
 
     /// Returns true if the queue is empty
     #[inline(always)]
-    pub fn is_empty(&self) -> bool {self.bits == 0}
+    pub const fn is_empty(&self) -> bool {self.bits == 0}
 
     /// Returns true if the queue is full
     #[inline(always)]
@@ -384,7 +921,7 @@ impl<E: Endianness, N: Numeric> BitQueue<E, N> {
     /// Panics if the number of bits pushed is larger than the queue can hold.
     #[inline(always)]
     pub fn push(&mut self, bits: u32, value: N) {
-        assert!(bits <= self.remaining_len());  // check for overflow
+        check!(bits <= self.remaining_len());  // check for overflow
         E::push(&mut self.bits, &mut self.value, bits, value)
     }
 
@@ -393,20 +930,43 @@ impl<E: Endianness, N: Numeric> BitQueue<E, N> {
     /// of bits in the queue.
     #[inline(always)]
     pub fn pop(&mut self, bits: u32) -> N {
-        assert!(bits <= self.len());  // check for underflow
+        check!(bits <= self.len());  // check for underflow
         E::pop(&mut self.bits, &mut self.value, bits)
     }
 
+    /// Returns a value with the given number of bits from the head
+    /// of the queue without removing them.
+    /// Panics if the number of bits peeked is larger than the number
+    /// of bits in the queue.
+    #[inline]
+    pub fn peek(&self, bits: u32) -> N {
+        self.clone().pop(bits)
+    }
+
     /// Drops the given number of bits from the head of the queue
     /// without returning them.
     /// Panics if the number of bits dropped is larger than the
     /// number of bits in the queue.
     #[inline(always)]
     pub fn drop(&mut self, bits: u32) {
-        assert!(bits <= self.len());  // check for underflow
+        check!(bits <= self.len());  // check for underflow
         E::drop(&mut self.bits, &mut self.value, bits)
     }
 
+    /// Returns the number of 0 bits at the head of the queue,
+    /// which is the queue's length if all of its bits are 0
+    #[inline]
+    pub fn leading_0s(&self) -> u32 {
+        core::cmp::min(E::next_zeros(self.bits, self.value), self.bits)
+    }
+
+    /// Returns the number of 1 bits at the head of the queue,
+    /// which is the queue's length if all of its bits are 1
+    #[inline]
+    pub fn leading_1s(&self) -> u32 {
+        core::cmp::min(E::next_ones(self.bits, self.value), self.bits)
+    }
+
     /// Pops all 0 bits up to and including the next 1 bit
     /// and returns the amount of 0 bits popped
     #[inline]
@@ -426,6 +986,38 @@ impl<E: Endianness, N: Numeric> BitQueue<E, N> {
     }
 }
 
+impl<E: Endianness, N: ConstNumeric> BitQueue<E, N> {
+    /// Returns a new empty queue, in a constant expression
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitQueue};
+    /// const EMPTY: BitQueue<BigEndian, u32> = BitQueue::empty();
+    /// let mut queue = EMPTY;
+    /// queue.push(3, 0b101);
+    /// assert_eq!(queue.len(), 3);
+    /// assert!(EMPTY.is_empty());
+    /// ```
+    #[inline]
+    pub const fn empty() -> BitQueue<E, N> {
+        BitQueue{phantom: PhantomData, value: N::ZERO, bits: 0}
+    }
+}
+
+impl<E: Endianness, N: Numeric> Clone for BitQueue<E, N> {
+    #[inline]
+    fn clone(&self) -> Self {
+        BitQueue{phantom: PhantomData, value: self.value, bits: self.bits}
+    }
+}
+
+impl<E: Endianness, N: Numeric> Default for BitQueue<E, N> {
+    #[inline]
+    fn default() -> Self {
+        BitQueue::new()
+    }
+}
+
 impl<E: Endianness> BitQueue<E, u8> {
     /// Returns the state of the queue as a single value
     /// which can be used to perform lookups.
@@ -0,0 +1,230 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Adaptors for reading or writing line-coded bits.
+//!
+//! A line code maps logical bits to the physical levels
+//! sent over a wire or radio link.
+//! These adaptors convert between the two, so that logical values
+//! may be read from or written to a stream of captured levels,
+//! where a 1 bit is a high level and a 0 bit is a low level.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Write, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::linecode::{LineCode, LineReader, LineWriter};
+//!
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     let mut line = LineWriter::new(&mut writer, LineCode::ManchesterIeee);
+//!     line.write(4, 0b1001).unwrap();
+//! }
+//! assert_eq!(data, [0b01_10_10_01]);
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! let mut line = LineReader::new(&mut reader, LineCode::ManchesterIeee);
+//! assert_eq!(line.read(4).unwrap(), 0b1001);
+//! ```
+
+#![warn(missing_docs)]
+
+use io;
+
+use super::{BitReader, BitWriter, BitQueue, Endianness};
+
+/// A mapping between logical bits and physical levels.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineCode {
+    /// Manchester coding as in IEEE 802.3,
+    /// where a 0 is sent as a high level followed by a low level
+    /// and a 1 is sent as a low level followed by a high level
+    ManchesterIeee,
+    /// Manchester coding as originally described by G. E. Thomas,
+    /// where a 1 is sent as a high level followed by a low level
+    /// and a 0 is sent as a low level followed by a high level
+    ManchesterThomas,
+    /// Non-return-to-zero inverted coding,
+    /// where each logical bit is sent as a single level
+    Nrzi {
+        /// The logical bit which is sent as a change in level,
+        /// with the other bit sent as an unchanged level.
+        /// This is `true` for NRZI-M and `false` for NRZI-S (as in USB).
+        transition: bool,
+        /// The line's level before the first bit is sent
+        initial: bool,
+    },
+}
+
+impl LineCode {
+    // the line's level before the first bit is sent
+    fn initial_level(&self) -> bool {
+        match *self {
+            LineCode::Nrzi{initial, ..} => initial,
+            _ => false,
+        }
+    }
+}
+
+/// Writes logical bits to a `BitWriter` as line-coded levels.
+pub struct LineWriter<'w, 'a: 'w, E: 'w + Endianness> {
+    writer: &'w mut BitWriter<'a, E>,
+    code: LineCode,
+    level: bool,
+}
+
+impl<'w, 'a: 'w, E: Endianness> LineWriter<'w, 'a, E> {
+    /// Wraps a LineWriter around a `BitWriter`
+    /// using the given line code.
+    pub fn new(writer: &'w mut BitWriter<'a, E>,
+               code: LineCode) -> LineWriter<'w, 'a, E> {
+        LineWriter{writer, code, level: code.initial_level()}
+    }
+
+    /// Writes a single logical bit.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn write_bit(&mut self, bit: bool) -> Result<(), io::Error> {
+        match self.code {
+            LineCode::ManchesterIeee => {
+                self.writer.write_bit(!bit)?;
+                self.writer.write_bit(bit)
+            }
+            LineCode::ManchesterThomas => {
+                self.writer.write_bit(bit)?;
+                self.writer.write_bit(!bit)
+            }
+            LineCode::Nrzi{transition, ..} => {
+                if bit == transition {
+                    self.level = !self.level;
+                }
+                self.writer.write_bit(self.level)
+            }
+        }
+    }
+
+    /// Writes an unsigned value of up to 64 bits,
+    /// taking its bits in the stream's order.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the number of bits is larger than 64
+    /// or if the value is too large to fit the given number of bits.
+    pub fn write(&mut self, bits: u32, value: u64) -> Result<(), io::Error> {
+        if bits > 64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive bits for type written"));
+        } else if (bits < 64) && (value >= (1 << bits)) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive value for bits written"));
+        } else if bits == 0 {
+            return Ok(());
+        }
+        let mut queue: BitQueue<E, u64> = BitQueue::from_value(value, bits);
+        while !queue.is_empty() {
+            self.write_bit(queue.pop(1) == 1)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the entirety of a byte buffer as logical bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn write_bytes(&mut self, buf: &[u8]) -> Result<(), io::Error> {
+        for byte in buf {
+            self.write(8, u64::from(*byte))?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads logical bits from a `BitReader` of line-coded levels.
+pub struct LineReader<'r, E: 'r + Endianness> {
+    reader: &'r mut BitReader<E>,
+    code: LineCode,
+    level: bool,
+}
+
+impl<'r, E: Endianness> LineReader<'r, E> {
+    /// Wraps a LineReader around a `BitReader`
+    /// using the given line code.
+    pub fn new(reader: &'r mut BitReader<E>,
+               code: LineCode) -> LineReader<'r, E> {
+        LineReader{reader, code, level: code.initial_level()}
+    }
+
+    /// Reads a single logical bit.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if a Manchester-coded bit
+    /// lacks a transition in the middle of its period.
+    pub fn read_bit(&mut self) -> Result<bool, io::Error> {
+        match self.code {
+            LineCode::ManchesterIeee | LineCode::ManchesterThomas => {
+                let first = self.reader.read_bit()?;
+                let second = self.reader.read_bit()?;
+                if first == second {
+                    Err(io::Error::new(io::ErrorKind::InvalidData,
+                                       "invalid Manchester symbol"))
+                } else if self.code == LineCode::ManchesterIeee {
+                    Ok(second)
+                } else {
+                    Ok(first)
+                }
+            }
+            LineCode::Nrzi{transition, ..} => {
+                let level = self.reader.read_bit()?;
+                let changed = level != self.level;
+                self.level = level;
+                Ok(if changed {transition} else {!transition})
+            }
+        }
+    }
+
+    /// Reads an unsigned value of up to 64 bits,
+    /// assembled from logical bits in the stream's order.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the number of bits is larger than 64
+    /// or if any Manchester-coded bit is invalid.
+    pub fn read(&mut self, bits: u32) -> Result<u64, io::Error> {
+        if bits > 64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive bits for type read"));
+        }
+        let mut queue: BitQueue<E, u64> = BitQueue::new();
+        for _ in 0..bits {
+            queue.push(1, self.read_bit()? as u64);
+        }
+        Ok(queue.value())
+    }
+
+    /// Fills the buffer with bytes assembled from logical bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if any Manchester-coded bit is invalid.
+    pub fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        for byte in buf.iter_mut() {
+            *byte = self.read(8)? as u8;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Measuring the bit rate of a stream as it's read or written.
+//!
+//! This module requires the `std` feature.
+//!
+//! A `BitRateMeter` wraps a `BitRead` or `BitWrite` stream
+//! and counts every bit passing through it,
+//! including any padding bits consumed or written by `byte_align`.
+//! Whenever a value passes through after the given interval
+//! has elapsed, it calls its callback with the rate
+//! measured over that interval.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::time::Duration;
+//! use bitstream_io::{BigEndian, BitRead, BitReader};
+//! use bitstream_io::meter::BitRateMeter;
+//!
+//! let data = [0b1011_1100, 0xFF];
+//! let mut reports = Vec::new();
+//! {
+//!     let reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+//!     let mut meter = BitRateMeter::new(reader, Duration::ZERO,
+//!                                       |rate| reports.push(rate.bits));
+//!     assert_eq!(meter.read::<u8>(3).unwrap(), 0b101);
+//!     assert_eq!(meter.read_unary0().unwrap(), 3);
+//!     meter.byte_align();
+//!     assert_eq!(meter.read::<u8>(8).unwrap(), 0xFF);
+//!     assert_eq!(meter.total_bits(), 16);
+//! }
+//! assert_eq!(reports, vec![3, 4, 1, 8]);
+//! ```
+
+#![warn(missing_docs)]
+
+use std::time::{Duration, Instant};
+
+use tap::{Observer, Tap};
+
+/// A number of bits transferred over a span of time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BitRate {
+    /// The number of bits transferred during the span
+    pub bits: u64,
+    /// The length of the span
+    pub elapsed: Duration,
+}
+
+impl BitRate {
+    /// Returns the rate in bits per second,
+    /// or 0 if no time has elapsed.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use bitstream_io::meter::BitRate;
+    ///
+    /// let rate = BitRate{bits: 3000, elapsed: Duration::from_millis(500)};
+    /// assert_eq!(rate.bits_per_second(), 6000.0);
+    /// ```
+    pub fn bits_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {self.bits as f64 / seconds} else {0.0}
+    }
+}
+
+/// Measures how quickly bits pass through a stream.
+pub struct Meter<F: FnMut(&BitRate)> {
+    interval: Duration,
+    callback: F,
+    created: Instant,
+    interval_start: Instant,
+    interval_bits: u64,
+}
+
+impl<F: FnMut(&BitRate)> Meter<F> {
+    /// Calls `callback` with the rate measured
+    /// over each interval of at least `interval`.
+    pub fn new(interval: Duration, callback: F) -> Meter<F> {
+        let now = Instant::now();
+        Meter{interval,
+              callback,
+              created: now,
+              interval_start: now,
+              interval_bits: 0}
+    }
+}
+
+impl<F: FnMut(&BitRate)> Observer for Meter<F> {
+    // counts bits which have passed through,
+    // reporting the rate if the interval has elapsed
+    fn bits(&mut self, _offset: u64, bits: u64) {
+        self.interval_bits += bits;
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.interval_start);
+        if elapsed >= self.interval {
+            (self.callback)(&BitRate{bits: self.interval_bits, elapsed});
+            self.interval_start = now;
+            self.interval_bits = 0;
+        }
+    }
+}
+
+/// Wraps a bit stream, measuring how quickly bits pass through it.
+pub type BitRateMeter<T, F> = Tap<T, Meter<F>>;
+
+impl<T, F: FnMut(&BitRate)> Tap<T, Meter<F>> {
+    /// Wraps the stream, calling `callback` with the rate
+    /// measured over each interval of at least `interval`.
+    pub fn new(inner: T, interval: Duration, callback: F) ->
+        BitRateMeter<T, F> {
+        Tap::with_observer(inner, Meter::new(interval, callback))
+    }
+
+    /// Returns the total number of bits which have passed through.
+    #[inline]
+    pub fn total_bits(&self) -> u64 {self.position()}
+
+    /// Returns the average rate since the meter was created.
+    pub fn average(&self) -> BitRate {
+        BitRate{bits: self.position(),
+                elapsed: self.observer().created.elapsed()}
+    }
+}
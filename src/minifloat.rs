@@ -0,0 +1,129 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Conversions between f64 and IEEE-like floating point values
+// with arbitrary exponent and mantissa widths.
+//
+// A minifloat is laid out as a sign bit, followed by the exponent field,
+// followed by the mantissa field, which makes it 1 + exp_bits + mant_bits
+// bits wide in total.
+
+use io;
+
+// The largest exponent and mantissa widths whose values
+// all fit exactly in an f64.
+const MAX_EXP_BITS: u32 = 11;
+const MAX_MANT_BITS: u32 = 52;
+
+// Returns an error if the given widths are not supported
+pub fn check_widths(exp_bits: u32, mant_bits: u32) -> Result<(), io::Error> {
+    if (2..=MAX_EXP_BITS).contains(&exp_bits) && (mant_bits <= MAX_MANT_BITS) {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidInput,
+                           "unsupported minifloat exponent or mantissa size"))
+    }
+}
+
+// Returns 2 raised to the given power, which must be in a normal range
+#[inline]
+fn pow2(exp: i32) -> f64 {
+    debug_assert!((-1022..=1023).contains(&exp));
+    f64::from_bits(((exp + 1023) as u64) << 52)
+}
+
+// Given raw minifloat bits of the given widths, returns its value as an f64
+pub fn decode(exp_bits: u32, mant_bits: u32, raw: u64) -> f64 {
+    let bias = (1i32 << (exp_bits - 1)) - 1;
+    let max_exp = (1u64 << exp_bits) - 1;
+    let mantissa = raw & ((1u64 << mant_bits) - 1);
+    let exponent = (raw >> mant_bits) & max_exp;
+    let negative = ((raw >> (mant_bits + exp_bits)) & 1) == 1;
+
+    let magnitude = if exponent == max_exp {
+        if mantissa == 0 {f64::INFINITY} else {f64::NAN}
+    } else if exponent == 0 {
+        // subnormal values are mantissa * 2 ^ (1 - bias - mant_bits)
+        // which may itself be subnormal in an f64
+        let scale = 1 - bias - (mant_bits as i32);
+        if scale >= -1022 {
+            mantissa as f64 * pow2(scale)
+        } else {
+            mantissa as f64 * pow2(-(mant_bits as i32)) *
+                pow2(scale + mant_bits as i32)
+        }
+    } else {
+        let fraction = mantissa << (MAX_MANT_BITS - mant_bits);
+        let exponent = (exponent as i32) - bias + 1023;
+        f64::from_bits(((exponent as u64) << 52) | fraction)
+    };
+
+    if negative {-magnitude} else {magnitude}
+}
+
+// Given an f64 value, returns its raw bits as a minifloat
+// of the given widths, rounding to the nearest representable value
+// (with ties to even) and overflowing to infinity.
+pub fn encode(exp_bits: u32, mant_bits: u32, value: f64) -> u64 {
+    let bias = (1i32 << (exp_bits - 1)) - 1;
+    let max_exp = (1u64 << exp_bits) - 1;
+    let sign = (value.to_bits() >> 63) << (exp_bits + mant_bits);
+
+    if value.is_nan() {
+        // a quiet NaN, preserving as much payload as fits
+        let payload = (value.to_bits() & ((1u64 << 52) - 1)) >>
+            (MAX_MANT_BITS - mant_bits);
+        let quiet = if mant_bits > 0 {1u64 << (mant_bits - 1)} else {0};
+        return sign | (max_exp << mant_bits) | payload | quiet;
+    } else if value.is_infinite() {
+        return sign | (max_exp << mant_bits);
+    } else if value == 0.0 {
+        return sign;
+    }
+
+    // normalize so that the significand's leading 1 is always bit 52
+    // and the value is significand * 2 ^ (exponent - 52)
+    let bits = value.to_bits();
+    let f64_exp = ((bits >> 52) & 0x7FF) as i32;
+    let (mut significand, mut exponent) = if f64_exp == 0 {
+        (bits & ((1u64 << 52) - 1), -1022)
+    } else {
+        ((bits & ((1u64 << 52) - 1)) | (1u64 << 52), f64_exp - 1023)
+    };
+    let shift = significand.leading_zeros() - 11;
+    significand <<= shift;
+    exponent -= shift as i32;
+
+    // values below the smallest normal exponent become subnormal
+    let target_exp = if exponent > 1 - bias {exponent} else {1 - bias};
+    let shift = (52 - mant_bits as i32 + (target_exp - exponent)) as u32;
+    let rounded = if shift >= 64 {
+        0
+    } else if shift == 0 {
+        significand
+    } else {
+        let truncated = significand >> shift;
+        let remainder = significand & ((1u64 << shift) - 1);
+        let half = 1u64 << (shift - 1);
+        if (remainder > half) || ((remainder == half) && (truncated & 1 == 1)) {
+            truncated + 1
+        } else {
+            truncated
+        }
+    };
+
+    // a rounded significand with its leading bit at mant_bits + 1
+    // carries over into the exponent field naturally
+    let magnitude =
+        (((target_exp + bias - 1) as u64) << mant_bits) + rounded;
+    if magnitude >= (max_exp << mant_bits) {
+        sign | (max_exp << mant_bits)
+    } else {
+        sign | magnitude
+    }
+}
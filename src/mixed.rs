@@ -0,0 +1,64 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Mixed-endian layouts for use with the `read_mixed_endian`
+//! and `write_mixed_endian` methods.
+//!
+//! Mixed-endian (or middle-endian) values are split into
+//! 16-bit words whose bytes are stored in one order
+//! while the words themselves are stored in the other.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Write, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::mixed::MixedEndian;
+//!
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     writer.write_mixed_endian(MixedEndian::Pdp, 0x0A0B0C0Du32).unwrap();
+//! }
+//! assert_eq!(data, [0x0B, 0x0A, 0x0D, 0x0C]);
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! assert_eq!(reader.read_mixed_endian::<u32>(MixedEndian::Pdp).unwrap(),
+//!            0x0A0B0C0D);
+//! ```
+
+#![warn(missing_docs)]
+
+/// A layout of 16-bit words within a larger value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MixedEndian {
+    /// The most-significant word is stored first
+    /// and each word's least-significant byte is stored first,
+    /// as on the PDP-11.
+    /// For example, 0x0A0B0C0D is stored as `0B 0A 0D 0C`.
+    Pdp,
+    /// The least-significant word is stored first
+    /// and each word's most-significant byte is stored first.
+    /// For example, 0x0A0B0C0D is stored as `0C 0D 0A 0B`.
+    WordSwapped,
+}
+
+impl MixedEndian {
+    // Given a byte's position in the stream,
+    // returns its position in the value's big-endian bytes.
+    #[inline]
+    pub(crate) fn big_endian_index(&self, position: usize, bytes: usize) ->
+        usize {
+
+        let (word, byte) = (position / 2, position % 2);
+        match *self {
+            MixedEndian::Pdp => word * 2 + (1 - byte),
+            MixedEndian::WordSwapped => (bytes / 2 - 1 - word) * 2 + byte,
+        }
+    }
+}
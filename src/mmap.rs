@@ -0,0 +1,75 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reading memory-mapped files.
+//!
+//! This module requires the `mmap` feature.
+//!
+//! A mapped file is read as an in-memory slice,
+//! so that reads never go through the operating system
+//! and `seek_bits` moves to any bit in the file at no cost.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::Write;
+//! use bitstream_io::{BigEndian, BitReader};
+//!
+//! let path = std::env::temp_dir().join("bitstream_io_mmap_doctest.bin");
+//! std::fs::File::create(&path).unwrap()
+//!     .write_all(&[0b1011_0011, 0b1100_0110]).unwrap();
+//!
+//! let mut reader = unsafe {BitReader::<BigEndian, _>::from_mmap(&path)}.unwrap();
+//! reader.seek_bits(6).unwrap();
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0b1111);
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+#![warn(missing_docs)]
+
+use std::fs::File;
+use std::io::{self, Cursor};
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use super::{BitReader, Endianness};
+
+/// The underlying stream of a `BitReader` over a memory-mapped file.
+pub type MmapSource = Cursor<Mmap>;
+
+impl<E: Endianness> BitReader<E, MmapSource> {
+    /// Opens and maps the file at the given path,
+    /// returning a reader positioned at its start.
+    ///
+    /// Requires the `mmap` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error opening or mapping the file.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified, truncated or removed
+    /// by this or any other process while the reader exists,
+    /// since its contents would then change out from under
+    /// the mapped slice.
+    pub unsafe fn from_mmap<P: AsRef<Path>>(path: P) ->
+        Result<BitReader<E, MmapSource>, io::Error> {
+        let file = File::open(path)?;
+        let map = Mmap::map(&file)?;
+        Ok(BitReader::from_read(Cursor::new(map)))
+    }
+
+    /// Builds a reader over an existing file mapping.
+    ///
+    /// Requires the `mmap` feature.
+    pub fn from_map(map: Mmap) -> BitReader<E, MmapSource> {
+        BitReader::from_read(Cursor::new(map))
+    }
+}
@@ -0,0 +1,553 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Multiplexing several logical channels into one stream,
+//! and splitting them apart again.
+//!
+//! A `Mux` holds a queue of bits for each channel,
+//! which are filled by writing fields to the channel
+//! as with any other `BitWrite` stream.
+//! Queued bits are then sent to a single output stream,
+//! either a frame at a time according to a fixed `Schedule`
+//! of slots, as in time-division multiplexed frames,
+//! or a slot at a time as the caller decides.
+//! The mux tracks how many bits each channel
+//! has been given and has sent.
+//!
+//! A `Demux` does the reverse, reading an incoming stream's bits
+//! into a queue for each channel by the same kind of schedule,
+//! or by a tag field naming each slot's channel.
+//! Each channel may then be read as a `BitRead` stream of its own,
+//! as when decoding the subcommutated channels of telemetry frames.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitRead, BitReader, BitWrite, BitWriter};
+//! use bitstream_io::mux::{Demux, Mux, Schedule};
+//!
+//! let mut mux = Mux::<BigEndian>::new(2);
+//! mux.channel(0).write(8, 0xABu8).unwrap();
+//! mux.channel(1).write(8, 0x12u8).unwrap();
+//!
+//! // each frame takes 4 bits from each channel in turn
+//! let schedule = Schedule::round_robin(2, 4);
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian, _>::from_write(&mut data);
+//!     while mux.frame_ready(&schedule) {
+//!         mux.write_frame(&schedule, &mut writer).unwrap();
+//!     }
+//! }
+//! assert_eq!(data, [0xA1, 0xB2]);
+//! assert_eq!(mux.emitted(0), 8);
+//!
+//! let mut demux = Demux::<BigEndian>::new(2);
+//! let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+//! demux.read_frame(&schedule, &mut reader).unwrap();
+//! demux.read_frame(&schedule, &mut reader).unwrap();
+//! assert_eq!(demux.channel(0).read::<u8>(8).unwrap(), 0xAB);
+//! assert_eq!(demux.channel(1).read::<u8>(8).unwrap(), 0x12);
+//! ```
+
+#![warn(missing_docs)]
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::marker::PhantomData;
+use io;
+
+use super::{BitQueue, BitRead, BitReader, BitWrite, BitWriter, Endianness,
+            Numeric, SignedNumeric};
+
+/// The slots of a frame, in the order they're sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    slots: Vec<(usize, u32)>,
+}
+
+impl Schedule {
+    /// Builds a schedule from each slot's channel and number of bits.
+    #[inline]
+    pub fn new(slots: Vec<(usize, u32)>) -> Schedule {
+        Schedule{slots}
+    }
+
+    /// Builds a schedule with one slot of the given number of bits
+    /// for each channel in turn.
+    pub fn round_robin(channels: usize, slot_bits: u32) -> Schedule {
+        Schedule{slots: (0..channels).map(|c| (c, slot_bits)).collect()}
+    }
+
+    /// Returns each slot's channel and number of bits.
+    #[inline]
+    pub fn slots(&self) -> &[(usize, u32)] {&self.slots}
+
+    /// Returns the total number of bits in a frame.
+    pub fn frame_bits(&self) -> u64 {
+        self.slots.iter().map(|&(_, bits)| u64::from(bits)).sum()
+    }
+
+    // the number of bits a frame takes from the given channel
+    fn channel_bits(&self, channel: usize) -> u64 {
+        self.slots.iter()
+            .filter(|&&(c, _)| c == channel)
+            .map(|&(_, bits)| u64::from(bits))
+            .sum()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct Channel {
+    queue: VecDeque<bool>,
+    written: u64,
+    emitted: u64,
+}
+
+/// Queues bits for several channels and interleaves them
+/// into a single stream.
+///
+/// Channels are numbered from 0.
+#[derive(Clone, Debug)]
+pub struct Mux<E: Endianness> {
+    phantom: PhantomData<E>,
+    channels: Vec<Channel>,
+}
+
+impl<E: Endianness> Mux<E> {
+    /// Builds a mux with the given number of empty channels.
+    pub fn new(channels: usize) -> Mux<E> {
+        Mux{phantom: PhantomData,
+            channels: (0..channels).map(|_| Channel::default()).collect()}
+    }
+
+    /// Returns the number of channels.
+    #[inline]
+    pub fn channels(&self) -> usize {self.channels.len()}
+
+    /// Returns a stream whose fields are queued on the given channel,
+    /// in the mux's endianness.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel doesn't exist.
+    #[inline]
+    pub fn channel(&mut self, channel: usize) -> ChannelWriter<'_, E> {
+        ChannelWriter{phantom: PhantomData,
+                      channel: &mut self.channels[channel]}
+    }
+
+    /// Returns the number of bits queued on the given channel
+    /// and not yet sent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel doesn't exist.
+    #[inline]
+    pub fn queued(&self, channel: usize) -> u64 {
+        self.channels[channel].queue.len() as u64
+    }
+
+    /// Returns the number of bits written to the given channel so far.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel doesn't exist.
+    #[inline]
+    pub fn written(&self, channel: usize) -> u64 {
+        self.channels[channel].written
+    }
+
+    /// Returns the number of bits sent from the given channel so far.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel doesn't exist.
+    #[inline]
+    pub fn emitted(&self, channel: usize) -> u64 {
+        self.channels[channel].emitted
+    }
+
+    /// Sends the given number of bits queued on a channel
+    /// to the output stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the output stream.
+    /// Returns an `InvalidInput` error, without sending anything,
+    /// if the channel doesn't exist or has too few bits queued.
+    pub fn emit<W>(&mut self, channel: usize, bits: u32, writer: &mut W) ->
+        Result<(), io::Error> where W: BitWrite + ?Sized {
+        match self.channels.get(channel) {
+            Some(c) if c.queue.len() as u64 >= u64::from(bits) => {
+                self.send(channel, bits, writer)
+            }
+            Some(_) => Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          "too few bits queued on channel")),
+            None => Err(missing_channel()),
+        }
+    }
+
+    /// Returns true if every channel has enough bits queued
+    /// to fill its slots in a frame of the given schedule.
+    pub fn frame_ready(&self, schedule: &Schedule) -> bool {
+        schedule.slots.iter().all(|&(c, _)| c < self.channels.len()) &&
+            self.channels.iter().enumerate().all(|(c, channel)| {
+                channel.queue.len() as u64 >= schedule.channel_bits(c)
+            })
+    }
+
+    /// Sends one frame of the given schedule to the output stream,
+    /// taking each slot's bits from its channel in turn.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the output stream.
+    /// Returns an `InvalidInput` error, without sending anything,
+    /// if a slot's channel doesn't exist
+    /// or if a channel has too few bits queued for its slots.
+    pub fn write_frame<W>(&mut self, schedule: &Schedule, writer: &mut W) ->
+        Result<(), io::Error> where W: BitWrite + ?Sized {
+        if schedule.slots.iter().any(|&(c, _)| c >= self.channels.len()) {
+            return Err(missing_channel());
+        } else if !self.frame_ready(schedule) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "too few bits queued on channel"));
+        }
+        for &(channel, bits) in schedule.slots.iter() {
+            self.send(channel, bits, writer)?;
+        }
+        Ok(())
+    }
+
+    // sends bits already known to be queued
+    fn send<W>(&mut self, channel: usize, bits: u32, writer: &mut W) ->
+        Result<(), io::Error> where W: BitWrite + ?Sized {
+        let channel = &mut self.channels[channel];
+        for _ in 0..bits {
+            if let Some(bit) = channel.queue.pop_front() {
+                writer.write_bit(bit)?;
+                channel.emitted += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn missing_channel() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "no such channel")
+}
+
+/// Queues the fields written to it on one of a mux's channels.
+pub struct ChannelWriter<'m, E: Endianness> {
+    phantom: PhantomData<E>,
+    channel: &'m mut Channel,
+}
+
+impl<'m, E: Endianness> ChannelWriter<'m, E> {
+    // writes to a scratch stream and queues the bits written,
+    // in the order they were written
+    fn queued<F>(&mut self, f: F) -> Result<(), io::Error>
+        where F: FnOnce(&mut BitWriter<E>) -> Result<(), io::Error> {
+        let mut bytes = Vec::new();
+        let (result, mut partial) = {
+            let mut writer = BitWriter::from_parts(&mut bytes,
+                                                   BitQueue::new());
+            let result = f(&mut writer);
+            (result, writer.into_bitqueue())
+        };
+        for byte in bytes {
+            let mut byte = BitQueue::<E, u8>::from_value(byte, 8);
+            while !byte.is_empty() {
+                self.push(byte.pop(1) == 1);
+            }
+        }
+        while !partial.is_empty() {
+            self.push(partial.pop(1) == 1);
+        }
+        result
+    }
+
+    #[inline]
+    fn push(&mut self, bit: bool) {
+        self.channel.queue.push_back(bit);
+        self.channel.written += 1;
+    }
+}
+
+impl<'m, E: Endianness> BitWrite for ChannelWriter<'m, E> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> Result<(), io::Error> {
+        self.push(bit);
+        Ok(())
+    }
+
+    fn write<U>(&mut self, bits: u32, value: U) -> Result<(), io::Error>
+        where U: Numeric {
+        self.queued(|w| w.write(bits, value))
+    }
+
+    fn write_signed<S>(&mut self, bits: u32, value: S) ->
+        Result<(), io::Error> where S: SignedNumeric {
+        self.queued(|w| w.write_signed(bits, value))
+    }
+
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), io::Error> {
+        self.queued(|w| w.write_bytes(buf))
+    }
+
+    fn write_unary0(&mut self, value: u32) -> Result<(), io::Error> {
+        self.queued(|w| w.write_unary0(value))
+    }
+
+    fn write_unary1(&mut self, value: u32) -> Result<(), io::Error> {
+        self.queued(|w| w.write_unary1(value))
+    }
+
+    /// Returns true if a whole number of bytes
+    /// has been written to the channel.
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.channel.written.is_multiple_of(8)
+    }
+
+    fn byte_align(&mut self) -> Result<(), io::Error> {
+        while !self.byte_aligned() {
+            self.push(false);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct Received {
+    queue: VecDeque<bool>,
+    received: u64,
+    read: u64,
+}
+
+/// Splits a single stream into several channels,
+/// each of which may be read on its own.
+///
+/// Channels are numbered from 0.
+#[derive(Clone, Debug)]
+pub struct Demux<E: Endianness> {
+    phantom: PhantomData<E>,
+    channels: Vec<Received>,
+}
+
+impl<E: Endianness> Demux<E> {
+    /// Builds a demux with the given number of empty channels.
+    pub fn new(channels: usize) -> Demux<E> {
+        Demux{phantom: PhantomData,
+              channels: (0..channels).map(|_| Received::default()).collect()}
+    }
+
+    /// Returns the number of channels.
+    #[inline]
+    pub fn channels(&self) -> usize {self.channels.len()}
+
+    /// Returns a stream reading the bits received on the given channel,
+    /// in the demux's endianness.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel doesn't exist.
+    #[inline]
+    pub fn channel(&mut self, channel: usize) -> ChannelReader<'_, E> {
+        ChannelReader{phantom: PhantomData,
+                      channel: &mut self.channels[channel]}
+    }
+
+    /// Returns the number of bits received on the given channel
+    /// and not yet read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel doesn't exist.
+    #[inline]
+    pub fn queued(&self, channel: usize) -> u64 {
+        self.channels[channel].queue.len() as u64
+    }
+
+    /// Returns the number of bits received on the given channel so far.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel doesn't exist.
+    #[inline]
+    pub fn received(&self, channel: usize) -> u64 {
+        self.channels[channel].received
+    }
+
+    /// Returns the number of bits read from the given channel so far.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel doesn't exist.
+    #[inline]
+    pub fn read(&self, channel: usize) -> u64 {
+        self.channels[channel].read
+    }
+
+    /// Reads the given number of bits from the input stream
+    /// onto a channel.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the input stream,
+    /// in which case any bits read before it stay on the channel.
+    /// Returns an `InvalidInput` error, without reading anything,
+    /// if the channel doesn't exist.
+    pub fn receive<R>(&mut self, channel: usize, bits: u32, reader: &mut R) ->
+        Result<(), io::Error> where R: BitRead + ?Sized {
+        let channel = self.channels.get_mut(channel)
+            .ok_or_else(missing_channel)?;
+        for _ in 0..bits {
+            channel.queue.push_back(reader.read_bit()?);
+            channel.received += 1;
+        }
+        Ok(())
+    }
+
+    /// Reads one frame of the given schedule from the input stream,
+    /// putting each slot's bits on its channel in turn.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the input stream.
+    /// Returns an `InvalidInput` error, without reading anything,
+    /// if a slot's channel doesn't exist.
+    pub fn read_frame<R>(&mut self, schedule: &Schedule, reader: &mut R) ->
+        Result<(), io::Error> where R: BitRead + ?Sized {
+        if schedule.slots.iter().any(|&(c, _)| c >= self.channels.len()) {
+            return Err(missing_channel());
+        }
+        for &(channel, bits) in schedule.slots.iter() {
+            self.receive(channel, bits, reader)?;
+        }
+        Ok(())
+    }
+
+    /// Reads an unsigned tag of `tag_bits` bits from the input stream,
+    /// naming a channel, and then `slot_bits` bits onto that channel.
+    /// Returns the channel.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the input stream.
+    /// Returns an `InvalidData` error if the tag names
+    /// a channel which doesn't exist.
+    pub fn read_tagged<R>(&mut self,
+                          tag_bits: u32,
+                          slot_bits: u32,
+                          reader: &mut R) -> Result<usize, io::Error>
+        where R: BitRead + ?Sized {
+        let tag = reader.read::<u64>(tag_bits)?;
+        match usize::try_from(tag) {
+            Ok(channel) if channel < self.channels.len() => {
+                self.receive(channel, slot_bits, reader).map(|()| channel)
+            }
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData,
+                                    "tag for missing channel")),
+        }
+    }
+}
+
+/// Reads the bits received on one of a demux's channels.
+///
+/// Reading past the bits received is an `UnexpectedEof` error,
+/// which consumes nothing, so the read may be retried
+/// once more bits have arrived.
+pub struct ChannelReader<'m, E: Endianness> {
+    phantom: PhantomData<E>,
+    channel: &'m mut Received,
+}
+
+impl<'m, E: Endianness> ChannelReader<'m, E> {
+    // reads from a scratch stream of the next bits received,
+    // consuming them only if the read succeeds
+    fn unqueued<F, T>(&mut self, bits: u64, f: F) -> Result<T, io::Error>
+        where F: FnOnce(&mut BitReader<E, &[u8]>) -> Result<T, io::Error> {
+        if (self.channel.queue.len() as u64) < bits {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                      "too few bits received on channel"));
+        }
+        let mut bytes = Vec::new();
+        {
+            let mut writer = BitWriter::<E>::from_parts(&mut bytes,
+                                                        BitQueue::new());
+            for &bit in self.channel.queue.iter().take(bits as usize) {
+                writer.write_bit(bit)?;
+            }
+            writer.byte_align()?;
+        }
+        let value = f(&mut BitReader::from_read(&bytes[..]))?;
+        self.channel.queue.drain(..bits as usize);
+        self.channel.read += bits;
+        Ok(value)
+    }
+
+    // the length of the next unary value, including its final bit
+    fn unary_len(&self, stop: bool) -> Result<u64, io::Error> {
+        match self.channel.queue.iter().position(|&bit| bit == stop) {
+            Some(position) => Ok(position as u64 + 1),
+            None => Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                       "too few bits received on channel")),
+        }
+    }
+}
+
+impl<'m, E: Endianness> BitRead for ChannelReader<'m, E> {
+    fn read_bit(&mut self) -> Result<bool, io::Error> {
+        self.unqueued(1, |r| r.read_bit())
+    }
+
+    fn read<U>(&mut self, bits: u32) -> Result<U, io::Error>
+        where U: Numeric {
+        self.unqueued(u64::from(bits), |r| r.read(bits))
+    }
+
+    fn read_signed<S>(&mut self, bits: u32) -> Result<S, io::Error>
+        where S: SignedNumeric {
+        self.unqueued(u64::from(bits), |r| r.read_signed(bits))
+    }
+
+    fn skip(&mut self, bits: u32) -> Result<(), io::Error> {
+        self.unqueued(u64::from(bits), |_| Ok(()))
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        self.unqueued(buf.len() as u64 * 8, |r| r.read_bytes(buf))
+    }
+
+    fn read_unary0(&mut self) -> Result<u32, io::Error> {
+        let len = self.unary_len(false)?;
+        self.unqueued(len, |r| r.read_unary0())
+    }
+
+    fn read_unary1(&mut self) -> Result<u32, io::Error> {
+        let len = self.unary_len(true)?;
+        self.unqueued(len, |r| r.read_unary1())
+    }
+
+    /// Returns true if a whole number of bytes
+    /// has been read from the channel.
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.channel.read.is_multiple_of(8)
+    }
+
+    fn byte_align(&mut self) {
+        while !self.byte_aligned() &&
+            self.channel.queue.pop_front().is_some() {
+            self.channel.read += 1;
+        }
+    }
+}
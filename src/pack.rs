@@ -0,0 +1,134 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Packing long runs of samples which all have the same width.
+//!
+//! This is the reverse of the `unpack` module.
+//! Rather than writing samples one at a time through a `BitWriter`,
+//! a whole slice of them is packed into a byte buffer at once,
+//! 32 bits at a time, which may then be written out in one go.
+//! As with a `BitWriter`, any partial byte at the end is
+//! padded with 0 bits.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitWriter};
+//! use bitstream_io::pack::pack_u32;
+//!
+//! // two 20-bit samples and a 24-bit one
+//! let mut packed = [0; 5];
+//! let len = pack_u32::<BigEndian>(&[0x12345, 0x6789A], 20, &mut packed);
+//! assert_eq!(len.unwrap(), 5);
+//!
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     writer.write_bytes(&packed).unwrap();
+//!     writer.write(24, 0xBCDEF0u32).unwrap();
+//! }
+//! assert_eq!(data, [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0]);
+//! ```
+
+#![warn(missing_docs)]
+
+use io;
+use super::{BitQueue, Endianness};
+
+/// Packs samples of the given width, from 0 to 16 bits,
+/// into the start of the output,
+/// returning the number of bytes filled.
+///
+/// # Errors
+///
+/// Returns an `InvalidInput` error if `bits` is larger than 16
+/// or any sample is too large to fit them,
+/// or a `WriteZero` error if the output is too short
+/// to hold every sample.
+///
+/// # Example
+/// ```
+/// use bitstream_io::LittleEndian;
+/// use bitstream_io::pack::pack_u16;
+///
+/// let mut packed = [0; 2];
+/// let len = pack_u16::<LittleEndian>(&[0b10110, 0b01100, 0b11101],
+///                                    5, &mut packed).unwrap();
+/// assert_eq!(len, 2);
+/// assert_eq!(packed, [0b1001_0110, 0b0111_0101]);
+/// ```
+pub fn pack_u16<E: Endianness>(samples: &[u16],
+                               bits: u32,
+                               output: &mut [u8]) -> io::Result<usize> {
+    pack::<E, u16>(samples, bits, 16, output)
+}
+
+/// Packs samples of the given width, from 0 to 32 bits,
+/// into the start of the output,
+/// returning the number of bytes filled.
+///
+/// # Errors
+///
+/// Returns an `InvalidInput` error if `bits` is larger than 32
+/// or any sample is too large to fit them,
+/// or a `WriteZero` error if the output is too short
+/// to hold every sample.
+pub fn pack_u32<E: Endianness>(samples: &[u32],
+                               bits: u32,
+                               output: &mut [u8]) -> io::Result<usize> {
+    pack::<E, u32>(samples, bits, 32, output)
+}
+
+fn pack<E, S>(samples: &[S],
+              bits: u32,
+              max_bits: u32,
+              output: &mut [u8]) -> io::Result<usize>
+    where E: Endianness, S: Copy + Into<u64> {
+
+    if bits > max_bits {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "excessive bits for type written"));
+    }
+    // a single pass over the samples, without branching on each,
+    // finds whether any is too large
+    if samples.iter().fold(0u64, |acc, &s| acc | s.into()) >> bits != 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "excessive value for bits written"));
+    }
+    let len = (samples.len() as u64 * u64::from(bits)).div_ceil(8);
+    if len > output.len() as u64 {
+        return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                  "output too short for samples"));
+    }
+
+    /*samples are queued until 32 bits are pending,
+      which are then written out as a single word*/
+    let mut queue = BitQueue::<E, u64>::new();
+    let mut words = output.chunks_exact_mut(4);
+    for &sample in samples {
+        queue.push(bits, sample.into());
+        if queue.len() >= 32 {
+            let word = E::to_bytes::<u32>(queue.pop(32) as u32);
+            if let Some(chunk) = words.next() {
+                chunk.copy_from_slice(&word);
+            }
+        }
+    }
+
+    // any bits left over fill the remaining bytes,
+    // with the last padded with 0s
+    let written = (samples.len() as u64 * u64::from(bits) / 32 * 4) as usize;
+    for byte in output[written..len as usize].iter_mut() {
+        if queue.len() < 8 {
+            let padding = 8 - queue.len();
+            queue.push(padding, 0);
+        }
+        *byte = queue.pop(8) as u8;
+    }
+    Ok(len as usize)
+}
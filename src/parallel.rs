@@ -0,0 +1,129 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Decoding independent blocks of a buffer in parallel.
+//!
+//! This module requires the `rayon` feature.
+//!
+//! Many formats divide their data into blocks which may be
+//! decoded independently, such as FLAC frames or AV1 tiles,
+//! and locate them through a table of byte offsets.
+//! Given those offsets, these functions build a reader
+//! over each block's slice of the buffer, decode the blocks
+//! on rayon's thread pool, and return the results in block order.
+//!
+//! Block `i` runs from `offsets[i]` up to `offsets[i + 1]`,
+//! and the last block runs to the end of the buffer.
+//! Any data before the first offset is ignored.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::BigEndian;
+//! use bitstream_io::parallel::decode_blocks;
+//!
+//! let data = [0b1010_0000, 0xFF, 0xFF, 0b0110_0000];
+//! let headers = decode_blocks::<BigEndian, _, std::io::Error, _>(
+//!     &data, &[0, 1, 3], |r| r.read::<u8>(3)).unwrap();
+//! assert_eq!(headers, vec![0b101, 0b111, 0b011]);
+//! ```
+
+#![warn(missing_docs)]
+
+use std::io;
+
+use rayon::prelude::*;
+
+use super::{BitRead, BitReader, Endianness, FromBitStream};
+
+/// Decodes each block with the given function, in parallel,
+/// returning the decoded values in block order.
+///
+/// # Errors
+///
+/// Returns an `InvalidInput` error if the offsets aren't in
+/// increasing order or run past the end of the buffer,
+/// or else the first block's error, in block order,
+/// if any blocks fail to decode.
+pub fn decode_blocks<E, T, X, F>(data: &[u8],
+                                 offsets: &[usize],
+                                 decode: F) -> Result<Vec<T>, X>
+    where E: Endianness,
+          T: Send,
+          X: From<io::Error> + Send,
+          F: Fn(&mut BitReader<E, &[u8]>) -> Result<T, X> + Sync {
+    let blocks = split_blocks(data, offsets)?;
+    let results: Vec<Result<T, X>> = blocks
+        .par_iter()
+        .map(|block| decode(&mut BitReader::from_read(*block)))
+        .collect();
+    results.into_iter().collect()
+}
+
+/// Parses a value from the start of each block, in parallel,
+/// returning the parsed values in block order.
+///
+/// # Errors
+///
+/// Returns an `InvalidInput` error if the offsets aren't in
+/// increasing order or run past the end of the buffer,
+/// or else the first block's error, in block order,
+/// if any blocks fail to parse.
+///
+/// # Example
+/// ```
+/// use std::io;
+/// use bitstream_io::{LittleEndian, BitRead, FromBitStream};
+/// use bitstream_io::parallel::parse_blocks;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Frame {
+///     channels: u8,
+///     samples: u16,
+/// }
+///
+/// impl FromBitStream for Frame {
+///     type Error = io::Error;
+///
+///     fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+///         Ok(Frame{channels: r.read(4)?, samples: r.read(12)?})
+///     }
+/// }
+///
+/// let data = [0x02, 0x10, 0x01, 0xFF];
+/// let frames = parse_blocks::<LittleEndian, Frame>(&data, &[0, 2]).unwrap();
+/// assert_eq!(frames, vec![Frame{channels: 2, samples: 0x100},
+///                         Frame{channels: 1, samples: 0xFF0}]);
+/// assert!(parse_blocks::<LittleEndian, Frame>(&data, &[0, 3]).is_err());
+/// ```
+pub fn parse_blocks<E, T>(data: &[u8], offsets: &[usize]) ->
+    Result<Vec<T>, T::Error>
+    where E: Endianness,
+          T: FromBitStream + Send,
+          T::Error: From<io::Error> + Send {
+    decode_blocks::<E, T, T::Error, _>(data, offsets, |r| r.parse())
+}
+
+// Splits the buffer into per-block slices,
+// checking that the offsets are in order and in bounds
+fn split_blocks<'a>(data: &'a [u8], offsets: &[usize]) ->
+    Result<Vec<&'a [u8]>, io::Error> {
+    let mut blocks = Vec::with_capacity(offsets.len());
+    for (i, &start) in offsets.iter().enumerate() {
+        let end = offsets.get(i + 1).copied().unwrap_or(data.len());
+        if end > data.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "block offset past end of data"));
+        } else if start > end {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "block offsets out of order"));
+        }
+        blocks.push(&data[start..end]);
+    }
+    Ok(blocks)
+}
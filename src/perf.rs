@@ -0,0 +1,91 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Counting how a reader or writer uses its underlying stream.
+//!
+//! This module requires the `perf-counters` feature.
+//!
+//! With it, every `BitReader` and `BitWriter` keeps a tally
+//! of the calls it makes to its underlying stream,
+//! the bytes passing through them
+//! and the byte operations it had to shift
+//! because the stream wasn't byte-aligned,
+//! as returned by their `perf_counters` methods.
+//! These are meant for tuning buffer sizes
+//! and finding format constructs which defeat the aligned fast paths.
+//! Without the feature, none of this is tracked at all.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitReader};
+//!
+//! let data = [0b1011_0000, 0x12, 0x34, 0x56];
+//! let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0b1011);
+//! let mut buf = [0; 2];
+//! reader.read_bytes(&mut buf).unwrap();
+//!
+//! let counters = reader.perf_counters();
+//! assert_eq!(counters.refills, 2);
+//! assert_eq!(counters.misaligned, 1);
+//! assert_eq!(counters.bytes, 3);
+//! ```
+
+#![warn(missing_docs)]
+
+use io;
+
+/// Counts of a stream's use of its underlying stream.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PerfCounters {
+    /// The number of reads from, or writes to, the underlying stream
+    pub refills: u64,
+    /// The number of whole-byte operations made
+    /// while the stream wasn't byte-aligned
+    pub misaligned: u64,
+    /// The number of bytes read from, or written to,
+    /// the underlying stream
+    pub bytes: u64,
+}
+
+impl PerfCounters {
+    /// Returns counters which are all 0.
+    #[inline]
+    pub const fn new() -> PerfCounters {
+        PerfCounters{refills: 0, misaligned: 0, bytes: 0}
+    }
+}
+
+// passes writes through to a stream,
+// counting each one along with its bytes
+pub(crate) struct Tally<'a, W: 'a + io::Write + ?Sized> {
+    pub(crate) writer: &'a mut W,
+    pub(crate) counters: &'a mut PerfCounters,
+}
+
+impl<'a, W: io::Write + ?Sized> io::Write for Tally<'a, W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.counters.refills += 1;
+        self.counters.bytes += written as u64;
+        Ok(written)
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.writer.write_all(buf)?;
+        self.counters.refills += 1;
+        self.counters.bytes += buf.len() as u64;
+        Ok(())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {self.writer.flush()}
+}
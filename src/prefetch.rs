@@ -0,0 +1,161 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reading ahead from slow sources on a background thread.
+//!
+//! This module requires the `prefetch` feature.
+//!
+//! A `PrefetchReader` moves its source onto a background thread
+//! which fills one buffer while the reader consumes another,
+//! so decoding from network or disk-backed sources
+//! doesn't stall on every read.
+//! It implements `Read` and `BufRead`, and so may be wrapped
+//! in a `BitReader` like any other stream.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::Cursor;
+//! use bitstream_io::{BigEndian, BitReader};
+//! use bitstream_io::prefetch::PrefetchReader;
+//!
+//! let data = vec![0b1011_0001, 0xFF, 0x00];
+//! let source = PrefetchReader::new(Cursor::new(data));
+//! let mut reader = BitReader::<BigEndian, _>::from_read(source);
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0b1011);
+//! assert_eq!(reader.read::<u16>(12).unwrap(), 0b0001_1111_1111);
+//! assert_eq!(reader.read::<u8>(8).unwrap(), 0);
+//! assert!(reader.read::<u8>(1).is_err());
+//! ```
+
+#![warn(missing_docs)]
+
+use std::cmp;
+use std::io::{self, BufRead, Read};
+use std::mem;
+use std::sync::mpsc::{channel, sync_channel, Receiver, Sender, SyncSender};
+use std::thread::{self, JoinHandle};
+
+// the default size of each buffer read ahead
+const DEFAULT_CAPACITY: usize = 64 * 1024;
+
+/// Reads from a source on a background thread,
+/// one buffer ahead of the consumer.
+pub struct PrefetchReader {
+    current: Vec<u8>,
+    position: usize,
+    filled: Receiver<io::Result<Vec<u8>>>,
+    recycle: Sender<Vec<u8>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PrefetchReader {
+    /// Starts reading ahead from the source
+    /// in buffers of 64 KiB.
+    pub fn new<R: Read + Send + 'static>(source: R) -> PrefetchReader {
+        PrefetchReader::with_capacity(DEFAULT_CAPACITY, source)
+    }
+
+    /// Starts reading ahead from the source
+    /// in buffers of the given size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn with_capacity<R>(capacity: usize, source: R) -> PrefetchReader
+        where R: Read + Send + 'static {
+        assert!(capacity > 0, "prefetch buffers must not be empty");
+        let (filled_sender, filled) = sync_channel(1);
+        let (recycle, recycled) = channel();
+        let thread = thread::spawn(move || {
+            prefetch(source, capacity, &filled_sender, &recycled)
+        });
+        PrefetchReader{current: Vec::new(),
+                       position: 0,
+                       filled,
+                       recycle,
+                       thread: Some(thread)}
+    }
+
+    // Swaps in the next buffer read ahead,
+    // returning false at the end of the stream
+    fn next_buffer(&mut self) -> io::Result<bool> {
+        match self.filled.recv() {
+            Ok(Ok(next)) => {
+                let used = mem::replace(&mut self.current, next);
+                let _ = self.recycle.send(used);
+                self.position = 0;
+                Ok(true)
+            }
+            Ok(Err(err)) => Err(err),
+            Err(_) => match self.thread.take().map(|t| t.join()) {
+                Some(Err(_)) => {
+                    Err(io::Error::other("prefetch thread panicked"))
+                }
+                _ => Ok(false),
+            },
+        }
+    }
+}
+
+impl Read for PrefetchReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let to_copy = cmp::min(available.len(), buf.len());
+        buf[0..to_copy].copy_from_slice(&available[0..to_copy]);
+        self.consume(to_copy);
+        Ok(to_copy)
+    }
+}
+
+impl BufRead for PrefetchReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.position == self.current.len() {
+            if !self.next_buffer()? {
+                break;
+            }
+        }
+        Ok(&self.current[self.position..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position = cmp::min(self.position + amt, self.current.len());
+    }
+}
+
+// Fills buffers from the source until it's exhausted,
+// an error occurs, or the reader is dropped.
+// The end of the stream is signalled by dropping the sender.
+fn prefetch<R: Read>(mut source: R,
+                     capacity: usize,
+                     filled: &SyncSender<io::Result<Vec<u8>>>,
+                     recycled: &Receiver<Vec<u8>>) {
+    loop {
+        let mut buf = recycled.try_recv().unwrap_or_default();
+        buf.resize(capacity, 0);
+        let result = loop {
+            match source.read(&mut buf) {
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                result => break result,
+            }
+        };
+        match result {
+            Ok(0) => return,
+            Ok(read) => {
+                buf.truncate(read);
+                if filled.send(Ok(buf)).is_err() {
+                    return;
+                }
+            }
+            Err(err) => {
+                let _ = filled.send(Err(err));
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,331 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Python bindings through pyo3.
+//!
+//! This module requires the `python` feature.
+//! A Python extension module named `bitstream_io` may then be built
+//! with the `python-extension` feature, such as by
+//! `maturin build --features python-extension`,
+//! or by building the crate as a `cdylib` with that feature
+//! and renaming the result to `bitstream_io.so`.
+//!
+//! The module provides `BitReader`, `BitWriter` and `HuffmanTree`
+//! classes which use the same logic as this crate's types.
+//! Streams are big-endian unless `little_endian=True` is given.
+//! Running out of data raises `EOFError`,
+//! invalid arguments or data raise `ValueError`,
+//! and other errors raise `IOError`.
+//!
+//! ```python
+//! from bitstream_io import BitReader, BitWriter, HuffmanTree
+//!
+//! tree = HuffmanTree([(0, [0]), (1, [1, 0]), (2, [1, 1])])
+//! w = BitWriter()
+//! w.write(4, 0b1011)
+//! w.write_signed(5, -3)
+//! w.write_huffman(tree, 2)
+//! data = w.finish()
+//!
+//! r = BitReader(data)
+//! assert r.read(4) == 0b1011
+//! assert r.read_signed(5) == -3
+//! assert r.read_huffman(tree) == 2
+//! ```
+
+#![warn(missing_docs)]
+
+use std::io::{self, Cursor};
+use std::mem;
+
+use pyo3::exceptions::{PyEOFError, PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use huffman::{compile_read_tree, compile_write_tree, HuffmanTreeError,
+              ReadHuffmanTree, WriteHuffmanTree};
+use super::{BigEndian, LittleEndian, BitQueue, BitReader, BitWriter,
+            Endianness};
+
+fn to_py(err: io::Error) -> PyErr {
+    match err.kind() {
+        io::ErrorKind::UnexpectedEof => PyEOFError::new_err(err.to_string()),
+        io::ErrorKind::InvalidInput |
+        io::ErrorKind::InvalidData => PyValueError::new_err(err.to_string()),
+        _ => PyIOError::new_err(err.to_string()),
+    }
+}
+
+fn tree_to_py(err: HuffmanTreeError) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+enum Reader {
+    Big(BitReader<BigEndian, Cursor<Vec<u8>>>),
+    Little(BitReader<LittleEndian, Cursor<Vec<u8>>>),
+}
+
+macro_rules! with_reader {
+    ($reader:expr, $r:ident => $body:expr) => {
+        match $reader {
+            Reader::Big(ref mut $r) => $body,
+            Reader::Little(ref mut $r) => $body,
+        }
+    }
+}
+
+/// Reads values from a bytes object.
+#[pyclass(name = "BitReader", module = "bitstream_io")]
+pub struct PyBitReader {
+    reader: Reader,
+}
+
+#[pymethods]
+impl PyBitReader {
+    #[new]
+    #[pyo3(signature = (data, little_endian=false))]
+    fn new(data: Vec<u8>, little_endian: bool) -> PyBitReader {
+        let cursor = Cursor::new(data);
+        PyBitReader{reader: if little_endian {
+            Reader::Little(BitReader::from_read(cursor))
+        } else {
+            Reader::Big(BitReader::from_read(cursor))
+        }}
+    }
+
+    /// Reads an unsigned value of up to 64 bits.
+    fn read(&mut self, bits: u32) -> PyResult<u64> {
+        with_reader!(self.reader, r => r.read(bits)).map_err(to_py)
+    }
+
+    /// Reads a twos-complement signed value of up to 64 bits.
+    fn read_signed(&mut self, bits: u32) -> PyResult<i64> {
+        with_reader!(self.reader, r => r.read_signed(bits)).map_err(to_py)
+    }
+
+    /// Reads a single bit.
+    fn read_bit(&mut self) -> PyResult<bool> {
+        with_reader!(self.reader, r => r.read_bit()).map_err(to_py)
+    }
+
+    /// Reads a unary value terminated by a 0 bit.
+    fn read_unary0(&mut self) -> PyResult<u32> {
+        with_reader!(self.reader, r => r.read_unary0()).map_err(to_py)
+    }
+
+    /// Reads a unary value terminated by a 1 bit.
+    fn read_unary1(&mut self) -> PyResult<u32> {
+        with_reader!(self.reader, r => r.read_unary1()).map_err(to_py)
+    }
+
+    /// Reads the given number of whole bytes.
+    fn read_bytes<'py>(&mut self, py: Python<'py>, bytes: usize) ->
+        PyResult<Bound<'py, PyBytes>> {
+        let mut buf = vec![0; bytes];
+        with_reader!(self.reader, r => r.read_bytes(&mut buf))
+            .map_err(to_py)?;
+        Ok(PyBytes::new(py, &buf))
+    }
+
+    /// Reads a symbol with the given Huffman tree.
+    fn read_huffman(&mut self, tree: &PyHuffmanTree) -> PyResult<i64> {
+        with_reader!(self.reader, r => r.read_huffman(tree.read_tree()))
+            .map_err(to_py)
+    }
+
+    /// Skips the given number of bits.
+    fn skip(&mut self, bits: u32) -> PyResult<()> {
+        with_reader!(self.reader, r => r.skip(bits)).map_err(to_py)
+    }
+
+    /// Moves to the given position in bits from the start.
+    fn seek(&mut self, position: u64) -> PyResult<()> {
+        with_reader!(self.reader, r => r.seek_bits(position)).map_err(to_py)
+    }
+
+    /// Discards any bits up to the next whole byte.
+    fn byte_align(&mut self) {
+        with_reader!(self.reader, r => r.byte_align())
+    }
+
+    /// Returns true if the reader is at a whole byte.
+    fn byte_aligned(&mut self) -> bool {
+        with_reader!(self.reader, r => r.byte_aligned())
+    }
+
+    /// Returns the position in bits from the start.
+    fn position(&mut self) -> u64 {
+        with_reader!(self.reader, r => r.position_in_bits())
+    }
+}
+
+enum Queue {
+    Big(BitQueue<BigEndian, u8>),
+    Little(BitQueue<LittleEndian, u8>),
+}
+
+/// Writes values to a bytes object.
+#[pyclass(name = "BitWriter", module = "bitstream_io")]
+pub struct PyBitWriter {
+    data: Vec<u8>,
+    bitqueue: Queue,
+}
+
+macro_rules! with_writer {
+    ($writer:expr, $w:ident => $body:expr) => {{
+        let writer = &mut *$writer;
+        match writer.bitqueue {
+            Queue::Big(ref mut queue) => {
+                let mut $w = BitWriter::from_parts(&mut writer.data,
+                                                   mem::take(queue));
+                let result = $body;
+                *queue = $w.into_bitqueue();
+                result
+            }
+            Queue::Little(ref mut queue) => {
+                let mut $w = BitWriter::from_parts(&mut writer.data,
+                                                   mem::take(queue));
+                let result = $body;
+                *queue = $w.into_bitqueue();
+                result
+            }
+        }.map_err(to_py)
+    }}
+}
+
+#[pymethods]
+impl PyBitWriter {
+    #[new]
+    #[pyo3(signature = (little_endian=false))]
+    fn new(little_endian: bool) -> PyBitWriter {
+        PyBitWriter{data: Vec::new(),
+                    bitqueue: if little_endian {
+                        Queue::Little(BitQueue::new())
+                    } else {
+                        Queue::Big(BitQueue::new())
+                    }}
+    }
+
+    /// Writes an unsigned value of up to 64 bits.
+    fn write(&mut self, bits: u32, value: u64) -> PyResult<()> {
+        with_writer!(self, w => w.write(bits, value))
+    }
+
+    /// Writes a twos-complement signed value of up to 64 bits.
+    fn write_signed(&mut self, bits: u32, value: i64) -> PyResult<()> {
+        with_writer!(self, w => w.write_signed(bits, value))
+    }
+
+    /// Writes a single bit.
+    fn write_bit(&mut self, bit: bool) -> PyResult<()> {
+        with_writer!(self, w => w.write_bit(bit))
+    }
+
+    /// Writes a unary value terminated by a 0 bit.
+    fn write_unary0(&mut self, value: u32) -> PyResult<()> {
+        with_writer!(self, w => w.write_unary0(value))
+    }
+
+    /// Writes a unary value terminated by a 1 bit.
+    fn write_unary1(&mut self, value: u32) -> PyResult<()> {
+        with_writer!(self, w => w.write_unary1(value))
+    }
+
+    /// Writes whole bytes.
+    fn write_bytes(&mut self, bytes: &[u8]) -> PyResult<()> {
+        with_writer!(self, w => w.write_bytes(bytes))
+    }
+
+    /// Writes a symbol with the given Huffman tree.
+    fn write_huffman(&mut self, tree: &PyHuffmanTree, symbol: i64) ->
+        PyResult<()> {
+        if !tree.write_be.has_symbol(symbol) {
+            return Err(PyValueError::new_err("symbol not in Huffman tree"));
+        }
+        with_writer!(self, w => w.write_huffman(tree.write_tree(), symbol))
+    }
+
+    /// Pads the output with 0 bits up to the next whole byte.
+    fn byte_align(&mut self) -> PyResult<()> {
+        with_writer!(self, w => w.byte_align())
+    }
+
+    /// Returns true if the writer is at a whole byte.
+    fn byte_aligned(&self) -> bool {
+        match self.bitqueue {
+            Queue::Big(ref queue) => queue.is_empty(),
+            Queue::Little(ref queue) => queue.is_empty(),
+        }
+    }
+
+    /// Pads the output to a whole byte and returns it.
+    fn finish<'py>(&mut self, py: Python<'py>) ->
+        PyResult<Bound<'py, PyBytes>> {
+        self.byte_align()?;
+        Ok(PyBytes::new(py, &self.data))
+    }
+}
+
+/// A Huffman tree for reading and writing integer symbols,
+/// built from a list of `(symbol, [bit, ...])` pairs.
+#[pyclass(name = "HuffmanTree", module = "bitstream_io")]
+pub struct PyHuffmanTree {
+    read_be: Box<[ReadHuffmanTree<BigEndian, i64>]>,
+    read_le: Box<[ReadHuffmanTree<LittleEndian, i64>]>,
+    write_be: WriteHuffmanTree<BigEndian, i64>,
+    write_le: WriteHuffmanTree<LittleEndian, i64>,
+}
+
+#[pymethods]
+impl PyHuffmanTree {
+    #[new]
+    fn new(codes: Vec<(i64, Vec<u8>)>) -> PyResult<PyHuffmanTree> {
+        Ok(PyHuffmanTree{
+            read_be: compile_read_tree(codes.clone()).map_err(tree_to_py)?,
+            read_le: compile_read_tree(codes.clone()).map_err(tree_to_py)?,
+            write_be: compile_write_tree(codes.clone()).map_err(tree_to_py)?,
+            write_le: compile_write_tree(codes).map_err(tree_to_py)?,
+        })
+    }
+}
+
+// Selects a tree's compiled form for a stream's endianness
+trait Compiled<E: Endianness> {
+    fn read_tree(&self) -> &[ReadHuffmanTree<E, i64>];
+
+    fn write_tree(&self) -> &WriteHuffmanTree<E, i64>;
+}
+
+impl Compiled<BigEndian> for PyHuffmanTree {
+    fn read_tree(&self) -> &[ReadHuffmanTree<BigEndian, i64>] {&self.read_be}
+
+    fn write_tree(&self) -> &WriteHuffmanTree<BigEndian, i64> {&self.write_be}
+}
+
+impl Compiled<LittleEndian> for PyHuffmanTree {
+    fn read_tree(&self) -> &[ReadHuffmanTree<LittleEndian, i64>] {
+        &self.read_le
+    }
+
+    fn write_tree(&self) -> &WriteHuffmanTree<LittleEndian, i64> {
+        &self.write_le
+    }
+}
+
+/// The `bitstream_io` Python module.
+///
+/// # Errors
+///
+/// Passes along any error adding the module's classes.
+#[pymodule]
+pub fn bitstream_io(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyBitReader>()?;
+    m.add_class::<PyBitWriter>()?;
+    m.add_class::<PyHuffmanTree>()?;
+    Ok(())
+}
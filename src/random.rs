@@ -0,0 +1,198 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reproducible pseudo-random bit streams.
+//!
+//! A `RandomSource` is an endless stream of bytes
+//! whose bits follow a `Pattern`, generated from a seed
+//! so that the same seed always gives the same stream,
+//! on every platform and in every version of this crate.
+//! Wrapped in a `BitReader` of the same endianness,
+//! it stress-tests decoders with input that's as
+//! random as required and reproducible from a failing seed,
+//! and feeds benchmarks without storing any test data.
+//! Taking a fixed number of bytes with `io::Read::take`
+//! gives a stream which ends.
+//!
+//! Bits are generated in stream order,
+//! so a pattern's runs are runs of the bits as a reader
+//! of the source's endianness reads them.
+//! The generator is SplitMix64, which isn't cryptographically secure.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitReader};
+//! use bitstream_io::random::{Pattern, RandomSource};
+//!
+//! let mut first = BitReader::<BigEndian, _>::from_read(
+//!     RandomSource::<BigEndian>::new(1234, Pattern::Uniform));
+//! let mut second = BitReader::<BigEndian, _>::from_read(
+//!     RandomSource::<BigEndian>::new(1234, Pattern::Uniform));
+//! for _ in 0..100 {
+//!     assert_eq!(first.read::<u32>(13).unwrap(),
+//!                second.read::<u32>(13).unwrap());
+//! }
+//!
+//! // runs of mostly-1 bits, averaging 20 1s between single 0s
+//! let pattern = Pattern::Runs{mean_zeros: 1.0, mean_ones: 20.0};
+//! let mut reader = BitReader::<BigEndian, _>::from_read(
+//!     RandomSource::<BigEndian>::new(1234, pattern));
+//! let ones = (0..1000).filter(|_| reader.read_bit().unwrap()).count();
+//! assert!(ones > 900);
+//! ```
+
+#![warn(missing_docs)]
+
+use core::marker::PhantomData;
+use io;
+
+use super::{BitQueue, Endianness};
+
+/// The statistics of a generated stream.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Pattern {
+    /// Independent bits, each equally likely to be 0 or 1
+    Uniform,
+    /// Independent bits, each 1 with the given probability
+    /// from 0 to 1
+    Density(f64),
+    /// Alternating runs of 0s and 1s whose lengths are
+    /// geometrically distributed, with the given mean lengths
+    /// of at least 1.
+    /// The stream's density of 1 bits is then
+    /// `mean_ones / (mean_zeros + mean_ones)`.
+    Runs {
+        /// The mean length of a run of 0 bits
+        mean_zeros: f64,
+        /// The mean length of a run of 1 bits
+        mean_ones: f64,
+    },
+}
+
+/// An endless stream of pseudo-random bytes,
+/// whose bits follow a pattern in the given endianness's order.
+#[derive(Clone, Debug)]
+pub struct RandomSource<E: Endianness> {
+    phantom: PhantomData<E>,
+    rng: SplitMix64,
+    model: Model,
+}
+
+impl<E: Endianness> RandomSource<E> {
+    /// Starts the stream generated from the given seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a `Density` isn't from 0 to 1,
+    /// or if a mean run length is less than 1.
+    pub fn new(seed: u64, pattern: Pattern) -> RandomSource<E> {
+        let mut rng = SplitMix64{state: seed};
+        let model = match pattern {
+            Pattern::Uniform => Model::Uniform{word: [0; 8], used: 8},
+            Pattern::Density(ones) => {
+                assert!((0.0..=1.0).contains(&ones),
+                        "density must be from 0 to 1");
+                Model::Density(threshold(ones))
+            }
+            Pattern::Runs{mean_zeros, mean_ones} => {
+                assert!(mean_zeros >= 1.0 && mean_ones >= 1.0,
+                        "mean run lengths must be at least 1");
+                Model::Runs{switch: [threshold(1.0 / mean_zeros),
+                                     threshold(1.0 / mean_ones)],
+                            bit: rng.next() >> 63 == 1}
+            }
+        };
+        RandomSource{phantom: PhantomData, rng, model}
+    }
+
+    // the next bit in stream order of a pattern generated bit by bit
+    fn next_bit(&mut self) -> bool {
+        match self.model {
+            Model::Uniform{..} => self.rng.next() >> 63 == 1,
+            Model::Density(ones) => u128::from(self.rng.next()) < ones,
+            Model::Runs{switch, ref mut bit} => {
+                let current = *bit;
+                if u128::from(self.rng.next()) < switch[usize::from(current)] {
+                    *bit = !current;
+                }
+                current
+            }
+        }
+    }
+}
+
+impl<E: Endianness> io::Read for RandomSource<E> {
+    /// Fills the whole buffer, since the stream never ends.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Model::Uniform{ref mut word, ref mut used} = self.model {
+            // each output is split into bytes, none of which are skipped,
+            // so the stream doesn't depend on the size of each read
+            for byte in buf.iter_mut() {
+                if *used == word.len() {
+                    *word = self.rng.next().to_le_bytes();
+                    *used = 0;
+                }
+                *byte = word[*used];
+                *used += 1;
+            }
+        } else {
+            for byte in buf.iter_mut() {
+                let mut queue: BitQueue<E, u8> = BitQueue::new();
+                while !queue.is_full() {
+                    let bit = self.next_bit();
+                    queue.push(1, u8::from(bit));
+                }
+                *byte = queue.pop(8);
+            }
+        }
+        Ok(buf.len())
+    }
+}
+
+// a pattern's probabilities, scaled to compare against
+// the generator's 64-bit outputs
+#[derive(Copy, Clone, Debug)]
+enum Model {
+    Uniform {
+        // the bytes of the latest output
+        word: [u8; 8],
+        // the number of those bytes already read
+        used: usize,
+    },
+    Density(u128),
+    Runs {
+        // the chance of a run of 0s or 1s ending after each bit
+        switch: [u128; 2],
+        // the next bit generated
+        bit: bool,
+    },
+}
+
+// a probability from 0 to 1 scaled so that an output
+// below it occurs with that probability
+fn threshold(probability: f64) -> u128 {
+    (probability * 18_446_744_073_709_551_616.0) as u128
+}
+
+// Sebastiano Vigna's SplitMix64 generator,
+// which gives well-mixed output from any seed, including 0
+#[derive(Clone, Debug)]
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
@@ -63,29 +63,1319 @@
 
 #![warn(missing_docs)]
 
-use std::boxed::Box;
-use std::io;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::convert::TryFrom;
+#[cfg(feature = "std")]
+use std::error;
+use core::fmt;
+use io::{self, Read};
 
-use super::{Numeric, SignedNumeric, BitQueue, BitWriter,
-            Endianness, BigEndian, LittleEndian};
+use super::{Numeric, SignedNumeric, Primitive, BitQueue, is_little_endian,
+            ranged_bits, Endianness, BigEndian, LittleEndian};
+#[cfg(feature = "alloc")]
+use super::BitWriter;
+#[cfg(feature = "alloc")]
 use huffman::ReadHuffmanTree;
+use huffman::HuffmanCode;
+use minifloat;
+use varint::{VarInt, ChunkOrder};
+use mixed::MixedEndian;
+#[cfg(feature = "alloc")]
+use format::{self, Field, Value};
+#[cfg(feature = "perf-counters")]
+use perf::PerfCounters;
+
+/// A trait for anything that can read a variable number of
+/// potentially un-aligned values from an input stream
+pub trait BitRead {
+    /// Reads a single bit from the stream.
+    /// `true` indicates 1, `false` indicates 0
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn read_bit(&mut self) -> Result<bool, io::Error>;
+
+    /// Reads an unsigned value from the stream with
+    /// the given number of bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Also returns an error if the output type is too small
+    /// to hold the requested number of bits.
+    fn read<U>(&mut self, bits: u32) -> Result<U, io::Error>
+        where U: Numeric;
+
+    /// Reads a twos-complement signed value from the stream with
+    /// the given number of bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Also returns an error if the output type is too small
+    /// to hold the requested number of bits.
+    fn read_signed<S>(&mut self, bits: u32) -> Result<S, io::Error>
+        where S: SignedNumeric;
+
+    /// Skips the given number of bits in the stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn skip(&mut self, bits: u32) -> Result<(), io::Error>;
+
+    /// Completely fills the given buffer with whole bytes.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), io::Error>;
+
+    /// Counts the number of 1 bits in the stream until the next
+    /// 0 bit and returns the amount read.
+    /// Because this field is variably-sized and may be large,
+    /// its output is always a `u32` type.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn read_unary0(&mut self) -> Result<u32, io::Error>;
+
+    /// Counts the number of 0 bits in the stream until the next
+    /// 1 bit and returns the amount read.
+    /// Because this field is variably-sized and may be large,
+    /// its output is always a `u32` type.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn read_unary1(&mut self) -> Result<u32, io::Error>;
+
+    /// Returns true if the stream is aligned at a whole byte.
+    fn byte_aligned(&self) -> bool;
+
+    /// Throws away all unread bit values until the next whole byte.
+    fn byte_align(&mut self);
+
+    /// Parses and returns a complex type
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the type's parser.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{self, Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitRead, BitReader, FromBitStream};
+    ///
+    /// struct Pair {
+    ///     first: u8,
+    ///     second: u8,
+    /// }
+    ///
+    /// impl FromBitStream for Pair {
+    ///     type Error = io::Error;
+    ///
+    ///     fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+    ///         Ok(Pair{first: r.read(4)?, second: r.read(4)?})
+    ///     }
+    /// }
+    ///
+    /// let data = [0b1011_0111];
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// let pair = reader.parse::<Pair>().unwrap();
+    /// assert_eq!(pair.first, 0b1011);
+    /// assert_eq!(pair.second, 0b0111);
+    /// ```
+    #[inline]
+    fn parse<F: FromBitStream>(&mut self) -> Result<F, F::Error> {
+        F::from_reader(self)
+    }
+
+    /// Parses a complex type if the condition is true,
+    /// or reads nothing and returns `None` otherwise
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the type's parser.
+    #[inline]
+    fn read_if<F: FromBitStream>(&mut self, condition: bool) ->
+        Result<Option<F>, F::Error> {
+        if condition {
+            F::from_reader(self).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Reads a single presence bit and, if it is 1,
+    /// parses a complex type following it
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream
+    /// or any error from the type's parser.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{self, Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitRead, BitReader, FromBitStream};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Crc(u8);
+    ///
+    /// impl FromBitStream for Crc {
+    ///     type Error = io::Error;
+    ///
+    ///     fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+    ///         r.read(7).map(Crc)
+    ///     }
+    /// }
+    ///
+    /// let data = [0b1101_0101, 0b0000_0000];
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// assert_eq!(reader.read_option_flagged::<Crc>().unwrap(),
+    ///            Some(Crc(0b101_0101)));
+    /// assert_eq!(reader.read_option_flagged::<Crc>().unwrap(), None);
+    /// ```
+    #[inline]
+    fn read_option_flagged<F>(&mut self) -> Result<Option<F>, F::Error>
+        where F: FromBitStream, F::Error: From<io::Error> {
+        let present = self.read_bit()?;
+        self.read_if(present)
+    }
+
+    /// Reads a Huffman code from the stream using a fixed table
+    /// and returns its symbol.
+    /// See `HuffmanCode` for how codes are stored.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an `InvalidData` error if no code in the table
+    /// matches the stream's next 32 bits.
+    fn read_huffman_code<T>(&mut self, table: &[HuffmanCode<T>]) ->
+        Result<T, io::Error> where T: Copy {
+        let mut code = 0u32;
+        let mut len = 0;
+        loop {
+            if let Some(entry) = table.iter().find(
+                |entry| (entry.len == len) && (entry.code == code)) {
+                return Ok(entry.symbol);
+            } else if len == 32 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          "no matching Huffman code"));
+            }
+            code = (code << 1) | (self.read_bit()? as u32);
+            len += 1;
+        }
+    }
+
+    /// Parses the given number of complex values of the same type
+    /// and returns them in order
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the type's parser.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{self, Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitRead, BitReader, FromBitStream};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Entry(u8);
+    ///
+    /// impl FromBitStream for Entry {
+    ///     type Error = io::Error;
+    ///
+    ///     fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+    ///         r.read(4).map(Entry)
+    ///     }
+    /// }
+    ///
+    /// let data = [0b1011_0111, 0b0001_0000];
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// assert_eq!(reader.parse_count::<Entry>(3).unwrap(),
+    ///            vec![Entry(0b1011), Entry(0b0111), Entry(0b0001)]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn parse_count<F: FromBitStream>(&mut self, count: usize) ->
+        Result<Vec<F>, F::Error> {
+        let mut values = Vec::new();
+        for _ in 0..count {
+            values.push(F::from_reader(self)?);
+        }
+        Ok(values)
+    }
+
+    /// Parses the given number of complex values of the same type
+    /// and combines them with the given function, without allocating.
+    /// Stops at the first error from either the parser or the function.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the type's parser or the function.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{self, Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitRead, BitReader, FromBitStream};
+    ///
+    /// struct Entry(u8);
+    ///
+    /// impl FromBitStream for Entry {
+    ///     type Error = io::Error;
+    ///
+    ///     fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+    ///         r.read(4).map(Entry)
+    ///     }
+    /// }
+    ///
+    /// let data = [0b1011_0111, 0b0001_0000];
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// let total = reader.parse_try_fold(3, 0u32, |total, entry: Entry| {
+    ///     Ok(total + u32::from(entry.0))
+    /// });
+    /// assert_eq!(total.unwrap(), 0b1011 + 0b0111 + 0b0001);
+    /// ```
+    fn parse_try_fold<F, B, G>(&mut self, count: usize, init: B, mut f: G) ->
+        Result<B, F::Error>
+        where F: FromBitStream, G: FnMut(B, F) -> Result<B, F::Error> {
+        let mut acc = init;
+        for _ in 0..count {
+            acc = f(acc, F::from_reader(self)?)?;
+        }
+        Ok(acc)
+    }
+
+    /// Runs the given function on a reader limited to the
+    /// given number of bits, then skips any of those bits
+    /// the function didn't read.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the function
+    /// or any I/O error from skipping its remaining bits.
+    /// Reads by the function beyond the limit return
+    /// `UnexpectedEof` errors without consuming anything.
+    fn read_limited<T, E, F>(&mut self, bits: u64, f: F) -> Result<T, E>
+        where F: FnOnce(&mut LimitedReader<Self>) -> Result<T, E>,
+              E: From<io::Error> {
+        let mut limited = LimitedReader{reader: self, remaining: bits};
+        let value = f(&mut limited)?;
+        let mut remaining = limited.remaining;
+        while remaining > 0 {
+            let to_skip = remaining.min(u64::from(u32::MAX)) as u32;
+            self.skip(to_skip)?;
+            remaining -= u64::from(to_skip);
+        }
+        Ok(value)
+    }
+
+    /// Runs the given function on a reader limited to the
+    /// given number of bits, requiring it to read all of them.
+    ///
+    /// This catches fields of the wrong width at the end
+    /// of the structure they belong to,
+    /// rather than somewhere in whatever follows it.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the function.
+    /// Reads by the function beyond the limit return
+    /// `UnexpectedEof` errors without consuming anything,
+    /// and if the function reads fewer bits than the limit,
+    /// an `InvalidData` error is returned instead of its value.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io;
+    /// use bitstream_io::{BigEndian, BitRead, BitReader};
+    /// let data = [0b1011_0111, 0xFF];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// let fields = reader.scoped_bits(8, |r| {
+    ///     Ok::<_, io::Error>((r.read::<u8>(3)?, r.read::<u8>(5)?))
+    /// });
+    /// assert_eq!(fields.unwrap(), (0b101, 0b10111));
+    ///
+    /// // a 4-bit field mistakenly read as 3 bits
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// let result = reader.scoped_bits(8, |r| {
+    ///     Ok::<_, io::Error>((r.read::<u8>(4)?, r.read::<u8>(3)?))
+    /// });
+    /// assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    /// ```
+    fn scoped_bits<T, E, F>(&mut self, bits: u64, f: F) -> Result<T, E>
+        where F: FnOnce(&mut LimitedReader<Self>) -> Result<T, E>,
+              E: From<io::Error> {
+        let mut limited = LimitedReader{reader: self, remaining: bits};
+        let value = f(&mut limited)?;
+        if limited.remaining == 0 {
+            Ok(value)
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidData,
+                               "fewer bits read than expected").into())
+        }
+    }
+
+    /// Runs the given function on a reader limited to the
+    /// given number of bits, which may read fewer of them.
+    ///
+    /// Unlike `read_limited`, any bits the function doesn't read
+    /// are left in the stream rather than skipped.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the function.
+    /// Reads by the function beyond the limit return
+    /// `UnexpectedEof` errors without consuming anything.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io;
+    /// use bitstream_io::{BigEndian, BitRead, BitReader};
+    /// let data = [0b1011_0111];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// let value = reader.scoped_bits_at_most(4, |r| r.read::<u8>(3));
+    /// assert_eq!(value.unwrap(), 0b101);
+    /// assert!(reader.scoped_bits_at_most(4, |r| r.read::<u8>(5)).is_err());
+    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10111);
+    /// ```
+    fn scoped_bits_at_most<T, E, F>(&mut self, bits: u64, f: F) ->
+        Result<T, E>
+        where F: FnOnce(&mut LimitedReader<Self>) -> Result<T, E> {
+        f(&mut LimitedReader{reader: self, remaining: bits})
+    }
+
+    /// Reads an unsigned length of the given number of bits,
+    /// then runs the given function on a reader limited to that
+    /// many bytes, skipping any bytes the function didn't read.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream
+    /// or any error from the function.
+    /// Reads by the function beyond the block return
+    /// `UnexpectedEof` errors without consuming anything.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitRead, BitReader};
+    /// // a tag, a length in bytes, and a value which has an unknown field
+    /// let data = [0x01, 0x03, 0xAB, 0xCD, 0xEF, 0x02];
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// assert_eq!(reader.read::<u8>(8).unwrap(), 1);
+    /// let value = reader.read_length_prefixed(8, |block| {
+    ///     block.read::<u16>(16)
+    /// }).unwrap();
+    /// assert_eq!(value, 0xABCD);
+    /// assert_eq!(reader.read::<u8>(8).unwrap(), 2);
+    /// ```
+    fn read_length_prefixed<T, E, F>(&mut self, length_bits: u32, f: F) ->
+        Result<T, E>
+        where F: FnOnce(&mut LimitedReader<Self>) -> Result<T, E>,
+              E: From<io::Error> {
+        let bytes: u64 = self.read(length_bits)?;
+        match bytes.checked_mul(8) {
+            Some(bits) => self.read_limited(bits, f),
+            None => Err(io::Error::new(io::ErrorKind::InvalidData,
+                                       "excessive block length").into()),
+        }
+    }
+
+    /// Returns a guard which reads from this stream
+    /// and requires it to be byte-aligned when the guard is finished.
+    ///
+    /// Sections of many formats must end on a byte boundary,
+    /// and a parser which reads one bit too many or too few
+    /// would otherwise go on to misread everything after them.
+    /// See `AlignmentGuard` for how the requirement is checked.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitRead, BitReader};
+    /// let data = [0b1011_0000, 0xFF];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// {
+    ///     let mut section = reader.require_alignment_on_exit();
+    ///     assert_eq!(section.read::<u8>(4).unwrap(), 0b1011);
+    ///     assert!(!section.is_aligned());
+    ///     section.skip(4).unwrap();
+    ///     section.finish().unwrap();
+    /// }
+    /// let mut section = reader.require_alignment_on_exit();
+    /// section.read::<u8>(3).unwrap();
+    /// assert!(section.finish().is_err());
+    /// ```
+    fn require_alignment_on_exit(&mut self) -> AlignmentGuard<'_, Self> {
+        AlignmentGuard::new(self, None)
+    }
+
+    /// Returns a guard which reads from this stream
+    /// and requires the number of bits read through it
+    /// to be a multiple of the given alignment
+    /// when the guard is finished.
+    /// An alignment of 0 or 1 always passes.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitRead, BitReader};
+    /// let data = [0xFF; 8];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// reader.read::<u8>(3).unwrap();
+    /// let mut section = reader.require_bit_alignment_on_exit(32);
+    /// section.read::<u32>(20).unwrap();
+    /// section.read::<u16>(12).unwrap();
+    /// assert!(section.finish().is_ok());
+    /// ```
+    fn require_bit_alignment_on_exit(&mut self, bits: u32) ->
+        AlignmentGuard<'_, Self> {
+        AlignmentGuard::new(self, Some(bits.max(1)))
+    }
+
+    /// Parses and returns a complex type whose layout
+    /// depends on some context, such as a previously parsed header
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the type's parser.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{self, Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitRead, BitReader, FromBitStreamWith};
+    ///
+    /// struct Header {
+    ///     sample_bits: u32,
+    /// }
+    ///
+    /// struct Sample(i32);
+    ///
+    /// impl FromBitStreamWith<Header> for Sample {
+    ///     type Error = io::Error;
+    ///
+    ///     fn from_reader<R: BitRead + ?Sized>(r: &mut R, header: &Header) ->
+    ///         io::Result<Self> {
+    ///         r.read_signed(header.sample_bits).map(Sample)
+    ///     }
+    /// }
+    ///
+    /// let data = [0b1011_0111];
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// let header = Header{sample_bits: 4};
+    /// assert_eq!(reader.parse_with::<_, Sample>(&header).unwrap().0, -5);
+    /// assert_eq!(reader.parse_with::<_, Sample>(&header).unwrap().0, 7);
+    /// ```
+    #[inline]
+    fn parse_with<C, F: FromBitStreamWith<C>>(&mut self, context: &C) ->
+        Result<F, F::Error> {
+        F::from_reader(self, context)
+    }
+}
+
+/// Implemented by complex types that know how to parse themselves
+/// from a `BitRead` stream, so that they may be composed
+/// into larger types.
+///
+/// With the `derive` feature enabled, this may also be derived
+/// for structs whose fields are annotated with their bit widths.
+/// See the `bitstream-io-derive` crate for the supported attributes.
+pub trait FromBitStream {
+    /// The error produced when parsing fails, such as `io::Error`
+    type Error;
+
+    /// Parses this type from the given reader
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error encountered while parsing.
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R) ->
+        Result<Self, Self::Error> where Self: Sized;
+}
+
+/// Implemented by complex types that need some context
+/// to parse themselves from a `BitRead` stream,
+/// such as field widths given by a previously parsed header.
+pub trait FromBitStreamWith<C> {
+    /// The error produced when parsing fails, such as `io::Error`
+    type Error;
+
+    /// Parses this type from the given reader using the given context
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error encountered while parsing.
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R, context: &C) ->
+        Result<Self, Self::Error> where Self: Sized;
+}
+
+/// An error from `BitReader::read_enum`.
+#[derive(Debug)]
+pub enum EnumError {
+    /// An error from the underlying stream
+    Io(io::Error),
+    /// A value which doesn't convert to the type being read
+    Unknown {
+        /// The value read
+        value: u64,
+        /// The stream position of the value's first bit
+        position: u64,
+    },
+}
+
+impl fmt::Display for EnumError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EnumError::Io(ref err) => err.fmt(f),
+            EnumError::Unknown{value, position} => {
+                write!(f, "unknown value {} at bit {}", value, position)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for EnumError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            EnumError::Io(ref err) => Some(err),
+            EnumError::Unknown{..} => None,
+        }
+    }
+}
+
+impl From<io::Error> for EnumError {
+    fn from(err: io::Error) -> Self {EnumError::Io(err)}
+}
+
+impl From<EnumError> for io::Error {
+    /// Unknown values become `InvalidData` errors
+    fn from(err: EnumError) -> Self {
+        match err {
+            EnumError::Io(err) => err,
+            #[cfg(feature = "std")]
+            unknown => io::Error::new(io::ErrorKind::InvalidData, unknown),
+            #[cfg(not(feature = "std"))]
+            EnumError::Unknown{..} => {
+                io::Error::new(io::ErrorKind::InvalidData, "unknown value")
+            }
+        }
+    }
+}
+
+/// A `BitRead` stream limited to a fixed number of bits,
+/// as used by `read_limited` and `read_length_prefixed`.
+///
+/// Any read which would go beyond the limit returns an
+/// `UnexpectedEof` error without consuming anything,
+/// and a read which the underlying stream rejects
+/// doesn't count against the limit.
+pub struct LimitedReader<'r, R: 'r + BitRead + ?Sized> {
+    reader: &'r mut R,
+    remaining: u64,
+}
+
+impl<'r, R: BitRead + ?Sized> LimitedReader<'r, R> {
+    /// Returns the number of bits remaining before the limit.
+    #[inline]
+    pub fn remaining(&self) -> u64 {self.remaining}
+}
+
+// Reserves the given number of bits from a reader's remaining limit
+// and reads them, handing the bits back if the read fails,
+// so a read which the underlying stream rejects,
+// such as one too wide for its type, doesn't use up the limit.
+pub(crate) fn read_within<R, T, F>(reader: &mut R,
+                                   remaining: &mut u64,
+                                   bits: u64,
+                                   message: &'static str,
+                                   read: F) -> Result<T, io::Error>
+    where R: ?Sized, F: FnOnce(&mut R) -> Result<T, io::Error> {
+    if bits > *remaining {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, message));
+    }
+    *remaining -= bits;
+    read(reader).inspect_err(|_| *remaining += bits)
+}
+
+impl<'r, R: BitRead + ?Sized> LimitedReader<'r, R> {
+    fn within<T, F>(&mut self, bits: u64, read: F) -> Result<T, io::Error>
+        where F: FnOnce(&mut R) -> Result<T, io::Error> {
+        read_within(self.reader, &mut self.remaining, bits,
+                    "read beyond end of limited block", read)
+    }
+}
+
+impl<'r, R: BitRead + ?Sized> BitRead for LimitedReader<'r, R> {
+    #[inline]
+    fn read_bit(&mut self) -> Result<bool, io::Error> {
+        self.within(1, |r| r.read_bit())
+    }
+
+    #[inline]
+    fn read<U>(&mut self, bits: u32) -> Result<U, io::Error>
+        where U: Numeric {
+        self.within(u64::from(bits), |r| r.read(bits))
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> Result<S, io::Error>
+        where S: SignedNumeric {
+        self.within(u64::from(bits), |r| r.read_signed(bits))
+    }
+
+    #[inline]
+    fn skip(&mut self, bits: u32) -> Result<(), io::Error> {
+        self.within(u64::from(bits), |r| r.skip(bits))
+    }
+
+    #[inline]
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        self.within(buf.len() as u64 * 8, |r| r.read_bytes(buf))
+    }
+
+    fn read_unary0(&mut self) -> Result<u32, io::Error> {
+        let mut count = 0;
+        while self.read_bit()? {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn read_unary1(&mut self) -> Result<u32, io::Error> {
+        let mut count = 0;
+        while !self.read_bit()? {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.reader.byte_aligned()
+    }
+
+    fn byte_align(&mut self) {
+        while !self.reader.byte_aligned() && (self.remaining > 0) {
+            if self.read_bit().is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// A `BitRead` stream which must end a section aligned,
+/// as returned by `require_alignment_on_exit`
+/// and `require_bit_alignment_on_exit`.
+///
+/// `finish` returns an `InvalidData` error if the stream
+/// isn't aligned as required.
+/// Since a guard can't return an error when simply dropped,
+/// one which goes out of scope misaligned without being finished
+/// panics instead in debug builds, unless a read through it failed,
+/// so forgetting to finish a misaligned section isn't silent.
+/// Release builds check nothing on drop.
+pub struct AlignmentGuard<'r, R: 'r + BitRead + ?Sized> {
+    reader: &'r mut R,
+    // the required multiple of bits read, or None for byte alignment
+    alignment: Option<u32>,
+    read: u64,
+    // whether the guard has been finished or a read has failed,
+    // either of which skips the check on drop
+    settled: bool,
+}
+
+impl<'r, R: BitRead + ?Sized> AlignmentGuard<'r, R> {
+    fn new(reader: &'r mut R, alignment: Option<u32>) ->
+        AlignmentGuard<'r, R> {
+        AlignmentGuard{reader, alignment, read: 0, settled: false}
+    }
+
+    /// Returns the number of bits read through the guard so far.
+    #[inline]
+    pub fn bits_read(&self) -> u64 {self.read}
+
+    /// Returns true if the stream is aligned as required.
+    pub fn is_aligned(&self) -> bool {
+        match self.alignment {
+            None => self.reader.byte_aligned(),
+            Some(bits) => self.read.is_multiple_of(u64::from(bits)),
+        }
+    }
+
+    /// Ends the section, checking the stream's alignment.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` error if the stream
+    /// isn't aligned as required.
+    pub fn finish(mut self) -> Result<(), io::Error> {
+        self.settled = true;
+        if self.is_aligned() {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidData,
+                               "section did not end aligned"))
+        }
+    }
+
+    // counts the bits of a successful read,
+    // or notes that a read has failed
+    fn counted<V>(&mut self, bits: u64, result: io::Result<V>) ->
+        io::Result<V> {
+        match result {
+            Ok(_) => self.read += bits,
+            Err(_) => self.settled = true,
+        }
+        result
+    }
+}
+
+impl<'r, R: BitRead + ?Sized> Drop for AlignmentGuard<'r, R> {
+    fn drop(&mut self) {
+        #[cfg(feature = "std")]
+        let panicking = std::thread::panicking();
+        #[cfg(not(feature = "std"))]
+        let panicking = false;
+        if cfg!(debug_assertions) && !self.settled && !panicking {
+            assert!(self.is_aligned(), "section did not end aligned");
+        }
+    }
+}
+
+impl<'r, R: BitRead + ?Sized> BitRead for AlignmentGuard<'r, R> {
+    fn read_bit(&mut self) -> Result<bool, io::Error> {
+        let result = self.reader.read_bit();
+        self.counted(1, result)
+    }
+
+    fn read<U>(&mut self, bits: u32) -> Result<U, io::Error>
+        where U: Numeric {
+        let result = self.reader.read(bits);
+        self.counted(u64::from(bits), result)
+    }
+
+    fn read_signed<S>(&mut self, bits: u32) -> Result<S, io::Error>
+        where S: SignedNumeric {
+        let result = self.reader.read_signed(bits);
+        self.counted(u64::from(bits), result)
+    }
+
+    fn skip(&mut self, bits: u32) -> Result<(), io::Error> {
+        let result = self.reader.skip(bits);
+        self.counted(u64::from(bits), result)
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        let result = self.reader.read_bytes(buf);
+        self.counted(buf.len() as u64 * 8, result)
+    }
+
+    fn read_unary0(&mut self) -> Result<u32, io::Error> {
+        let result = self.reader.read_unary0();
+        let bits = result.as_ref().map(|&v| u64::from(v) + 1).unwrap_or(0);
+        self.counted(bits, result)
+    }
+
+    fn read_unary1(&mut self) -> Result<u32, io::Error> {
+        let result = self.reader.read_unary1();
+        let bits = result.as_ref().map(|&v| u64::from(v) + 1).unwrap_or(0);
+        self.counted(bits, result)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.reader.byte_aligned()
+    }
+
+    fn byte_align(&mut self) {
+        while !self.reader.byte_aligned() && self.read_bit().is_ok() {}
+    }
+}
 
 /// For reading non-aligned bits from a stream of bytes in a given endianness.
 ///
 /// This will read exactly as many whole bytes needed to return
 /// the requested number of bits.  It may cache up to a single partial byte
 /// but no more.
-pub struct BitReader<E: Endianness> {
-    reader: Box<io::Read>,
-    bitqueue: BitQueue<E,u8>
+///
+/// The underlying stream is boxed by default, but may be any type
+/// implementing `Read` - such as a byte slice, which requires
+/// no allocation at all.
+/// Fields are read through a 64-bit cache, which is refilled
+/// with all the whole bytes a field needs, up to 8 at a time,
+/// in a single read from the stream.
+/// Since the stream is never read past the byte holding
+/// the last bit read, only a partial byte remains cached between reads.
+/// Buffered streams of many small fields may also be read through
+/// a `word::WordReader`, which caches 64 bits at a time
+/// from the stream's buffer without consuming any more of it,
+/// and `into_word_reader` switches to one part way through a stream.
+///
+/// A reader is `Send` or `Sync` whenever its stream is,
+/// so readers meant for other threads should be built with `from_read`
+/// rather than around a boxed stream.
+pub struct BitReader<E: Endianness, R = DefaultReader> {
+    reader: Counted<R>,
+    // holds no more than a partial byte between reads
+    bitqueue: BitQueue<E,u64>
+}
+
+/// The underlying stream of a `BitReader` whose type isn't given.
+#[cfg(feature = "alloc")]
+pub type DefaultReader = Box<dyn io::Read>;
+
+/// The underlying stream of a `BitReader` whose type isn't given.
+#[cfg(not(feature = "alloc"))]
+pub type DefaultReader = &'static [u8];
+
+// Counts the bytes read from the underlying stream,
+// so that readers know their position.
+#[derive(Clone)]
+struct Counted<R> {
+    reader: R,
+    bytes: u64,
+    #[cfg(feature = "perf-counters")]
+    counters: PerfCounters,
+}
+
+impl<R: io::Read> Read for Counted<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.bytes += read as u64;
+        #[cfg(feature = "perf-counters")]
+        {
+            self.counters.refills += 1;
+            self.counters.bytes += read as u64;
+        }
+        Ok(read)
+    }
 }
 
+#[cfg(feature = "std")]
+impl<R: io::BufRead> io::BufRead for Counted<R> {
+    #[inline]
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        #[cfg(feature = "perf-counters")]
+        {
+            self.counters.refills += 1;
+        }
+        self.reader.fill_buf()
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.reader.consume(amt);
+        self.bytes += amt as u64;
+        #[cfg(feature = "perf-counters")]
+        {
+            self.counters.bytes += amt as u64;
+        }
+    }
+}
+
+/// Cloning a reader clones its stream along with any partial byte
+/// not yet read, so the clone continues from the same bit.
+/// Over in-memory data such as a byte slice or `Cursor`,
+/// this is a cheap way to look ahead without disturbing the original.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitReader};
+/// let data = [0b1011_0110, 0xFF];
+/// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+/// assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+/// let mut lookahead = reader.clone();
+/// assert_eq!(lookahead.read::<u16>(13).unwrap(), 0b1_0110_1111_1111);
+/// assert!(lookahead.read::<u8>(1).is_err());
+/// assert_eq!(reader.read::<u8>(5).unwrap(), 0b1_0110);
+/// assert_eq!(reader.position_in_bits(), 8);
+/// ```
+impl<E: Endianness, R: Clone> Clone for BitReader<E, R> {
+    fn clone(&self) -> Self {
+        BitReader{reader: self.reader.clone(),
+                  bitqueue: self.bitqueue.clone()}
+    }
+}
+
+/// Shows the number of whole bytes read from the stream
+/// along with the bits of any partial byte not yet read.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitReader};
+/// let data = [0b1011_0110, 0xFF];
+/// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+/// reader.read::<u8>(3).unwrap();
+/// assert_eq!(format!("{:?}", reader),
+///            "BitReader { byte_position: 1, pending_bits: 5, \
+///             pending_value: 0b10110 }");
+/// ```
+impl<E: Endianness, R> fmt::Debug for BitReader<E, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bits = self.bitqueue.len();
+        f.debug_struct("BitReader")
+            .field("byte_position", &self.reader.bytes)
+            .field("pending_bits", &bits)
+            .field("pending_value", &format_args!("{:#0w$b}",
+                                                  self.bitqueue.value,
+                                                  w = bits as usize + 2))
+            .finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E: Endianness, R: io::Read + Clone> BitReader<E, R> {
+    /// Returns a printout of up to the given number of upcoming bits,
+    /// read from a clone of the reader so that none are consumed.
+    ///
+    /// The printout starts with the current position in bits,
+    /// followed by one line per byte with the byte's offset,
+    /// its value in hex and its bits in the order they're read.
+    /// Bits before the current position or past the last one
+    /// printed are shown as `.`, in which case the byte's value
+    /// is shown as `--`.
+    /// If the stream ends first, the printout says so.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream
+    /// other than reaching its end.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b1011_0110, 0xFF, 0x12];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// reader.read::<u8>(3).unwrap();
+    /// assert_eq!(reader.dump_next(15).unwrap(),
+    ///            "bit 3:\n\
+    ///             00000000 -- ...10110\n\
+    ///             00000001 ff 11111111\n\
+    ///             00000002 -- 00......");
+    /// assert_eq!(reader.dump_next(40).unwrap().lines().last(),
+    ///            Some("(end of stream)"));
+    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10110);
+    /// ```
+    pub fn dump_next(&self, bits: u32) -> Result<String, io::Error> {
+        use core::fmt::Write;
+
+        let mut view = self.clone();
+        let mut position = self.position_in_bits();
+        let end = position + u64::from(bits);
+        let mut dump = format!("bit {}:", position);
+        while position < end {
+            let byte_start = position - position % 8;
+            let mut line = String::with_capacity(8);
+            let mut value = 0u8;
+            let mut whole = true;
+            for (index, bit) in (byte_start..byte_start + 8).enumerate() {
+                if bit < position || bit >= end {
+                    line.push('.');
+                    whole = false;
+                    continue;
+                }
+                match view.read_bit() {
+                    Ok(one) => {
+                        line.push(if one {'1'} else {'0'});
+                        if one {
+                            value |= if is_little_endian::<E>() {
+                                1 << index
+                            } else {
+                                0x80 >> index
+                            };
+                        }
+                    }
+                    Err(ref err)
+                        if err.kind() == io::ErrorKind::UnexpectedEof => {
+                        if line.chars().any(|c| c != '.') {
+                            while line.len() < 8 {
+                                line.push('.');
+                            }
+                            let _ = write!(dump, "\n{:08x} -- {}",
+                                           byte_start / 8, line);
+                        }
+                        dump.push_str("\n(end of stream)");
+                        return Ok(dump);
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+            let _ = if whole {
+                write!(dump, "\n{:08x} {:02x} {}", byte_start / 8, value, line)
+            } else {
+                write!(dump, "\n{:08x} -- {}", byte_start / 8, line)
+            };
+            position = byte_start + 8;
+        }
+        Ok(dump)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: Endianness, R: io::Read + io::Seek> BitReader<E, R> {
+    /// Moves the stream to the given position in bits,
+    /// counting from where it was when the BitReader was created,
+    /// as returned by `position_in_bits`.
+    ///
+    /// The underlying stream is seeked to the byte containing
+    /// that bit, so positions before or after the current one
+    /// can be reached without reading any data in between.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b1011_0011, 0b1100_0110];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(Cursor::new(data));
+    /// reader.seek_bits(6).unwrap();
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0b1111);
+    /// assert_eq!(reader.position_in_bits(), 10);
+    /// reader.seek_bits(1).unwrap();
+    /// assert_eq!(reader.read::<u8>(3).unwrap(), 0b011);
+    /// ```
+    pub fn seek_bits(&mut self, position: u64) -> Result<(), io::Error> {
+        use std::io::SeekFrom;
+
+        let target = position / 8;
+        let delta = i64::try_from(target).and_then(|t| {
+            i64::try_from(self.reader.bytes).map(|b| t - b)
+        }).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput,
+                                      "seek position too large"))?;
+        self.reader.reader.seek(SeekFrom::Current(delta))?;
+        self.reader.bytes = target;
+        self.bitqueue.clear();
+        self.skip((position % 8) as u32)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: Endianness, R: io::BufRead> BitReader<E, R> {
+    /// Skips the given number of bits in a buffered stream.
+    ///
+    /// Whereas `skip` copies the bytes it skips out of the stream,
+    /// these are discarded directly from the stream's buffer,
+    /// which makes skipping large regions much faster
+    /// for sources that can't be seeked, such as pipes.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::BufReader;
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0xFF; 1000];
+    /// let stream = BufReader::new(&data[..]);
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(stream);
+    /// reader.skip_buffered(7995).unwrap();
+    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b11111);
+    /// assert!(reader.read::<u8>(1).is_err());
+    /// ```
+    pub fn skip_buffered(&mut self, mut bits: u32) -> Result<(), io::Error> {
+        use core::cmp::min;
+
+        let to_drop = min(self.bitqueue.len(), bits);
+        if to_drop != 0 {
+            self.bitqueue.drop(to_drop);
+            bits -= to_drop;
+        }
+
+        consume_bytes(&mut self.reader, u64::from(bits / 8))
+        .and_then(|()| skip_unaligned(&mut self.reader,
+                                      bits % 8,
+                                      &mut self.bitqueue))
+    }
+
+    /// Counts the number of 1 bits in a buffered stream until the next
+    /// 0 bit and returns the amount read.
+    ///
+    /// This is the same as `read_unary0`,
+    /// but long runs are scanned directly in the stream's buffer
+    /// a chunk at a time rather than read out a byte at a time.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::BufReader;
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let mut data = vec![0xFF; 1000];
+    /// data.push(0b1101_1110);
+    /// let stream = BufReader::new(&data[..]);
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(stream);
+    /// assert_eq!(reader.read::<u8>(3).unwrap(), 0b111);
+    /// assert_eq!(reader.read_unary0_buffered().unwrap(), 7999);
+    /// assert_eq!(reader.read_unary0_buffered().unwrap(), 4);
+    /// ```
+    pub fn read_unary0_buffered(&mut self) -> Result<u32, io::Error> {
+        let base = self.bitqueue.leading_1s();
+        if base < self.bitqueue.len() {
+            self.bitqueue.drop(base + 1);
+            Ok(base)
+        } else {
+            self.bitqueue.clear();
+            read_buffered_unary(&mut self.reader,
+                                0b11111111,
+                                &mut self.bitqueue).map(
+                |u| base + u + self.bitqueue.pop_1())
+        }
+    }
+
+    /// Counts the number of 0 bits in a buffered stream until the next
+    /// 1 bit and returns the amount read.
+    ///
+    /// This is the same as `read_unary1`,
+    /// but long runs are scanned directly in the stream's buffer
+    /// a chunk at a time rather than read out a byte at a time.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::BufReader;
+    /// use bitstream_io::{LittleEndian, BitReader};
+    /// let mut data = vec![0; 1000];
+    /// data.push(0b0000_0100);
+    /// let stream = BufReader::new(&data[..]);
+    /// let mut reader = BitReader::<LittleEndian, _>::from_read(stream);
+    /// assert_eq!(reader.read_unary1_buffered().unwrap(), 8002);
+    /// assert!(reader.read_unary1_buffered().is_err());
+    /// ```
+    pub fn read_unary1_buffered(&mut self) -> Result<u32, io::Error> {
+        let base = self.bitqueue.leading_0s();
+        if base < self.bitqueue.len() {
+            self.bitqueue.drop(base + 1);
+            Ok(base)
+        } else {
+            self.bitqueue.clear();
+            read_buffered_unary(&mut self.reader,
+                                0b00000000,
+                                &mut self.bitqueue).map(
+                |u| base + u + self.bitqueue.pop_0())
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
 impl<E: Endianness> BitReader<E> {
     /// Wraps a BitReader around something that implements `Read`
-    pub fn new(reader: Box<io::Read>) -> BitReader<E> {
-        BitReader{reader: reader, bitqueue: BitQueue::new()}
+    pub fn new(reader: Box<dyn io::Read>) -> BitReader<E> {
+        BitReader::from_read(reader)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E: Endianness> BitReader<E, io::Cursor<Vec<u8>>> {
+    /// Wraps a BitReader around a buffer, taking ownership of it.
+    ///
+    /// Since the reader's stream is then an in-memory `Cursor`,
+    /// it may be seeked and its buffered methods,
+    /// such as `skip_buffered`, work from the buffer directly.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let mut reader = BitReader::<BigEndian, _>::from_vec(vec![0xA1, 0x23]);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0xA);
+    /// reader.seek_bits(8).unwrap();
+    /// assert_eq!(reader.read::<u8>(8).unwrap(), 0x23);
+    /// assert_eq!(reader.into_byte_reader().into_inner(), [0xA1, 0x23]);
+    /// ```
+    pub fn from_vec(data: Vec<u8>) -> BitReader<E, io::Cursor<Vec<u8>>> {
+        BitReader::from_read(io::Cursor::new(data))
+    }
+}
+
+impl<E: Endianness, const N: usize> BitReader<E, io::Cursor<[u8; N]>> {
+    /// Wraps a BitReader around an array, taking ownership of it,
+    /// so that a reader may be returned from a function
+    /// without borrowing its data.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{LittleEndian, BitReader};
+    /// let mut r = BitReader::<LittleEndian, _>::from_bytes([0xA1, 0x23]);
+    /// assert_eq!(r.read::<u16>(12).unwrap(), 0x3A1);
+    /// assert_eq!(r.read::<u8>(4).unwrap(), 0x2);
+    /// ```
+    pub fn from_bytes(data: [u8; N]) -> BitReader<E, io::Cursor<[u8; N]>> {
+        BitReader::from_read(io::Cursor::new(data))
+    }
+}
+
+impl<E: Endianness, R: io::Read> BitReader<E, R> {
+    /// Wraps a BitReader around a stream of any type implementing `Read`
+    /// without boxing it.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b1011_0000];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0b1011);
+    /// ```
+    pub const fn from_read(reader: R) -> BitReader<E, R> {
+        BitReader{reader: Counted{reader,
+                                  bytes: 0,
+                                  #[cfg(feature = "perf-counters")]
+                                  counters: PerfCounters::new()},
+                  bitqueue: BitQueue::empty()}
+    }
+
+    /// Returns the number of bits read from the stream so far,
+    /// counting from where it was when the BitReader was created.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0xFF; 4];
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// assert_eq!(reader.position_in_bits(), 0);
+    /// reader.read::<u8>(3).unwrap();
+    /// assert_eq!(reader.position_in_bits(), 3);
+    /// reader.skip(20).unwrap();
+    /// assert_eq!(reader.position_in_bits(), 23);
+    /// ```
+    #[inline]
+    pub fn position_in_bits(&self) -> u64 {
+        self.reader.bytes * 8 - u64::from(self.bitqueue.len())
     }
 
+    /// Returns counts of the reader's use of its underlying stream
+    /// since it was created.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0x12, 0x34, 0x56];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// reader.read::<u32>(20).unwrap();
+    /// assert_eq!(reader.perf_counters().bytes, 3);
+    /// assert_eq!(reader.perf_counters().misaligned, 0);
+    /// ```
+    #[cfg(feature = "perf-counters")]
+    #[inline]
+    pub fn perf_counters(&self) -> PerfCounters {self.reader.counters}
+
     /// Reads a single bit from the stream.
     /// `true` indicates 1, `false` indicates 0
     ///
@@ -129,7 +1419,7 @@ impl<E: Endianness> BitReader<E> {
     #[inline(always)]
     pub fn read_bit(&mut self) -> Result<bool, io::Error> {
         if self.bitqueue.is_empty() {
-            self.bitqueue.set(read_byte(&mut self.reader)?, 8);
+            self.bitqueue.set(u64::from(read_byte(&mut self.reader)?), 8);
         }
         Ok(self.bitqueue.pop(1) == 1)
     }
@@ -177,26 +1467,199 @@ impl<E: Endianness> BitReader<E> {
     /// assert!(reader.read::<u32>(33).is_err());  // can't read 33 bits to u32
     /// assert!(reader.read::<u64>(65).is_err());  // can't read 65 bits to u64
     /// ```
-    pub fn read<U>(&mut self, mut bits: u32) -> Result<U, io::Error>
+    pub fn read<U>(&mut self, bits: u32) -> Result<U, io::Error>
         where U: Numeric {
 
         if bits <= U::bits_size() {
-            let bitqueue_len = self.bitqueue.len();
-            if bits <= bitqueue_len {
-                Ok(U::from_u8(self.bitqueue.pop(bits)))
-            } else {
-                let mut acc = BitQueue::from_value(
-                    U::from_u8(self.bitqueue.pop(bitqueue_len)),
-                    bitqueue_len);
-                bits -= bitqueue_len;
-
-                read_aligned(&mut self.reader, bits / 8, &mut acc)
-                .and_then(|()| read_unaligned(&mut self.reader,
-                                              bits % 8,
-                                              &mut acc,
-                                              &mut self.bitqueue))
-                .map(|()| acc.value())
+            self.read_unchecked(bits)
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidInput,
+                               "excessive bits for type read"))
+        }
+    }
+
+    /// Reads an unsigned value from the stream with
+    /// the given number of bits, without first checking
+    /// that the output type is large enough to hold them.
+    ///
+    /// This is meant for tight loops reading fields
+    /// whose widths are constants already known to be valid.
+    /// No `unsafe` code is involved, but a width
+    /// larger than the output type gives a meaningless result
+    /// or a panic.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the output type is too small
+    /// to hold the requested number of bits.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b1011_0111, 0xFF];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// assert_eq!(reader.read_unchecked::<u8>(3).unwrap(), 0b101);
+    /// assert_eq!(reader.read_unchecked::<u16>(13).unwrap(), 0x17FF);
+    /// ```
+    #[inline]
+    pub fn read_unchecked<U>(&mut self, bits: u32) -> Result<U, io::Error>
+        where U: Numeric {
+
+        debug_assert!(bits <= U::bits_size());
+
+        if bits <= self.bitqueue.len() {
+            Ok(U::from_u64(self.bitqueue.pop(bits)))
+        } else if self.bitqueue.is_empty() && bits.is_multiple_of(8) &&
+                  bits <= 64 {
+            // whole bytes from a byte-aligned stream,
+            // as is common for headers, are read at once
+            read_aligned_value::<E,U>(&mut self.reader, bits / 8)
+        } else {
+            // values of up to 64 bits take a single refill,
+            // and larger ones take one per word
+            let mut acc = BitQueue::<E,U>::new();
+            let mut remaining = bits;
+            while remaining > self.bitqueue.len() {
+                let queued = self.bitqueue.len();
+                acc.push(queued, U::from_u64(self.bitqueue.pop(queued)));
+                remaining -= queued;
+                self.refill(remaining)?;
             }
+            acc.push(remaining, U::from_u64(self.bitqueue.pop(remaining)));
+            Ok(acc.value())
+        }
+    }
+
+    // fills the empty cache with the whole bytes holding
+    // the given number of upcoming bits, up to 8 bytes at once,
+    // so the stream is never read past the byte holding the last bit
+    fn refill(&mut self, bits: u32) -> Result<(), io::Error> {
+        debug_assert!(self.bitqueue.is_empty());
+
+        let bytes = bits.div_ceil(8).min(8);
+        let mut buf = [0; 8];
+        self.reader.read_exact(&mut buf[0..bytes as usize])?;
+        let mut word = BitQueue::<E,u64>::from_value(E::from_bytes(buf), 64);
+        self.bitqueue.set(word.pop(bytes * 8), bytes * 8);
+        Ok(())
+    }
+
+    /// Reads an unsigned value from the stream with
+    /// the given number of bits, stopping early at the end of the stream.
+    ///
+    /// Returns the value of the bits obtained, as though the field
+    /// were only that wide, along with how many there were.
+    /// A truncated stream's last field may then be used as far as it goes,
+    /// as progressive decoders rendering a partial download need.
+    /// Fewer bits than requested means the stream has ended
+    /// and every bit of it has been read.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream
+    /// other than reaching its end.
+    /// Also returns an error if the output type is too small
+    /// to hold the requested number of bits.
+    ///
+    /// # Examples
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b1011_0111, 0b0101_0011];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// assert_eq!(reader.read_partial::<u8>(3).unwrap(), (0b101, 3));
+    /// assert_eq!(reader.read_partial::<u32>(20).unwrap(),
+    ///            (0b1_0111_0101_0011, 13));
+    /// assert_eq!(reader.read_partial::<u8>(8).unwrap(), (0, 0));
+    /// ```
+    ///
+    /// ```
+    /// use bitstream_io::{LittleEndian, BitReader};
+    /// let data = [0b1011_0111, 0b0101_0011];
+    /// let mut reader = BitReader::<LittleEndian, _>::from_read(&data[..]);
+    /// assert_eq!(reader.read_partial::<u8>(3).unwrap(), (0b111, 3));
+    /// assert_eq!(reader.read_partial::<u32>(20).unwrap(),
+    ///            (0b0101_0011_1011_0, 13));
+    /// ```
+    pub fn read_partial<U>(&mut self, bits: u32) ->
+        Result<(U, u32), io::Error> where U: Numeric {
+
+        if bits > U::bits_size() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive bits for type read"));
+        }
+
+        let queued = bits.min(self.bitqueue.len());
+        let mut acc = BitQueue::<E,U>::from_value(
+            U::from_u64(self.bitqueue.pop(queued)), queued);
+        let mut remaining = bits - queued;
+        while remaining > 0 {
+            let byte = match read_byte(&mut self.reader) {
+                Ok(byte) => byte,
+                Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    break;
+                }
+                Err(err) => return Err(err),
+            };
+            let taken = remaining.min(8);
+            self.bitqueue.set(u64::from(byte), 8);
+            acc.push(taken, U::from_u64(self.bitqueue.pop(taken)));
+            remaining -= taken;
+        }
+        let read = acc.len();
+        Ok((acc.value(), read))
+    }
+
+    /// Reads a twos-complement signed value from the stream with
+    /// the given number of bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Also returns an error if the output type is too small
+    /// to hold the requested number of bits.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b10110111];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), -5);
+    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), 7);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader};
+    /// let data = [0b10110111];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<LittleEndian>::new(Box::new(cursor));
+    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), 7);
+    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), -5);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0;10];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut r = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// assert!(r.read_signed::<i8>(9).is_err());   // can't read 9 bits to i8
+    /// assert!(r.read_signed::<i16>(17).is_err()); // can't read 17 bits to i16
+    /// assert!(r.read_signed::<i32>(33).is_err()); // can't read 33 bits to i32
+    /// assert!(r.read_signed::<i64>(65).is_err()); // can't read 65 bits to i64
+    /// ```
+    #[inline]
+    pub fn read_signed<S>(&mut self, bits: u32) -> Result<S, io::Error>
+        where S: SignedNumeric {
+
+        if bits <= S::bits_size() {
+            E::read_signed(self, bits)
         } else {
             Err(io::Error::new(io::ErrorKind::InvalidInput,
                                "excessive bits for type read"))
@@ -211,6 +1674,8 @@ impl<E: Endianness> BitReader<E> {
     /// which may be skipped.
     /// These bits are still read from the stream, however,
     /// and are never skipped via a `seek` method.
+    /// Buffered streams may use `skip_buffered` instead,
+    /// which discards them without copying.
     ///
     /// # Errors
     ///
@@ -229,58 +1694,361 @@ impl<E: Endianness> BitReader<E> {
     ///
     /// ```
     /// use std::io::{Read, Cursor};
-    /// use bitstream_io::{LittleEndian, BitReader};
-    /// let data = [0b10110111];
-    /// let mut cursor = Cursor::new(data);
-    /// let mut reader = BitReader::<LittleEndian>::new(Box::new(cursor));
-    /// assert!(reader.skip(3).is_ok());
-    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10110);
+    /// use bitstream_io::{LittleEndian, BitReader};
+    /// let data = [0b10110111];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<LittleEndian>::new(Box::new(cursor));
+    /// assert!(reader.skip(3).is_ok());
+    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10110);
+    /// ```
+    pub fn skip(&mut self, mut bits: u32) -> Result<(), io::Error> {
+        use core::cmp::min;
+
+        let to_drop = min(self.bitqueue.len(), bits);
+        if to_drop != 0 {
+            self.bitqueue.drop(to_drop);
+            bits -= to_drop;
+        }
+
+        skip_aligned(&mut self.reader, bits / 8)
+        .and_then(|()| skip_unaligned(&mut self.reader,
+                                      bits % 8,
+                                      &mut self.bitqueue))
+    }
+
+    /// Completely fills the given buffer with whole bytes.
+    /// This maps to a single `read_exact` call on the underlying stream.
+    /// If the stream isn't byte-aligned, the bytes read are then
+    /// shifted against the bits of the partial byte.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = b"foobar";
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// assert!(reader.skip(24).is_ok());
+    /// let mut buf = [0;3];
+    /// assert!(reader.read_bytes(&mut buf).is_ok());
+    /// assert_eq!(&buf, b"bar");
+    /// ```
+    pub fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        if self.byte_aligned() {
+            self.reader.read_exact(buf)
+        } else {
+            // the whole bytes are read in one go and then shifted
+            // in place against the bits of the pending partial byte,
+            // whose remainder carries over from each byte to the next
+            #[cfg(feature = "perf-counters")]
+            {
+                self.reader.counters.misaligned += 1;
+            }
+            self.reader.read_exact(buf)?;
+            let pending = self.bitqueue.len();
+            let partial = self.bitqueue.value as u8;
+            let carry = if is_little_endian::<E>() {
+                shift_bytes_le(buf, pending, partial)
+            } else {
+                shift_bytes_be(buf, pending, partial)
+            };
+            self.bitqueue.set(u64::from(carry), pending);
+            Ok(())
+        }
+    }
+
+    /// Reads 7-bit characters, packed without padding
+    /// as in SMS and other telematics formats, to fill the buffer.
+    ///
+    /// Each character is read as a 7-bit value in the stream's bit order,
+    /// so a little-endian reader unpacks the GSM 03.38 layout,
+    /// whose first character is in the lowest bits of the first byte,
+    /// while a big-endian reader unpacks characters
+    /// from the highest bits down.
+    /// No character set is applied; each byte is the value read.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{LittleEndian, BitReader};
+    /// let data = [0xE8, 0x32, 0x9B, 0xFD, 0x06];
+    /// let mut reader = BitReader::<LittleEndian, _>::from_read(&data[..]);
+    /// let mut text = [0; 5];
+    /// reader.read_ascii7(&mut text).unwrap();
+    /// assert_eq!(&text, b"hello");
+    /// ```
+    pub fn read_ascii7(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        for c in buf.iter_mut() {
+            *c = self.read_unchecked::<u8>(7)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the given number of packed 7-bit characters, as with
+    /// `read_ascii7`, into a string.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b1000001_1, 0b000010_00];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// assert_eq!(reader.read_ascii7_string(2).unwrap(), "AB");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn read_ascii7_string(&mut self, chars: usize) ->
+        Result<String, io::Error> {
+        let mut text = Vec::with_capacity(chars);
+        for _ in 0..chars {
+            text.push(self.read_unchecked::<u8>(7)?);
+        }
+        // every 7-bit value is an ASCII character
+        Ok(text.into_iter().map(char::from).collect())
+    }
+
+    /// Reads an unsigned 24-bit value from the stream.
+    /// If the stream is byte-aligned, this maps to
+    /// a single 3 byte read from the underlying stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, LittleEndian, BitReader};
+    /// let data = [0x12, 0x34, 0x56];
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// assert_eq!(reader.read_u24().unwrap(), 0x123456);
+    /// let mut reader = BitReader::<LittleEndian>::new(Box::new(Cursor::new(data)));
+    /// assert_eq!(reader.read_u24().unwrap(), 0x563412);
+    /// ```
+    #[inline]
+    pub fn read_u24(&mut self) -> Result<u32, io::Error> {
+        let mut buf = [0; 3];
+        self.read_bytes(&mut buf)?;
+        let mut acc: BitQueue<E, u32> = BitQueue::new();
+        for b in &buf {
+            acc.push(8, u32::from(*b));
+        }
+        Ok(acc.value())
+    }
+
+    /// Reads a twos-complement signed 24-bit value from the stream.
+    /// If the stream is byte-aligned, this maps to
+    /// a single 3 byte read from the underlying stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader};
+    /// let data = [0xFE, 0xFF, 0xFF, 0x00, 0x00, 0x80];
+    /// let mut reader = BitReader::<LittleEndian>::new(Box::new(Cursor::new(data)));
+    /// assert_eq!(reader.read_i24().unwrap(), -2);
+    /// assert_eq!(reader.read_i24().unwrap(), -8388608);
+    /// ```
+    #[inline]
+    pub fn read_i24(&mut self) -> Result<i32, io::Error> {
+        self.read_u24().map(|u| (u as i32).sign_extend(24))
+    }
+
+    /// Reads a whole primitive value from a byte-aligned stream,
+    /// in the stream's byte order.
+    /// This is a single read from the underlying stream
+    /// which bypasses the bit accumulator entirely.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an `InvalidInput` error if the stream
+    /// is not byte-aligned.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, LittleEndian, BitReader};
+    /// let data = [0x12, 0x34, 0x56, 0x78];
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// assert_eq!(reader.read_aligned::<u16>().unwrap(), 0x1234);
+    /// assert_eq!(reader.read_aligned::<i16>().unwrap(), 0x5678);
+    /// let mut reader = BitReader::<LittleEndian>::new(Box::new(Cursor::new(data)));
+    /// assert_eq!(reader.read_aligned::<u32>().unwrap(), 0x78563412);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// reader.skip(4).unwrap();
+    /// assert!(reader.read_aligned::<u16>().is_err());
+    /// ```
+    #[inline]
+    pub fn read_aligned<V: Primitive>(&mut self) -> Result<V, io::Error> {
+        if !self.byte_aligned() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "stream is not byte-aligned"));
+        }
+        let mut buf = V::buffer();
+        self.reader.read_exact(buf.as_mut())?;
+        Ok(E::from_bytes(buf))
+    }
+
+    /// Reads an unsigned 16-bit value from a byte-aligned stream.
+    /// See `read_aligned` for details.
+    ///
+    /// # Errors
+    ///
+    /// As with `read_aligned`.
+    #[inline]
+    pub fn read_u16_aligned(&mut self) -> Result<u16, io::Error> {
+        self.read_aligned()
+    }
+
+    /// Reads an unsigned 32-bit value from a byte-aligned stream.
+    /// See `read_aligned` for details.
+    ///
+    /// # Errors
+    ///
+    /// As with `read_aligned`.
+    #[inline]
+    pub fn read_u32_aligned(&mut self) -> Result<u32, io::Error> {
+        self.read_aligned()
+    }
+
+    /// Reads an unsigned 64-bit value from a byte-aligned stream.
+    /// See `read_aligned` for details.
+    ///
+    /// # Errors
+    ///
+    /// As with `read_aligned`.
+    #[inline]
+    pub fn read_u64_aligned(&mut self) -> Result<u64, io::Error> {
+        self.read_aligned()
+    }
+
+    /// Reads a signed 16-bit value from a byte-aligned stream.
+    /// See `read_aligned` for details.
+    ///
+    /// # Errors
+    ///
+    /// As with `read_aligned`.
+    #[inline]
+    pub fn read_i16_aligned(&mut self) -> Result<i16, io::Error> {
+        self.read_aligned()
+    }
+
+    /// Reads a signed 32-bit value from a byte-aligned stream.
+    /// See `read_aligned` for details.
+    ///
+    /// # Errors
+    ///
+    /// As with `read_aligned`.
+    #[inline]
+    pub fn read_i32_aligned(&mut self) -> Result<i32, io::Error> {
+        self.read_aligned()
+    }
+
+    /// Reads a signed 64-bit value from a byte-aligned stream.
+    /// See `read_aligned` for details.
+    ///
+    /// # Errors
+    ///
+    /// As with `read_aligned`.
+    #[inline]
+    pub fn read_i64_aligned(&mut self) -> Result<i64, io::Error> {
+        self.read_aligned()
+    }
+
+    /// Reads an unsigned 48-bit value from the stream.
+    /// If the stream is byte-aligned, this maps to
+    /// a single 6 byte read from the underlying stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, LittleEndian, BitReader};
+    /// let data = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC];
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// assert_eq!(reader.read_u48().unwrap(), 0x123456789ABC);
+    /// let mut reader = BitReader::<LittleEndian>::new(Box::new(Cursor::new(data)));
+    /// assert_eq!(reader.read_u48().unwrap(), 0xBC9A78563412);
     /// ```
-    pub fn skip(&mut self, mut bits: u32) -> Result<(), io::Error> {
-        use std::cmp::min;
-
-        let to_drop = min(self.bitqueue.len(), bits);
-        if to_drop != 0 {
-            self.bitqueue.drop(to_drop);
-            bits -= to_drop;
+    #[inline]
+    pub fn read_u48(&mut self) -> Result<u64, io::Error> {
+        let mut buf = [0; 6];
+        self.read_bytes(&mut buf)?;
+        let mut acc: BitQueue<E, u64> = BitQueue::new();
+        for b in &buf {
+            acc.push(8, u64::from(*b));
         }
-
-        skip_aligned(&mut self.reader, bits / 8)
-        .and_then(|()| skip_unaligned(&mut self.reader,
-                                      bits % 8,
-                                      &mut self.bitqueue))
+        Ok(acc.value())
     }
 
-    /// Completely fills the given buffer with whole bytes.
-    /// If the stream is already byte-aligned, it will typically map
-    /// to a faster `read_exact` call.  Otherwise it will read
-    /// bytes individually in 8-bit increments.
+    /// Reads a whole number of 16-bit words from the stream
+    /// in the given mixed-endian layout.
+    /// The number of words read is determined by the size of the output type.
     ///
     /// # Errors
     ///
     /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the output type is not a whole number of words.
     ///
-    /// # Example
+    /// # Examples
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader};
-    /// let data = b"foobar";
+    /// use bitstream_io::mixed::MixedEndian;
+    /// let data = [0x0B, 0x0A, 0x0D, 0x0C, 0x0C, 0x0D, 0x0A, 0x0B];
     /// let mut cursor = Cursor::new(data);
     /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
-    /// assert!(reader.skip(24).is_ok());
-    /// let mut buf = [0;3];
-    /// assert!(reader.read_bytes(&mut buf).is_ok());
-    /// assert_eq!(&buf, b"bar");
+    /// assert_eq!(reader.read_mixed_endian::<u32>(MixedEndian::Pdp).unwrap(),
+    ///            0x0A0B0C0D);
+    /// assert_eq!(reader.read_mixed_endian::<u32>(MixedEndian::WordSwapped).unwrap(),
+    ///            0x0A0B0C0D);
     /// ```
-    pub fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
-        if self.byte_aligned() {
-            self.reader.read_exact(buf)
-        } else {
-            for b in buf.iter_mut() {
-                *b = self.read::<u8>(8)?;
-            }
-            Ok(())
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// use bitstream_io::mixed::MixedEndian;
+    /// let data = [0x01];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// assert!(reader.read_mixed_endian::<u8>(MixedEndian::Pdp).is_err());
+    /// ```
+    pub fn read_mixed_endian<U>(&mut self, layout: MixedEndian) ->
+        Result<U, io::Error> where U: Numeric {
+
+        let bytes = (U::bits_size() / 8) as usize;
+        if !bytes.is_multiple_of(2) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "type is not a whole number of words"));
         }
+        let mut stored = [0; 16];
+        self.read_bytes(&mut stored[0..bytes])?;
+        let mut logical = [0; 16];
+        for (position, byte) in stored[0..bytes].iter().enumerate() {
+            logical[layout.big_endian_index(position, bytes)] = *byte;
+        }
+        let mut acc: BitQueue<BigEndian, U> = BitQueue::new();
+        for byte in &logical[0..bytes] {
+            acc.push(8, U::from_u8(*byte));
+        }
+        Ok(acc.value())
     }
 
     /// Creates and returns a BitReader that reads the next `bits` bits,
@@ -318,6 +2086,7 @@ impl<E: Endianness> BitReader<E> {
     /// assert!(sub_reader.read_bit().is_err());
     /// assert_eq!(reader.read::<u8>(7).unwrap(), 0b1111111);
     /// ```
+    #[cfg(feature = "alloc")]
     pub fn create_sub_reader(&mut self, bits: u32) -> Result<BitReader<LittleEndian>, io::Error> {
         let bytes = bits / 8;
 	let remainder_bits = bits % 8;
@@ -339,30 +2108,26 @@ impl<E: Endianness> BitReader<E> {
         if remainder_bits > 0 {
           new_reader.skip(8 - remainder_bits)?;
         }
-        return Ok(new_reader);
+        Ok(new_reader)
     }
 
-    fn copy_reader_to_writer(r: &mut BitReader<E>, w: &mut BitWriter<LittleEndian>) -> Result<(), io::Error> {
-        let mut buffer:Vec<u8> = vec![0;1];
+    #[cfg(feature = "alloc")]
+    fn copy_reader_to_writer(r: &mut BitReader<E, R>, w: &mut BitWriter<LittleEndian>) -> Result<(), io::Error> {
+        // the rest of the stream is moved in chunks
+        // through a buffer on the stack
+        let mut buffer = [0; 512];
         let bq_len = r.bitqueue.len();
         if bq_len > 0 {
             w.write(bq_len, r.bitqueue.pop(bq_len))?;
         }
         loop {
-            let read_opt = r.reader.read_exact(&mut buffer);
-            match read_opt {
-                Ok(_) => {
-                    w.write_bytes(&buffer)?;
-                }
-                Err(e) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
-                        break;
-                    }
-                    return Err(e);
-                }
+            match r.reader.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(read) => w.write_bytes(&buffer[0..read])?,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
             }
         }
-        return Ok(());
     }
 
     /// Combines two bit readers into one bit reader. Consumes each of
@@ -390,7 +2155,8 @@ impl<E: Endianness> BitReader<E> {
     /// concat_reader.read_bytes(&mut read_data).unwrap();
     /// assert_eq!(expected_data, read_data);
     /// ```
-    pub fn concatenate_reader(&mut self, rhs:&mut BitReader<E>) -> Result<BitReader<LittleEndian>, io::Error> {
+    #[cfg(feature = "alloc")]
+    pub fn concatenate_reader(&mut self, rhs:&mut BitReader<E, R>) -> Result<BitReader<LittleEndian>, io::Error> {
         let lhs_bitqueue_bits = self.bitqueue.len();
         let rhs_bitqueue_bits = rhs.bitqueue.len();
         let total_bits = lhs_bitqueue_bits + rhs_bitqueue_bits;
@@ -401,8 +2167,8 @@ impl<E: Endianness> BitReader<E> {
           if bit_offset > 0 {
               w.write(8 - bit_offset, 0)?;
           }
-          BitReader::<E>::copy_reader_to_writer(self, &mut w)?;
-          BitReader::<E>::copy_reader_to_writer(rhs, &mut w)?;
+          BitReader::copy_reader_to_writer(self, &mut w)?;
+          BitReader::copy_reader_to_writer(rhs, &mut w)?;
         }
 	let new_cursor = io::Cursor::new(concatenate_buffer);
 	let mut new_reader = BitReader::<LittleEndian>::new(Box::new(new_cursor));
@@ -410,105 +2176,555 @@ impl<E: Endianness> BitReader<E> {
         if bit_offset > 0 {
           new_reader.skip(8 - bit_offset)?;
         }
-        return Ok(new_reader);
+        Ok(new_reader)
+    }
+
+    /// Counts the number of 1 bits in the stream until the next
+    /// 0 bit and returns the amount read.
+    /// Because this field is variably-sized and may be large,
+    /// its output is always a `u32` type.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b01110111, 0b11111110];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// assert_eq!(reader.read_unary0().unwrap(), 0);
+    /// assert_eq!(reader.read_unary0().unwrap(), 3);
+    /// assert_eq!(reader.read_unary0().unwrap(), 10);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader};
+    /// let data = [0b11101110, 0b01111111];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<LittleEndian>::new(Box::new(cursor));
+    /// assert_eq!(reader.read_unary0().unwrap(), 0);
+    /// assert_eq!(reader.read_unary0().unwrap(), 3);
+    /// assert_eq!(reader.read_unary0().unwrap(), 10);
+    /// ```
+    pub fn read_unary0(&mut self) -> Result<u32, io::Error> {
+        let base = self.bitqueue.leading_1s();
+        if base < self.bitqueue.len() {
+            self.bitqueue.drop(base + 1);
+            Ok(base)
+        } else {
+            self.bitqueue.clear();
+            read_aligned_unary(&mut self.reader,
+                               0b11111111,
+                               &mut self.bitqueue).map(
+                |u| base + u + self.bitqueue.pop_1())
+        }
+    }
+
+    /// Counts the number of 0 bits in the stream until the next
+    /// 1 bit and returns the amount read.
+    /// Because this field is variably-sized and may be large,
+    /// its output is always a `u32` type.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b10001000, 0b00000001];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// assert_eq!(reader.read_unary1().unwrap(), 0);
+    /// assert_eq!(reader.read_unary1().unwrap(), 3);
+    /// assert_eq!(reader.read_unary1().unwrap(), 10);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader};
+    /// let data = [0b00010001, 0b10000000];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<LittleEndian>::new(Box::new(cursor));
+    /// assert_eq!(reader.read_unary1().unwrap(), 0);
+    /// assert_eq!(reader.read_unary1().unwrap(), 3);
+    /// assert_eq!(reader.read_unary1().unwrap(), 10);
+    /// ```
+    pub fn read_unary1(&mut self) -> Result<u32, io::Error> {
+        let base = self.bitqueue.leading_0s();
+        if base < self.bitqueue.len() {
+            self.bitqueue.drop(base + 1);
+            Ok(base)
+        } else {
+            self.bitqueue.clear();
+            read_aligned_unary(&mut self.reader,
+                               0b00000000,
+                               &mut self.bitqueue).map(
+                |u| base + u + self.bitqueue.pop_0())
+        }
+    }
+
+    /// Reads a k-th order Exp-Golomb coded value from the stream.
+    /// The code is some number of 0 bits, followed by a 1 bit,
+    /// followed by that same number of bits plus `k` more.
+    /// A `k` of 0 is the common `ue(v)` code used in H.264 and others.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if `k` is larger than 63
+    /// or if the coded value is too large for a `u64`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b1_010_011_0, 0b0100_0000];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// assert_eq!(reader.read_exp_golomb(0).unwrap(), 0);
+    /// assert_eq!(reader.read_exp_golomb(0).unwrap(), 1);
+    /// assert_eq!(reader.read_exp_golomb(0).unwrap(), 2);
+    /// assert_eq!(reader.read_exp_golomb(0).unwrap(), 3);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b10_11_0100, 0b0101_0000];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// assert_eq!(reader.read_exp_golomb(1).unwrap(), 0);
+    /// assert_eq!(reader.read_exp_golomb(1).unwrap(), 1);
+    /// assert_eq!(reader.read_exp_golomb(1).unwrap(), 2);
+    /// assert_eq!(reader.read_exp_golomb(1).unwrap(), 3);
+    /// ```
+    pub fn read_exp_golomb(&mut self, k: u32) -> Result<u64, io::Error> {
+        if k > 63 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive Exp-Golomb order"));
+        }
+        let zeros = self.read_unary1()?;
+        if zeros + k > 64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "excessive Exp-Golomb prefix"));
+        }
+        let suffix = if zeros + k > 0 {
+            self.read::<u64>(zeros + k)?
+        } else {
+            0
+        };
+        let value = (1u128 << (zeros + k)) - (1u128 << k) + suffix as u128;
+        if value <= u128::from(u64::MAX) {
+            Ok(value as u64)
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidData,
+                               "excessive Exp-Golomb value"))
+        }
+    }
+
+    /// Reads a k-th order interleaved Exp-Golomb coded value
+    /// from the stream, as used by Dirac and VC-2.
+    /// Each bit of the value after its implicit leading 1
+    /// is preceded by a 0 bit, and the code is terminated by a 1 bit.
+    /// For orders above 0, the `k` least-significant bits
+    /// of the value follow as-is.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if `k` is larger than 63
+    /// or if the coded value is too large for a `u64`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b1_001_011_0, 0b0001_0000];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// assert_eq!(reader.read_interleaved_exp_golomb(0).unwrap(), 0);
+    /// assert_eq!(reader.read_interleaved_exp_golomb(0).unwrap(), 1);
+    /// assert_eq!(reader.read_interleaved_exp_golomb(0).unwrap(), 2);
+    /// assert_eq!(reader.read_interleaved_exp_golomb(0).unwrap(), 3);
+    /// ```
+    pub fn read_interleaved_exp_golomb(&mut self, k: u32) ->
+        Result<u64, io::Error> {
+
+        if k > 63 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive Exp-Golomb order"));
+        }
+        let mut value = 1u128;
+        while !self.read_bit()? {
+            value = (value << 1) | (self.read_bit()? as u128);
+            if value > (1u128 << 64) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          "excessive Exp-Golomb value"));
+            }
+        }
+        let value = ((value - 1) << k) |
+            if k > 0 {self.read::<u64>(k)? as u128} else {0};
+        if value <= u128::from(u64::MAX) {
+            Ok(value as u64)
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidData,
+                               "excessive Exp-Golomb value"))
+        }
+    }
+
+    /// Reads a truncated binary coded value in the range `[0, n)`.
+    /// Given `k` as the floor of log2(`n`), the first
+    /// 2<sup>k+1</sup> - `n` values are stored in `k` bits
+    /// and the remaining values are stored in `k` + 1 bits.
+    /// When `n` is a power of two, all values are stored in `k` bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if `n` is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b00_01_100_1, 0b01_110_111];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// // 0 and 1 take 2 bits, 2 to 5 take 3 bits
+    /// assert_eq!(reader.read_truncated_binary(6).unwrap(), 0);
+    /// assert_eq!(reader.read_truncated_binary(6).unwrap(), 1);
+    /// assert_eq!(reader.read_truncated_binary(6).unwrap(), 2);
+    /// assert_eq!(reader.read_truncated_binary(6).unwrap(), 3);
+    /// assert_eq!(reader.read_truncated_binary(6).unwrap(), 4);
+    /// assert_eq!(reader.read_truncated_binary(6).unwrap(), 5);
+    /// ```
+    pub fn read_truncated_binary(&mut self, n: u64) -> Result<u64, io::Error> {
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "empty truncated binary range"));
+        }
+        let k = 63 - n.leading_zeros();
+        let short = (1u128 << (k + 1)) - n as u128;
+        let prefix = if k > 0 {self.read::<u64>(k)? as u128} else {0};
+        if prefix < short {
+            Ok(prefix as u64)
+        } else {
+            let extended = (prefix << 1) | self.read_bit()? as u128;
+            Ok((extended - short) as u64)
+        }
+    }
+
+    /// Reads a value in the range from `min` to `max` inclusive,
+    /// stored as its offset from `min` in as few bits as the range needs.
+    ///
+    /// The width is that given by `ranged_bits`,
+    /// so ranges known at compile time read fixed-width fields.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an `InvalidInput` error if `min` is greater than `max`,
+    /// or an `InvalidData` error if the offset read is past `max`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b1100100_1, 0b1111_1111];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// assert_eq!(reader.read_ranged(0, 100).unwrap(), 100);
+    /// assert_eq!(reader.read_ranged(-256, 255).unwrap(), 255);
+    /// ```
+    pub fn read_ranged(&mut self, min: i64, max: i64) ->
+        Result<i64, io::Error> {
+
+        if min > max {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "invalid range"));
+        }
+        let offset = self.read::<u64>(ranged_bits(min, max))?;
+        if offset <= max.wrapping_sub(min) as u64 {
+            Ok(min.wrapping_add(offset as i64))
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidData,
+                               "excessive value for range read"))
+        }
+    }
+
+    /// Reads an unsigned value of the given number of bits
+    /// and converts it to some type, typically an enum,
+    /// using its `TryFrom<u64>` implementation.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EnumError::Io` for any I/O error from the underlying stream
+    /// or if the number of bits is larger than 64.
+    /// Returns `EnumError::Unknown` if the conversion fails,
+    /// with the value read and the stream position where it started.
+    ///
+    /// # Example
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// use bitstream_io::read::EnumError;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum BlockType {
+    ///     Streaminfo,
+    ///     Padding,
+    /// }
+    ///
+    /// impl TryFrom<u64> for BlockType {
+    ///     type Error = ();
+    ///
+    ///     fn try_from(value: u64) -> Result<Self, ()> {
+    ///         match value {
+    ///             0 => Ok(BlockType::Streaminfo),
+    ///             1 => Ok(BlockType::Padding),
+    ///             _ => Err(()),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let data = [0b0000_0001, 0b0000_0101];
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// assert_eq!(reader.read_enum::<BlockType>(8).unwrap(), BlockType::Padding);
+    /// match reader.read_enum::<BlockType>(8) {
+    ///     Err(EnumError::Unknown{value, position}) => {
+    ///         assert_eq!(value, 5);
+    ///         assert_eq!(position, 8);
+    ///     }
+    ///     _ => panic!("unexpected result"),
+    /// }
+    /// ```
+    pub fn read_enum<T>(&mut self, bits: u32) -> Result<T, EnumError>
+        where T: TryFrom<u64> {
+
+        let position = self.position_in_bits();
+        let value = self.read::<u64>(bits)?;
+        T::try_from(value).map_err(|_| EnumError::Unknown{value, position})
+    }
+
+    /// Reads a variable-length integer in the given format.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the format's payload size is invalid
+    /// or if the value read is too large for a `u64`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// use bitstream_io::varint::VarInt;
+    /// let data = [0xE5, 0x8E, 0x26, 0x81, 0x80, 0x00];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// assert_eq!(reader.read_varint(&VarInt::leb128()).unwrap(), 624485);
+    /// assert_eq!(reader.read_varint(&VarInt::vlq()).unwrap(), 0x4000);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// use bitstream_io::varint::{VarInt, FlagPosition, ChunkOrder};
+    /// // 3 payload bits per chunk, where a flag of 0 means more follow
+    /// let format = VarInt{payload_bits: 3,
+    ///                     flag: FlagPosition::Low,
+    ///                     continue_flag: false,
+    ///                     order: ChunkOrder::MostSignificantFirst};
+    /// let data = [0b0010_1101];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// assert_eq!(reader.read_varint(&format).unwrap(), 0o16);
+    /// ```
+    pub fn read_varint(&mut self, format: &VarInt) -> Result<u64, io::Error> {
+        format.check()?;
+        let chunk_bits = format.chunk_bits();
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let (payload, more) = format.split(self.read::<u64>(chunk_bits)?);
+            match format.order {
+                ChunkOrder::LeastSignificantFirst => {
+                    // even a chunk of zeroes beyond a u64 is excessive,
+                    // which also keeps the shift from overflowing
+                    if (shift >= 64) ||
+                        ((payload << shift) >> shift != payload) {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "excessive varint value"));
+                    }
+                    value |= payload << shift;
+                    shift += format.payload_bits;
+                }
+                ChunkOrder::MostSignificantFirst => {
+                    if (value >> (64 - format.payload_bits)) != 0 {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "excessive varint value"));
+                    }
+                    value = (value << format.payload_bits) | payload;
+                }
+            }
+            if !more {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Reads a length as a variable-length integer in the given format,
+    /// followed by that many bytes, which needn't be byte-aligned.
+    ///
+    /// A corrupt length fails at the end of the stream
+    /// rather than allocating all of it up front.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream,
+    /// or any error reading the length.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// use bitstream_io::varint::VarInt;
+    /// let data = [0x03, b'a', b'b', b'c'];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// let blob = reader.read_prefixed_bytes(&VarInt::leb128()).unwrap();
+    /// assert_eq!(blob, b"abc");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn read_prefixed_bytes(&mut self, format: &VarInt) ->
+        Result<Vec<u8>, io::Error> {
+        let mut remaining = self.read_varint(format)?;
+        let mut data = Vec::new();
+        while remaining > 0 {
+            let start = data.len();
+            let chunk = remaining.min(4096);
+            data.resize(start + chunk as usize, 0);
+            self.read_bytes(&mut data[start..])?;
+            remaining -= chunk;
+        }
+        Ok(data)
     }
 
-    /// Counts the number of 1 bits in the stream until the next
-    /// 0 bit and returns the amount read.
-    /// Because this field is variably-sized and may be large,
-    /// its output is always a `u32` type.
+    /// Reads a length as a variable-length integer in the given format,
+    /// followed by that many bytes of UTF-8 text.
     ///
     /// # Errors
     ///
-    /// Passes along any I/O error from the underlying stream.
+    /// Passes along any I/O error from the underlying stream,
+    /// or any error reading the length.
+    /// Returns an `InvalidData` error if the text isn't valid UTF-8.
     ///
-    /// # Examples
+    /// # Example
     /// ```
-    /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader};
-    /// let data = [0b01110111, 0b11111110];
-    /// let mut cursor = Cursor::new(data);
-    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
-    /// assert_eq!(reader.read_unary0().unwrap(), 0);
-    /// assert_eq!(reader.read_unary0().unwrap(), 3);
-    /// assert_eq!(reader.read_unary0().unwrap(), 10);
+    /// use bitstream_io::varint::VarInt;
+    /// let data = [0b1010_0000, 0x3C, 0x3A, 0x96, 0x90];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0b1010);
+    /// let text = reader.read_prefixed_str(&VarInt::leb128()).unwrap();
+    /// assert_eq!(text, "\u{e9}i");
     /// ```
+    #[cfg(feature = "alloc")]
+    pub fn read_prefixed_str(&mut self, format: &VarInt) ->
+        Result<String, io::Error> {
+        String::from_utf8(self.read_prefixed_bytes(format)?).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "invalid UTF-8 text")
+        })
+    }
+
+    /// Reads several values as described by a format string,
+    /// such as `"u1 u7 u24"`, and returns them in order.
+    /// See the `format` module for the fields supported.
+    ///
+    /// # Errors
     ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the format string is invalid,
+    /// in which case nothing is read.
+    ///
+    /// # Example
     /// ```
     /// use std::io::{Read, Cursor};
-    /// use bitstream_io::{LittleEndian, BitReader};
-    /// let data = [0b11101110, 0b01111111];
-    /// let mut cursor = Cursor::new(data);
-    /// let mut reader = BitReader::<LittleEndian>::new(Box::new(cursor));
-    /// assert_eq!(reader.read_unary0().unwrap(), 0);
-    /// assert_eq!(reader.read_unary0().unwrap(), 3);
-    /// assert_eq!(reader.read_unary0().unwrap(), 10);
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// use bitstream_io::format::Value;
+    /// let data = [0x80, 0x00, 0x00, 0x22];
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    /// let values = reader.parse_fmt("u1 u7 u24").unwrap();
+    /// assert_eq!(values, vec![Value::Unsigned(1),
+    ///                         Value::Unsigned(0),
+    ///                         Value::Unsigned(34)]);
+    /// assert!(reader.parse_fmt("u65").is_err());
     /// ```
-    pub fn read_unary0(&mut self) -> Result<u32, io::Error> {
-        if self.bitqueue.is_empty() {
-            read_aligned_unary(&mut self.reader,
-                               0b11111111,
-                               &mut self.bitqueue).map(
-                |u| u + self.bitqueue.pop_1())
-        } else if self.bitqueue.all_1() {
-            let base = self.bitqueue.len();
-            self.bitqueue.clear();
-            read_aligned_unary(&mut self.reader,
-                               0b11111111,
-                               &mut self.bitqueue).map(
-                |u| base + u + self.bitqueue.pop_1())
-        } else {
-            Ok(self.bitqueue.pop_1())
+    #[cfg(feature = "alloc")]
+    pub fn parse_fmt(&mut self, format: &str) -> Result<Vec<Value>, io::Error> {
+        let fields = format::parse(format)?;
+        let mut values = Vec::with_capacity(fields.len());
+        for field in fields {
+            match field {
+                Field::Unsigned(bits) => {
+                    values.push(Value::Unsigned(self.read(bits)?));
+                }
+                Field::Signed(bits) => {
+                    values.push(Value::Signed(self.read_signed(bits)?));
+                }
+                Field::Skip(bits) => {self.skip(bits)?;}
+            }
         }
+        Ok(values)
     }
 
-    /// Counts the number of 0 bits in the stream until the next
-    /// 1 bit and returns the amount read.
-    /// Because this field is variably-sized and may be large,
-    /// its output is always a `u32` type.
+    /// Reads an IEEE-like floating point value with the given
+    /// number of exponent and mantissa bits, and returns it as an `f64`.
+    /// The value is stored as a sign bit, followed by the biased
+    /// exponent, followed by the mantissa, for a total of
+    /// 1 + `exp_bits` + `mant_bits` bits.
+    /// Subnormal values, infinities and NaN are all supported.
     ///
     /// # Errors
     ///
     /// Passes along any I/O error from the underlying stream.
+    /// Also returns an error if `exp_bits` is not between 2 and 11,
+    /// or if `mant_bits` is larger than 52.
     ///
     /// # Examples
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader};
-    /// let data = [0b10001000, 0b00000001];
+    /// let data = [0x3C, 0x00, 0xC0, 0x00, 0x7C, 0x00];
     /// let mut cursor = Cursor::new(data);
     /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
-    /// assert_eq!(reader.read_unary1().unwrap(), 0);
-    /// assert_eq!(reader.read_unary1().unwrap(), 3);
-    /// assert_eq!(reader.read_unary1().unwrap(), 10);
+    /// // IEEE 754 half precision values
+    /// assert_eq!(reader.read_minifloat(5, 10).unwrap(), 1.0);
+    /// assert_eq!(reader.read_minifloat(5, 10).unwrap(), -2.0);
+    /// assert_eq!(reader.read_minifloat(5, 10).unwrap(), f64::INFINITY);
     /// ```
     ///
     /// ```
     /// use std::io::{Read, Cursor};
-    /// use bitstream_io::{LittleEndian, BitReader};
-    /// let data = [0b00010001, 0b10000000];
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b0_0111_100, 0b0_0000_001];
     /// let mut cursor = Cursor::new(data);
-    /// let mut reader = BitReader::<LittleEndian>::new(Box::new(cursor));
-    /// assert_eq!(reader.read_unary1().unwrap(), 0);
-    /// assert_eq!(reader.read_unary1().unwrap(), 3);
-    /// assert_eq!(reader.read_unary1().unwrap(), 10);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// // 8-bit values with 4 exponent bits and 3 mantissa bits
+    /// assert_eq!(reader.read_minifloat(4, 3).unwrap(), 1.5);
+    /// assert_eq!(reader.read_minifloat(4, 3).unwrap(), 1.0 / 512.0);
     /// ```
-    pub fn read_unary1(&mut self) -> Result<u32, io::Error> {
-        if self.bitqueue.is_empty() {
-            read_aligned_unary(&mut self.reader,
-                               0b00000000,
-                               &mut self.bitqueue).map(
-                |u| u + self.bitqueue.pop_0())
-        } else if self.bitqueue.all_0() {
-            let base = self.bitqueue.len();
-            self.bitqueue.clear();
-            read_aligned_unary(&mut self.reader,
-                               0b00000000,
-                               &mut self.bitqueue).map(
-                |u| base + u + self.bitqueue.pop_0())
-        } else {
-            Ok(self.bitqueue.pop_0())
-        }
+    pub fn read_minifloat(&mut self, exp_bits: u32, mant_bits: u32) ->
+        Result<f64, io::Error> {
+
+        minifloat::check_widths(exp_bits, mant_bits)?;
+        self.read::<u64>(1 + exp_bits + mant_bits)
+            .map(|raw| minifloat::decode(exp_bits, mant_bits, raw))
     }
 
     /// Returns true if the stream is aligned at a whole byte.
@@ -550,6 +2766,85 @@ impl<E: Endianness> BitReader<E> {
         self.bitqueue.clear()
     }
 
+    /// Reads every remaining bit of the stream,
+    /// including those left in a partial byte.
+    ///
+    /// Returns the bits packed into bytes in the stream's bit order,
+    /// as a `BitWriter` of the same endianness would write them,
+    /// along with the number of bits used in the final byte
+    /// if it's a partial one, or 0 if every byte is whole.
+    /// A partial final byte is padded with 0 bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b1010_0101, 0xFF, 0x00];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+    /// let (rest, trailing) = reader.read_to_end_bits().unwrap();
+    /// assert_eq!(rest, [0b0010_1111, 0b1111_1000, 0b0000_0000]);
+    /// assert_eq!(trailing, 5);
+    /// assert_eq!(reader.read_to_end_bits().unwrap(), (vec![], 0));
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn read_to_end_bits(&mut self) -> Result<(Vec<u8>, u32), io::Error> {
+        let mut rest = Vec::new();
+        let mut buf = [0; 256];
+        loop {
+            match self.reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => rest.extend_from_slice(&buf[..read]),
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        let queued = self.bitqueue.len();
+        if queued == 0 {
+            return Ok((rest, 0));
+        }
+        // the rest of the stream is shifted to follow the partial byte
+        let mut packed = Vec::with_capacity(rest.len() + 1);
+        {
+            let mut writer = BitWriter::<E, _>::from_write(&mut packed);
+            writer.write(queued, self.bitqueue.pop(queued))?;
+            writer.write_bytes(&rest)?;
+            writer.byte_align()?;
+        }
+        Ok((packed, queued))
+    }
+
+    /// Consumes the reader, returning the underlying stream
+    /// positioned at the next whole byte, so that the rest of the
+    /// stream may be handed to byte-oriented code.
+    ///
+    /// Since a reader buffers no more than a single partial byte,
+    /// no whole bytes are lost.
+    /// Any unread bits of a partial byte are discarded,
+    /// as with `byte_align`.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Read;
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b1010_0000, b'a', b'b', b'c'];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+    /// let mut rest = reader.into_byte_reader();
+    /// let mut buf = Vec::new();
+    /// rest.read_to_end(&mut buf).unwrap();
+    /// assert_eq!(buf, b"abc");
+    /// ```
+    #[inline]
+    pub fn into_byte_reader(mut self) -> R {
+        self.byte_align();
+        self.reader.reader
+    }
+
     /// Given a compiled Huffman tree, reads bits from the stream
     /// until the next symbol is encountered.
     ///
@@ -574,24 +2869,82 @@ impl<E: Endianness> BitReader<E> {
     /// assert_eq!(reader.read_huffman(&tree).unwrap(), 'c');
     /// assert_eq!(reader.read_huffman(&tree).unwrap(), 'd');
     /// ```
+    #[cfg(feature = "alloc")]
     pub fn read_huffman<T>(&mut self, tree: &[ReadHuffmanTree<E,T>]) ->
         Result<T,io::Error> where T: Clone {
 
         let mut result: &ReadHuffmanTree<E,T> =
-            &tree[self.bitqueue.to_state()];
+            &tree[self.partial_byte().to_state()];
         loop {
-            match result {
-                &ReadHuffmanTree::Done(
+            match *result {
+                ReadHuffmanTree::Done(
                     ref value, ref queue_val, ref queue_bits, _) => {
-                    self.bitqueue.set(*queue_val, *queue_bits);
+                    self.bitqueue.set(u64::from(*queue_val), *queue_bits);
                     return Ok(value.clone())
                 }
-                &ReadHuffmanTree::Continue(ref tree) => {
+                ReadHuffmanTree::Continue(ref tree) => {
                     result = &tree[read_byte(&mut self.reader)? as usize];
                 }
-                &ReadHuffmanTree::InvalidState => {panic!("invalid state");}
+                ReadHuffmanTree::InvalidState => {panic!("invalid state");}
+            }
+        }
+    }
+
+    /// Given a compiled Huffman tree, skips past the given number
+    /// of symbols in the stream without returning them.
+    ///
+    /// This walks the tree just as `read_huffman` does,
+    /// but never clones the symbols it passes,
+    /// which makes stepping over long runs of them cheaper.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// use bitstream_io::huffman::compile_read_tree;
+    /// let tree = compile_read_tree(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])]).unwrap();
+    /// let data = [0b10110111];
+    /// let mut cursor = Cursor::new(data);
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// reader.skip_huffman(&tree, 2).unwrap();
+    /// assert_eq!(reader.read_huffman(&tree).unwrap(), 'd');
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn skip_huffman<T>(&mut self,
+                           tree: &[ReadHuffmanTree<E,T>],
+                           count: u32) -> Result<(), io::Error>
+        where T: Clone {
+
+        for _ in 0..count {
+            let mut result: &ReadHuffmanTree<E,T> =
+                &tree[self.partial_byte().to_state()];
+            loop {
+                match *result {
+                    ReadHuffmanTree::Done(
+                        _, ref queue_val, ref queue_bits, _) => {
+                        self.bitqueue.set(u64::from(*queue_val),
+                                          *queue_bits);
+                        break;
+                    }
+                    ReadHuffmanTree::Continue(ref tree) => {
+                        result =
+                            &tree[read_byte(&mut self.reader)? as usize];
+                    }
+                    ReadHuffmanTree::InvalidState => {
+                        panic!("invalid state");
+                    }
+                }
             }
         }
+        Ok(())
     }
 
     /// Consumes reader and returns any un-read partial byte
@@ -623,175 +2976,335 @@ impl<E: Endianness> BitReader<E> {
     /// ```
     #[inline(always)]
     pub fn into_unread(self) -> (u32,u8) {
-        (self.bitqueue.len(), self.bitqueue.value())
+        (self.bitqueue.len(), self.bitqueue.value() as u8)
+    }
+
+    // returns the underlying stream along with any partial byte
+    #[cfg(feature = "std")]
+    #[inline]
+    pub(crate) fn into_parts(self) -> (R, BitQueue<E,u8>) {
+        let partial = self.partial_byte();
+        (self.reader.reader, partial)
+    }
+
+    // the cached bits of a partially read byte
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn partial_byte(&self) -> BitQueue<E,u8> {
+        BitQueue::from_value(self.bitqueue.value as u8, self.bitqueue.len())
     }
 }
 
-impl BitReader<BigEndian> {
-    /// Reads a twos-complement signed value from the stream with
-    /// the given number of bits.
+impl<E: Endianness, R: io::Read> BitRead for BitReader<E, R> {
+    #[inline]
+    fn read_bit(&mut self) -> Result<bool, io::Error> {
+        BitReader::read_bit(self)
+    }
+
+    #[inline]
+    fn read<U>(&mut self, bits: u32) -> Result<U, io::Error>
+        where U: Numeric {
+        BitReader::read(self, bits)
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> Result<S, io::Error>
+        where S: SignedNumeric {
+        BitReader::read_signed(self, bits)
+    }
+
+    #[inline]
+    fn skip(&mut self, bits: u32) -> Result<(), io::Error> {
+        BitReader::skip(self, bits)
+    }
+
+    #[inline]
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        BitReader::read_bytes(self, buf)
+    }
+
+    #[inline]
+    fn read_unary0(&mut self) -> Result<u32, io::Error> {
+        BitReader::read_unary0(self)
+    }
+
+    #[inline]
+    fn read_unary1(&mut self) -> Result<u32, io::Error> {
+        BitReader::read_unary1(self)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        BitReader::byte_aligned(self)
+    }
+
+    #[inline]
+    fn byte_align(&mut self) {
+        BitReader::byte_align(self)
+    }
+}
+
+impl<R: io::Read> BitReader<BigEndian, R> {
+    /// Reads an unsigned value from the stream with the given number
+    /// of bits, keeping only as many of its least-significant bits
+    /// as fit in the output type.
+    /// Any higher bits are discarded, which wraps the value
+    /// as an `as` cast to the output type would.
     ///
     /// # Errors
     ///
     /// Passes along any I/O error from the underlying stream.
-    /// Also returns an error if the output type is too small
-    /// to hold the requested number of bits.
     ///
     /// # Examples
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader};
-    /// let data = [0b10110111];
+    /// let data = [0x12, 0x34, 0x56, 0x78, 0x9A];
     /// let mut cursor = Cursor::new(data);
     /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
-    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), -5);
-    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), 7);
+    /// assert_eq!(reader.read_truncating::<u32>(40).unwrap(), 0x3456789A);
     /// ```
     ///
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader};
-    /// let data = [0;10];
+    /// let data = [0b10110111, 0b01010000];
     /// let mut cursor = Cursor::new(data);
-    /// let mut r = BitReader::<BigEndian>::new(Box::new(cursor));
-    /// assert!(r.read_signed::<i8>(9).is_err());   // can't read 9 bits to i8
-    /// assert!(r.read_signed::<i16>(17).is_err()); // can't read 17 bits to i16
-    /// assert!(r.read_signed::<i32>(33).is_err()); // can't read 33 bits to i32
-    /// assert!(r.read_signed::<i64>(65).is_err()); // can't read 65 bits to i64
+    /// let mut reader = BitReader::<BigEndian>::new(Box::new(cursor));
+    /// assert_eq!(reader.read_truncating::<u8>(12).unwrap(), 0b0111_0101);
     /// ```
-    pub fn read_signed<S>(&mut self, bits: u32) -> Result<S, io::Error>
-        where S: SignedNumeric {
+    pub fn read_truncating<U>(&mut self, bits: u32) -> Result<U, io::Error>
+        where U: Numeric {
 
-        if bits <= S::bits_size() {
-            let is_negative = self.read_bit()?;
-            let unsigned = self.read::<S>(bits - 1)?;
-            Ok(if is_negative {unsigned.as_negative(bits)} else {unsigned})
+        if bits <= U::bits_size() {
+            self.read::<U>(bits)
         } else {
-            Err(io::Error::new(io::ErrorKind::InvalidInput,
-                               "excessive bits for type read"))
+            self.skip(bits - U::bits_size())?;
+            self.read::<U>(U::bits_size())
         }
     }
 }
 
-impl BitReader<LittleEndian> {
-    /// Reads a twos-complement signed value from the stream with
-    /// the given number of bits.
+impl<R: io::Read> BitReader<LittleEndian, R> {
+    /// Reads an unsigned value from the stream with the given number
+    /// of bits, keeping only as many of its least-significant bits
+    /// as fit in the output type.
+    /// Any higher bits are discarded, which wraps the value
+    /// as an `as` cast to the output type would.
     ///
     /// # Errors
     ///
     /// Passes along any I/O error from the underlying stream.
-    /// Also returns an error if the output type is too small
-    /// to hold the requested number of bits.
     ///
     /// # Examples
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{LittleEndian, BitReader};
-    /// let data = [0b10110111];
+    /// let data = [0x12, 0x34, 0x56, 0x78, 0x9A];
     /// let mut cursor = Cursor::new(data);
     /// let mut reader = BitReader::<LittleEndian>::new(Box::new(cursor));
-    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), 7);
-    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), -5);
+    /// assert_eq!(reader.read_truncating::<u32>(40).unwrap(), 0x78563412);
     /// ```
     ///
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{LittleEndian, BitReader};
-    /// let data = [0;10];
+    /// let data = [0b10110111, 0b01010000];
     /// let mut cursor = Cursor::new(data);
-    /// let mut r = BitReader::<LittleEndian>::new(Box::new(cursor));
-    /// assert!(r.read_signed::<i8>(9).is_err());   // can't read 9 bits to i8
-    /// assert!(r.read_signed::<i16>(17).is_err()); // can't read 17 bits to i16
-    /// assert!(r.read_signed::<i32>(33).is_err()); // can't read 33 bits to i32
-    /// assert!(r.read_signed::<i64>(65).is_err()); // can't read 65 bits to i64
+    /// let mut reader = BitReader::<LittleEndian>::new(Box::new(cursor));
+    /// assert_eq!(reader.read_truncating::<u8>(12).unwrap(), 0b1011_0111);
     /// ```
-    pub fn read_signed<S>(&mut self, bits: u32) -> Result<S, io::Error>
-        where S: SignedNumeric {
+    pub fn read_truncating<U>(&mut self, bits: u32) -> Result<U, io::Error>
+        where U: Numeric {
 
-        if bits <= S::bits_size() {
-            let unsigned = self.read::<S>(bits - 1)?;
-            let is_negative = self.read_bit()?;
-            Ok(if is_negative {unsigned.as_negative(bits)} else {unsigned})
+        if bits <= U::bits_size() {
+            self.read::<U>(bits)
         } else {
-            Err(io::Error::new(io::ErrorKind::InvalidInput,
-                               "excessive bits for type read"))
+            let value = self.read::<U>(U::bits_size())?;
+            self.skip(bits - U::bits_size()).map(|()| value)
         }
     }
 }
 
 #[inline]
-fn read_byte(reader: &mut io::Read) -> Result<u8,io::Error> {
+fn read_byte(reader: &mut dyn io::Read) -> Result<u8,io::Error> {
 	let mut buf = [0; 1];
     reader.read_exact(&mut buf).map(|()| buf[0])
 }
 
-fn read_aligned<E,N>(reader: &mut io::Read,
-                     bytes: u32,
-                     acc: &mut BitQueue<E,N>) -> Result<(), io::Error>
+#[inline]
+fn read_aligned_value<E,N>(reader: &mut dyn io::Read,
+                           bytes: u32) -> Result<N, io::Error>
     where E: Endianness, N: Numeric {
 
-    // 64-bit types are the maximum supported
+    // only called for values of up to 64 bits
     debug_assert!(bytes <= 8);
 
+    /*the bytes are read in a single call and converted
+      as one word in the stream's byte order,
+      whose head holds the value read*/
     let mut buf = [0; 8];
-    reader.read_exact(&mut buf[0..bytes as usize])
-          .map(|()| {for b in &buf[0..bytes as usize]
-                     {acc.push(8, N::from_u8(*b))}})
+    reader.read_exact(&mut buf[0..bytes as usize])?;
+    let mut word = BitQueue::<E,u64>::from_value(E::from_bytes(buf), 64);
+    Ok(N::from_u64(word.pop(bytes * 8)))
 }
 
-fn skip_aligned(reader: &mut io::Read,
-                mut bytes: u32) -> Result<(), io::Error> {
-    use std::cmp::min;
+// discards the given number of bytes from a stream's buffer
+// without copying them out
+#[cfg(feature = "std")]
+pub(crate) fn consume_bytes<R>(reader: &mut R,
+                               mut bytes: u64) -> Result<(), io::Error>
+    where R: io::BufRead + ?Sized {
 
-    /*skip 8 bytes at a time
-      (unlike with read_aligned, bytes may be larger than any native type)*/
-    let mut buf = [0; 8];
     while bytes > 0 {
-        let to_read = min(8, bytes);
-        reader.read_exact(&mut buf[0..to_read as usize])?;
-        bytes -= to_read;
+        let available = match reader.fill_buf() {
+            Ok(buf) => buf.len(),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if available == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                      "failed to fill whole buffer"));
+        }
+        let to_consume = bytes.min(available as u64);
+        reader.consume(to_consume as usize);
+        bytes -= to_consume;
     }
     Ok(())
 }
 
+// counts the bits in bytes of the given value at the start of
+// a stream's buffer and loads the first byte which differs,
+// scanning as much of the buffer as is available at once
+#[cfg(feature = "std")]
+fn read_buffered_unary<E,R>(reader: &mut R,
+                            continue_val: u8,
+                            rem: &mut BitQueue<E,u64>) -> Result<u32, io::Error>
+    where E: Endianness, R: io::BufRead + ?Sized {
 
-#[inline]
-fn read_unaligned<E,N>(reader: &mut io::Read,
-                       bits: u32,
-                       acc: &mut BitQueue<E,N>,
-                       rem: &mut BitQueue<E,u8>) -> Result<(), io::Error>
-    where E: Endianness, N: Numeric {
+    let mut acc = 0;
+    loop {
+        let (run, stop) = match reader.fill_buf() {
+            Ok([]) => {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                          "failed to fill whole buffer"));
+            }
+            Ok(buf) => {
+                let run = run_length(buf, continue_val);
+                (run, buf.get(run).cloned())
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        acc += run as u32 * 8;
+        if let Some(byte) = stop {
+            reader.consume(run + 1);
+            rem.set(u64::from(byte), 8);
+            return Ok(acc);
+        }
+        reader.consume(run);
+    }
+}
 
-    debug_assert!(bits <= 8);
+// returns the number of bytes of the given value
+// at the start of the buffer, comparing 8 at a time
+#[cfg(feature = "std")]
+fn run_length(buf: &[u8], value: u8) -> usize {
+    let pattern = u64::from_ne_bytes([value; 8]);
+    let mut run = 0;
+    for word in buf.chunks_exact(8) {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(word);
+        if u64::from_ne_bytes(bytes) != pattern {
+            break;
+        }
+        run += 8;
+    }
+    run + buf[run..].iter().take_while(|b| **b == value).count()
+}
 
-    if bits > 0 {
-        read_byte(reader).map(|byte|
-            {rem.set(byte, 8);
-             acc.push(bits, N::from_u8(rem.pop(bits)))})
-    } else {
-        Ok(())
+// shifts bytes read from a big-endian stream later by the given
+// number of bits, from 1 to 7, starting with the given bits,
+// and returns the bits shifted out of the last byte
+fn shift_bytes_be(buf: &mut [u8], bits: u32, mut carry: u8) -> u8 {
+    debug_assert!((1..8).contains(&bits));
+
+    /*whole words are shifted at once,
+      followed by any bytes left over*/
+    let mut words = buf.chunks_exact_mut(8);
+    for chunk in &mut words {
+        let mut word = [0; 8];
+        word.copy_from_slice(chunk);
+        let word = u64::from_be_bytes(word);
+        let shifted = (u64::from(carry) << (64 - bits)) | (word >> bits);
+        chunk.copy_from_slice(&shifted.to_be_bytes());
+        carry = (word as u8) & ((1 << bits) - 1);
+    }
+    for b in words.into_remainder() {
+        let byte = *b;
+        *b = (carry << (8 - bits)) | (byte >> bits);
+        carry = byte & ((1 << bits) - 1);
+    }
+    carry
+}
+
+// as shift_bytes_be, but for little-endian streams
+fn shift_bytes_le(buf: &mut [u8], bits: u32, mut carry: u8) -> u8 {
+    debug_assert!((1..8).contains(&bits));
+
+    let mut words = buf.chunks_exact_mut(8);
+    for chunk in &mut words {
+        let mut word = [0; 8];
+        word.copy_from_slice(chunk);
+        let word = u64::from_le_bytes(word);
+        let shifted = u64::from(carry) | (word << bits);
+        chunk.copy_from_slice(&shifted.to_le_bytes());
+        carry = (word >> (64 - bits)) as u8;
+    }
+    for b in words.into_remainder() {
+        let byte = *b;
+        *b = carry | (byte << bits);
+        carry = byte >> (8 - bits);
+    }
+    carry
+}
+
+fn skip_aligned(reader: &mut dyn io::Read,
+                mut bytes: u32) -> Result<(), io::Error> {
+    use core::cmp::min;
+
+    /*skip up to 512 bytes at a time
+      (unlike with read_aligned, bytes may be larger than any native type)
+      so that long skips take few calls to the underlying stream*/
+    let mut buf = [0; 512];
+    while bytes > 0 {
+        let to_read = min(512, bytes);
+        reader.read_exact(&mut buf[0..to_read as usize])?;
+        bytes -= to_read;
     }
+    Ok(())
 }
 
+
 #[inline]
-fn skip_unaligned<E>(reader: &mut io::Read,
+fn skip_unaligned<E>(reader: &mut dyn io::Read,
                     bits: u32,
-                    rem: &mut BitQueue<E,u8>) -> Result<(), io::Error>
+                    rem: &mut BitQueue<E,u64>) -> Result<(), io::Error>
     where E: Endianness {
 
     debug_assert!(bits <= 8);
 
     if bits > 0 {
-        rem.set(read_byte(reader)?, 8);
+        rem.set(u64::from(read_byte(reader)?), 8);
         rem.pop(bits);
     }
     Ok(())
 }
 
 #[inline]
-fn read_aligned_unary<E>(reader: &mut io::Read,
+fn read_aligned_unary<E>(reader: &mut dyn io::Read,
                         continue_val: u8,
-                        rem: &mut BitQueue<E,u8>) -> Result<u32,io::Error>
+                        rem: &mut BitQueue<E,u64>) -> Result<u32,io::Error>
     where E: Endianness {
     let mut acc = 0;
     let mut byte = read_byte(reader)?;
@@ -799,6 +3312,47 @@ fn read_aligned_unary<E>(reader: &mut io::Read,
         acc += 8;
         byte = read_byte(reader)?;
     }
-    rem.set(byte, 8);
+    rem.set(u64::from(byte), 8);
     Ok(acc)
 }
+
+/// Reads from an in-memory buffer which may not yet hold a whole value,
+/// noting whether a read ever came up short.
+#[cfg(any(feature = "tokio-util", feature = "futures"))]
+struct Accumulated<'a> {
+    data: &'a [u8],
+    exhausted: &'a ::core::cell::Cell<bool>,
+}
+
+#[cfg(any(feature = "tokio-util", feature = "futures"))]
+impl<'a> io::Read for Accumulated<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.len() > self.data.len() {
+            self.exhausted.set(true);
+        }
+        self.data.read(buf)
+    }
+}
+
+/// Parses a value from the bytes accumulated so far,
+/// starting the given number of bits into the first byte.
+///
+/// Returns the value and the stream position in bits after it,
+/// or `None` if the parser ran out of data before finishing.
+#[cfg(any(feature = "tokio-util", feature = "futures"))]
+pub(crate) fn parse_accumulated<E, T>(data: &[u8], skip: u32) ->
+    Result<Option<(T, u64)>, T::Error>
+    where E: Endianness, T: FromBitStream, T::Error: From<io::Error> {
+    let exhausted = ::core::cell::Cell::new(false);
+    let mut reader = BitReader::<E, _>::from_read(
+        Accumulated{data, exhausted: &exhausted});
+    let result = match reader.skip(skip) {
+        Ok(()) => T::from_reader(&mut reader),
+        Err(err) => Err(err.into()),
+    };
+    match result {
+        Ok(value) => Ok(Some((value, reader.position_in_bits()))),
+        Err(_) if exhausted.get() => Ok(None),
+        Err(err) => Err(err),
+    }
+}
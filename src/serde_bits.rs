@@ -0,0 +1,864 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A serde data format for bit-packed values.
+//!
+//! Values are written in the order serde visits them,
+//! with no field names or type information,
+//! so the same `Schema` must be used to write and read them back.
+//! The schema gives the bit widths of named fields,
+//! which apply to every integer inside that field.
+//! Integers in fields without a width use their type's full size.
+//!
+//! | Type | Encoding |
+//! |---|---|
+//! | `bool` | a single bit |
+//! | integers | the field's width, or the type's size |
+//! | `char` | the field's width, or 32 bits |
+//! | `f32`, `f64` | the value's 32 or 64 bits |
+//! | strings, bytes, sequences, maps | a `length_bits` count, then the items |
+//! | `Option` | a single bit for whether a value follows |
+//! | tuples, structs | their fields in order |
+//! | enums | a variant index of the field's width or `variant_bits` |
+//!
+//! Integers within a newtype or tuple variant use the width
+//! given for that variant's name, if any.
+//!
+//! This module requires the `serde` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! #[macro_use]
+//! extern crate serde_derive;
+//! # extern crate bitstream_io;
+//! use std::io::{Write, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::serde_bits::{Schema, to_writer, from_reader};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct BlockHeader {
+//!     last_block: bool,
+//!     block_type: u8,
+//!     block_size: u32,
+//! }
+//!
+//! # fn main() {
+//! let schema = Schema::new().field("block_type", 7).field("block_size", 24);
+//! let header = BlockHeader{last_block: true, block_type: 4, block_size: 34};
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     to_writer(&mut writer, &schema, &header).unwrap();
+//! }
+//! assert_eq!(data, [0x84, 0x00, 0x00, 0x22]);
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! assert_eq!(from_reader::<_, BlockHeader>(&mut reader, &schema).unwrap(),
+//!            header);
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+use core::cmp::min;
+use std::collections::HashMap;
+use std::error;
+use core::fmt;
+use io;
+
+use serde::{de, ser};
+use serde::de::IntoDeserializer;
+
+use super::{BitRead, BitWrite};
+
+/// The bit widths used to serialize and deserialize values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schema {
+    widths: HashMap<String, u32>,
+    length_bits: u32,
+    variant_bits: u32,
+}
+
+impl Schema {
+    /// Returns a schema with no field widths,
+    /// 32-bit lengths and 32-bit variant indexes.
+    pub fn new() -> Schema {
+        Schema{widths: HashMap::new(), length_bits: 32, variant_bits: 32}
+    }
+
+    /// Sets the width of integers within fields or variants
+    /// of the given name.
+    pub fn field(mut self, name: &str, bits: u32) -> Schema {
+        self.widths.insert(name.to_owned(), bits);
+        self
+    }
+
+    /// Sets the width of the counts written before
+    /// strings, bytes, sequences and maps.
+    pub fn length_bits(mut self, bits: u32) -> Schema {
+        self.length_bits = bits;
+        self
+    }
+
+    /// Sets the width of enum variant indexes
+    /// in fields without a width of their own.
+    pub fn variant_bits(mut self, bits: u32) -> Schema {
+        self.variant_bits = bits;
+        self
+    }
+
+    fn width(&self, name: &str) -> Option<u32> {
+        self.widths.get(name).cloned()
+    }
+}
+
+impl Default for Schema {
+    fn default() -> Schema {
+        Schema::new()
+    }
+}
+
+/// An error when serializing or deserializing a value.
+#[derive(Debug)]
+pub enum Error {
+    /// An error from the underlying stream,
+    /// including values too large for their widths
+    Io(io::Error),
+    /// An error from a value's implementation,
+    /// or a value this format cannot represent
+    Message(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref err) => err.fmt(f),
+            Error::Message(ref msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            Error::Message(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {Error::Io(err)}
+}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {Error::Message(msg.to_string())}
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {Error::Message(msg.to_string())}
+}
+
+/// Serializes a value to the given writer using the given schema.
+///
+/// # Errors
+///
+/// Passes along any I/O error from the underlying stream.
+/// Returns an error if a value is too large for its width
+/// or cannot be represented by this format.
+pub fn to_writer<W, T>(writer: &mut W, schema: &Schema, value: &T) ->
+    Result<(), Error>
+    where W: BitWrite + ?Sized, T: ser::Serialize + ?Sized {
+
+    value.serialize(&mut Serializer::new(writer, schema))
+}
+
+/// Deserializes a value from the given reader using the given schema.
+///
+/// # Errors
+///
+/// Passes along any I/O error from the underlying stream.
+/// Returns an error if the data is invalid for the type being read.
+pub fn from_reader<R, T>(reader: &mut R, schema: &Schema) -> Result<T, Error>
+    where R: BitRead + ?Sized, T: de::DeserializeOwned {
+
+    T::deserialize(&mut Deserializer::new(reader, schema))
+}
+
+/// A serde `Serializer` which writes to a `BitWrite` stream.
+pub struct Serializer<'w, W: 'w + BitWrite + ?Sized> {
+    writer: &'w mut W,
+    schema: &'w Schema,
+    width: Option<u32>,
+}
+
+impl<'w, W: BitWrite + ?Sized> Serializer<'w, W> {
+    /// Wraps a Serializer around a writer using the given schema.
+    pub fn new(writer: &'w mut W, schema: &'w Schema) -> Serializer<'w, W> {
+        Serializer{writer, schema, width: None}
+    }
+
+    fn write_length(&mut self, len: usize) -> Result<(), Error> {
+        self.writer.write(self.schema.length_bits, len as u64)
+            .map_err(Error::from)
+    }
+
+    fn write_variant(&mut self, index: u32) -> Result<(), Error> {
+        self.writer.write(self.width.unwrap_or(self.schema.variant_bits),
+                          index).map_err(Error::from)
+    }
+}
+
+macro_rules! serialize_unsigned {
+    ($method:ident, $t:ty, $bits:expr) => {
+        fn $method(self, v: $t) -> Result<(), Error> {
+            self.writer.write(self.width.unwrap_or($bits), v)
+                .map_err(Error::from)
+        }
+    }
+}
+
+macro_rules! serialize_signed {
+    ($method:ident, $t:ty, $bits:expr) => {
+        fn $method(self, v: $t) -> Result<(), Error> {
+            self.writer.write_signed(self.width.unwrap_or($bits), v)
+                .map_err(Error::from)
+        }
+    }
+}
+
+impl<'s, 'w, W: BitWrite + ?Sized> ser::Serializer for &'s mut Serializer<'w, W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'s, 'w, W>;
+    type SerializeTuple = Compound<'s, 'w, W>;
+    type SerializeTupleStruct = Compound<'s, 'w, W>;
+    type SerializeTupleVariant = Compound<'s, 'w, W>;
+    type SerializeMap = Compound<'s, 'w, W>;
+    type SerializeStruct = Compound<'s, 'w, W>;
+    type SerializeStructVariant = Compound<'s, 'w, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.writer.write_bit(v).map_err(Error::from)
+    }
+
+    serialize_unsigned!(serialize_u8, u8, 8);
+    serialize_unsigned!(serialize_u16, u16, 16);
+    serialize_unsigned!(serialize_u32, u32, 32);
+    serialize_unsigned!(serialize_u64, u64, 64);
+    serialize_signed!(serialize_i8, i8, 8);
+    serialize_signed!(serialize_i16, i16, 16);
+    serialize_signed!(serialize_i32, i32, 32);
+    serialize_signed!(serialize_i64, i64, 64);
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.writer.write(32, v.to_bits()).map_err(Error::from)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.writer.write(64, v.to_bits()).map_err(Error::from)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.writer.write(self.width.unwrap_or(32), v as u32)
+            .map_err(Error::from)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.write_length(v.len())?;
+        self.writer.write_bytes(v).map_err(Error::from)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.writer.write_bit(false).map_err(Error::from)
+    }
+
+    fn serialize_some<T: ser::Serialize + ?Sized>(self, value: &T) ->
+        Result<(), Error> {
+        self.writer.write_bit(true)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self,
+                              _name: &'static str,
+                              index: u32,
+                              _variant: &'static str) -> Result<(), Error> {
+        self.write_variant(index)
+    }
+
+    fn serialize_newtype_struct<T: ser::Serialize + ?Sized>(
+        self, _name: &'static str, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ser::Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T) -> Result<(), Error> {
+
+        self.write_variant(index)?;
+        let saved = self.width;
+        self.width = self.schema.width(variant);
+        let result = value.serialize(&mut *self);
+        self.width = saved;
+        result
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Compound<'s, 'w, W>, Error> {
+        match len {
+            Some(len) => {
+                self.write_length(len)?;
+                Ok(Compound::new(self))
+            }
+            None => Err(Error::Message(
+                "sequence length must be known".to_owned())),
+        }
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'s, 'w, W>, Error> {
+        Ok(Compound::new(self))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) ->
+        Result<Compound<'s, 'w, W>, Error> {
+        Ok(Compound::new(self))
+    }
+
+    fn serialize_tuple_variant(self,
+                               _name: &'static str,
+                               index: u32,
+                               variant: &'static str,
+                               _len: usize) ->
+        Result<Compound<'s, 'w, W>, Error> {
+
+        self.write_variant(index)?;
+        let compound = Compound::new(self);
+        compound.ser.width = compound.ser.schema.width(variant);
+        Ok(compound)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Compound<'s, 'w, W>, Error> {
+        match len {
+            Some(len) => {
+                self.write_length(len)?;
+                Ok(Compound::new(self))
+            }
+            None => Err(Error::Message("map length must be known".to_owned())),
+        }
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) ->
+        Result<Compound<'s, 'w, W>, Error> {
+        Ok(Compound::new(self))
+    }
+
+    fn serialize_struct_variant(self,
+                                _name: &'static str,
+                                index: u32,
+                                _variant: &'static str,
+                                _len: usize) ->
+        Result<Compound<'s, 'w, W>, Error> {
+
+        self.write_variant(index)?;
+        Ok(Compound::new(self))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Serializes the items of a compound value,
+/// restoring the enclosing field's width once finished.
+pub struct Compound<'s, 'w: 's, W: 'w + BitWrite + ?Sized> {
+    ser: &'s mut Serializer<'w, W>,
+    saved: Option<u32>,
+}
+
+impl<'s, 'w, W: BitWrite + ?Sized> Compound<'s, 'w, W> {
+    fn new(ser: &'s mut Serializer<'w, W>) -> Compound<'s, 'w, W> {
+        let saved = ser.width;
+        Compound{ser, saved}
+    }
+
+    fn element<T: ser::Serialize + ?Sized>(&mut self, value: &T) ->
+        Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn field<T: ser::Serialize + ?Sized>(&mut self,
+                                         key: &'static str,
+                                         value: &T) -> Result<(), Error> {
+        self.ser.width = self.ser.schema.width(key);
+        value.serialize(&mut *self.ser)
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        self.ser.width = self.saved;
+        Ok(())
+    }
+}
+
+impl<'s, 'w, W: BitWrite + ?Sized> ser::SerializeSeq for Compound<'s, 'w, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ser::Serialize + ?Sized>(&mut self, value: &T) ->
+        Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'s, 'w, W: BitWrite + ?Sized> ser::SerializeTuple for Compound<'s, 'w, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ser::Serialize + ?Sized>(&mut self, value: &T) ->
+        Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'s, 'w, W: BitWrite + ?Sized> ser::SerializeTupleStruct
+    for Compound<'s, 'w, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(&mut self, value: &T) ->
+        Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'s, 'w, W: BitWrite + ?Sized> ser::SerializeTupleVariant
+    for Compound<'s, 'w, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(&mut self, value: &T) ->
+        Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'s, 'w, W: BitWrite + ?Sized> ser::SerializeMap for Compound<'s, 'w, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ser::Serialize + ?Sized>(&mut self, key: &T) ->
+        Result<(), Error> {
+        self.element(key)
+    }
+
+    fn serialize_value<T: ser::Serialize + ?Sized>(&mut self, value: &T) ->
+        Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'s, 'w, W: BitWrite + ?Sized> ser::SerializeStruct for Compound<'s, 'w, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(&mut self,
+                                                   key: &'static str,
+                                                   value: &T) ->
+        Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'s, 'w, W: BitWrite + ?Sized> ser::SerializeStructVariant
+    for Compound<'s, 'w, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(&mut self,
+                                                   key: &'static str,
+                                                   value: &T) ->
+        Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+/// A serde `Deserializer` which reads from a `BitRead` stream.
+pub struct Deserializer<'r, R: 'r + BitRead + ?Sized> {
+    reader: &'r mut R,
+    schema: &'r Schema,
+    width: Option<u32>,
+}
+
+impl<'r, R: BitRead + ?Sized> Deserializer<'r, R> {
+    /// Wraps a Deserializer around a reader using the given schema,
+    /// which must match the one used to serialize the stream.
+    pub fn new(reader: &'r mut R, schema: &'r Schema) -> Deserializer<'r, R> {
+        Deserializer{reader, schema, width: None}
+    }
+
+    fn read_length(&mut self) -> Result<u64, Error> {
+        self.reader.read(self.schema.length_bits).map_err(Error::from)
+    }
+
+    fn read_byte_buf(&mut self) -> Result<Vec<u8>, Error> {
+        // grow the buffer as bytes arrive
+        // so a corrupt length can't allocate too much up front
+        let len = self.read_length()?;
+        let mut buf = Vec::new();
+        while (buf.len() as u64) < len {
+            let start = buf.len();
+            let chunk = min(len - start as u64, 4096) as usize;
+            buf.resize(start + chunk, 0);
+            self.reader.read_bytes(&mut buf[start..])?;
+        }
+        Ok(buf)
+    }
+}
+
+macro_rules! deserialize_unsigned {
+    ($method:ident, $visit:ident, $t:ty, $bits:expr) => {
+        fn $method<V: de::Visitor<'de>>(self, visitor: V) ->
+            Result<V::Value, Error> {
+            visitor.$visit(self.reader.read::<$t>(self.width.unwrap_or($bits))?)
+        }
+    }
+}
+
+macro_rules! deserialize_signed {
+    ($method:ident, $visit:ident, $t:ty, $bits:expr) => {
+        fn $method<V: de::Visitor<'de>>(self, visitor: V) ->
+            Result<V::Value, Error> {
+            visitor.$visit(
+                self.reader.read_signed::<$t>(self.width.unwrap_or($bits))?)
+        }
+    }
+}
+
+impl<'de, 'a, 'r, R: BitRead + ?Sized> de::Deserializer<'de>
+    for &'a mut Deserializer<'r, R> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, _visitor: V) ->
+        Result<V::Value, Error> {
+        Err(Error::Message("bit-packed values are not self-describing"
+                           .to_owned()))
+    }
+
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) ->
+        Result<V::Value, Error> {
+        visitor.visit_bool(self.reader.read_bit()?)
+    }
+
+    deserialize_unsigned!(deserialize_u8, visit_u8, u8, 8);
+    deserialize_unsigned!(deserialize_u16, visit_u16, u16, 16);
+    deserialize_unsigned!(deserialize_u32, visit_u32, u32, 32);
+    deserialize_unsigned!(deserialize_u64, visit_u64, u64, 64);
+    deserialize_signed!(deserialize_i8, visit_i8, i8, 8);
+    deserialize_signed!(deserialize_i16, visit_i16, i16, 16);
+    deserialize_signed!(deserialize_i32, visit_i32, i32, 32);
+    deserialize_signed!(deserialize_i64, visit_i64, i64, 64);
+
+    fn deserialize_f32<V: de::Visitor<'de>>(self, visitor: V) ->
+        Result<V::Value, Error> {
+        visitor.visit_f32(f32::from_bits(self.reader.read(32)?))
+    }
+
+    fn deserialize_f64<V: de::Visitor<'de>>(self, visitor: V) ->
+        Result<V::Value, Error> {
+        visitor.visit_f64(f64::from_bits(self.reader.read(64)?))
+    }
+
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) ->
+        Result<V::Value, Error> {
+        let value: u32 = self.reader.read(self.width.unwrap_or(32))?;
+        match ::core::char::from_u32(value) {
+            Some(c) => visitor.visit_char(c),
+            None => Err(de::Error::invalid_value(
+                de::Unexpected::Unsigned(u64::from(value)), &"a char")),
+        }
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) ->
+        Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) ->
+        Result<V::Value, Error> {
+        match String::from_utf8(self.read_byte_buf()?) {
+            Ok(s) => visitor.visit_string(s),
+            Err(err) => Err(de::Error::invalid_value(
+                de::Unexpected::Bytes(err.as_bytes()), &"a UTF-8 string")),
+        }
+    }
+
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) ->
+        Result<V::Value, Error> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) ->
+        Result<V::Value, Error> {
+        visitor.visit_byte_buf(self.read_byte_buf()?)
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) ->
+        Result<V::Value, Error> {
+        if self.reader.read_bit()? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) ->
+        Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(self,
+                                                    _name: &'static str,
+                                                    visitor: V) ->
+        Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(self,
+                                                       _name: &'static str,
+                                                       visitor: V) ->
+        Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) ->
+        Result<V::Value, Error> {
+        let remaining = self.read_length()?;
+        visitor.visit_seq(Items{de: self, remaining})
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(self, len: usize, visitor: V) ->
+        Result<V::Value, Error> {
+        visitor.visit_seq(Items{de: self, remaining: len as u64})
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(self,
+                                                     _name: &'static str,
+                                                     len: usize,
+                                                     visitor: V) ->
+        Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) ->
+        Result<V::Value, Error> {
+        let remaining = self.read_length()?;
+        visitor.visit_map(Items{de: self, remaining})
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(self,
+                                               _name: &'static str,
+                                               fields: &'static [&'static str],
+                                               visitor: V) ->
+        Result<V::Value, Error> {
+        let saved = self.width;
+        let result = visitor.visit_seq(Fields{de: &mut *self, fields, index: 0});
+        self.width = saved;
+        result
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(self,
+                                             _name: &'static str,
+                                             variants: &'static [&'static str],
+                                             visitor: V) ->
+        Result<V::Value, Error> {
+        let saved = self.width;
+        let result = visitor.visit_enum(Variant{de: &mut *self, variants});
+        self.width = saved;
+        result
+    }
+
+    fn deserialize_identifier<V: de::Visitor<'de>>(self, _visitor: V) ->
+        Result<V::Value, Error> {
+        Err(Error::Message("bit-packed values have no identifiers"
+                           .to_owned()))
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(self, visitor: V) ->
+        Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+// the items of a sequence, tuple or map
+struct Items<'a, 'r: 'a, R: 'r + BitRead + ?Sized> {
+    de: &'a mut Deserializer<'r, R>,
+    remaining: u64,
+}
+
+impl<'de, 'a, 'r, R: BitRead + ?Sized> de::SeqAccess<'de> for Items<'a, 'r, R> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) ->
+        Result<Option<T::Value>, Error> {
+        if self.remaining == 0 {
+            Ok(None)
+        } else {
+            self.remaining -= 1;
+            seed.deserialize(&mut *self.de).map(Some)
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(min(self.remaining, 4096) as usize)
+    }
+}
+
+impl<'de, 'a, 'r, R: BitRead + ?Sized> de::MapAccess<'de> for Items<'a, 'r, R> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) ->
+        Result<Option<K::Value>, Error> {
+        if self.remaining == 0 {
+            Ok(None)
+        } else {
+            self.remaining -= 1;
+            seed.deserialize(&mut *self.de).map(Some)
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) ->
+        Result<V::Value, Error> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(min(self.remaining, 4096) as usize)
+    }
+}
+
+// the fields of a struct, each read with its own width
+struct Fields<'a, 'r: 'a, R: 'r + BitRead + ?Sized> {
+    de: &'a mut Deserializer<'r, R>,
+    fields: &'static [&'static str],
+    index: usize,
+}
+
+impl<'de, 'a, 'r, R: BitRead + ?Sized> de::SeqAccess<'de> for Fields<'a, 'r, R> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) ->
+        Result<Option<T::Value>, Error> {
+        match self.fields.get(self.index) {
+            Some(field) => {
+                self.index += 1;
+                self.de.width = self.de.schema.width(field);
+                seed.deserialize(&mut *self.de).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len() - self.index)
+    }
+}
+
+// an enum's variant index and its contents
+struct Variant<'a, 'r: 'a, R: 'r + BitRead + ?Sized> {
+    de: &'a mut Deserializer<'r, R>,
+    variants: &'static [&'static str],
+}
+
+impl<'de, 'a, 'r, R: BitRead + ?Sized> de::EnumAccess<'de>
+    for Variant<'a, 'r, R> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) ->
+        Result<(V::Value, Self), Error> {
+        let bits = self.de.width.unwrap_or(self.de.schema.variant_bits);
+        let index: u32 = self.de.reader.read(bits)?;
+        let value = seed.deserialize(
+            IntoDeserializer::<'de, Error>::into_deserializer(index))?;
+        self.de.width = self.variants.get(index as usize)
+            .and_then(|variant| self.de.schema.width(variant));
+        Ok((value, self))
+    }
+}
+
+impl<'de, 'a, 'r, R: BitRead + ?Sized> de::VariantAccess<'de>
+    for Variant<'a, 'r, R> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) ->
+        Result<T::Value, Error> {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(self, len: usize, visitor: V) ->
+        Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self.de, len, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(self,
+                                           fields: &'static [&'static str],
+                                           visitor: V) ->
+        Result<V::Value, Error> {
+        visitor.visit_seq(Fields{de: self.de, fields, index: 0})
+    }
+}
@@ -0,0 +1,239 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Bit-level statistics of a stream.
+//!
+//! This module requires the `std` feature.
+//!
+//! An `Analyzer` reads a stream to its end and reports
+//! how dense its 1 bits are, how long its runs of 0s and 1s are,
+//! and an estimate of its entropy over a sliding window.
+//! These help when choosing coding parameters,
+//! such as a Rice parameter from the typical run length,
+//! and when checking that scrambled or whitened data
+//! looks as random as it should.
+//!
+//! Each window's entropy is estimated from how often
+//! each symbol of `symbol_bits` bits appears in it,
+//! and is given in bits of entropy per bit of stream,
+//! from 0 for a constant stream to 1 for one indistinguishable
+//! from random at that symbol size.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitReader};
+//! use bitstream_io::stats::Analyzer;
+//!
+//! let data = [0b1111_0000, 0b1100_1100, 0b1010_1010, 0b1010_1010];
+//! let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+//! let analyzer = Analyzer{window: 16, step: 8, symbol_bits: 2};
+//! let stats = analyzer.analyze(&mut reader).unwrap();
+//! assert_eq!(stats.bits, 32);
+//! assert_eq!(stats.ones_density(), 0.5);
+//! assert_eq!(stats.one_runs.get(&4), Some(&1));
+//! assert_eq!(stats.zero_runs.get(&1), Some(&8));
+//! // the last window holds nothing but 0b10 symbols
+//! assert_eq!(stats.entropy, [0.5, 0.75, 0.0]);
+//! ```
+
+#![warn(missing_docs)]
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io;
+
+use super::BitRead;
+
+/// The parameters of a stream's analysis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Analyzer {
+    /// The number of bits in each window whose entropy is estimated,
+    /// which must be a multiple of `symbol_bits`
+    pub window: u64,
+    /// The number of bits from the start of one window
+    /// to the start of the next, which must be a multiple
+    /// of `symbol_bits`
+    pub step: u64,
+    /// The size of the symbols counted in each window, from 1 to 16
+    pub symbol_bits: u32,
+}
+
+/// Windows of 4096 bits, one starting every 1024 bits,
+/// counting bytes.
+impl Default for Analyzer {
+    fn default() -> Self {
+        Analyzer{window: 4096, step: 1024, symbol_bits: 8}
+    }
+}
+
+/// The statistics gathered from a stream.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Statistics {
+    /// The number of bits read
+    pub bits: u64,
+    /// The number of 1 bits read
+    pub ones: u64,
+    /// The number of runs of 0 bits of each length
+    pub zero_runs: BTreeMap<u64, u64>,
+    /// The number of runs of 1 bits of each length
+    pub one_runs: BTreeMap<u64, u64>,
+    /// The estimated entropy, per bit, of each whole window
+    /// in stream order
+    pub entropy: Vec<f64>,
+}
+
+impl Statistics {
+    /// Returns the proportion of bits which are 1,
+    /// or 0 if the stream is empty.
+    pub fn ones_density(&self) -> f64 {
+        if self.bits > 0 {self.ones as f64 / self.bits as f64} else {0.0}
+    }
+
+    /// Returns the mean length of the runs of both values,
+    /// or 0 if the stream is empty.
+    pub fn mean_run(&self) -> f64 {
+        let runs: u64 = self.zero_runs.values().chain(self.one_runs.values())
+            .sum();
+        if runs > 0 {self.bits as f64 / runs as f64} else {0.0}
+    }
+
+    fn count_run(&mut self, (value, len): (bool, u64)) {
+        let runs = if value {&mut self.one_runs} else {&mut self.zero_runs};
+        *runs.entry(len).or_insert(0) += 1;
+    }
+}
+
+impl Analyzer {
+    /// Reads the stream until its end, gathering its statistics.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidInput` error if the analyzer's parameters
+    /// are invalid, or passes along any I/O error
+    /// other than reaching the end of the stream.
+    pub fn analyze<R>(&self, reader: &mut R) -> io::Result<Statistics>
+        where R: BitRead + ?Sized {
+
+        self.check()?;
+        let window_symbols = (self.window / u64::from(self.symbol_bits))
+            as usize;
+        let step_symbols = self.step / u64::from(self.symbol_bits);
+
+        let mut stats = Statistics::default();
+        let mut run: Option<(bool, u64)> = None;
+        let mut symbol = 0;
+        let mut symbol_len = 0;
+        let mut symbols = 0u64;
+        let mut window = SymbolWindow::new(self.symbol_bits);
+        let mut queue = VecDeque::with_capacity(window_symbols + 1);
+
+        loop {
+            let bit = match reader.read_bit() {
+                Ok(bit) => bit,
+                Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    break;
+                }
+                Err(err) => return Err(err),
+            };
+            stats.bits += 1;
+            if bit {
+                stats.ones += 1;
+            }
+
+            run = match run {
+                Some((value, len)) if value == bit => Some((value, len + 1)),
+                Some(finished) => {
+                    stats.count_run(finished);
+                    Some((bit, 1))
+                }
+                None => Some((bit, 1)),
+            };
+
+            symbol = (symbol << 1) | usize::from(bit);
+            symbol_len += 1;
+            if symbol_len == self.symbol_bits {
+                window.add(symbol);
+                queue.push_back(symbol);
+                if queue.len() > window_symbols {
+                    if let Some(oldest) = queue.pop_front() {
+                        window.remove(oldest);
+                    }
+                }
+                symbols += 1;
+                if symbols >= window_symbols as u64 &&
+                    (symbols - window_symbols as u64)
+                    .is_multiple_of(step_symbols) {
+                    stats.entropy.push(window.entropy());
+                }
+                symbol = 0;
+                symbol_len = 0;
+            }
+        }
+
+        if let Some(finished) = run {
+            stats.count_run(finished);
+        }
+        Ok(stats)
+    }
+
+    fn check(&self) -> io::Result<()> {
+        let symbol_bits = u64::from(self.symbol_bits);
+        if (1..=16).contains(&self.symbol_bits) &&
+            self.window > 0 && self.window.is_multiple_of(symbol_bits) &&
+            self.step > 0 && self.step.is_multiple_of(symbol_bits) {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidInput,
+                               "invalid analyzer parameters"))
+        }
+    }
+}
+
+// counts the symbols within a window,
+// along with the sum of each count times its log,
+// so that entropy can be found without a pass over every count
+struct SymbolWindow {
+    counts: Vec<u64>,
+    total: u64,
+    weighted: f64,
+    symbol_bits: u32,
+}
+
+impl SymbolWindow {
+    fn new(symbol_bits: u32) -> SymbolWindow {
+        SymbolWindow{counts: vec![0; 1 << symbol_bits],
+                     total: 0,
+                     weighted: 0.0,
+                     symbol_bits}
+    }
+
+    fn add(&mut self, symbol: usize) {
+        let count = &mut self.counts[symbol];
+        self.weighted += n_log_n(*count + 1) - n_log_n(*count);
+        *count += 1;
+        self.total += 1;
+    }
+
+    fn remove(&mut self, symbol: usize) {
+        let count = &mut self.counts[symbol];
+        self.weighted += n_log_n(*count - 1) - n_log_n(*count);
+        *count -= 1;
+        self.total -= 1;
+    }
+
+    // the Shannon entropy of the symbols, per bit
+    fn entropy(&self) -> f64 {
+        let total = self.total as f64;
+        let entropy = total.log2() - self.weighted / total;
+        (entropy / f64::from(self.symbol_bits)).clamp(0.0, 1.0)
+    }
+}
+
+fn n_log_n(n: u64) -> f64 {
+    if n == 0 {0.0} else {n as f64 * (n as f64).log2()}
+}
@@ -0,0 +1,197 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Asynchronous reading of bit-packed values as a `Stream`.
+//!
+//! `AsyncBitReader` wraps any `futures` `AsyncRead`, and its
+//! `into_stream` method turns it into a `Stream` of values
+//! parsed one after another with `FromBitStream`.
+//! Values are packed back-to-back and need not end on byte boundaries.
+//!
+//! Bytes are buffered as they arrive, so a value split across
+//! several reads - and several polls - is parsed once
+//! all of it is available.
+//! The stream ends cleanly if the source ends with at most
+//! the padding bits of a partially-used final byte left over,
+//! and yields one `UnexpectedEof` error if it ends partway
+//! through a value.
+//! After any error, the stream ends.
+//!
+//! ## Example
+//!
+//! ```
+//! extern crate futures;
+//! extern crate bitstream_io;
+//! use std::io;
+//! use futures::executor::block_on;
+//! use futures::stream::StreamExt;
+//! use bitstream_io::{BigEndian, BitRead, FromBitStream};
+//! use bitstream_io::stream::AsyncBitReader;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Reading {
+//!     sensor: u8,
+//!     value: u16,
+//! }
+//!
+//! impl FromBitStream for Reading {
+//!     type Error = io::Error;
+//!     fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+//!         Ok(Reading{sensor: r.read(2)?, value: r.read(10)?})
+//!     }
+//! }
+//!
+//! # fn main() {
+//! // two 12-bit readings in three bytes
+//! let data: &[u8] = &[0b0100_0000, 0b0111_1111, 0b1111_1111];
+//! let reader = AsyncBitReader::<BigEndian, _>::new(data);
+//! let readings: Vec<Reading> = block_on(
+//!     reader.into_stream::<Reading>().map(|r| r.unwrap()).collect());
+//! assert_eq!(readings, vec![Reading{sensor: 1, value: 7},
+//!                           Reading{sensor: 3, value: 1023}]);
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use futures_io::AsyncRead;
+
+use read;
+use super::{Endianness, FromBitStream};
+
+/// The number of bytes requested from the source at a time.
+const CHUNK_SIZE: usize = 4096;
+
+/// Reads bit-packed values from an asynchronous byte source.
+pub struct AsyncBitReader<E: Endianness, R> {
+    reader: R,
+    phantom: PhantomData<fn() -> E>,
+}
+
+impl<E: Endianness, R: AsyncRead + Unpin> AsyncBitReader<E, R> {
+    /// Wraps an asynchronous reader.
+    #[inline]
+    pub fn new(reader: R) -> AsyncBitReader<E, R> {
+        AsyncBitReader{reader, phantom: PhantomData}
+    }
+
+    /// Returns a reference to the underlying reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {&self.reader}
+
+    /// Returns the underlying reader.
+    #[inline]
+    pub fn into_inner(self) -> R {self.reader}
+
+    /// Converts the reader into a stream of values of type `T`,
+    /// parsed one after another.
+    pub fn into_stream<T>(self) -> BitStream<E, R, T>
+        where T: FromBitStream, T::Error: From<io::Error> {
+        BitStream{reader: self.reader,
+                  buf: Vec::new(),
+                  skip: 0,
+                  eof: false,
+                  done: false,
+                  phantom: PhantomData}
+    }
+}
+
+impl<E: Endianness, R> fmt::Debug for AsyncBitReader<E, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AsyncBitReader").finish()
+    }
+}
+
+/// A stream of values parsed from an `AsyncBitReader`.
+pub struct BitStream<E: Endianness, R, T> {
+    reader: R,
+    /*bytes received but not yet fully consumed*/
+    buf: Vec<u8>,
+    /*bits of buf's first byte already consumed*/
+    skip: u32,
+    eof: bool,
+    done: bool,
+    phantom: PhantomData<fn() -> (E, T)>,
+}
+
+impl<E: Endianness, R, T> BitStream<E, R, T> {
+    /// Returns the number of bits received but not yet parsed.
+    #[inline]
+    pub fn buffered_bits(&self) -> u64 {
+        (self.buf.len() as u64 * 8) - u64::from(self.skip)
+    }
+}
+
+impl<E: Endianness, R, T> fmt::Debug for BitStream<E, R, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BitStream")
+            .field("buffered_bits", &self.buffered_bits())
+            .field("eof", &self.eof)
+            .finish()
+    }
+}
+
+impl<E, R, T> Stream for BitStream<E, R, T>
+    where E: Endianness,
+          R: AsyncRead + Unpin,
+          T: FromBitStream,
+          T::Error: From<io::Error> {
+    type Item = Result<T, T::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) ->
+        Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut chunk = [0; CHUNK_SIZE];
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+            match read::parse_accumulated::<E, T>(&this.buf, this.skip) {
+                Ok(Some((value, bits))) => {
+                    this.buf.drain(..(bits / 8) as usize);
+                    this.skip = (bits % 8) as u32;
+                    return Poll::Ready(Some(Ok(value)));
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+            if this.eof {
+                this.done = true;
+                /*leftover bits of a partially-used byte are padding*/
+                if this.buf.len() > 1 || (this.buf.len() == 1 && this.skip == 0) {
+                    return Poll::Ready(Some(Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "stream ended partway through a value").into())));
+                } else {
+                    return Poll::Ready(None);
+                }
+            }
+            match Pin::new(&mut this.reader).poll_read(cx, &mut chunk) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Ok(0)) => this.eof = true,
+                Poll::Ready(Ok(n)) => this.buf.extend_from_slice(&chunk[..n]),
+                Poll::Ready(Err(ref err))
+                    if err.kind() == io::ErrorKind::Interrupted => {}
+                Poll::Ready(Err(err)) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(err.into())));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Precomputed tables of per-byte bit properties.
+//!
+//! Each table is indexed by a byte's value and built at compile time,
+//! for use by fast paths which handle a whole byte at once,
+//! such as those built on a `BitQueue<E, u8>`.
+//! Runs are counted from a byte's most significant bit
+//! for big-endian streams and from its least significant bit
+//! for little-endian ones, so a byte of all 0s or all 1s
+//! has a run of 8.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::tables::{LEADING_ONES, REVERSED, TRAILING_ZEROS};
+//!
+//! // a big-endian unary value of 3 is 3 1 bits and a 0 bit
+//! assert_eq!(LEADING_ONES[0b1110_0101], 3);
+//! // the same bits in a little-endian stream
+//! assert_eq!(REVERSED[0b1110_0101], 0b1010_0111);
+//! assert_eq!(TRAILING_ZEROS[0b1000_0000], 7);
+//! ```
+
+#![warn(missing_docs)]
+
+use super::{is_little_endian, Endianness};
+
+macro_rules! byte_table {
+    ($f:ident) => {{
+        let mut table = [0; 256];
+        let mut byte = 0;
+        while byte < 256 {
+            table[byte] = (byte as u8).$f() as u8;
+            byte += 1;
+        }
+        table
+    }}
+}
+
+/// Each byte with the order of its bits reversed
+pub const REVERSED: [u8; 256] = byte_table!(reverse_bits);
+
+/// The number of 1 bits in each byte
+pub const ONES: [u8; 256] = byte_table!(count_ones);
+
+/// The number of 0 bits before the first 1 bit,
+/// starting from each byte's most significant bit
+pub const LEADING_ZEROS: [u8; 256] = byte_table!(leading_zeros);
+
+/// The number of 1 bits before the first 0 bit,
+/// starting from each byte's most significant bit
+pub const LEADING_ONES: [u8; 256] = byte_table!(leading_ones);
+
+/// The number of 0 bits before the first 1 bit,
+/// starting from each byte's least significant bit
+pub const TRAILING_ZEROS: [u8; 256] = byte_table!(trailing_zeros);
+
+/// The number of 1 bits before the first 0 bit,
+/// starting from each byte's least significant bit
+pub const TRAILING_ONES: [u8; 256] = byte_table!(trailing_ones);
+
+/// Returns the number of 0 bits at the start of a byte,
+/// in the order they would be read from a stream
+/// of the given endianness.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, LittleEndian};
+/// use bitstream_io::tables::zeros_run;
+/// assert_eq!(zeros_run::<BigEndian>(0b0001_1000), 3);
+/// assert_eq!(zeros_run::<LittleEndian>(0b0001_1000), 3);
+/// assert_eq!(zeros_run::<LittleEndian>(0b0001_0000), 4);
+/// ```
+#[inline(always)]
+pub fn zeros_run<E: Endianness>(byte: u8) -> u32 {
+    u32::from(if is_little_endian::<E>() {
+        TRAILING_ZEROS[byte as usize]
+    } else {
+        LEADING_ZEROS[byte as usize]
+    })
+}
+
+/// Returns the number of 1 bits at the start of a byte,
+/// in the order they would be read from a stream
+/// of the given endianness.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, LittleEndian};
+/// use bitstream_io::tables::ones_run;
+/// assert_eq!(ones_run::<BigEndian>(0b1100_0111), 2);
+/// assert_eq!(ones_run::<LittleEndian>(0b1100_0111), 3);
+/// ```
+#[inline(always)]
+pub fn ones_run<E: Endianness>(byte: u8) -> u32 {
+    u32::from(if is_little_endian::<E>() {
+        TRAILING_ONES[byte as usize]
+    } else {
+        LEADING_ONES[byte as usize]
+    })
+}
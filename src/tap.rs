@@ -0,0 +1,395 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Observing the fields which pass through a stream.
+//!
+//! This module requires the `std` feature.
+//!
+//! A `Tap` wraps a `BitRead` or `BitWrite` stream and counts
+//! the bits passing through it, from where the stream was
+//! when it was wrapped.
+//! Each field the stream accepts is shown to an `Observer`
+//! along with its offset, and any error the stream returns
+//! may be given context by the observer, such as a `TapError`
+//! carrying the offset of the field which failed.
+//! The readers of the `context` and `history` modules,
+//! the meters of the `meter` module and the writers
+//! of the `trace` module are all taps with their own observers.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitRead, BitReader};
+//! use bitstream_io::tap::{Observer, Tap};
+//!
+//! // keeps the offset of every field
+//! struct Offsets(Vec<u64>);
+//!
+//! impl Observer for Offsets {
+//!     fn bits(&mut self, offset: u64, _bits: u64) {self.0.push(offset);}
+//! }
+//!
+//! let data = [0b1011_0001, 0xFF];
+//! let mut reader = Tap::with_observer(
+//!     BitReader::<BigEndian, _>::from_read(&data[..]),
+//!     Offsets(Vec::new()));
+//! assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+//! assert_eq!(reader.read_unary0().unwrap(), 1);
+//! reader.byte_align();
+//! assert_eq!(reader.read::<u8>(8).unwrap(), 0xFF);
+//! assert_eq!(reader.position(), 16);
+//! assert_eq!(reader.observer().0, [0, 3, 5, 8]);
+//! ```
+
+#![warn(missing_docs)]
+
+use std::cmp::min;
+use std::error;
+use std::fmt;
+use std::io;
+
+use super::{BitRead, BitWrite, Numeric, SignedNumeric};
+
+/// Watches the fields passing through a `Tap`.
+///
+/// Each method is called once a field has passed through,
+/// with the field's offset from where the stream was wrapped.
+/// By default, every kind of field is passed along to `bits`,
+/// which does nothing.
+pub trait Observer {
+    /// Observes a field of the given number of bits.
+    #[inline]
+    fn bits(&mut self, _offset: u64, _bits: u64) {}
+
+    /// Observes a single bit.
+    #[inline]
+    fn bit(&mut self, offset: u64, _bit: bool) {self.bits(offset, 1)}
+
+    /// Observes an unsigned value of the given number of bits.
+    #[inline]
+    fn unsigned<U>(&mut self, offset: u64, bits: u32, _value: U)
+        where U: Numeric {
+        self.bits(offset, u64::from(bits))
+    }
+
+    /// Observes a signed value of the given number of bits.
+    #[inline]
+    fn signed<S>(&mut self, offset: u64, bits: u32, _value: S)
+        where S: SignedNumeric {
+        self.bits(offset, u64::from(bits))
+    }
+
+    /// Observes a run of whole bytes.
+    #[inline]
+    fn bytes(&mut self, offset: u64, buf: &[u8]) {
+        self.bits(offset, buf.len() as u64 * 8)
+    }
+
+    /// Observes a unary value of 1 bits terminated by a 0 bit.
+    #[inline]
+    fn unary0(&mut self, offset: u64, value: u32) {
+        self.bits(offset, u64::from(value) + 1)
+    }
+
+    /// Observes a unary value of 0 bits terminated by a 1 bit.
+    #[inline]
+    fn unary1(&mut self, offset: u64, value: u32) {
+        self.bits(offset, u64::from(value) + 1)
+    }
+
+    /// Observes bits skipped over without being read.
+    #[inline]
+    fn skipped(&mut self, offset: u64, bits: u64) {self.bits(offset, bits)}
+
+    /// Observes the padding bits read or written by `byte_align`,
+    /// in stream order.
+    #[inline]
+    fn padding(&mut self, offset: u64, bits: &[bool]) {
+        self.bits(offset, bits.len() as u64)
+    }
+
+    /// Given the number of bits about to be skipped,
+    /// returns how many of the last of them should be read
+    /// and observed as unsigned values of up to 64 bits instead.
+    /// By default, none are.
+    #[inline]
+    fn watched(&self, _skip: u32) -> u32 {0}
+
+    /// Returns the error to pass along in place of one returned
+    /// by the stream for the field at the given offset.
+    /// By default, the stream's error is passed along unchanged.
+    #[inline]
+    fn error(&self, _offset: u64, error: io::Error) -> io::Error {error}
+}
+
+/// An error along with where in the stream it occurred
+/// and any context attached to it.
+#[derive(Debug)]
+pub struct TapError<C> {
+    error: io::Error,
+    position: u64,
+    context: C,
+}
+
+impl<C> TapError<C> {
+    /// Attaches the given bit offset and context to an error.
+    #[inline]
+    pub fn new(error: io::Error, position: u64, context: C) -> TapError<C> {
+        TapError{error, position, context}
+    }
+
+    /// Returns the bit offset of the field being read or written
+    /// when the error occurred.
+    #[inline]
+    pub fn position(&self) -> u64 {self.position}
+
+    /// Returns the context attached to the error.
+    #[inline]
+    pub fn context(&self) -> &C {&self.context}
+
+    /// Returns the original error.
+    #[inline]
+    pub fn get_ref(&self) -> &io::Error {&self.error}
+
+    /// Returns the original error, discarding its context.
+    #[inline]
+    pub fn into_inner(self) -> io::Error {self.error}
+}
+
+impl<C> TapError<C>
+    where C: fmt::Debug + fmt::Display + Send + Sync + 'static {
+    /// Returns the context of this type attached to an error, if any.
+    pub fn find(err: &io::Error) -> Option<&TapError<C>> {
+        err.get_ref().and_then(|inner| inner.downcast_ref())
+    }
+}
+
+/// Displays the original error, its offset and then its context
+impl<C: fmt::Display> fmt::Display for TapError<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at bit {}{}", self.error, self.position, self.context)
+    }
+}
+
+impl<C: fmt::Debug + fmt::Display> error::Error for TapError<C> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<C> From<TapError<C>> for io::Error
+    where C: fmt::Debug + fmt::Display + Send + Sync + 'static {
+    /// The error keeps the original error's kind
+    fn from(err: TapError<C>) -> Self {
+        io::Error::new(err.error.kind(), err)
+    }
+}
+
+/// Wraps a bit stream, showing the fields passing through it
+/// to an observer.
+///
+/// Offsets count from where the stream was when it was wrapped.
+pub struct Tap<T, O> {
+    inner: T,
+    position: u64,
+    observer: O,
+}
+
+impl<T, O: Observer> Tap<T, O> {
+    /// Wraps the stream, starting at bit offset 0.
+    #[inline]
+    pub fn with_observer(inner: T, observer: O) -> Tap<T, O> {
+        Tap{inner, position: 0, observer}
+    }
+
+    /// Returns the number of bits passed through so far.
+    #[inline]
+    pub fn position(&self) -> u64 {self.position}
+
+    /// Returns a reference to the observer.
+    #[inline]
+    pub fn observer(&self) -> &O {&self.observer}
+
+    /// Returns a mutable reference to the observer.
+    #[inline]
+    pub fn observer_mut(&mut self) -> &mut O {&mut self.observer}
+
+    /// Returns a reference to the wrapped stream.
+    #[inline]
+    pub fn get_ref(&self) -> &T {&self.inner}
+
+    /// Returns a mutable reference to the wrapped stream.
+    /// Bits passing through it directly aren't counted or observed.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {&mut self.inner}
+
+    /// Returns the wrapped stream.
+    #[inline]
+    pub fn into_inner(self) -> T {self.inner}
+
+    /// Returns the wrapped stream and the observer.
+    #[inline]
+    pub fn into_parts(self) -> (T, O) {(self.inner, self.observer)}
+
+    // shows a successful field to the observer and counts it,
+    // or has the observer attach context to a failed one
+    fn observed<V, F>(&mut self, bits: u64, result: io::Result<V>, f: F) ->
+        io::Result<V> where F: FnOnce(&mut O, u64, &V) {
+        match result {
+            Ok(value) => {
+                f(&mut self.observer, self.position, &value);
+                self.position += bits;
+                Ok(value)
+            }
+            Err(error) => Err(self.observer.error(self.position, error)),
+        }
+    }
+
+    // shows padding bits to the observer and counts them
+    fn padded(&mut self, padding: &[bool]) {
+        if !padding.is_empty() {
+            self.observer.padding(self.position, padding);
+            self.position += padding.len() as u64;
+        }
+    }
+}
+
+impl<T: BitRead, O: Observer> BitRead for Tap<T, O> {
+    fn read_bit(&mut self) -> Result<bool, io::Error> {
+        let result = self.inner.read_bit();
+        self.observed(1, result, |o, offset, &bit| o.bit(offset, bit))
+    }
+
+    fn read<U>(&mut self, bits: u32) -> Result<U, io::Error>
+        where U: Numeric {
+        let result = self.inner.read(bits);
+        self.observed(u64::from(bits), result,
+                      |o, offset, &value| o.unsigned(offset, bits, value))
+    }
+
+    fn read_signed<S>(&mut self, bits: u32) -> Result<S, io::Error>
+        where S: SignedNumeric {
+        let result = self.inner.read_signed(bits);
+        self.observed(u64::from(bits), result,
+                      |o, offset, &value| o.signed(offset, bits, value))
+    }
+
+    fn skip(&mut self, bits: u32) -> Result<(), io::Error> {
+        // only the bits the observer doesn't watch are skipped
+        let watched = min(self.observer.watched(bits), bits);
+        let skipped = bits - watched;
+        let result = self.inner.skip(skipped);
+        self.observed(u64::from(skipped), result,
+                      |o, offset, _| o.skipped(offset, u64::from(skipped)))?;
+        let mut remaining = watched;
+        while remaining > 0 {
+            let chunk = min(remaining, 64);
+            let result = self.inner.read::<u64>(chunk);
+            self.observed(u64::from(chunk), result, |o, offset, &value| {
+                o.unsigned(offset, chunk, value)
+            })?;
+            remaining -= chunk;
+        }
+        Ok(())
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        let result = self.inner.read_bytes(buf);
+        self.observed(buf.len() as u64 * 8, result,
+                      |o, offset, _| o.bytes(offset, buf))
+    }
+
+    fn read_unary0(&mut self) -> Result<u32, io::Error> {
+        let result = self.inner.read_unary0();
+        let bits = result.as_ref().map(|&v| u64::from(v) + 1).unwrap_or(0);
+        self.observed(bits, result,
+                      |o, offset, &value| o.unary0(offset, value))
+    }
+
+    fn read_unary1(&mut self) -> Result<u32, io::Error> {
+        let result = self.inner.read_unary1();
+        let bits = result.as_ref().map(|&v| u64::from(v) + 1).unwrap_or(0);
+        self.observed(bits, result,
+                      |o, offset, &value| o.unary1(offset, value))
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.inner.byte_aligned()
+    }
+
+    fn byte_align(&mut self) {
+        let mut padding = [false; 8];
+        let mut len = 0;
+        while !self.inner.byte_aligned() && len < padding.len() {
+            match self.inner.read_bit() {
+                Ok(bit) => {
+                    padding[len] = bit;
+                    len += 1;
+                }
+                Err(_) => break,
+            }
+        }
+        self.padded(&padding[0..len]);
+    }
+}
+
+impl<T: BitWrite, O: Observer> BitWrite for Tap<T, O> {
+    fn write_bit(&mut self, bit: bool) -> Result<(), io::Error> {
+        let result = self.inner.write_bit(bit);
+        self.observed(1, result, |o, offset, _| o.bit(offset, bit))
+    }
+
+    fn write<U>(&mut self, bits: u32, value: U) -> Result<(), io::Error>
+        where U: Numeric {
+        let result = self.inner.write(bits, value);
+        self.observed(u64::from(bits), result,
+                      |o, offset, _| o.unsigned(offset, bits, value))
+    }
+
+    fn write_signed<S>(&mut self, bits: u32, value: S) ->
+        Result<(), io::Error> where S: SignedNumeric {
+        let result = self.inner.write_signed(bits, value);
+        self.observed(u64::from(bits), result,
+                      |o, offset, _| o.signed(offset, bits, value))
+    }
+
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), io::Error> {
+        let result = self.inner.write_bytes(buf);
+        self.observed(buf.len() as u64 * 8, result,
+                      |o, offset, _| o.bytes(offset, buf))
+    }
+
+    fn write_unary0(&mut self, value: u32) -> Result<(), io::Error> {
+        let result = self.inner.write_unary0(value);
+        self.observed(u64::from(value) + 1, result,
+                      |o, offset, _| o.unary0(offset, value))
+    }
+
+    fn write_unary1(&mut self, value: u32) -> Result<(), io::Error> {
+        let result = self.inner.write_unary1(value);
+        self.observed(u64::from(value) + 1, result,
+                      |o, offset, _| o.unary1(offset, value))
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.inner.byte_aligned()
+    }
+
+    fn byte_align(&mut self) -> Result<(), io::Error> {
+        let padding = [false; 8];
+        let mut len = 0;
+        while !self.inner.byte_aligned() && len < padding.len() {
+            self.inner.write_bit(false)?;
+            len += 1;
+        }
+        self.padded(&padding[0..len]);
+        Ok(())
+    }
+}
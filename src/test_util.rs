@@ -0,0 +1,832 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Helpers for testing `FromBitStream` and `ToBitStream` implementations.
+//!
+//! These check that a value written to a stream reads back
+//! as the same value in both endiannesses, and describe any mismatch
+//! with a bit-level diff of the two encodings.
+//!
+//! It also provides proptest strategies for sequences of fields
+//! and properties checking that any such sequence reads back
+//! as written, in either endianness and at any alignment.
+//! The strategies favor the widths which most often expose bugs,
+//! such as 1 bit and the full size of the field's type.
+//!
+//! Golden tests of an encoder's exact output are run with `check_golden`
+//! and `assert_golden`, which report a mismatch field by field,
+//! naming any fields labeled through the `TraceWriter` they write to.
+//!
+//! Finally, the `Dribble`, `Flaky` and `FailAfter` adaptors
+//! wrap a reader or writer to deliver short transfers
+//! and inject errors, for testing how decoders and encoders
+//! handle them.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io;
+//! use bitstream_io::{BitRead, BitWrite, FromBitStream, ToBitStream};
+//! use bitstream_io::test_util::assert_roundtrip;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Sample {
+//!     left: i16,
+//!     right: i16,
+//! }
+//!
+//! impl FromBitStream for Sample {
+//!     type Error = io::Error;
+//!
+//!     fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+//!         Ok(Sample{left: r.read_signed(12)?, right: r.read_signed(12)?})
+//!     }
+//! }
+//!
+//! impl ToBitStream for Sample {
+//!     type Error = io::Error;
+//!
+//!     fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+//!         w.write_signed(12, self.left)?;
+//!         w.write_signed(12, self.right)
+//!     }
+//! }
+//!
+//! assert_roundtrip(&Sample{left: -2048, right: 2047});
+//! ```
+
+#![warn(missing_docs)]
+
+use core::fmt::Debug;
+use io::{self, Cursor};
+
+use proptest::prelude::*;
+use proptest::collection::vec;
+use proptest::test_runner::TestCaseError;
+
+use super::{BigEndian, LittleEndian, Endianness, BitQueue,
+            BitReader, BitWriter, BitRead, BitWrite,
+            FromBitStream, ToBitStream,
+            Numeric, SignedNumeric};
+use trace::TraceWriter;
+
+// the number of bytes shown on either side of the first difference
+const CONTEXT_BYTES: usize = 2;
+
+// the number of fields shown on either side of the first
+// differing field of a golden stream
+const CONTEXT_FIELDS: usize = 2;
+
+// the most bits of a single field shown in a golden stream's report
+const GOLDEN_ROW_BITS: u64 = 64;
+
+/// Writes the value to a byte-aligned stream in the given endianness.
+///
+/// # Errors
+///
+/// Returns a description of any error encountered while writing.
+pub fn encode<E, T>(value: &T) -> Result<Vec<u8>, String>
+    where E: Endianness, T: ToBitStream, T::Error: Debug {
+    let mut data = Vec::new();
+    {
+        let mut writer = BitWriter::<E>::new(&mut data);
+        writer.build(value).map_err(|err| format!("write failed: {:?}", err))?;
+        writer.byte_align().map_err(|err| format!("write failed: {:?}", err))?;
+    }
+    Ok(data)
+}
+
+/// Writes the value in the given endianness, reads it back
+/// and checks that it's unchanged.
+///
+/// # Errors
+///
+/// Returns a description of the failure if writing or reading fails,
+/// if the reader doesn't consume everything the writer wrote,
+/// or if the value read doesn't match the value written.
+/// When the values differ, the description includes a bit-level diff
+/// of the value written against the value read, written again.
+pub fn roundtrip<E, T>(value: &T) -> Result<(), String>
+    where E: Endianness,
+          T: ToBitStream + FromBitStream + PartialEq + Debug,
+          <T as ToBitStream>::Error: Debug,
+          <T as FromBitStream>::Error: Debug {
+    let expected = encode::<E, T>(value)?;
+    let mut reader =
+        BitReader::<E>::new(Box::new(Cursor::new(expected.clone())));
+    let read = reader.parse::<T>()
+        .map_err(|err| format!("read failed: {:?}", err))?;
+    let written_bits = expected.len() as u64 * 8;
+    let read_bits = reader.position_in_bits();
+    if read != *value {
+        let mut message =
+            format!("value mismatch\n written: {:?}\n    read: {:?}",
+                    value, read);
+        if let Ok(actual) = encode::<E, T>(&read) {
+            if let Some(diff) = bit_diff(&expected, &actual) {
+                message.push('\n');
+                message.push_str(&diff);
+            }
+        }
+        Err(message)
+    } else if written_bits - read_bits >= 8 {
+        Err(format!("wrote {} bits but read only {}",
+                    written_bits, read_bits))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that the value round-trips in both big-endian
+/// and little-endian streams.
+///
+/// # Panics
+///
+/// Panics with a description of the failure if either round trip fails.
+pub fn assert_roundtrip<T>(value: &T)
+    where T: ToBitStream + FromBitStream + PartialEq + Debug,
+          <T as ToBitStream>::Error: Debug,
+          <T as FromBitStream>::Error: Debug {
+    if let Err(message) = roundtrip::<BigEndian, T>(value) {
+        panic!("big-endian round trip failed: {}", message);
+    }
+    if let Err(message) = roundtrip::<LittleEndian, T>(value) {
+        panic!("little-endian round trip failed: {}", message);
+    }
+}
+
+/// Compares two encoded streams and, if they differ,
+/// returns a description of where.
+///
+/// The description gives the offset of the first differing byte
+/// and shows the bytes around it in binary, most significant bit first,
+/// marking each differing bit with a `^`.
+/// Missing bytes in the shorter stream are shown as `-`.
+///
+/// # Example
+/// ```
+/// use bitstream_io::test_util::bit_diff;
+///
+/// assert_eq!(bit_diff(&[1, 2, 3], &[1, 2, 3]), None);
+/// assert_eq!(bit_diff(&[0b1011_0000], &[0b1001_0001]).unwrap(),
+///            concat!("first difference at byte 0\n",
+///                    "expected: 10110000\n",
+///                    "  actual: 10010001\n",
+///                    "            ^    ^"));
+/// ```
+pub fn bit_diff(expected: &[u8], actual: &[u8]) -> Option<String> {
+    let first = (0..expected.len().max(actual.len()))
+        .find(|&i| expected.get(i) != actual.get(i))?;
+
+    let start = first.saturating_sub(CONTEXT_BYTES);
+    let end = (first + CONTEXT_BYTES + 1).min(expected.len().max(actual.len()));
+    let mut expected_line = String::new();
+    let mut actual_line = String::new();
+    let mut marker_line = String::new();
+    for i in start..end {
+        if i > start {
+            expected_line.push(' ');
+            actual_line.push(' ');
+            marker_line.push(' ');
+        }
+        for shift in (0..8).rev() {
+            let e = expected.get(i).map(|b| (b >> shift) & 1);
+            let a = actual.get(i).map(|b| (b >> shift) & 1);
+            expected_line.push(bit_char(e));
+            actual_line.push(bit_char(a));
+            marker_line.push(if e != a {'^'} else {' '});
+        }
+    }
+
+    Some(format!("first difference at byte {}\n\
+                  expected: {}\n  \
+                    actual: {}\n          {}",
+                 first, expected_line, actual_line,
+                 marker_line.trim_end()))
+}
+
+fn bit_char(bit: Option<u8>) -> char {
+    match bit {
+        Some(0) => '0',
+        Some(_) => '1',
+        None => '-',
+    }
+}
+
+/// Runs an encoder against a stored stream of the given number of bits,
+/// checking that it writes exactly that stream.
+///
+/// The encoder writes through a `TraceWriter`,
+/// so any fields labeled with `label_next` are named in the report.
+/// Bits written to the `TraceWriter`'s inner stream aren't traced,
+/// so they should only follow the last traced field.
+///
+/// # Errors
+///
+/// Returns a description of the failure if the encoder fails,
+/// or if its output doesn't match the stored stream.
+/// A mismatch is described field by field around the first differing bit,
+/// with each field's offset, its expected and actual bits side by side
+/// in stream order, and its label,
+/// marking each differing bit with a `^`.
+/// Bits written after the last traced field are shown as `untraced`,
+/// and expected bits beyond the end of the encoder's output
+/// as `missing`.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitWrite};
+/// use bitstream_io::test_util::check_golden;
+///
+/// let result = check_golden::<BigEndian, _, _>(&[0xA6], 8, |w| {
+///     w.label_next("version");
+///     w.write(3, 0b101u8)?;
+///     w.label_next("flags");
+///     w.write(5, 0b00011u8)
+/// });
+/// assert_eq!(result.unwrap_err(),
+///            concat!("first difference at bit 5\n",
+///                    "offset  expected  actual    field\n",
+///                    "     0  101       101       version\n",
+///                    "     3  00110     00011     flags\n",
+///                    "                    ^ ^"));
+/// ```
+pub fn check_golden<E, F, X>(expected: &[u8], bits: u64, f: F) ->
+    Result<(), String>
+    where E: Endianness,
+          F: FnOnce(&mut TraceWriter<BitWriter<E>>) -> Result<(), X>,
+          X: Debug {
+    if bits > expected.len() as u64 * 8 {
+        return Err(format!("stored stream of {} bytes has fewer than {} bits",
+                           expected.len(), bits));
+    }
+    compare_golden(stream_bits::<E>(expected, bits), f)
+}
+
+/// Runs an encoder against a stored stream given as a string
+/// of `0` and `1` characters in stream order,
+/// checking that it writes exactly that stream.
+/// Whitespace and `_` characters between the bits are ignored.
+///
+/// # Errors
+///
+/// Returns a description of the failure if the string holds
+/// any other characters, if the encoder fails,
+/// or if its output doesn't match, as described by `check_golden`.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{LittleEndian, BitWrite};
+/// use bitstream_io::test_util::check_golden_bits;
+///
+/// // little-endian streams start from each byte's least significant bit
+/// assert!(check_golden_bits::<LittleEndian, _, _>("1000 0000 1", |w| {
+///     w.write(9, 0x101u16)
+/// }).is_ok());
+/// ```
+pub fn check_golden_bits<E, F, X>(expected: &str, f: F) -> Result<(), String>
+    where E: Endianness,
+          F: FnOnce(&mut TraceWriter<BitWriter<E>>) -> Result<(), X>,
+          X: Debug {
+    let mut bits = Vec::with_capacity(expected.len());
+    for c in expected.chars() {
+        match c {
+            '0' => bits.push(false),
+            '1' => bits.push(true),
+            '_' => {}
+            c if c.is_whitespace() => {}
+            c => return Err(format!("invalid character {:?} in bits", c)),
+        }
+    }
+    compare_golden(bits, f)
+}
+
+/// Checks that the encoder writes exactly the stored stream
+/// of the given number of bits.
+///
+/// # Panics
+///
+/// Panics with a description of the failure if `check_golden` fails.
+pub fn assert_golden<E, F, X>(expected: &[u8], bits: u64, f: F)
+    where E: Endianness,
+          F: FnOnce(&mut TraceWriter<BitWriter<E>>) -> Result<(), X>,
+          X: Debug {
+    if let Err(message) = check_golden(expected, bits, f) {
+        panic!("golden output mismatch: {}", message);
+    }
+}
+
+/// Checks that the encoder writes exactly the stored stream
+/// of `0` and `1` characters.
+///
+/// # Panics
+///
+/// Panics with a description of the failure
+/// if `check_golden_bits` fails.
+pub fn assert_golden_bits<E, F, X>(expected: &str, f: F)
+    where E: Endianness,
+          F: FnOnce(&mut TraceWriter<BitWriter<E>>) -> Result<(), X>,
+          X: Debug {
+    if let Err(message) = check_golden_bits(expected, f) {
+        panic!("golden output mismatch: {}", message);
+    }
+}
+
+// the first bits of a stream in stream order
+fn stream_bits<E: Endianness>(data: &[u8], bits: u64) -> Vec<bool> {
+    let mut reader = BitReader::<E, _>::from_read(data);
+    (0..bits).map_while(|_| reader.read_bit().ok()).collect()
+}
+
+fn compare_golden<E, F, X>(expected: Vec<bool>, f: F) -> Result<(), String>
+    where E: Endianness,
+          F: FnOnce(&mut TraceWriter<BitWriter<E>>) -> Result<(), X>,
+          X: Debug {
+    let mut data = Vec::new();
+    let (partial, entries) = {
+        let mut writer = TraceWriter::new(
+            BitWriter::<E>::from_parts(&mut data, BitQueue::new()));
+        f(&mut writer).map_err(|err| format!("write failed: {:?}", err))?;
+        let (writer, entries) = writer.into_log();
+        (writer.into_bitqueue(), entries)
+    };
+    // bits written to the inner writer count too
+    let written = data.len() as u64 * 8 + u64::from(partial.len());
+    BitWriter::from_parts(&mut data, partial).byte_align()
+        .map_err(|err| format!("write failed: {:?}", err))?;
+    let actual = stream_bits::<E>(&data, written);
+
+    let first = match (0..expected.len().max(actual.len()))
+        .find(|&i| expected.get(i) != actual.get(i)) {
+        Some(first) => first as u64,
+        None => return Ok(()),
+    };
+
+    // the fields written, followed by rows for any bits written
+    // after the last of them and any expected beyond the end
+    let mut rows: Vec<(u64, u64, &str)> = entries.iter()
+        .map(|entry| (entry.offset, entry.bits, entry.path.as_str()))
+        .collect();
+    let traced = rows.last().map(|&(offset, bits, _)| offset + bits)
+        .unwrap_or(0);
+    if traced < written {
+        rows.push((traced, written - traced, "untraced"));
+    }
+    if written < expected.len() as u64 {
+        rows.push((written, expected.len() as u64 - written, "missing"));
+    }
+    let shown = rows.iter()
+        .position(|&(offset, bits, _)| offset + bits > first)
+        .unwrap_or(0);
+    let rows = &rows[shown.saturating_sub(CONTEXT_FIELDS)..
+                     (shown + CONTEXT_FIELDS + 1).min(rows.len())];
+
+    let row_bits = |bits: &[bool], offset: u64, len: u64| -> String {
+        let mut s: String = (offset..offset + len.min(GOLDEN_ROW_BITS))
+            .map(|i| bit_char(bits.get(i as usize).map(|&b| u8::from(b))))
+            .collect();
+        if len > GOLDEN_ROW_BITS {
+            s.push_str("...");
+        }
+        s
+    };
+    let width = rows.iter()
+        .map(|&(_, bits, _)| bits.min(GOLDEN_ROW_BITS) as usize +
+             if bits > GOLDEN_ROW_BITS {3} else {0})
+        .max().unwrap_or(0).max("expected".len());
+
+    let mut report = format!("first difference at bit {}\n\
+                              offset  {:w$}  {:w$}  field",
+                             first, "expected", "actual", w = width);
+    for &(offset, bits, label) in rows {
+        let expected_bits = row_bits(&expected, offset, bits);
+        let actual_bits = row_bits(&actual, offset, bits);
+        report.push_str(&format!("\n{:>6}  {:w$}  {:w$}  {}",
+                                 offset, expected_bits, actual_bits, label,
+                                 w = width));
+        let markers: String = expected_bits.chars().zip(actual_bits.chars())
+            .map(|(e, a)| if e != a {'^'} else {' '})
+            .collect();
+        if markers.contains('^') {
+            report.push_str(&format!("\n{:w$}{}",
+                                     "", markers.trim_end(),
+                                     w = 6 + 2 + width + 2));
+        }
+    }
+    Err(report)
+}
+
+/// A single value to write to a stream and read back.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Field {
+    /// An unsigned value in the given number of bits
+    U8(u32, u8),
+    /// An unsigned value in the given number of bits
+    U16(u32, u16),
+    /// An unsigned value in the given number of bits
+    U32(u32, u32),
+    /// An unsigned value in the given number of bits
+    U64(u32, u64),
+    /// A twos-complement signed value in the given number of bits
+    I8(u32, i8),
+    /// A twos-complement signed value in the given number of bits
+    I16(u32, i16),
+    /// A twos-complement signed value in the given number of bits
+    I32(u32, i32),
+    /// A twos-complement signed value in the given number of bits
+    I64(u32, i64),
+    /// A unary value terminated by a 0 bit
+    Unary0(u32),
+    /// A unary value terminated by a 1 bit
+    Unary1(u32),
+}
+
+impl Field {
+    /// Returns the field's size in the stream, in bits.
+    pub fn bits(&self) -> u64 {
+        match *self {
+            Field::U8(bits, _) | Field::U16(bits, _) |
+            Field::U32(bits, _) | Field::U64(bits, _) |
+            Field::I8(bits, _) | Field::I16(bits, _) |
+            Field::I32(bits, _) | Field::I64(bits, _) => u64::from(bits),
+            Field::Unary0(value) | Field::Unary1(value) => {
+                u64::from(value) + 1
+            }
+        }
+    }
+
+    /// Writes the field to the given stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the stream.
+    pub fn write<W: BitWrite + ?Sized>(&self, w: &mut W) ->
+        Result<(), io::Error> {
+        match *self {
+            Field::U8(bits, value) => w.write(bits, value),
+            Field::U16(bits, value) => w.write(bits, value),
+            Field::U32(bits, value) => w.write(bits, value),
+            Field::U64(bits, value) => w.write(bits, value),
+            Field::I8(bits, value) => w.write_signed(bits, value),
+            Field::I16(bits, value) => w.write_signed(bits, value),
+            Field::I32(bits, value) => w.write_signed(bits, value),
+            Field::I64(bits, value) => w.write_signed(bits, value),
+            Field::Unary0(value) => w.write_unary0(value),
+            Field::Unary1(value) => w.write_unary1(value),
+        }
+    }
+
+    /// Reads a field of the same kind and width from the given stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the stream.
+    pub fn read_same<R: BitRead + ?Sized>(&self, r: &mut R) ->
+        Result<Field, io::Error> {
+        Ok(match *self {
+            Field::U8(bits, _) => Field::U8(bits, r.read(bits)?),
+            Field::U16(bits, _) => Field::U16(bits, r.read(bits)?),
+            Field::U32(bits, _) => Field::U32(bits, r.read(bits)?),
+            Field::U64(bits, _) => Field::U64(bits, r.read(bits)?),
+            Field::I8(bits, _) => Field::I8(bits, r.read_signed(bits)?),
+            Field::I16(bits, _) => Field::I16(bits, r.read_signed(bits)?),
+            Field::I32(bits, _) => Field::I32(bits, r.read_signed(bits)?),
+            Field::I64(bits, _) => Field::I64(bits, r.read_signed(bits)?),
+            Field::Unary0(_) => Field::Unary0(r.read_unary0()?),
+            Field::Unary1(_) => Field::Unary1(r.read_unary1()?),
+        })
+    }
+}
+
+// widths from `min` to the type's size, favoring the extremes
+fn width<N: Numeric>(min: u32) -> impl Strategy<Value = u32> {
+    prop_oneof![Just(min), Just(N::bits_size()), min..=N::bits_size()]
+}
+
+// values of a type, favoring all 0 and all 1 bits
+fn value<N: Numeric + Arbitrary>() -> impl Strategy<Value = N> {
+    prop_oneof![Just(N::default()), Just(!N::default()), any::<N>()]
+}
+
+fn unsigned<N, F>(field: F) -> impl Strategy<Value = Field>
+    where N: Numeric + Arbitrary, F: Fn(u32, N) -> Field {
+    (width::<N>(0), value::<N>())
+        .prop_map(move |(bits, value)| field(bits, value.truncate(bits)))
+}
+
+fn signed<N, F>(field: F) -> impl Strategy<Value = Field>
+    where N: SignedNumeric + Arbitrary, F: Fn(u32, N) -> Field {
+    (width::<N>(1), value::<N>())
+        .prop_map(move |(bits, value)| field(bits, value.sign_extend(bits)))
+}
+
+/// Generates any single field.
+///
+/// Widths of 0 bits (for unsigned fields), 1 bit
+/// and the full size of the field's type are favored,
+/// as are values of all 0 or all 1 bits.
+pub fn field() -> impl Strategy<Value = Field> {
+    prop_oneof![
+        unsigned(Field::U8),
+        unsigned(Field::U16),
+        unsigned(Field::U32),
+        unsigned(Field::U64),
+        signed(Field::I8),
+        signed(Field::I16),
+        signed(Field::I32),
+        signed(Field::I64),
+        (0..80u32).prop_map(Field::Unary0),
+        (0..80u32).prop_map(Field::Unary1),
+    ]
+}
+
+/// Generates sequences of up to 32 fields.
+pub fn fields() -> impl Strategy<Value = Vec<Field>> {
+    vec(field(), 0..=32)
+}
+
+/// Writes the fields after `offset` bits of padding
+/// in the given endianness, reads them back
+/// and checks that they're unchanged.
+///
+/// # Errors
+///
+/// Fails with a description of the problem if writing or reading fails,
+/// if any field reads back differently, or if the fields
+/// don't occupy the expected number of bits.
+pub fn check_fields<E: Endianness>(offset: u32, fields: &[Field]) ->
+    Result<(), TestCaseError> {
+    let offset = offset % 8;
+    let mut data = Vec::new();
+    {
+        let mut writer = BitWriter::<E>::new(&mut data);
+        writer.write(offset, 0b0101_0101u8.truncate(offset))
+            .map_err(|err| TestCaseError::fail(format!("{:?}", err)))?;
+        for (i, field) in fields.iter().enumerate() {
+            field.write(&mut writer).map_err(|err| {
+                TestCaseError::fail(format!("writing field {} ({:?}): {:?}",
+                                            i, field, err))
+            })?;
+        }
+        writer.byte_align()
+            .map_err(|err| TestCaseError::fail(format!("{:?}", err)))?;
+    }
+
+    let total = u64::from(offset) +
+        fields.iter().map(|field| field.bits()).sum::<u64>();
+    if data.len() as u64 != total.div_ceil(8) {
+        return Err(TestCaseError::fail(
+            format!("wrote {} bytes for {} bits", data.len(), total)));
+    }
+
+    let mut reader = BitReader::<E, _>::from_read(Cursor::new(&data));
+    reader.skip(offset)
+        .map_err(|err| TestCaseError::fail(format!("{:?}", err)))?;
+    for (i, field) in fields.iter().enumerate() {
+        let read = field.read_same(&mut reader).map_err(|err| {
+            TestCaseError::fail(format!("reading field {} ({:?}): {:?}",
+                                        i, field, err))
+        })?;
+        if read != *field {
+            return Err(TestCaseError::fail(
+                format!("field {} written as {:?} but read as {:?}",
+                        i, field, read)));
+        }
+    }
+    if reader.position_in_bits() != total {
+        return Err(TestCaseError::fail(
+            format!("fields took {} bits to read but should take {}",
+                    reader.position_in_bits(), total)));
+    }
+    Ok(())
+}
+
+/// Checks that the fields round-trip in both big-endian
+/// and little-endian streams, starting at every bit offset
+/// from 0 to 7.
+///
+/// # Errors
+///
+/// Fails with a description of the first failure.
+///
+/// # Example
+/// ```
+/// extern crate proptest;
+/// extern crate bitstream_io;
+/// use proptest::test_runner::TestRunner;
+/// use bitstream_io::test_util::{check_fields_roundtrip, fields};
+///
+/// # fn main() {
+/// let mut runner = TestRunner::default();
+/// runner.run(&fields(), |fields| check_fields_roundtrip(&fields)).unwrap();
+/// # }
+/// ```
+pub fn check_fields_roundtrip(fields: &[Field]) -> Result<(), TestCaseError> {
+    for offset in 0..8 {
+        check_fields::<BigEndian>(offset, fields).map_err(|err| {
+            TestCaseError::fail(format!("big-endian, offset {}: {}",
+                                        offset, err))
+        })?;
+        check_fields::<LittleEndian>(offset, fields).map_err(|err| {
+            TestCaseError::fail(format!("little-endian, offset {}: {}",
+                                        offset, err))
+        })?;
+    }
+    Ok(())
+}
+
+/// Passes reads and writes through in pieces of at most 1, 2 or 3 bytes,
+/// in rotation, to exercise the handling of short transfers.
+///
+/// # Example
+/// ```
+/// use std::io::Read;
+/// use bitstream_io::test_util::Dribble;
+///
+/// let mut reader = Dribble::new(&[1, 2, 3, 4, 5, 6, 7][..]);
+/// let mut buf = [0; 8];
+/// assert_eq!(reader.read(&mut buf).unwrap(), 1);
+/// assert_eq!(reader.read(&mut buf).unwrap(), 2);
+/// assert_eq!(reader.read(&mut buf).unwrap(), 3);
+/// assert_eq!(reader.read(&mut buf).unwrap(), 1);
+/// ```
+pub struct Dribble<T> {
+    inner: T,
+    calls: usize,
+}
+
+impl<T> Dribble<T> {
+    /// Wraps the reader or writer.
+    pub fn new(inner: T) -> Dribble<T> {
+        Dribble{inner, calls: 0}
+    }
+
+    /// Returns the wrapped reader or writer.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    // the most bytes to pass through on the next call
+    fn next_size(&mut self) -> usize {
+        self.calls += 1;
+        (self.calls - 1) % 3 + 1
+    }
+}
+
+impl<T: io::Read> io::Read for Dribble<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.next_size().min(buf.len());
+        self.inner.read(&mut buf[..size])
+    }
+}
+
+impl<T: io::Write> io::Write for Dribble<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let size = self.next_size().min(buf.len());
+        self.inner.write(&buf[..size])
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Fails every other read or write with an error of the given kind,
+/// such as `Interrupted` or `WouldBlock`,
+/// passing the calls in between through.
+/// The first call fails.
+///
+/// # Example
+/// ```
+/// use std::io::{self, Read};
+/// use bitstream_io::test_util::Flaky;
+///
+/// let mut reader = Flaky::new(&[1, 2, 3][..], io::ErrorKind::WouldBlock);
+/// let mut buf = [0; 3];
+/// assert_eq!(reader.read(&mut buf).unwrap_err().kind(),
+///            io::ErrorKind::WouldBlock);
+/// assert_eq!(reader.read(&mut buf).unwrap(), 3);
+///
+/// // read_exact retries interrupted reads
+/// let mut reader = Flaky::new(&[1, 2, 3][..], io::ErrorKind::Interrupted);
+/// reader.read_exact(&mut buf).unwrap();
+/// ```
+pub struct Flaky<T> {
+    inner: T,
+    kind: io::ErrorKind,
+    fail_next: bool,
+}
+
+impl<T> Flaky<T> {
+    /// Wraps the reader or writer, failing with errors of the given kind.
+    pub fn new(inner: T, kind: io::ErrorKind) -> Flaky<T> {
+        Flaky{inner, kind, fail_next: true}
+    }
+
+    /// Returns the wrapped reader or writer.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    // fails if this call is one which should
+    fn check(&mut self) -> io::Result<()> {
+        self.fail_next = !self.fail_next;
+        if self.fail_next {
+            Ok(())
+        } else {
+            Err(io::Error::new(self.kind, "injected failure"))
+        }
+    }
+}
+
+impl<T: io::Read> io::Read for Flaky<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.check()?;
+        self.inner.read(buf)
+    }
+}
+
+impl<T: io::Write> io::Write for Flaky<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check()?;
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Passes through up to the given number of bytes,
+/// then fails every read or write with an error of the given kind.
+///
+/// # Example
+/// ```
+/// use std::io::{self, Write};
+/// use bitstream_io::{BigEndian, BitWriter};
+/// use bitstream_io::test_util::FailAfter;
+///
+/// let mut sink = FailAfter::new(Vec::new(), 1, io::ErrorKind::BrokenPipe);
+/// {
+///     let mut writer = BitWriter::<BigEndian>::new(&mut sink);
+///     writer.write(8, 0xFFu8).unwrap();
+///     assert_eq!(writer.write(8, 0u8).unwrap_err().kind(),
+///                io::ErrorKind::BrokenPipe);
+/// }
+/// assert_eq!(sink.into_inner(), [0xFF]);
+/// ```
+pub struct FailAfter<T> {
+    inner: T,
+    remaining: u64,
+    kind: io::ErrorKind,
+}
+
+impl<T> FailAfter<T> {
+    /// Wraps the reader or writer, failing with errors of the given kind
+    /// once `bytes` bytes have passed through.
+    pub fn new(inner: T, bytes: u64, kind: io::ErrorKind) -> FailAfter<T> {
+        FailAfter{inner, remaining: bytes, kind}
+    }
+
+    /// Returns the wrapped reader or writer.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    // the most bytes to pass through on this call,
+    // which fails once the limit is reached
+    fn allowance(&self, len: usize) -> io::Result<usize> {
+        if self.remaining == 0 && len > 0 {
+            Err(io::Error::new(self.kind, "injected failure"))
+        } else {
+            Ok(len.min(self.remaining.min(usize::MAX as u64) as usize))
+        }
+    }
+}
+
+impl<T: io::Read> io::Read for FailAfter<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.allowance(buf.len())?;
+        let read = self.inner.read(&mut buf[..size])?;
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+impl<T: io::Write> io::Write for FailAfter<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let size = self.allowance(buf.len())?;
+        let written = self.inner.write(&buf[..size])?;
+        self.remaining -= written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Recording each field as it's written.
+//!
+//! This module requires the `std` feature.
+//!
+//! A `TraceWriter` wraps a `BitWrite` stream and records
+//! every field written through it, along with its bit offset,
+//! its width and any label given to it beforehand,
+//! as the same `inspect::Entry` values that `inspect` returns
+//! when reading.
+//! The log may then be dumped as text, one field per line,
+//! or as JSON, for comparing an encoder's output
+//! field by field against a specification's table.
+//!
+//! Fields without a label are named after how they were written,
+//! such as `unsigned` or `bytes`,
+//! and any bits written by `byte_align` are logged as `padding`.
+//! Values wider than 64 bits are logged as bytes,
+//! most significant first.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitWrite, BitWriter};
+//! use bitstream_io::trace::TraceWriter;
+//!
+//! let mut data = Vec::new();
+//! let mut writer = TraceWriter::new(
+//!     BitWriter::<BigEndian, _>::from_write(&mut data));
+//! writer.label_next("sync");
+//! writer.write(12, 0xFFFu16).unwrap();
+//! writer.write_signed(3, -2i8).unwrap();
+//! writer.byte_align().unwrap();
+//! assert_eq!(writer.to_text(),
+//!            "0: sync (12 bits) = 4095\n\
+//!             12: signed (3 bits) = -2\n\
+//!             15: padding (1 bit) = 0");
+//! assert_eq!(writer.to_json(),
+//!            "[{\"offset\":0,\"bits\":12,\"label\":\"sync\",\
+//!               \"value\":4095},\
+//!             {\"offset\":12,\"bits\":3,\"label\":\"signed\",\
+//!               \"value\":-2},\
+//!             {\"offset\":15,\"bits\":1,\"label\":\"padding\",\
+//!               \"value\":0}]");
+//! ```
+
+#![warn(missing_docs)]
+
+use std::fmt::Write;
+
+use super::{BitWrite, Numeric, SignedNumeric};
+use inspect::{Entry, FieldValue};
+use tap::{Observer, Tap};
+
+/// Records each field passing through a stream.
+#[derive(Clone, Debug, Default)]
+pub struct Trace {
+    label: Option<&'static str>,
+    entries: Vec<Entry>,
+}
+
+impl Trace {
+    /// Returns every field recorded so far, in stream order.
+    #[inline]
+    pub fn entries(&self) -> &[Entry] {&self.entries}
+
+    /// Returns every field recorded.
+    #[inline]
+    pub fn into_entries(self) -> Vec<Entry> {self.entries}
+
+    // logs a field under the pending label,
+    // or the given name if there is none
+    fn log(&mut self,
+           name: &'static str,
+           offset: u64,
+           bits: u64,
+           value: FieldValue) {
+        let label = self.label.take().unwrap_or(name);
+        self.entries.push(Entry{path: label.to_string(),
+                                offset,
+                                bits,
+                                value});
+    }
+}
+
+impl Observer for Trace {
+    fn bit(&mut self, offset: u64, bit: bool) {
+        self.log("bit", offset, 1, FieldValue::Flag(bit))
+    }
+
+    fn unsigned<U>(&mut self, offset: u64, bits: u32, value: U)
+        where U: Numeric {
+        let value = if bits <= 64 {
+            FieldValue::Unsigned(low_bits(value, bits))
+        } else {
+            FieldValue::Bytes(wide_bytes(value, bits))
+        };
+        self.log("unsigned", offset, u64::from(bits), value)
+    }
+
+    fn signed<S>(&mut self, offset: u64, bits: u32, value: S)
+        where S: SignedNumeric {
+        let value = if bits == 0 {
+            FieldValue::Signed(0)
+        } else if bits <= 64 {
+            // sign-extends the twos-complement bits
+            let shift = 64 - bits;
+            FieldValue::Signed(((low_bits(value, bits) << shift) as i64)
+                               >> shift)
+        } else {
+            FieldValue::Bytes(wide_bytes(value, bits))
+        };
+        self.log("signed", offset, u64::from(bits), value)
+    }
+
+    fn bytes(&mut self, offset: u64, buf: &[u8]) {
+        self.log("bytes", offset, buf.len() as u64 * 8,
+                 FieldValue::Bytes(buf.to_vec()))
+    }
+
+    fn unary0(&mut self, offset: u64, value: u32) {
+        self.log("unary0", offset, u64::from(value) + 1,
+                 FieldValue::Unsigned(u64::from(value)))
+    }
+
+    fn unary1(&mut self, offset: u64, value: u32) {
+        self.log("unary1", offset, u64::from(value) + 1,
+                 FieldValue::Unsigned(u64::from(value)))
+    }
+
+    fn padding(&mut self, offset: u64, bits: &[bool]) {
+        self.log("padding", offset, bits.len() as u64,
+                 FieldValue::Unsigned(0))
+    }
+}
+
+/// Wraps a bit stream, recording each field written through it.
+///
+/// Offsets count from where the stream was when it was wrapped.
+pub type TraceWriter<W> = Tap<W, Trace>;
+
+impl<W: BitWrite> Tap<W, Trace> {
+    /// Wraps the stream, starting at bit offset 0 with an empty log.
+    pub fn new(inner: W) -> TraceWriter<W> {
+        Tap::with_observer(inner, Trace::default())
+    }
+
+    /// Labels the next field written.
+    #[inline]
+    pub fn label_next(&mut self, label: &'static str) {
+        self.observer_mut().label = Some(label);
+    }
+
+    /// Returns every field written so far, in stream order.
+    #[inline]
+    pub fn entries(&self) -> &[Entry] {self.observer().entries()}
+
+    /// Removes every field from the log,
+    /// without changing the current offset.
+    #[inline]
+    pub fn clear(&mut self) {self.observer_mut().entries.clear()}
+
+    /// Returns the log with one field per line,
+    /// as each field is displayed.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (i, entry) in self.entries().iter().enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+            let _ = write!(text, "{}", entry);
+        }
+        text
+    }
+
+    /// Returns the log as a JSON array of objects,
+    /// each with the field's `offset`, `bits`, `label` and `value`.
+    /// Flags are given as booleans and bytes as a string of hex digits.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("[");
+        for (i, entry) in self.entries().iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let _ = write!(json, "{{\"offset\":{},\"bits\":{},\"label\":",
+                           entry.offset, entry.bits);
+            push_json_string(&mut json, &entry.path);
+            json.push_str(",\"value\":");
+            match entry.value {
+                FieldValue::Bytes(_) => {
+                    let _ = write!(json, "\"{}\"", entry.value);
+                }
+                _ => {
+                    let _ = write!(json, "{}", entry.value);
+                }
+            }
+            json.push('}');
+        }
+        json.push(']');
+        json
+    }
+
+    /// Returns the wrapped stream and the log.
+    #[inline]
+    pub fn into_log(self) -> (W, Vec<Entry>) {
+        let (inner, trace) = self.into_parts();
+        (inner, trace.into_entries())
+    }
+}
+
+// the lowest bits of a value, up to 64 of them
+fn low_bits<N: Numeric>(value: N, bits: u32) -> u64 {
+    let value = (0..bits.div_ceil(8)).fold(0, |acc, byte| {
+        acc | u64::from((value >> (byte * 8)).to_u8()) << (byte * 8)
+    });
+    if bits < 64 {value & ((1 << bits) - 1)} else {value}
+}
+
+// the bytes holding the lowest bits of a value,
+// most significant first
+fn wide_bytes<N: Numeric>(value: N, bits: u32) -> Vec<u8> {
+    let mut bytes: Vec<u8> = (0..bits.div_ceil(8)).rev()
+        .map(|byte| (value >> (byte * 8)).to_u8())
+        .collect();
+    if !bits.is_multiple_of(8) {
+        bytes[0] &= (1 << (bits % 8)) - 1;
+    }
+    bytes
+}
+
+fn push_json_string(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
@@ -0,0 +1,184 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Converting whole streams between bit-packing conventions.
+//!
+//! Tools disagree on how a stream's bits are packed into bytes.
+//! A capture stored most significant bit first, as a big-endian
+//! `BitWriter` packs it, holds the same bits as one stored
+//! least significant bit first, as a little-endian `BitWriter`
+//! packs it, but with the bits of every byte reversed.
+//! Some tools also store a stream as words of several bytes,
+//! such as 32-bit words with their bytes in the opposite order.
+//!
+//! A `Transcoder` repacks a stream from one convention to another,
+//! reversing the bits within each byte, the bytes within each word,
+//! or both, as it copies from any reader to any writer.
+//! The bits themselves stay in the same order, so a reader
+//! of the new convention reads what a reader of the old one would.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+//! use bitstream_io::transcode::Transcoder;
+//!
+//! let mut msb_first = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian, _>::from_write(&mut msb_first);
+//!     writer.write_unary0(3).unwrap();
+//!     writer.write_unary1(9).unwrap();
+//!     writer.byte_align().unwrap();
+//! }
+//! assert_eq!(msb_first, [0b1110_0000, 0b0000_0100]);
+//!
+//! let mut lsb_first = Vec::new();
+//! Transcoder::between::<BigEndian, LittleEndian>()
+//!     .transcode(&mut &msb_first[..], &mut lsb_first)
+//!     .unwrap();
+//! assert_eq!(lsb_first, [0b0000_0111, 0b0010_0000]);
+//!
+//! let mut reader = BitReader::<LittleEndian, _>::from_read(&lsb_first[..]);
+//! assert_eq!(reader.read_unary0().unwrap(), 3);
+//! assert_eq!(reader.read_unary1().unwrap(), 9);
+//! ```
+
+#![warn(missing_docs)]
+
+use io;
+
+use super::{is_little_endian, Endianness};
+use tables::REVERSED;
+
+// the most bytes transcoded at a time
+const BUFFER_BYTES: usize = 4096;
+
+/// A conversion from one bit-packing convention to another.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Transcoder {
+    /// Whether the order of the bits within each byte is reversed,
+    /// converting between streams packed most significant bit first
+    /// and least significant bit first
+    pub reverse_bits: bool,
+    /// The number of bytes in each word whose byte order is reversed,
+    /// from 1, which leaves bytes in order, to 4096
+    pub word_bytes: usize,
+}
+
+impl Transcoder {
+    /// The conversion from streams packed as a `BitWriter`
+    /// of endianness `F` packs them to streams packed as one
+    /// of endianness `T` does.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, LittleEndian};
+    /// use bitstream_io::transcode::Transcoder;
+    /// assert!(Transcoder::between::<BigEndian, LittleEndian>().reverse_bits);
+    /// assert!(!Transcoder::between::<BigEndian, BigEndian>().reverse_bits);
+    /// ```
+    pub fn between<F: Endianness, T: Endianness>() -> Transcoder {
+        Transcoder{reverse_bits: is_little_endian::<F>() !=
+                                 is_little_endian::<T>(),
+                   word_bytes: 1}
+    }
+
+    /// Converts a buffer in place,
+    /// which must hold a whole number of words.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidInput` error if the word size is invalid,
+    /// or an `InvalidData` error if the buffer ends
+    /// part way through a word, in which case it's left unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::transcode::Transcoder;
+    /// // 16-bit words stored least significant byte first, read MSB first
+    /// let mut data = [0x34, 0x12, 0x78, 0x56];
+    /// Transcoder{reverse_bits: false, word_bytes: 2}
+    ///     .transcode_slice(&mut data)
+    ///     .unwrap();
+    /// assert_eq!(data, [0x12, 0x34, 0x56, 0x78]);
+    /// ```
+    pub fn transcode_slice(&self, buf: &mut [u8]) -> Result<(), io::Error> {
+        self.check()?;
+        if !buf.len().is_multiple_of(self.word_bytes) {
+            return Err(partial_word());
+        }
+        if self.word_bytes > 1 {
+            for word in buf.chunks_exact_mut(self.word_bytes) {
+                word.reverse();
+            }
+        }
+        if self.reverse_bits {
+            for byte in buf.iter_mut() {
+                *byte = REVERSED[usize::from(*byte)];
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts the whole of a stream from the reader,
+    /// writing it to the writer,
+    /// and returns the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from either stream.
+    /// Returns an `InvalidInput` error if the word size is invalid,
+    /// or an `InvalidData` error if the stream ends
+    /// part way through a word, after every whole word is written.
+    pub fn transcode<R, W>(&self, reader: &mut R, writer: &mut W) ->
+        Result<u64, io::Error>
+        where R: io::Read + ?Sized, W: io::Write + ?Sized {
+        self.check()?;
+        // only whole words are converted, with any partial word
+        // kept at the start of the buffer until the rest is read
+        let mut buf = [0; BUFFER_BYTES];
+        let usable = BUFFER_BYTES - BUFFER_BYTES % self.word_bytes;
+        let mut filled = 0;
+        let mut written = 0;
+        loop {
+            match reader.read(&mut buf[filled..usable]) {
+                Ok(0) => break,
+                Ok(read) => filled += read,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {
+                    continue;
+                }
+                Err(err) => return Err(err),
+            }
+            let whole = filled - filled % self.word_bytes;
+            self.transcode_slice(&mut buf[..whole])?;
+            writer.write_all(&buf[..whole])?;
+            buf.copy_within(whole..filled, 0);
+            filled -= whole;
+            written += whole as u64;
+        }
+        if filled == 0 {
+            Ok(written)
+        } else {
+            Err(partial_word())
+        }
+    }
+
+    fn check(&self) -> Result<(), io::Error> {
+        if (1..=BUFFER_BYTES).contains(&self.word_bytes) {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidInput,
+                               "invalid word size"))
+        }
+    }
+}
+
+fn partial_word() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData,
+                   "stream ends part way through a word")
+}
@@ -0,0 +1,300 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Unpacking long runs of fields which all have the same width.
+//!
+//! Raw sensor and video streams are often nothing more than
+//! packed samples of a fixed width, such as 10-bit video
+//! or 12-bit ADC data.
+//! Rather than reading them one at a time through a `BitReader`,
+//! the functions here unpack a whole buffer of them at once
+//! into `u16` or `u32` values.
+//! The `pack` module does the reverse.
+//!
+//! Fields are unpacked with a scalar loop that works anywhere.
+//! With the `simd` feature, x86-64 processors supporting AVX2
+//! unpack 8 fields at a time instead,
+//! as detected when the function is called.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::BigEndian;
+//! use bitstream_io::unpack::unpack_u16;
+//!
+//! // four 12-bit samples
+//! let data = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC];
+//! let mut samples = [0; 4];
+//! unpack_u16::<BigEndian>(&data, 12, &mut samples).unwrap();
+//! assert_eq!(samples, [0x123, 0x456, 0x789, 0xABC]);
+//! ```
+
+#![warn(missing_docs)]
+
+use io;
+use super::{is_little_endian, Endianness};
+
+/// Unpacks consecutive fields of the given width, from 0 to 16 bits,
+/// from the start of the input until the output is filled.
+///
+/// # Errors
+///
+/// Returns an `InvalidInput` error if `bits` is larger than 16,
+/// or an `UnexpectedEof` error if the input is too short
+/// to fill the output.
+///
+/// # Example
+/// ```
+/// use bitstream_io::LittleEndian;
+/// use bitstream_io::unpack::unpack_u16;
+///
+/// let data = [0b1001_0110, 0b0111_0101];
+/// let mut fields = [0; 3];
+/// unpack_u16::<LittleEndian>(&data, 5, &mut fields).unwrap();
+/// assert_eq!(fields, [0b10110, 0b01100, 0b11101]);
+/// ```
+pub fn unpack_u16<E: Endianness>(input: &[u8],
+                                 bits: u32,
+                                 output: &mut [u16]) -> io::Result<()> {
+    check_lengths(input, bits, 16, output.len())?;
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    let unpacked = if is_x86_feature_detected!("avx2") {
+        // only whole fields are read, as checked above
+        unsafe {avx2::unpack_u16(input, bits, output, is_little_endian::<E>())}
+    } else {
+        0
+    };
+    #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+    let unpacked = 0;
+
+    for (i, field) in output.iter_mut().enumerate().skip(unpacked) {
+        let position = i as u64 * u64::from(bits);
+        let window = E::from_bytes::<u32>(
+            window(input, (position / 8) as usize));
+        *field = extract(u64::from(window), 32,
+                         (position % 8) as u32, bits,
+                         is_little_endian::<E>()) as u16;
+    }
+    Ok(())
+}
+
+/// Unpacks consecutive fields of the given width, from 0 to 32 bits,
+/// from the start of the input until the output is filled.
+///
+/// # Errors
+///
+/// Returns an `InvalidInput` error if `bits` is larger than 32,
+/// or an `UnexpectedEof` error if the input is too short
+/// to fill the output.
+///
+/// # Example
+/// ```
+/// use bitstream_io::BigEndian;
+/// use bitstream_io::unpack::unpack_u32;
+///
+/// // three 20-bit fields
+/// let data = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0];
+/// let mut fields = [0; 3];
+/// unpack_u32::<BigEndian>(&data, 20, &mut fields).unwrap();
+/// assert_eq!(fields, [0x12345, 0x6789A, 0xBCDEF]);
+/// ```
+pub fn unpack_u32<E: Endianness>(input: &[u8],
+                                 bits: u32,
+                                 output: &mut [u32]) -> io::Result<()> {
+    check_lengths(input, bits, 32, output.len())?;
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    let unpacked = if is_x86_feature_detected!("avx2") {
+        // only whole fields are read, as checked above
+        unsafe {avx2::unpack_u32(input, bits, output, is_little_endian::<E>())}
+    } else {
+        0
+    };
+    #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+    let unpacked = 0;
+
+    for (i, field) in output.iter_mut().enumerate().skip(unpacked) {
+        let position = i as u64 * u64::from(bits);
+        let window = E::from_bytes::<u64>(
+            window(input, (position / 8) as usize));
+        *field = extract(window, 64,
+                         (position % 8) as u32, bits,
+                         is_little_endian::<E>()) as u32;
+    }
+    Ok(())
+}
+
+// ensures the width fits the output type
+// and the input holds all the fields to be unpacked
+fn check_lengths(input: &[u8],
+                 bits: u32,
+                 max_bits: u32,
+                 fields: usize) -> io::Result<()> {
+    if bits > max_bits {
+        Err(io::Error::new(io::ErrorKind::InvalidInput,
+                           "excessive bits for type read"))
+    } else if fields as u64 * u64::from(bits) > input.len() as u64 * 8 {
+        Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                           "input too short for fields"))
+    } else {
+        Ok(())
+    }
+}
+
+// returns the bytes starting at the given position,
+// padded with 0s past the end of the input
+#[inline]
+fn window<B: Default + AsMut<[u8]>>(input: &[u8], start: usize) -> B {
+    let mut bytes = B::default();
+    {
+        let bytes = bytes.as_mut();
+        let end = start + bytes.len();
+        if end <= input.len() {
+            bytes.copy_from_slice(&input[start..end]);
+        } else {
+            let available = input.len().saturating_sub(start);
+            bytes[0..available].copy_from_slice(&input[start..]);
+        }
+    }
+    bytes
+}
+
+// extracts a field of the given width from a window of the given width,
+// after skipping the given number of bits
+#[inline(always)]
+fn extract(window: u64,
+           window_bits: u32,
+           skip: u32,
+           bits: u32,
+           little: bool) -> u64 {
+    if bits == 0 {
+        0
+    } else if little {
+        (window >> skip) & (!0 >> (64 - bits))
+    } else {
+        (window << (64 - window_bits + skip)) >> (64 - bits)
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod avx2 {
+    use core::arch::x86_64::*;
+
+    /*each field is gathered as the 32- or 64-bit word at the byte
+      it starts in, and shifted and masked into place in every lane at once,
+      for as long as those words lie entirely within the input*/
+
+    // unpacks fields of up to 16 bits 8 at a time,
+    // returning the number of fields unpacked
+    //
+    // safety: the processor must support AVX2
+    // and the input must hold every field of the output
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn unpack_u16(input: &[u8],
+                                    bits: u32,
+                                    output: &mut [u16],
+                                    little: bool) -> usize {
+        if bits == 0 {
+            return 0;
+        }
+        let bits = bits as i32;
+        let lanes = _mm256_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7);
+        let widths = _mm256_mullo_epi32(lanes, _mm256_set1_epi32(bits));
+        let swap = _mm256_setr_epi8(3, 2, 1, 0, 7, 6, 5, 4,
+                                    11, 10, 9, 8, 15, 14, 13, 12,
+                                    3, 2, 1, 0, 7, 6, 5, 4,
+                                    11, 10, 9, 8, 15, 14, 13, 12);
+        let mask = _mm256_set1_epi32((1 << bits) - 1);
+        let drop = _mm_cvtsi32_si128(32 - bits);
+        let sevens = _mm256_set1_epi32(7);
+
+        let mut unpacked = 0;
+        while unpacked + 8 <= output.len() {
+            // the last lane's word must lie within the input
+            let first = unpacked as u64 * bits as u64;
+            let last_byte = (first + 7 * bits as u64) / 8;
+            if last_byte + 4 > input.len() as u64 ||
+                first + 7 * bits as u64 > i32::MAX as u64 {
+                break;
+            }
+            let positions = _mm256_add_epi32(_mm256_set1_epi32(first as i32),
+                                             widths);
+            let bytes = _mm256_srli_epi32::<3>(positions);
+            let skips = _mm256_and_si256(positions, sevens);
+            let words = _mm256_i32gather_epi32::<1>(
+                input.as_ptr() as *const i32, bytes);
+            let fields = if little {
+                _mm256_and_si256(_mm256_srlv_epi32(words, skips), mask)
+            } else {
+                let words = _mm256_shuffle_epi8(words, swap);
+                _mm256_srl_epi32(_mm256_sllv_epi32(words, skips), drop)
+            };
+            // narrow each 32-bit lane to 16 bits, in order
+            let packed = _mm256_packus_epi32(fields, fields);
+            let packed = _mm256_permute4x64_epi64::<0b1000>(packed);
+            _mm_storeu_si128(
+                output[unpacked..].as_mut_ptr() as *mut __m128i,
+                _mm256_castsi256_si128(packed));
+            unpacked += 8;
+        }
+        unpacked
+    }
+
+    // unpacks fields of up to 32 bits 4 at a time,
+    // returning the number of fields unpacked
+    //
+    // safety: the processor must support AVX2
+    // and the input must hold every field of the output
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn unpack_u32(input: &[u8],
+                                    bits: u32,
+                                    output: &mut [u32],
+                                    little: bool) -> usize {
+        if bits == 0 {
+            return 0;
+        }
+        let lanes = _mm256_setr_epi64x(0, 1, 2, 3);
+        let widths = _mm256_mul_epu32(lanes, _mm256_set1_epi64x(bits as i64));
+        let swap = _mm256_setr_epi8(7, 6, 5, 4, 3, 2, 1, 0,
+                                    15, 14, 13, 12, 11, 10, 9, 8,
+                                    7, 6, 5, 4, 3, 2, 1, 0,
+                                    15, 14, 13, 12, 11, 10, 9, 8);
+        let mask = _mm256_set1_epi64x((1 << bits) - 1);
+        let drop = _mm_cvtsi32_si128(64 - bits as i32);
+        let sevens = _mm256_set1_epi64x(7);
+        let evens = _mm256_setr_epi32(0, 2, 4, 6, 0, 2, 4, 6);
+
+        let mut unpacked = 0;
+        while unpacked + 4 <= output.len() {
+            // the last lane's word must lie within the input
+            let first = unpacked as u64 * u64::from(bits);
+            let last_byte = (first + 3 * u64::from(bits)) / 8;
+            if last_byte + 8 > input.len() as u64 {
+                break;
+            }
+            let positions = _mm256_add_epi64(
+                _mm256_set1_epi64x(first as i64), widths);
+            let bytes = _mm256_srli_epi64::<3>(positions);
+            let skips = _mm256_and_si256(positions, sevens);
+            let words = _mm256_i64gather_epi64::<1>(
+                input.as_ptr() as *const i64, bytes);
+            let fields = if little {
+                _mm256_and_si256(_mm256_srlv_epi64(words, skips), mask)
+            } else {
+                let words = _mm256_shuffle_epi8(words, swap);
+                _mm256_srl_epi64(_mm256_sllv_epi64(words, skips), drop)
+            };
+            // narrow each 64-bit lane to 32 bits, in order
+            let packed = _mm256_permutevar8x32_epi32(fields, evens);
+            _mm_storeu_si128(
+                output[unpacked..].as_mut_ptr() as *mut __m128i,
+                _mm256_castsi256_si128(packed));
+            unpacked += 4;
+        }
+        unpacked
+    }
+}
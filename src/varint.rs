@@ -0,0 +1,144 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Descriptions of variable-length integer formats
+//! for use with the `read_varint` and `write_varint` methods.
+//!
+//! A variable-length integer is stored as one or more chunks,
+//! each holding a fixed number of payload bits and a single flag bit
+//! which indicates whether more chunks follow.
+//! Each chunk is read or written as a single value
+//! with the stream's endianness.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Write, Read, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::varint::VarInt;
+//!
+//! let leb128 = VarInt::leb128();
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     writer.write_varint(&leb128, 624485).unwrap();
+//! }
+//! assert_eq!(data, [0xE5, 0x8E, 0x26]);
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! assert_eq!(reader.read_varint(&leb128).unwrap(), 624485);
+//! ```
+
+#![warn(missing_docs)]
+
+use io;
+
+/// Where a chunk's flag bit is stored relative to its payload.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FlagPosition {
+    /// The flag is the chunk's most-significant bit
+    High,
+    /// The flag is the chunk's least-significant bit
+    Low,
+}
+
+/// The order in which a value's chunks are stored.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChunkOrder {
+    /// The least-significant chunk is stored first
+    LeastSignificantFirst,
+    /// The most-significant chunk is stored first
+    MostSignificantFirst,
+}
+
+/// A variable-length integer format.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VarInt {
+    /// The number of payload bits in each chunk, from 1 to 63
+    pub payload_bits: u32,
+    /// Where each chunk's flag bit is stored
+    pub flag: FlagPosition,
+    /// The flag value indicating that more chunks follow
+    pub continue_flag: bool,
+    /// The order in which chunks are stored
+    pub order: ChunkOrder,
+}
+
+impl VarInt {
+    /// Unsigned LEB128, as used by DWARF, WebAssembly and Protocol Buffers.
+    /// Each byte holds 7 payload bits, least-significant first,
+    /// and a high bit of 1 indicates that more bytes follow.
+    pub const fn leb128() -> VarInt {
+        VarInt{payload_bits: 7,
+               flag: FlagPosition::High,
+               continue_flag: true,
+               order: ChunkOrder::LeastSignificantFirst}
+    }
+
+    /// A variable-length quantity, as used by MIDI files.
+    /// Each byte holds 7 payload bits, most-significant first,
+    /// and a high bit of 1 indicates that more bytes follow.
+    pub const fn vlq() -> VarInt {
+        VarInt{payload_bits: 7,
+               flag: FlagPosition::High,
+               continue_flag: true,
+               order: ChunkOrder::MostSignificantFirst}
+    }
+
+    /// The size of each chunk, in bits
+    #[inline]
+    pub const fn chunk_bits(&self) -> u32 {self.payload_bits + 1}
+
+    /// Returns the number of chunks needed to store the given value
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::varint::VarInt;
+    /// assert_eq!(VarInt::leb128().chunks(0), 1);
+    /// assert_eq!(VarInt::leb128().chunks(127), 1);
+    /// assert_eq!(VarInt::leb128().chunks(128), 2);
+    /// assert_eq!(VarInt::leb128().chunks(u64::MAX), 10);
+    /// ```
+    pub fn chunks(&self, value: u64) -> u32 {
+        let bits = 64 - value.leading_zeros();
+        if bits == 0 {1} else {bits.div_ceil(self.payload_bits)}
+    }
+
+    pub(crate) fn check(&self) -> Result<(), io::Error> {
+        if (1..=63).contains(&self.payload_bits) {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidInput,
+                               "invalid varint payload size"))
+        }
+    }
+
+    // returns a chunk's payload and whether more chunks follow
+    #[inline]
+    pub(crate) fn split(&self, chunk: u64) -> (u64, bool) {
+        match self.flag {
+            FlagPosition::High => {
+                (chunk & ((1 << self.payload_bits) - 1),
+                 ((chunk >> self.payload_bits) == 1) == self.continue_flag)
+            }
+            FlagPosition::Low => {
+                (chunk >> 1, ((chunk & 1) == 1) == self.continue_flag)
+            }
+        }
+    }
+
+    // returns a chunk with the given payload and continuation
+    #[inline]
+    pub(crate) fn join(&self, payload: u64, more: bool) -> u64 {
+        let flag = if more == self.continue_flag {1} else {0};
+        match self.flag {
+            FlagPosition::High => (flag << self.payload_bits) | payload,
+            FlagPosition::Low => (payload << 1) | flag,
+        }
+    }
+}
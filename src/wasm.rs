@@ -0,0 +1,282 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! JavaScript bindings for WebAssembly builds.
+//!
+//! This module requires the `wasm` feature.
+//!
+//! The core of the crate has no platform requirements beyond
+//! the `io` traits, so it builds for `wasm32-unknown-unknown`
+//! as-is.  This module additionally exports reader and writer
+//! classes to JavaScript through `wasm-bindgen`, one pair per
+//! endianness, which work on in-memory byte arrays
+//! using the same decoding logic as `BitReader` and `BitWriter`.
+//!
+//! Values are passed as 32-bit numbers, and errors are
+//! thrown as JavaScript `Error`s.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::wasm::{BigEndianReader, BigEndianWriter};
+//!
+//! let mut writer = BigEndianWriter::new();
+//! writer.write(4, 0b1011).unwrap();
+//! writer.write_signed(12, -2).unwrap();
+//! let data = writer.finish().unwrap();
+//! assert_eq!(data, vec![0b1011_1111, 0b1111_1110]);
+//!
+//! let mut reader = BigEndianReader::new(data);
+//! assert_eq!(reader.read(4).unwrap(), 0b1011);
+//! assert_eq!(reader.read_signed(12).unwrap(), -2);
+//! ```
+
+#![warn(missing_docs)]
+
+use std::io::{self, Cursor};
+use std::mem;
+
+use wasm_bindgen::prelude::*;
+
+use super::{BigEndian, LittleEndian, BitQueue, BitReader, BitWriter};
+
+fn to_js(err: io::Error) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+macro_rules! define_wasm_reader {
+    ($name:ident, $endianness:ident, $doc:expr) => {
+        #[doc = $doc]
+        #[wasm_bindgen]
+        pub struct $name {
+            reader: BitReader<$endianness, Cursor<Vec<u8>>>,
+        }
+
+        #[wasm_bindgen]
+        impl $name {
+            /// Builds a reader over a copy of the given bytes.
+            #[wasm_bindgen(constructor)]
+            pub fn new(data: Vec<u8>) -> $name {
+                $name{reader: BitReader::from_read(Cursor::new(data))}
+            }
+
+            /// Reads an unsigned value of up to 32 bits.
+            ///
+            /// # Errors
+            ///
+            /// Throws if the data runs out or `bits` is over 32.
+            pub fn read(&mut self, bits: u32) -> Result<u32, JsError> {
+                self.reader.read(bits).map_err(to_js)
+            }
+
+            /// Reads a twos-complement signed value of up to 32 bits.
+            ///
+            /// # Errors
+            ///
+            /// Throws if the data runs out or `bits` is out of range.
+            pub fn read_signed(&mut self, bits: u32) -> Result<i32, JsError> {
+                self.reader.read_signed(bits).map_err(to_js)
+            }
+
+            /// Reads a single bit.
+            ///
+            /// # Errors
+            ///
+            /// Throws if the data runs out.
+            pub fn read_bit(&mut self) -> Result<bool, JsError> {
+                self.reader.read_bit().map_err(to_js)
+            }
+
+            /// Reads a unary value terminated by a 0 bit.
+            ///
+            /// # Errors
+            ///
+            /// Throws if the data runs out.
+            pub fn read_unary0(&mut self) -> Result<u32, JsError> {
+                self.reader.read_unary0().map_err(to_js)
+            }
+
+            /// Reads a unary value terminated by a 1 bit.
+            ///
+            /// # Errors
+            ///
+            /// Throws if the data runs out.
+            pub fn read_unary1(&mut self) -> Result<u32, JsError> {
+                self.reader.read_unary1().map_err(to_js)
+            }
+
+            /// Reads the given number of whole bytes.
+            ///
+            /// # Errors
+            ///
+            /// Throws if the data runs out.
+            pub fn read_bytes(&mut self, bytes: u32) ->
+                Result<Vec<u8>, JsError> {
+                let mut buf = vec![0; bytes as usize];
+                self.reader.read_bytes(&mut buf).map_err(to_js)?;
+                Ok(buf)
+            }
+
+            /// Skips the given number of bits.
+            ///
+            /// # Errors
+            ///
+            /// Throws if the data runs out.
+            pub fn skip(&mut self, bits: u32) -> Result<(), JsError> {
+                self.reader.skip(bits).map_err(to_js)
+            }
+
+            /// Moves to the given position in bits from the start.
+            ///
+            /// # Errors
+            ///
+            /// Throws if the position is out of range.
+            pub fn seek_bits(&mut self, position: f64) -> Result<(), JsError> {
+                self.reader.seek_bits(position as u64).map_err(to_js)
+            }
+
+            /// Discards any bits up to the next whole byte.
+            pub fn byte_align(&mut self) {
+                self.reader.byte_align()
+            }
+
+            /// Returns true if the reader is at a whole byte.
+            pub fn byte_aligned(&self) -> bool {
+                self.reader.byte_aligned()
+            }
+
+            /// Returns the position in bits from the start.
+            pub fn position_in_bits(&self) -> f64 {
+                self.reader.position_in_bits() as f64
+            }
+        }
+    }
+}
+
+macro_rules! define_wasm_writer {
+    ($name:ident, $endianness:ident, $doc:expr) => {
+        #[doc = $doc]
+        #[wasm_bindgen]
+        pub struct $name {
+            data: Vec<u8>,
+            bitqueue: BitQueue<$endianness, u8>,
+        }
+
+        impl $name {
+            fn with_writer<F, T>(&mut self, f: F) -> Result<T, JsError>
+                where F: FnOnce(&mut BitWriter<$endianness>) ->
+                      Result<T, io::Error> {
+                let bitqueue = mem::take(&mut self.bitqueue);
+                let mut writer = BitWriter::from_parts(&mut self.data,
+                                                       bitqueue);
+                let result = f(&mut writer);
+                self.bitqueue = writer.into_bitqueue();
+                result.map_err(to_js)
+            }
+        }
+
+        #[wasm_bindgen]
+        impl $name {
+            /// Builds a new, empty writer.
+            #[wasm_bindgen(constructor)]
+            #[allow(clippy::new_without_default)]
+            pub fn new() -> $name {
+                $name{data: Vec::new(), bitqueue: BitQueue::new()}
+            }
+
+            /// Writes an unsigned value of up to 32 bits.
+            ///
+            /// # Errors
+            ///
+            /// Throws if the value doesn't fit in `bits`.
+            pub fn write(&mut self, bits: u32, value: u32) ->
+                Result<(), JsError> {
+                self.with_writer(|w| w.write(bits, value))
+            }
+
+            /// Writes a twos-complement signed value of up to 32 bits.
+            ///
+            /// # Errors
+            ///
+            /// Throws if the value doesn't fit in `bits`.
+            pub fn write_signed(&mut self, bits: u32, value: i32) ->
+                Result<(), JsError> {
+                self.with_writer(|w| w.write_signed(bits, value))
+            }
+
+            /// Writes a single bit.
+            ///
+            /// # Errors
+            ///
+            /// Never throws in practice, since output is in memory.
+            pub fn write_bit(&mut self, bit: bool) -> Result<(), JsError> {
+                self.with_writer(|w| w.write_bit(bit))
+            }
+
+            /// Writes a unary value terminated by a 0 bit.
+            ///
+            /// # Errors
+            ///
+            /// Never throws in practice, since output is in memory.
+            pub fn write_unary0(&mut self, value: u32) -> Result<(), JsError> {
+                self.with_writer(|w| w.write_unary0(value))
+            }
+
+            /// Writes a unary value terminated by a 1 bit.
+            ///
+            /// # Errors
+            ///
+            /// Never throws in practice, since output is in memory.
+            pub fn write_unary1(&mut self, value: u32) -> Result<(), JsError> {
+                self.with_writer(|w| w.write_unary1(value))
+            }
+
+            /// Writes whole bytes.
+            ///
+            /// # Errors
+            ///
+            /// Never throws in practice, since output is in memory.
+            pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), JsError> {
+                self.with_writer(|w| w.write_bytes(bytes))
+            }
+
+            /// Pads the output with 0 bits up to the next whole byte.
+            ///
+            /// # Errors
+            ///
+            /// Never throws in practice, since output is in memory.
+            pub fn byte_align(&mut self) -> Result<(), JsError> {
+                self.with_writer(|w| w.byte_align())
+            }
+
+            /// Returns true if the writer is at a whole byte.
+            pub fn byte_aligned(&self) -> bool {
+                self.bitqueue.is_empty()
+            }
+
+            /// Pads the output to a whole byte and returns it.
+            ///
+            /// # Errors
+            ///
+            /// Never throws in practice, since output is in memory.
+            pub fn finish(mut self) -> Result<Vec<u8>, JsError> {
+                self.byte_align()?;
+                Ok(self.data)
+            }
+        }
+    }
+}
+
+define_wasm_reader!(BigEndianReader, BigEndian,
+                    "Reads big-endian values from an array of bytes.");
+define_wasm_reader!(LittleEndianReader, LittleEndian,
+                    "Reads little-endian values from an array of bytes.");
+define_wasm_writer!(BigEndianWriter, BigEndian,
+                    "Writes big-endian values to an array of bytes.");
+define_wasm_writer!(LittleEndianWriter, LittleEndian,
+                    "Writes little-endian values to an array of bytes.");
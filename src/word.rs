@@ -0,0 +1,481 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reading many small fields a 64-bit word at a time.
+//!
+//! This module requires the `std` feature.
+//!
+//! A `BitReader` caches no more than a single partial byte,
+//! so that it never takes more from its stream than it needs,
+//! but this means fetching a fresh byte from the stream
+//! for every 8 bits read.
+//! When the stream is buffered, a `WordReader` instead loads
+//! 64 bits at a time directly from the stream's buffer,
+//! so that most fields are extracted from a single word
+//! with no per-byte overhead.
+//! Those bytes are only consumed from the buffer once all of
+//! the word's bits have been read, and `into_byte_reader`
+//! consumes only those bytes which bits have been read from,
+//! so the same guarantee holds.
+//!
+//! Near the end of a buffer, where fewer than 8 bytes remain,
+//! bits are read a byte at a time just as a `BitReader` would.
+//! Unbuffered streams, such as files, may be given a buffer
+//! of their own with `WordReader::buffered`.
+//!
+//! This makes a `WordReader` the fast path for decoding
+//! many small fields, which is why `BitReader` keeps
+//! its single byte cache: only a buffered stream lets bytes be
+//! looked at before they're taken, so a reader of any `Read` stream
+//! which cached more would take bytes it might not need.
+//! `BitReader::into_word_reader` switches to a `WordReader`
+//! part way through a buffered stream.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitRead};
+//! use bitstream_io::word::WordReader;
+//!
+//! let data = [0b1011_0001, 0xFF, 0x00, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC];
+//! let mut reader = WordReader::<BigEndian, _>::new(&data[..]);
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0b1011);
+//! assert_eq!(reader.read::<u16>(12).unwrap(), 0b0001_1111_1111);
+//! assert_eq!(reader.read_unary1().unwrap(), 11);
+//! assert_eq!(reader.read::<u32>(28).unwrap(), 0x2345678);
+//! assert_eq!(reader.read::<u16>(12).unwrap(), 0x9AB);
+//!
+//! // the last partial byte is consumed along with the rest
+//! let rest = reader.into_byte_reader();
+//! assert!(rest.is_empty());
+//! ```
+
+#![warn(missing_docs)]
+
+use std::cmp::min;
+use std::io::{self, BufRead, BufReader};
+
+use super::{is_little_endian, BitQueue, BitRead, BitReader, Endianness,
+            Numeric, SignedNumeric};
+use read::consume_bytes;
+
+/// Reads bits from a buffered stream, up to 64 at a time.
+pub struct WordReader<E: Endianness, R: BufRead> {
+    reader: R,
+    // bits not yet read from the first 8 bytes of the stream's buffer,
+    // which are consumed once all of their bits have been read
+    word: BitQueue<E, u64>,
+    // whether the first 8 bytes of the stream's buffer
+    // are held in the word, and so not yet consumed
+    loaded: bool,
+    // bits of a byte already consumed from the stream,
+    // as read near the end of its buffer
+    bitqueue: BitQueue<E, u8>,
+}
+
+impl<E: Endianness, R: BufRead> WordReader<E, R> {
+    /// Wraps a WordReader around a buffered stream.
+    pub const fn new(reader: R) -> WordReader<E, R> {
+        WordReader{reader,
+                   word: BitQueue::empty(),
+                   loaded: false,
+                   bitqueue: BitQueue::empty()}
+    }
+
+    /// Returns a reference to the underlying stream,
+    /// which may still hold up to 8 bytes which have been read.
+    #[inline]
+    pub fn get_ref(&self) -> &R {&self.reader}
+
+    /// Returns the underlying stream, discarding any bits
+    /// remaining in the current partial byte.
+    /// The stream is positioned at the first byte
+    /// no bits have been read from.
+    pub fn into_byte_reader(mut self) -> R {
+        self.byte_align();
+        if self.loaded {
+            self.reader.consume(8 - (self.word.len() / 8) as usize);
+        }
+        self.reader
+    }
+
+    /// Reads Rice-coded values with the given parameter
+    /// until the output is filled.
+    ///
+    /// Each value is a quotient, stored in unary as some number
+    /// of 0 bits followed by a 1 bit, then a `k`-bit remainder.
+    /// The whole block is decoded in a single loop over
+    /// the cached word, rather than with two calls per value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidInput` error if `k` is larger than 32,
+    /// an `InvalidData` error if a value is too large for a `u32`,
+    /// or passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::BigEndian;
+    /// use bitstream_io::word::WordReader;
+    ///
+    /// // 5, 2 and 12 with a k of 2
+    /// let data = [0b01_01_1_10_0, 0b001_00_000];
+    /// let mut reader = WordReader::<BigEndian, _>::new(&data[..]);
+    /// let mut values = [0; 3];
+    /// reader.read_rice_block_unsigned(2, &mut values).unwrap();
+    /// assert_eq!(values, [5, 2, 12]);
+    /// ```
+    pub fn read_rice_block_unsigned(&mut self,
+                                    k: u32,
+                                    output: &mut [u32]) -> io::Result<()> {
+        check_rice_parameter(k)?;
+        for value in output.iter_mut() {
+            *value = self.read_rice(k)?;
+        }
+        Ok(())
+    }
+
+    /// Reads Rice-coded signed residuals with the given parameter
+    /// until the output is filled.
+    ///
+    /// These are stored as `read_rice_block_unsigned` reads them,
+    /// after being folded to unsigned values with zigzag encoding,
+    /// as in FLAC.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidInput` error if `k` is larger than 32,
+    /// an `InvalidData` error if a folded value is too large for a `u32`,
+    /// or passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::BigEndian;
+    /// use bitstream_io::word::WordReader;
+    ///
+    /// // -3, 1 and 6, folded to 5, 2 and 12, with a k of 2
+    /// let data = [0b01_01_1_10_0, 0b001_00_000];
+    /// let mut reader = WordReader::<BigEndian, _>::new(&data[..]);
+    /// let mut residuals = [0; 3];
+    /// reader.read_rice_block(2, &mut residuals).unwrap();
+    /// assert_eq!(residuals, [-3, 1, 6]);
+    /// ```
+    pub fn read_rice_block(&mut self,
+                           k: u32,
+                           output: &mut [i32]) -> io::Result<()> {
+        check_rice_parameter(k)?;
+        for residual in output.iter_mut() {
+            let folded = self.read_rice(k)?;
+            *residual = ((folded >> 1) as i32) ^ -((folded & 1) as i32);
+        }
+        Ok(())
+    }
+
+    // reads a single Rice-coded value, whose parameter has been checked,
+    // straight from the word when all of its bits are there
+    #[inline(always)]
+    fn read_rice(&mut self, k: u32) -> io::Result<u32> {
+        /*the quotient's 0 bits and 1 bit, the remainder
+          and the bits after it are split from the word at once,
+          and since each part fits, the checks in BitQueue::set
+          and BitQueue::pop can be skipped*/
+        let (word, len) = (self.word.value, self.word.bits);
+        let little = is_little_endian::<E>();
+        let zeros = if little {
+            word.trailing_zeros()
+        } else {
+            (word << (64 - len.max(1))).leading_zeros()
+        };
+        let value = if zeros + 1 + k <= len {
+            let rest = len - (zeros + 1 + k);
+            let (remainder, word) = if little {
+                let word = word >> zeros >> 1;
+                (word & low_bits(k), word >> k)
+            } else {
+                ((word >> rest) & low_bits(k), word & low_bits(rest))
+            };
+            self.word.value = word;
+            self.word.bits = rest;
+            (u64::from(zeros) << k) | remainder
+        } else {
+            let quotient = self.read_unary1()?;
+            (u64::from(quotient) << k) | self.read::<u64>(k)?
+        };
+        if value <= u64::from(u32::MAX) {
+            Ok(value as u32)
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidData,
+                               "excessive Rice value"))
+        }
+    }
+
+    // once the word is empty, consumes its bytes
+    // and loads the next 8 from the stream's buffer, if available,
+    // returning whether the word was loaded
+    fn load_word(&mut self) -> io::Result<bool> {
+        debug_assert!(self.word.is_empty() && self.bitqueue.is_empty());
+        if self.loaded {
+            self.reader.consume(8);
+            self.loaded = false;
+        }
+        let buf = self.reader.fill_buf()?;
+        if buf.len() >= 8 {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&buf[0..8]);
+            self.word.set(E::from_bytes::<u64>(bytes), 64);
+            self.loaded = true;
+        }
+        Ok(self.loaded)
+    }
+
+    // consumes the next byte from the stream into the bit queue,
+    // for when its buffer holds less than a word
+    fn load_byte(&mut self) -> io::Result<()> {
+        let mut byte = [0; 1];
+        io::Read::read_exact(&mut self.reader, &mut byte)?;
+        self.bitqueue.set(byte[0], 8);
+        Ok(())
+    }
+}
+
+// a mask of the given number of low bits, from 0 to 64
+#[inline(always)]
+fn low_bits(bits: u32) -> u64 {
+    if bits == 0 {0} else {!0 >> (64 - bits)}
+}
+
+// Rice-coded values are read into 32-bit outputs
+fn check_rice_parameter(k: u32) -> io::Result<()> {
+    if k <= 32 {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidInput,
+                           "excessive Rice parameter"))
+    }
+}
+
+impl<E: Endianness, R: BufRead> BitRead for WordReader<E, R> {
+    #[inline]
+    fn read_bit(&mut self) -> Result<bool, io::Error> {
+        self.read::<u8>(1).map(|bit| bit == 1)
+    }
+
+    fn read<U>(&mut self, bits: u32) -> Result<U, io::Error>
+        where U: Numeric {
+        if bits > U::bits_size() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive bits for type read"));
+        }
+        if bits <= self.word.len() {
+            // the common case, where the whole value is in the word
+            return Ok(if bits > 0 {
+                U::from_u64(self.word.pop(bits))
+            } else {
+                U::default()
+            });
+        }
+        let mut acc = BitQueue::<E, U>::new();
+        let mut remaining = bits;
+        while remaining > 0 {
+            if !self.word.is_empty() {
+                let to_read = min(remaining, self.word.len());
+                acc.push(to_read, U::from_u64(self.word.pop(to_read)));
+                remaining -= to_read;
+            } else if !self.bitqueue.is_empty() {
+                let to_read = min(remaining, self.bitqueue.len());
+                acc.push(to_read, U::from_u8(self.bitqueue.pop(to_read)));
+                remaining -= to_read;
+            } else if !self.load_word()? {
+                self.load_byte()?;
+            }
+        }
+        Ok(acc.value())
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> Result<S, io::Error>
+        where S: SignedNumeric {
+        if bits <= S::bits_size() {
+            E::read_signed(self, bits)
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidInput,
+                               "excessive bits for type read"))
+        }
+    }
+
+    fn skip(&mut self, bits: u32) -> Result<(), io::Error> {
+        let mut remaining = bits;
+        while remaining > 0 {
+            if !self.word.is_empty() {
+                let to_drop = min(remaining, self.word.len());
+                self.word.drop(to_drop);
+                remaining -= to_drop;
+            } else if !self.bitqueue.is_empty() {
+                let to_drop = min(remaining, self.bitqueue.len());
+                self.bitqueue.drop(to_drop);
+                remaining -= to_drop;
+            } else if remaining >= 64 {
+                // whole bytes are discarded straight from the buffer
+                if self.loaded {
+                    self.reader.consume(8);
+                    self.loaded = false;
+                }
+                consume_bytes(&mut self.reader, u64::from(remaining / 8))?;
+                remaining %= 8;
+            } else if !self.load_word()? {
+                self.load_byte()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        if self.byte_aligned() {
+            let from_word = min(buf.len(), (self.word.len() / 8) as usize);
+            let (head, tail) = buf.split_at_mut(from_word);
+            for b in head.iter_mut() {
+                *b = self.word.pop(8) as u8;
+            }
+            if !tail.is_empty() {
+                if self.loaded {
+                    self.reader.consume(8);
+                    self.loaded = false;
+                }
+                io::Read::read_exact(&mut self.reader, tail)?;
+            }
+            Ok(())
+        } else {
+            for b in buf.iter_mut() {
+                *b = self.read::<u8>(8)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn read_unary0(&mut self) -> Result<u32, io::Error> {
+        // each cached word or byte is scanned with a single count
+        // of its leading bits, so long runs cost one step per 64 bits
+        let mut acc = 0;
+        loop {
+            if !self.word.is_empty() {
+                let run = self.word.leading_1s();
+                if run < self.word.len() {
+                    self.word.drop(run + 1);
+                    return Ok(acc + run);
+                }
+                acc += run;
+                self.word.clear();
+            } else if !self.bitqueue.is_empty() {
+                let run = self.bitqueue.leading_1s();
+                if run < self.bitqueue.len() {
+                    self.bitqueue.drop(run + 1);
+                    return Ok(acc + run);
+                }
+                acc += run;
+                self.bitqueue.clear();
+            } else if !self.load_word()? {
+                self.load_byte()?;
+            }
+        }
+    }
+
+    fn read_unary1(&mut self) -> Result<u32, io::Error> {
+        let mut acc = 0;
+        loop {
+            if !self.word.is_empty() {
+                let run = self.word.leading_0s();
+                if run < self.word.len() {
+                    self.word.drop(run + 1);
+                    return Ok(acc + run);
+                }
+                acc += run;
+                self.word.clear();
+            } else if !self.bitqueue.is_empty() {
+                let run = self.bitqueue.leading_0s();
+                if run < self.bitqueue.len() {
+                    self.bitqueue.drop(run + 1);
+                    return Ok(acc + run);
+                }
+                acc += run;
+                self.bitqueue.clear();
+            } else if !self.load_word()? {
+                self.load_byte()?;
+            }
+        }
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.word.len().is_multiple_of(8) && self.bitqueue.is_empty()
+    }
+
+    fn byte_align(&mut self) {
+        let partial = self.word.len() % 8;
+        if partial > 0 {
+            self.word.drop(partial);
+        }
+        self.bitqueue.clear();
+    }
+}
+
+impl<E: Endianness, R: io::Read> WordReader<E, BufReader<R>> {
+    /// Wraps a WordReader around an unbuffered stream,
+    /// such as a `File` or socket, through a buffer of 8 KiB.
+    ///
+    /// Every byte read through a `BitReader` is a separate read
+    /// from its stream, which is very slow for streams
+    /// where every read is a system call.
+    /// Reading through a buffer makes one read per 8 KiB instead.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Cursor, Read};
+    /// use bitstream_io::{LittleEndian, BitRead};
+    /// use bitstream_io::word::WordReader;
+    ///
+    /// let data = Cursor::new(vec![0b1011_0001, 0xFF]);
+    /// let mut reader = WordReader::<LittleEndian, _>::buffered(data);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0b0001);
+    /// assert_eq!(reader.read::<u16>(12).unwrap(), 0xFFB);
+    /// ```
+    pub fn buffered(reader: R) -> WordReader<E, BufReader<R>> {
+        WordReader::new(BufReader::new(reader))
+    }
+
+    /// Wraps a WordReader around an unbuffered stream
+    /// through a buffer of the given size in bytes.
+    pub fn with_capacity(capacity: usize, reader: R) ->
+        WordReader<E, BufReader<R>> {
+        WordReader::new(BufReader::with_capacity(capacity, reader))
+    }
+}
+
+impl<E: Endianness, R: BufRead> BitReader<E, R> {
+    /// Converts the reader into a `WordReader` over the same stream,
+    /// which reads on from the same bit, including any bits
+    /// remaining in the current partial byte.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitRead, BitReader};
+    ///
+    /// let data = [0b1011_0001, 0xFF, 0x12, 0x34];
+    /// let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0b1011);
+    /// let mut reader = reader.into_word_reader();
+    /// assert_eq!(reader.read::<u16>(12).unwrap(), 0b0001_1111_1111);
+    /// assert_eq!(reader.read::<u16>(16).unwrap(), 0x1234);
+    /// ```
+    pub fn into_word_reader(self) -> WordReader<E, R> {
+        let (reader, bitqueue) = self.into_parts();
+        WordReader{reader,
+                   word: BitQueue::new(),
+                   loaded: false,
+                   bitqueue}
+    }
+}
@@ -71,11 +71,324 @@
 
 #![warn(missing_docs)]
 
-use std::io;
+use core::fmt;
 
-use super::{Numeric, SignedNumeric, BitQueue,
-            Endianness, BigEndian, LittleEndian};
+use io;
+
+use super::{Numeric, SignedNumeric, Primitive, BitQueue, Endianness,
+            exceeds_bits, ranged_bits};
+#[cfg(feature = "alloc")]
 use huffman::WriteHuffmanTree;
+use huffman::HuffmanCode;
+use minifloat;
+use varint::{VarInt, ChunkOrder};
+use mixed::MixedEndian;
+#[cfg(feature = "perf-counters")]
+use perf::{PerfCounters, Tally};
+
+// the stream a writer's bytes are written to,
+// by way of a tally of them when counting
+#[cfg(feature = "perf-counters")]
+macro_rules! sink {
+    ($w:ident) => {&mut Tally{writer: &mut *$w.writer,
+                              counters: &mut $w.counters}}
+}
+
+#[cfg(not(feature = "perf-counters"))]
+macro_rules! sink {
+    ($w:ident) => {&mut *$w.writer}
+}
+
+/// A trait for anything that can write a variable number of
+/// potentially un-aligned values to an output stream
+pub trait BitWrite {
+    /// Writes a single bit to the stream.
+    /// `true` indicates 1, `false` indicates 0
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn write_bit(&mut self, bit: bool) -> Result<(), io::Error>;
+
+    /// Writes an unsigned value to the stream using the given
+    /// number of bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the input type is too small
+    /// to hold the given number of bits.
+    /// Returns an error if the value is too large
+    /// to fit the given number of bits.
+    fn write<U>(&mut self, bits: u32, value: U) -> Result<(), io::Error>
+        where U: Numeric;
+
+    /// Writes a twos-complement signed value to the stream
+    /// with the given number of bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the input type is too small
+    /// to hold the given number of bits.
+    /// Returns an error if the value is too large
+    /// to fit the given number of bits.
+    fn write_signed<S>(&mut self, bits: u32, value: S) ->
+        Result<(), io::Error> where S: SignedNumeric;
+
+    /// Writes the entirety of a byte buffer to the stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), io::Error>;
+
+    /// Writes `value` number of 1 bits to the stream
+    /// and then writes a 0 bit.  This field is variably-sized.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn write_unary0(&mut self, value: u32) -> Result<(), io::Error>;
+
+    /// Writes `value` number of 0 bits to the stream
+    /// and then writes a 1 bit.  This field is variably-sized.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn write_unary1(&mut self, value: u32) -> Result<(), io::Error>;
+
+    /// Returns true if the stream is aligned at a whole byte.
+    fn byte_aligned(&self) -> bool;
+
+    /// Pads the stream with 0 bits until it is aligned at a whole byte.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn byte_align(&mut self) -> Result<(), io::Error>;
+
+    /// Builds and writes a complex type
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the type's builder.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{self, Write};
+    /// use bitstream_io::{BigEndian, BitWrite, BitWriter, ToBitStream};
+    ///
+    /// struct Pair {
+    ///     first: u8,
+    ///     second: u8,
+    /// }
+    ///
+    /// impl ToBitStream for Pair {
+    ///     type Error = io::Error;
+    ///
+    ///     fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+    ///         w.write(4, self.first)?;
+    ///         w.write(4, self.second)
+    ///     }
+    /// }
+    ///
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     writer.build(&Pair{first: 0b1011, second: 0b0111}).unwrap();
+    /// }
+    /// assert_eq!(data, [0b1011_0111]);
+    /// ```
+    #[inline]
+    fn build<T: ToBitStream>(&mut self, value: &T) -> Result<(), T::Error> {
+        value.to_writer(self)
+    }
+
+    /// Builds and writes a complex type if present,
+    /// or writes nothing otherwise
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the type's builder.
+    #[inline]
+    fn write_if<T: ToBitStream>(&mut self, value: Option<&T>) ->
+        Result<(), T::Error> {
+        match value {
+            Some(value) => value.to_writer(self),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the Huffman code for the given symbol
+    /// using a fixed table.
+    /// See `HuffmanCode` for how codes are stored.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an `InvalidInput` error if the symbol
+    /// isn't in the table.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitWrite, BitWriter};
+    /// use bitstream_io::huffman::HuffmanCode;
+    ///
+    /// const TABLE: [HuffmanCode<char>; 3] = [
+    ///     HuffmanCode{symbol: 'a', len: 1, code: 0b0},
+    ///     HuffmanCode{symbol: 'b', len: 2, code: 0b10},
+    ///     HuffmanCode{symbol: 'c', len: 2, code: 0b11},
+    /// ];
+    ///
+    /// let mut data = [0; 1];
+    /// {
+    ///     let mut buf = &mut data[..];
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut buf);
+    ///     writer.write_huffman_code(&TABLE, 'b').unwrap();
+    ///     writer.write_huffman_code(&TABLE, 'c').unwrap();
+    ///     writer.write_huffman_code(&TABLE, 'a').unwrap();
+    ///     assert!(writer.write_huffman_code(&TABLE, 'd').is_err());
+    ///     writer.byte_align().unwrap();
+    /// }
+    /// assert_eq!(data, [0b1011_0000]);
+    /// ```
+    fn write_huffman_code<T>(&mut self, table: &[HuffmanCode<T>], symbol: T) ->
+        Result<(), io::Error> where T: PartialEq {
+        match table.iter().find(|entry| entry.symbol == symbol) {
+            Some(entry) => {
+                for i in (0..entry.len).rev() {
+                    self.write_bit((entry.code >> i) & 1 == 1)?;
+                }
+                Ok(())
+            }
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                       "symbol not in Huffman table")),
+        }
+    }
+
+    /// Writes a single presence bit, followed by
+    /// a complex type if present
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream
+    /// or any error from the type's builder.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{self, Write};
+    /// use bitstream_io::{BigEndian, BitWrite, BitWriter, ToBitStream};
+    ///
+    /// struct Crc(u8);
+    ///
+    /// impl ToBitStream for Crc {
+    ///     type Error = io::Error;
+    ///
+    ///     fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+    ///         w.write(7, self.0)
+    ///     }
+    /// }
+    ///
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     writer.write_option_flagged(Some(&Crc(0b101_0101))).unwrap();
+    ///     writer.write_option_flagged::<Crc>(None).unwrap();
+    ///     writer.byte_align().unwrap();
+    /// }
+    /// assert_eq!(data, [0b1101_0101, 0b0000_0000]);
+    /// ```
+    #[inline]
+    fn write_option_flagged<T>(&mut self, value: Option<&T>) ->
+        Result<(), T::Error>
+        where T: ToBitStream, T::Error: From<io::Error> {
+        self.write_bit(value.is_some())?;
+        self.write_if(value)
+    }
+
+    /// Builds and writes a complex type whose layout
+    /// depends on some context, such as a previously written header
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the type's builder.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{self, Write};
+    /// use bitstream_io::{BigEndian, BitWrite, BitWriter, ToBitStreamWith};
+    ///
+    /// struct Header {
+    ///     sample_bits: u32,
+    /// }
+    ///
+    /// struct Sample(i32);
+    ///
+    /// impl ToBitStreamWith<Header> for Sample {
+    ///     type Error = io::Error;
+    ///
+    ///     fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W,
+    ///                                        header: &Header) ->
+    ///         io::Result<()> {
+    ///         w.write_signed(header.sample_bits, self.0)
+    ///     }
+    /// }
+    ///
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     let header = Header{sample_bits: 4};
+    ///     writer.build_with(&Sample(-5), &header).unwrap();
+    ///     writer.build_with(&Sample(7), &header).unwrap();
+    /// }
+    /// assert_eq!(data, [0b1011_0111]);
+    /// ```
+    #[inline]
+    fn build_with<C, T: ToBitStreamWith<C>>(&mut self,
+                                            value: &T,
+                                            context: &C) ->
+        Result<(), T::Error> {
+        value.to_writer(self, context)
+    }
+}
+
+/// Implemented by complex types that know how to build themselves
+/// to a `BitWrite` stream, so that they may be composed
+/// into larger types.
+///
+/// With the `derive` feature enabled, this may also be derived
+/// for structs whose fields are annotated with their bit widths.
+/// See the `bitstream-io-derive` crate for the supported attributes.
+pub trait ToBitStream {
+    /// The error produced when building fails, such as `io::Error`
+    type Error;
+
+    /// Builds this type to the given writer
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error encountered while building.
+    fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) ->
+        Result<(), Self::Error> where Self: Sized;
+}
+
+/// Implemented by complex types that need some context
+/// to build themselves to a `BitWrite` stream,
+/// such as field widths given by a previously written header.
+pub trait ToBitStreamWith<C> {
+    /// The error produced when building fails, such as `io::Error`
+    type Error;
+
+    /// Builds this type to the given writer using the given context
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error encountered while building.
+    fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W, context: &C) ->
+        Result<(), Self::Error> where Self: Sized;
+}
 
 /// For writing bit values to an underlying stream in a given endianness.
 ///
@@ -84,17 +397,90 @@ use huffman::WriteHuffmanTree;
 /// writer's lifetime ends.
 /// **Partial bytes will be lost** if the writer is disposed of
 /// before they can be written.
-pub struct BitWriter<'a, E: Endianness> {
-    writer: &'a mut io::Write,
-    bitqueue: BitQueue<E,u8>
+///
+/// Writers built with `new` write to any `Write` trait object,
+/// while those built with `from_write` keep their stream's type
+/// and so are `Send` or `Sync` whenever it is.
+pub struct BitWriter<'a, E, W = dyn io::Write + 'a>
+    where E: Endianness, W: 'a + io::Write + ?Sized {
+    writer: &'a mut W,
+    bitqueue: BitQueue<E,u8>,
+    #[cfg(feature = "perf-counters")]
+    counters: PerfCounters,
 }
 
 impl<'a, E: Endianness> BitWriter<'a, E> {
     /// Wraps a BitWriter around something that implements `Write`
-    pub fn new(writer: &mut io::Write) -> BitWriter<E> {
-        BitWriter{writer: writer, bitqueue: BitQueue::new()}
+    pub const fn new(writer: &'a mut dyn io::Write) -> BitWriter<'a, E> {
+        BitWriter{writer,
+                  bitqueue: BitQueue::empty(),
+                  #[cfg(feature = "perf-counters")]
+                  counters: PerfCounters::new()}
+    }
+
+    /// Wraps a BitWriter around a stream, resuming with
+    /// the partial byte left by an earlier writer.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn from_parts(writer: &'a mut dyn io::Write,
+                             bitqueue: BitQueue<E,u8>) -> BitWriter<'a, E> {
+        BitWriter{writer,
+                  bitqueue,
+                  #[cfg(feature = "perf-counters")]
+                  counters: PerfCounters::new()}
+    }
+}
+
+impl<'a, E: Endianness, W: io::Write + ?Sized> BitWriter<'a, E, W> {
+    /// Wraps a BitWriter around a stream, keeping the stream's type
+    /// so that the writer may be sent between threads
+    /// if the stream may be.
+    ///
+    /// # Example
+    /// ```
+    /// use std::thread;
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian, _>::from_write(&mut data);
+    ///     thread::scope(|s| {
+    ///         s.spawn(move || {
+    ///             writer.write(4, 0b1011u8).unwrap();
+    ///             writer.byte_align().unwrap();
+    ///         });
+    ///     });
+    /// }
+    /// assert_eq!(data, [0b1011_0000]);
+    /// ```
+    pub const fn from_write(writer: &'a mut W) -> BitWriter<'a, E, W> {
+        BitWriter{writer,
+                  bitqueue: BitQueue::empty(),
+                  #[cfg(feature = "perf-counters")]
+                  counters: PerfCounters::new()}
     }
 
+    /// Returns counts of the writer's use of its underlying stream
+    /// since it was created.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// let mut writer = BitWriter::<BigEndian, _>::from_write(&mut data);
+    /// writer.write(4, 0b1011u8).unwrap();
+    /// writer.write_bytes(b"ab").unwrap();
+    /// writer.write(4, 0b0110u8).unwrap();
+    /// assert_eq!(writer.perf_counters().refills, 3);
+    /// assert_eq!(writer.perf_counters().misaligned, 1);
+    /// assert_eq!(writer.perf_counters().bytes, 3);
+    /// ```
+    #[cfg(feature = "perf-counters")]
+    #[inline]
+    pub fn perf_counters(&self) -> PerfCounters {self.counters}
+
+    /// Consumes the writer, returning its partial byte.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn into_bitqueue(self) -> BitQueue<E,u8> {self.bitqueue}
+
     /// Writes a single bit to the stream.
     /// `true` indicates 1, `false` indicates 0
     ///
@@ -141,7 +527,7 @@ impl<'a, E: Endianness> BitWriter<'a, E> {
     pub fn write_bit(&mut self, bit: bool) -> Result<(), io::Error> {
         self.bitqueue.push(1, if bit {1} else {0});
         if self.bitqueue.is_full() {
-            write_byte(self.writer, self.bitqueue.pop(8))
+            write_byte(sink!(self), self.bitqueue.pop(8))
         } else {
             Ok(())
         }
@@ -205,18 +591,108 @@ impl<'a, E: Endianness> BitWriter<'a, E> {
         if bits > U::bits_size() {
             Err(io::Error::new(io::ErrorKind::InvalidInput,
                                "excessive bits for type written"))
-        } else if (bits < U::bits_size()) && (value >= (U::one() << bits)) {
+        } else if exceeds_bits(value, bits) {
             Err(io::Error::new(io::ErrorKind::InvalidInput,
                                "excessive value for bits written"))
-        } else if bits < self.bitqueue.remaining_len() {
-            Ok(self.bitqueue.push(bits, value.to_u8()))
+        } else {
+            self.write_unchecked(bits, value)
+        }
+    }
+
+    /// Writes an unsigned value to the stream using the given
+    /// number of bits, without first checking that the bits
+    /// fit the input type or that the value fits the bits.
+    ///
+    /// This is meant for tight loops writing fields
+    /// whose widths are constants already known to be valid.
+    /// No `unsafe` code is involved, but invalid input
+    /// writes meaningless bits or panics.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the input type is too small
+    /// to hold the given number of bits or if the value
+    /// is too large to fit them.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     writer.write_unchecked(3, 0b101u8).unwrap();
+    ///     writer.write_unchecked(13, 0x17FFu16).unwrap();
+    /// }
+    /// assert_eq!(data, [0b1011_0111, 0xFF]);
+    /// ```
+    #[inline]
+    pub fn write_unchecked<U>(&mut self, bits: u32, value: U) ->
+        Result<(), io::Error> where U: Numeric {
+
+        debug_assert!(bits <= U::bits_size());
+        debug_assert!(!exceeds_bits(value, bits));
+
+        if bits < self.bitqueue.remaining_len() {
+            self.bitqueue.push(bits, value.to_u8());
+            Ok(())
         } else {
             let mut acc = BitQueue::from_value(value, bits);
-            write_unaligned(&mut self.writer, &mut acc, &mut self.bitqueue)
-            .and_then(|()|
-                write_aligned(&mut self.writer, &mut acc))
+            write_unaligned(sink!(self), &mut acc, &mut self.bitqueue)
             .and_then(|()|
-                Ok(self.bitqueue.push(acc.len(), acc.value().to_u8())))
+                write_aligned(sink!(self), &mut acc))
+            .map(|()|
+                self.bitqueue.push(acc.len(), acc.value().to_u8()))
+        }
+    }
+
+    /// Writes a twos-complement signed value to the stream
+    /// with the given number of bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the input type is too small
+    /// to hold the given number of bits.
+    /// Returns an error if the value is too large
+    /// to fit the given number of bits.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     writer.write_signed(4, -5).unwrap();
+    ///     writer.write_signed(4, 7).unwrap();
+    /// }
+    /// assert_eq!(data, [0b10110111]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<LittleEndian>::new(&mut data);
+    ///     writer.write_signed(4, 7).unwrap();
+    ///     writer.write_signed(4, -5).unwrap();
+    /// }
+    /// assert_eq!(data, [0b10110111]);
+    /// ```
+    #[inline]
+    pub fn write_signed<S>(&mut self, bits: u32, value: S) ->
+        Result<(), io::Error> where S: SignedNumeric {
+
+        if bits > S::bits_size() {
+            Err(io::Error::new(io::ErrorKind::InvalidInput,
+                               "excessive bits for type written"))
+        } else {
+            E::write_signed(self, bits, value)
         }
     }
 
@@ -246,8 +722,12 @@ impl<'a, E: Endianness> BitWriter<'a, E> {
     /// ```
     pub fn write_bytes(&mut self, buf: &[u8]) -> Result<(), io::Error> {
         if self.byte_aligned() {
-            self.writer.write_all(buf)
+            io::Write::write_all(sink!(self), buf)
         } else {
+            #[cfg(feature = "perf-counters")]
+            {
+                self.counters.misaligned += 1;
+            }
             for b in buf {
                 self.write(8, *b)?;
             }
@@ -255,6 +735,295 @@ impl<'a, E: Endianness> BitWriter<'a, E> {
         }
     }
 
+    /// Writes 7-bit characters, packed without padding
+    /// as in SMS and other telematics formats.
+    ///
+    /// Each character is written as a 7-bit value in the stream's
+    /// bit order, so a little-endian writer packs the GSM 03.38 layout,
+    /// whose first character is in the lowest bits of the first byte,
+    /// while a big-endian writer packs characters
+    /// from the highest bits down.
+    /// No character set is applied; each byte is written as-is.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an `InvalidInput` error, without writing anything,
+    /// if any byte has its high bit set.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{LittleEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut w = BitWriter::<LittleEndian, _>::from_write(&mut data);
+    ///     w.write_ascii7(b"hellohello").unwrap();
+    ///     assert!(w.write_ascii7("h\u{e9}".as_bytes()).is_err());
+    ///     w.byte_align().unwrap();
+    /// }
+    /// assert_eq!(data,
+    ///            [0xE8, 0x32, 0x9B, 0xFD, 0x46, 0x97, 0xD9, 0xEC, 0x37]);
+    /// ```
+    pub fn write_ascii7(&mut self, text: &[u8]) -> Result<(), io::Error> {
+        if !text.is_ascii() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "non-ASCII character written"));
+        }
+        for &c in text {
+            self.write_unchecked(7, c)?;
+        }
+        Ok(())
+    }
+
+    /// Writes an unsigned 24-bit value to the stream.
+    /// If the stream is byte-aligned, this maps to
+    /// a single 3 byte write to the underlying stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the value is too large to fit in 24 bits.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     writer.write_u24(0x123456).unwrap();
+    ///     assert!(writer.write_u24(0x1000000).is_err());
+    /// }
+    /// assert_eq!(data, [0x12, 0x34, 0x56]);
+    /// ```
+    #[inline]
+    pub fn write_u24(&mut self, value: u32) -> Result<(), io::Error> {
+        if value >= (1 << 24) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive value for bits written"));
+        }
+        let mut acc: BitQueue<E, u32> = BitQueue::from_value(value, 24);
+        let mut buf = [0; 3];
+        for b in buf.iter_mut() {
+            *b = acc.pop(8) as u8;
+        }
+        self.write_bytes(&buf)
+    }
+
+    /// Writes a twos-complement signed 24-bit value to the stream.
+    /// If the stream is byte-aligned, this maps to
+    /// a single 3 byte write to the underlying stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the value is too large to fit in 24 bits.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<LittleEndian>::new(&mut data);
+    ///     writer.write_i24(-2).unwrap();
+    ///     writer.write_i24(-8388608).unwrap();
+    ///     assert!(writer.write_i24(8388608).is_err());
+    /// }
+    /// assert_eq!(data, [0xFE, 0xFF, 0xFF, 0x00, 0x00, 0x80]);
+    /// ```
+    #[inline]
+    pub fn write_i24(&mut self, value: i32) -> Result<(), io::Error> {
+        if (-(1 << 23)..(1 << 23)).contains(&value) {
+            self.write_u24((value as u32).truncate(24))
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidInput,
+                               "excessive value for bits written"))
+        }
+    }
+
+    /// Writes a whole primitive value to a byte-aligned stream,
+    /// in the stream's byte order.
+    /// This is a single write to the underlying stream
+    /// which bypasses the bit accumulator entirely.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an `InvalidInput` error if the stream
+    /// is not byte-aligned.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, LittleEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     writer.write_aligned(0x1234u16).unwrap();
+    ///     writer.write(4, 0u8).unwrap();
+    ///     assert!(writer.write_aligned(0x56u8).is_err());
+    /// }
+    /// assert_eq!(data, [0x12, 0x34]);
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<LittleEndian>::new(&mut data);
+    ///     writer.write_aligned(0x78563412u32).unwrap();
+    /// }
+    /// assert_eq!(data, [0x12, 0x34, 0x56, 0x78]);
+    /// ```
+    #[inline]
+    pub fn write_aligned<V: Primitive>(&mut self, value: V) ->
+        Result<(), io::Error> {
+        if !self.byte_aligned() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "stream is not byte-aligned"));
+        }
+        io::Write::write_all(sink!(self), E::to_bytes(value).as_ref())
+    }
+
+    /// Writes an unsigned 16-bit value to a byte-aligned stream.
+    /// See `write_aligned` for details.
+    ///
+    /// # Errors
+    ///
+    /// As with `write_aligned`.
+    #[inline]
+    pub fn write_u16_aligned(&mut self, value: u16) -> Result<(), io::Error> {
+        self.write_aligned(value)
+    }
+
+    /// Writes an unsigned 32-bit value to a byte-aligned stream.
+    /// See `write_aligned` for details.
+    ///
+    /// # Errors
+    ///
+    /// As with `write_aligned`.
+    #[inline]
+    pub fn write_u32_aligned(&mut self, value: u32) -> Result<(), io::Error> {
+        self.write_aligned(value)
+    }
+
+    /// Writes an unsigned 64-bit value to a byte-aligned stream.
+    /// See `write_aligned` for details.
+    ///
+    /// # Errors
+    ///
+    /// As with `write_aligned`.
+    #[inline]
+    pub fn write_u64_aligned(&mut self, value: u64) -> Result<(), io::Error> {
+        self.write_aligned(value)
+    }
+
+    /// Writes a signed 16-bit value to a byte-aligned stream.
+    /// See `write_aligned` for details.
+    ///
+    /// # Errors
+    ///
+    /// As with `write_aligned`.
+    #[inline]
+    pub fn write_i16_aligned(&mut self, value: i16) -> Result<(), io::Error> {
+        self.write_aligned(value)
+    }
+
+    /// Writes a signed 32-bit value to a byte-aligned stream.
+    /// See `write_aligned` for details.
+    ///
+    /// # Errors
+    ///
+    /// As with `write_aligned`.
+    #[inline]
+    pub fn write_i32_aligned(&mut self, value: i32) -> Result<(), io::Error> {
+        self.write_aligned(value)
+    }
+
+    /// Writes a signed 64-bit value to a byte-aligned stream.
+    /// See `write_aligned` for details.
+    ///
+    /// # Errors
+    ///
+    /// As with `write_aligned`.
+    #[inline]
+    pub fn write_i64_aligned(&mut self, value: i64) -> Result<(), io::Error> {
+        self.write_aligned(value)
+    }
+
+    /// Writes an unsigned 48-bit value to the stream.
+    /// If the stream is byte-aligned, this maps to
+    /// a single 6 byte write to the underlying stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the value is too large to fit in 48 bits.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<LittleEndian>::new(&mut data);
+    ///     writer.write_u48(0x123456789ABC).unwrap();
+    /// }
+    /// assert_eq!(data, [0xBC, 0x9A, 0x78, 0x56, 0x34, 0x12]);
+    /// ```
+    #[inline]
+    pub fn write_u48(&mut self, value: u64) -> Result<(), io::Error> {
+        if value >= (1 << 48) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive value for bits written"));
+        }
+        let mut acc: BitQueue<E, u64> = BitQueue::from_value(value, 48);
+        let mut buf = [0; 6];
+        for b in buf.iter_mut() {
+            *b = acc.pop(8) as u8;
+        }
+        self.write_bytes(&buf)
+    }
+
+    /// Writes a value to the stream as a whole number of 16-bit words
+    /// in the given mixed-endian layout.
+    /// The number of words written is determined by the size of the input type.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the input type is not a whole number of words.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter};
+    /// use bitstream_io::mixed::MixedEndian;
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<LittleEndian>::new(&mut data);
+    ///     writer.write_mixed_endian(MixedEndian::Pdp, 0x0A0B0C0Du32).unwrap();
+    ///     writer.write_mixed_endian(MixedEndian::WordSwapped, 0x0A0B0C0Du32).unwrap();
+    ///     assert!(writer.write_mixed_endian(MixedEndian::Pdp, 1u8).is_err());
+    /// }
+    /// assert_eq!(data, [0x0B, 0x0A, 0x0D, 0x0C, 0x0C, 0x0D, 0x0A, 0x0B]);
+    /// ```
+    pub fn write_mixed_endian<U>(&mut self, layout: MixedEndian, value: U) ->
+        Result<(), io::Error> where U: Numeric {
+
+        let bytes = (U::bits_size() / 8) as usize;
+        if !bytes.is_multiple_of(2) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "type is not a whole number of words"));
+        }
+        let mut logical = [0; 16];
+        for (i, byte) in logical[0..bytes].iter_mut().enumerate() {
+            *byte = (value >> (8 * (bytes - 1 - i) as u32)).to_u8();
+        }
+        let mut stored = [0; 16];
+        for (position, byte) in stored[0..bytes].iter_mut().enumerate() {
+            *byte = logical[layout.big_endian_index(position, bytes)];
+        }
+        self.write_bytes(&stored[0..bytes])
+    }
+
     /// Writes Huffman code for the given symbol to the stream.
     ///
     /// # Errors
@@ -280,11 +1049,15 @@ impl<'a, E: Endianness> BitWriter<'a, E> {
     /// }
     /// assert_eq!(data, [0b10110111]);
     /// ```
+    #[cfg(feature = "alloc")]
     pub fn write_huffman<T>(&mut self,
                             tree: &WriteHuffmanTree<E,T>,
                             symbol: T) ->
         Result<(), io::Error> where T: Ord + Copy {
 
+        if let Some((bits, value)) = tree.get_dense(&symbol) {
+            return self.write(bits, value);
+        }
         for &(bits, value) in tree.get(symbol) {
             self.write(bits, value)?;
         }
@@ -327,11 +1100,11 @@ impl<'a, E: Endianness> BitWriter<'a, E> {
     pub fn write_unary0(&mut self, value: u32) -> Result<(), io::Error> {
         match value {
             0 => {self.write_bit(false)}
-            bits @ 1...31 => {self.write(value, (1u32 << bits) - 1)
+            bits @ 1..=31 => {self.write(value, (1u32 << bits) - 1)
                                   .and_then(|()| self.write_bit(false))}
             32 => {self.write(value, 0xFFFFFFFFu32)
                        .and_then(|()| self.write_bit(false))}
-            bits @ 32...63  => {self.write(value, (1u64 << bits) - 1)
+            bits @ 32..=63  => {self.write(value, (1u64 << bits) - 1)
                                     .and_then(|()| self.write_bit(false))}
             64 => {self.write(value, 0xFFFFFFFFFFFFFFFFu64)
                        .and_then(|()| self.write_bit(false))}
@@ -379,15 +1152,376 @@ impl<'a, E: Endianness> BitWriter<'a, E> {
     pub fn write_unary1(&mut self, value: u32) -> Result<(), io::Error> {
         match value {
             0        => {self.write_bit(true)}
-            1...32   => {self.write(value, 0u32)
+            1..=32   => {self.write(value, 0u32)
                              .and_then(|()| self.write_bit(true))}
-            33...64  => {self.write(value, 0u64)
+            33..=64  => {self.write(value, 0u64)
                              .and_then(|()| self.write_bit(true))}
             mut bits => {while bits > 64 {self.write(64, 0u64)?; bits -= 64;}
                          self.write_unary1(bits)}
         }
     }
 
+    /// Writes a k-th order Exp-Golomb coded value to the stream.
+    /// The code is some number of 0 bits, followed by a 1 bit,
+    /// followed by that same number of bits plus `k` more.
+    /// A `k` of 0 is the common `ue(v)` code used in H.264 and others.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if `k` is larger than 63.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     writer.write_exp_golomb(0, 0).unwrap();
+    ///     writer.write_exp_golomb(0, 1).unwrap();
+    ///     writer.write_exp_golomb(0, 2).unwrap();
+    ///     writer.write_exp_golomb(0, 3).unwrap();
+    ///     writer.byte_align().unwrap();
+    /// }
+    /// assert_eq!(data, [0b1_010_011_0, 0b0100_0000]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     writer.write_exp_golomb(1, 0).unwrap();
+    ///     writer.write_exp_golomb(1, 1).unwrap();
+    ///     writer.write_exp_golomb(1, 2).unwrap();
+    ///     writer.write_exp_golomb(1, 3).unwrap();
+    ///     writer.byte_align().unwrap();
+    /// }
+    /// assert_eq!(data, [0b10_11_0100, 0b0101_0000]);
+    /// ```
+    pub fn write_exp_golomb(&mut self, k: u32, value: u64) ->
+        Result<(), io::Error> {
+
+        if k > 63 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive Exp-Golomb order"));
+        }
+        let value = value as u128 + (1u128 << k);
+        let bits = 128 - value.leading_zeros();
+        self.write_unary1(bits - k - 1)?;
+        if bits > 1 {
+            self.write(bits - 1, (value - (1u128 << (bits - 1))) as u64)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Writes a k-th order interleaved Exp-Golomb coded value
+    /// to the stream, as used by Dirac and VC-2.
+    /// Each bit of the value after its implicit leading 1
+    /// is preceded by a 0 bit, and the code is terminated by a 1 bit.
+    /// For orders above 0, the `k` least-significant bits
+    /// of the value follow as-is.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if `k` is larger than 63.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     writer.write_interleaved_exp_golomb(0, 0).unwrap();
+    ///     writer.write_interleaved_exp_golomb(0, 1).unwrap();
+    ///     writer.write_interleaved_exp_golomb(0, 2).unwrap();
+    ///     writer.write_interleaved_exp_golomb(0, 3).unwrap();
+    ///     writer.byte_align().unwrap();
+    /// }
+    /// assert_eq!(data, [0b1_001_011_0, 0b0001_0000]);
+    /// ```
+    pub fn write_interleaved_exp_golomb(&mut self, k: u32, value: u64) ->
+        Result<(), io::Error> {
+
+        if k > 63 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive Exp-Golomb order"));
+        }
+        let prefix = (value >> k) as u128 + 1;
+        let bits = 128 - prefix.leading_zeros();
+        for bit in (0..(bits - 1)).rev() {
+            self.write_bit(false)?;
+            self.write_bit(((prefix >> bit) & 1) == 1)?;
+        }
+        self.write_bit(true)?;
+        if k > 0 {
+            self.write(k, value & ((1u64 << k) - 1))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Writes a truncated binary coded value in the range `[0, n)`.
+    /// Given `k` as the floor of log2(`n`), the first
+    /// 2<sup>k+1</sup> - `n` values are stored in `k` bits
+    /// and the remaining values are stored in `k` + 1 bits.
+    /// When `n` is a power of two, all values are stored in `k` bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if `n` is 0 or if `value` is not less than `n`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     // 0 and 1 take 2 bits, 2 to 5 take 3 bits
+    ///     for value in 0..6 {
+    ///         writer.write_truncated_binary(6, value).unwrap();
+    ///     }
+    ///     assert!(writer.write_truncated_binary(6, 6).is_err());
+    /// }
+    /// assert_eq!(data, [0b00_01_100_1, 0b01_110_111]);
+    /// ```
+    pub fn write_truncated_binary(&mut self, n: u64, value: u64) ->
+        Result<(), io::Error> {
+
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "empty truncated binary range"));
+        } else if value >= n {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "excessive value for range written"));
+        }
+        let k = 63 - n.leading_zeros();
+        let short = (1u128 << (k + 1)) - n as u128;
+        if (value as u128) < short {
+            if k > 0 {self.write(k, value)} else {Ok(())}
+        } else {
+            // the extra bit always comes last, regardless of endianness,
+            // so that the first k bits determine the code's length
+            let extended = value as u128 + short;
+            if k > 0 {
+                self.write(k, (extended >> 1) as u64)?;
+            }
+            self.write_bit((extended & 1) == 1)
+        }
+    }
+
+    /// Writes a value in the range from `min` to `max` inclusive,
+    /// as its offset from `min` in as few bits as the range needs.
+    ///
+    /// The width is that given by `ranged_bits`,
+    /// so ranges known at compile time write fixed-width fields.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an `InvalidInput` error if `min` is greater than `max`
+    /// or if the value is outside the range.
+    ///
+    /// # Examples
+    /// ```
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian, _>::from_write(&mut data);
+    ///     writer.write_ranged(0, 100, 100).unwrap();
+    ///     writer.write_ranged(-256, 255, 255).unwrap();
+    ///     writer.write_ranged(7, 7, 7).unwrap();
+    ///     assert!(writer.write_ranged(0, 100, 101).is_err());
+    /// }
+    /// assert_eq!(data, [0b1100100_1, 0b1111_1111]);
+    /// ```
+    pub fn write_ranged(&mut self, min: i64, max: i64, value: i64) ->
+        Result<(), io::Error> {
+
+        if min > max {
+            Err(io::Error::new(io::ErrorKind::InvalidInput,
+                               "invalid range"))
+        } else if value < min || value > max {
+            Err(io::Error::new(io::ErrorKind::InvalidInput,
+                               "excessive value for range written"))
+        } else {
+            self.write(ranged_bits(min, max),
+                       value.wrapping_sub(min) as u64)
+        }
+    }
+
+    /// Writes a variable-length integer in the given format,
+    /// using as few chunks as possible.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the format's payload size is invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// use bitstream_io::varint::VarInt;
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     writer.write_varint(&VarInt::leb128(), 624485).unwrap();
+    ///     writer.write_varint(&VarInt::vlq(), 0x4000).unwrap();
+    /// }
+    /// assert_eq!(data, [0xE5, 0x8E, 0x26, 0x81, 0x80, 0x00]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// use bitstream_io::varint::{VarInt, FlagPosition, ChunkOrder};
+    /// // 3 payload bits per chunk, where a flag of 0 means more follow
+    /// let format = VarInt{payload_bits: 3,
+    ///                     flag: FlagPosition::Low,
+    ///                     continue_flag: false,
+    ///                     order: ChunkOrder::MostSignificantFirst};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     writer.write_varint(&format, 0o16).unwrap();
+    /// }
+    /// assert_eq!(data, [0b0010_1101]);
+    /// ```
+    pub fn write_varint(&mut self, format: &VarInt, value: u64) ->
+        Result<(), io::Error> {
+
+        format.check()?;
+        let chunk_bits = format.chunk_bits();
+        let chunks = format.chunks(value);
+        let mask = (1u64 << format.payload_bits) - 1;
+        for i in 0..chunks {
+            let index = match format.order {
+                ChunkOrder::LeastSignificantFirst => i,
+                ChunkOrder::MostSignificantFirst => chunks - i - 1,
+            };
+            let shift = index * format.payload_bits;
+            let payload = if shift < 64 {(value >> shift) & mask} else {0};
+            self.write(chunk_bits, format.join(payload, i + 1 < chunks))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffer's length as a variable-length integer
+    /// in the given format, followed by its bytes,
+    /// which needn't be byte-aligned.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream,
+    /// or any error writing the length.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// use bitstream_io::varint::VarInt;
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian, _>::from_write(&mut data);
+    ///     writer.write_prefixed_bytes(&VarInt::leb128(), b"abc").unwrap();
+    /// }
+    /// assert_eq!(data, [0x03, b'a', b'b', b'c']);
+    /// ```
+    pub fn write_prefixed_bytes(&mut self, format: &VarInt, buf: &[u8]) ->
+        Result<(), io::Error> {
+        self.write_varint(format, buf.len() as u64)?;
+        self.write_bytes(buf)
+    }
+
+    /// Writes the string's length in bytes as a variable-length integer
+    /// in the given format, followed by its UTF-8 bytes.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream,
+    /// or any error writing the length.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// use bitstream_io::varint::VarInt;
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian, _>::from_write(&mut data);
+    ///     writer.write(4, 0b1010u8).unwrap();
+    ///     writer.write_prefixed_str(&VarInt::leb128(), "\u{e9}i").unwrap();
+    ///     writer.byte_align().unwrap();
+    /// }
+    /// assert_eq!(data, [0b1010_0000, 0x3C, 0x3A, 0x96, 0x90]);
+    /// ```
+    #[inline]
+    pub fn write_prefixed_str(&mut self, format: &VarInt, text: &str) ->
+        Result<(), io::Error> {
+        self.write_prefixed_bytes(format, text.as_bytes())
+    }
+
+    /// Writes an IEEE-like floating point value with the given
+    /// number of exponent and mantissa bits.
+    /// The value is stored as a sign bit, followed by the biased
+    /// exponent, followed by the mantissa, for a total of
+    /// 1 + `exp_bits` + `mant_bits` bits.
+    ///
+    /// Values which cannot be represented exactly are rounded
+    /// to the nearest representable value (with ties to even),
+    /// values too large for the format become infinities,
+    /// and values too small become subnormals or zero.
+    /// NaN is written as a quiet NaN.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Also returns an error if `exp_bits` is not between 2 and 11,
+    /// or if `mant_bits` is larger than 52.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     // IEEE 754 half precision values
+    ///     writer.write_minifloat(5, 10, 1.0).unwrap();
+    ///     writer.write_minifloat(5, 10, -2.0).unwrap();
+    ///     writer.write_minifloat(5, 10, 1.0e6).unwrap();
+    /// }
+    /// assert_eq!(data, [0x3C, 0x00, 0xC0, 0x00, 0x7C, 0x00]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// let mut data = Vec::new();
+    /// {
+    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    ///     // 8-bit values with 4 exponent bits and 3 mantissa bits
+    ///     writer.write_minifloat(4, 3, 1.5).unwrap();
+    ///     writer.write_minifloat(4, 3, 1.0 / 512.0).unwrap();
+    /// }
+    /// assert_eq!(data, [0b0_0111_100, 0b0_0000_001]);
+    /// ```
+    pub fn write_minifloat(&mut self,
+                           exp_bits: u32,
+                           mant_bits: u32,
+                           value: f64) -> Result<(), io::Error> {
+
+        minifloat::check_widths(exp_bits, mant_bits)?;
+        self.write(1 + exp_bits + mant_bits,
+                   minifloat::encode(exp_bits, mant_bits, value))
+    }
+
     /// Returns true if the stream is aligned at a whole byte.
     ///
     /// # Example
@@ -469,103 +1603,91 @@ impl<'a, E: Endianness> BitWriter<'a, E> {
     }
 }
 
-impl<'a> BitWriter<'a, BigEndian> {
-    /// Writes a twos-complement signed value to the stream
-    /// with the given number of bits.
-    ///
-    /// # Errors
-    ///
-    /// Passes along any I/O error from the underlying stream.
-    /// Returns an error if the input type is too small
-    /// to hold the given number of bits.
-    /// Returns an error if the value is too large
-    /// to fit the given number of bits.
-    ///
-    /// # Example
-    /// ```
-    /// use std::io::Write;
-    /// use bitstream_io::{BigEndian, BitWriter};
-    /// let mut data = Vec::new();
-    /// {
-    ///     let mut writer = BitWriter::<BigEndian>::new(&mut data);
-    ///     writer.write_signed(4, -5).unwrap();
-    ///     writer.write_signed(4, 7).unwrap();
-    /// }
-    /// assert_eq!(data, [0b10110111]);
-    /// ```
-    pub fn write_signed<S>(&mut self, bits: u32, value: S) ->
-        Result<(), io::Error> where S: SignedNumeric {
+/// Shows the bits of any partial byte not yet written.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{LittleEndian, BitWriter};
+/// let mut data = Vec::new();
+/// let mut writer = BitWriter::<LittleEndian>::new(&mut data);
+/// writer.write(11, 0b101_1111_1111u16).unwrap();
+/// assert_eq!(format!("{:?}", writer),
+///            "BitWriter { pending_bits: 3, pending_value: 0b101 }");
+/// ```
+impl<'a, E: Endianness, W: io::Write + ?Sized> fmt::Debug
+    for BitWriter<'a, E, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bits = self.bitqueue.len();
+        f.debug_struct("BitWriter")
+            .field("pending_bits", &bits)
+            .field("pending_value", &format_args!("{:#0w$b}",
+                                                  self.bitqueue.value,
+                                                  w = bits as usize + 2))
+            .finish()
+    }
+}
 
-        if bits > S::bits_size() {
-            Err(io::Error::new(io::ErrorKind::InvalidInput,
-                               "excessive bits for type written"))
-        } else if value.is_negative() {
-            self.write_bit(true)
-            .and_then(|()| self.write(bits - 1, value.as_unsigned(bits)))
-        } else {
-            self.write_bit(false)
-            .and_then(|()| self.write(bits - 1, value))
-        }
+impl<'a, E: Endianness, W: io::Write + ?Sized> BitWrite
+    for BitWriter<'a, E, W> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> Result<(), io::Error> {
+        BitWriter::write_bit(self, bit)
     }
 
-}
+    #[inline]
+    fn write<U>(&mut self, bits: u32, value: U) -> Result<(), io::Error>
+        where U: Numeric {
+        BitWriter::write(self, bits, value)
+    }
 
-impl<'a> BitWriter<'a, LittleEndian> {
-    /// Writes a twos-complement signed value to the stream
-    /// with the given number of bits.
-    ///
-    /// # Errors
-    ///
-    /// Passes along any I/O error from the underlying stream.
-    /// Returns an error if the input type is too small
-    /// to hold the given number of bits.
-    /// Returns an error if the value is too large
-    /// to fit the given number of bits.
-    ///
-    /// # Example
-    /// ```
-    /// use std::io::Write;
-    /// use bitstream_io::{LittleEndian, BitWriter};
-    /// let mut data = Vec::new();
-    /// {
-    ///     let mut writer = BitWriter::<LittleEndian>::new(&mut data);
-    ///     writer.write_signed(4, 7).unwrap();
-    ///     writer.write_signed(4, -5).unwrap();
-    /// }
-    /// assert_eq!(data, [0b10110111]);
-    /// ```
-    pub fn write_signed<S>(&mut self, bits: u32, value: S) ->
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) ->
         Result<(), io::Error> where S: SignedNumeric {
+        BitWriter::write_signed(self, bits, value)
+    }
 
-        if bits > S::bits_size() {
-            Err(io::Error::new(io::ErrorKind::InvalidInput,
-                               "excessive bits for type written"))
-        } else if value.is_negative() {
-            self.write(bits - 1, value.as_unsigned(bits))
-            .and_then(|()| self.write_bit(true))
-        } else {
-            self.write(bits - 1, value)
-            .and_then(|()| self.write_bit(false))
-        }
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), io::Error> {
+        BitWriter::write_bytes(self, buf)
+    }
+
+    #[inline]
+    fn write_unary0(&mut self, value: u32) -> Result<(), io::Error> {
+        BitWriter::write_unary0(self, value)
+    }
+
+    #[inline]
+    fn write_unary1(&mut self, value: u32) -> Result<(), io::Error> {
+        BitWriter::write_unary1(self, value)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        BitWriter::byte_aligned(self)
     }
 
+    #[inline]
+    fn byte_align(&mut self) -> Result<(), io::Error> {
+        BitWriter::byte_align(self)
+    }
 }
 
 #[inline]
-fn write_byte(writer: &mut io::Write, byte: u8) -> Result<(),io::Error> {
+fn write_byte<W>(writer: &mut W, byte: u8) -> Result<(),io::Error>
+    where W: io::Write + ?Sized {
     let buf = [byte];
     writer.write_all(&buf)
 }
 
-fn write_unaligned<E,N>(writer: &mut io::Write,
-                        acc: &mut BitQueue<E,N>,
-                        rem: &mut BitQueue<E,u8>) -> Result<(), io::Error>
-    where E:Endianness, N: Numeric {
+fn write_unaligned<W,E,N>(writer: &mut W,
+                          acc: &mut BitQueue<E,N>,
+                          rem: &mut BitQueue<E,u8>) -> Result<(), io::Error>
+    where W: io::Write + ?Sized, E: Endianness, N: Numeric {
 
     if rem.is_empty() {
         Ok(())
     } else {
-        use std::cmp::min;
+        use core::cmp::min;
         let bits_to_transfer = min(8 - rem.len(), acc.len());
         rem.push(bits_to_transfer, acc.pop(bits_to_transfer).to_u8());
         if rem.len() == 8 {
@@ -576,15 +1698,15 @@ fn write_unaligned<E,N>(writer: &mut io::Write,
     }
 }
 
-fn write_aligned<E,N>(writer: &mut io::Write,
-                      acc: &mut BitQueue<E,N>) -> Result<(), io::Error>
-    where E: Endianness, N: Numeric {
+fn write_aligned<W,E,N>(writer: &mut W,
+                        acc: &mut BitQueue<E,N>) -> Result<(), io::Error>
+    where W: io::Write + ?Sized, E: Endianness, N: Numeric {
 
     let to_write = (acc.len() / 8) as usize;
     if to_write > 0 {
-        // 64-bit types are the maximum supported
-        debug_assert!(to_write <= 8);
-        let mut buf = [0; 8];
+        // 128-bit types are the maximum supported
+        debug_assert!(to_write <= 16);
+        let mut buf = [0; 16];
         for b in buf[0..to_write].iter_mut() {
             *b = acc.pop(8).to_u8();
         }
@@ -0,0 +1,87 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Functions for zigzag mapping signed values to unsigned values.
+//!
+//! Zigzag mapping interleaves positive and negative values
+//! so that values of small magnitude map to small unsigned values:
+//! 0 becomes 0, -1 becomes 1, 1 becomes 2, -2 becomes 3, and so on.
+//! This makes signed values suitable for unsigned integer codes
+//! such as Exp-Golomb or Rice codes.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Write, Read, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::zigzag;
+//!
+//! let mut data = Vec::new();
+//! {
+//!     let mut writer = BitWriter::<BigEndian>::new(&mut data);
+//!     writer.write_exp_golomb(0, zigzag::encode(32, -2)).unwrap();
+//!     writer.byte_align().unwrap();
+//! }
+//! assert_eq!(data, [0b00100_000]);
+//!
+//! let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+//! let value = reader.read_exp_golomb(0).unwrap();
+//! assert_eq!(zigzag::decode(32, value), -2);
+//! ```
+
+#![warn(missing_docs)]
+
+/// Maps a signed value of the given number of bits
+/// to an unsigned value of the same number of bits.
+///
+/// The value must fit in the given number of bits
+/// as a twos-complement signed value,
+/// and the number of bits must be between 1 and 64.
+///
+/// # Examples
+/// ```
+/// use bitstream_io::zigzag::encode;
+/// assert_eq!(encode(8, 0), 0);
+/// assert_eq!(encode(8, -1), 1);
+/// assert_eq!(encode(8, 1), 2);
+/// assert_eq!(encode(8, -128), 255);
+/// assert_eq!(encode(8, 127), 254);
+/// assert_eq!(encode(64, i64::MIN), u64::MAX);
+/// ```
+#[inline]
+pub const fn encode(bits: u32, value: i64) -> u64 {
+    debug_assert!(bits >= 1 && bits <= 64);
+    debug_assert!((bits == 64) ||
+                  ((value >= (-1 << (bits - 1))) &&
+                   (value < (1 << (bits - 1)))));
+    let mapped = ((value << 1) ^ (value >> (bits - 1))) as u64;
+    if bits < 64 {mapped & ((1 << bits) - 1)} else {mapped}
+}
+
+/// Maps an unsigned value of the given number of bits
+/// back to a signed value of the same number of bits.
+///
+/// The value must fit in the given number of bits,
+/// and the number of bits must be between 1 and 64.
+///
+/// # Examples
+/// ```
+/// use bitstream_io::zigzag::decode;
+/// assert_eq!(decode(8, 0), 0);
+/// assert_eq!(decode(8, 1), -1);
+/// assert_eq!(decode(8, 2), 1);
+/// assert_eq!(decode(8, 255), -128);
+/// assert_eq!(decode(8, 254), 127);
+/// assert_eq!(decode(64, u64::MAX), i64::MIN);
+/// ```
+#[inline]
+pub const fn decode(bits: u32, value: u64) -> i64 {
+    debug_assert!(bits >= 1 && bits <= 64);
+    debug_assert!((bits == 64) || (value < (1 << bits)));
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
@@ -0,0 +1,82 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "num-bigint")]
+
+extern crate bitstream_io;
+extern crate num_bigint;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use num_bigint::{BigInt, BigUint};
+use std::io::Cursor;
+
+macro_rules! define_bigint_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            /*fixed-width reads and writes agree with big ones*/
+            let mut output: Vec<u8> = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut output);
+                for bits in 0..=64u32 {
+                    let value = if bits == 0 {0} else {u64::MAX >> (64 - bits)};
+                    writer.write_biguint(bits, &BigUint::from(value)).unwrap();
+                    if bits > 0 {
+                        writer.write(bits, value).unwrap();
+                        writer.write_bigint(bits, &BigInt::from(-1i32)).unwrap();
+                    }
+                }
+                writer.byte_align().unwrap();
+            }
+            let c = Cursor::new(output);
+            let mut reader = BitReader::<$endianness>::new(Box::new(c));
+            for bits in 0..=64u32 {
+                let value = if bits == 0 {0} else {u64::MAX >> (64 - bits)};
+                if bits > 0 {
+                    assert_eq!(reader.read::<u64>(bits).unwrap(), value);
+                    assert_eq!(reader.read_biguint(bits).unwrap(),
+                               BigUint::from(value));
+                    assert_eq!(reader.read::<u64>(bits).unwrap(), value);
+                } else {
+                    assert_eq!(reader.read_biguint(0).unwrap(),
+                               BigUint::from(0u32));
+                }
+            }
+
+            /*values wider than any fixed-width type*/
+            let big = (BigUint::from(0x1234_5678u32) << 200u32) + 0xABCDu32;
+            let mut output: Vec<u8> = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut output);
+                writer.write(3, 0b101u8).unwrap();
+                writer.write_biguint(229, &big).unwrap();
+                writer.write_bigint(257, &-BigInt::from(big.clone())).unwrap();
+                let min = -(BigInt::from(1i32) << 199u32);
+                let max = (BigInt::from(1i32) << 199u32) - 1;
+                writer.write_bigint(200, &min).unwrap();
+                writer.write_bigint(200, &max).unwrap();
+                assert!(writer.write_bigint(200, &(min - 1)).is_err());
+                assert!(writer.write_bigint(200, &(max + 1)).is_err());
+                assert!(writer.write_biguint(228, &big).is_err());
+                assert!(writer.write_bigint(0, &BigInt::from(-1)).is_err());
+                writer.byte_align().unwrap();
+            }
+            let c = Cursor::new(output);
+            let mut reader = BitReader::<$endianness>::new(Box::new(c));
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            assert_eq!(reader.read_biguint(229).unwrap(), big);
+            assert_eq!(reader.read_bigint(257).unwrap(), -BigInt::from(big));
+            assert_eq!(reader.read_bigint(200).unwrap(),
+                       -(BigInt::from(1i32) << 199u32));
+            assert_eq!(reader.read_bigint(200).unwrap(),
+                       (BigInt::from(1i32) << 199u32) - 1);
+        }
+    }
+}
+
+define_bigint_roundtrip!(test_bigint_roundtrip_be, BigEndian);
+define_bigint_roundtrip!(test_bigint_roundtrip_le, LittleEndian);
@@ -0,0 +1,91 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[macro_use]
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter,
+                   BitRead, BitWrite, ConstBits};
+use std::io::Cursor;
+
+define_bitfields! {
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    struct Status(u8) {
+        ready, set_ready: 7..8;
+        mode, set_mode: 4..7;
+        count, set_count: 0..4
+    }
+}
+
+define_bitfields! {
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    pub struct Wide(u64) {
+        all, set_all: 0..64;
+        high, set_high: 32..64;
+        low, set_low: 0..32;
+    }
+}
+
+#[test]
+fn test_bitfields_accessors() {
+    let mut status = Status::default();
+    status.set_ready(1);
+    status.set_mode(0b101);
+    status.set_count(0b1001);
+    assert_eq!(status, Status(0b1101_1001));
+    assert_eq!(status.ready(), 1);
+    assert_eq!(status.mode(), 0b101);
+    assert_eq!(status.count(), 0b1001);
+
+    /*excess bits are discarded without touching other fields*/
+    status.set_mode(0b1010);
+    assert_eq!(status, Status(0b1010_1001));
+
+    let mut wide = Wide(0x0123_4567_89AB_CDEF);
+    assert_eq!(wide.all(), 0x0123_4567_89AB_CDEF);
+    assert_eq!(wide.high(), 0x0123_4567);
+    assert_eq!(wide.low(), 0x89AB_CDEF);
+    wide.set_low(u64::MAX);
+    assert_eq!(wide, Wide(0x0123_4567_FFFF_FFFF));
+    wide.set_all(0);
+    assert_eq!(wide, Wide(0));
+
+    assert_eq!(Status::BITS, 8);
+    assert_eq!(Wide::BITS, 64);
+}
+
+macro_rules! define_bitfields_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let fields = [Status(0b1101_1001), Status(0), Status(0xFF)];
+            let wide = Wide(0x0123_4567_89AB_CDEF);
+
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                for status in fields.iter() {
+                    writer.build(status).unwrap();
+                }
+                writer.build(&wide).unwrap();
+            }
+            assert_eq!(&data[0..3], [0b1101_1001, 0, 0xFF]);
+            assert_eq!(data.len(), 11);
+
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+            for status in fields.iter() {
+                assert_eq!(&reader.parse::<Status>().unwrap(), status);
+            }
+            assert_eq!(reader.parse::<Wide>().unwrap(), wide);
+            assert!(reader.parse::<Status>().is_err());
+        }
+    }
+}
+
+define_bitfields_roundtrip!(test_bitfields_roundtrip_be, BigEndian);
+define_bitfields_roundtrip!(test_bitfields_roundtrip_le, LittleEndian);
@@ -0,0 +1,68 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::bitplane::{read_planes, write_planes};
+use std::io::Cursor;
+
+macro_rules! define_bitplane_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let samples: Vec<u16> =
+                (0..1000u32).map(|i| (i.wrapping_mul(2654435761) >> 20) as u16)
+                .collect();
+            let mut output: Vec<u8> = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut output);
+                write_planes(&mut writer, 12, &samples).unwrap();
+                writer.byte_align().unwrap();
+            }
+            assert_eq!(output.len(), 1500);
+            {
+                let c = Cursor::new(output);
+                let mut reader = BitReader::<$endianness>::new(Box::new(c));
+                let mut read_samples = vec![0xFFFFu16; samples.len()];
+                read_planes(&mut reader, 12, &mut read_samples).unwrap();
+                assert_eq!(read_samples, samples);
+            }
+
+            /*signed samples are stored as full-width twos-complement*/
+            let samples: Vec<i8> = (-128..128).map(|i| i as i8).collect();
+            let mut output: Vec<u8> = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut output);
+                write_planes(&mut writer, 8, &samples).unwrap();
+            }
+            assert_eq!(&output[0..16], &[0xFF; 16]);
+            assert_eq!(&output[16..32], &[0x00; 16]);
+            {
+                let c = Cursor::new(output);
+                let mut reader = BitReader::<$endianness>::new(Box::new(c));
+                let mut read_samples = vec![0i8; samples.len()];
+                read_planes(&mut reader, 8, &mut read_samples).unwrap();
+                assert_eq!(read_samples, samples);
+            }
+        }
+    }
+}
+
+define_bitplane_roundtrip!(test_bitplane_roundtrip_be, BigEndian);
+define_bitplane_roundtrip!(test_bitplane_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_bitplane_errors() {
+    let mut output: Vec<u8> = Vec::new();
+    let mut writer = BitWriter::<BigEndian>::new(&mut output);
+    assert!(write_planes(&mut writer, 9, &[0u8]).is_err());
+    assert!(write_planes(&mut writer, 3, &[1u8, 8u8]).is_err());
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new([0])));
+    assert!(read_planes(&mut reader, 17, &mut [0u16]).is_err());
+    assert!(read_planes(&mut reader, 8, &mut [0u8, 0u8]).is_err());
+}
@@ -0,0 +1,74 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "bitvec")]
+
+extern crate bitvec;
+extern crate bitstream_io;
+
+use bitvec::prelude::*;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::io::Cursor;
+
+macro_rules! define_bitvec_roundtrip {
+    ($func_name:ident, $endianness:ident, $order:ident) => {
+        #[test]
+        fn $func_name() {
+            let pattern = bits![u8, $order; 1, 1, 0, 1, 0, 0, 0, 1,
+                                            1, 0, 1, 1, 1];
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                writer.write(3, 0b101u8).unwrap();
+                writer.write_bitslice(pattern).unwrap();
+                writer.byte_align().unwrap();
+            }
+
+            let mut reader = BitReader::<$endianness>::new(
+                Box::new(Cursor::new(data.clone())));
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            let bits: BitVec<u8, $order> = reader.read_bitvec(13).unwrap();
+            assert_eq!(bits.as_bitslice(), pattern);
+
+            /*draining the stream takes its partial first byte too*/
+            let mut reader =
+                BitReader::<$endianness, _>::from_read(&data[..]);
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            let bits: BitVec<u8, $order> =
+                reader.read_to_end_bitvec().unwrap();
+            assert_eq!(bits.as_bitslice(), pattern);
+
+            /*a slice's bits read back in the same order*/
+            let mut reader =
+                BitReader::<$endianness, _>::from_bitslice(pattern);
+            for bit in pattern.iter().by_vals() {
+                assert_eq!(reader.read_bit().unwrap(), bit);
+            }
+            /*the final byte is padded*/
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0);
+            assert!(reader.read_bit().is_err());
+
+            /*a matching bit order shares the stream's layout*/
+            let stored = data.view_bits::<$order>();
+            assert_eq!(&stored[3..16], pattern);
+        }
+    }
+}
+
+define_bitvec_roundtrip!(test_bitvec_roundtrip_be, BigEndian, Msb0);
+define_bitvec_roundtrip!(test_bitvec_roundtrip_le, LittleEndian, Lsb0);
+
+#[test]
+fn test_bitvec_mismatched_order() {
+    /*the slice's order only affects its storage, not the bits read*/
+    let pattern = bits![u16, Lsb0; 0, 0, 1, 0, 1, 1, 1, 1, 0];
+    let mut reader = BitReader::<BigEndian, _>::from_bitslice(pattern);
+    assert_eq!(reader.read::<u16>(9).unwrap(), 0b0_0101_1110);
+    let mut reader = BitReader::<LittleEndian, _>::from_bitslice(pattern);
+    assert_eq!(reader.read::<u16>(9).unwrap(), 0b0_1111_0100);
+}
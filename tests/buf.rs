@@ -0,0 +1,71 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "bytes")]
+
+extern crate bitstream_io;
+extern crate bytes;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::buf::{BufSink, BufSource};
+use bytes::{Buf, Bytes, BytesMut};
+use std::io;
+
+macro_rules! define_buf_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let mut data = BytesMut::new();
+            {
+                let mut sink = BufSink::new(&mut data);
+                let mut writer = BitWriter::<$endianness>::new(&mut sink);
+                writer.write(3, 0b101u8).unwrap();
+                writer.write_signed(21, -123_456i32).unwrap();
+                writer.write_bytes(b"payload").unwrap();
+            }
+            assert_eq!(data.len(), 10);
+
+            /*split the data across several buffers*/
+            let data = data.freeze();
+            let chain = data.slice(0..1)
+                .chain(data.slice(1..5))
+                .chain(data.slice(5..));
+            let mut reader =
+                BitReader::<$endianness, _>::from_buf(chain);
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            assert_eq!(reader.read_signed::<i32>(21).unwrap(), -123_456);
+            let mut payload = [0; 7];
+            reader.read_bytes(&mut payload).unwrap();
+            assert_eq!(&payload, b"payload");
+            assert_eq!(reader.read_bit().unwrap_err().kind(),
+                       io::ErrorKind::UnexpectedEof);
+        }
+    }
+}
+
+define_buf_roundtrip!(test_buf_roundtrip_be, BigEndian);
+define_buf_roundtrip!(test_buf_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_buf_adaptors() {
+    use std::io::{Read, Write};
+
+    /*reading advances the underlying buffer*/
+    let mut source = BufSource::new(Bytes::from_static(b"abcdef"));
+    let mut buf = [0; 4];
+    source.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"abcd");
+    assert_eq!(source.get_ref().remaining(), 2);
+    assert_eq!(&source.into_inner()[..], b"ef");
+
+    /*writing past a fixed-size buffer's end fails*/
+    let mut out = [0; 3];
+    let mut sink = BufSink::new(&mut out[..]);
+    assert_eq!(sink.write_all(b"xyzw").unwrap_err().kind(),
+               io::ErrorKind::WriteZero);
+    assert_eq!(&out, b"xyz");
+}
@@ -0,0 +1,59 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+use bitstream_io::{BigEndian, LittleEndian, BitWriter};
+use bitstream_io::builder::BitStreamBuilder;
+
+#[test]
+fn test_builder_matches_writer() {
+    let mut expected = Vec::new();
+    {
+        let mut w = BitWriter::<LittleEndian, _>::from_write(&mut expected);
+        w.write_bit(true).unwrap();
+        w.write(13, 0x1234u16).unwrap();
+        w.write_signed(7, -5i8).unwrap();
+        w.write_bytes(b"xyz").unwrap();
+        w.write_unary1(9).unwrap();
+        w.byte_align().unwrap();
+        w.write(64, u64::MAX).unwrap();
+    }
+
+    let builder = BitStreamBuilder::<LittleEndian>::new()
+        .bit(true)
+        .bits(13, 0x1234)
+        .signed(7, -5)
+        .bytes(b"xyz")
+        .unary1(9);
+    assert_eq!(builder.len_bits(), 55);
+    let (bytes, trailing) = builder.align().bits(64, u64::MAX).build();
+    assert_eq!(bytes, expected);
+    assert_eq!(trailing, 0);
+}
+
+#[test]
+fn test_builder_trailing() {
+    /*a partial final byte is padded and its used bits counted*/
+    let (bytes, trailing) = BitStreamBuilder::<BigEndian>::new()
+        .bits(12, 0xABC)
+        .build();
+    assert_eq!(bytes, [0xAB, 0xC0]);
+    assert_eq!(trailing, 4);
+
+    assert_eq!(BitStreamBuilder::<BigEndian>::default().build(),
+               (vec![], 0));
+}
+
+#[test]
+#[should_panic(expected = "unable to build stream")]
+fn test_builder_oversized() {
+    BitStreamBuilder::<BigEndian>::new().bits(3, 8);
+}
@@ -0,0 +1,62 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+use std::collections::VecDeque;
+use std::io::Read;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::chunks::ChunkSource;
+
+#[test]
+fn test_chunks_match_contiguous() {
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<LittleEndian, _>::from_write(&mut data);
+        w.write(3, 0b101u8).unwrap();
+        w.write_signed(21, -123_456i32).unwrap();
+        w.write_bytes(b"payload").unwrap();
+        w.write(64, 0x0123_4567_89AB_CDEFu64).unwrap();
+    }
+
+    /*every way of splitting the data reads the same values*/
+    for split in 1..data.len() {
+        let chunks: VecDeque<Vec<u8>> =
+            data.chunks(split).map(|chunk| chunk.to_vec()).collect();
+        let mut r = BitReader::<LittleEndian, _>::from_chunks(chunks);
+        assert_eq!(r.read::<u8>(3).unwrap(), 0b101);
+        assert_eq!(r.read_signed::<i32>(21).unwrap(), -123_456);
+        let mut buf = [0; 7];
+        r.read_bytes(&mut buf).unwrap();
+        assert_eq!(&buf, b"payload");
+        assert_eq!(r.read::<u64>(64).unwrap(), 0x0123_4567_89AB_CDEF);
+        assert!(r.read_bit().is_err());
+    }
+}
+
+#[test]
+fn test_chunk_source() {
+    let chunks: Vec<&[u8]> = vec![&[], &[1, 2], &[], &[], &[3], &[4, 5, 6]];
+    let mut source = ChunkSource::new(&chunks);
+    let mut buf = [0; 4];
+    assert_eq!(source.read(&mut buf).unwrap(), 4);
+    assert_eq!(buf, [1, 2, 3, 4]);
+    assert_eq!(source.position(), (5, 1));
+    assert_eq!(source.read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf[..2], &[5, 6]);
+    assert_eq!(source.read(&mut buf).unwrap(), 0);
+
+    /*buffered skipping discards whole chunks at a time*/
+    let chunks = [vec![0xFF; 100], vec![0x0F; 100]];
+    let mut r = BitReader::<BigEndian, _>::from_chunks(&chunks[..]);
+    r.skip_buffered(804).unwrap();
+    assert_eq!(r.read::<u8>(4).unwrap(), 0xF);
+    assert_eq!(r.into_byte_reader().position(), (1, 1));
+}
@@ -0,0 +1,101 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "tokio-util")]
+
+extern crate bytes;
+extern crate tokio_util;
+extern crate bitstream_io;
+
+use std::io;
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+use bitstream_io::{BigEndian, LittleEndian, BitRead, BitWrite,
+                   FromBitStream, ToBitStream};
+use bitstream_io::codec::BitCodec;
+
+#[derive(Debug, PartialEq)]
+struct Message {
+    tag: u8,
+    payload: Vec<u8>,
+}
+
+impl FromBitStream for Message {
+    type Error = io::Error;
+
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+        let tag = r.read(4)?;
+        if tag == 0xF {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "reserved tag"));
+        }
+        let length: u8 = r.read(5)?;
+        let mut payload = vec![0; length as usize];
+        r.read_bytes(&mut payload)?;
+        Ok(Message{tag, payload})
+    }
+}
+
+impl ToBitStream for Message {
+    type Error = io::Error;
+
+    fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write(4, self.tag)?;
+        w.write(5, self.payload.len() as u8)?;
+        w.write_bytes(&self.payload)
+    }
+}
+
+macro_rules! define_codec_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let frames = vec![Message{tag: 3, payload: vec![1, 2, 3]},
+                              Message{tag: 0, payload: vec![]},
+                              Message{tag: 9, payload: vec![0xFF; 20]}];
+            let mut codec = BitCodec::<$endianness, Message>::new();
+            let mut encoded = BytesMut::new();
+            for frame in frames.iter() {
+                codec.encode(Message{tag: frame.tag,
+                                     payload: frame.payload.clone()},
+                             &mut encoded).unwrap();
+            }
+            /*feed the decoder one byte at a time*/
+            let mut buf = BytesMut::new();
+            let mut decoded = Vec::new();
+            for byte in encoded.iter() {
+                buf.extend_from_slice(&[*byte]);
+                while let Some(frame) = codec.decode(&mut buf).unwrap() {
+                    assert_eq!(codec.frame_bits(),
+                               9 + frame.payload.len() as u64 * 8);
+                    decoded.push(frame);
+                }
+            }
+            assert_eq!(decoded, frames);
+            assert!(buf.is_empty());
+        }
+    }
+}
+
+define_codec_roundtrip!(test_codec_roundtrip_be, BigEndian);
+define_codec_roundtrip!(test_codec_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_codec_errors() {
+    let mut codec = BitCodec::<BigEndian, Message>::new();
+
+    /*an empty buffer is not an error*/
+    let mut buf = BytesMut::new();
+    assert!(codec.decode(&mut buf).unwrap().is_none());
+
+    /*a parse error with the whole frame present is passed along*/
+    let mut buf = BytesMut::from(&[0xF0, 0x00][..]);
+    assert_eq!(codec.decode(&mut buf).unwrap_err().kind(),
+               io::ErrorKind::InvalidData);
+    assert_eq!(buf.len(), 2);
+}
@@ -0,0 +1,85 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter,
+                   BitRead};
+use bitstream_io::combinators::{unsigned, signed, flag, tag, map, pair,
+                                count, length_prefixed, many_till};
+use std::io::{self, Cursor};
+
+#[derive(Debug, PartialEq)]
+struct Point {
+    x: i16,
+    y: i16,
+}
+
+fn point<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Point> {
+    map(pair(signed(10), signed(10)), |(x, y)| Point{x, y})(r)
+}
+
+macro_rules! define_combinators_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                writer.write(12, 0xABCu16).unwrap();
+                writer.write(5, 3u8).unwrap();
+                for (x, y) in [(-512, 511), (0, 1), (7, -7)].iter() {
+                    writer.write_signed(10, *x as i16).unwrap();
+                    writer.write_signed(10, *y as i16).unwrap();
+                }
+                for value in [1u8, 2, 3].iter() {
+                    writer.write_bit(false).unwrap();
+                    writer.write(6, *value).unwrap();
+                }
+                writer.write_bit(true).unwrap();
+                writer.write(3, 5u8).unwrap();
+                writer.write(3, 6u8).unwrap();
+                writer.byte_align().unwrap();
+            }
+
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+            assert_eq!(tag(12, 0xABCu16)(&mut reader).unwrap(), 0xABC);
+            assert_eq!(length_prefixed(5, point)(&mut reader).unwrap(),
+                       vec![Point{x: -512, y: 511},
+                            Point{x: 0, y: 1},
+                            Point{x: 7, y: -7}]);
+            assert_eq!(many_till(unsigned::<_, u8>(6), flag())(&mut reader)
+                       .unwrap(),
+                       vec![1, 2, 3]);
+            assert_eq!(count(unsigned::<_, u8>(3), 2)(&mut reader).unwrap(),
+                       vec![5, 6]);
+        }
+    }
+}
+
+define_combinators_roundtrip!(test_combinators_roundtrip_be, BigEndian);
+define_combinators_roundtrip!(test_combinators_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_combinators_errors() {
+    let data = [0xFF, 0xE0];
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+
+    /*a mismatched tag is invalid data, but its bits are consumed*/
+    assert_eq!(tag(4, 0u8)(&mut reader).unwrap_err().kind(),
+               io::ErrorKind::InvalidData);
+    assert_eq!(unsigned::<_, u8>(4)(&mut reader).unwrap(), 0xF);
+
+    /*errors from the items are passed along*/
+    assert_eq!(length_prefixed(3, unsigned::<_, u8>(8))(&mut reader)
+               .unwrap_err().kind(),
+               io::ErrorKind::UnexpectedEof);
+    assert_eq!(many_till(unsigned::<_, u8>(1), flag())(&mut reader)
+               .unwrap_err().kind(),
+               io::ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,22 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*fixtures shared by the tests and benchmarks*/
+
+use bitstream_io::BigEndian;
+use bitstream_io::io::Read;
+use bitstream_io::random::{Pattern, RandomSource};
+
+/*a reproducible, uniformly random byte stream*/
+pub fn noise(len: usize, seed: u64) -> Vec<u8> {
+    let mut data = vec![0; len];
+    RandomSource::<BigEndian>::new(seed, Pattern::Uniform)
+        .read_exact(&mut data)
+        .unwrap();
+    data
+}
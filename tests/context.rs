@@ -0,0 +1,107 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+use std::error::Error;
+use std::io;
+use bitstream_io::{BigEndian, LittleEndian, BitRead, BitReader,
+                   FromBitStream};
+use bitstream_io::context::{ContextError, ContextReader};
+
+#[derive(Debug)]
+struct Header {
+    sync: u16,
+    channels: u8,
+    samples: u32,
+}
+
+impl FromBitStream for Header {
+    type Error = io::Error;
+
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+        let sync = r.read(12)?;
+        let channels = r.read(4)?;
+        if channels == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "no channels"));
+        }
+        Ok(Header{sync, channels, samples: r.read(32)?})
+    }
+}
+
+#[test]
+fn test_context_position() {
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+    let mut r = ContextReader::new(
+        BitReader::<LittleEndian, _>::from_read(&actual_data[..]));
+    assert_eq!(r.read::<u8>(2).unwrap(), 1);
+    assert_eq!(r.read_signed::<i8>(3).unwrap(), -4);
+    assert!(r.read_bit().unwrap());
+    r.skip(3).unwrap();
+    assert_eq!(r.read_unary1().unwrap(), 1);
+    assert_eq!(r.position(), 11);
+    r.byte_align();
+    assert_eq!(r.position(), 16);
+    let mut buf = [0; 2];
+    r.read_bytes(&mut buf).unwrap();
+    assert_eq!(r.position(), 32);
+
+    /*failed reads don't advance*/
+    let err = r.read::<u8>(1).unwrap_err();
+    assert_eq!(r.position(), 32);
+    assert_eq!(ContextError::find(&err).unwrap().position(), 32);
+    assert_eq!(ContextError::find(&err).unwrap().label(), None);
+    assert_eq!(err.to_string(), "failed to fill whole buffer at bit 32");
+}
+
+#[test]
+fn test_context_parse() {
+    /*errors from inside a parser keep their kind and gain a location*/
+    let data = [0xFF, 0xF2, 0x00, 0x01];
+    let mut r = ContextReader::new(
+        BitReader::<BigEndian, _>::from_read(&data[..]));
+    r.set_label("header");
+    let err = r.parse::<Header>().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    let context = ContextError::find(&err).unwrap();
+    assert_eq!(context.position(), 16);
+    assert_eq!(context.label(), Some("header"));
+    assert_eq!(context.get_ref().kind(), io::ErrorKind::UnexpectedEof);
+    assert!(context.source().is_some());
+    assert_eq!(err.to_string(),
+               "failed to fill whole buffer at bit 16 (header)");
+
+    /*but errors raised by the parser itself are its own*/
+    let data = [0xFF, 0xF0, 0x00];
+    let mut r = ContextReader::new(
+        BitReader::<BigEndian, _>::from_read(&data[..]));
+    let err = r.parse::<Header>().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(ContextError::find(&err).is_none());
+}
+
+#[test]
+fn test_context_labels() {
+    let data = [0xFF, 0xF2, 0x00, 0x00, 0x00, 0x01];
+    let mut r = ContextReader::new(
+        BitReader::<BigEndian, _>::from_read(&data[..]));
+    r.set_label("sync");
+    assert_eq!(r.label(), Some("sync"));
+    let header = r.parse::<Header>().unwrap();
+    assert_eq!(header.sync, 0xFFF);
+    assert_eq!(header.channels, 2);
+    assert_eq!(header.samples, 1);
+    r.clear_label();
+    assert_eq!(r.label(), None);
+
+    let inner = r.into_inner();
+    assert!(inner.byte_aligned());
+}
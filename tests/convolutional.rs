@@ -0,0 +1,86 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "convolutional")]
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::convolutional::{Convolutional, ConvolutionalWriter,
+                                  ViterbiReader};
+use std::io::Cursor;
+
+#[test]
+fn test_convolutional_invalid() {
+    let mut data = Vec::new();
+    let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    for code in [Convolutional{constraint: 1, polys: [1, 1]},
+                 Convolutional{constraint: 17, polys: [1, 1]},
+                 Convolutional{constraint: 3, polys: [0, 7]},
+                 Convolutional{constraint: 3, polys: [5, 8]}].iter() {
+        assert!(ConvolutionalWriter::new(&mut writer, code).is_err());
+    }
+}
+
+macro_rules! define_viterbi_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let codes = [Convolutional::nasa(),
+                         Convolutional{constraint: 3, polys: [0b111, 0b101]},
+                         Convolutional{constraint: 9,
+                                       polys: [0o753, 0o561]}];
+            let values: Vec<u64> = (0..40u64)
+                .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 41)
+                .collect();
+
+            for code in codes.iter() {
+                let mut data = Vec::new();
+                {
+                    let mut writer = BitWriter::<$endianness>::new(&mut data);
+                    writer.write(5, 0b10110).unwrap();
+                    {
+                        let mut encoder =
+                            ConvolutionalWriter::new(&mut writer, code)
+                            .unwrap();
+                        for value in values.iter() {
+                            encoder.write(23, *value).unwrap();
+                        }
+                        encoder.finish().unwrap();
+                    }
+                    writer.write(7, 0b1100101).unwrap();
+                    writer.byte_align().unwrap();
+                }
+
+                /*flip isolated bits in the coded block*/
+                for (i, byte) in data.iter_mut().enumerate() {
+                    if (i > 0) && (i % 6 == 0) {
+                        *byte ^= 0x08;
+                    }
+                }
+
+                let mut reader = BitReader::<$endianness>::new(
+                    Box::new(Cursor::new(data)));
+                assert_eq!(reader.read::<u8>(5).unwrap(), 0b10110);
+                {
+                    let mut decoder = ViterbiReader::new(
+                        &mut reader, code, values.len() as u64 * 23).unwrap();
+                    for value in values.iter() {
+                        assert_eq!(decoder.read(23).unwrap(), *value);
+                    }
+                    assert_eq!(decoder.remaining(), 0);
+                    assert!(decoder.read_bit().is_err());
+                }
+                /*the decoder stops reading at the end of the block*/
+                assert_eq!(reader.read::<u8>(7).unwrap(), 0b1100101);
+            }
+        }
+    }
+}
+
+define_viterbi_roundtrip!(test_viterbi_roundtrip_be, BigEndian);
+define_viterbi_roundtrip!(test_viterbi_roundtrip_le, LittleEndian);
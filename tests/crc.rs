@@ -0,0 +1,74 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::crc::{Crc, CrcReader, CrcWriter};
+use std::io::Cursor;
+
+#[test]
+fn test_crc_check_values() {
+    let data = b"123456789";
+    assert_eq!(Crc::crc8().checksum::<BigEndian>(data, 0, 72).unwrap(),
+               0xF4);
+    assert_eq!(Crc::crc16_ccitt().checksum::<BigEndian>(data, 0, 72)
+               .unwrap(), 0x29B1);
+    assert_eq!(Crc::crc32_mpeg2().checksum::<BigEndian>(data, 0, 72)
+               .unwrap(), 0x0376E6E7);
+    assert_eq!(Crc::crc32().checksum::<LittleEndian>(data, 0, 72).unwrap(),
+               0xCBF43926);
+    assert!(Crc::crc8().checksum::<BigEndian>(data, 1, 72).is_err());
+    assert!(Crc::crc8().checksum::<BigEndian>(data, u64::MAX, 1).is_err());
+}
+
+macro_rules! define_crc_unaligned {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let crc = Crc::crc16_ccitt();
+            let fields: [(u32, u64); 6] =
+                [(3, 0b101), (11, 0x5A5), (1, 1), (17, 0x1_2345),
+                 (8, 0xC3), (2, 0b10)];
+
+            /*CRC every field except the first*/
+            let mut data = Vec::new();
+            let written = {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                writer.write(fields[0].0, fields[0].1).unwrap();
+                let checksum = {
+                    let mut crc_writer = CrcWriter::new(&mut writer, &crc);
+                    for &(bits, value) in fields[1..].iter() {
+                        crc_writer.write(bits, value).unwrap();
+                    }
+                    crc_writer.checksum()
+                };
+                writer.byte_align().unwrap();
+                checksum
+            };
+            let total: u32 = fields[1..].iter().map(|f| f.0).sum();
+            assert_eq!(crc.checksum::<$endianness>(&data,
+                                                   u64::from(fields[0].0),
+                                                   u64::from(total))
+                       .unwrap(),
+                       written);
+
+            /*bit-at-a-time reads produce the same CRC*/
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+            assert_eq!(reader.read::<u64>(fields[0].0).unwrap(), fields[0].1);
+            let mut crc_reader = CrcReader::new(&mut reader, &crc);
+            for _ in 0..total {
+                crc_reader.read_bit().unwrap();
+            }
+            assert_eq!(crc_reader.checksum(), written);
+        }
+    }
+}
+
+define_crc_unaligned!(test_crc_unaligned_be, BigEndian);
+define_crc_unaligned!(test_crc_unaligned_le, LittleEndian);
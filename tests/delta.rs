@@ -0,0 +1,82 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::delta::{DeltaReader, DeltaWriter, Order, Residual};
+use std::io::Cursor;
+
+macro_rules! define_delta_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let ramp: Vec<i64> = (0..100).map(|i| i * 3 - 50).collect();
+            let curve: Vec<i64> = (0..100).map(|i| i * i - 200).collect();
+            let extremes = vec![i64::MIN, i64::MAX, 0, -1, i64::MIN];
+            for order in [Order::First, Order::Second].iter() {
+                for residual in [Residual::Fixed(64),
+                                 Residual::Rice(0),
+                                 Residual::Rice(4),
+                                 Residual::Rice(63)].iter() {
+                    for values in [&ramp, &curve, &extremes].iter() {
+                        let is_extreme = values.len() == extremes.len();
+                        if is_extreme && (*residual == Residual::Rice(0) ||
+                                          *residual == Residual::Rice(4)) {
+                            // quotients would be far too large
+                            continue;
+                        }
+                        let mut output: Vec<u8> = Vec::new();
+                        {
+                            let mut writer =
+                                BitWriter::<$endianness>::new(&mut output);
+                            {
+                                let mut delta = DeltaWriter::new(
+                                    &mut writer, *order, *residual);
+                                for value in values.iter() {
+                                    delta.write(*value).unwrap();
+                                }
+                            }
+                            writer.byte_align().unwrap();
+                        }
+                        let c = Cursor::new(output);
+                        let mut reader =
+                            BitReader::<$endianness>::new(Box::new(c));
+                        let mut delta = DeltaReader::new(
+                            &mut reader, *order, *residual);
+                        for value in values.iter() {
+                            assert_eq!(delta.read().unwrap(), *value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+define_delta_roundtrip!(test_delta_roundtrip_be, BigEndian);
+define_delta_roundtrip!(test_delta_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_delta_fixed_residuals() {
+    // a second-order predictor turns a ramp into zeros
+    let mut output: Vec<u8> = Vec::new();
+    {
+        let mut writer = BitWriter::<BigEndian>::new(&mut output);
+        {
+            let mut delta = DeltaWriter::new(&mut writer,
+                                             Order::Second,
+                                             Residual::Fixed(4));
+            for value in [5, 7, 9, 11, 13, 15].iter() {
+                delta.write(*value).unwrap();
+            }
+            assert!(delta.write(100).is_err());
+        }
+        writer.byte_align().unwrap();
+    }
+    assert_eq!(output, [0b0101_0010, 0b0000_0000, 0b0000_0000]);
+}
@@ -0,0 +1,186 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "derive")]
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter,
+                   BitRead, BitWrite, FromBitStream, ToBitStream, ConstBits,
+                   Describe};
+use bitstream_io::inspect::{self, FieldDescriptor, FieldKind, FieldValue};
+use std::io::{self, Cursor};
+
+#[derive(Debug, PartialEq, FromBitStream, ToBitStream, ConstBits, Describe)]
+struct BlockHeader {
+    last_block: bool,
+    #[bits(7)]
+    block_type: u8,
+    #[bits(24)]
+    block_size: u32,
+}
+
+#[derive(Debug, PartialEq, FromBitStream, ToBitStream, ConstBits, Describe)]
+struct Streaminfo {
+    #[bits(16)]
+    minimum_block_size: u16,
+    #[bits(16)]
+    maximum_block_size: u16,
+    #[bits(24)]
+    minimum_frame_size: u32,
+    #[bits(24)]
+    maximum_frame_size: u32,
+    #[bits(20)]
+    sample_rate: u32,
+    #[bits(3)]
+    channels: u8,
+    #[bits(5)]
+    bits_per_sample: u8,
+    #[bits(36)]
+    total_samples: u64,
+    #[bytes]
+    md5: [u8; 16],
+}
+
+#[derive(Debug, PartialEq, FromBitStream, ToBitStream, ConstBits, Describe)]
+struct Metadata {
+    #[bits(32, expect = 0x664C6143)]
+    magic: u32,
+    header: BlockHeader,
+    streaminfo: Streaminfo,
+}
+
+#[derive(Debug, PartialEq, FromBitStream, ToBitStream, ConstBits, Describe)]
+struct Offsets(#[bits(4, signed)] i8, #[bits(12, signed)] i16, bool);
+
+fn metadata() -> Metadata {
+    Metadata{magic: 0x664C6143,
+             header: BlockHeader{last_block: false,
+                                 block_type: 0,
+                                 block_size: 34},
+             streaminfo: Streaminfo{minimum_block_size: 4096,
+                                    maximum_block_size: 4096,
+                                    minimum_frame_size: 1542,
+                                    maximum_frame_size: 8546,
+                                    sample_rate: 44100,
+                                    channels: 1,
+                                    bits_per_sample: 15,
+                                    total_samples: 304844,
+                                    md5: *b"\xFA\xF2i\x2F\xFD\xEC-[0\x01vO\xB8\xD8%f"}}
+}
+
+#[test]
+fn test_derive_be() {
+    let mut data = Vec::new();
+    {
+        let mut writer = BitWriter::<BigEndian>::new(&mut data);
+        writer.build(&metadata()).unwrap();
+    }
+    assert_eq!(&data[0..8], b"fLaC\x00\x00\x00\x22");
+    assert_eq!(data.len(), 42);
+
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    assert_eq!(reader.parse::<Metadata>().unwrap(), metadata());
+}
+
+#[test]
+fn test_derive_le() {
+    let mut data = Vec::new();
+    {
+        let mut writer = BitWriter::<LittleEndian>::new(&mut data);
+        metadata().to_writer(&mut writer).unwrap();
+    }
+    assert_eq!(data.len(), 42);
+
+    let mut reader =
+        BitReader::<LittleEndian>::new(Box::new(Cursor::new(data)));
+    assert_eq!(Metadata::from_reader(&mut reader).unwrap(), metadata());
+}
+
+#[test]
+fn test_derive_tuple() {
+    let offsets = Offsets(-8, -2048, true);
+    let mut data = Vec::new();
+    {
+        let mut writer = BitWriter::<BigEndian>::new(&mut data);
+        writer.build(&offsets).unwrap();
+        writer.byte_align().unwrap();
+    }
+    assert_eq!(data, [0b1000_1000, 0b0000_0000, 0b1000_0000]);
+
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    assert_eq!(reader.parse::<Offsets>().unwrap(), offsets);
+}
+
+#[test]
+fn test_derive_errors() {
+    /*an unexpected value is invalid data*/
+    let data = b"RIFF\x00\x00\x00\x22".to_vec();
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    assert_eq!(reader.parse::<Metadata>().unwrap_err().kind(),
+               io::ErrorKind::InvalidData);
+
+    /*fields too large for their bits are not written*/
+    let mut data = Vec::new();
+    let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    let header = BlockHeader{last_block: true, block_type: 128, block_size: 0};
+    assert_eq!(writer.build(&header).unwrap_err().kind(),
+               io::ErrorKind::InvalidInput);
+
+    /*truncated streams pass along their errors*/
+    let data = b"fLaC\x00\x00".to_vec();
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    assert_eq!(reader.parse::<Metadata>().unwrap_err().kind(),
+               io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_const_bits() {
+    assert_eq!(BlockHeader::BITS, 32);
+    assert_eq!(Streaminfo::BITS, 272);
+    assert_eq!(Metadata::BITS, 336);
+    assert_eq!(Offsets::BITS, 17);
+
+    let mut data = Vec::new();
+    {
+        let mut writer = BitWriter::<BigEndian>::new(&mut data);
+        writer.build(&metadata()).unwrap();
+    }
+    assert_eq!(data.len() as u32 * 8, Metadata::BITS);
+}
+
+#[test]
+fn test_describe() {
+    assert_eq!(Offsets::FIELDS,
+               &[FieldDescriptor{name: "0", kind: FieldKind::Signed(4)},
+                 FieldDescriptor{name: "1", kind: FieldKind::Signed(12)},
+                 FieldDescriptor{name: "2", kind: FieldKind::Flag}]);
+    assert_eq!(Metadata::FIELDS[1],
+               FieldDescriptor{name: "header",
+                               kind: FieldKind::Nested(BlockHeader::FIELDS)});
+    assert_eq!(Streaminfo::FIELDS[8],
+               FieldDescriptor{name: "md5", kind: FieldKind::Bytes(16)});
+
+    let mut data = Vec::new();
+    {
+        let mut writer = BitWriter::<BigEndian>::new(&mut data);
+        writer.build(&metadata()).unwrap();
+    }
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    let entries = inspect::inspect(&mut reader, Metadata::FIELDS).unwrap();
+    assert_eq!(entries.len(), 13);
+    assert_eq!(entries[2].path, "header.block_type");
+    assert_eq!(entries[2].offset, 33);
+    assert_eq!(entries[2].value, FieldValue::Unsigned(0));
+    assert_eq!(entries[8].to_string(),
+               "144: streaminfo.sample_rate (20 bits) = 44100");
+    assert_eq!(entries[12].to_string(),
+               "208: streaminfo.md5 (128 bits) = \
+                FAF2692FFDEC2D5B3001764FB8D82566");
+    assert_eq!(entries.iter().map(|e| e.bits).sum::<u64>(),
+               u64::from(Metadata::BITS));
+}
@@ -0,0 +1,82 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+
+mod common;
+
+use std::io::ErrorKind;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, Endianness};
+use bitstream_io::diff::diff;
+use common::noise;
+
+/*diffs streams with scattered changes and checks the patch's runs
+  against the bits as read, and that applying it goes either way*/
+fn check_diff<E: Endianness>() {
+    let old = noise(200, 0);
+    for (len, seed) in [(200, 1), (150, 2), (250, 3)] {
+        let changes = noise(len, seed);
+        let new = (0..len).map(|i| {
+            let byte = old.get(i).map_or(0, |&b| b);
+            if changes[i] < 0x40 {byte ^ changes[i]} else {byte}
+        }).collect::<Vec<u8>>();
+        let patch = diff::<E>(&old, &new);
+        assert_eq!(patch.target_len(), len);
+
+        let mut padded = old.clone();
+        padded.resize(len, 0);
+        let mut old_bits = BitReader::<E, _>::from_read(&padded[..]);
+        let mut new_bits = BitReader::<E, _>::from_read(&new[..]);
+        let mut ranges = patch.ranges().iter().peekable();
+        let mut differing = 0;
+        for position in 0..len as u64 * 8 {
+            while ranges.peek().is_some_and(|r| r.end <= position) {
+                ranges.next();
+            }
+            let in_patch = ranges.peek().is_some_and(|r| r.start <= position);
+            let differs = old_bits.read_bit().unwrap() !=
+                new_bits.read_bit().unwrap();
+            assert_eq!(in_patch, differs);
+            if differs {
+                differing += 1;
+            }
+        }
+        assert_eq!(patch.differing_bits(), differing);
+        assert!(patch.ranges().windows(2).all(|w| w[0].end < w[1].start));
+
+        let mut data = padded.clone();
+        patch.apply(&mut data).unwrap();
+        assert_eq!(data, new);
+        patch.apply(&mut data).unwrap();
+        assert_eq!(data, padded);
+    }
+}
+
+#[test]
+fn test_diff_be() {
+    check_diff::<BigEndian>();
+}
+
+#[test]
+fn test_diff_le() {
+    check_diff::<LittleEndian>();
+}
+
+#[test]
+fn test_diff_identical() {
+    let data = noise(64, 7);
+    let patch = diff::<BigEndian>(&data, &data);
+    assert!(patch.is_empty());
+    assert_eq!(patch.differing_bits(), 0);
+
+    let mut copy = data.clone();
+    patch.apply(&mut copy).unwrap();
+    assert_eq!(copy, data);
+    assert_eq!(patch.apply(&mut copy[1..]).unwrap_err().kind(),
+               ErrorKind::InvalidInput);
+}
@@ -0,0 +1,99 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::elias_fano::EliasFano;
+use std::io::Cursor;
+
+fn sequences() -> Vec<(u64, Vec<u64>)> {
+    let mut sparse = Vec::new();
+    let mut value = 0u64;
+    for i in 0..500u64 {
+        value += (i * 7919) % 1000;
+        sparse.push(value);
+    }
+    let dense: Vec<u64> = (0..300).map(|i| i / 3).collect();
+    vec![(0, vec![]),
+         (1, vec![0, 0, 0]),
+         (value + 1, sparse),
+         (100, dense),
+         (u64::MAX, vec![0, 1 << 40, u64::MAX - 1]),
+         (1 << 20, vec![(1 << 20) - 1])]
+}
+
+macro_rules! define_elias_fano_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            for (universe, values) in sequences() {
+                let sequence =
+                    EliasFano::from_values(universe, &values).unwrap();
+                assert_eq!(sequence.len(), values.len());
+                assert_eq!(sequence.iter().collect::<Vec<u64>>(), values);
+
+                let mut output: Vec<u8> = Vec::new();
+                {
+                    let mut writer =
+                        BitWriter::<$endianness>::new(&mut output);
+                    sequence.write(&mut writer).unwrap();
+                    writer.write(8, 0xFFu8).unwrap();
+                    writer.byte_align().unwrap();
+                }
+                let c = Cursor::new(output);
+                let mut reader = BitReader::<$endianness>::new(Box::new(c));
+                let read = EliasFano::read(&mut reader,
+                                           universe,
+                                           values.len()).unwrap();
+                assert_eq!(reader.read::<u8>(8).unwrap(), 0xFF);
+                assert_eq!(read, sequence);
+            }
+        }
+    }
+}
+
+define_elias_fano_roundtrip!(test_elias_fano_roundtrip_be, BigEndian);
+define_elias_fano_roundtrip!(test_elias_fano_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_elias_fano_random_access() {
+    for (universe, values) in sequences() {
+        let sequence = EliasFano::from_values(universe, &values).unwrap();
+        for (index, value) in values.iter().enumerate() {
+            assert_eq!(sequence.get(index), Some(*value));
+        }
+        assert_eq!(sequence.get(values.len()), None);
+
+        let mut probes: Vec<u64> = values.clone();
+        probes.extend(values.iter().map(|v| v.saturating_add(1)));
+        probes.extend(values.iter().map(|v| v.saturating_sub(1)));
+        probes.extend([0, universe / 2, universe].iter().cloned());
+        for probe in probes {
+            assert_eq!(sequence.rank(probe),
+                       values.iter().filter(|v| **v < probe).count());
+        }
+    }
+}
+
+#[test]
+fn test_elias_fano_invalid_stream() {
+    // a high part difference which goes beyond the universe
+    let data = [0b0000_0000, 0b0000_0001];
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    assert!(EliasFano::read(&mut reader, 16, 1).is_err());
+}
+
+#[test]
+fn test_elias_fano_decreasing_stream() {
+    // low bits 5 then 2 with the same high bits,
+    // which would decode to 5 followed by 2
+    let data = [0b1010_1011];
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    assert_eq!(EliasFano::read(&mut reader, 16, 2).unwrap_err().kind(),
+               std::io::ErrorKind::InvalidData);
+}
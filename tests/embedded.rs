@@ -0,0 +1,101 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "embedded-io")]
+
+extern crate bitstream_io;
+extern crate embedded_io;
+
+use std::io::{self, Write};
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::embedded::{EmbeddedSink, EmbeddedSource};
+
+/*a serial port which transfers a byte at a time
+  and fails once its buffer is used up*/
+struct Uart {
+    buf: Vec<u8>,
+    capacity: usize,
+    flushed: bool,
+}
+
+#[derive(Debug)]
+struct UartError(embedded_io::ErrorKind);
+
+impl embedded_io::Error for UartError {
+    fn kind(&self) -> embedded_io::ErrorKind {self.0}
+}
+
+impl embedded_io::ErrorType for Uart {
+    type Error = UartError;
+}
+
+impl embedded_io::Read for Uart {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, UartError> {
+        if self.buf.is_empty() {
+            Err(UartError(embedded_io::ErrorKind::TimedOut))
+        } else {
+            buf[0] = self.buf.remove(0);
+            Ok(1)
+        }
+    }
+}
+
+impl embedded_io::Write for Uart {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, UartError> {
+        if self.buf.len() == self.capacity {
+            Err(UartError(embedded_io::ErrorKind::InvalidInput))
+        } else {
+            self.buf.push(buf[0]);
+            Ok(1)
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), UartError> {
+        self.flushed = true;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_embedded_source() {
+    let uart = Uart{buf: vec![0xB1, 0xED, 0x3B, 0xC1],
+                    capacity: 0,
+                    flushed: false};
+    let mut r = BitReader::<BigEndian, _>::from_embedded(uart);
+    assert_eq!(r.read::<u8>(2).unwrap(), 2);
+    assert_eq!(r.read::<u32>(22).unwrap(), 0x31ED3B);
+    assert_eq!(r.read::<u8>(8).unwrap(), 0xC1);
+
+    /*driver errors become I/O errors of the nearest kind*/
+    assert_eq!(r.read::<u8>(1).unwrap_err().kind(), io::ErrorKind::Other);
+
+    let mut source = EmbeddedSource::new(&[0x01, 0x80][..]);
+    let mut r = BitReader::<LittleEndian, _>::from_read(&mut source);
+    assert_eq!(r.read::<u16>(16).unwrap(), 0x8001);
+    assert!(r.read::<u8>(1).is_err());
+}
+
+#[test]
+fn test_embedded_sink() {
+    let mut sink = EmbeddedSink::new(Uart{buf: Vec::new(),
+                                          capacity: 3,
+                                          flushed: false});
+    {
+        let mut w = BitWriter::<LittleEndian, _>::from_write(&mut sink);
+        w.write(2, 1u8).unwrap();
+        w.write_signed(3, -4i8).unwrap();
+        w.write(19, 0x1DF6Du32).unwrap();
+
+        /*the driver's buffer is full*/
+        assert_eq!(w.write(8, 0xC1u8).unwrap_err().kind(),
+                   io::ErrorKind::InvalidInput);
+    }
+    sink.flush().unwrap();
+    assert!(sink.get_ref().flushed);
+    assert_eq!(sink.into_inner().buf, [0xB1, 0xED, 0x3B]);
+}
@@ -0,0 +1,88 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "ffi")]
+
+extern crate bitstream_io;
+
+use std::ptr;
+use bitstream_io::{BigEndian, LittleEndian, BitWriter};
+use bitstream_io::ffi::*;
+
+macro_rules! define_ffi_roundtrip {
+    ($func_name:ident, $endianness:ident, $little:expr) => {
+        #[test]
+        fn $func_name() {
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                writer.write(3, 0b101u8).unwrap();
+                writer.write_signed(33, -0x1_2345_678i64).unwrap();
+                writer.write_unary0(4).unwrap();
+                writer.write_unary1(2).unwrap();
+                writer.write(64, u64::MAX).unwrap();
+                writer.write(7, 0u8).unwrap();
+            }
+            unsafe {
+                let reader = bitstream_reader_new(data.as_ptr(), data.len(),
+                                                  $little);
+                /*the reader owns a copy of the buffer*/
+                drop(data);
+                assert!(!reader.is_null());
+                let mut u = 0;
+                let mut s = 0;
+                let mut unary = 0;
+                assert_eq!(bitstream_reader_read(reader, 3, &mut u),
+                           BITSTREAM_OK);
+                assert_eq!(u, 0b101);
+                assert_eq!(bitstream_reader_read_signed(reader, 33, &mut s),
+                           BITSTREAM_OK);
+                assert_eq!(s, -0x1_2345_678);
+                assert_eq!(bitstream_reader_read_unary(reader, 0, &mut unary),
+                           BITSTREAM_OK);
+                assert_eq!(unary, 4);
+                assert_eq!(bitstream_reader_read_unary(reader, 1, &mut unary),
+                           BITSTREAM_OK);
+                assert_eq!(unary, 2);
+                assert_eq!(bitstream_reader_read_unary(reader, 2, &mut unary),
+                           BITSTREAM_INVALID_INPUT);
+                assert_eq!(bitstream_reader_position(reader), 44);
+                assert_eq!(bitstream_reader_read(reader, 65, &mut u),
+                           BITSTREAM_INVALID_INPUT);
+                assert_eq!(bitstream_reader_read(reader, 64, &mut u),
+                           BITSTREAM_OK);
+                assert_eq!(u, u64::MAX);
+                bitstream_reader_byte_align(reader);
+                assert_eq!(bitstream_reader_position(reader), 112);
+                assert_eq!(bitstream_reader_skip(reader, 1), BITSTREAM_EOF);
+                assert_eq!(bitstream_reader_read(reader, 1, ptr::null_mut()),
+                           BITSTREAM_NULL_POINTER);
+                bitstream_reader_free(reader);
+            }
+        }
+    }
+}
+
+define_ffi_roundtrip!(test_ffi_roundtrip_be, BigEndian, 0);
+define_ffi_roundtrip!(test_ffi_roundtrip_le, LittleEndian, 1);
+
+#[test]
+fn test_ffi_null() {
+    unsafe {
+        assert!(bitstream_reader_new(ptr::null(), 1, 0).is_null());
+        let empty = bitstream_reader_new(ptr::null(), 0, 0);
+        assert!(!empty.is_null());
+        let mut u = 0;
+        assert_eq!(bitstream_reader_read(empty, 1, &mut u), BITSTREAM_EOF);
+        bitstream_reader_free(empty);
+        assert_eq!(bitstream_reader_read(ptr::null_mut(), 1, &mut u),
+                   BITSTREAM_NULL_POINTER);
+        assert_eq!(bitstream_reader_position(ptr::null()), 0);
+        bitstream_reader_free(ptr::null_mut());
+    }
+}
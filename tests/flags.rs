@@ -0,0 +1,97 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "bitflags")]
+
+#[macro_use]
+extern crate bitflags;
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::flags::UnknownBits;
+use std::io::{self, Cursor};
+
+bitflags! {
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    struct Header: u16 {
+        const PROTECTED = 1 << 0;
+        const PADDED = 1 << 1;
+        const PRIVATE = 1 << 2;
+        const COPYRIGHT = 1 << 9;
+    }
+}
+
+macro_rules! define_flags_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let unknown = Header::from_bits_retain(0b10_0000_1000);
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                writer.write_flags(10, Header::PROTECTED | Header::COPYRIGHT,
+                                   UnknownBits::Error).unwrap();
+                writer.write_flags(10, Header::empty(),
+                                   UnknownBits::Error).unwrap();
+                writer.write_flags(10, Header::all(),
+                                   UnknownBits::Error).unwrap();
+                writer.write_flags(10, unknown, UnknownBits::Preserve)
+                    .unwrap();
+                writer.write_flags(10, unknown, UnknownBits::Preserve)
+                    .unwrap();
+                writer.write_flags(10, unknown, UnknownBits::Preserve)
+                    .unwrap();
+                writer.write_flags(10, unknown, UnknownBits::Truncate)
+                    .unwrap();
+                writer.byte_align().unwrap();
+            }
+            assert_eq!(data.len(), 9);
+
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+            assert_eq!(reader.read_flags::<Header>(10, UnknownBits::Error)
+                       .unwrap(),
+                       Header::PROTECTED | Header::COPYRIGHT);
+            assert_eq!(reader.read_flags::<Header>(10, UnknownBits::Error)
+                       .unwrap(),
+                       Header::empty());
+            assert_eq!(reader.read_flags::<Header>(10, UnknownBits::Error)
+                       .unwrap(),
+                       Header::all());
+            assert_eq!(reader.read_flags::<Header>(10, UnknownBits::Error)
+                       .unwrap_err().kind(),
+                       io::ErrorKind::InvalidData);
+            assert_eq!(reader.read_flags::<Header>(10, UnknownBits::Truncate)
+                       .unwrap(),
+                       Header::COPYRIGHT);
+            assert_eq!(reader.read_flags::<Header>(10, UnknownBits::Preserve)
+                       .unwrap(),
+                       unknown);
+            assert_eq!(reader.read_flags::<Header>(10, UnknownBits::Error)
+                       .unwrap(),
+                       Header::COPYRIGHT);
+        }
+    }
+}
+
+define_flags_roundtrip!(test_flags_roundtrip_be, BigEndian);
+define_flags_roundtrip!(test_flags_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_flags_errors() {
+    let mut data = Vec::new();
+    let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    assert_eq!(writer.write_flags(10, Header::from_bits_retain(1 << 5),
+                                  UnknownBits::Error).unwrap_err().kind(),
+               io::ErrorKind::InvalidInput);
+    assert_eq!(writer.write_flags(9, Header::COPYRIGHT,
+                                  UnknownBits::Error).unwrap_err().kind(),
+               io::ErrorKind::InvalidInput);
+    assert_eq!(writer.write_flags(17, Header::empty(),
+                                  UnknownBits::Error).unwrap_err().kind(),
+               io::ErrorKind::InvalidInput);
+}
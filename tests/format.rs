@@ -0,0 +1,76 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::format::Value;
+use std::io::{self, Cursor};
+
+macro_rules! define_format_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                writer.write(1, 1u8).unwrap();
+                writer.write(7, 4u8).unwrap();
+                writer.write(24, 34u32).unwrap();
+                writer.write_signed(12, -2048i16).unwrap();
+                writer.write(4, 0b1010u8).unwrap();
+                writer.write(36, 0xF_FFFF_FFFFu64).unwrap();
+                writer.write_signed(20, 12345i32).unwrap();
+                writer.write(64, u64::MAX).unwrap();
+            }
+
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+            assert_eq!(reader.parse_fmt("u1 u7\tu24\n  s12 x4 u36 i20 u0 u64")
+                       .unwrap(),
+                       vec![Value::Unsigned(1),
+                            Value::Unsigned(4),
+                            Value::Unsigned(34),
+                            Value::Signed(-2048),
+                            Value::Unsigned(0xF_FFFF_FFFF),
+                            Value::Signed(12345),
+                            Value::Unsigned(0),
+                            Value::Unsigned(u64::MAX)]);
+            assert_eq!(reader.parse_fmt("").unwrap(), vec![]);
+            assert_eq!(reader.parse_fmt("u1").unwrap_err().kind(),
+                       io::ErrorKind::UnexpectedEof);
+        }
+    }
+}
+
+define_format_roundtrip!(test_format_roundtrip_be, BigEndian);
+define_format_roundtrip!(test_format_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_format_errors() {
+    let data = [0xFF; 4];
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+
+    /*invalid formats read nothing*/
+    for format in ["u8 u65", "s0", "i65", "u", "8", "f32", "u-1", "u8,u8"]
+        .iter() {
+        assert_eq!(reader.parse_fmt(format).unwrap_err().kind(),
+                   io::ErrorKind::InvalidInput);
+    }
+    assert_eq!(reader.parse_fmt("u32").unwrap(),
+               vec![Value::Unsigned(0xFFFF_FFFF)]);
+}
+
+#[test]
+fn test_format_values() {
+    assert_eq!(Value::Unsigned(5).to_u64(), Some(5));
+    assert_eq!(Value::Unsigned(5).to_i64(), Some(5));
+    assert_eq!(Value::Unsigned(u64::MAX).to_i64(), None);
+    assert_eq!(Value::Signed(-5).to_u64(), None);
+    assert_eq!(Value::Signed(-5).to_i64(), Some(-5));
+    assert_eq!(Value::Signed(5).to_u64(), Some(5));
+}
@@ -0,0 +1,138 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+use std::io;
+use bitstream_io::{BigEndian, LittleEndian, BitRead, BitReader, BitWriter};
+use bitstream_io::crc::Crc;
+use bitstream_io::frame::{FrameError, Framing};
+
+#[test]
+fn test_frame_crc() {
+    /*the CRC covers exactly the payload, matching the check value*/
+    let framing = Framing{crc: Crc::crc32(), align: false};
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<LittleEndian, _>::from_write(&mut data);
+        let checksum = w.write_checksummed(&framing, |w| {
+            w.write_bytes(b"123456789")
+        }).unwrap();
+        assert_eq!(checksum, 0xCBF43926);
+    }
+    assert_eq!(&data[..9], b"123456789");
+    assert_eq!(&data[9..], [0x26, 0x39, 0xF4, 0xCB]);
+
+    let mut r = BitReader::<LittleEndian, _>::from_read(&data[..]);
+    let mut payload = r.read_checksummed(&framing, 72).unwrap();
+    let mut buf = [0; 9];
+    payload.read_bytes(&mut buf).unwrap();
+    assert_eq!(&buf, b"123456789");
+    assert_eq!(payload.remaining(), 0);
+}
+
+#[test]
+fn test_frame_unaligned() {
+    let framing = Framing{crc: Crc::crc16_ccitt(), align: false};
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<BigEndian, _>::from_write(&mut data);
+        w.write(3, 0b110u8).unwrap();
+        for value in 0..3u8 {
+            w.write_checksummed(&framing, |w| {
+                w.write(5, value)?;
+                w.write_signed(7, -(value as i8))
+            }).unwrap();
+        }
+        w.write(3, 0b011u8).unwrap();
+        w.byte_align().unwrap();
+    }
+    assert_eq!(data.len(), (3 + 3 * (12 + 16) + 3 + 6) / 8);
+
+    let mut r = BitReader::<BigEndian, _>::from_read(&data[..]);
+    assert_eq!(r.read::<u8>(3).unwrap(), 0b110);
+    for value in 0..3u8 {
+        let mut payload = r.read_checksummed(&framing, 12).unwrap();
+        assert_eq!(payload.payload_bits(), 12);
+        assert_eq!(payload.read::<u8>(5).unwrap(), value);
+        assert_eq!(payload.remaining(), 7);
+        /*alignment is relative to the payload's start*/
+        assert!(!payload.byte_aligned());
+        payload.byte_align();
+        assert_eq!(payload.remaining(), 4);
+        assert_eq!(payload.read::<u8>(5).unwrap_err().kind(),
+                   io::ErrorKind::UnexpectedEof);
+        assert_eq!(payload.remaining(), 4);
+    }
+    assert_eq!(r.read::<u8>(3).unwrap(), 0b011);
+}
+
+#[test]
+fn test_frame_mismatch() {
+    let framing = Framing{crc: Crc::crc8(), align: true};
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<BigEndian, _>::from_write(&mut data);
+        w.write_checksummed(&framing, |w| w.write(10, 0x2AAu16)).unwrap();
+        w.write(8, 0xEEu8).unwrap();
+
+        /*a failed payload writes nothing*/
+        assert!(w.write_checksummed(&framing, |w| {
+            w.write(8, 0xFFu8)?;
+            w.write(3, 8u8)
+        }).is_err());
+    }
+    assert_eq!(data.len(), 4);
+
+    data[0] ^= 1;
+    let mut r = BitReader::<BigEndian, _>::from_read(&data[..]);
+    let crc = Crc::crc8().checksum::<BigEndian>(&data, 0, 10).unwrap();
+    match r.read_checksummed(&framing, 10) {
+        Err(FrameError::Checksum{expected, calculated}) => {
+            assert_eq!(u64::from(data[2]), expected);
+            assert_eq!(crc, calculated);
+        }
+        _ => panic!("corrupt frame accepted"),
+    }
+    /*the whole frame is consumed regardless*/
+    assert_eq!(r.read::<u8>(8).unwrap(), 0xEE);
+
+    let mut r = BitReader::<BigEndian, _>::from_read(&data[..]);
+    let err: io::Error = r.read_checksummed(&framing, 10)
+        .map(|_| ()).unwrap_err().into();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    /*truncated frames are I/O errors*/
+    let mut r = BitReader::<BigEndian, _>::from_read(&data[..2]);
+    match r.read_checksummed(&framing, 10) {
+        Err(FrameError::Io(ref err)) => {
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        }
+        _ => panic!("truncated frame accepted"),
+    }
+}
+
+#[test]
+fn test_frame_rejected_read() {
+    let framing = Framing{crc: Crc::crc8(), align: true};
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<BigEndian, _>::from_write(&mut data);
+        w.write_checksummed(&framing, |w| w.write(16, 0xABCDu16)).unwrap();
+    }
+
+    /*a read too wide for its type doesn't use up the payload*/
+    let mut r = BitReader::<BigEndian, _>::from_read(&data[..]);
+    let mut payload = r.read_checksummed(&framing, 16).unwrap();
+    assert!(payload.read::<u8>(9).is_err());
+    assert_eq!(payload.remaining(), 16);
+    assert_eq!(payload.read::<u16>(16).unwrap(), 0xABCD);
+    assert_eq!(payload.remaining(), 0);
+}
@@ -0,0 +1,130 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "arbitrary")]
+
+extern crate arbitrary;
+extern crate bitstream_io;
+
+use std::io;
+use arbitrary::{Arbitrary, Unstructured};
+use bitstream_io::{BigEndian, LittleEndian, BitRead, BitReader, BitWrite,
+                   FromBitStream, ToBitStream};
+use bitstream_io::fuzz::{arbitrary_stream, check_decoder, check_parse,
+                         Encoded, Field, Schema, Stream};
+
+#[derive(Arbitrary, Debug, PartialEq)]
+struct Sample {
+    flag: bool,
+    level: u8,
+    offset: u16,
+}
+
+impl FromBitStream for Sample {
+    type Error = io::Error;
+
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+        Ok(Sample{flag: r.read_bit()?,
+                  level: r.read(8)?,
+                  offset: r.read(16)?})
+    }
+}
+
+impl ToBitStream for Sample {
+    type Error = io::Error;
+
+    fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write_bit(self.flag)?;
+        w.write(8, self.level)?;
+        w.write(16, self.offset)
+    }
+}
+
+struct Frame;
+
+impl Schema for Frame {
+    const FIELDS: &'static [Field] = &[
+        Field::Constant{bits: 11, value: 0x7FF},
+        Field::Range{bits: 3, min: 2, max: 5},
+        Field::Bits(70),
+    ];
+}
+
+#[test]
+fn test_arbitrary_stream() {
+    let noise: Vec<u8> = (0..64u8).map(|i| i.wrapping_mul(37)).collect();
+
+    /*every stream follows the schema, whatever the noise*/
+    for start in 0..32 {
+        let mut u = Unstructured::new(&noise[start..]);
+        let stream = Stream::<BigEndian, Frame>::arbitrary(&mut u).unwrap();
+        assert_eq!(stream.data.len(), 11);
+        let mut r = BitReader::<BigEndian, _>::from_read(&stream.data[..]);
+        assert_eq!(r.read::<u16>(11).unwrap(), 0x7FF);
+        let range = r.read::<u8>(3).unwrap();
+        assert!((2..=5).contains(&range));
+
+        let mut u = Unstructured::new(&noise[start..]);
+        let stream =
+            Stream::<LittleEndian, Frame>::arbitrary(&mut u).unwrap();
+        let mut r = BitReader::<LittleEndian, _>::from_read(&stream.data[..]);
+        assert_eq!(r.read::<u16>(11).unwrap(), 0x7FF);
+        assert_eq!(r.read::<u8>(3).unwrap(), range);
+    }
+
+    /*running out of noise fills fields with 0 bits*/
+    let mut u = Unstructured::new(&[]);
+    assert_eq!(arbitrary_stream::<BigEndian>(&mut u, &[Field::Bits(12)])
+               .unwrap(), [0, 0]);
+}
+
+#[test]
+fn test_encoded() {
+    let noise: Vec<u8> = (0..64u8).map(|i| i.wrapping_mul(91)).collect();
+    let mut u = Unstructured::new(&noise);
+    while !u.is_empty() {
+        let encoded = Encoded::<BigEndian, Sample>::arbitrary(&mut u).unwrap();
+        assert_eq!(encoded.data.len(), 4);
+        assert_eq!(check_parse::<BigEndian, Sample>(&encoded.data),
+                   Some(encoded.value));
+    }
+}
+
+#[test]
+fn test_check_decoder() {
+    check_decoder::<Sample>(&[0xFF, 0x00, 0x80, 0x01]);
+    check_decoder::<Sample>(&[0x12]);
+    assert_eq!(check_parse::<LittleEndian, Sample>(&[0x03, 0x02, 0x00, 0x00]),
+               Some(Sample{flag: true, level: 1, offset: 1}));
+}
+
+#[derive(Debug, PartialEq)]
+struct Lossy(u8);
+
+impl FromBitStream for Lossy {
+    type Error = io::Error;
+
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+        r.read(8).map(Lossy)
+    }
+}
+
+impl ToBitStream for Lossy {
+    type Error = io::Error;
+
+    /*drops the value's low bit, which check_parse should catch*/
+    fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write(8, self.0 & !1)
+    }
+}
+
+#[test]
+#[should_panic(expected = "parsed value failed to round trip")]
+fn test_check_decoder_mismatch() {
+    check_decoder::<Lossy>(&[0x01]);
+}
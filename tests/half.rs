@@ -0,0 +1,75 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "half")]
+
+extern crate half;
+extern crate bitstream_io;
+
+use std::io::Cursor;
+use half::{bf16, f16};
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+
+macro_rules! define_half_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let f16s = [f16::ZERO, f16::NEG_ZERO, f16::ONE, f16::MAX,
+                        f16::MIN_POSITIVE_SUBNORMAL, f16::INFINITY,
+                        f16::from_f32(-3.140625)];
+            let bf16s = [bf16::ZERO, bf16::ONE, bf16::MIN, bf16::EPSILON,
+                         bf16::NEG_INFINITY, bf16::from_f32(1.0e30)];
+            /*alternate between aligned and unaligned values*/
+            for offset in 0..8 {
+                let mut output: Vec<u8> = Vec::new();
+                {
+                    let mut writer =
+                        BitWriter::<$endianness>::new(&mut output);
+                    writer.write(offset, 0u8).unwrap();
+                    for (h, b) in f16s.iter().zip(bf16s.iter().cycle()) {
+                        writer.write_f16(*h).unwrap();
+                        writer.write(3, 0b101u8).unwrap();
+                        writer.write_bf16(*b).unwrap();
+                    }
+                    writer.byte_align().unwrap();
+                    writer.write_aligned(f16::ONE).unwrap();
+                    writer.write_aligned(bf16::ONE).unwrap();
+                }
+                let c = Cursor::new(output);
+                let mut reader = BitReader::<$endianness>::new(Box::new(c));
+                reader.skip(offset).unwrap();
+                for (h, b) in f16s.iter().zip(bf16s.iter().cycle()) {
+                    assert_eq!(reader.read_f16().unwrap().to_bits(),
+                               h.to_bits());
+                    assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+                    assert_eq!(reader.read_bf16().unwrap().to_bits(),
+                               b.to_bits());
+                }
+                reader.byte_align();
+                assert_eq!(reader.read_aligned::<f16>().unwrap(), f16::ONE);
+                assert_eq!(reader.read_aligned::<bf16>().unwrap(), bf16::ONE);
+            }
+        }
+    }
+}
+
+define_half_roundtrip!(test_half_roundtrip_be, BigEndian);
+define_half_roundtrip!(test_half_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_half_nan() {
+    let mut output: Vec<u8> = Vec::new();
+    {
+        let mut writer = BitWriter::<BigEndian>::new(&mut output);
+        writer.write_f16(f16::NAN).unwrap();
+        writer.write_bf16(bf16::NAN).unwrap();
+    }
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(output)));
+    assert!(reader.read_f16().unwrap().is_nan());
+    assert!(reader.read_bf16().unwrap().is_nan());
+}
@@ -0,0 +1,91 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "fec")]
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::hamming::{Code, Decoded, HammingReader, HammingWriter,
+                            encode, decode};
+use std::io::Cursor;
+
+#[test]
+fn test_hamming_errors() {
+    for code in [Code::Hamming74, Code::Secded].iter() {
+        let bits = code.codeword_bits();
+        for nibble in 0..16 {
+            let codeword = encode(*code, nibble);
+            assert!(u32::from(codeword) < (1 << bits));
+            assert_eq!(decode(*code, codeword), Decoded::Valid(nibble));
+            for i in 0..bits {
+                assert_eq!(decode(*code, codeword ^ (1 << i)),
+                           Decoded::Corrected(nibble));
+                if *code == Code::Secded {
+                    for j in (i + 1)..bits {
+                        assert_eq!(decode(*code,
+                                          codeword ^ (1 << i) ^ (1 << j)),
+                                   Decoded::Uncorrectable);
+                    }
+                }
+            }
+        }
+    }
+}
+
+macro_rules! define_hamming_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let bytes: Vec<u8> = (0..=255).collect();
+            for code in [Code::Hamming74, Code::Secded].iter() {
+                let mut data = Vec::new();
+                {
+                    let mut writer = BitWriter::<$endianness>::new(&mut data);
+                    HammingWriter::new(&mut writer, *code)
+                        .write_bytes(&bytes).unwrap();
+                    writer.byte_align().unwrap();
+                }
+                assert_eq!(data.len() as u32,
+                           (bytes.len() as u32 * 2 * code.codeword_bits())
+                           .div_ceil(8));
+
+                /*a single flipped bit per byte is corrected*/
+                let mut damaged = data.clone();
+                for byte in damaged.iter_mut().step_by(2) {
+                    *byte ^= 0x10;
+                }
+                let mut reader =
+                    BitReader::<$endianness>::new(Box::new(Cursor::new(damaged)));
+                let mut hamming = HammingReader::new(&mut reader, *code);
+                let mut read = vec![0; bytes.len()];
+                hamming.read_bytes(&mut read).unwrap();
+                assert_eq!(read, bytes);
+                assert_eq!(hamming.corrected(), (data.len() as u64).div_ceil(2));
+            }
+
+            /*double errors are flagged by SECDED*/
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                HammingWriter::new(&mut writer, Code::Secded)
+                    .write_bytes(b"ok").unwrap();
+            }
+            data[1] ^= 0x81;
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+            let mut hamming = HammingReader::new(&mut reader, Code::Secded);
+            assert!(hamming.read_nibble().unwrap() != Decoded::Uncorrectable);
+            assert_eq!(hamming.read_nibble().unwrap(), Decoded::Uncorrectable);
+            let mut buf = [0];
+            assert!(hamming.read_bytes(&mut buf).is_ok());
+        }
+    }
+}
+
+define_hamming_roundtrip!(test_hamming_roundtrip_be, BigEndian);
+define_hamming_roundtrip!(test_hamming_roundtrip_le, LittleEndian);
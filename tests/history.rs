@@ -0,0 +1,90 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+use std::error::Error;
+use std::io;
+use bitstream_io::{BigEndian, LittleEndian, BitRead, BitReader};
+use bitstream_io::history::{HistoryError, HistoryReader};
+
+fn kept<E, R>(r: &HistoryReader<E, R>) -> String
+    where E: bitstream_io::Endianness, R: BitRead {
+    r.bits().iter().map(|&bit| if bit {'1'} else {'0'}).collect()
+}
+
+#[test]
+fn test_history_bits() {
+    let data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+    let mut r = HistoryReader::<BigEndian, _>::new(
+        BitReader::<BigEndian, _>::from_read(&data[..]), 64);
+    assert_eq!(r.read::<u8>(2).unwrap(), 2);
+    assert_eq!(r.read_signed::<i8>(3).unwrap(), -2);
+    assert!(!r.read_bit().unwrap());
+    r.skip(3).unwrap();
+    assert_eq!(r.read_unary1().unwrap(), 0);
+    assert_eq!(r.read_unary0().unwrap(), 1);
+    r.byte_align();
+    let mut buf = [0; 2];
+    r.read_bytes(&mut buf).unwrap();
+    assert_eq!(r.position(), 32);
+    assert_eq!(kept(&r), "10110001111011010011101111000001");
+
+    let mut r = HistoryReader::<LittleEndian, _>::new(
+        BitReader::<LittleEndian, _>::from_read(&data[..]), 64);
+    assert_eq!(r.read::<u8>(2).unwrap(), 1);
+    assert_eq!(r.read_signed::<i8>(3).unwrap(), -4);
+    assert!(r.read_bit().unwrap());
+    r.skip(3).unwrap();
+    assert_eq!(r.read_unary1().unwrap(), 1);
+    r.byte_align();
+    r.read_bytes(&mut buf).unwrap();
+    assert_eq!(kept(&r), "10001101101101111101110010000011");
+}
+
+#[test]
+fn test_history_capacity() {
+    let data = [0x0F, 0xF0, 0xAA];
+    let mut r = HistoryReader::<BigEndian, _>::new(
+        BitReader::<BigEndian, _>::from_read(&data[..]), 6);
+    r.skip(13).unwrap();
+    assert_eq!(r.position(), 13);
+    assert_eq!(kept(&r), "111110");
+    assert_eq!(r.read::<u8>(4).unwrap(), 0b0001);
+    assert_eq!(kept(&r), "100001");
+    assert_eq!(r.history(),
+               "last 6 bits before bit 17:\n\
+                ...10000 1\n          ^");
+
+    /*nothing is kept, but position is still counted*/
+    let mut r = HistoryReader::<BigEndian, _>::new(
+        BitReader::<BigEndian, _>::from_read(&data[..]), 0);
+    r.skip(13).unwrap();
+    assert_eq!(r.read_unary0().unwrap(), 0);
+    assert!(r.bits().is_empty());
+    assert_eq!(r.history(), "last 0 bits before bit 14:\n......\n      ^");
+}
+
+#[test]
+fn test_history_error() {
+    let data = [0b1010_0000];
+    let mut r = HistoryReader::<BigEndian, _>::new(
+        BitReader::<BigEndian, _>::from_read(&data[..]), 16);
+    assert_eq!(r.read::<u8>(4).unwrap(), 0b1010);
+    let err = r.read::<u8>(8).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    let history = HistoryError::find(&err).unwrap();
+    assert_eq!(history.position(), 4);
+    assert_eq!(history.get_ref().kind(), io::ErrorKind::UnexpectedEof);
+    assert!(history.source().is_some());
+    assert_eq!(err.to_string(),
+               "failed to fill whole buffer at bit 4\n\
+                last 4 bits before bit 4:\n1010\n    ^");
+}
@@ -72,7 +72,7 @@ fn test_huffman_values() {
                  (Some(1), vec![1, 0]),
                  (Some(2), vec![1, 1, 0]),
                  (None, vec![1, 1, 1])]).unwrap();
-        let mut c = Cursor::new(data);
+        let c = Cursor::new(data);
         let mut r = BitReader::<BE>::new(Box::new(c));
         assert_eq!(r.read_huffman(&tree).unwrap(), Some(1));
         assert_eq!(r.read_huffman(&tree).unwrap(), Some(2));
@@ -88,7 +88,7 @@ fn test_huffman_values() {
                  (Rc::new("bar".to_owned()), vec![1, 0]),
                  (Rc::new("baz".to_owned()), vec![1, 1, 0]),
                  (Rc::new("kelp".to_owned()), vec![1, 1, 1])]).unwrap();
-        let mut c = Cursor::new(data);
+        let c = Cursor::new(data);
         let mut r = BitReader::<BE>::new(Box::new(c));
         assert_eq!(r.read_huffman(&tree).unwrap().deref(), "bar");
         assert_eq!(r.read_huffman(&tree).unwrap().deref(), "baz");
@@ -107,18 +107,11 @@ fn test_lengthy_huffman_values() {
     let max_bits = 70;
     let mut spec = Vec::new();
     for bits in 0..max_bits {
-        let mut entry = Vec::new();
-        for _ in 0..bits {
-            entry.push(0);
-        }
+        let mut entry = vec![0; bits as usize];
         entry.push(1);
         spec.push((Some(bits), entry));
     }
-    let mut entry = Vec::new();
-    for _ in 0..max_bits {
-        entry.push(0);
-    }
-    spec.push((None, entry));
+    spec.push((None, vec![0; max_bits as usize]));
 
     let read_tree_be =
         compile_read_tree::<BE,Option<i32>>(spec.clone()).unwrap();
@@ -144,8 +137,8 @@ fn test_lengthy_huffman_values() {
         writer_le.byte_align().unwrap();
     }
     {
-        let mut cursor_be = Cursor::new(data_be);
-        let mut cursor_le = Cursor::new(data_le);
+        let cursor_be = Cursor::new(data_be);
+        let cursor_le = Cursor::new(data_le);
         let mut reader_be = BitReader::new(Box::new(cursor_be));
         let mut reader_le = BitReader::new(Box::new(cursor_le));
         for _ in 0..20 {
@@ -158,3 +151,229 @@ fn test_lengthy_huffman_values() {
         }
     }
 }
+
+#[test]
+fn test_skip_huffman() {
+    use std::io::Cursor;
+    use bitstream_io::{BE, LE, BitReader, BitWriter};
+    use bitstream_io::huffman::{compile_read_tree, compile_write_tree};
+
+    /*symbols of every length, some spanning several bytes,
+      which are skipped in runs of varying size*/
+    let mut spec = Vec::new();
+    for bits in 0..20 {
+        let mut entry = vec![1; bits as usize];
+        entry.push(0);
+        spec.push((bits, entry));
+    }
+    spec.push((20, vec![1; 20]));
+
+    let read_be = compile_read_tree::<BE,i32>(spec.clone()).unwrap();
+    let write_be = compile_write_tree::<BE,i32>(spec.clone()).unwrap();
+    let read_le = compile_read_tree::<LE,i32>(spec.clone()).unwrap();
+    let write_le = compile_write_tree::<LE,i32>(spec).unwrap();
+
+    let mut data_be = Vec::new();
+    let mut data_le = Vec::new();
+    {
+        let mut writer_be = BitWriter::new(&mut data_be);
+        let mut writer_le = BitWriter::new(&mut data_le);
+        for symbol in (0..100).map(|i| (i * 7) % 21) {
+            writer_be.write_huffman(&write_be, symbol).unwrap();
+            writer_le.write_huffman(&write_le, symbol).unwrap();
+        }
+        writer_be.byte_align().unwrap();
+        writer_le.byte_align().unwrap();
+    }
+
+    let mut reader_be = BitReader::new(Box::new(Cursor::new(data_be)));
+    let mut reader_le = BitReader::new(Box::new(Cursor::new(data_le)));
+    let mut position = 0;
+    for skipped in 0..13 {
+        reader_be.skip_huffman(&read_be, skipped).unwrap();
+        reader_le.skip_huffman(&read_le, skipped).unwrap();
+        position += skipped;
+        let expected = ((position * 7) % 21) as i32;
+        assert_eq!(reader_be.read_huffman(&read_be).unwrap(), expected);
+        assert_eq!(reader_le.read_huffman(&read_le).unwrap(), expected);
+        position += 1;
+    }
+    /*skipping past the end of the stream is an error*/
+    assert!(reader_be.skip_huffman(&read_be, 100).is_err());
+    assert!(reader_le.skip_huffman(&read_le, 100).is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_huffman_short_tree() {
+    use std::io::Cursor;
+    use bitstream_io::{BE, BitReader};
+    use bitstream_io::huffman::compile_read_tree;
+
+    /*a slice too short for the reader's state is caught,
+      with or without the unchecked-bitqueue feature*/
+    let tree = compile_read_tree::<BE,u8>(
+        vec![(0, vec![0]), (1, vec![1])]).unwrap();
+    let mut reader = BitReader::new(Box::new(Cursor::new(vec![0xFF])));
+    let _ = reader.read_huffman(&tree[..1]);
+}
+
+#[test]
+#[should_panic]
+fn test_huffman_short_branch() {
+    use std::io::Cursor;
+    use std::marker::PhantomData;
+    use bitstream_io::{BE, BitReader};
+    use bitstream_io::huffman::ReadHuffmanTree;
+
+    /*a hand-built branch without an entry for every byte is caught,
+      with or without the unchecked-bitqueue feature*/
+    let tree: Vec<ReadHuffmanTree<BE,u8>> = (0..256)
+        .map(|_| ReadHuffmanTree::Continue(
+            vec![ReadHuffmanTree::Done(0, 0, 0, PhantomData)]
+            .into_boxed_slice()))
+        .collect();
+    let mut reader = BitReader::new(Box::new(Cursor::new(vec![0xFF])));
+    let _ = reader.skip_huffman(&tree, 1);
+}
+
+#[test]
+fn test_dense_huffman_values() {
+    use bitstream_io::{BE, LE, BitWriter, Endianness};
+    use bitstream_io::huffman::{compile_write_tree,
+                                compile_dense_write_tree};
+
+    fn check<E: Endianness>() {
+        /*codes of every length up to 40 bits,
+          where those over 32 bits are left out of the dense table,
+          along with symbols too large or negative to be indexed*/
+        let mut spec = Vec::new();
+        for bits in 0..40 {
+            let mut entry = vec![1; bits as usize];
+            entry.push(0);
+            let symbol = match bits {
+                0 => -1,
+                1 => 0x10000,
+                _ => bits * 3,
+            };
+            spec.push((symbol, entry));
+        }
+        spec.push((1000, vec![1; 40]));
+
+        let tree = compile_write_tree::<E,i32>(spec.clone()).unwrap();
+        let dense = compile_dense_write_tree::<E,i32>(spec.clone()).unwrap();
+        assert_eq!(dense.get_dense(&6), Some((3, tree.get(6)[0].1)));
+        assert_eq!(dense.get_dense(&(31 * 3)), tree.get(31 * 3).first()
+                   .cloned());
+        assert_eq!(dense.get_dense(&(32 * 3)), None);
+        assert_eq!(dense.get_dense(&-1), None);
+        assert_eq!(dense.get_dense(&0x10000), None);
+        assert_eq!(dense.get_dense(&4), None);
+        assert_eq!(tree.get_dense(&6), None);
+
+        let mut expected = Vec::new();
+        let mut data = Vec::new();
+        {
+            let mut w1 = BitWriter::<E>::new(&mut expected);
+            let mut w2 = BitWriter::<E>::new(&mut data);
+            for &(symbol, _) in spec.iter().cycle().take(200) {
+                w1.write_huffman(&tree, symbol).unwrap();
+                w2.write_huffman(&dense, symbol).unwrap();
+            }
+            w1.byte_align().unwrap();
+            w2.byte_align().unwrap();
+        }
+        assert_eq!(data, expected);
+    }
+
+    check::<BE>();
+    check::<LE>();
+}
+
+#[test]
+fn test_fixed_huffman_table() {
+    use bitstream_io::{BE, LE, BitRead, BitReader, BitWrite, BitWriter};
+    use bitstream_io::huffman::{compile_read_tree, validate_table,
+                                HuffmanCode};
+
+    const TABLE: [HuffmanCode<Option<u8>>; 5] = [
+        HuffmanCode{symbol: Some(0), len: 1, code: 0b0},
+        HuffmanCode{symbol: Some(1), len: 2, code: 0b10},
+        HuffmanCode{symbol: Some(2), len: 3, code: 0b110},
+        HuffmanCode{symbol: Some(3), len: 4, code: 0b1110},
+        HuffmanCode{symbol: None, len: 4, code: 0b1111},
+    ];
+    assert_eq!(validate_table(&TABLE), Ok(()));
+
+    let symbols = [Some(1), Some(3), None, Some(0), Some(2), Some(0)];
+
+    /*fixed tables write the same codes as compiled trees read*/
+    let tree_be = compile_read_tree::<BE, Option<u8>>(
+        TABLE.iter().map(|entry| {
+            (entry.symbol,
+             (0..entry.len).rev()
+             .map(|i| ((entry.code >> i) & 1) as u8).collect())
+        }).collect()).unwrap();
+
+    let mut data_be = [0; 2];
+    let mut data_le = [0; 2];
+    {
+        let mut buf_be = &mut data_be[..];
+        let mut buf_le = &mut data_le[..];
+        let mut writer_be = BitWriter::<BE>::new(&mut buf_be);
+        let mut writer_le = BitWriter::<LE>::new(&mut buf_le);
+        for symbol in symbols.iter() {
+            writer_be.write_huffman_code(&TABLE, *symbol).unwrap();
+            writer_le.write_huffman_code(&TABLE, *symbol).unwrap();
+        }
+        writer_be.byte_align().unwrap();
+        writer_le.byte_align().unwrap();
+        assert!(writer_be.write_huffman_code(&TABLE, Some(4)).is_err());
+    }
+    assert_eq!(data_be, [0b1011_1011, 0b1101_1000]);
+    assert_eq!(data_le, [0b1101_1101, 0b0001_1011]);
+
+    let mut reader_be = BitReader::<BE, _>::from_read(&data_be[..]);
+    let mut reader_le = BitReader::<LE, _>::from_read(&data_le[..]);
+    for symbol in symbols.iter() {
+        assert_eq!(reader_be.read_huffman_code(&TABLE).unwrap(), *symbol);
+        assert_eq!(reader_le.read_huffman_code(&TABLE).unwrap(), *symbol);
+    }
+    let mut reader_be =
+        BitReader::<BE>::new(Box::new(std::io::Cursor::new(data_be)));
+    for symbol in symbols.iter() {
+        assert_eq!(reader_be.read_huffman(&tree_be).unwrap(), *symbol);
+    }
+
+    /*a table with an unused code fails to read it*/
+    let partial = [HuffmanCode{symbol: 0, len: 1, code: 0},
+                   HuffmanCode{symbol: 1, len: 2, code: 0b10}];
+    let data = [0xFF; 5];
+    let mut reader = BitReader::<BE, _>::from_read(&data[..]);
+    assert_eq!(reader.read_huffman_code(&partial).unwrap_err().kind(),
+               std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_fixed_huffman_table_errors() {
+    use bitstream_io::huffman::{validate_table, HuffmanCode};
+
+    fn code(len: u32, code: u32) -> HuffmanCode<()> {
+        HuffmanCode{symbol: (), len, code}
+    }
+
+    assert_eq!(validate_table(&[code(0, 0)]), Ok(()));
+    assert_eq!(validate_table::<()>(&[]),
+               Err(HuffmanTreeError::MissingLeaf));
+    assert_eq!(validate_table(&[code(1, 0), code(1, 2)]),
+               Err(HuffmanTreeError::InvalidBit));
+    assert_eq!(validate_table(&[code(33, 0)]),
+               Err(HuffmanTreeError::InvalidBit));
+    assert_eq!(validate_table(&[code(1, 0), code(1, 1), code(1, 1)]),
+               Err(HuffmanTreeError::DuplicateLeaf));
+    assert_eq!(validate_table(&[code(1, 1), code(2, 0b11), code(1, 0)]),
+               Err(HuffmanTreeError::OrphanedLeaf));
+    assert_eq!(validate_table(&[code(32, 0), code(32, 1), code(31, 1),
+                                code(30, 1), code(1, 1)]),
+               Err(HuffmanTreeError::MissingLeaf));
+}
@@ -0,0 +1,85 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::interleave::{Interleaver, InterleaveWriter,
+                               DeinterleaveReader};
+use std::io::Cursor;
+
+#[test]
+fn test_interleave_burst() {
+    /*a burst of 4 errors lands in 4 different rows*/
+    let block = Interleaver{rows: 4, columns: 8};
+    let mut data = Vec::new();
+    {
+        let mut writer = BitWriter::<BigEndian>::new(&mut data);
+        let mut interleaver = InterleaveWriter::new(&mut writer, block)
+            .unwrap();
+        interleaver.write(32, 0).unwrap();
+    }
+    data[1] ^= 0b0011_1100;
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    let mut deinterleaver = DeinterleaveReader::new(&mut reader, block)
+        .unwrap();
+    for _ in 0..4 {
+        assert_eq!(deinterleaver.read(8).unwrap().count_ones(), 1);
+    }
+
+    let mut data = Vec::new();
+    let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    assert!(InterleaveWriter::new(&mut writer,
+                                  Interleaver{rows: 0, columns: 3}).is_err());
+    assert!(InterleaveWriter::new(&mut writer,
+                                  Interleaver{rows: usize::MAX,
+                                              columns: 2}).is_err());
+}
+
+macro_rules! define_interleave_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let values: Vec<u64> = (0..50u64)
+                .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 51)
+                .collect();
+            for (rows, columns) in [(1, 1), (1, 7), (7, 1), (3, 5), (8, 8)]
+                .iter() {
+                let block = Interleaver{rows: *rows, columns: *columns};
+                let mut data = Vec::new();
+                {
+                    let mut writer = BitWriter::<$endianness>::new(&mut data);
+                    {
+                        let mut interleaver =
+                            InterleaveWriter::new(&mut writer, block)
+                            .unwrap();
+                        for value in values.iter() {
+                            interleaver.write(13, *value).unwrap();
+                        }
+                        interleaver.finish().unwrap();
+                    }
+                    writer.write(6, 0b101101).unwrap();
+                    writer.byte_align().unwrap();
+                }
+                let mut reader =
+                    BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+                {
+                    let mut deinterleaver =
+                        DeinterleaveReader::new(&mut reader, block).unwrap();
+                    for value in values.iter() {
+                        assert_eq!(deinterleaver.read(13).unwrap(), *value);
+                    }
+                    deinterleaver.discard_block();
+                }
+                assert_eq!(reader.read::<u8>(6).unwrap(), 0b101101);
+            }
+        }
+    }
+}
+
+define_interleave_roundtrip!(test_interleave_roundtrip_be, BigEndian);
+define_interleave_roundtrip!(test_interleave_roundtrip_le, LittleEndian);
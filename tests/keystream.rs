@@ -0,0 +1,128 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(all(feature = "keystream", feature = "std"))]
+
+extern crate bitstream_io;
+extern crate chacha20;
+
+use std::io::{self, Write};
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::keystream::{CipherReader, CipherWriter};
+use chacha20::ChaCha20;
+use chacha20::cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+
+fn cipher() -> ChaCha20 {
+    ChaCha20::new(&[0x55; 32].into(), &[0xAA; 12].into())
+}
+
+/*a sink accepting at most a few bytes per write*/
+struct Trickle(Vec<u8>);
+
+impl Write for Trickle {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(3);
+        self.0.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {Ok(())}
+}
+
+#[test]
+fn test_keystream_round_trip() {
+    let plain: Vec<u8> = (0..1000).map(|i| (i * 7) as u8).collect();
+    let mut sink = CipherWriter::new(Trickle(Vec::new()), cipher());
+    {
+        let mut w = BitWriter::<LittleEndian, _>::from_write(&mut sink);
+        w.write(3, 0b101u8).unwrap();
+        w.write_bytes(&plain).unwrap();
+        w.write(5, 0b10011u8).unwrap();
+    }
+    let (Trickle(encrypted), cipher_used) = sink.into_inner();
+    assert_eq!(encrypted.len(), 1001);
+    assert_eq!(cipher_used.current_pos::<u64>(), 1001);
+
+    /*the output is the cipher applied to the unencrypted stream*/
+    let mut expected = Vec::new();
+    {
+        let mut w = BitWriter::<LittleEndian, _>::from_write(&mut expected);
+        w.write(3, 0b101u8).unwrap();
+        w.write_bytes(&plain).unwrap();
+        w.write(5, 0b10011u8).unwrap();
+    }
+    cipher().apply_keystream(&mut expected);
+    assert_eq!(encrypted, expected);
+
+    /*each byte is decrypted as the reader takes it, whatever its alignment*/
+    let source = CipherReader::new(&encrypted[..], cipher());
+    let mut r = BitReader::<LittleEndian, _>::from_read(source);
+    assert_eq!(r.read::<u8>(3).unwrap(), 0b101);
+    let mut buf = vec![0; plain.len()];
+    r.read_bytes(&mut buf).unwrap();
+    assert_eq!(buf, plain);
+    assert_eq!(r.read::<u8>(5).unwrap(), 0b10011);
+    assert!(r.read_bit().is_err());
+}
+
+#[test]
+fn test_keystream_partial_byte() {
+    let mut expected = [0x12, 0x3F];
+    cipher().apply_keystream(&mut expected);
+
+    let mut data = Vec::new();
+    {
+        let mut sink = CipherWriter::new(&mut data, cipher());
+        let mut w = BitWriter::<BigEndian, _>::from_write(&mut sink);
+        w.write(12, 0x123u16).unwrap();
+        w.write(4, 0xFu8).unwrap();
+    }
+    assert_eq!(data, expected);
+
+    /*a partial byte never reaches the keystream*/
+    let mut sink = CipherWriter::new(Vec::new(), cipher());
+    {
+        let mut w = BitWriter::<BigEndian, _>::from_write(&mut sink);
+        w.write(12, 0x123u16).unwrap();
+    }
+    let (data, cipher_used) = sink.into_inner();
+    assert_eq!(data, expected[..1]);
+    assert_eq!(cipher_used.current_pos::<u64>(), 1);
+}
+
+/*a sink which fails once it holds a given number of bytes*/
+struct Full(Vec<u8>, usize);
+
+impl Write for Full {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.1 - self.0.len());
+        if len == 0 && !buf.is_empty() {
+            return Err(io::Error::other("sink full"));
+        }
+        self.0.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {Ok(())}
+}
+
+#[test]
+fn test_keystream_failed_write() {
+    /*bytes written before a failure are counted,
+      and the failure is returned by the next write*/
+    let plain = [0x5A; 600];
+    let mut sink = CipherWriter::new(Full(Vec::new(), 300), cipher());
+    assert_eq!(sink.write(&plain).unwrap(), 256);
+    assert_eq!(sink.write(&plain[256..]).unwrap_err().kind(),
+               io::ErrorKind::Other);
+
+    let (Full(encrypted, _), _) = sink.into_inner();
+    let mut expected = plain[..256].to_vec();
+    cipher().apply_keystream(&mut expected);
+    assert_eq!(encrypted[..256], expected[..]);
+}
@@ -0,0 +1,77 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::linecode::{LineCode, LineReader, LineWriter};
+use std::io::Cursor;
+
+#[test]
+fn test_linecode_levels() {
+    /*USB's NRZI, idling high, changes level for every 0 bit*/
+    let usb = LineCode::Nrzi{transition: false, initial: true};
+    let mut data = Vec::new();
+    {
+        let mut writer = BitWriter::<BigEndian>::new(&mut data);
+        LineWriter::new(&mut writer, usb).write(8, 0b0000_0001).unwrap();
+    }
+    assert_eq!(data, [0b0101_0100]);
+
+    let mut data = Vec::new();
+    {
+        let mut writer = BitWriter::<BigEndian>::new(&mut data);
+        LineWriter::new(&mut writer, LineCode::ManchesterThomas)
+            .write(4, 0b1100).unwrap();
+    }
+    assert_eq!(data, [0b10_10_01_01]);
+
+    let mut reader =
+        BitReader::<BigEndian>::new(Box::new(Cursor::new(vec![0b0111_0000])));
+    let mut line = LineReader::new(&mut reader, LineCode::ManchesterIeee);
+    assert!(line.read_bit().unwrap());
+    assert!(line.read_bit().is_err());
+}
+
+macro_rules! define_linecode_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let codes = [LineCode::ManchesterIeee,
+                         LineCode::ManchesterThomas,
+                         LineCode::Nrzi{transition: true, initial: false},
+                         LineCode::Nrzi{transition: true, initial: true},
+                         LineCode::Nrzi{transition: false, initial: false},
+                         LineCode::Nrzi{transition: false, initial: true}];
+            let bytes: Vec<u8> = (0..=255).rev().collect();
+            for code in codes.iter() {
+                let mut data = Vec::new();
+                {
+                    let mut writer = BitWriter::<$endianness>::new(&mut data);
+                    let mut line = LineWriter::new(&mut writer, *code);
+                    line.write(3, 0b101).unwrap();
+                    line.write_bytes(&bytes).unwrap();
+                    line.write(64, 0xFEDC_BA98_7654_3210).unwrap();
+                    line.write_bit(true).unwrap();
+                    writer.byte_align().unwrap();
+                }
+                let mut reader =
+                    BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+                let mut line = LineReader::new(&mut reader, *code);
+                assert_eq!(line.read(3).unwrap(), 0b101);
+                let mut read = vec![0; bytes.len()];
+                line.read_bytes(&mut read).unwrap();
+                assert_eq!(read, bytes);
+                assert_eq!(line.read(64).unwrap(), 0xFEDC_BA98_7654_3210);
+                assert!(line.read_bit().unwrap());
+            }
+        }
+    }
+}
+
+define_linecode_roundtrip!(test_linecode_roundtrip_be, BigEndian);
+define_linecode_roundtrip!(test_linecode_roundtrip_le, LittleEndian);
@@ -0,0 +1,82 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+use std::time::Duration;
+use bitstream_io::{BigEndian, LittleEndian, BitRead, BitReader, BitWrite,
+                   BitWriter};
+use bitstream_io::meter::BitRateMeter;
+
+#[test]
+fn test_meter_reader() {
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+    let mut reports = Vec::new();
+    {
+        let reader = BitReader::<LittleEndian, _>::from_read(&actual_data[..]);
+        let mut meter = BitRateMeter::new(reader, Duration::ZERO,
+                                          |rate| reports.push(rate.bits));
+        assert_eq!(meter.read::<u8>(2).unwrap(), 1);
+        assert_eq!(meter.read_signed::<i8>(3).unwrap(), -4);
+        assert!(meter.read_bit().unwrap());
+        meter.skip(3).unwrap();
+        assert_eq!(meter.read_unary1().unwrap(), 1);
+        meter.byte_align();
+        let mut buf = [0; 2];
+        meter.read_bytes(&mut buf).unwrap();
+        assert_eq!(buf, [0x3B, 0xC1]);
+
+        /*failed reads aren't counted*/
+        assert!(meter.read::<u8>(1).is_err());
+        assert_eq!(meter.total_bits(), 32);
+        assert_eq!(meter.average().bits, 32);
+    }
+    assert_eq!(reports, vec![2, 3, 1, 3, 2, 5, 16]);
+}
+
+#[test]
+fn test_meter_writer() {
+    let mut reports = Vec::new();
+    let mut output = Vec::new();
+    {
+        let writer = BitWriter::<BigEndian>::new(&mut output);
+        let mut meter = BitRateMeter::new(writer, Duration::ZERO,
+                                          |rate| reports.push(rate.bits));
+        meter.write(2, 2u8).unwrap();
+        meter.write_signed(3, -3i8).unwrap();
+        meter.write_bit(true).unwrap();
+        meter.write_unary0(2).unwrap();
+        meter.byte_align().unwrap();
+        meter.write_bytes(&[0x3B, 0xC1]).unwrap();
+        meter.byte_align().unwrap();
+
+        /*failed writes aren't counted*/
+        assert!(meter.write(2, 4u8).is_err());
+        assert_eq!(meter.total_bits(), 32);
+    }
+    assert_eq!(output, [0b1010_1111, 0b0000_0000, 0x3B, 0xC1]);
+    assert_eq!(reports, vec![2, 3, 1, 3, 7, 16]);
+}
+
+#[test]
+fn test_meter_interval() {
+    let data = [0; 64];
+    let mut reports = Vec::new();
+    {
+        let reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+        let mut meter = BitRateMeter::new(reader, Duration::from_secs(3600),
+                                          |rate| reports.push(*rate));
+        for _ in 0..64 {
+            meter.read::<u8>(8).unwrap();
+        }
+        assert_eq!(meter.total_bits(), 512);
+    }
+    assert!(reports.is_empty());
+}
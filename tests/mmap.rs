@@ -0,0 +1,58 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "mmap")]
+
+extern crate bitstream_io;
+
+use std::fs::{self, File};
+use std::io::Write;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+
+macro_rules! define_mmap_roundtrip {
+    ($func_name:ident, $endianness:ident, $file_name:expr) => {
+        #[test]
+        fn $func_name() {
+            let path = std::env::temp_dir().join($file_name);
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                for i in 0..1000u32 {
+                    writer.write(13, i).unwrap();
+                }
+                writer.byte_align().unwrap();
+            }
+            File::create(&path).unwrap().write_all(&data).unwrap();
+
+            let mut reader = unsafe {
+                BitReader::<$endianness, _>::from_mmap(&path)
+            }.unwrap();
+            for i in 0..1000u32 {
+                assert_eq!(reader.read::<u32>(13).unwrap(), i);
+            }
+            /*jump to arbitrary fields out of order*/
+            for i in [999u32, 0, 500, 1, 998].iter() {
+                reader.seek_bits(u64::from(*i) * 13).unwrap();
+                assert_eq!(reader.read::<u32>(13).unwrap(), *i);
+            }
+            drop(reader);
+            fs::remove_file(&path).unwrap();
+        }
+    }
+}
+
+define_mmap_roundtrip!(test_mmap_roundtrip_be, BigEndian,
+                       "bitstream_io_mmap_be.bin");
+define_mmap_roundtrip!(test_mmap_roundtrip_le, LittleEndian,
+                       "bitstream_io_mmap_le.bin");
+
+#[test]
+fn test_mmap_missing() {
+    let path = std::env::temp_dir().join("bitstream_io_mmap_missing.bin");
+    assert!(unsafe {BitReader::<BigEndian, _>::from_mmap(&path)}.is_err());
+}
@@ -0,0 +1,140 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+use std::io;
+use bitstream_io::{BigEndian, LittleEndian, BitRead, BitReader, BitWrite,
+                   BitWriter};
+use bitstream_io::mux::{Demux, Mux, Schedule};
+
+#[test]
+fn test_mux_schedule() {
+    /*a frame of a 3-bit sync pattern, two slots of channel 0
+      and a slot of subcommutated channel 1*/
+    let mut mux = Mux::<LittleEndian>::new(3);
+    let schedule = Schedule::new(vec![(2, 3), (0, 4), (1, 1), (0, 4)]);
+    assert_eq!(schedule.frame_bits(), 12);
+
+    for frame in 0..4u8 {
+        mux.channel(2).write(3, 0b101u8).unwrap();
+        mux.channel(0).write(8, frame * 0x11).unwrap();
+        mux.channel(1).write_bit(frame % 2 == 1).unwrap();
+    }
+    assert!(!mux.frame_ready(&Schedule::round_robin(4, 1)));
+
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<LittleEndian, _>::from_write(&mut data);
+        while mux.frame_ready(&schedule) {
+            mux.write_frame(&schedule, &mut w).unwrap();
+        }
+        assert_eq!(mux.write_frame(&schedule, &mut w).unwrap_err().kind(),
+                   io::ErrorKind::InvalidInput);
+    }
+    assert_eq!(data.len(), 6);
+    assert_eq!((mux.written(0), mux.emitted(0), mux.queued(0)), (32, 32, 0));
+    assert_eq!((mux.written(1), mux.emitted(1), mux.queued(1)), (4, 4, 0));
+
+    let mut r = BitReader::<LittleEndian, _>::from_read(&data[..]);
+    for frame in 0..4u8 {
+        assert_eq!(r.read::<u8>(3).unwrap(), 0b101);
+        assert_eq!(r.read::<u8>(4).unwrap(), (frame * 0x11) & 0xF);
+        assert_eq!(r.read_bit().unwrap(), frame % 2 == 1);
+        assert_eq!(r.read::<u8>(4).unwrap(), (frame * 0x11) >> 4);
+    }
+}
+
+#[test]
+fn test_mux_emit() {
+    let mut mux = Mux::<BigEndian>::new(2);
+    mux.channel(0).write_signed(5, -3i8).unwrap();
+    mux.channel(0).byte_align().unwrap();
+    assert_eq!(mux.queued(0), 8);
+    assert!(mux.channel(1).write(3, 8u8).is_err());
+    mux.channel(1).write_bytes(b"z").unwrap();
+
+    let mut data = Vec::new();
+    {
+        /*the caller decides which channel goes next*/
+        let mut w = BitWriter::<BigEndian, _>::from_write(&mut data);
+        mux.emit(1, 4, &mut w).unwrap();
+        mux.emit(0, 8, &mut w).unwrap();
+        assert!(mux.emit(0, 1, &mut w).is_err());
+        assert!(mux.emit(2, 0, &mut w).is_err());
+        mux.emit(1, 4, &mut w).unwrap();
+    }
+    assert_eq!(data, [0x7E, 0x8A]);
+    assert_eq!(mux.emitted(1), 8);
+}
+
+#[test]
+fn test_demux_schedule() {
+    let schedule = Schedule::new(vec![(2, 3), (0, 4), (1, 1), (0, 4)]);
+    let mut mux = Mux::<LittleEndian>::new(3);
+    for frame in 0..4u8 {
+        mux.channel(2).write(3, 0b101u8).unwrap();
+        mux.channel(0).write_signed(8, -(frame as i8)).unwrap();
+        mux.channel(1).write_bit(frame % 2 == 1).unwrap();
+    }
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<LittleEndian, _>::from_write(&mut data);
+        while mux.frame_ready(&schedule) {
+            mux.write_frame(&schedule, &mut w).unwrap();
+        }
+    }
+
+    let mut demux = Demux::<LittleEndian>::new(3);
+    let mut r = BitReader::<LittleEndian, _>::from_read(&data[..]);
+    demux.read_frame(&schedule, &mut r).unwrap();
+    assert_eq!(demux.channel(2).read::<u8>(3).unwrap(), 0b101);
+    assert_eq!(demux.channel(0).read_signed::<i8>(8).unwrap(), 0);
+
+    /*reads past what's been received fail without consuming anything*/
+    assert_eq!(demux.channel(0).read::<u8>(1).unwrap_err().kind(),
+               io::ErrorKind::UnexpectedEof);
+    assert_eq!(demux.queued(1), 1);
+    for _ in 1..4 {
+        demux.read_frame(&schedule, &mut r).unwrap();
+    }
+    assert!(demux.read_frame(&schedule, &mut r).is_err());
+    assert_eq!((demux.received(0), demux.read(0)), (32, 8));
+
+    let mut channel = demux.channel(1);
+    assert_eq!(channel.read::<u8>(4).unwrap(), 0b1010);
+    for frame in 1..4 {
+        assert_eq!(demux.channel(0).read_signed::<i8>(8).unwrap(), -frame);
+    }
+    assert_eq!(demux.queued(0), 0);
+}
+
+#[test]
+fn test_demux_tagged() {
+    /*slots of a 2-bit channel tag and 6 bits of payload*/
+    let data = [0b01_111110, 0b00_000001, 0b01_000000, 0b11_000000];
+    let mut demux = Demux::<BigEndian>::new(3);
+    let mut r = BitReader::<BigEndian, _>::from_read(&data[..]);
+    assert_eq!(demux.read_tagged(2, 6, &mut r).unwrap(), 1);
+    assert_eq!(demux.read_tagged(2, 6, &mut r).unwrap(), 0);
+    assert_eq!(demux.read_tagged(2, 6, &mut r).unwrap(), 1);
+    assert_eq!(demux.read_tagged(2, 6, &mut r).unwrap_err().kind(),
+               io::ErrorKind::InvalidData);
+
+    let mut channel = demux.channel(1);
+    assert_eq!(channel.read_unary0().unwrap(), 5);
+    assert!(!channel.byte_aligned());
+    channel.byte_align();
+    assert!(channel.read_unary1().is_err());
+    assert_eq!(channel.read::<u8>(4).unwrap(), 0);
+    assert_eq!(demux.channel(0).read_unary1().unwrap(), 5);
+    assert_eq!(demux.queued(1), 0);
+    assert!(demux.receive(3, 1, &mut r).is_err());
+}
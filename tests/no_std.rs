@@ -0,0 +1,102 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*run with --no-default-features, with or without --features alloc*/
+#![cfg(not(feature = "std"))]
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter,
+                   BitRead, BitWrite};
+use bitstream_io::huffman::HuffmanCode;
+use bitstream_io::io::{self, Read, Write};
+
+macro_rules! define_no_std_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        #[cfg(feature = "alloc")]
+        fn $func_name() {
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                writer.write(3, 5u8).unwrap();
+                writer.write_signed(13, -1000i16).unwrap();
+                writer.write_bytes(b"abc").unwrap();
+            }
+            assert_eq!(data.len(), 5);
+
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(io::Cursor::new(data)));
+            assert_eq!(reader.read::<u8>(3).unwrap(), 5);
+            assert_eq!(reader.read_signed::<i16>(13).unwrap(), -1000);
+            let mut buf = [0; 3];
+            reader.read_bytes(&mut buf).unwrap();
+            assert_eq!(&buf, b"abc");
+            assert_eq!(reader.read_bit().unwrap_err().kind(),
+                       io::ErrorKind::UnexpectedEof);
+        }
+    }
+}
+
+define_no_std_roundtrip!(test_no_std_roundtrip_be, BigEndian);
+define_no_std_roundtrip!(test_no_std_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_io_buffers() {
+    static DATA: [u8; 4] = [1, 2, 3, 4];
+    let mut slice = &DATA[..];
+    let mut buf = [0; 3];
+    slice.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [1, 2, 3]);
+    assert_eq!(slice.read_exact(&mut buf).unwrap_err().kind(),
+               io::ErrorKind::UnexpectedEof);
+
+    let mut out = [0; 2];
+    {
+        let mut sink = &mut out[..];
+        sink.write_all(&[5, 6]).unwrap();
+        assert_eq!(sink.write_all(&[7]).unwrap_err().kind(),
+                   io::ErrorKind::WriteZero);
+    }
+    assert_eq!(out, [5, 6]);
+}
+
+macro_rules! define_no_alloc_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            const TABLE: [HuffmanCode<u8>; 3] = [
+                HuffmanCode{symbol: 0, len: 1, code: 0b0},
+                HuffmanCode{symbol: 1, len: 2, code: 0b10},
+                HuffmanCode{symbol: 2, len: 2, code: 0b11},
+            ];
+
+            let mut data = [0; 3];
+            {
+                let mut buf = &mut data[..];
+                let mut writer = BitWriter::<$endianness>::new(&mut buf);
+                writer.write(5, 17u8).unwrap();
+                writer.write_huffman_code(&TABLE, 2).unwrap();
+                writer.write_huffman_code(&TABLE, 0).unwrap();
+                writer.write_signed(14, -5i16).unwrap();
+                assert_eq!(writer.write(16, 0u16).unwrap_err().kind(),
+                           io::ErrorKind::WriteZero);
+            }
+
+            let mut reader =
+                BitReader::<$endianness, _>::from_read(&data[..]);
+            assert_eq!(reader.read::<u8>(5).unwrap(), 17);
+            assert_eq!(reader.read_huffman_code(&TABLE).unwrap(), 2);
+            assert_eq!(reader.read_huffman_code(&TABLE).unwrap(), 0);
+            assert_eq!(reader.read_signed::<i16>(14).unwrap(), -5);
+            assert_eq!(reader.position_in_bits(), 22);
+        }
+    }
+}
+
+define_no_alloc_roundtrip!(test_no_alloc_roundtrip_be, BigEndian);
+define_no_alloc_roundtrip!(test_no_alloc_roundtrip_le, LittleEndian);
@@ -0,0 +1,146 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::{Numeric, SignedNumeric};
+
+#[test]
+fn test_truncate() {
+    for bits in 0..=64 {
+        let expected = if bits == 64 {u64::MAX} else {(1u64 << bits) - 1};
+        assert_eq!(u64::MAX.truncate(bits), expected);
+        assert_eq!((-1i64).truncate(bits) as u64, expected);
+    }
+    for bits in 0..=8 {
+        for value in i8::MIN..=i8::MAX {
+            assert_eq!(value.truncate(bits) as u8, (value as u8).truncate(bits));
+        }
+    }
+}
+
+#[test]
+fn test_sign_extend() {
+    for bits in 1..=8 {
+        let min = -1i16 << (bits - 1);
+        let max = 1i16 << (bits - 1);
+        for value in min..max {
+            /*the truncated twos-complement form extends back to the value*/
+            let truncated = (value as i8).truncate(bits);
+            assert_eq!(i16::from(truncated.sign_extend(bits)), value);
+            assert_eq!(i64::from(value).truncate(bits).sign_extend(bits),
+                       i64::from(value));
+        }
+    }
+    assert_eq!(i64::MIN.sign_extend(64), i64::MIN);
+    assert_eq!(i64::MIN.sign_extend(63), 0);
+}
+
+#[test]
+fn test_const_construction() {
+    use bitstream_io::{BigEndian, LittleEndian, BitQueue, BitReader,
+                       ConstNumeric};
+    use bitstream_io::crc::Crc;
+    use bitstream_io::varint::VarInt;
+    use bitstream_io::zigzag;
+
+    /*all of these are evaluated at compile time*/
+    const QUEUE: BitQueue<LittleEndian, u128> = BitQueue::empty();
+    const LEN: u32 = QUEUE.len();
+    static CRC: Crc = Crc::crc32();
+    static LEB128: VarInt = VarInt::leb128();
+    const ZIGZAG: u64 = zigzag::encode(8, -128);
+    const UNZIGZAG: i64 = zigzag::decode(8, 254);
+    static DATA: [u8; 2] = [0b1011_0110, 0xFF];
+
+    assert!(QUEUE.is_empty());
+    assert_eq!(u128::ZERO, 0);
+    assert_eq!(i16::BITS_SIZE, 16);
+    assert_eq!(LEN, 0);
+    assert_eq!(QUEUE.remaining_len(), 128);
+    assert_eq!(CRC.width, 32);
+    assert_eq!(LEB128.chunk_bits(), 8);
+    assert_eq!(ZIGZAG, 255);
+    assert_eq!(UNZIGZAG, 127);
+
+    let mut reader = const {BitReader::<BigEndian, &[u8]>::from_read(&DATA)};
+    assert_eq!(reader.read::<u8>(4).unwrap(), 0b1011);
+}
+
+/*a 12-bit type implementing only Numeric's required methods*/
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+struct U12(i16);
+
+impl U12 {
+    fn masked(value: i16) -> U12 {U12(value & 0xFFF)}
+}
+
+macro_rules! forward_op {
+    ($op:ident, $method:ident, $rhs:ty, $($field:tt)*) => {
+        impl std::ops::$op<$rhs> for U12 {
+            type Output = U12;
+            fn $method(self, rhs: $rhs) -> U12 {
+                U12::masked(std::ops::$op::$method(self.0, rhs$($field)*))
+            }
+        }
+    }
+}
+
+forward_op!(Shl, shl, u32,);
+forward_op!(Shr, shr, u32,);
+forward_op!(Rem, rem, U12, .0);
+forward_op!(BitXor, bitxor, U12, .0);
+forward_op!(Sub, sub, U12, .0);
+
+impl std::ops::Not for U12 {
+    type Output = U12;
+    fn not(self) -> U12 {U12::masked(!self.0)}
+}
+
+impl std::ops::ShlAssign<u32> for U12 {
+    fn shl_assign(&mut self, rhs: u32) {*self = *self << rhs}
+}
+
+impl std::ops::ShrAssign<u32> for U12 {
+    fn shr_assign(&mut self, rhs: u32) {*self = *self >> rhs}
+}
+
+impl std::ops::RemAssign<U12> for U12 {
+    fn rem_assign(&mut self, rhs: U12) {*self = *self % rhs}
+}
+
+impl std::ops::BitOrAssign<U12> for U12 {
+    fn bitor_assign(&mut self, rhs: U12) {self.0 |= rhs.0}
+}
+
+impl Numeric for U12 {
+    fn one() -> Self {U12(1)}
+    fn is_zero(self) -> bool {self.0 == 0}
+    fn from_u8(u: u8) -> Self {U12(i16::from(u))}
+    fn to_u8(self) -> u8 {self.0 as u8}
+    fn count_ones(self) -> u32 {self.0.count_ones()}
+    fn leading_zeros(self) -> u32 {self.0.leading_zeros() - 4}
+    fn trailing_zeros(self) -> u32 {self.0.trailing_zeros().min(12)}
+    fn bits_size() -> u32 {12}
+}
+
+#[test]
+fn test_numeric_defaults() {
+    use bitstream_io::{BigEndian, BitReader};
+
+    assert_eq!(U12::from_u64(0xFFFF_FABC), U12(0xABC));
+    assert_eq!(U12::from_u64(0x123), U12(0x123));
+    assert_eq!(U12(0xABC).truncate(8), U12(0xBC));
+    assert_eq!(U12(0xABC).truncate(0), U12(0));
+    assert_eq!(U12(0xABC).truncate(12), U12(0xABC));
+
+    /*types without ConstNumeric can still be read*/
+    let data = [0xAB, 0xCD, 0xEF];
+    let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+    assert_eq!(reader.read::<U12>(12).unwrap(), U12(0xABC));
+    assert_eq!(reader.read::<U12>(12).unwrap(), U12(0xDEF));
+}
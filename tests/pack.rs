@@ -0,0 +1,93 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+mod common;
+
+use std::io::ErrorKind;
+use bitstream_io::{BigEndian, LittleEndian, BitWriter, Endianness};
+use bitstream_io::pack::{pack_u16, pack_u32};
+use bitstream_io::unpack::unpack_u32;
+use common::noise;
+
+/*packs every width over many sample counts
+  and checks the bytes against those written one at a time*/
+fn check_pack<E: Endianness>() {
+    for bits in 0..=32 {
+        let data = noise(70 * 4, u64::from(bits));
+        for count in 0..=70 {
+            let samples = data.chunks(4).take(count).map(|b| {
+                let sample = u32::from_be_bytes([b[0], b[1], b[2], b[3]]);
+                if bits == 0 {0} else {sample >> (32 - bits)}
+            }).collect::<Vec<u32>>();
+
+            let mut expected = Vec::new();
+            {
+                let mut w = BitWriter::<E>::new(&mut expected);
+                for &sample in samples.iter() {
+                    w.write(bits, sample).unwrap();
+                }
+                w.byte_align().unwrap();
+            }
+
+            let mut packed = vec![0xFF; expected.len() + 3];
+            let len = pack_u32::<E>(&samples, bits, &mut packed).unwrap();
+            assert_eq!(&packed[0..len], expected.as_slice());
+            assert!(packed[len..].iter().all(|&b| b == 0xFF));
+
+            let mut unpacked = vec![0; count];
+            unpack_u32::<E>(&packed, bits, &mut unpacked).unwrap();
+            assert_eq!(unpacked, samples);
+
+            if bits <= 16 {
+                let samples = samples.iter().map(|&s| s as u16)
+                    .collect::<Vec<u16>>();
+                let mut packed = vec![0; expected.len()];
+                assert_eq!(pack_u16::<E>(&samples, bits, &mut packed)
+                           .unwrap(), expected.len());
+                assert_eq!(packed, expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_pack_be() {
+    check_pack::<BigEndian>();
+}
+
+#[test]
+fn test_pack_le() {
+    check_pack::<LittleEndian>();
+}
+
+#[test]
+fn test_pack_errors() {
+    let mut packed = [0; 15];
+
+    let samples = [0x3FF; 12];
+    assert_eq!(pack_u16::<BigEndian>(&samples, 17, &mut packed)
+               .unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(pack_u16::<BigEndian>(&samples, 9, &mut packed)
+               .unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(pack_u16::<BigEndian>(&[0x3FF; 13], 10, &mut packed)
+               .unwrap_err().kind(), ErrorKind::WriteZero);
+    assert_eq!(pack_u16::<BigEndian>(&samples, 10, &mut packed).unwrap(),
+               15);
+    assert_eq!(packed, [0xFF; 15]);
+
+    assert_eq!(pack_u32::<LittleEndian>(&[0; 4], 33, &mut packed)
+               .unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(pack_u32::<LittleEndian>(&[1 << 31; 4], 31, &mut packed)
+               .unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(pack_u32::<LittleEndian>(&[0; 4], 31, &mut packed)
+               .unwrap_err().kind(), ErrorKind::WriteZero);
+}
@@ -0,0 +1,105 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "rayon")]
+
+extern crate bitstream_io;
+
+use std::io;
+use bitstream_io::{BigEndian, LittleEndian, BitRead, BitWriter,
+                   Endianness, FromBitStream};
+use bitstream_io::parallel::{decode_blocks, parse_blocks};
+
+#[derive(Debug, PartialEq)]
+struct Block {
+    index: u16,
+    samples: Vec<i8>,
+}
+
+impl FromBitStream for Block {
+    type Error = io::Error;
+
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+        let index = r.read(12)?;
+        let count: u8 = r.read(4)?;
+        let mut samples = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            samples.push(r.read_signed(5)?);
+        }
+        Ok(Block{index, samples})
+    }
+}
+
+// builds a buffer of blocks, each padded to a whole byte,
+// and returns it with each block's offset
+fn build_blocks<E: Endianness>(count: u16) -> (Vec<u8>, Vec<usize>) {
+    let mut data = Vec::new();
+    let mut offsets = Vec::new();
+    for index in 0..count {
+        offsets.push(data.len());
+        let mut w = BitWriter::<E>::new(&mut data);
+        let length = index % 16;
+        w.write(12, index).unwrap();
+        w.write(4, length).unwrap();
+        for i in 0..length {
+            w.write_signed(5, i as i8 - 8).unwrap();
+        }
+        w.byte_align().unwrap();
+    }
+    (data, offsets)
+}
+
+fn expected_blocks(count: u16) -> Vec<Block> {
+    (0..count).map(|index| {
+        Block{index, samples: (0..index % 16).map(|i| i as i8 - 8).collect()}
+    }).collect()
+}
+
+#[test]
+fn test_parse_blocks() {
+    let (data, offsets) = build_blocks::<BigEndian>(500);
+    assert_eq!(parse_blocks::<BigEndian, Block>(&data, &offsets).unwrap(),
+               expected_blocks(500));
+
+    let (data, offsets) = build_blocks::<LittleEndian>(500);
+    assert_eq!(parse_blocks::<LittleEndian, Block>(&data, &offsets).unwrap(),
+               expected_blocks(500));
+
+    assert!(parse_blocks::<BigEndian, Block>(&data, &[]).unwrap().is_empty());
+}
+
+#[test]
+fn test_decode_blocks_errors() {
+    let (data, mut offsets) = build_blocks::<BigEndian>(100);
+
+    /*the first failing block's error is returned*/
+    let err = decode_blocks::<BigEndian, _, io::Error, _>(
+        &data, &offsets, |r| {
+            let block = r.parse::<Block>()?;
+            if block.index % 30 == 29 {
+                Err(io::Error::new(io::ErrorKind::InvalidData,
+                                   format!("block {}", block.index)))
+            } else {
+                Ok(block)
+            }
+        }).unwrap_err();
+    assert_eq!(err.to_string(), "block 29");
+
+    /*blocks which run out of data fail*/
+    offsets[50] -= 1;
+    assert_eq!(parse_blocks::<BigEndian, Block>(&data, &offsets)
+               .unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    offsets[50] += 1;
+
+    /*invalid offsets are rejected*/
+    offsets.swap(10, 11);
+    assert_eq!(parse_blocks::<BigEndian, Block>(&data, &offsets)
+               .unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(parse_blocks::<BigEndian, Block>(&data, &[0, data.len() + 1])
+               .unwrap_err().kind(), io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,398 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter,
+                   BitRead, BitWrite, FromBitStream, ToBitStream,
+                   FromBitStreamWith, ToBitStreamWith};
+use std::io::{self, Cursor};
+
+#[derive(Debug, PartialEq)]
+struct BlockHeader {
+    last_block: bool,
+    block_type: u8,
+    block_size: u32,
+}
+
+impl FromBitStream for BlockHeader {
+    type Error = io::Error;
+
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+        Ok(BlockHeader{last_block: r.read_bit()?,
+                       block_type: r.read(7)?,
+                       block_size: r.read(24)?})
+    }
+}
+
+impl ToBitStream for BlockHeader {
+    type Error = io::Error;
+
+    fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write_bit(self.last_block)?;
+        w.write(7, self.block_type)?;
+        w.write(24, self.block_size)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum BlockError {
+    Io,
+    Truncated,
+}
+
+impl From<io::Error> for BlockError {
+    fn from(_: io::Error) -> Self {BlockError::Io}
+}
+
+/*a type composed of another, with its own error type*/
+#[derive(Debug, PartialEq)]
+struct Block {
+    header: BlockHeader,
+    offset: i16,
+    data: Vec<u8>,
+}
+
+impl FromBitStream for Block {
+    type Error = BlockError;
+
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R) ->
+        Result<Self, BlockError> {
+
+        let header: BlockHeader = r.parse()?;
+        if header.block_size < 2 {
+            return Err(BlockError::Truncated);
+        }
+        let offset = r.read_signed(12)?;
+        r.byte_align();
+        let mut data = vec![0; header.block_size as usize - 2];
+        r.read_bytes(&mut data)?;
+        Ok(Block{header, offset, data})
+    }
+}
+
+impl ToBitStream for Block {
+    type Error = BlockError;
+
+    fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) ->
+        Result<(), BlockError> {
+
+        w.build(&self.header)?;
+        w.write_signed(12, self.offset)?;
+        w.byte_align()?;
+        w.write_bytes(&self.data)?;
+        Ok(())
+    }
+}
+
+/*a type whose layout depends on a previously parsed header*/
+#[derive(Debug, PartialEq)]
+struct FrameHeader {
+    channels: u8,
+    bits_per_sample: u32,
+}
+
+#[derive(Debug, PartialEq)]
+struct Frame {
+    samples: Vec<i32>,
+}
+
+impl FromBitStreamWith<FrameHeader> for Frame {
+    type Error = io::Error;
+
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R, header: &FrameHeader) ->
+        io::Result<Self> {
+
+        let mut samples = Vec::with_capacity(header.channels as usize);
+        for _ in 0..header.channels {
+            samples.push(r.read_signed(header.bits_per_sample)?);
+        }
+        Ok(Frame{samples})
+    }
+}
+
+impl ToBitStreamWith<FrameHeader> for Frame {
+    type Error = io::Error;
+
+    fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W,
+                                       header: &FrameHeader) ->
+        io::Result<()> {
+
+        if self.samples.len() != header.channels as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "channel count mismatch"));
+        }
+        for sample in self.samples.iter() {
+            w.write_signed(header.bits_per_sample, *sample)?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! define_parse_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let blocks = vec![
+                Block{header: BlockHeader{last_block: false,
+                                          block_type: 4,
+                                          block_size: 7},
+                      offset: -300,
+                      data: b"hello".to_vec()},
+                Block{header: BlockHeader{last_block: true,
+                                          block_type: 127,
+                                          block_size: 2},
+                      offset: 2047,
+                      data: Vec::new()}];
+
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                for block in blocks.iter() {
+                    writer.build(block).unwrap();
+                }
+            }
+            assert_eq!(data.len(), 6 + 5 + 6);
+
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+            for block in blocks.iter() {
+                assert_eq!(&reader.parse::<Block>().unwrap(), block);
+            }
+            assert_eq!(reader.parse::<Block>(), Err(BlockError::Io));
+
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                writer.build(&BlockHeader{last_block: false,
+                                          block_type: 0,
+                                          block_size: 1}).unwrap();
+            }
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+            assert_eq!(reader.parse::<Block>(), Err(BlockError::Truncated));
+        }
+    }
+}
+
+define_parse_roundtrip!(test_parse_roundtrip_be, BigEndian);
+define_parse_roundtrip!(test_parse_roundtrip_le, LittleEndian);
+
+macro_rules! define_parse_count_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let headers: Vec<BlockHeader> = (0..5).map(|i| {
+                BlockHeader{last_block: i == 4,
+                            block_type: i,
+                            block_size: 1 << (i * 5)}
+            }).collect();
+
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                for header in headers.iter() {
+                    writer.build(header).unwrap();
+                }
+            }
+
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(
+                    data.clone())));
+            assert_eq!(reader.parse_count::<BlockHeader>(0).unwrap(), vec![]);
+            assert_eq!(reader.parse_count::<BlockHeader>(5).unwrap(), headers);
+            assert!(reader.parse_count::<BlockHeader>(1).is_err());
+
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(
+                    data.clone())));
+            assert_eq!(reader.parse_try_fold(5, 0, |total, h: BlockHeader| {
+                Ok(total + h.block_size)
+            }).unwrap(), 1 + (1 << 5) + (1 << 10) + (1 << 15) + (1 << 20));
+
+            /*errors from the function stop the fold*/
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+            assert_eq!(reader.parse_try_fold(5, 0, |count, h: BlockHeader| {
+                if h.block_type < 2 {
+                    Ok(count + 1)
+                } else {
+                    Err(io::Error::new(io::ErrorKind::InvalidData, "type"))
+                }
+            }).unwrap_err().kind(), io::ErrorKind::InvalidData);
+            assert_eq!(reader.parse::<BlockHeader>().unwrap(), headers[3]);
+        }
+    }
+}
+
+define_parse_count_roundtrip!(test_parse_count_roundtrip_be, BigEndian);
+define_parse_count_roundtrip!(test_parse_count_roundtrip_le, LittleEndian);
+
+macro_rules! define_optional_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let header = BlockHeader{last_block: true,
+                                     block_type: 5,
+                                     block_size: 0x12345};
+            let block = Block{header: BlockHeader{last_block: false,
+                                                  block_type: 1,
+                                                  block_size: 3},
+                              offset: -1,
+                              data: vec![0xAA]};
+
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                writer.write_if(Some(&header)).unwrap();
+                writer.write_if::<BlockHeader>(None).unwrap();
+                writer.write_option_flagged(Some(&header)).unwrap();
+                writer.write_option_flagged::<BlockHeader>(None).unwrap();
+                writer.write_option_flagged(Some(&block)).unwrap();
+                writer.byte_align().unwrap();
+            }
+            assert_eq!(data.len(), 15);
+
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+            assert_eq!(reader.read_if::<BlockHeader>(true).unwrap(),
+                       Some(header));
+            assert_eq!(reader.read_if::<BlockHeader>(false).unwrap(), None);
+            assert_eq!(reader.read_option_flagged::<BlockHeader>().unwrap(),
+                       Some(BlockHeader{last_block: true,
+                                        block_type: 5,
+                                        block_size: 0x12345}));
+            assert_eq!(reader.read_option_flagged::<BlockHeader>().unwrap(),
+                       None);
+            assert_eq!(reader.read_option_flagged::<Block>(), Ok(Some(block)));
+            assert_eq!(reader.read_option_flagged::<Block>(),
+                       Err(BlockError::Io));
+        }
+    }
+}
+
+define_optional_roundtrip!(test_optional_roundtrip_be, BigEndian);
+define_optional_roundtrip!(test_optional_roundtrip_le, LittleEndian);
+
+macro_rules! define_parse_with_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let headers = [FrameHeader{channels: 2, bits_per_sample: 5},
+                           FrameHeader{channels: 3, bits_per_sample: 17}];
+            let frames = [Frame{samples: vec![-16, 15]},
+                          Frame{samples: vec![-65536, 0, 65535]}];
+
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                for (frame, header) in frames.iter().zip(headers.iter()) {
+                    writer.build_with(frame, header).unwrap();
+                }
+                assert!(writer.build_with(&frames[0], &headers[1]).is_err());
+                writer.byte_align().unwrap();
+            }
+            assert_eq!(data.len(), 8);
+
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+            for (frame, header) in frames.iter().zip(headers.iter()) {
+                assert_eq!(&reader.parse_with::<_, Frame>(header).unwrap(),
+                           frame);
+            }
+        }
+    }
+}
+
+define_parse_with_roundtrip!(test_parse_with_roundtrip_be, BigEndian);
+define_parse_with_roundtrip!(test_parse_with_roundtrip_le, LittleEndian);
+
+macro_rules! define_length_prefixed_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                /*a fully-consumed block*/
+                writer.write(8, 2u8).unwrap();
+                writer.write(12, 0xABCu16).unwrap();
+                writer.write(4, 0x5u8).unwrap();
+                /*a block with trailing data the reader doesn't know about*/
+                writer.write(8, 3u8).unwrap();
+                writer.write_bit(true).unwrap();
+                writer.write(23, 0x12_3456u32).unwrap();
+                /*a block too short for its contents*/
+                writer.write(8, 1u8).unwrap();
+                writer.write(8, 0xFFu8).unwrap();
+                writer.write(8, 0x77u8).unwrap();
+            }
+
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+            assert_eq!(reader.read_length_prefixed(8, |block| {
+                let a = block.read::<u16>(12)?;
+                let b = block.read::<u8>(4)?;
+                assert_eq!(block.remaining(), 0);
+                Ok::<_, io::Error>((a, b))
+            }).unwrap(), (0xABC, 0x5));
+            assert!(reader.read_length_prefixed(8, |block| {
+                let flag = block.read_bit()?;
+                assert_eq!(block.remaining(), 23);
+                Ok::<_, io::Error>(flag)
+            }).unwrap());
+            assert_eq!(reader.read_length_prefixed(8, |block| {
+                block.read::<u16>(16)
+            }).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+            /*the failed read consumed nothing, so the data follows*/
+            assert_eq!(reader.read::<u8>(8).unwrap(), 0xFF);
+            assert_eq!(reader.read::<u8>(8).unwrap(), 0x77);
+        }
+    }
+}
+
+define_length_prefixed_roundtrip!(test_length_prefixed_roundtrip_be, BigEndian);
+define_length_prefixed_roundtrip!(test_length_prefixed_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_scoped_bits() {
+    let data = [0x04, 0x00, 0x00, 0x22, 0x84, 0x00, 0x00, 0x22, 0xFF];
+    let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+
+    /*a structure reading exactly its budget*/
+    assert_eq!(reader.scoped_bits(32, |r| r.parse::<BlockHeader>()).unwrap(),
+               BlockHeader{last_block: false, block_type: 4, block_size: 34});
+
+    /*budgets one bit too large or too small*/
+    let mut too_large = reader.clone();
+    assert_eq!(too_large.scoped_bits(33, |r| r.parse::<BlockHeader>())
+               .unwrap_err().kind(), io::ErrorKind::InvalidData);
+    let mut too_small = reader.clone();
+    assert_eq!(too_small.scoped_bits(31, |r| r.parse::<BlockHeader>())
+               .unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+    /*an upper bound leaves any unread bits in the stream*/
+    assert!(reader.scoped_bits_at_most(40, |r| r.parse::<BlockHeader>())
+            .unwrap().last_block);
+    assert_eq!(reader.read::<u8>(8).unwrap(), 0xFF);
+}
+
+#[test]
+fn test_limited_rejected_read() {
+    let data = [0xAB, 0xCD];
+    let mut reader = BitReader::<BigEndian, _>::from_read(&data[..]);
+
+    /*a read too wide for its type doesn't use up the limit*/
+    reader.read_limited(16, |r| {
+        assert!(r.read::<u8>(9).is_err());
+        assert!(r.read_signed::<i8>(9).is_err());
+        assert_eq!(r.remaining(), 16);
+        assert_eq!(r.read::<u16>(16)?, 0xABCD);
+        assert_eq!(r.remaining(), 0);
+        Ok::<_, io::Error>(())
+    }).unwrap();
+}
@@ -0,0 +1,70 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(all(feature = "perf-counters", feature = "std"))]
+
+extern crate bitstream_io;
+
+use std::io::Cursor;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::perf::PerfCounters;
+
+#[test]
+fn test_reader_counters() {
+    let data = (0..64).collect::<Vec<u8>>();
+    let mut r = BitReader::<BigEndian, _>::from_read(Cursor::new(&data));
+    assert_eq!(r.perf_counters(), PerfCounters::new());
+
+    /*an aligned value is read with a single call*/
+    assert_eq!(r.read::<u32>(32).unwrap(), 0x00010203);
+    assert_eq!(r.perf_counters(),
+               PerfCounters{refills: 1, misaligned: 0, bytes: 4});
+
+    /*as are whole bytes, even when they must be shifted*/
+    r.read::<u8>(4).unwrap();
+    let mut buf = [0; 8];
+    r.read_bytes(&mut buf).unwrap();
+    assert_eq!(r.perf_counters(),
+               PerfCounters{refills: 3, misaligned: 1, bytes: 13});
+
+    /*bytes skipped by seeking aren't transferred*/
+    r.seek_bits(400).unwrap();
+    assert_eq!(r.read::<u8>(8).unwrap(), 50);
+    assert_eq!(r.perf_counters().bytes, 14);
+    assert_eq!(r.position_in_bits(), 408);
+
+    /*clones continue from the same counts*/
+    let mut lookahead = r.clone();
+    lookahead.read::<u16>(16).unwrap();
+    assert_eq!(lookahead.perf_counters().bytes, 16);
+    assert_eq!(r.perf_counters().bytes, 14);
+}
+
+#[test]
+fn test_writer_counters() {
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<LittleEndian, _>::from_write(&mut data);
+        w.write(32, 0x03020100u32).unwrap();
+        assert_eq!(w.perf_counters(),
+                   PerfCounters{refills: 1, misaligned: 0, bytes: 4});
+
+        w.write_bytes(&[4, 5, 6]).unwrap();
+        w.write_aligned(0x0807u16).unwrap();
+        assert_eq!(w.perf_counters(),
+                   PerfCounters{refills: 3, misaligned: 0, bytes: 9});
+
+        /*each unaligned byte is written on its own*/
+        w.write(4, 9u8).unwrap();
+        w.write_bytes(&[0xA0, 0xB0]).unwrap();
+        w.write(4, 0u8).unwrap();
+        assert_eq!(w.perf_counters(),
+                   PerfCounters{refills: 6, misaligned: 1, bytes: 12});
+    }
+    assert_eq!(data, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0x0A, 0x0B]);
+}
@@ -0,0 +1,94 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "prefetch")]
+
+extern crate bitstream_io;
+
+use std::io::{self, BufRead, Read};
+use bitstream_io::{BigEndian, LittleEndian, BitReader};
+use bitstream_io::prefetch::PrefetchReader;
+
+/*a source which returns a few bytes per read,
+  then fails if given an error to fail with*/
+struct Trickle {
+    data: Vec<u8>,
+    position: usize,
+    error: Option<io::ErrorKind>,
+}
+
+impl Read for Trickle {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.data.len() {
+            return match self.error.take() {
+                Some(kind) => Err(io::Error::new(kind, "source failed")),
+                None => Ok(0),
+            };
+        }
+        let size = buf.len().min(3).min(self.data.len() - self.position);
+        buf[..size].copy_from_slice(&self.data[self.position..][..size]);
+        self.position += size;
+        Ok(size)
+    }
+}
+
+fn trickle(data: Vec<u8>, error: Option<io::ErrorKind>) -> Trickle {
+    Trickle{data, position: 0, error}
+}
+
+#[test]
+fn test_prefetch_reader() {
+    let data: Vec<u8> = (0..10000u32).map(|i| (i * 7) as u8).collect();
+
+    let mut source = PrefetchReader::with_capacity(
+        5, trickle(data.clone(), None));
+    let mut read = Vec::new();
+    source.read_to_end(&mut read).unwrap();
+    assert_eq!(read, data);
+
+    let source = PrefetchReader::new(trickle(data.clone(), None));
+    let mut r = BitReader::<BigEndian, _>::from_read(source);
+    for &byte in data.iter() {
+        assert_eq!(r.read::<u8>(3).unwrap(), byte >> 5);
+        assert_eq!(r.read::<u8>(5).unwrap(), byte & 0x1F);
+    }
+    assert_eq!(r.read::<u8>(1).unwrap_err().kind(),
+               io::ErrorKind::UnexpectedEof);
+
+    let source = PrefetchReader::with_capacity(7, trickle(data.clone(), None));
+    let mut r = BitReader::<LittleEndian, _>::from_read(source);
+    for &byte in data.iter() {
+        assert_eq!(r.read::<u8>(3).unwrap(), byte & 0x7);
+        assert_eq!(r.read::<u8>(5).unwrap(), byte >> 3);
+    }
+}
+
+#[test]
+fn test_prefetch_buf_read() {
+    let mut source = PrefetchReader::with_capacity(
+        4, trickle(b"first line\nsecond line\n".to_vec(), None));
+    let mut line = String::new();
+    source.read_line(&mut line).unwrap();
+    assert_eq!(line, "first line\n");
+    line.clear();
+    source.read_line(&mut line).unwrap();
+    assert_eq!(line, "second line\n");
+    assert!(source.fill_buf().unwrap().is_empty());
+}
+
+#[test]
+fn test_prefetch_errors() {
+    let mut source = PrefetchReader::with_capacity(
+        4, trickle(vec![1, 2, 3, 4, 5], Some(io::ErrorKind::BrokenPipe)));
+    let mut buf = [0; 5];
+    source.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [1, 2, 3, 4, 5]);
+    assert_eq!(source.read(&mut buf).unwrap_err().kind(),
+               io::ErrorKind::BrokenPipe);
+    assert_eq!(source.read(&mut buf).unwrap(), 0);
+}
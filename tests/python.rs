@@ -0,0 +1,111 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(all(feature = "python", not(feature = "python-extension")))]
+
+extern crate pyo3;
+extern crate bitstream_io;
+
+use std::ffi::CString;
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+
+/*runs the given Python code with the module importable*/
+fn run_python(code: &str) {
+    Python::initialize();
+    Python::attach(|py| {
+        let module = PyModule::new(py, "bitstream_io").unwrap();
+        bitstream_io::python::bitstream_io(&module).unwrap();
+        py.import("sys").unwrap()
+            .getattr("modules").unwrap()
+            .set_item("bitstream_io", module).unwrap();
+        let code = CString::new(code).unwrap();
+        if let Err(err) = py.run(&code, None, None) {
+            err.print(py);
+            panic!("Python code failed");
+        }
+    })
+}
+
+#[test]
+fn test_python_roundtrip() {
+    run_python("
+from bitstream_io import BitReader, BitWriter, HuffmanTree
+
+tree = HuffmanTree([(0, [0]), (1, [1, 0]), (2, [1, 1, 0]), (-3, [1, 1, 1])])
+for little_endian in [False, True]:
+    w = BitWriter(little_endian=little_endian)
+    w.write(4, 0b1011)
+    w.write_signed(5, -3)
+    w.write_bit(True)
+    w.write_unary0(3)
+    w.write_unary1(2)
+    for symbol in [2, -3, 0, 1]:
+        w.write_huffman(tree, symbol)
+    w.write(64, 2 ** 64 - 1)
+    assert not w.byte_aligned()
+    w.byte_align()
+    w.write_bytes(b'xyz')
+    data = w.finish()
+
+    r = BitReader(data, little_endian=little_endian)
+    assert r.read(4) == 0b1011
+    assert r.read_signed(5) == -3
+    assert r.read_bit()
+    assert r.read_unary0() == 3
+    assert r.read_unary1() == 2
+    assert [r.read_huffman(tree) for _ in range(4)] == [2, -3, 0, 1]
+    assert r.read(64) == 2 ** 64 - 1
+    r.byte_align()
+    assert r.byte_aligned()
+    assert r.read_bytes(3) == b'xyz'
+    r.seek(4)
+    assert r.position() == 4
+    assert r.read_signed(5) == -3
+");
+}
+
+#[test]
+fn test_python_big_endian() {
+    run_python("
+from bitstream_io import BitReader, BitWriter
+
+w = BitWriter()
+w.write(4, 0xA)
+w.write(12, 0x123)
+assert w.finish() == bytes([0xA1, 0x23])
+
+w = BitWriter(True)
+w.write(4, 0xA)
+w.write(12, 0x123)
+assert w.finish() == bytes([0x3A, 0x12])
+");
+}
+
+#[test]
+fn test_python_errors() {
+    run_python("
+from bitstream_io import BitReader, BitWriter, HuffmanTree
+
+def raises(exception, f):
+    try:
+        f()
+    except exception:
+        return True
+    return False
+
+r = BitReader(bytes([0xFF]))
+assert raises(EOFError, lambda: r.read(9))
+assert raises(ValueError, lambda: r.read(65))
+w = BitWriter()
+assert raises(ValueError, lambda: w.write(4, 16))
+tree = HuffmanTree([(0, [0]), (1, [1])])
+assert raises(ValueError, lambda: w.write_huffman(tree, 2))
+assert raises(ValueError, lambda: HuffmanTree([(0, [0]), (1, [0])]))
+");
+}
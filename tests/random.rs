@@ -0,0 +1,89 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+use std::io::Read;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, Endianness};
+use bitstream_io::random::{Pattern, RandomSource};
+use bitstream_io::stats::{Analyzer, Statistics};
+
+fn analyze<E: Endianness>(seed: u64, pattern: Pattern) -> Statistics {
+    let source = RandomSource::<E>::new(seed, pattern).take(1 << 16);
+    let mut reader = BitReader::<E, _>::from_read(source);
+    Analyzer::default().analyze(&mut reader).unwrap()
+}
+
+#[test]
+fn test_random_reproducible() {
+    let mut first = [0; 16];
+    let mut second = [0; 16];
+    RandomSource::<BigEndian>::new(0, Pattern::Uniform)
+        .read_exact(&mut first).unwrap();
+    /*reads of any size give the same stream*/
+    let mut source = RandomSource::<BigEndian>::new(0, Pattern::Uniform);
+    for chunk in second.chunks_mut(3) {
+        source.read_exact(chunk).unwrap();
+    }
+    assert_eq!(first, second);
+    assert_eq!(first[..8], 0xE220_A839_7B1D_CDAFu64.to_le_bytes());
+
+    RandomSource::<BigEndian>::new(1, Pattern::Uniform)
+        .read_exact(&mut second).unwrap();
+    assert_ne!(first, second);
+
+    let pattern = Pattern::Runs{mean_zeros: 3.0, mean_ones: 5.0};
+    RandomSource::<LittleEndian>::new(7, pattern)
+        .read_exact(&mut first).unwrap();
+    RandomSource::<LittleEndian>::new(7, pattern)
+        .read_exact(&mut second).unwrap();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_random_statistics() {
+    let stats = analyze::<BigEndian>(1, Pattern::Uniform);
+    assert_eq!(stats.bits, 1 << 19);
+    assert!((stats.ones_density() - 0.5).abs() < 0.01);
+    assert!(stats.entropy.iter().all(|&entropy| entropy > 0.9));
+
+    for &density in [0.0, 0.1, 0.75, 1.0].iter() {
+        let stats = analyze::<LittleEndian>(2, Pattern::Density(density));
+        assert!((stats.ones_density() - density).abs() < 0.01);
+    }
+
+    /*runs are counted in stream order, whatever the endianness*/
+    let pattern = Pattern::Runs{mean_zeros: 2.0, mean_ones: 8.0};
+    for stats in [analyze::<BigEndian>(3, pattern),
+                  analyze::<LittleEndian>(3, pattern)].iter() {
+        assert!((stats.ones_density() - 0.8).abs() < 0.02);
+        let mean = |runs: &std::collections::BTreeMap<u64, u64>| {
+            let count: u64 = runs.values().sum();
+            let total: u64 = runs.iter().map(|(len, n)| len * n).sum();
+            total as f64 / count as f64
+        };
+        assert!((mean(&stats.zero_runs) - 2.0).abs() < 0.1);
+        assert!((mean(&stats.one_runs) - 8.0).abs() < 0.4);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_random_invalid_density() {
+    RandomSource::<BigEndian>::new(0, Pattern::Density(1.5));
+}
+
+#[test]
+#[should_panic]
+fn test_random_invalid_runs() {
+    RandomSource::<BigEndian>::new(0,
+                                   Pattern::Runs{mean_zeros: 0.5,
+                                                 mean_ones: 2.0});
+}
@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![allow(clippy::bool_assert_comparison, clippy::unusual_byte_groupings)]
+
 extern crate bitstream_io;
 use std::boxed::Box;
 use std::io::Cursor;
@@ -58,6 +60,117 @@ fn test_read_queue_le() {
     assert_eq!(q.value(), 0);
 }
 
+#[test]
+fn test_queue_wide() {
+    use bitstream_io::{BE, LE, BitQueue};
+
+    let mut q: BitQueue<BE,u128> = BitQueue::new();
+    assert_eq!(q.max_len(), 128);
+    q.push(64, 0x0123_4567_89AB_CDEF);
+    q.push(64, 0xFEDC_BA98_7654_3210);
+    assert!(q.is_full());
+    /*popping or dropping nothing from a full queue leaves it as-is*/
+    assert_eq!(q.pop(0), 0);
+    q.drop(0);
+    assert_eq!(q.peek(12), 0x012);
+    assert_eq!(q.len(), 128);
+    assert_eq!(q.pop(72), 0x01_2345_6789_ABCD_EFFE);
+    assert_eq!(q.peek(56), 0xDC_BA98_7654_3210);
+    assert_eq!(q.pop(56), 0xDC_BA98_7654_3210);
+    assert!(q.is_empty());
+
+    let mut q: BitQueue<LE,u128> = BitQueue::new();
+    q.push(64, 0x0123_4567_89AB_CDEF);
+    q.push(64, 0xFEDC_BA98_7654_3210);
+    assert_eq!(q.pop(0), 0);
+    assert_eq!(q.peek(12), 0xDEF);
+    assert_eq!(q.pop(72), 0x10_0123_4567_89AB_CDEF);
+    assert_eq!(q.pop(56), 0xFE_DCBA_9876_5432);
+    assert!(q.is_empty());
+
+    let mut q: BitQueue<BE,u64> = BitQueue::from_value(!0, 64);
+    assert_eq!(q.pop(0), 0);
+    q.drop(0);
+    assert_eq!(q.pop(64), !0);
+}
+
+#[test]
+fn test_reader_wide() {
+    use bitstream_io::{BE, LE, BitReader};
+    use bitstream_io::mixed::MixedEndian;
+    let data = [0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF,
+                0xFE, 0xDC, 0xBA, 0x98, 0x76, 0x54, 0x32, 0x10, 0x80];
+
+    let mut r = BitReader::<BE>::new(Box::new(Cursor::new(data)));
+    assert_eq!(r.read::<u128>(128).unwrap(),
+               0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210);
+    let mut r = BitReader::<BE>::new(Box::new(Cursor::new(data)));
+    assert_eq!(r.read::<u8>(4).unwrap(), 0);
+    assert_eq!(r.read::<u128>(124).unwrap(),
+               0x123_4567_89AB_CDEF_FEDC_BA98_7654_3210);
+    assert_eq!(r.read_signed::<i128>(8).unwrap(), -128);
+    let mut r = BitReader::<BE>::new(Box::new(Cursor::new(data)));
+    assert_eq!(r.read_signed::<i128>(72).unwrap(), 0x01_2345_6789_ABCD_EFFE);
+    assert_eq!(r.read_aligned::<i128>().unwrap_err().kind(),
+               std::io::ErrorKind::UnexpectedEof);
+    let mut r = BitReader::<BE>::new(Box::new(Cursor::new(data)));
+    assert_eq!(r.read_mixed_endian::<u128>(MixedEndian::Pdp).unwrap(),
+               0x2301_6745_AB89_EFCD_DCFE_98BA_5476_1032);
+
+    let mut r = BitReader::<LE>::new(Box::new(Cursor::new(data)));
+    assert_eq!(r.read::<u128>(128).unwrap(),
+               0x1032_5476_98BA_DCFE_EFCD_AB89_6745_2301);
+    let mut r = BitReader::<LE>::new(Box::new(Cursor::new(data)));
+    assert_eq!(r.read::<u8>(4).unwrap(), 1);
+    assert_eq!(r.read::<u128>(124).unwrap(),
+               0x103_2547_698B_ADCF_EEFC_DAB8_9674_5230);
+    assert_eq!(r.read_signed::<i128>(8).unwrap(), -128);
+}
+
+#[test]
+fn test_reader_signed_widths() {
+    use bitstream_io::{BE, LE, BitRead, BitReader, Endianness};
+    use bitstream_io::word::WordReader;
+
+    /*every width of every signed type, including the full width,
+      matches a sign bit and magnitude read separately*/
+    fn check<E: Endianness>(little: bool) {
+        let data = (0..64u32).map(|i| (i * 0x9D + 0x31) as u8)
+            .collect::<Vec<u8>>();
+        for bits in 1..=64 {
+            let mut r = BitReader::<E, _>::from_read(&data[..]);
+            let mut expected = BitReader::<E, _>::from_read(&data[..]);
+            let mut w = WordReader::<E, _>::new(&data[..]);
+            for _ in 0..(data.len() * 8 / bits as usize) {
+                let (sign, magnitude) = if little {
+                    let magnitude = expected.read::<i64>(bits - 1).unwrap();
+                    (expected.read_bit().unwrap(), magnitude)
+                } else {
+                    let sign = expected.read_bit().unwrap();
+                    (sign, expected.read::<i64>(bits - 1).unwrap())
+                };
+                let value = if sign {
+                    magnitude.wrapping_sub(1i64.wrapping_shl(bits - 1))
+                } else {
+                    magnitude
+                };
+                assert_eq!(r.read_signed::<i64>(bits).unwrap(), value);
+                assert_eq!(w.read_signed::<i64>(bits).unwrap(), value);
+            }
+        }
+
+        let full = [0x80, 0xFF, 0x7F, 0x01];
+        let mut r = BitReader::<E, _>::from_read(&full[..]);
+        assert_eq!(r.read_signed::<i8>(8).unwrap(), -128);
+        assert_eq!(r.read_signed::<i8>(8).unwrap(), -1);
+        assert_eq!(r.read_signed::<i16>(16).unwrap(),
+                   if little {0x017F} else {0x7F01});
+    }
+
+    check::<BE>(false);
+    check::<LE>(true);
+}
+
 #[test]
 fn test_reader_be() {
     use bitstream_io::{BE, BitReader};
@@ -66,7 +179,7 @@ fn test_reader_be() {
 
     {
         /*reading individual bits*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<BE>::new(Box::new(c));
         assert_eq!(r.read_bit().unwrap(), true);
         assert_eq!(r.read_bit().unwrap(), false);
@@ -87,7 +200,7 @@ fn test_reader_be() {
     }
     {
         /*reading unsigned values*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<BE>::new(Box::new(c));
         assert!(r.byte_aligned());
         assert_eq!(r.read::<u32>(2).unwrap(), 2);
@@ -104,7 +217,7 @@ fn test_reader_be() {
     }
     {
         /*skipping bits*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<BE>::new(Box::new(c));
         assert_eq!(r.read::<u32>(2).unwrap(), 2);
         assert!(r.skip(3).is_ok());
@@ -114,7 +227,7 @@ fn test_reader_be() {
     }
     {
         /*reading signed values*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<BE>::new(Box::new(c));
         assert_eq!(r.read_signed::<i32>(2).unwrap(), -2);
         assert_eq!(r.read_signed::<i32>(3).unwrap(), -2);
@@ -124,7 +237,7 @@ fn test_reader_be() {
     }
     {
         /*reading unary 0 values*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<BE>::new(Box::new(c));
         assert_eq!(r.read_unary0().unwrap(), 1);
         assert_eq!(r.read_unary0().unwrap(), 2);
@@ -134,7 +247,7 @@ fn test_reader_be() {
     }
     {
         /*reading unary 1 values*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<BE>::new(Box::new(c));
         assert_eq!(r.read_unary1().unwrap(), 0);
         assert_eq!(r.read_unary1().unwrap(), 1);
@@ -144,7 +257,7 @@ fn test_reader_be() {
     }
     {
         /*byte aligning*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<BE>::new(Box::new(c));
         assert_eq!(r.read::<u32>(3).unwrap(), 5);
         r.byte_align();
@@ -157,7 +270,7 @@ fn test_reader_be() {
     }
     {
         /*reading bytes, aligned*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<BE>::new(Box::new(c));
         let mut sub_data = [0; 2];
         assert!(r.read_bytes(&mut sub_data).is_ok());
@@ -165,7 +278,7 @@ fn test_reader_be() {
     }
     {
         /*reading bytes, un-aligned*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<BE>::new(Box::new(c));
         let mut sub_data = [0; 2];
         assert_eq!(r.read::<u32>(4).unwrap(), 11);
@@ -237,7 +350,7 @@ fn test_reader_huffman_be() {
              (4, vec![0, 0, 0])]).unwrap();
 
     let actual_data: [u8;4] = [0xB1, 0xED, 0x3B, 0xC1];
-    let c = Cursor::new(actual_data.clone());
+    let c = Cursor::new(actual_data);
     let mut r = BitReader::<BE>::new(Box::new(c));
 
     assert_eq!(r.read_huffman(&tree).unwrap(), 1);
@@ -265,7 +378,7 @@ fn test_reader_le() {
 
     {
         /*reading individual bits*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<LE>::new(Box::new(c));
         assert_eq!(r.read_bit().unwrap(), true);
         assert_eq!(r.read_bit().unwrap(), false);
@@ -286,7 +399,7 @@ fn test_reader_le() {
     }
     {
         /*reading unsigned values*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<LE>::new(Box::new(c));
         assert!(r.byte_aligned());
         assert_eq!(r.read::<u32>(2).unwrap(), 1);
@@ -303,7 +416,7 @@ fn test_reader_le() {
     }
     {
         /*skipping bits*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<LE>::new(Box::new(c));
         assert_eq!(r.read::<u32>(2).unwrap(), 1);
         assert!(r.skip(3).is_ok());
@@ -313,7 +426,7 @@ fn test_reader_le() {
     }
     {
         /*reading signed values*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<LE>::new(Box::new(c));
         assert_eq!(r.read_signed::<i32>(2).unwrap(), 1);
         assert_eq!(r.read_signed::<i32>(3).unwrap(), -4);
@@ -323,7 +436,7 @@ fn test_reader_le() {
     }
     {
         /*reading unary 0 values*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<LE>::new(Box::new(c));
         assert_eq!(r.read_unary0().unwrap(), 1);
         assert_eq!(r.read_unary0().unwrap(), 0);
@@ -333,7 +446,7 @@ fn test_reader_le() {
     }
     {
         /*reading unary 1 values*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<LE>::new(Box::new(c));
         assert_eq!(r.read_unary1().unwrap(), 0);
         assert_eq!(r.read_unary1().unwrap(), 3);
@@ -343,7 +456,7 @@ fn test_reader_le() {
     }
     {
         /*byte aligning*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<LE>::new(Box::new(c));
         assert_eq!(r.read::<u32>(3).unwrap(), 1);
         r.byte_align();
@@ -356,7 +469,7 @@ fn test_reader_le() {
     }
     {
         /*reading bytes, aligned*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<LE>::new(Box::new(c));
         let mut sub_data = [0; 2];
         assert!(r.read_bytes(&mut sub_data).is_ok());
@@ -364,7 +477,7 @@ fn test_reader_le() {
     }
     {
         /*reading bytes, un-aligned*/
-        let c = Cursor::new(actual_data.clone());
+        let c = Cursor::new(actual_data);
         let mut r = BitReader::<LE>::new(Box::new(c));
         let mut sub_data = [0; 2];
         assert_eq!(r.read::<u32>(4).unwrap(), 1);
@@ -660,3 +773,595 @@ fn test_reader_bits_errors() {
                    ErrorKind::InvalidInput);
     }
 }
+
+#[test]
+fn test_reader_position() {
+    use bitstream_io::{BE, LE, BitReader};
+    let actual_data: [u8; 8] = [0xB1, 0xED, 0x3B, 0xC1,
+                                0x00, 0x00, 0x00, 0x01];
+
+    {
+        let mut r = BitReader::<BE>::new(Box::new(Cursor::new(actual_data)));
+        assert_eq!(r.position_in_bits(), 0);
+        r.read_bit().unwrap();
+        assert_eq!(r.position_in_bits(), 1);
+        r.read::<u16>(10).unwrap();
+        assert_eq!(r.position_in_bits(), 11);
+        assert_eq!(r.read_unary0().unwrap(), 0);
+        assert_eq!(r.position_in_bits(), 12);
+        r.byte_align();
+        assert_eq!(r.position_in_bits(), 16);
+        let mut buf = [0; 2];
+        r.read_bytes(&mut buf).unwrap();
+        assert_eq!(r.position_in_bits(), 32);
+        r.skip(31).unwrap();
+        assert_eq!(r.position_in_bits(), 63);
+        assert!(r.read::<u8>(2).is_err());
+    }
+    {
+        let mut r = BitReader::<LE>::new(Box::new(Cursor::new(actual_data)));
+        r.read_signed::<i32>(17).unwrap();
+        assert_eq!(r.position_in_bits(), 17);
+        assert_eq!(r.read_unary1().unwrap(), 0);
+        assert_eq!(r.position_in_bits(), 18);
+        r.skip(38).unwrap();
+        assert_eq!(r.position_in_bits(), 56);
+        r.read::<u8>(8).unwrap();
+        assert_eq!(r.position_in_bits(), 64);
+    }
+}
+
+#[test]
+fn test_reader_seek() {
+    use std::io::{Seek, SeekFrom};
+    use bitstream_io::{BE, LE, BitReader};
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+
+    {
+        let mut r = BitReader::<BE, _>::from_read(Cursor::new(actual_data));
+        r.seek_bits(12).unwrap();
+        assert_eq!(r.read::<u8>(8).unwrap(), 0xD3);
+        assert_eq!(r.position_in_bits(), 20);
+        r.seek_bits(0).unwrap();
+        assert_eq!(r.read::<u8>(4).unwrap(), 0xB);
+        r.seek_bits(31).unwrap();
+        assert_eq!(r.read_bit().unwrap(), true);
+        assert!(r.read_bit().is_err());
+        r.seek_bits(16).unwrap();
+        assert!(r.byte_aligned());
+        assert_eq!(r.read::<u16>(16).unwrap(), 0x3BC1);
+    }
+    {
+        let mut r = BitReader::<LE, _>::from_read(Cursor::new(actual_data));
+        r.seek_bits(9).unwrap();
+        assert_eq!(r.read::<u8>(3).unwrap(), 0b110);
+        r.seek_bits(4).unwrap();
+        assert_eq!(r.read::<u8>(4).unwrap(), 0xB);
+    }
+    {
+        /*positions count from where the reader was created*/
+        let mut c = Cursor::new(actual_data);
+        c.seek(SeekFrom::Start(1)).unwrap();
+        let mut r = BitReader::<BE, _>::from_read(c);
+        r.seek_bits(8).unwrap();
+        assert_eq!(r.read::<u8>(8).unwrap(), 0x3B);
+        r.seek_bits(4).unwrap();
+        assert_eq!(r.read::<u8>(4).unwrap(), 0xD);
+    }
+}
+
+#[test]
+fn test_reader_into_byte_reader() {
+    use std::io::Read;
+    use bitstream_io::{BE, LE, BitReader};
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+
+    /*aligned readers lose nothing*/
+    let mut r = BitReader::<BE, _>::from_read(&actual_data[..]);
+    assert_eq!(r.read::<u8>(8).unwrap(), 0xB1);
+    let mut rest = Vec::new();
+    r.into_byte_reader().read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, [0xED, 0x3B, 0xC1]);
+
+    /*unaligned readers drop the rest of the partial byte*/
+    let mut r = BitReader::<LE, _>::from_read(Cursor::new(actual_data));
+    assert_eq!(r.read::<u16>(9).unwrap(), 0x1B1);
+    let mut c = r.into_byte_reader();
+    assert_eq!(c.position(), 2);
+    let mut rest = Vec::new();
+    c.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, [0x3B, 0xC1]);
+}
+
+#[test]
+fn test_reader_clone() {
+    use bitstream_io::{BE, LE, BitReader};
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+
+    /*clones continue from the same bit as the original*/
+    let mut r = BitReader::<BE, _>::from_read(Cursor::new(&actual_data[..]));
+    assert_eq!(r.read::<u8>(3).unwrap(), 5);
+    let mut c = r.clone();
+    assert_eq!(c.position_in_bits(), 3);
+    assert_eq!(c.read::<u8>(5).unwrap(), 17);
+    assert_eq!(c.read::<u16>(16).unwrap(), 0xED3B);
+    assert_eq!(r.read::<u8>(5).unwrap(), 17);
+    assert_eq!(r.position_in_bits(), 8);
+
+    /*and reading from one doesn't disturb the other*/
+    let mut r = BitReader::<LE, _>::from_read(&actual_data[..]);
+    assert_eq!(r.read::<u8>(3).unwrap(), 1);
+    let mut c = r.clone();
+    assert_eq!(c.read::<u32>(29).unwrap(), 0x18277DB6);
+    assert!(c.read::<u8>(1).is_err());
+    assert_eq!(r.read::<u8>(5).unwrap(), 22);
+    assert_eq!(r.read::<u8>(8).unwrap(), 0xED);
+}
+
+#[test]
+fn test_reader_aligned_widths() {
+    use bitstream_io::{BE, LE, BitReader};
+    let actual_data: [u8; 8] =
+        [0xB1, 0xED, 0x3B, 0xC1, 0x12, 0x34, 0x56, 0x78];
+
+    /*whole bytes read from a byte-aligned stream
+      are assembled in one step*/
+    let mut r = BitReader::<BE, _>::from_read(&actual_data[..]);
+    assert_eq!(r.read::<u32>(24).unwrap(), 0xB1ED3B);
+    assert_eq!(r.read_signed::<i8>(8).unwrap(), -63);
+    assert_eq!(r.read::<u16>(16).unwrap(), 0x1234);
+    assert_eq!(r.position_in_bits(), 48);
+    assert!(r.read::<u32>(24).is_err());
+
+    let mut r = BitReader::<LE, _>::from_read(&actual_data[..]);
+    assert_eq!(r.read::<u32>(24).unwrap(), 0x3BEDB1);
+    assert_eq!(r.read::<i8>(8).unwrap(), -63);
+    assert_eq!(r.read::<u32>(32).unwrap(), 0x78563412);
+    assert!(r.read::<u8>(8).is_err());
+
+    let mut r = BitReader::<BE, _>::from_read(&actual_data[..]);
+    assert_eq!(r.read::<u64>(64).unwrap(), 0xB1ED_3BC1_1234_5678);
+    let mut r = BitReader::<LE, _>::from_read(&actual_data[..]);
+    assert_eq!(r.read::<i64>(64).unwrap(), 0x7856_3412_C13B_EDB1);
+}
+
+#[test]
+fn test_skip_buffered() {
+    use std::io::{BufRead, BufReader};
+    use bitstream_io::{BE, LE, BitReader};
+    let data = (0..1000).map(|i| i as u8).collect::<Vec<u8>>();
+
+    /*skipped bytes are consumed from the buffer
+      across as many refills as needed*/
+    let stream = BufReader::with_capacity(7, &data[..]);
+    let mut r = BitReader::<BE, _>::from_read(stream);
+    assert_eq!(r.read::<u8>(3).unwrap(), 0);
+    r.skip_buffered(5 + 8 * 500 + 4).unwrap();
+    assert_eq!(r.position_in_bits(), 8 * 501 + 4);
+    assert_eq!(r.read::<u8>(4).unwrap(), 0x5);
+    assert_eq!(r.read::<u8>(8).unwrap(), 0xF6);
+    r.skip_buffered(0).unwrap();
+    assert_eq!(r.read::<u8>(8).unwrap(), 0xF7);
+
+    let stream = BufReader::with_capacity(16, &data[..]);
+    let mut r = BitReader::<LE, _>::from_read(stream);
+    r.skip_buffered(8 * 999 + 1).unwrap();
+    assert_eq!(r.read::<u8>(7).unwrap(), 0xE7 >> 1);
+    assert!(r.read::<u8>(1).is_err());
+
+    /*skipping past the end is an error*/
+    let stream = BufReader::with_capacity(16, &data[..]);
+    let mut r = BitReader::<LE, _>::from_read(stream);
+    assert!(r.skip_buffered(8 * 1000 + 1).is_err());
+
+    /*and the stream is left at the first unread byte*/
+    let mut stream = BufReader::with_capacity(16, &data[..]);
+    {
+        let mut r = BitReader::<BE, _>::from_read(&mut stream);
+        r.skip_buffered(8 * 100).unwrap();
+    }
+    assert_eq!(stream.fill_buf().unwrap()[0], 100);
+}
+
+#[test]
+fn test_unary_buffered() {
+    use std::io::BufReader;
+    use bitstream_io::{BE, LE, BitReader, BitWriter, Endianness};
+
+    fn check<E: Endianness>() {
+        /*runs of every length up to several thousand bits,
+          which span many refills of a small buffer*/
+        let values = (0..200).map(|i| (i * i * 7) % 5000)
+            .collect::<Vec<u32>>();
+        let mut data = Vec::new();
+        {
+            let mut w = BitWriter::<E>::new(&mut data);
+            for &value in values.iter() {
+                w.write_unary0(value).unwrap();
+                w.write_unary1(value).unwrap();
+            }
+            w.byte_align().unwrap();
+        }
+
+        let stream = BufReader::with_capacity(13, &data[..]);
+        let mut r = BitReader::<E, _>::from_read(stream);
+        for &value in values.iter() {
+            assert_eq!(r.read_unary0_buffered().unwrap(), value);
+            assert_eq!(r.read_unary1_buffered().unwrap(), value);
+        }
+        assert_eq!(r.position_in_bits(), 8 * data.len() as u64 -
+                   u64::from(r.into_unread().0));
+
+        /*a run reaching the end of the stream is an error*/
+        let data = [0; 100];
+        let stream = BufReader::with_capacity(13, &data[..]);
+        let mut r = BitReader::<E, _>::from_read(stream);
+        assert!(r.read_unary1_buffered().is_err());
+    }
+
+    check::<BE>();
+    check::<LE>();
+}
+
+#[test]
+fn test_concatenate_large() {
+    use std::io::Read;
+    use bitstream_io::{LE, BitReader};
+
+    /*a stream which returns a byte at a time*/
+    struct Trickle(Cursor<Vec<u8>>);
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(1);
+            self.0.read(&mut buf[0..len])
+        }
+    }
+
+    let lhs_data = (0..1500).map(|i| (i * 7) as u8).collect::<Vec<u8>>();
+    let rhs_data = (0..700).map(|i| (i * 13) as u8).collect::<Vec<u8>>();
+    let mut lhs =
+        BitReader::<LE>::new(Box::new(Cursor::new(lhs_data.clone())));
+    let mut rhs = BitReader::<LE>::new(
+        Box::new(Trickle(Cursor::new(rhs_data.clone()))));
+    lhs.skip(3).unwrap();
+    rhs.skip(2).unwrap();
+    let mut concat = lhs.concatenate_reader(&mut rhs).unwrap();
+
+    /*every remaining bit of both streams is carried over in order*/
+    let mut expected_lhs = BitReader::<LE, _>::from_read(&lhs_data[..]);
+    let mut expected_rhs = BitReader::<LE, _>::from_read(&rhs_data[..]);
+    expected_lhs.skip(3).unwrap();
+    expected_rhs.skip(2).unwrap();
+    for _ in 0..(1500 * 8 - 3) {
+        assert_eq!(concat.read_bit().unwrap(),
+                   expected_lhs.read_bit().unwrap());
+    }
+    for _ in 0..(700 * 8 - 2) {
+        assert_eq!(concat.read_bit().unwrap(),
+                   expected_rhs.read_bit().unwrap());
+    }
+    assert!(concat.read_bit().is_err());
+}
+
+#[test]
+fn test_reader_debug() {
+    use bitstream_io::{BE, LE, BitReader};
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+
+    let mut r = BitReader::<BE>::new(Box::new(Cursor::new(actual_data)));
+    assert_eq!(format!("{:?}", r),
+               "BitReader { byte_position: 0, pending_bits: 0, \
+                pending_value: 0b0 }");
+    assert_eq!(r.read::<u8>(2).unwrap(), 2);
+    assert_eq!(format!("{:?}", r),
+               "BitReader { byte_position: 1, pending_bits: 6, \
+                pending_value: 0b110001 }");
+    assert_eq!(r.read::<u16>(14).unwrap(), 0x31ED);
+    assert_eq!(format!("{:?}", r),
+               "BitReader { byte_position: 2, pending_bits: 0, \
+                pending_value: 0b0 }");
+
+    let mut r = BitReader::<LE, _>::from_read(&actual_data[..]);
+    r.skip(13).unwrap();
+    assert_eq!(format!("{:?}", r),
+               "BitReader { byte_position: 2, pending_bits: 3, \
+                pending_value: 0b111 }");
+}
+
+#[test]
+fn test_reader_dump_next() {
+    use bitstream_io::{BE, LE, BitReader};
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+
+    let mut r = BitReader::<BE, _>::from_read(&actual_data[..]);
+    assert_eq!(r.dump_next(0).unwrap(), "bit 0:");
+    assert_eq!(r.dump_next(16).unwrap(),
+               "bit 0:\n00000000 b1 10110001\n00000001 ed 11101101");
+    r.skip(12).unwrap();
+    assert_eq!(r.dump_next(2).unwrap(), "bit 12:\n00000001 -- ....11..");
+
+    /*little-endian bits are shown in the order they're read*/
+    let mut r = BitReader::<LE, _>::from_read(&actual_data[..]);
+    r.skip(20).unwrap();
+    assert_eq!(r.dump_next(12).unwrap(),
+               "bit 20:\n00000002 -- ....1100\n00000003 c1 10000011");
+    assert_eq!(r.dump_next(13).unwrap(),
+               "bit 20:\n00000002 -- ....1100\n00000003 c1 10000011\n\
+                (end of stream)");
+    r.skip(12).unwrap();
+    assert_eq!(r.dump_next(1).unwrap(), "bit 32:\n(end of stream)");
+}
+
+#[test]
+fn test_reader_enum() {
+    use bitstream_io::{BE, LE, BitReader};
+    use bitstream_io::read::EnumError;
+    use std::convert::TryFrom;
+    use std::io;
+
+    #[derive(Debug, PartialEq)]
+    enum Color {
+        Red,
+        Green,
+        Blue,
+    }
+
+    impl TryFrom<u64> for Color {
+        type Error = ();
+
+        fn try_from(value: u64) -> Result<Self, ()> {
+            match value {
+                1 => Ok(Color::Red),
+                2 => Ok(Color::Green),
+                3 => Ok(Color::Blue),
+                _ => Err(()),
+            }
+        }
+    }
+
+    let actual_data = [0b0110_1100];
+    {
+        let mut r = BitReader::<BE>::new(Box::new(Cursor::new(actual_data)));
+        assert_eq!(r.read_enum::<Color>(2).unwrap(), Color::Red);
+        assert_eq!(r.read_enum::<Color>(2).unwrap(), Color::Green);
+        assert_eq!(r.read_enum::<Color>(2).unwrap(), Color::Blue);
+        match r.read_enum::<Color>(2) {
+            Err(EnumError::Unknown{value: 0, position: 6}) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+        match r.read_enum::<Color>(2) {
+            Err(EnumError::Io(ref err))
+                if err.kind() == io::ErrorKind::UnexpectedEof => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+    {
+        let mut r = BitReader::<LE>::new(Box::new(Cursor::new(actual_data)));
+        match r.read_enum::<Color>(2) {
+            Err(err) => {
+                assert_eq!(err.to_string(), "unknown value 0 at bit 0");
+                assert_eq!(io::Error::from(err).kind(),
+                           io::ErrorKind::InvalidData);
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert_eq!(r.read_enum::<Color>(2).unwrap(), Color::Blue);
+        assert_eq!(r.read_enum::<Color>(2).unwrap(), Color::Green);
+        assert_eq!(r.read_enum::<Color>(2).unwrap(), Color::Red);
+        match r.read_enum::<Color>(65) {
+            Err(EnumError::Io(ref err))
+                if err.kind() == io::ErrorKind::InvalidInput => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+}
+
+#[test]
+fn test_reader_unchecked() {
+    use bitstream_io::{BE, LE, BitReader};
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+
+    /*unchecked reads return the same values as checked ones*/
+    let mut r = BitReader::<BE, _>::from_read(&actual_data[..]);
+    assert_eq!(r.read_unchecked::<u32>(2).unwrap(), 2);
+    assert_eq!(r.read_unchecked::<u32>(3).unwrap(), 6);
+    assert_eq!(r.read_unchecked::<u32>(5).unwrap(), 7);
+    assert_eq!(r.read_unchecked::<u32>(3).unwrap(), 5);
+    assert_eq!(r.read_unchecked::<u32>(19).unwrap(), 0x53BC1);
+    assert!(r.read_unchecked::<u8>(1).is_err());
+
+    let mut r = BitReader::<LE, _>::from_read(&actual_data[..]);
+    assert_eq!(r.read_unchecked::<u32>(2).unwrap(), 1);
+    assert_eq!(r.read_unchecked::<u32>(3).unwrap(), 4);
+    assert_eq!(r.read_unchecked::<u32>(5).unwrap(), 13);
+    assert_eq!(r.read_unchecked::<u32>(3).unwrap(), 3);
+    assert_eq!(r.read_unchecked::<u32>(19).unwrap(), 0x609DF);
+    assert!(r.read_unchecked::<u8>(1).is_err());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn test_reader_unchecked_excessive() {
+    use bitstream_io::{BE, BitReader};
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+    let mut r = BitReader::<BE, _>::from_read(&actual_data[..]);
+    let _ = r.read_unchecked::<u8>(9);
+}
+
+#[test]
+fn test_reader_misaligned_bytes() {
+    use bitstream_io::{BE, LE, BitReader};
+    let data = (0..40).map(|i| (i * 37 + 11) as u8).collect::<Vec<u8>>();
+
+    /*bytes read at every offset and length, including those
+      covering whole words, match those read one at a time*/
+    for offset in 1..8 {
+        for len in 0..30 {
+            let mut r = BitReader::<BE, _>::from_read(&data[..]);
+            let mut expected = BitReader::<BE, _>::from_read(&data[..]);
+            r.skip(offset).unwrap();
+            expected.skip(offset).unwrap();
+            let mut buf = vec![0; len];
+            r.read_bytes(&mut buf).unwrap();
+            for &b in buf.iter() {
+                assert_eq!(b, expected.read::<u8>(8).unwrap());
+            }
+            assert_eq!(r.read::<u8>(5).unwrap(),
+                       expected.read::<u8>(5).unwrap());
+
+            let mut r = BitReader::<LE, _>::from_read(&data[..]);
+            let mut expected = BitReader::<LE, _>::from_read(&data[..]);
+            r.skip(offset).unwrap();
+            expected.skip(offset).unwrap();
+            let mut buf = vec![0; len];
+            r.read_bytes(&mut buf).unwrap();
+            for &b in buf.iter() {
+                assert_eq!(b, expected.read::<u8>(8).unwrap());
+            }
+            assert_eq!(r.read::<u8>(5).unwrap(),
+                       expected.read::<u8>(5).unwrap());
+        }
+    }
+
+    let mut r = BitReader::<BE, _>::from_read(&data[..]);
+    r.skip(3).unwrap();
+    assert!(r.read_bytes(&mut [0; 40]).is_err());
+}
+
+#[test]
+fn test_alignment_guard() {
+    use bitstream_io::{BE, LE, BitRead, BitReader};
+    use std::io::ErrorKind;
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+
+    /*byte alignment is checked against the stream itself*/
+    let mut r = BitReader::<BE, _>::from_read(&actual_data[..]);
+    r.read::<u8>(2).unwrap();
+    {
+        let mut section = r.require_alignment_on_exit();
+        assert_eq!(section.read_unary0().unwrap(), 2);
+        assert_eq!(section.read_signed::<i8>(4).unwrap(), 3);
+        assert_eq!(section.bits_read(), 7);
+        section.byte_align();
+        assert_eq!(section.bits_read(), 14);
+        section.finish().unwrap();
+    }
+    let mut section = r.require_alignment_on_exit();
+    let mut buf = [0; 1];
+    section.read_bytes(&mut buf).unwrap();
+    section.read_bit().unwrap();
+    assert_eq!(section.finish().unwrap_err().kind(), ErrorKind::InvalidData);
+
+    /*other alignments count from the start of the guard*/
+    let mut r = BitReader::<LE, _>::from_read(&actual_data[..]);
+    r.read::<u8>(3).unwrap();
+    let mut section = r.require_bit_alignment_on_exit(12);
+    section.skip(8).unwrap();
+    assert!(!section.is_aligned());
+    section.read::<u8>(4).unwrap();
+    assert!(section.is_aligned());
+    section.read::<u16>(12).unwrap();
+    section.finish().unwrap();
+    assert_eq!(r.position_in_bits(), 27);
+
+    /*failed reads leave the section unchecked when dropped*/
+    let mut r = BitReader::<LE, _>::from_read(&actual_data[..]);
+    let mut section = r.require_alignment_on_exit();
+    section.read::<u8>(3).unwrap();
+    assert!(section.read::<u32>(32).is_err());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "section did not end aligned")]
+fn test_alignment_guard_dropped() {
+    use bitstream_io::{BE, BitRead, BitReader};
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+    let mut r = BitReader::<BE, _>::from_read(&actual_data[..]);
+    let mut section = r.require_bit_alignment_on_exit(16);
+    section.read::<u8>(8).unwrap();
+}
+
+#[test]
+fn test_reader_partial() {
+    use bitstream_io::{BE, LE, BitReader};
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+
+    /*complete fields read as usual*/
+    let mut r = BitReader::<BE, _>::from_read(&actual_data[..]);
+    assert_eq!(r.read_partial::<u8>(4).unwrap(), (0xB, 4));
+    assert_eq!(r.read_partial::<u16>(12).unwrap(), (0x1ED, 12));
+
+    /*a truncated field keeps the bits available*/
+    assert_eq!(r.read_partial::<u64>(40).unwrap(), (0x3BC1, 16));
+    assert_eq!(r.position_in_bits(), 32);
+    assert!(r.read::<u8>(1).is_err());
+    assert_eq!(r.read_partial::<u128>(128).unwrap(), (0, 0));
+
+    let mut r = BitReader::<LE, _>::from_read(&actual_data[..]);
+    assert_eq!(r.read_partial::<u8>(4).unwrap(), (0x1, 4));
+    assert_eq!(r.read_partial::<u32>(32).unwrap(), (0xC13BED_B, 28));
+
+    /*oversized fields are rejected before anything is read*/
+    let mut r = BitReader::<BE, _>::from_read(&actual_data[..]);
+    assert!(r.read_partial::<u8>(9).is_err());
+    assert_eq!(r.read::<u8>(8).unwrap(), 0xB1);
+}
+
+#[test]
+fn test_reader_to_end_bits() {
+    use bitstream_io::{BE, LE, BitReader, BitWriter};
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+
+    /*an aligned stream's remaining bytes are returned as-is*/
+    let mut r = BitReader::<BE, _>::from_read(&actual_data[..]);
+    assert_eq!(r.read::<u8>(8).unwrap(), 0xB1);
+    assert_eq!(r.read_to_end_bits().unwrap(), (vec![0xED, 0x3B, 0xC1], 0));
+    assert_eq!(r.position_in_bits(), 32);
+
+    /*an unaligned stream's bits read back the same from the result*/
+    for skipped in 1..8 {
+        let mut r = BitReader::<LE, _>::from_read(&actual_data[..]);
+        r.skip(skipped).unwrap();
+        let (rest, trailing) = r.read_to_end_bits().unwrap();
+        assert_eq!(rest.len(), 4);
+        assert_eq!(trailing, 8 - skipped);
+
+        let mut expected = Vec::new();
+        {
+            let mut w = BitWriter::<LE, _>::from_write(&mut expected);
+            let mut r = BitReader::<LE, _>::from_read(&actual_data[..]);
+            r.skip(skipped).unwrap();
+            for _ in 0..32 - skipped {
+                w.write_bit(r.read_bit().unwrap()).unwrap();
+            }
+            w.byte_align().unwrap();
+        }
+        assert_eq!(rest, expected);
+    }
+}
+
+#[test]
+fn test_reader_owned() {
+    use bitstream_io::{BE, LE, BitReader};
+
+    /*readers over owned data may outlive the data's original binding*/
+    fn header() -> BitReader<BE, Cursor<Vec<u8>>> {
+        let data = vec![0xB1, 0xED, 0x3B, 0xC1];
+        BitReader::from_vec(data)
+    }
+
+    let mut r = header();
+    assert_eq!(r.read::<u8>(4).unwrap(), 0xB);
+    r.skip_buffered(12).unwrap();
+    assert_eq!(r.read::<u16>(16).unwrap(), 0x3BC1);
+    assert!(r.read_bit().is_err());
+
+    let mut r = BitReader::<LE, _>::from_bytes([0xB1, 0xED, 0x3B, 0xC1]);
+    assert_eq!(r.read::<u32>(32).unwrap(), 0xC13BEDB1);
+    r.seek_bits(12).unwrap();
+    assert_eq!(r.read_to_end_bits().unwrap(),
+               (vec![0xBE, 0x13, 0x0C], 4));
+}
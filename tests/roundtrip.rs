@@ -7,8 +7,9 @@
 // except according to those terms.
 
 extern crate bitstream_io;
-use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
-use std::io::Cursor;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter,
+                   Endianness, Numeric};
+use std::io::{self, Cursor};
 
 macro_rules! define_roundtrip {
     ($func_name:ident, $endianness:ident) => {
@@ -26,7 +27,7 @@ macro_rules! define_roundtrip {
                     writer.byte_align().unwrap();
                 }
                 {
-                    let mut c = Cursor::new(output);
+                    let c = Cursor::new(output);
                     let mut reader = BitReader::<$endianness>::new(Box::new(c));
                     for value in 0..max {
                         assert_eq!(reader.read::<u32>(bits).unwrap(),
@@ -106,3 +107,580 @@ macro_rules! define_unary_roundtrip {
 
 define_unary_roundtrip!(test_unary_roundtrip_be, BigEndian);
 define_unary_roundtrip!(test_unary_roundtrip_le, LittleEndian);
+
+macro_rules! define_minifloat_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            /*every half precision value survives a round trip*/
+            let mut output: Vec<u8> = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut output);
+                for raw in 0..0x10000u32 {
+                    writer.write(16, raw).unwrap();
+                }
+            }
+            let mut values = Vec::new();
+            {
+                let c = Cursor::new(output.clone());
+                let mut reader = BitReader::<$endianness>::new(Box::new(c));
+                for _ in 0..0x10000u32 {
+                    values.push(reader.read_minifloat(5, 10).unwrap());
+                }
+            }
+            let mut rewritten: Vec<u8> = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut rewritten);
+                for value in values.iter() {
+                    writer.write_minifloat(5, 10, *value).unwrap();
+                }
+            }
+            {
+                let c = Cursor::new(rewritten);
+                let mut reader = BitReader::<$endianness>::new(Box::new(c));
+                for raw in 0..0x10000u32 {
+                    let exponent = (raw >> 10) & 0x1F;
+                    let mantissa = raw & 0x3FF;
+                    let read = reader.read::<u32>(16).unwrap();
+                    if (exponent == 0x1F) && (mantissa != 0) {
+                        assert_eq!(read & 0x7E00, 0x7E00);
+                    } else {
+                        assert_eq!(read, raw);
+                    }
+                }
+            }
+
+            /*single precision values round like an f64 to f32 cast*/
+            let samples = [0.0f64, -0.0, 1.0, -1.5, 1234.5678,
+                           1.0e-40, -1.0e-45, 7.0e-46, 1.0e-50, 1.0e38,
+                           3.5e38, -1.0e300, 16777217.0, 0.1, 1.0 / 3.0];
+            let mut output: Vec<u8> = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut output);
+                for sample in samples.iter() {
+                    writer.write_minifloat(8, 23, *sample).unwrap();
+                }
+            }
+            {
+                let c = Cursor::new(output);
+                let mut reader = BitReader::<$endianness>::new(Box::new(c));
+                for sample in samples.iter() {
+                    let expected = *sample as f32;
+                    assert_eq!(reader.read::<u32>(32).unwrap(),
+                               expected.to_bits());
+                }
+            }
+        }
+    }
+}
+
+define_minifloat_roundtrip!(test_minifloat_roundtrip_be, BigEndian);
+define_minifloat_roundtrip!(test_minifloat_roundtrip_le, LittleEndian);
+
+macro_rules! define_exp_golomb_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let values = [0u64, 1, 2, 3, 4, 7, 8, 255, 256, 65535,
+                          0xFFFFFFFF, 0x1_0000_0000,
+                          0xFFFFFFFFFFFFFFFE, 0xFFFFFFFFFFFFFFFF];
+            for k in [0, 1, 2, 5, 31, 63].iter() {
+                let mut output: Vec<u8> = Vec::new();
+                {
+                    let mut writer =
+                        BitWriter::<$endianness>::new(&mut output);
+                    for value in values.iter() {
+                        writer.write_exp_golomb(*k, *value).unwrap();
+                        writer.write_interleaved_exp_golomb(*k, *value)
+                            .unwrap();
+                    }
+                    writer.byte_align().unwrap();
+                }
+                {
+                    let c = Cursor::new(output);
+                    let mut reader =
+                        BitReader::<$endianness>::new(Box::new(c));
+                    for value in values.iter() {
+                        assert_eq!(reader.read_exp_golomb(*k).unwrap(),
+                                   *value);
+                        assert_eq!(
+                            reader.read_interleaved_exp_golomb(*k).unwrap(),
+                            *value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+define_exp_golomb_roundtrip!(test_exp_golomb_roundtrip_be, BigEndian);
+define_exp_golomb_roundtrip!(test_exp_golomb_roundtrip_le, LittleEndian);
+
+macro_rules! define_truncated_binary_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            for n in (1..300u64).chain([0xFFFFFFFF, 0xFFFFFFFFFFFFFFFF,
+                                        0x8000000000000001].iter().cloned()) {
+                let values: Vec<u64> = if n < 300 {
+                    (0..n).collect()
+                } else {
+                    vec![0, 1, n / 2, n - 2, n - 1]
+                };
+                let mut output: Vec<u8> = Vec::new();
+                {
+                    let mut writer =
+                        BitWriter::<$endianness>::new(&mut output);
+                    for value in values.iter() {
+                        writer.write_truncated_binary(n, *value).unwrap();
+                    }
+                    writer.write(7, 0u8).unwrap();
+                }
+                {
+                    let c = Cursor::new(output);
+                    let mut reader =
+                        BitReader::<$endianness>::new(Box::new(c));
+                    for value in values.iter() {
+                        assert_eq!(reader.read_truncated_binary(n).unwrap(),
+                                   *value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+define_truncated_binary_roundtrip!(test_truncated_binary_roundtrip_be,
+                                   BigEndian);
+define_truncated_binary_roundtrip!(test_truncated_binary_roundtrip_le,
+                                   LittleEndian);
+
+macro_rules! define_varint_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::varint::{VarInt, FlagPosition, ChunkOrder};
+
+            let values = [0u64, 1, 2, 127, 128, 255, 256, 16383, 16384,
+                          0xFFFFFFFF, 0x1_0000_0000, u64::MAX - 1, u64::MAX];
+            let mut formats = Vec::new();
+            for payload_bits in [1, 3, 4, 7, 15, 31, 63].iter() {
+                for flag in [FlagPosition::High, FlagPosition::Low].iter() {
+                    for continue_flag in [false, true].iter() {
+                        for order in [ChunkOrder::LeastSignificantFirst,
+                                      ChunkOrder::MostSignificantFirst]
+                            .iter() {
+                            formats.push(VarInt{payload_bits: *payload_bits,
+                                                flag: *flag,
+                                                continue_flag: *continue_flag,
+                                                order: *order});
+                        }
+                    }
+                }
+            }
+            for format in formats.iter() {
+                let mut output: Vec<u8> = Vec::new();
+                {
+                    let mut writer =
+                        BitWriter::<$endianness>::new(&mut output);
+                    for value in values.iter() {
+                        writer.write_varint(format, *value).unwrap();
+                    }
+                    writer.byte_align().unwrap();
+                }
+                {
+                    let c = Cursor::new(output);
+                    let mut reader =
+                        BitReader::<$endianness>::new(Box::new(c));
+                    for value in values.iter() {
+                        assert_eq!(reader.read_varint(format).unwrap(),
+                                   *value);
+                    }
+                }
+            }
+
+            /*values too large for a u64 are errors*/
+            let data = [0xFF; 10];
+            let c = Cursor::new(data);
+            let mut reader = BitReader::<$endianness>::new(Box::new(c));
+            assert!(reader.read_varint(&VarInt::vlq()).is_err());
+            let data = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+                        0xFF, 0xFF, 0xFF, 0xFF, 0x02];
+            let c = Cursor::new(data);
+            let mut reader = BitReader::<$endianness>::new(Box::new(c));
+            assert!(reader.read_varint(&VarInt::leb128()).is_err());
+
+            /*as are chunks of zeroes beyond a u64*/
+            let mut data = [0x80; 11];
+            data[10] = 0x00;
+            let c = Cursor::new(data);
+            let mut reader = BitReader::<$endianness>::new(Box::new(c));
+            assert_eq!(reader.read_varint(&VarInt::leb128()).unwrap_err()
+                       .kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
+
+define_varint_roundtrip!(test_varint_roundtrip_be, BigEndian);
+define_varint_roundtrip!(test_varint_roundtrip_le, LittleEndian);
+
+macro_rules! define_truncating_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let values: Vec<u64> = (0..64u64)
+                .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+                .collect();
+            let mut output: Vec<u8> = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut output);
+                for (bits, value) in (1..=64).zip(values.iter()) {
+                    writer.write(bits, value >> (64 - bits)).unwrap();
+                }
+                writer.byte_align().unwrap();
+            }
+            let c = Cursor::new(output);
+            let mut reader = BitReader::<$endianness>::new(Box::new(c));
+            for (bits, value) in (1..=64).zip(values.iter()) {
+                let value = value >> (64 - bits);
+                match bits % 3 {
+                    0 => assert_eq!(reader.read_truncating::<u8>(bits)
+                                    .unwrap(), value as u8),
+                    1 => assert_eq!(reader.read_truncating::<u16>(bits)
+                                    .unwrap(), value as u16),
+                    _ => assert_eq!(reader.read_truncating::<u32>(bits)
+                                    .unwrap(), value as u32),
+                }
+            }
+        }
+    }
+}
+
+define_truncating_roundtrip!(test_truncating_roundtrip_be, BigEndian);
+define_truncating_roundtrip!(test_truncating_roundtrip_le, LittleEndian);
+
+macro_rules! define_odd_width_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let u24s = [0u32, 1, 0x123456, 0x7FFFFF, 0x800000, 0xFFFFFF];
+            let i24s = [0i32, 1, -1, 0x7FFFFF, -0x800000, -12345];
+            let u48s = [0u64, 1, 0x1234_5678_9ABC, (1 << 48) - 1];
+            /*alternate between aligned and unaligned values*/
+            for offset in 0..8 {
+                let mut output: Vec<u8> = Vec::new();
+                {
+                    let mut writer =
+                        BitWriter::<$endianness>::new(&mut output);
+                    writer.write(offset, 0u8).unwrap();
+                    for ((u24, i24), u48) in
+                        u24s.iter().zip(i24s.iter()).zip(u48s.iter().cycle()) {
+                        writer.write_u24(*u24).unwrap();
+                        writer.write_i24(*i24).unwrap();
+                        writer.write_u48(*u48).unwrap();
+                        writer.write(24, *u24).unwrap();
+                    }
+                    writer.byte_align().unwrap();
+                }
+                let c = Cursor::new(output);
+                let mut reader = BitReader::<$endianness>::new(Box::new(c));
+                reader.skip(offset).unwrap();
+                for ((u24, i24), u48) in
+                    u24s.iter().zip(i24s.iter()).zip(u48s.iter().cycle()) {
+                    assert_eq!(reader.read_u24().unwrap(), *u24);
+                    assert_eq!(reader.read_i24().unwrap(), *i24);
+                    assert_eq!(reader.read_u48().unwrap(), *u48);
+                    assert_eq!(reader.read::<u32>(24).unwrap(), *u24);
+                }
+            }
+        }
+    }
+}
+
+define_odd_width_roundtrip!(test_odd_width_roundtrip_be, BigEndian);
+define_odd_width_roundtrip!(test_odd_width_roundtrip_le, LittleEndian);
+
+macro_rules! define_mixed_endian_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::mixed::MixedEndian;
+
+            for layout in [MixedEndian::Pdp, MixedEndian::WordSwapped].iter() {
+                let mut output: Vec<u8> = Vec::new();
+                {
+                    let mut writer =
+                        BitWriter::<$endianness>::new(&mut output);
+                    writer.write_mixed_endian(*layout, 0x0102u16).unwrap();
+                    writer.write(3, 0b101u8).unwrap();
+                    writer.write_mixed_endian(*layout, 0x0A0B0C0Du32).unwrap();
+                    writer.write_mixed_endian(*layout, -2i64).unwrap();
+                    writer.write_mixed_endian(*layout, -0x0102030405060708i64)
+                        .unwrap();
+                    writer.write(5, 0b10110u8).unwrap();
+                    writer.write_mixed_endian(*layout, 0x0102030405060708u64)
+                        .unwrap();
+                }
+                if *layout == MixedEndian::Pdp {
+                    assert_eq!(&output[0..2], &[0x02, 0x01]);
+                } else {
+                    assert_eq!(&output[0..2], &[0x01, 0x02]);
+                }
+                let c = Cursor::new(output);
+                let mut reader = BitReader::<$endianness>::new(Box::new(c));
+                assert_eq!(reader.read_mixed_endian::<u16>(*layout).unwrap(),
+                           0x0102);
+                assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+                assert_eq!(reader.read_mixed_endian::<u32>(*layout).unwrap(),
+                           0x0A0B0C0D);
+                assert_eq!(reader.read_mixed_endian::<i64>(*layout).unwrap(),
+                           -2);
+                assert_eq!(reader.read_mixed_endian::<i64>(*layout).unwrap(),
+                           -0x0102030405060708);
+                assert_eq!(reader.read::<u8>(5).unwrap(), 0b10110);
+                assert_eq!(reader.read_mixed_endian::<u64>(*layout).unwrap(),
+                           0x0102030405060708);
+            }
+        }
+    }
+}
+
+define_mixed_endian_roundtrip!(test_mixed_endian_roundtrip_be, BigEndian);
+define_mixed_endian_roundtrip!(test_mixed_endian_roundtrip_le, LittleEndian);
+
+macro_rules! define_aligned_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let mut output: Vec<u8> = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut output);
+                writer.write_u16_aligned(0x0102).unwrap();
+                writer.write_i16_aligned(-2).unwrap();
+                writer.write(3, 0b101u8).unwrap();
+                assert!(writer.write_u32_aligned(1).is_err());
+                writer.write(5, 0b10110u8).unwrap();
+                writer.write_u32_aligned(0x0A0B0C0D).unwrap();
+                writer.write_i32_aligned(-0x0A0B0C0D).unwrap();
+                writer.write_u64_aligned(0x0102030405060708).unwrap();
+                writer.write_i64_aligned(i64::MIN).unwrap();
+                writer.write_aligned(1.5f32).unwrap();
+            }
+            /*aligned values match the bit-level methods*/
+            let c = Cursor::new(output.clone());
+            let mut reader = BitReader::<$endianness>::new(Box::new(c));
+            assert_eq!(reader.read::<u16>(16).unwrap(), 0x0102);
+            assert_eq!(reader.read_signed::<i16>(16).unwrap(), -2);
+
+            let c = Cursor::new(output);
+            let mut reader = BitReader::<$endianness>::new(Box::new(c));
+            assert_eq!(reader.read_u16_aligned().unwrap(), 0x0102);
+            assert_eq!(reader.read_i16_aligned().unwrap(), -2);
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            assert_eq!(reader.read_u32_aligned().unwrap_err().kind(),
+                       io::ErrorKind::InvalidInput);
+            assert_eq!(reader.read::<u8>(5).unwrap(), 0b10110);
+            assert_eq!(reader.read_u32_aligned().unwrap(), 0x0A0B0C0D);
+            assert_eq!(reader.read_i32_aligned().unwrap(), -0x0A0B0C0D);
+            assert_eq!(reader.read_u64_aligned().unwrap(), 0x0102030405060708);
+            assert_eq!(reader.read_i64_aligned().unwrap(), i64::MIN);
+            assert_eq!(reader.read_aligned::<f32>().unwrap(), 1.5);
+            assert!(reader.read_u16_aligned().is_err());
+        }
+    }
+}
+
+define_aligned_roundtrip!(test_aligned_roundtrip_be, BigEndian);
+define_aligned_roundtrip!(test_aligned_roundtrip_le, LittleEndian);
+
+macro_rules! define_ranged_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let ranges = [(0, 0), (0, 1), (-1, 1), (0, 100), (-300, -200),
+                          (i64::MIN, i64::MAX), (i64::MAX - 5, i64::MAX)];
+            let mut output: Vec<u8> = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut output);
+                for &(min, max) in ranges.iter() {
+                    writer.write_ranged(min, max, min).unwrap();
+                    writer.write_ranged(min, max, max).unwrap();
+                    for &outside in [min.checked_sub(1), max.checked_add(1)]
+                        .iter().flatten() {
+                        assert!(writer.write_ranged(min, max, outside)
+                                .is_err());
+                    }
+                }
+                assert!(writer.write_ranged(1, 0, 0).is_err());
+                writer.byte_align().unwrap();
+            }
+            let mut reader =
+                BitReader::<$endianness, _>::from_read(&output[..]);
+            for &(min, max) in ranges.iter() {
+                assert_eq!(reader.read_ranged(min, max).unwrap(), min);
+                assert_eq!(reader.read_ranged(min, max).unwrap(), max);
+            }
+
+            /*offsets past the range's end are invalid*/
+            let mut reader =
+                BitReader::<$endianness, _>::from_read(&[0xFF][..]);
+            assert_eq!(reader.read_ranged(0, 4).unwrap_err().kind(),
+                       io::ErrorKind::InvalidData);
+        }
+    }
+}
+
+define_ranged_roundtrip!(test_ranged_roundtrip_be, BigEndian);
+define_ranged_roundtrip!(test_ranged_roundtrip_le, LittleEndian);
+
+macro_rules! define_ascii7_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let text: Vec<u8> = (0..128).chain((0..128).rev()).collect();
+            let mut output: Vec<u8> = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut output);
+                writer.write(3, 0b101u8).unwrap();
+                writer.write_ascii7(&text).unwrap();
+                assert!(writer.write_ascii7(&[0x41, 0x80]).is_err());
+                writer.write_ascii7(b"end").unwrap();
+                writer.byte_align().unwrap();
+            }
+            /*the 3 bits and 259 characters of 7 bits fill 227 bytes*/
+            assert_eq!(output.len(), 227);
+
+            let mut reader =
+                BitReader::<$endianness, _>::from_read(&output[..]);
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            let mut buf = vec![0; text.len()];
+            reader.read_ascii7(&mut buf).unwrap();
+            assert_eq!(buf, text);
+            assert_eq!(reader.read_ascii7_string(3).unwrap(), "end");
+            assert!(reader.read_ascii7_string(1).is_err());
+        }
+    }
+}
+
+define_ascii7_roundtrip!(test_ascii7_roundtrip_be, BigEndian);
+define_ascii7_roundtrip!(test_ascii7_roundtrip_le, LittleEndian);
+
+macro_rules! define_prefixed_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::varint::VarInt;
+
+            let format = VarInt::leb128();
+            let blob: Vec<u8> = (0..10_000).map(|i| (i % 251) as u8).collect();
+            for skipped in 0..8 {
+                let mut output: Vec<u8> = Vec::new();
+                {
+                    let mut writer =
+                        BitWriter::<$endianness>::new(&mut output);
+                    writer.write(skipped, 0u8).unwrap();
+                    writer.write_prefixed_bytes(&format, &blob).unwrap();
+                    writer.write_prefixed_str(&format, "").unwrap();
+                    writer.write_prefixed_str(&format, "h\u{e9}llo").unwrap();
+                    writer.byte_align().unwrap();
+                }
+                let mut reader =
+                    BitReader::<$endianness, _>::from_read(&output[..]);
+                reader.skip(skipped).unwrap();
+                assert_eq!(reader.read_prefixed_bytes(&format).unwrap(),
+                           blob);
+                assert_eq!(reader.read_prefixed_str(&format).unwrap(), "");
+                assert_eq!(reader.read_prefixed_str(&format).unwrap(),
+                           "h\u{e9}llo");
+            }
+
+            /*invalid text and lengths past the end are errors*/
+            let mut reader =
+                BitReader::<$endianness, _>::from_read(&[0x01, 0xFF][..]);
+            assert_eq!(reader.read_prefixed_str(&format).unwrap_err().kind(),
+                       io::ErrorKind::InvalidData);
+            let mut reader = BitReader::<$endianness, _>::from_read(
+                &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0x00][..]);
+            assert_eq!(reader.read_prefixed_bytes(&format).unwrap_err().kind(),
+                       io::ErrorKind::UnexpectedEof);
+        }
+    }
+}
+
+define_prefixed_roundtrip!(test_prefixed_roundtrip_be, BigEndian);
+define_prefixed_roundtrip!(test_prefixed_roundtrip_le, LittleEndian);
+
+/*a bit order implementing only the accumulator methods,
+  which are big-endian's*/
+struct Msb;
+
+impl Endianness for Msb {
+    fn push<N>(bits_acc: &mut u32, value_acc: &mut N, bits: u32, value: N)
+        where N: Numeric {
+        BigEndian::push(bits_acc, value_acc, bits, value)
+    }
+
+    fn pop<N>(bits_acc: &mut u32, value_acc: &mut N, bits: u32) -> N
+        where N: Numeric {
+        BigEndian::pop(bits_acc, value_acc, bits)
+    }
+
+    fn drop<N>(bits_acc: &mut u32, value_acc: &mut N, bits: u32)
+        where N: Numeric {
+        BigEndian::drop(bits_acc, value_acc, bits)
+    }
+
+    fn next_zeros<N>(bits: u32, value: N) -> u32 where N: Numeric {
+        BigEndian::next_zeros(bits, value)
+    }
+
+    fn next_ones<N>(bits: u32, value: N) -> u32 where N: Numeric {
+        BigEndian::next_ones(bits, value)
+    }
+}
+
+#[test]
+fn test_default_signed() {
+    let mut expected = Vec::new();
+    let mut output = Vec::new();
+    {
+        let mut be = BitWriter::<BigEndian>::new(&mut expected);
+        let mut msb = BitWriter::<Msb>::new(&mut output);
+        for bits in 1..9 {
+            let min = -1i16 << (bits - 1);
+            for value in min..-min {
+                be.write_signed(bits, value).unwrap();
+                msb.write_signed(bits, value).unwrap();
+            }
+            /*values out of range are rejected*/
+            assert!(msb.write_signed(bits, -min).is_err());
+            assert!(msb.write_signed(bits, min - 1).is_err());
+        }
+        be.byte_align().unwrap();
+        msb.byte_align().unwrap();
+    }
+    assert_eq!(output, expected);
+
+    let mut reader = BitReader::<Msb>::new(Box::new(Cursor::new(output)));
+    for bits in 1..9 {
+        let min = -1i16 << (bits - 1);
+        for value in min..-min {
+            assert_eq!(reader.read_signed::<i16>(bits).unwrap(), value);
+        }
+    }
+}
+
+#[test]
+fn test_default_bytes() {
+    assert_eq!(Msb::to_bytes(0x1234_5678u32), [0x12, 0x34, 0x56, 0x78]);
+    assert_eq!(Msb::from_bytes::<i16>([0xFF, 0xFE]), -2);
+
+    let mut output = Vec::new();
+    {
+        let mut writer = BitWriter::<Msb>::new(&mut output);
+        writer.write_aligned(0x1234_5678u32).unwrap();
+    }
+    assert_eq!(output, [0x12, 0x34, 0x56, 0x78]);
+    let mut reader = BitReader::<Msb>::new(Box::new(Cursor::new(output)));
+    assert_eq!(reader.read_aligned::<u32>().unwrap(), 0x1234_5678);
+}
@@ -0,0 +1,84 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+
+use std::io::{Cursor, Read, Write};
+use std::thread;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+
+fn assert_send<T: Send>() {}
+
+fn assert_sync<T: Sync>() {}
+
+/*these only need to compile*/
+#[allow(dead_code)]
+fn reader_send<R: Read + Send>() {
+    assert_send::<BitReader<BigEndian, R>>();
+    assert_send::<BitReader<LittleEndian, R>>();
+}
+
+#[allow(dead_code)]
+fn reader_sync<R: Read + Sync>() {
+    assert_sync::<BitReader<BigEndian, R>>();
+    assert_sync::<BitReader<LittleEndian, R>>();
+}
+
+#[allow(dead_code)]
+fn writer_send<'a, W: Write + Send + 'a>() {
+    assert_send::<BitWriter<'a, BigEndian, W>>();
+    assert_send::<BitWriter<'a, LittleEndian, W>>();
+}
+
+#[allow(dead_code)]
+fn writer_sync<'a, W: Write + Sync + 'a>() {
+    assert_sync::<BitWriter<'a, BigEndian, W>>();
+    assert_sync::<BitWriter<'a, LittleEndian, W>>();
+}
+
+#[test]
+fn test_concrete_streams() {
+    assert_send::<BitReader<BigEndian, Cursor<Vec<u8>>>>();
+    assert_sync::<BitReader<BigEndian, Cursor<Vec<u8>>>>();
+    assert_send::<BitReader<LittleEndian, &'static [u8]>>();
+    assert_sync::<BitReader<LittleEndian, &'static [u8]>>();
+    assert_send::<BitWriter<'static, BigEndian, Vec<u8>>>();
+    assert_sync::<BitWriter<'static, BigEndian, Vec<u8>>>();
+    assert_send::<BitWriter<'static, LittleEndian, Cursor<Vec<u8>>>>();
+    assert_send::<BitWriter<'static, LittleEndian, dyn Write + Send>>();
+}
+
+#[test]
+fn test_reader_threads() {
+    let data = vec![0b1011_0001, 0xFF, 0x00, 0x80];
+    let readers = (0..4).map(|i| {
+        let mut reader =
+            BitReader::<BigEndian, _>::from_read(Cursor::new(data.clone()));
+        reader.skip(i * 8).unwrap();
+        thread::spawn(move || reader.read::<u8>(8).unwrap())
+    }).collect::<Vec<_>>();
+    let read = readers.into_iter()
+        .map(|t| t.join().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(read, data);
+}
+
+#[test]
+fn test_writer_threads() {
+    let mut blocks = vec![Vec::new(), Vec::new()];
+    thread::scope(|s| {
+        for (i, block) in blocks.iter_mut().enumerate() {
+            let mut writer = BitWriter::<LittleEndian, _>::from_write(block);
+            s.spawn(move || {
+                writer.write(4, i as u8).unwrap();
+                writer.write(12, 0xABCu16).unwrap();
+            });
+        }
+    });
+    assert_eq!(blocks, vec![vec![0xC0, 0xAB], vec![0xC1, 0xAB]]);
+}
@@ -0,0 +1,208 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "serde")]
+
+extern crate bitstream_io;
+#[macro_use]
+extern crate serde_derive;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWriter};
+use bitstream_io::serde_bits::{Schema, Error, to_writer, from_reader};
+use std::collections::BTreeMap;
+use std::io::{self, Cursor};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct BlockHeader {
+    last_block: bool,
+    block_type: u8,
+    block_size: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Channels {
+    Mono,
+    Stereo{left: i16, right: i16},
+    Surround(Vec<i16>),
+    Independent(u8, u8),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Frame {
+    header: BlockHeader,
+    channels: Channels,
+    samples: Vec<i16>,
+    gain: Option<u8>,
+    name: String,
+    scale: f32,
+    initial: char,
+    tags: BTreeMap<u8, bool>,
+    marker: (),
+    pair: (u8, i64),
+}
+
+fn schema() -> Schema {
+    Schema::new()
+        .field("block_type", 7)
+        .field("block_size", 24)
+        .field("channels", 2)
+        .field("left", 12)
+        .field("right", 12)
+        .field("Surround", 5)
+        .field("Independent", 3)
+        .field("samples", 9)
+        .field("gain", 4)
+        .field("initial", 7)
+        .field("pair", 8)
+        .length_bits(8)
+}
+
+fn frames() -> Vec<Frame> {
+    let mut tags = BTreeMap::new();
+    tags.insert(1, true);
+    tags.insert(200, false);
+    vec![Frame{header: BlockHeader{last_block: false,
+                                   block_type: 127,
+                                   block_size: 0xFF_FFFF},
+               channels: Channels::Stereo{left: -2048, right: 2047},
+               samples: vec![-256, 0, 255],
+               gain: Some(15),
+               name: "fLaC".to_owned(),
+               scale: 0.5,
+               initial: 'A',
+               tags,
+               marker: (),
+               pair: (7, -1)},
+         Frame{header: BlockHeader{last_block: true,
+                                   block_type: 0,
+                                   block_size: 0},
+               channels: Channels::Surround(vec![-16, 15, 0]),
+               samples: Vec::new(),
+               gain: None,
+               name: String::new(),
+               scale: -1.0,
+               initial: '\u{7F}',
+               tags: BTreeMap::new(),
+               marker: (),
+               pair: (0, -128)},
+         Frame{header: BlockHeader{last_block: true,
+                                   block_type: 1,
+                                   block_size: 2},
+               channels: Channels::Mono,
+               samples: vec![1],
+               gain: Some(0),
+               name: "\u{1F600}".to_owned(),
+               scale: 0.0,
+               initial: '\0',
+               tags: BTreeMap::new(),
+               marker: (),
+               pair: (255, 0)},
+         Frame{header: BlockHeader{last_block: false,
+                                   block_type: 3,
+                                   block_size: 4},
+               channels: Channels::Independent(7, 0),
+               samples: vec![],
+               gain: None,
+               name: "x".to_owned(),
+               scale: 1.0e10,
+               initial: 'z',
+               tags: BTreeMap::new(),
+               marker: (),
+               pair: (1, 1)}]
+}
+
+macro_rules! define_serde_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let schema = schema();
+            let frames = frames();
+
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                for frame in frames.iter() {
+                    to_writer(&mut writer, &schema, frame).unwrap();
+                }
+                writer.byte_align().unwrap();
+            }
+
+            let mut reader =
+                BitReader::<$endianness>::new(Box::new(Cursor::new(data)));
+            for frame in frames.iter() {
+                assert_eq!(&from_reader::<_, Frame>(&mut reader, &schema)
+                           .unwrap(),
+                           frame);
+            }
+        }
+    }
+}
+
+define_serde_roundtrip!(test_serde_roundtrip_be, BigEndian);
+define_serde_roundtrip!(test_serde_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_serde_layout() {
+    let schema = schema();
+    let mut data = Vec::new();
+    {
+        let mut writer = BitWriter::<BigEndian>::new(&mut data);
+        to_writer(&mut writer, &schema,
+                  &BlockHeader{last_block: true,
+                               block_type: 4,
+                               block_size: 34}).unwrap();
+        to_writer(&mut writer, &schema, &Channels::Mono).unwrap();
+        to_writer(&mut writer, &schema, &Some(3u8)).unwrap();
+        to_writer(&mut writer, &schema, "hi").unwrap();
+        writer.byte_align().unwrap();
+    }
+    assert_eq!(data, [0x84, 0x00, 0x00, 0x22,
+                      0x00, 0x00, 0x00, 0x00,
+                      0x81, 0x81, 0x34, 0x34, 0x80]);
+}
+
+#[test]
+fn test_serde_errors() {
+    let schema = schema();
+
+    /*values too large for their widths*/
+    let mut data = Vec::new();
+    let mut writer = BitWriter::<BigEndian>::new(&mut data);
+    match to_writer(&mut writer, &schema,
+                    &BlockHeader{last_block: false,
+                                 block_type: 128,
+                                 block_size: 0}) {
+        Err(Error::Io(err)) => {
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert!(to_writer(&mut writer, &schema, &vec![0u8; 256]).is_err());
+
+    /*truncated streams*/
+    let data = [0x84, 0x00];
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    match from_reader::<_, BlockHeader>(&mut reader, &schema) {
+        Err(Error::Io(err)) => {
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    /*invalid strings and chars*/
+    let data = [0x01, 0xFF];
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    assert!(from_reader::<_, String>(&mut reader, &schema).is_err());
+    let data = [0xFF, 0xFF, 0xFF, 0xFF];
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    assert!(from_reader::<_, char>(&mut reader, &schema).is_err());
+
+    /*unknown variants*/
+    let data = [0x00, 0x00, 0x00, 0x04];
+    let mut reader = BitReader::<BigEndian>::new(Box::new(Cursor::new(data)));
+    assert!(from_reader::<_, Channels>(&mut reader, &schema).is_err());
+}
@@ -0,0 +1,76 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+mod common;
+
+use std::io::ErrorKind;
+use bitstream_io::{BigEndian, LittleEndian, BitReader};
+use bitstream_io::stats::Analyzer;
+use common::noise;
+
+#[test]
+fn test_stats_noise() {
+    let data = noise(4000, 1);
+    let mut r = BitReader::<BigEndian, _>::from_read(&data[..]);
+    let stats = Analyzer::default().analyze(&mut r).unwrap();
+    assert_eq!(stats.bits, 32000);
+    assert!((stats.ones_density() - 0.5).abs() < 0.02);
+    assert!((stats.mean_run() - 2.0).abs() < 0.1);
+
+    /*every bit belongs to exactly one run*/
+    let run_bits: u64 = stats.zero_runs.iter().chain(stats.one_runs.iter())
+        .map(|(len, count)| len * count).sum();
+    assert_eq!(run_bits, stats.bits);
+    let ones: u64 = stats.one_runs.iter().map(|(len, count)| len * count)
+        .sum();
+    assert_eq!(ones, stats.ones);
+
+    /*windows start every 1024 bits for as long as 4096 remain*/
+    assert_eq!(stats.entropy.len(), (32000 - 4096) / 1024 + 1);
+    assert!(stats.entropy.iter().all(|&e| e > 0.9 && e <= 1.0));
+}
+
+#[test]
+fn test_stats_constant() {
+    let data = [0xFF; 100];
+    let mut r = BitReader::<LittleEndian, _>::from_read(&data[..]);
+    let analyzer = Analyzer{window: 64, step: 100, symbol_bits: 4};
+    let stats = analyzer.analyze(&mut r).unwrap();
+    assert_eq!(stats.ones_density(), 1.0);
+    assert!(stats.zero_runs.is_empty());
+    assert_eq!(stats.one_runs.len(), 1);
+    assert_eq!(stats.one_runs.get(&800), Some(&1));
+    assert_eq!(stats.entropy, vec![0.0; 8]);
+
+    /*an empty stream has no statistics at all*/
+    let mut r = BitReader::<LittleEndian, _>::from_read(&data[0..0]);
+    let stats = analyzer.analyze(&mut r).unwrap();
+    assert_eq!(stats.bits, 0);
+    assert_eq!(stats.ones_density(), 0.0);
+    assert_eq!(stats.mean_run(), 0.0);
+    assert!(stats.entropy.is_empty());
+}
+
+#[test]
+fn test_stats_errors() {
+    let data = [0; 4];
+    for &analyzer in [Analyzer{window: 16, step: 8, symbol_bits: 0},
+                      Analyzer{window: 34, step: 34, symbol_bits: 17},
+                      Analyzer{window: 12, step: 8, symbol_bits: 8},
+                      Analyzer{window: 16, step: 12, symbol_bits: 8},
+                      Analyzer{window: 0, step: 8, symbol_bits: 8},
+                      Analyzer{window: 16, step: 0, symbol_bits: 8}].iter() {
+        let mut r = BitReader::<BigEndian, _>::from_read(&data[..]);
+        assert_eq!(analyzer.analyze(&mut r).unwrap_err().kind(),
+                   ErrorKind::InvalidInput);
+    }
+}
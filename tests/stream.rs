@@ -0,0 +1,118 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "futures")]
+
+extern crate futures;
+extern crate bitstream_io;
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use futures::executor::block_on;
+use futures::io::AsyncRead;
+use futures::stream::StreamExt;
+use bitstream_io::{BigEndian, LittleEndian, BitRead, BitWrite, BitWriter,
+                   FromBitStream, ToBitStream};
+use bitstream_io::stream::AsyncBitReader;
+
+#[derive(Debug, PartialEq)]
+struct Sample {
+    channel: u8,
+    value: i32,
+}
+
+impl FromBitStream for Sample {
+    type Error = io::Error;
+
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+        Ok(Sample{channel: r.read(3)?, value: r.read_signed(17)?})
+    }
+}
+
+impl ToBitStream for Sample {
+    type Error = io::Error;
+
+    fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write(3, self.channel)?;
+        w.write_signed(17, self.value)
+    }
+}
+
+/*delivers one byte per read, returning Pending between each*/
+struct Trickle {
+    data: Vec<u8>,
+    position: usize,
+    ready: bool,
+}
+
+impl AsyncRead for Trickle {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut [u8])
+                 -> Poll<io::Result<usize>> {
+        if !self.ready {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        self.ready = false;
+        if self.position < self.data.len() && !buf.is_empty() {
+            buf[0] = self.data[self.position];
+            self.position += 1;
+            Poll::Ready(Ok(1))
+        } else {
+            Poll::Ready(Ok(0))
+        }
+    }
+}
+
+macro_rules! define_stream_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let samples: Vec<Sample> = (0..10).map(
+                |i| Sample{channel: i % 8, value: (i as i32 - 5) * 1000})
+                .collect();
+            let mut data = Vec::new();
+            {
+                let mut writer = BitWriter::<$endianness>::new(&mut data);
+                for sample in samples.iter() {
+                    writer.build(sample).unwrap();
+                }
+                writer.byte_align().unwrap();
+            }
+            /*10 samples of 20 bits, with 4 bits of padding*/
+            assert_eq!(data.len(), 25);
+
+            let reader = AsyncBitReader::<$endianness, _>::new(
+                Trickle{data: data.clone(), position: 0, ready: false});
+            let read: Vec<Sample> = block_on(
+                reader.into_stream::<Sample>().map(|s| s.unwrap()).collect());
+            assert_eq!(read, samples);
+
+            /*a truncated value is an error, after which the stream ends*/
+            let reader = AsyncBitReader::<$endianness, _>::new(&data[..24]);
+            let read: Vec<io::Result<Sample>> = block_on(
+                reader.into_stream::<Sample>().collect());
+            assert_eq!(read.len(), 10);
+            assert!(read[..9].iter().all(|s| s.is_ok()));
+            assert_eq!(read[9].as_ref().unwrap_err().kind(),
+                       io::ErrorKind::UnexpectedEof);
+        }
+    }
+}
+
+define_stream_roundtrip!(test_stream_roundtrip_be, BigEndian);
+define_stream_roundtrip!(test_stream_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_stream_empty() {
+    let reader = AsyncBitReader::<BigEndian, _>::new(&[][..]);
+    let read: Vec<io::Result<Sample>> = block_on(
+        reader.into_stream::<Sample>().collect());
+    assert!(read.is_empty());
+}
@@ -0,0 +1,47 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+
+use bitstream_io::{BigEndian, LittleEndian, BitReader};
+use bitstream_io::tables::{ones_run, zeros_run, LEADING_ONES, LEADING_ZEROS,
+                           ONES, REVERSED, TRAILING_ONES, TRAILING_ZEROS};
+
+#[test]
+fn test_tables() {
+    for byte in 0..=255u8 {
+        let i = byte as usize;
+        assert_eq!(REVERSED[i], byte.reverse_bits());
+        assert_eq!(REVERSED[REVERSED[i] as usize], byte);
+        assert_eq!(u32::from(ONES[i]), byte.count_ones());
+        assert_eq!(u32::from(LEADING_ZEROS[i]), byte.leading_zeros());
+        assert_eq!(u32::from(LEADING_ONES[i]), byte.leading_ones());
+        assert_eq!(u32::from(TRAILING_ZEROS[i]), byte.trailing_zeros());
+        assert_eq!(u32::from(TRAILING_ONES[i]), byte.trailing_ones());
+    }
+    assert_eq!(LEADING_ZEROS[0], 8);
+    assert_eq!(TRAILING_ONES[0xFF], 8);
+}
+
+#[test]
+fn test_runs() {
+    /*runs match the unary values read from a single byte*/
+    for byte in 0..=255u8 {
+        let data = [byte];
+        let mut r = BitReader::<BigEndian, _>::from_read(&data[..]);
+        match r.read_unary1() {
+            Ok(zeros) => assert_eq!(zeros_run::<BigEndian>(byte), zeros),
+            Err(_) => assert_eq!(zeros_run::<BigEndian>(byte), 8),
+        }
+        let mut r = BitReader::<LittleEndian, _>::from_read(&data[..]);
+        match r.read_unary0() {
+            Ok(ones) => assert_eq!(ones_run::<LittleEndian>(byte), ones),
+            Err(_) => assert_eq!(ones_run::<LittleEndian>(byte), 8),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+use std::io;
+use bitstream_io::{BigEndian, BitRead, BitReader, BitWrite, BitWriter,
+                   Numeric};
+use bitstream_io::tap::{Observer, Tap, TapError};
+
+/*logs each field by kind, offset and size,
+  and attaches the number of fields seen to errors*/
+#[derive(Default)]
+struct Log(Vec<(&'static str, u64, u64)>);
+
+impl Observer for Log {
+    fn unsigned<U: Numeric>(&mut self, offset: u64, bits: u32, _value: U) {
+        self.0.push(("unsigned", offset, u64::from(bits)));
+    }
+
+    fn skipped(&mut self, offset: u64, bits: u64) {
+        self.0.push(("skipped", offset, bits));
+    }
+
+    fn padding(&mut self, offset: u64, bits: &[bool]) {
+        self.0.push(("padding", offset, bits.len() as u64));
+    }
+
+    fn bits(&mut self, offset: u64, bits: u64) {
+        self.0.push(("bits", offset, bits));
+    }
+
+    fn watched(&self, skip: u32) -> u32 {skip.saturating_sub(4)}
+
+    fn error(&self, offset: u64, error: io::Error) -> io::Error {
+        TapError::new(error, offset,
+                      format!(" after {} fields", self.0.len())).into()
+    }
+}
+
+#[test]
+fn test_tap_reader() {
+    let data = [0b1011_0001, 0xFF, 0x00];
+    let mut r = Tap::with_observer(
+        BitReader::<BigEndian, _>::from_read(&data[..]), Log::default());
+    assert!(r.read_bit().unwrap());
+    r.skip(6).unwrap();
+    r.byte_align();
+    r.skip(3).unwrap();
+    assert_eq!(r.read_unary1().unwrap(), 0);
+    r.byte_align();
+    assert_eq!(r.position(), 16);
+
+    /*failed reads don't advance and carry the observer's context*/
+    let err = r.read::<u16>(9).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    let tap = TapError::<String>::find(&err).unwrap();
+    assert_eq!(tap.position(), 16);
+    assert_eq!(tap.to_string(),
+               "failed to fill whole buffer at bit 16 after 7 fields");
+    assert_eq!(r.position(), 16);
+
+    /*only the first 4 bits of a skip are skipped,
+      and the rest are read as values*/
+    let (_, log) = r.into_parts();
+    assert_eq!(log.0, [("bits", 0, 1),
+                       ("skipped", 1, 4),
+                       ("unsigned", 5, 2),
+                       ("padding", 7, 1),
+                       ("skipped", 8, 3),
+                       ("bits", 11, 1),
+                       ("padding", 12, 4)]);
+}
+
+#[test]
+fn test_tap_writer() {
+    let mut data = Vec::new();
+    {
+        let mut w = Tap::with_observer(
+            BitWriter::<BigEndian, _>::from_write(&mut data), Log::default());
+        w.write(3, 0b101u8).unwrap();
+        w.write_unary0(2).unwrap();
+        w.byte_align().unwrap();
+        w.write_bytes(b"a").unwrap();
+        assert!(w.write(2, 4u8).is_err());
+        assert_eq!(w.position(), 16);
+        assert_eq!(w.observer().0, [("unsigned", 0, 3),
+                                    ("bits", 3, 3),
+                                    ("padding", 6, 2),
+                                    ("bits", 8, 8)]);
+    }
+    assert_eq!(data, [0b1011_1000, b'a']);
+}
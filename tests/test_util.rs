@@ -0,0 +1,270 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "test-util")]
+
+#[macro_use]
+extern crate proptest;
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitRead, BitWrite,
+                   BitReader, BitWriter, FromBitStream, ToBitStream};
+use bitstream_io::test_util::{assert_roundtrip, bit_diff, roundtrip,
+                              assert_golden, assert_golden_bits,
+                              check_golden, check_golden_bits,
+                              check_fields, check_fields_roundtrip,
+                              field, fields, Field,
+                              Dribble, Flaky, FailAfter};
+use std::io;
+
+#[derive(Debug, PartialEq)]
+struct Header {
+    version: u8,
+    flags: u16,
+}
+
+impl FromBitStream for Header {
+    type Error = io::Error;
+
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+        Ok(Header{version: r.read(3)?, flags: r.read(11)?})
+    }
+}
+
+impl ToBitStream for Header {
+    type Error = io::Error;
+
+    fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write(3, self.version)?;
+        w.write(11, self.flags)
+    }
+}
+
+/*reads its fields in the wrong order*/
+#[derive(Debug, PartialEq)]
+struct Swapped {
+    high: u8,
+    low: u8,
+}
+
+impl FromBitStream for Swapped {
+    type Error = io::Error;
+
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+        let low = r.read(8)?;
+        let high = r.read(8)?;
+        Ok(Swapped{high, low})
+    }
+}
+
+impl ToBitStream for Swapped {
+    type Error = io::Error;
+
+    fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write(8, self.high)?;
+        w.write(8, self.low)
+    }
+}
+
+/*reads fewer bits than it writes*/
+#[derive(Debug, PartialEq)]
+struct Padded(u8);
+
+impl FromBitStream for Padded {
+    type Error = io::Error;
+
+    fn from_reader<R: BitRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+        r.read(8).map(Padded)
+    }
+}
+
+impl ToBitStream for Padded {
+    type Error = io::Error;
+
+    fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write(8, self.0)?;
+        w.write(8, 0u8)
+    }
+}
+
+#[test]
+fn test_roundtrip() {
+    assert_roundtrip(&Header{version: 5, flags: 0x7FF});
+    assert_roundtrip(&Swapped{high: 1, low: 1});
+
+    let error = roundtrip::<BigEndian, _>(&Swapped{high: 1, low: 2})
+        .unwrap_err();
+    assert!(error.starts_with("value mismatch"));
+    assert!(error.contains("first difference at byte 0"));
+
+    assert_eq!(roundtrip::<LittleEndian, _>(&Padded(1)),
+               Err("wrote 16 bits but read only 8".to_string()));
+    assert!(roundtrip::<BigEndian, _>(&Header{version: 8, flags: 0})
+            .unwrap_err().starts_with("write failed"));
+}
+
+#[test]
+#[should_panic(expected = "big-endian round trip failed")]
+fn test_assert_roundtrip_panics() {
+    assert_roundtrip(&Swapped{high: 0xAB, low: 0xCD});
+}
+
+#[test]
+fn test_bit_diff() {
+    assert_eq!(bit_diff(&[], &[]), None);
+    assert_eq!(bit_diff(&[0, 1, 2, 3, 4, 5], &[0, 1, 2, 7, 4, 5]).unwrap(),
+               concat!("first difference at byte 3\n",
+                       "expected: 00000001 00000010 00000011 00000100 00000101\n",
+                       "  actual: 00000001 00000010 00000111 00000100 00000101\n",
+                       "                                 ^"));
+    assert_eq!(bit_diff(&[0xFF, 0x80], &[0xFF]).unwrap(),
+               concat!("first difference at byte 1\n",
+                       "expected: 11111111 10000000\n",
+                       "  actual: 11111111 --------\n",
+                       "                   ^^^^^^^^"));
+}
+
+#[test]
+fn test_golden() {
+    let header = Header{version: 5, flags: 0x123};
+    assert_golden::<BigEndian, _, _>(&[0b1010_0100, 0b1000_1100], 14, |w| {
+        w.build(&header)
+    });
+    assert_golden_bits::<LittleEndian, _, _>("101 1100 0100 100", |w| {
+        w.build(&header)
+    });
+    assert!(check_golden_bits::<BigEndian, _, _>("10x", |w| {
+        w.write(2, 2u8)
+    }).is_err());
+    assert!(check_golden::<BigEndian, _, _>(&[0], 9, |w| {
+        w.write(9, 0u16)
+    }).is_err());
+    assert_eq!(check_golden_bits::<BigEndian, _, _>("", |w| {
+        w.write_bit(true)
+    }).unwrap_err(), "first difference at bit 0\n\
+                      offset  expected  actual    field\n     \
+                           0  -         1         bit\n                  \
+                      ^");
+
+    /*long, untraced and missing fields*/
+    let message = check_golden_bits::<BigEndian, _, _>(
+        &format!("11 {} 0 01 111", "0".repeat(72)), |w| {
+        w.write(2, 3u8)?;
+        w.write_bytes(&[0; 9])?;
+        w.label_next("last");
+        w.write(1, 1u8)?;
+        w.get_mut().write(2, 1u8)
+    }).unwrap_err();
+    let lines: Vec<&str> = message.lines().collect();
+    assert_eq!(lines[0], "first difference at bit 74");
+    assert!(lines[2].starts_with("     0  11 ") &&
+            lines[2].ends_with(" unsigned"));
+    assert!(lines[3].starts_with("     2  0000") &&
+            lines[3].ends_with("...  bytes"));
+    assert!(lines[4].starts_with("    74  0 ") &&
+            lines[4].ends_with(" last"));
+    assert_eq!(lines[5].trim(), "^");
+    assert!(lines[6].starts_with("    75  01 ") &&
+            lines[6].ends_with(" untraced"));
+    assert!(lines[7].starts_with("    77  111 ") &&
+            lines[7].ends_with(" missing"));
+    assert_eq!(lines[8].trim(), "^^^");
+    assert_eq!(lines.len(), 9);
+}
+
+proptest! {
+    #[test]
+    fn test_fields_roundtrip(fields in fields()) {
+        check_fields_roundtrip(&fields)?;
+    }
+
+    #[test]
+    fn test_field_roundtrip(field in field(), offset in 0..8u32) {
+        check_fields::<BigEndian>(offset, &[field])?;
+        check_fields::<LittleEndian>(offset, &[field])?;
+    }
+}
+
+#[test]
+fn test_full_width_fields() {
+    check_fields_roundtrip(&[Field::I16(16, 5),
+                             Field::I16(16, i16::MIN),
+                             Field::U8(8, 0xFF),
+                             Field::I8(1, -1),
+                             Field::U64(64, u64::MAX),
+                             Field::I64(64, i64::MAX),
+                             Field::U32(0, 0)]).unwrap();
+}
+
+#[test]
+fn test_dribble() {
+    use std::io::Read;
+
+    let data: Vec<u8> = (0..50).collect();
+    let mut r =
+        BitReader::<LittleEndian, _>::from_read(Dribble::new(&data[..]));
+    for i in 0..50 {
+        assert_eq!(r.read::<u8>(3).unwrap(), i & 0x7);
+        assert_eq!(r.read::<u8>(5).unwrap(), i >> 3);
+    }
+
+    let mut output = Dribble::new(Vec::new());
+    {
+        let mut w = BitWriter::<BigEndian>::new(&mut output);
+        w.write_bytes(&data).unwrap();
+    }
+    assert_eq!(output.into_inner(), data);
+
+    let mut reader = Dribble::new(&data[..]);
+    let mut read = Vec::new();
+    reader.read_to_end(&mut read).unwrap();
+    assert_eq!(read, data);
+}
+
+#[test]
+fn test_flaky() {
+    let data = [0xB1, 0xED, 0x3B, 0xC1];
+
+    /*interrupted calls are retried*/
+    let mut r = BitReader::<BigEndian, _>::from_read(
+        Flaky::new(&data[..], io::ErrorKind::Interrupted));
+    assert_eq!(r.read::<u32>(32).unwrap(), 0xB1ED3BC1);
+    let mut output = Flaky::new(Vec::new(), io::ErrorKind::Interrupted);
+    {
+        let mut w = BitWriter::<BigEndian>::new(&mut output);
+        w.write(32, 0xB1ED3BC1u32).unwrap();
+    }
+    assert_eq!(output.into_inner(), data);
+
+    /*other errors are passed along*/
+    let mut r = BitReader::<BigEndian, _>::from_read(
+        Flaky::new(&data[..], io::ErrorKind::WouldBlock));
+    assert_eq!(r.read::<u8>(4).unwrap_err().kind(),
+               io::ErrorKind::WouldBlock);
+    assert_eq!(r.read::<u8>(4).unwrap(), 0xB);
+}
+
+#[test]
+fn test_fail_after() {
+    let data = [0xB1, 0xED, 0x3B, 0xC1];
+
+    let mut r = BitReader::<LittleEndian, _>::from_read(
+        FailAfter::new(&data[..], 2, io::ErrorKind::ConnectionReset));
+    assert_eq!(r.read::<u16>(12).unwrap(), 0xDB1);
+    assert_eq!(r.read::<u8>(4).unwrap(), 0xE);
+    assert_eq!(r.read::<u8>(1).unwrap_err().kind(),
+               io::ErrorKind::ConnectionReset);
+
+    let mut output = Dribble::new(
+        FailAfter::new(Vec::new(), 3, io::ErrorKind::BrokenPipe));
+    {
+        let mut w = BitWriter::<BigEndian>::new(&mut output);
+        assert_eq!(w.write_bytes(&data).unwrap_err().kind(),
+                   io::ErrorKind::BrokenPipe);
+    }
+    assert_eq!(output.into_inner().into_inner(), [0xB1, 0xED, 0x3B]);
+}
@@ -0,0 +1,117 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+use bitstream_io::{BigEndian, LittleEndian, BitReader, BitWrite, BitWriter};
+use bitstream_io::inspect::{inspect, FieldDescriptor, FieldKind, FieldValue};
+use bitstream_io::trace::TraceWriter;
+
+#[test]
+fn test_trace_fields() {
+    let mut data = Vec::new();
+    let mut w = TraceWriter::new(
+        BitWriter::<LittleEndian, _>::from_write(&mut data));
+    w.write_bit(true).unwrap();
+    w.label_next("width");
+    w.write_unary0(3).unwrap();
+    w.write_signed(8, -128i8).unwrap();
+    w.write_signed(64, i64::MIN).unwrap();
+    w.write(100, (1u128 << 99) | 0xAB).unwrap();
+    w.write_signed(68, -2i128).unwrap();
+    w.byte_align().unwrap();
+    w.label_next("payload");
+    w.write_bytes(b"\x01\xFE").unwrap();
+
+    /*rejected fields aren't logged and keep any pending label*/
+    w.label_next("rejected");
+    assert!(w.write(9, 0u8).is_err());
+    assert!(w.write(2, 4u8).is_err());
+    w.write_unary1(0).unwrap();
+    assert_eq!(w.position(), 265);
+
+    let (_, entries) = w.into_log();
+    let offsets = entries.iter().map(|e| (e.path.as_str(), e.offset, e.bits))
+        .collect::<Vec<_>>();
+    assert_eq!(offsets, [("bit", 0, 1),
+                         ("width", 1, 4),
+                         ("signed", 5, 8),
+                         ("signed", 13, 64),
+                         ("unsigned", 77, 100),
+                         ("signed", 177, 68),
+                         ("padding", 245, 3),
+                         ("payload", 248, 16),
+                         ("rejected", 264, 1)]);
+    let mut wide = vec![0; 13];
+    wide[0] = 0x08;
+    wide[12] = 0xAB;
+    let mut negative = vec![0xFF; 9];
+    negative[0] = 0x0F;
+    negative[8] = 0xFE;
+    assert_eq!(entries.into_iter().map(|e| e.value).collect::<Vec<_>>(),
+               [FieldValue::Flag(true),
+                FieldValue::Unsigned(3),
+                FieldValue::Signed(-128),
+                FieldValue::Signed(i64::MIN),
+                FieldValue::Bytes(wide),
+                FieldValue::Bytes(negative),
+                FieldValue::Unsigned(0),
+                FieldValue::Bytes(vec![0x01, 0xFE]),
+                FieldValue::Unsigned(0)]);
+}
+
+#[test]
+fn test_trace_matches_inspect() {
+    /*a traced encoder's log reads back the same through inspect*/
+    const FIELDS: &[FieldDescriptor] = &[
+        FieldDescriptor{name: "flag", kind: FieldKind::Flag},
+        FieldDescriptor{name: "rate", kind: FieldKind::Unsigned(20)},
+        FieldDescriptor{name: "offset", kind: FieldKind::Signed(11)},
+        FieldDescriptor{name: "id", kind: FieldKind::Bytes(2)},
+    ];
+    let mut data = Vec::new();
+    let logged = {
+        let mut w = TraceWriter::new(
+            BitWriter::<BigEndian, _>::from_write(&mut data));
+        w.label_next("flag");
+        w.write_bit(false).unwrap();
+        w.label_next("rate");
+        w.write(20, 44100u32).unwrap();
+        w.label_next("offset");
+        w.write_signed(11, -1000i16).unwrap();
+        w.label_next("id");
+        w.write_bytes(b"ab").unwrap();
+        w.into_log().1
+    };
+    let mut r = BitReader::<BigEndian, _>::from_read(&data[..]);
+    assert_eq!(inspect(&mut r, FIELDS).unwrap(), logged);
+    assert!(r.read_bit().is_err());
+}
+
+#[test]
+fn test_trace_json() {
+    let mut data = Vec::new();
+    let mut w = TraceWriter::new(
+        BitWriter::<BigEndian, _>::from_write(&mut data));
+    assert_eq!(w.to_json(), "[]");
+    assert_eq!(w.to_text(), "");
+    w.label_next("a \"quoted\"\\label\n");
+    w.write_bit(true).unwrap();
+    w.write_bytes(&[0xAB, 0x01]).unwrap();
+    assert_eq!(w.to_json(),
+               "[{\"offset\":0,\"bits\":1,\
+                 \"label\":\"a \\\"quoted\\\"\\\\label\\u000a\",\
+                 \"value\":true},\
+                {\"offset\":1,\"bits\":16,\"label\":\"bytes\",\
+                 \"value\":\"AB01\"}]");
+    w.clear();
+    assert!(w.entries().is_empty());
+    assert_eq!(w.position(), 17);
+}
@@ -0,0 +1,101 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+use std::io::{self, Read};
+use bitstream_io::{BigEndian, LittleEndian, BitReader};
+use bitstream_io::random::{Pattern, RandomSource};
+use bitstream_io::transcode::Transcoder;
+
+/*returns a byte at a time, interrupted before each*/
+struct Trickle<'a> {
+    data: &'a [u8],
+    interrupt: bool,
+}
+
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "again"));
+        }
+        let amount = buf.len().min(self.data.len()).min(1);
+        buf[..amount].copy_from_slice(&self.data[..amount]);
+        self.data = &self.data[amount..];
+        Ok(amount)
+    }
+}
+
+#[test]
+fn test_transcode_bits() {
+    let mut original = vec![0; 10_000];
+    RandomSource::<BigEndian>::new(5, Pattern::Uniform)
+        .read_exact(&mut original).unwrap();
+    let mut transcoded = Vec::new();
+    let written = Transcoder::between::<BigEndian, LittleEndian>()
+        .transcode(&mut &original[..], &mut transcoded).unwrap();
+    assert_eq!(written, 10_000);
+
+    /*the same bits are read in the other convention*/
+    let mut msb = BitReader::<BigEndian, _>::from_read(&original[..]);
+    let mut lsb = BitReader::<LittleEndian, _>::from_read(&transcoded[..]);
+    for _ in 0..80_000 {
+        assert_eq!(msb.read_bit().unwrap(), lsb.read_bit().unwrap());
+    }
+    assert!(lsb.read_bit().is_err());
+
+    /*and converting back restores the original*/
+    let mut restored = Vec::new();
+    Transcoder::between::<LittleEndian, BigEndian>()
+        .transcode(&mut &transcoded[..], &mut restored).unwrap();
+    assert_eq!(restored, original);
+}
+
+#[test]
+fn test_transcode_words() {
+    let data = [0x01, 0x02, 0x03, 0x04, 0x80, 0x40, 0x20, 0x10];
+    let transcoder = Transcoder{reverse_bits: true, word_bytes: 4};
+    let mut output = Vec::new();
+    assert_eq!(transcoder.transcode(&mut Trickle{data: &data,
+                                                 interrupt: false},
+                                    &mut output).unwrap(), 8);
+    assert_eq!(output, [0x20, 0xC0, 0x40, 0x80, 0x08, 0x04, 0x02, 0x01]);
+
+    let mut slice = data;
+    transcoder.transcode_slice(&mut slice).unwrap();
+    assert_eq!(slice[..], output[..]);
+
+    /*whole words are written before a partial one is reported*/
+    let mut output = Vec::new();
+    let err = transcoder.transcode(&mut &data[..7], &mut output).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(output, [0x20, 0xC0, 0x40, 0x80]);
+    let mut slice = [1, 2, 3];
+    assert!(transcoder.transcode_slice(&mut slice).is_err());
+    assert_eq!(slice, [1, 2, 3]);
+
+    /*words which don't divide the buffer evenly*/
+    let mut original = vec![0; 3 * 5000];
+    RandomSource::<BigEndian>::new(6, Pattern::Uniform)
+        .read_exact(&mut original).unwrap();
+    let transcoder = Transcoder{reverse_bits: false, word_bytes: 3};
+    let mut output = Vec::new();
+    transcoder.transcode(&mut &original[..], &mut output).unwrap();
+    for (before, after) in original.chunks(3).zip(output.chunks(3)) {
+        assert_eq!(before, [after[2], after[1], after[0]]);
+    }
+
+    for &word_bytes in [0, 4097].iter() {
+        let transcoder = Transcoder{reverse_bits: false, word_bytes};
+        assert_eq!(transcoder.transcode(&mut &data[..], &mut Vec::new())
+                   .unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*run with --features unchecked-bitqueue, in debug and release builds*/
+#![cfg(feature = "unchecked-bitqueue")]
+
+extern crate bitstream_io;
+use bitstream_io::{BigEndian, LittleEndian, BitQueue, BitReader, BitWriter};
+
+#[test]
+fn test_unchecked_roundtrip() {
+    /*values read and written through unchecked queues are unchanged*/
+    let fields: Vec<(u32, u64)> = (1..=64)
+        .map(|bits| (bits, (u64::MAX >> (64 - bits)) ^ u64::from(bits)))
+        .collect();
+
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<BigEndian>::new(&mut data);
+        for &(bits, value) in fields.iter() {
+            w.write(bits, value).unwrap();
+        }
+        w.byte_align().unwrap();
+    }
+    let mut r = BitReader::<BigEndian, _>::from_read(&data[..]);
+    for &(bits, value) in fields.iter() {
+        assert_eq!(r.read::<u64>(bits).unwrap(), value);
+    }
+
+    let mut data = Vec::new();
+    {
+        let mut w = BitWriter::<LittleEndian>::new(&mut data);
+        for &(bits, value) in fields.iter() {
+            w.write(bits, value).unwrap();
+        }
+        w.byte_align().unwrap();
+    }
+    let mut r = BitReader::<LittleEndian, _>::from_read(&data[..]);
+    for &(bits, value) in fields.iter() {
+        assert_eq!(r.read::<u64>(bits).unwrap(), value);
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn test_unchecked_debug_underflow() {
+    /*debug builds keep the queue's checks as assertions*/
+    let mut q: BitQueue<BigEndian, u8> = BitQueue::from_value(0b101, 3);
+    q.pop(4);
+}
+
+#[test]
+#[cfg(not(debug_assertions))]
+fn test_unchecked_release_underflow() {
+    /*release builds drop them, so an overlong pop empties the queue*/
+    let mut q: BitQueue<BigEndian, u8> = BitQueue::from_value(0b101, 3);
+    assert_eq!(q.pop(4), 0b101);
+    assert!(q.is_empty());
+}
@@ -0,0 +1,73 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+
+mod common;
+
+use std::io::ErrorKind;
+use bitstream_io::{BigEndian, LittleEndian, BitReader, Endianness};
+use bitstream_io::unpack::{unpack_u16, unpack_u32};
+use common::noise;
+
+/*unpacks every width over every output length,
+  including those which end right at the end of the input,
+  and checks the fields against those read one at a time*/
+fn check_unpack<E: Endianness>() {
+    let data = noise(300, 1);
+    for bits in 0..=32 {
+        for fields in (0..=70).chain(Some(300 * 8 / bits.max(1) as usize)) {
+            let mut expected = Vec::with_capacity(fields);
+            let mut r = BitReader::<E, _>::from_read(&data[..]);
+            for _ in 0..fields {
+                expected.push(r.read::<u32>(bits).unwrap());
+            }
+
+            let mut wide = vec![0; fields];
+            unpack_u32::<E>(&data, bits, &mut wide).unwrap();
+            assert_eq!(wide, expected);
+
+            if bits <= 16 {
+                let mut narrow = vec![0; fields];
+                unpack_u16::<E>(&data, bits, &mut narrow).unwrap();
+                assert!(narrow.iter().map(|&f| f as u32).eq(expected));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_unpack_be() {
+    check_unpack::<BigEndian>();
+}
+
+#[test]
+fn test_unpack_le() {
+    check_unpack::<LittleEndian>();
+}
+
+#[test]
+fn test_unpack_errors() {
+    let data = [0xFF; 15];
+
+    let mut narrow = [0; 12];
+    assert_eq!(unpack_u16::<BigEndian>(&data, 17, &mut narrow)
+               .unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(unpack_u16::<BigEndian>(&data, 11, &mut narrow)
+               .unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    unpack_u16::<BigEndian>(&data, 10, &mut narrow).unwrap();
+    assert_eq!(narrow, [0x3FF; 12]);
+
+    let mut wide = [0; 4];
+    assert_eq!(unpack_u32::<LittleEndian>(&data, 33, &mut wide)
+               .unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(unpack_u32::<LittleEndian>(&data, 31, &mut wide)
+               .unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    unpack_u32::<LittleEndian>(&data, 30, &mut wide).unwrap();
+    assert_eq!(wide, [0x3FFF_FFFF; 4]);
+}
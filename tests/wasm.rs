@@ -0,0 +1,76 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "wasm")]
+
+extern crate bitstream_io;
+
+/*errors are JavaScript values which can't be built natively,
+  so only successful calls are exercised here*/
+
+use bitstream_io::{BigEndian, LittleEndian, BitWriter};
+use bitstream_io::wasm::{BigEndianReader, BigEndianWriter,
+                         LittleEndianReader, LittleEndianWriter};
+
+macro_rules! define_wasm_roundtrip {
+    ($func_name:ident, $endianness:ident, $reader:ident, $writer:ident) => {
+        #[test]
+        fn $func_name() {
+            let mut writer = $writer::new();
+            writer.write(3, 0b101).unwrap();
+            writer.write_signed(17, -12345).unwrap();
+            assert!(!writer.byte_aligned());
+            writer.write_bit(true).unwrap();
+            writer.write_unary0(5).unwrap();
+            writer.write_unary1(2).unwrap();
+            writer.write_bytes(b"abc").unwrap();
+            writer.write(32, 0xDEADBEEF).unwrap();
+            writer.write(5, 0b10011).unwrap();
+            let data = writer.finish().unwrap();
+
+            /*output matches BitWriter's exactly*/
+            let mut expected = Vec::new();
+            {
+                let mut w = BitWriter::<$endianness>::new(&mut expected);
+                w.write(3, 0b101u8).unwrap();
+                w.write_signed(17, -12345i32).unwrap();
+                w.write_bit(true).unwrap();
+                w.write_unary0(5).unwrap();
+                w.write_unary1(2).unwrap();
+                w.write_bytes(b"abc").unwrap();
+                w.write(32, 0xDEADBEEFu32).unwrap();
+                w.write(5, 0b10011u8).unwrap();
+                w.byte_align().unwrap();
+            }
+            assert_eq!(data, expected);
+
+            let mut reader = $reader::new(data);
+            assert_eq!(reader.read(3).unwrap(), 0b101);
+            assert_eq!(reader.read_signed(17).unwrap(), -12345);
+            assert!(reader.read_bit().unwrap());
+            assert_eq!(reader.read_unary0().unwrap(), 5);
+            assert_eq!(reader.read_unary1().unwrap(), 2);
+            assert_eq!(reader.read_bytes(3).unwrap(), b"abc");
+            assert_eq!(reader.read(32).unwrap(), 0xDEADBEEF);
+            assert_eq!(reader.position_in_bits(), 86.0);
+            assert_eq!(reader.read(5).unwrap(), 0b10011);
+            reader.byte_align();
+            assert!(reader.byte_aligned());
+            assert_eq!(reader.position_in_bits(), 96.0);
+            reader.seek_bits(3.0).unwrap();
+            assert_eq!(reader.read_signed(17).unwrap(), -12345);
+            reader.skip(1).unwrap();
+            assert_eq!(reader.position_in_bits(), 21.0);
+        }
+    }
+}
+
+define_wasm_roundtrip!(test_wasm_roundtrip_be, BigEndian,
+                       BigEndianReader, BigEndianWriter);
+define_wasm_roundtrip!(test_wasm_roundtrip_le, LittleEndian,
+                       LittleEndianReader, LittleEndianWriter);
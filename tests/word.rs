@@ -0,0 +1,367 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "std")]
+
+extern crate bitstream_io;
+
+use std::io::{BufReader, Cursor, Read};
+use bitstream_io::{BigEndian, LittleEndian, BitRead, BitReader, BitWriter,
+                   BitQueue, Endianness};
+use bitstream_io::random::{Pattern, RandomSource};
+use bitstream_io::word::WordReader;
+
+/*reproducible choices of fields and values, the same from run to run*/
+struct Choices(BitReader<BigEndian, RandomSource<BigEndian>>);
+
+impl Choices {
+    fn new(seed: u64) -> Choices {
+        Choices(BitReader::from_read(
+            RandomSource::new(seed, Pattern::Uniform)))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0.read::<u64>(31).unwrap()
+    }
+}
+
+/*reads the same fields from a BitReader and a WordReader,
+  checking that they agree all the way to the end of the stream*/
+fn check_against_reader<E: Endianness>(data: &[u8], capacity: usize) {
+    let mut expected = BitReader::<E, _>::from_read(data);
+    let mut actual =
+        WordReader::<E, _>::new(BufReader::with_capacity(capacity, data));
+    let mut fields = Choices::new(data.len() as u64);
+    loop {
+        match fields.next() % 7 {
+            0 => {
+                let bits = (fields.next() % 65) as u32;
+                let e = expected.read::<u64>(bits);
+                let a = actual.read::<u64>(bits);
+                assert_eq!(e.is_ok(), a.is_ok());
+                match (e, a) {
+                    (Ok(e), Ok(a)) => assert_eq!(e, a),
+                    _ => return,
+                }
+            }
+            1 => {
+                let bits = (fields.next() % 8 + 1) as u32;
+                match (expected.read_signed::<i8>(bits),
+                       actual.read_signed::<i8>(bits)) {
+                    (Ok(e), Ok(a)) => assert_eq!(e, a),
+                    (e, a) => {assert_eq!(e.is_ok(), a.is_ok()); return}
+                }
+            }
+            2 => {
+                match (expected.read_unary0(), actual.read_unary0()) {
+                    (Ok(e), Ok(a)) => assert_eq!(e, a),
+                    (e, a) => {assert_eq!(e.is_ok(), a.is_ok()); return}
+                }
+            }
+            3 => {
+                match (expected.read_unary1(), actual.read_unary1()) {
+                    (Ok(e), Ok(a)) => assert_eq!(e, a),
+                    (e, a) => {assert_eq!(e.is_ok(), a.is_ok()); return}
+                }
+            }
+            4 => {
+                let bits = (fields.next() % 100) as u32;
+                let e = expected.skip(bits);
+                let a = actual.skip(bits);
+                assert_eq!(e.is_ok(), a.is_ok());
+                if e.is_err() {
+                    return;
+                }
+            }
+            5 => {
+                let mut e = [0; 3];
+                let mut a = [0; 3];
+                match (expected.read_bytes(&mut e), actual.read_bytes(&mut a)) {
+                    (Ok(()), Ok(())) => assert_eq!(e, a),
+                    (e, a) => {assert_eq!(e.is_ok(), a.is_ok()); return}
+                }
+            }
+            _ => {
+                assert_eq!(expected.byte_aligned(), actual.byte_aligned());
+                expected.byte_align();
+                actual.byte_align();
+            }
+        }
+        assert_eq!(expected.byte_aligned(), actual.byte_aligned());
+    }
+}
+
+#[test]
+fn test_word_reader_matches() {
+    for len in [0, 1, 7, 8, 9, 31, 64, 255, 1000] {
+        /*runs of 0s and 1s for the unary reads*/
+        let pattern = Pattern::Runs{mean_zeros: 12.0, mean_ones: 12.0};
+        let mut data = Vec::new();
+        RandomSource::<BigEndian>::new(len, pattern)
+            .take(len)
+            .read_to_end(&mut data)
+            .unwrap();
+        for capacity in [1, 3, 8, 13, 4096] {
+            check_against_reader::<BigEndian>(&data, capacity);
+            check_against_reader::<LittleEndian>(&data, capacity);
+        }
+    }
+}
+
+#[test]
+fn test_word_reader_edges() {
+    let data = [0xB1, 0xED, 0x3B, 0xC1, 0x12, 0x34, 0x56, 0x78, 0x9A];
+
+    let mut r = WordReader::<BigEndian, _>::new(&data[..]);
+    assert_eq!(r.read::<u8>(0).unwrap(), 0);
+    assert!(r.read::<u8>(9).is_err());
+    assert!(r.read_signed::<i8>(9).is_err());
+    assert_eq!(r.read::<u64>(64).unwrap(), 0xB1ED3BC112345678);
+    assert_eq!(r.read::<u8>(8).unwrap(), 0x9A);
+    assert!(r.read::<u8>(1).is_err());
+
+    let mut r = WordReader::<LittleEndian, _>::new(&data[..]);
+    assert_eq!(r.read::<u8>(4).unwrap(), 0x1);
+    assert_eq!(r.read::<u64>(64).unwrap(), 0xA785_6341_2C13_BEDB);
+    assert_eq!(r.read::<u8>(4).unwrap(), 0x9);
+    assert!(r.read_bit().is_err());
+}
+
+#[test]
+fn test_word_reader_consumed() {
+    /*bytes are left in the stream's buffer until the word is used up,
+      and only those bits have been read from are consumed at the end*/
+    let data = [0xFF; 16];
+    let mut r = WordReader::<BigEndian, _>::new(Cursor::new(&data[..]));
+    r.skip(3).unwrap();
+    assert_eq!(r.get_ref().position(), 0);
+    r.skip(13).unwrap();
+    assert_eq!(r.read::<u8>(1).unwrap(), 1);
+    let mut rest = r.into_byte_reader();
+    assert_eq!(rest.position(), 3);
+    let mut buf = Vec::new();
+    rest.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf.len(), 13);
+
+    /*long skips are consumed from the buffer directly*/
+    let data = (0..100).collect::<Vec<u8>>();
+    let mut r = WordReader::<BigEndian, _>::new(Cursor::new(&data[..]));
+    r.skip(4).unwrap();
+    r.skip(60 + 8 * 50 + 3).unwrap();
+    assert_eq!(r.get_ref().position(), 58);
+    assert_eq!(r.read::<u8>(5).unwrap(), 58 & 0x1F);
+    assert_eq!(r.read::<u8>(8).unwrap(), 59);
+    assert!(r.skip(8 * 40 + 1).is_err());
+
+    let data = [0xFF; 16];
+    let mut r = WordReader::<LittleEndian, _>::new(Cursor::new(&data[..]));
+    r.skip(64).unwrap();
+    assert_eq!(r.read::<u16>(16).unwrap(), 0xFFFF);
+    assert_eq!(r.get_ref().position(), 8);
+    assert_eq!(r.into_byte_reader().position(), 10);
+}
+
+#[test]
+fn test_into_word_reader() {
+    /*a BitReader switched part way through a byte reads on
+      from the same bit, and consumes no more than it would have*/
+    let data = (0..64).map(|i| (i * 37) as u8).collect::<Vec<u8>>();
+    for offset in 0..16 {
+        let mut expected = BitReader::<BigEndian, _>::from_read(&data[..]);
+        let mut r = BitReader::<BigEndian, _>::from_read(Cursor::new(&data));
+        assert_eq!(r.read::<u16>(offset).unwrap(),
+                   expected.read::<u16>(offset).unwrap());
+        let mut r = r.into_word_reader();
+        for _ in 0..((64 * 8 - offset) / 7) {
+            assert_eq!(r.read::<u8>(7).unwrap(),
+                       expected.read::<u8>(7).unwrap());
+        }
+        let read = 64 * 8 - (64 * 8 - offset) % 7;
+        assert_eq!(r.into_byte_reader().position(),
+                   u64::from(read.div_ceil(8)));
+
+        let mut expected = BitReader::<LittleEndian, _>::from_read(&data[..]);
+        let mut r = BitReader::<LittleEndian, _>::from_read(&data[..]);
+        assert_eq!(r.read::<u16>(offset).unwrap(),
+                   expected.read::<u16>(offset).unwrap());
+        let mut r = r.into_word_reader();
+        assert_eq!(r.read::<u64>(64).unwrap(),
+                   expected.read::<u64>(64).unwrap());
+    }
+}
+
+/*writes runs long enough to span several words, starting at
+  every offset within a byte, and reads them back with both readers*/
+fn check_long_unary<E: Endianness>() {
+    let runs = [0, 63, 64, 65, 1, 200, 7, 8, 129, 0, 512];
+    for offset in 0..8 {
+        let mut data = Vec::new();
+        {
+            let mut w = BitWriter::<E>::new(&mut data);
+            w.write(offset, 0u8).unwrap();
+            for &run in runs.iter() {
+                w.write_unary0(run).unwrap();
+            }
+            for &run in runs.iter() {
+                w.write_unary1(run).unwrap();
+            }
+            w.byte_align().unwrap();
+        }
+        let mut r = BitReader::<E, _>::from_read(&data[..]);
+        let mut word = WordReader::<E, _>::new(&data[..]);
+        r.skip(offset).unwrap();
+        word.skip(offset).unwrap();
+        for &run in runs.iter() {
+            assert_eq!(r.read_unary0().unwrap(), run);
+            assert_eq!(word.read_unary0().unwrap(), run);
+        }
+        for &run in runs.iter() {
+            assert_eq!(r.read_unary1().unwrap(), run);
+            assert_eq!(word.read_unary1().unwrap(), run);
+        }
+    }
+}
+
+#[test]
+fn test_long_unary() {
+    check_long_unary::<BigEndian>();
+    check_long_unary::<LittleEndian>();
+}
+
+/*an unbuffered source which counts its reads*/
+struct Counting<'a> {
+    data: &'a [u8],
+    reads: usize,
+}
+
+impl<'a> Read for Counting<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reads += 1;
+        self.data.read(buf)
+    }
+}
+
+#[test]
+fn test_word_reader_buffered() {
+    let data = (0..1000).map(|i| i as u8).collect::<Vec<u8>>();
+
+    /*a BitReader makes one read per byte*/
+    let mut source = Counting{data: &data, reads: 0};
+    {
+        let mut r = BitReader::<BigEndian, _>::from_read(&mut source);
+        for _ in 0..(1000 * 8 / 5) {
+            r.read::<u8>(5).unwrap();
+        }
+    }
+    assert_eq!(source.reads, 1000);
+
+    /*but a buffered WordReader makes one read per buffer*/
+    let mut source = Counting{data: &data, reads: 0};
+    {
+        let mut r = WordReader::<BigEndian, _>::with_capacity(256, &mut source);
+        let mut expected = BitReader::<BigEndian, _>::from_read(&data[..]);
+        for _ in 0..(1000 * 8 / 5) {
+            assert_eq!(r.read::<u8>(5).unwrap(),
+                       expected.read::<u8>(5).unwrap());
+        }
+        assert!(r.read::<u8>(1).is_err());
+    }
+    assert!(source.reads <= 6);
+
+    let mut source = Counting{data: &data, reads: 0};
+    {
+        let mut r = WordReader::<LittleEndian, _>::buffered(&mut source);
+        r.skip(8000).unwrap();
+    }
+    assert_eq!(source.reads, 1);
+}
+
+/*a BitReader refills its cache with each field's bytes in a single read,
+  never reading past the byte holding the field's last bit*/
+fn check_bit_reader_refill<E: Endianness>() {
+    let data = (0..1000).map(|i| (i * 7) as u8).collect::<Vec<u8>>();
+    let mut source = Counting{data: &data, reads: 0};
+    let mut expected = BitReader::<E, _>::from_read(&data[..]);
+    let mut fields = 0;
+    {
+        let mut r = BitReader::<E, _>::from_read(&mut source);
+        assert_eq!(r.read::<u8>(3).unwrap(), expected.read::<u8>(3).unwrap());
+        while let Ok(value) = r.read::<u64>(61) {
+            let mut bits = BitQueue::<E, u64>::new();
+            for _ in 0..61 {
+                bits.push(1, u64::from(expected.read_bit().unwrap()));
+            }
+            assert_eq!(value, bits.value());
+            fields += 1;
+        }
+    }
+    assert_eq!(fields, (1000 * 8 - 3) / 61);
+    /*one read for the first byte, one per field and a failed last one*/
+    assert_eq!(source.reads, 1 + fields + 1);
+}
+
+#[test]
+fn test_bit_reader_refill() {
+    check_bit_reader_refill::<BigEndian>();
+    check_bit_reader_refill::<LittleEndian>();
+}
+
+/*Rice-coded blocks match values written one at a time,
+  for parameters from 0 to 32 and buffers small enough
+  that values often straddle the end of the cached word*/
+fn check_rice_block<E: Endianness>() {
+    let mut choices = Choices::new(17);
+    for k in 0..=32 {
+        let values = (0..300).map(|_| {
+            let quotient = choices.next() % 40;
+            let remainder =
+                if k == 0 {0} else {choices.next() & ((1 << k) - 1)};
+            ((quotient << k) | remainder).min(u64::from(u32::MAX)) as u32
+        }).collect::<Vec<u32>>();
+        let mut data = Vec::new();
+        {
+            let mut w = BitWriter::<E>::new(&mut data);
+            for &value in values.iter() {
+                w.write_unary1((u64::from(value) >> k) as u32).unwrap();
+                w.write(k, u64::from(value) & ((1 << k) - 1)).unwrap();
+            }
+            w.byte_align().unwrap();
+        }
+
+        let mut r = WordReader::<E, _>::with_capacity(
+            11, Cursor::new(&data));
+        let mut unsigned = vec![0; values.len()];
+        r.read_rice_block_unsigned(k, &mut unsigned[0..100]).unwrap();
+        r.read_rice_block_unsigned(k, &mut unsigned[100..]).unwrap();
+        assert_eq!(unsigned, values);
+
+        let mut r = WordReader::<E, _>::new(&data[..]);
+        let mut signed = vec![0; values.len()];
+        r.read_rice_block(k, &mut signed).unwrap();
+        assert!(signed.iter().zip(values.iter()).all(
+            |(&s, &v)| s == ((v >> 1) as i32) ^ -((v & 1) as i32)));
+        assert!(r.read_rice_block(k, &mut [0; 100]).is_err());
+    }
+}
+
+#[test]
+fn test_rice_block() {
+    check_rice_block::<BigEndian>();
+    check_rice_block::<LittleEndian>();
+
+    let data = [0; 16];
+    let mut r = WordReader::<BigEndian, _>::new(&data[..]);
+    assert_eq!(r.read_rice_block(33, &mut [0; 1]).unwrap_err().kind(),
+               std::io::ErrorKind::InvalidInput);
+
+    /*a quotient of 1 with a k of 32 is too large*/
+    let data = [0b01000000, 0, 0, 0, 0];
+    let mut r = WordReader::<BigEndian, _>::new(&data[..]);
+    assert_eq!(r.read_rice_block(32, &mut [0; 1]).unwrap_err().kind(),
+               std::io::ErrorKind::InvalidData);
+}
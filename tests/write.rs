@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![allow(clippy::bool_assert_comparison, clippy::unusual_byte_groupings)]
+
 extern crate bitstream_io;
 
 #[test]
@@ -369,6 +371,40 @@ fn test_writer_huffman_be() {
     assert_eq!(output.as_slice(), &final_data);
 }
 
+#[test]
+fn test_writer_wide() {
+    use bitstream_io::{BE, LE, BitWriter};
+    use bitstream_io::mixed::MixedEndian;
+    let data = [0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF,
+                0xFE, 0xDC, 0xBA, 0x98, 0x76, 0x54, 0x32, 0x10, 0x80];
+
+    let mut output = Vec::new();
+    {
+        let mut w = BitWriter::<BE>::new(&mut output);
+        w.write(4, 0u8).unwrap();
+        w.write(124, 0x123_4567_89AB_CDEF_FEDC_BA98_7654_3210u128).unwrap();
+        w.write_signed(8, -128i128).unwrap();
+        w.write_aligned(0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210u128)
+         .unwrap();
+        w.write_mixed_endian(MixedEndian::Pdp,
+                             0x2301_6745_AB89_EFCD_DCFE_98BA_5476_1032u128)
+         .unwrap();
+    }
+    assert_eq!(&output[0..17], &data[..]);
+    assert_eq!(&output[17..33], &data[0..16]);
+    assert_eq!(&output[33..], &data[0..16]);
+
+    let mut output = Vec::new();
+    {
+        let mut w = BitWriter::<LE>::new(&mut output);
+        w.write(4, 1u8).unwrap();
+        w.write(124, 0x103_2547_698B_ADCF_EEFC_DAB8_9674_5230u128).unwrap();
+        w.write_signed(8, -128i128).unwrap();
+        assert!(w.write(129, 0u128).is_err());
+    }
+    assert_eq!(output, data);
+}
+
 #[test]
 fn test_writer_le() {
     use bitstream_io::{LE, BitWriter};
@@ -935,3 +971,67 @@ fn test_writer_bits_errors() {
                    ErrorKind::InvalidInput);
     }
 }
+
+#[test]
+fn test_writer_debug() {
+    use bitstream_io::{BE, LE, BitWriter};
+
+    let mut output = Vec::new();
+    {
+        let mut w = BitWriter::<BE>::new(&mut output);
+        assert_eq!(format!("{:?}", w),
+                   "BitWriter { pending_bits: 0, pending_value: 0b0 }");
+        w.write(2, 2u8).unwrap();
+        w.write(3, 6u8).unwrap();
+        assert_eq!(format!("{:?}", w),
+                   "BitWriter { pending_bits: 5, pending_value: 0b10110 }");
+        w.write(3, 1u8).unwrap();
+        assert_eq!(format!("{:?}", w),
+                   "BitWriter { pending_bits: 0, pending_value: 0b0 }");
+    }
+
+    let mut output = Vec::new();
+    let mut w = BitWriter::<LE, _>::from_write(&mut output);
+    w.write(12, 0x0B1u16).unwrap();
+    assert_eq!(format!("{:?}", w),
+               "BitWriter { pending_bits: 4, pending_value: 0b0000 }");
+}
+
+#[test]
+fn test_writer_unchecked() {
+    use bitstream_io::{BE, LE, BitWriter};
+    let final_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+
+    /*unchecked writes produce the same stream as checked ones*/
+    let mut output = Vec::new();
+    {
+        let mut w = BitWriter::<BE>::new(&mut output);
+        w.write_unchecked(2, 2u32).unwrap();
+        w.write_unchecked(3, 6u32).unwrap();
+        w.write_unchecked(5, 7u32).unwrap();
+        w.write_unchecked(3, 5u32).unwrap();
+        w.write_unchecked(19, 0x53BC1u32).unwrap();
+    }
+    assert_eq!(output.as_slice(), &final_data);
+
+    let mut output = Vec::new();
+    {
+        let mut w = BitWriter::<LE>::new(&mut output);
+        w.write_unchecked(2, 1u32).unwrap();
+        w.write_unchecked(3, 4u32).unwrap();
+        w.write_unchecked(5, 13u32).unwrap();
+        w.write_unchecked(3, 3u32).unwrap();
+        w.write_unchecked(19, 0x609DFu32).unwrap();
+    }
+    assert_eq!(output.as_slice(), &final_data);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn test_writer_unchecked_excessive() {
+    use bitstream_io::{BE, BitWriter};
+    let mut output = Vec::new();
+    let mut w = BitWriter::<BE>::new(&mut output);
+    let _ = w.write_unchecked(3, 8u8);
+}
@@ -0,0 +1,32 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::zigzag::{encode, decode};
+
+#[test]
+fn test_zigzag_widths() {
+    for bits in 1..17 {
+        let min = -1i64 << (bits - 1);
+        let max = 1i64 << (bits - 1);
+        let mut seen = vec![false; 1 << bits];
+        for value in min..max {
+            let mapped = encode(bits, value);
+            assert!(mapped < (1 << bits));
+            assert!(!seen[mapped as usize]);
+            seen[mapped as usize] = true;
+            assert_eq!(mapped, if value < 0 {(-value * 2 - 1) as u64}
+                               else {(value * 2) as u64});
+            assert_eq!(decode(bits, mapped), value);
+        }
+    }
+    for value in [i64::MIN, i64::MIN + 1, -1, 0, 1, i64::MAX - 1, i64::MAX]
+        .iter() {
+        assert_eq!(decode(64, encode(64, *value)), *value);
+    }
+}